@@ -0,0 +1,53 @@
+//! Coste de serializar un mensaje del puente WebSocket en cada formato
+//!
+//! `cargo bench --bench bridge_encoding`
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use overlay_native::bridge::{BridgeFrame, FrameFormat};
+use overlay_native::mapping::{MappedMessage, MappedMessageType, MappedMetadata, UserLevel};
+
+fn busy_channel_message() -> MappedMessage {
+    MappedMessage {
+        id: "3d5a6c1e-2f1b-4a7e-9c55-0c6f2b7d8e90".to_string(),
+        platform: "twitch".to_string(),
+        channel: "busychannel".to_string(),
+        username: "some_viewer_123".to_string(),
+        display_name: Some("Some_Viewer_123".to_string()),
+        content: "PogChamp that play was insane KEKW !question how do you practice aim?"
+            .to_string(),
+        emotes: Vec::new(),
+        badges: Vec::new(),
+        timestamp: chrono::Utc::now(),
+        user_level: UserLevel::Subscriber,
+        message_type: MappedMessageType::Normal,
+        metadata: MappedMetadata::default(),
+    }
+}
+
+fn bench_formats(c: &mut Criterion) {
+    let message = busy_channel_message();
+    let mut group = c.benchmark_group("bridge_encoding");
+
+    for (name, format) in [
+        ("json", FrameFormat::Json),
+        ("msgpack", FrameFormat::MessagePack),
+        ("cbor", FrameFormat::Cbor),
+    ] {
+        let size = BridgeFrame::new(message.clone())
+            .encode(format)
+            .map(|frame| frame.len())
+            .unwrap_or(0);
+        group.throughput(Throughput::Bytes(size as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(name), &format, |b, format| {
+            b.iter(|| {
+                // Un frame nuevo por iteración: mide la serialización, no la caché
+                let frame = BridgeFrame::new(message.clone());
+                black_box(frame.encode(*format))
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_formats);
+criterion_main!(benches);
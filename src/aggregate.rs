@@ -0,0 +1,470 @@
+//! Modo resumen: nube de palabras y participantes más activos
+//!
+//! Con `aggregate` activado los mensajes de chat no abren ventana propia: se
+//! cuentan en una ventana deslizante de `window_secs` segundos y una sola
+//! ventana persistente muestra las palabras y emotes más repetidos y quién
+//! más escribe. El resumen se reconstruye cada `refresh_secs` y solo se
+//! repinta si ha cambiado. Suscripciones, raids y demás eventos siguen
+//! mostrándose como siempre.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::SystemUser;
+use crate::connection::{ChatMessage, Emote, MessageType, TextPosition};
+use crate::system_users::{system_message, SYSTEM_PLATFORM};
+
+static GLOBAL_AGGREGATOR: OnceLock<ChatAggregator> = OnceLock::new();
+
+/// Rol del usuario virtual de la ventana resumen
+pub const AGGREGATE_ROLE: &str = "aggregate";
+
+/// `aggregate`
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct AggregateConfig {
+    /// Desactivado por defecto: cada mensaje abre su ventana
+    pub enabled: bool,
+    /// Segundos de chat que cubre el resumen
+    pub window_secs: u64,
+    /// Cada cuántos segundos se actualiza la ventana
+    pub refresh_secs: u64,
+    /// Palabras y emotes que se muestran
+    pub top_words: usize,
+    /// Participantes que se muestran
+    pub top_chatters: usize,
+    /// Las palabras más cortas no cuentan (los emotes sí)
+    pub min_word_length: usize,
+    /// Palabras que no cuentan, sin distinguir mayúsculas
+    pub stop_words: Vec<String>,
+}
+
+impl Default for AggregateConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            window_secs: 300,
+            refresh_secs: 5,
+            top_words: 8,
+            top_chatters: 5,
+            min_word_length: 3,
+            stop_words: [
+                "the", "and", "you", "that", "this", "for", "with", "are", "but", "not", "que",
+                "los", "las", "una", "por", "para", "con", "del", "pero", "como", "más", "mas",
+            ]
+            .into_iter()
+            .map(str::to_string)
+            .collect(),
+        }
+    }
+}
+
+/// Qué hacer con la ventana resumen tras [`ChatAggregator::refresh`]
+#[derive(Debug, Clone)]
+pub enum AggregateRefresh {
+    /// Nada nuevo que pintar
+    Unchanged,
+    /// Mostrar este resumen en lugar del anterior
+    Show(Box<ChatMessage>),
+    /// No queda chat en la ventana de tiempo: se cierra
+    Clear,
+}
+
+/// Palabras y participantes ordenados de más a menos frecuente
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AggregateSnapshot {
+    pub words: Vec<(String, u32)>,
+    pub chatters: Vec<(String, u32)>,
+    /// Mensajes dentro de la ventana de tiempo
+    pub messages: usize,
+}
+
+#[derive(Debug)]
+struct Entry {
+    at: Instant,
+    chatter: String,
+    words: Vec<String>,
+}
+
+#[derive(Debug)]
+struct WordCount {
+    count: u32,
+    /// Emote con el que se pinta la palabra, si lo es
+    emote: Option<Emote>,
+}
+
+#[derive(Debug, Default)]
+struct AggregateState {
+    config: AggregateConfig,
+    stop_words: HashSet<String>,
+    entries: VecDeque<Entry>,
+    words: HashMap<String, WordCount>,
+    chatters: HashMap<String, u32>,
+    last_refresh: Option<Instant>,
+    /// Texto de la ventana visible, para no repintarla si no cambia
+    shown: Option<String>,
+}
+
+impl AggregateState {
+    /// Descuenta los mensajes que han salido de la ventana de tiempo
+    fn expire(&mut self, now: Instant) {
+        let window = Duration::from_secs(self.config.window_secs);
+        while let Some(entry) = self.entries.front() {
+            if now.saturating_duration_since(entry.at) < window {
+                break;
+            }
+            let Some(entry) = self.entries.pop_front() else {
+                break;
+            };
+            for word in entry.words {
+                if let Some(count) = self.words.get_mut(&word) {
+                    count.count -= 1;
+                    if count.count == 0 {
+                        self.words.remove(&word);
+                    }
+                }
+            }
+            if let Some(count) = self.chatters.get_mut(&entry.chatter) {
+                *count -= 1;
+                if *count == 0 {
+                    self.chatters.remove(&entry.chatter);
+                }
+            }
+        }
+    }
+
+    /// Palabras distintas de `message` que cuentan, con su emote si lo es
+    fn tokens(&self, message: &ChatMessage) -> Vec<(String, Option<Emote>)> {
+        let mut tokens: Vec<(String, Option<Emote>)> = Vec::new();
+        for (_, word) in crate::text::words(&message.content) {
+            let token = match message.emotes.iter().find(|emote| emote.name == word) {
+                Some(emote) => {
+                    let emote = Emote {
+                        positions: Vec::new(),
+                        ..emote.clone()
+                    };
+                    (word.to_string(), Some(emote))
+                }
+                None => {
+                    if word.starts_with('@') || word.contains("://") {
+                        continue;
+                    }
+                    let word = word
+                        .trim_matches(|c: char| !c.is_alphanumeric())
+                        .to_lowercase();
+                    if word.chars().count() < self.config.min_word_length
+                        || self.stop_words.contains(&word)
+                    {
+                        continue;
+                    }
+                    (word, None)
+                }
+            };
+            // Repetir una palabra en el mismo mensaje no la hace más popular
+            if !tokens.iter().any(|(seen, _)| *seen == token.0) {
+                tokens.push(token);
+            }
+        }
+        tokens
+    }
+
+    fn snapshot(&self) -> AggregateSnapshot {
+        AggregateSnapshot {
+            words: top(
+                self.words.iter().map(|(word, count)| (word, count.count)),
+                self.config.top_words,
+            ),
+            chatters: top(
+                self.chatters.iter().map(|(name, count)| (name, *count)),
+                self.config.top_chatters,
+            ),
+            messages: self.entries.len(),
+        }
+    }
+}
+
+/// Las `limit` entradas más frecuentes; a igual cuenta, por orden alfabético
+fn top<'a>(counts: impl Iterator<Item = (&'a String, u32)>, limit: usize) -> Vec<(String, u32)> {
+    let mut counts: Vec<(String, u32)> = counts.map(|(key, count)| (key.clone(), count)).collect();
+    counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    counts.truncate(limit);
+    counts
+}
+
+/// Contador de palabras y participantes, compartido por todas las conexiones
+#[derive(Debug, Default)]
+pub struct ChatAggregator {
+    state: Mutex<AggregateState>,
+}
+
+impl ChatAggregator {
+    pub fn new(config: AggregateConfig) -> Self {
+        let aggregator = Self::default();
+        aggregator.configure(config);
+        aggregator
+    }
+
+    pub fn global() -> &'static ChatAggregator {
+        GLOBAL_AGGREGATOR.get_or_init(|| Self::new(AggregateConfig::default()))
+    }
+
+    /// Aplica la configuración y empieza a contar de cero
+    pub fn configure(&self, config: AggregateConfig) {
+        let mut state = self.lock();
+        state.stop_words = config
+            .stop_words
+            .iter()
+            .map(|word| word.to_lowercase())
+            .collect();
+        state.config = config;
+        state.entries.clear();
+        state.words.clear();
+        state.chatters.clear();
+        state.last_refresh = None;
+        // `shown` se conserva para que el próximo refresco cierre la ventana
+    }
+
+    /// Cuenta `message` si es chat; `true` si ya no debe abrir ventana propia
+    pub fn observe(&self, message: &ChatMessage, now: Instant) -> bool {
+        let mut state = self.lock();
+        if !state.config.enabled
+            || message.platform == SYSTEM_PLATFORM
+            || !matches!(
+                message.message_type,
+                MessageType::Normal | MessageType::Action
+            )
+        {
+            return false;
+        }
+
+        state.expire(now);
+        let chatter = message
+            .display_name
+            .clone()
+            .unwrap_or_else(|| message.username.clone());
+        let tokens = state.tokens(message);
+        let mut words = Vec::with_capacity(tokens.len());
+        for (word, emote) in tokens {
+            let count = state.words.entry(word.clone()).or_insert(WordCount {
+                count: 0,
+                emote: None,
+            });
+            count.count += 1;
+            if emote.is_some() {
+                count.emote = emote;
+            }
+            words.push(word);
+        }
+        *state.chatters.entry(chatter.clone()).or_default() += 1;
+        state.entries.push_back(Entry {
+            at: now,
+            chatter,
+            words,
+        });
+        true
+    }
+
+    /// Reconstruye el resumen si toca (cada `refresh_secs`) y ha cambiado
+    pub fn refresh(&self, now: Instant, user: &SystemUser) -> AggregateRefresh {
+        let mut state = self.lock();
+        if !state.config.enabled {
+            return match state.shown.take() {
+                Some(_) => AggregateRefresh::Clear,
+                None => AggregateRefresh::Unchanged,
+            };
+        }
+        let interval = Duration::from_secs(state.config.refresh_secs);
+        if state
+            .last_refresh
+            .is_some_and(|last| now.saturating_duration_since(last) < interval)
+        {
+            return AggregateRefresh::Unchanged;
+        }
+        state.last_refresh = Some(now);
+        state.expire(now);
+
+        let snapshot = state.snapshot();
+        if snapshot.messages == 0 {
+            return match state.shown.take() {
+                Some(_) => AggregateRefresh::Clear,
+                None => AggregateRefresh::Unchanged,
+            };
+        }
+
+        let message = summary_message(&state, &snapshot, user);
+        if state.shown.as_deref() == Some(message.content.as_str()) {
+            return AggregateRefresh::Unchanged;
+        }
+        state.shown = Some(message.content.clone());
+        AggregateRefresh::Show(Box::new(message))
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, AggregateState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Mensaje de la ventana resumen, con los emotes colocados sobre su nombre
+fn summary_message(
+    state: &AggregateState,
+    snapshot: &AggregateSnapshot,
+    user: &SystemUser,
+) -> ChatMessage {
+    let mut content = String::new();
+    let mut emotes: Vec<Emote> = Vec::new();
+
+    if !snapshot.words.is_empty() {
+        content.push_str("Top words: ");
+        for (i, (word, count)) in snapshot.words.iter().enumerate() {
+            if i > 0 {
+                content.push_str(" · ");
+            }
+            if let Some(emote) = state.words.get(word).and_then(|w| w.emote.as_ref()) {
+                let start = content.chars().count();
+                let position = TextPosition {
+                    start,
+                    end: start + word.chars().count() - 1,
+                };
+                match emotes.iter_mut().find(|e| e.name == emote.name) {
+                    Some(existing) => existing.positions.push(position),
+                    None => emotes.push(Emote {
+                        positions: vec![position],
+                        ..emote.clone()
+                    }),
+                }
+            }
+            // El emote tiene que quedar como palabra suelta para pintarse
+            content.push_str(&format!("{} ×{}", word, count));
+        }
+        content.push('\n');
+    }
+
+    let chatters: Vec<String> = snapshot
+        .chatters
+        .iter()
+        .map(|(name, count)| format!("{} ({})", name, count))
+        .collect();
+    content.push_str(&format!("Top chatters: {}", chatters.join(" · ")));
+
+    let window_minutes = (state.config.window_secs as f64 / 60.0).ceil() as u64;
+    let mut message = system_message(
+        user,
+        AGGREGATE_ROLE,
+        content,
+        HashMap::from([
+            ("aggregate_messages".to_string(), snapshot.messages.into()),
+            (
+                "aggregate_window_minutes".to_string(),
+                window_minutes.into(),
+            ),
+        ]),
+    );
+    message.emotes = emotes;
+    message
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot_at(aggregator: &ChatAggregator, now: Instant) -> AggregateSnapshot {
+        let mut state = aggregator.lock();
+        state.expire(now);
+        state.snapshot()
+    }
+
+    fn aggregator() -> ChatAggregator {
+        ChatAggregator::new(AggregateConfig {
+            enabled: true,
+            window_secs: 60,
+            top_words: 3,
+            top_chatters: 2,
+            ..AggregateConfig::default()
+        })
+    }
+
+    #[test]
+    fn test_counts_words_emotes_and_chatters_in_window() {
+        let aggregator = aggregator();
+        let start = Instant::now();
+
+        assert!(aggregator.observe(
+            &ChatMessage::test("alice", "KEKW hola hola, the").with_emote("KEKW"),
+            start
+        ));
+        assert!(aggregator.observe(
+            &ChatMessage::test("bob", "Hola KEKW ok").with_emote("KEKW"),
+            start
+        ));
+        assert!(aggregator.observe(
+            &ChatMessage::test("alice", "gg @bob https://x.y"),
+            start + Duration::from_secs(30)
+        ));
+        // Los eventos siguen abriendo su ventana
+        let sub = ChatMessage::test("carol", "subscribed").with_type(MessageType::Subscription);
+        assert!(!aggregator.observe(&sub, start));
+
+        let snapshot = snapshot_at(&aggregator, start + Duration::from_secs(30));
+        assert_eq!(snapshot.messages, 3);
+        assert_eq!(
+            snapshot.words,
+            vec![("KEKW".to_string(), 2), ("hola".to_string(), 2)]
+        );
+        assert_eq!(
+            snapshot.chatters,
+            vec![("alice".to_string(), 2), ("bob".to_string(), 1)]
+        );
+
+        // Los dos primeros mensajes salen de la ventana deslizante
+        let snapshot = snapshot_at(&aggregator, start + Duration::from_secs(61));
+        assert_eq!(snapshot.messages, 1);
+        assert!(snapshot.words.is_empty());
+        assert_eq!(snapshot.chatters, vec![("alice".to_string(), 1)]);
+    }
+
+    #[test]
+    fn test_refresh_renders_summary_only_when_it_changes() {
+        let aggregator = aggregator();
+        let user = SystemUser::default();
+        let start = Instant::now();
+
+        assert!(matches!(
+            aggregator.refresh(start, &user),
+            AggregateRefresh::Unchanged
+        ));
+        aggregator.observe(
+            &ChatMessage::test("alice", "hola KEKW").with_emote("KEKW"),
+            start,
+        );
+
+        let AggregateRefresh::Show(summary) =
+            aggregator.refresh(start + Duration::from_secs(5), &user)
+        else {
+            panic!("expected a summary");
+        };
+        assert_eq!(
+            summary.content,
+            "Top words: KEKW ×1 · hola ×1\nTop chatters: alice (1)"
+        );
+        assert_eq!(summary.emotes.len(), 1);
+        let position = &summary.emotes[0].positions[0];
+        assert_eq!((position.start, position.end), (11, 14));
+        // Antes de `refresh_secs` y sin cambios no se repinta
+        assert!(matches!(
+            aggregator.refresh(start + Duration::from_secs(6), &user),
+            AggregateRefresh::Unchanged
+        ));
+        assert!(matches!(
+            aggregator.refresh(start + Duration::from_secs(10), &user),
+            AggregateRefresh::Unchanged
+        ));
+        // Vacía la ventana de tiempo, se cierra
+        assert!(matches!(
+            aggregator.refresh(start + Duration::from_secs(61), &user),
+            AggregateRefresh::Clear
+        ));
+    }
+}
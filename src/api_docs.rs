@@ -0,0 +1,202 @@
+//! Documentación de la API de control generada desde los tipos de Rust
+//!
+//! Con la API activada, `GET /openapi.json` devuelve un documento OpenAPI 3 y
+//! `GET /docs` una página mínima con los endpoints, los tipos de frame del
+//! puente WebSocket y la versión del esquema de mensajes. Ambos salen de
+//! [`ENDPOINTS`] y de los tipos que derivan `JsonSchema`, así que no se
+//! desfasan del código.
+
+use schemars::gen::{SchemaGenerator, SchemaSettings};
+use serde_json::{json, Map, Value};
+
+use crate::bridge::FRAME_TYPES;
+use crate::control::{Endpoint, ENDPOINTS};
+use crate::mapping::{MappedMessage, MESSAGE_SCHEMA_VERSION};
+
+/// Documento OpenAPI 3 de la API de control
+pub fn openapi() -> Value {
+    let mut generator = SchemaSettings::openapi3().into_generator();
+
+    let mut paths = Map::new();
+    for endpoint in ENDPOINTS {
+        let operation = operation(endpoint, &mut generator);
+        let item = paths
+            .entry(endpoint.path)
+            .or_insert_with(|| Value::Object(Map::new()));
+        item[endpoint.method.to_lowercase()] = operation;
+    }
+
+    let message = generator.subschema_for::<MappedMessage>();
+    let frames: Vec<Value> = FRAME_TYPES
+        .iter()
+        .map(|(kind, description)| {
+            json!({
+                "type": kind,
+                "description": description,
+                "data": message,
+            })
+        })
+        .collect();
+
+    json!({
+        "openapi": "3.0.3",
+        "info": {
+            "title": "overlay-native control API",
+            "version": env!("CARGO_PKG_VERSION"),
+        },
+        "paths": paths,
+        "components": {
+            "schemas": generator.take_definitions(),
+            "securitySchemes": {
+                "bearer": { "type": "http", "scheme": "bearer" },
+            },
+        },
+        "security": [{ "bearer": [] }],
+        "x-message-schema-version": MESSAGE_SCHEMA_VERSION,
+        "x-bridge-frames": frames,
+    })
+}
+
+fn operation(endpoint: &Endpoint, generator: &mut SchemaGenerator) -> Value {
+    let parameters: Vec<Value> = path_parameters(endpoint.path)
+        .map(|name| {
+            json!({
+                "name": name,
+                "in": "path",
+                "required": true,
+                "schema": { "type": "string" },
+            })
+        })
+        .collect();
+
+    let success = match endpoint.response {
+        Some((key, schema)) => json!({
+            "description": "OK",
+            "content": {
+                "application/json": {
+                    "schema": {
+                        "type": "object",
+                        "properties": { key: schema(generator) },
+                    },
+                },
+            },
+        }),
+        None => json!({ "description": "OK" }),
+    };
+
+    let mut operation = json!({
+        "summary": endpoint.summary,
+        "parameters": parameters,
+        "responses": {
+            "200": success,
+            "default": {
+                "description": "Error",
+                "content": {
+                    "application/json": {
+                        "schema": {
+                            "type": "object",
+                            "properties": { "error": { "type": "string" } },
+                        },
+                    },
+                },
+            },
+        },
+    });
+    if let Some(schema) = endpoint.request {
+        operation["requestBody"] = json!({
+            "required": false,
+            "content": { "application/json": { "schema": schema(generator) } },
+        });
+    }
+    operation
+}
+
+/// Nombres de los parámetros `{...}` de una ruta
+fn path_parameters(path: &str) -> impl Iterator<Item = &str> {
+    path.split('/')
+        .filter_map(|segment| segment.strip_prefix('{')?.strip_suffix('}'))
+}
+
+/// Página HTML con los endpoints, los frames del puente y la versión del esquema
+pub fn html_page() -> String {
+    let mut page = String::from(
+        "<!DOCTYPE html>\n<html><head><meta charset=\"utf-8\"><title>overlay-native API</title></head><body>\n",
+    );
+    page.push_str(&format!(
+        "<h1>overlay-native {}</h1>\n<p>Versión del esquema de mensajes: {} &middot; <a href=\"/openapi.json\">openapi.json</a></p>\n",
+        env!("CARGO_PKG_VERSION"),
+        MESSAGE_SCHEMA_VERSION
+    ));
+
+    page.push_str(
+        "<h2>Endpoints</h2>\n<table>\n<tr><th>Método</th><th>Ruta</th><th>Descripción</th></tr>\n",
+    );
+    for endpoint in ENDPOINTS {
+        page.push_str(&format!(
+            "<tr><td>{}</td><td><code>{}</code></td><td>{}</td></tr>\n",
+            endpoint.method,
+            escape(endpoint.path),
+            escape(endpoint.summary)
+        ));
+    }
+    page.push_str("</table>\n");
+
+    page.push_str("<h2>Frames del puente WebSocket</h2>\n<ul>\n");
+    for (kind, description) in FRAME_TYPES {
+        page.push_str(&format!(
+            "<li><code>{{\"type\": \"{}\", \"data\": ...}}</code> &mdash; {}</li>\n",
+            escape(kind),
+            escape(description)
+        ));
+    }
+    page.push_str("</ul>\n</body></html>\n");
+    page
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_openapi_lists_every_endpoint_with_schemas() {
+        let document = openapi();
+        for endpoint in ENDPOINTS {
+            let operation = &document["paths"][endpoint.path][endpoint.method.to_lowercase()];
+            assert!(
+                operation.is_object(),
+                "{} {}",
+                endpoint.method,
+                endpoint.path
+            );
+        }
+
+        let start = &document["paths"]["/connections/{id}/start"]["post"];
+        assert_eq!(start["parameters"][0]["name"], "id");
+
+        let search = &document["paths"]["/messages/search"]["post"];
+        assert_eq!(
+            search["requestBody"]["content"]["application/json"]["schema"]["$ref"],
+            "#/components/schemas/HistoryQuery"
+        );
+        let schemas = &document["components"]["schemas"];
+        assert!(schemas["HistoryQuery"]["properties"]["regex"].is_object());
+        assert!(schemas["MappedMessage"]["properties"]["user_level"].is_object());
+        assert_eq!(document["x-message-schema-version"], MESSAGE_SCHEMA_VERSION);
+        assert_eq!(document["x-bridge-frames"][0]["type"], "message");
+    }
+
+    #[test]
+    fn test_html_page_lists_endpoints_and_frames() {
+        let page = html_page();
+        assert!(page.contains("<code>/connections/{id}/commands-only</code>"));
+        assert!(page.contains("<code>{\"type\": \"message\", \"data\": ...}</code>"));
+        assert!(page.contains("«solo comandos»"));
+    }
+}
@@ -0,0 +1,332 @@
+//! API de observadores para aplicaciones que integran el overlay
+//!
+//! `OverlayApp` expone callbacks tipados para que la aplicación anfitriona pueda
+//! reflejar el estado del overlay (ventanas abiertas, mensajes mostrados) en su
+//! propia interfaz o en sus analíticas.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+
+use crate::config::Config;
+use crate::connection::ChatMessage;
+use crate::health::BudgetAlert;
+use crate::mapping::MappingSystem;
+use crate::moderation::DroppedMessage;
+
+/// Identificador único de una ventana del overlay dentro del proceso
+pub type WindowId = u64;
+
+static NEXT_WINDOW_ID: AtomicU64 = AtomicU64::new(1);
+
+/// Reserva un nuevo identificador de ventana
+pub fn next_window_id() -> WindowId {
+    NEXT_WINDOW_ID.fetch_add(1, Ordering::Relaxed)
+}
+
+/// Se abrió una ventana para un mensaje
+#[derive(Debug, Clone, PartialEq)]
+pub struct WindowCreatedEvent {
+    pub window_id: WindowId,
+    pub message_id: String,
+    pub platform: String,
+    pub channel: String,
+    pub position: (i32, i32),
+    pub duration: Duration,
+}
+
+/// Motivo por el que se cerró una ventana
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CloseReason {
+    /// Terminó su tiempo de visualización
+    Expired,
+    /// Se cerró para dejar sitio a ventanas más nuevas
+    Evicted,
+    /// Un moderador quitó el mensaje fijado
+    Unpinned,
+    /// La plataforma borró el mensaje
+    Deleted,
+    /// Un moderador limpió el overlay con `!overlay clear`
+    Cleared,
+    /// El overlay se está cerrando
+    Shutdown,
+}
+
+/// Se cerró una ventana
+#[derive(Debug, Clone, PartialEq)]
+pub struct WindowClosedEvent {
+    pub window_id: WindowId,
+    pub message_id: String,
+    pub reason: CloseReason,
+    /// Tiempo que la ventana estuvo visible
+    pub visible_for: Duration,
+}
+
+/// Un mensaje se mostró en pantalla
+#[derive(Debug, Clone)]
+pub struct MessageDisplayedEvent {
+    pub window_id: WindowId,
+    pub message: ChatMessage,
+}
+
+type Callback<E> = Arc<dyn Fn(&E) + Send + Sync>;
+
+#[derive(Default)]
+struct Callbacks {
+    window_created: Vec<Callback<WindowCreatedEvent>>,
+    window_closed: Vec<Callback<WindowClosedEvent>>,
+    message_displayed: Vec<Callback<MessageDisplayedEvent>>,
+    error_budget_exceeded: Vec<Callback<BudgetAlert>>,
+    message_dropped: Vec<Callback<DroppedMessage>>,
+}
+
+/// Registro de callbacks compartido entre `OverlayApp` y el gestor de ventanas
+#[derive(Clone, Default)]
+pub struct OverlayObservers {
+    callbacks: Arc<RwLock<Callbacks>>,
+}
+
+impl OverlayObservers {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn on_window_created<F>(&self, callback: F)
+    where
+        F: Fn(&WindowCreatedEvent) + Send + Sync + 'static,
+    {
+        self.write().window_created.push(Arc::new(callback));
+    }
+
+    pub fn on_window_closed<F>(&self, callback: F)
+    where
+        F: Fn(&WindowClosedEvent) + Send + Sync + 'static,
+    {
+        self.write().window_closed.push(Arc::new(callback));
+    }
+
+    pub fn on_message_displayed<F>(&self, callback: F)
+    where
+        F: Fn(&MessageDisplayedEvent) + Send + Sync + 'static,
+    {
+        self.write().message_displayed.push(Arc::new(callback));
+    }
+
+    pub fn on_error_budget_exceeded<F>(&self, callback: F)
+    where
+        F: Fn(&BudgetAlert) + Send + Sync + 'static,
+    {
+        self.write().error_budget_exceeded.push(Arc::new(callback));
+    }
+
+    pub fn on_message_dropped<F>(&self, callback: F)
+    where
+        F: Fn(&DroppedMessage) + Send + Sync + 'static,
+    {
+        self.write().message_dropped.push(Arc::new(callback));
+    }
+
+    pub fn emit_window_created(&self, event: &WindowCreatedEvent) {
+        // Se clonan los callbacks para no mantener el lock mientras se ejecutan
+        let callbacks = self.read().window_created.clone();
+        for callback in callbacks {
+            callback(event);
+        }
+    }
+
+    pub fn emit_window_closed(&self, event: &WindowClosedEvent) {
+        let callbacks = self.read().window_closed.clone();
+        for callback in callbacks {
+            callback(event);
+        }
+    }
+
+    pub fn emit_message_displayed(&self, event: &MessageDisplayedEvent) {
+        let callbacks = self.read().message_displayed.clone();
+        for callback in callbacks {
+            callback(event);
+        }
+    }
+
+    pub fn emit_error_budget_exceeded(&self, alert: &BudgetAlert) {
+        let callbacks = self.read().error_budget_exceeded.clone();
+        for callback in callbacks {
+            callback(alert);
+        }
+    }
+
+    pub fn emit_message_dropped(&self, dropped: &DroppedMessage) {
+        let callbacks = self.read().message_dropped.clone();
+        for callback in callbacks {
+            callback(dropped);
+        }
+    }
+
+    fn read(&self) -> std::sync::RwLockReadGuard<'_, Callbacks> {
+        self.callbacks.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn write(&self) -> std::sync::RwLockWriteGuard<'_, Callbacks> {
+        self.callbacks.write().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Datos de seguimiento de una ventana abierta
+#[derive(Debug, Clone)]
+pub struct WindowHandle {
+    pub id: WindowId,
+    pub message_id: String,
+    pub opened_at: Instant,
+    /// Posición de la ventana en el monitor
+    pub position: (i32, i32),
+}
+
+impl WindowHandle {
+    pub fn new(message_id: impl Into<String>, position: (i32, i32)) -> Self {
+        Self {
+            id: next_window_id(),
+            message_id: message_id.into(),
+            opened_at: Instant::now(),
+            position,
+        }
+    }
+
+    /// Evento de cierre para esta ventana
+    pub fn closed(&self, reason: CloseReason) -> WindowClosedEvent {
+        WindowClosedEvent {
+            window_id: self.id,
+            message_id: self.message_id.clone(),
+            reason,
+            visible_for: self.opened_at.elapsed(),
+        }
+    }
+}
+
+/// Punto de entrada para aplicaciones que integran el overlay
+pub struct OverlayApp {
+    config: Config,
+    observers: OverlayObservers,
+    /// Mapeo que usa el procesador de mensajes del overlay en marcha
+    mapping_system: Arc<tokio::sync::RwLock<MappingSystem>>,
+}
+
+impl OverlayApp {
+    pub fn new(config: Config) -> Self {
+        Self {
+            config,
+            observers: OverlayObservers::new(),
+            mapping_system: Arc::new(tokio::sync::RwLock::new(MappingSystem::default())),
+        }
+    }
+
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /// Registro de callbacks para pasarlo al gestor de ventanas
+    pub fn observers(&self) -> OverlayObservers {
+        self.observers.clone()
+    }
+
+    /// Sistema de mapeo compartido con el procesador de mensajes
+    pub fn mapping_system(&self) -> Arc<tokio::sync::RwLock<MappingSystem>> {
+        self.mapping_system.clone()
+    }
+
+    /// Registra una transformación que se aplica a los mensajes siguientes,
+    /// también con el overlay ya en marcha
+    #[cfg(feature = "plugin-api")]
+    pub async fn register_transform(
+        &self,
+        transform: Box<dyn crate::mapping::MessageTransform>,
+    ) -> &Self {
+        self.mapping_system
+            .write()
+            .await
+            .register_transform(transform);
+        self
+    }
+
+    pub fn on_window_created<F>(&self, callback: F) -> &Self
+    where
+        F: Fn(&WindowCreatedEvent) + Send + Sync + 'static,
+    {
+        self.observers.on_window_created(callback);
+        self
+    }
+
+    pub fn on_window_closed<F>(&self, callback: F) -> &Self
+    where
+        F: Fn(&WindowClosedEvent) + Send + Sync + 'static,
+    {
+        self.observers.on_window_closed(callback);
+        self
+    }
+
+    pub fn on_message_displayed<F>(&self, callback: F) -> &Self
+    where
+        F: Fn(&MessageDisplayedEvent) + Send + Sync + 'static,
+    {
+        self.observers.on_message_displayed(callback);
+        self
+    }
+
+    /// Una plataforma o proveedor superó su presupuesto de errores
+    pub fn on_error_budget_exceeded<F>(&self, callback: F) -> &Self
+    where
+        F: Fn(&BudgetAlert) + Send + Sync + 'static,
+    {
+        self.observers.on_error_budget_exceeded(callback);
+        self
+    }
+
+    /// Un mensaje se descartó (filtros, solo alertas, error de mapeo); requiere
+    /// `moderation_lane.enabled`
+    pub fn on_message_dropped<F>(&self, callback: F) -> &Self
+    where
+        F: Fn(&DroppedMessage) + Send + Sync + 'static,
+    {
+        self.observers.on_message_dropped(callback);
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[test]
+    fn test_callbacks_receive_typed_events() {
+        let app = OverlayApp::new(Config::default());
+        let created = Arc::new(Mutex::new(Vec::new()));
+        let closed = Arc::new(Mutex::new(Vec::new()));
+
+        let created_clone = created.clone();
+        let closed_clone = closed.clone();
+        app.on_window_created(move |e| created_clone.lock().unwrap().push(e.window_id))
+            .on_window_closed(move |e| closed_clone.lock().unwrap().push(e.reason));
+
+        let observers = app.observers();
+        let handle = WindowHandle::new("msg-1", (0, 0));
+        observers.emit_window_created(&WindowCreatedEvent {
+            window_id: handle.id,
+            message_id: handle.message_id.clone(),
+            platform: "twitch".to_string(),
+            channel: "foo".to_string(),
+            position: (10, 20),
+            duration: Duration::from_secs(10),
+        });
+        observers.emit_window_closed(&handle.closed(CloseReason::Expired));
+
+        assert_eq!(*created.lock().unwrap(), vec![handle.id]);
+        assert_eq!(*closed.lock().unwrap(), vec![CloseReason::Expired]);
+    }
+
+    #[test]
+    fn test_window_ids_are_unique() {
+        let a = WindowHandle::new("a", (0, 0));
+        let b = WindowHandle::new("b", (0, 0));
+        assert_ne!(a.id, b.id);
+    }
+}
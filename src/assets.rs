@@ -0,0 +1,104 @@
+//! Recursos gráficos integrados en el binario
+//!
+//! Arte de reserva para emotes que no se pueden cargar, iconos de plataforma e
+//! insignias por defecto. Los bytes se incluyen con `include_bytes!`, así que
+//! viven en la sección de solo lectura del ejecutable (el sistema la mapea en
+//! memoria al cargarlo) y se sirven sin copias ni acceso a disco.
+
+/// Recurso del paquete integrado
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Asset {
+    /// Ruta relativa dentro de `assets/`
+    pub name: &'static str,
+    pub mime_type: &'static str,
+    pub bytes: &'static [u8],
+}
+
+macro_rules! png_asset {
+    ($name:literal) => {
+        Asset {
+            name: $name,
+            mime_type: "image/png",
+            bytes: include_bytes!(concat!("../assets/", $name)),
+        }
+    };
+}
+
+const EMOTE_PLACEHOLDER: &str = "emotes/placeholder.png";
+const DEFAULT_PLATFORM_ICON: &str = "platforms/overlay.png";
+const DEFAULT_BADGE: &str = "badges/default.png";
+
+static BUNDLE: &[Asset] = &[
+    png_asset!("emotes/placeholder.png"),
+    png_asset!("platforms/twitch.png"),
+    png_asset!("platforms/kick.png"),
+    png_asset!("platforms/youtube.png"),
+    png_asset!("platforms/tiktok.png"),
+    png_asset!("platforms/overlay.png"),
+    png_asset!("badges/default.png"),
+    png_asset!("badges/broadcaster.png"),
+    png_asset!("badges/moderator.png"),
+    png_asset!("badges/vip.png"),
+    png_asset!("badges/subscriber.png"),
+];
+
+/// Busca un recurso por su ruta (p.ej. `"badges/vip.png"`)
+pub fn get(name: &str) -> Option<&'static Asset> {
+    BUNDLE.iter().find(|asset| asset.name == name)
+}
+
+fn get_or(name: &str, fallback: &str) -> &'static Asset {
+    get(name)
+        .or_else(|| get(fallback))
+        .expect("fallback asset missing from bundle")
+}
+
+/// Imagen que se muestra cuando un emote no se puede descargar o decodificar
+pub fn emote_placeholder() -> &'static Asset {
+    get(EMOTE_PLACEHOLDER).expect("emote placeholder missing from bundle")
+}
+
+/// Icono de la plataforma, o el del overlay si no hay uno específico
+pub fn platform_icon(platform: &str) -> &'static Asset {
+    let name = format!("platforms/{}.png", platform.to_lowercase());
+    get_or(&name, DEFAULT_PLATFORM_ICON)
+}
+
+/// Insignia por defecto para un nombre de insignia de cualquier plataforma
+pub fn badge(name: &str) -> &'static Asset {
+    let file = match name.to_lowercase().as_str() {
+        "broadcaster" => "badges/broadcaster.png",
+        "moderator" | "mod" => "badges/moderator.png",
+        "vip" => "badges/vip.png",
+        "subscriber" | "founder" | "og" => "badges/subscriber.png",
+        _ => DEFAULT_BADGE,
+    };
+    get_or(file, DEFAULT_BADGE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bundled_assets_decode() {
+        for asset in BUNDLE {
+            let image = image::load_from_memory(asset.bytes)
+                .unwrap_or_else(|e| panic!("{} does not decode: {}", asset.name, e));
+            assert!(image.width() > 0 && image.height() > 0, "{}", asset.name);
+        }
+    }
+
+    #[test]
+    fn test_platform_icons_fall_back_to_overlay() {
+        assert_eq!(platform_icon("Twitch").name, "platforms/twitch.png");
+        assert_eq!(platform_icon("trovo").name, DEFAULT_PLATFORM_ICON);
+    }
+
+    #[test]
+    fn test_badge_aliases() {
+        assert_eq!(badge("mod").name, "badges/moderator.png");
+        assert_eq!(badge("founder").name, "badges/subscriber.png");
+        assert_eq!(badge("bits-leader").name, DEFAULT_BADGE);
+    }
+}
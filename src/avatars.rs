@@ -0,0 +1,408 @@
+//! Avatares de los usuarios del chat
+//!
+//! Con `display.show_avatars` cada ventana muestra la foto de perfil del
+//! usuario recortada en círculo a la izquierda del nombre. La URL se toma de
+//! `custom_data.avatar_url` si la plataforma la trae, o se pide a la API:
+//! Helix en Twitch (necesita `oauth_token` y `client_id`) y la API pública de
+//! Kick. Las imágenes ya recortadas se guardan por plataforma e id de usuario
+//! en memoria (LRU) y en disco, y los usuarios sin avatar no se vuelven a
+//! consultar en un rato.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::io::Cursor;
+use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant, SystemTime};
+
+use image::{imageops, ImageOutputFormat, Rgba, RgbaImage};
+
+use crate::config::{PlatformConfig, PlatformType};
+use crate::connection::ChatMessage;
+use crate::platforms::twitch_eventsub::EventSubAuth;
+use crate::theme::Rgb;
+
+static GLOBAL_AVATARS: OnceLock<AvatarStore> = OnceLock::new();
+
+/// Clave en `custom_data` con la URL de la foto de perfil
+pub const AVATAR_URL_KEY: &str = "avatar_url";
+/// Avatares guardados en memoria antes de descartar los menos usados
+const MAX_MEMORY_AVATARS: usize = 512;
+/// Antigüedad máxima de un avatar en disco
+const DISK_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+/// Tiempo que se espera a un usuario sin avatar antes de volver a buscarlo
+const MISSING_RETRY: Duration = Duration::from_secs(10 * 60);
+/// Lado en píxeles de la imagen recortada; los renderers la escalan
+const RENDER_SIZE: u32 = 64;
+
+/// Lado del avatar en una ventana: algo más alto que el nombre
+pub fn avatar_side(font_size: u32) -> u32 {
+    font_size + 4
+}
+
+/// Recorta `bytes` en un círculo de `size` píxeles (centrado, borde suavizado)
+/// y lo devuelve como PNG con transparencia
+pub fn circular_avatar(bytes: &[u8], size: u32) -> Option<Vec<u8>> {
+    // `resize_to_fill` recorta al centro las fotos que no son cuadradas
+    let mut image = image::load_from_memory(bytes)
+        .ok()?
+        .resize_to_fill(size, size, imageops::FilterType::Lanczos3)
+        .to_rgba8();
+
+    let radius = size as f32 / 2.0;
+    for (x, y, pixel) in image.enumerate_pixels_mut() {
+        let dx = x as f32 + 0.5 - radius;
+        let dy = y as f32 + 0.5 - radius;
+        let coverage = (radius - (dx * dx + dy * dy).sqrt() + 0.5).clamp(0.0, 1.0);
+        pixel[3] = (pixel[3] as f32 * coverage).round() as u8;
+    }
+
+    let mut png = Vec::new();
+    image
+        .write_to(&mut Cursor::new(&mut png), ImageOutputFormat::Png)
+        .ok()?;
+    Some(png)
+}
+
+/// Avatar escalado a `side` y mezclado sobre `background`, en BGRA, para los
+/// renderers sin transparencia (GDI)
+pub fn flatten_bgra(png: &[u8], side: u32, background: Rgb) -> Option<Vec<u8>> {
+    let image = image::load_from_memory(png).ok()?.to_rgba8();
+    let image = imageops::resize(&image, side, side, imageops::FilterType::Triangle);
+    let mut canvas = RgbaImage::from_pixel(
+        side,
+        side,
+        Rgba([background.0, background.1, background.2, 255]),
+    );
+    imageops::overlay(&mut canvas, &image, 0, 0);
+    Some(
+        canvas
+            .pixels()
+            .flat_map(|pixel| [pixel[2], pixel[1], pixel[0], 255])
+            .collect(),
+    )
+}
+
+/// Dónde buscar la foto de perfil de un usuario
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum AvatarLookup {
+    Url(String),
+    Twitch { user_id: String },
+    Kick { username: String },
+}
+
+/// Clave de caché (`twitch:12345`) y forma de resolver el avatar del autor
+fn lookup_for(message: &ChatMessage) -> Option<(String, AvatarLookup)> {
+    let data = &message.metadata.custom_data;
+    let platform = message.platform.to_lowercase();
+    let user_id = match data.get("user_id") {
+        Some(serde_json::Value::String(id)) if !id.is_empty() => Some(id.clone()),
+        Some(serde_json::Value::Number(id)) => Some(id.to_string()),
+        _ => None,
+    };
+    let username = message.username.to_lowercase();
+    if username.is_empty() || username == "system" {
+        return None;
+    }
+    let key = format!("{}:{}", platform, user_id.as_deref().unwrap_or(&username));
+
+    let lookup = match data.get(AVATAR_URL_KEY).and_then(|url| url.as_str()) {
+        Some(url) if url.starts_with("http") => AvatarLookup::Url(url.to_string()),
+        _ => match platform.as_str() {
+            "twitch" => AvatarLookup::Twitch { user_id: user_id? },
+            "kick" => AvatarLookup::Kick { username },
+            _ => return None,
+        },
+    };
+    Some((key, lookup))
+}
+
+#[derive(Debug, Default)]
+struct AvatarState {
+    /// Token de Helix para los avatares de Twitch
+    twitch: Option<EventSubAuth>,
+    images: HashMap<String, Vec<u8>>,
+    /// Claves de `images`, de la menos a la más usada
+    order: VecDeque<String>,
+    missing: HashMap<String, Instant>,
+    pending: HashSet<String>,
+}
+
+impl AvatarState {
+    fn touch(&mut self, key: &str) {
+        if let Some(position) = self.order.iter().position(|k| k == key) {
+            self.order.remove(position);
+        }
+        self.order.push_back(key.to_string());
+    }
+
+    fn insert(&mut self, key: String, png: Vec<u8>) {
+        self.missing.remove(&key);
+        self.touch(&key);
+        self.images.insert(key, png);
+        while self.images.len() > MAX_MEMORY_AVATARS {
+            match self.order.pop_front() {
+                Some(oldest) => {
+                    self.images.remove(&oldest);
+                }
+                None => break,
+            }
+        }
+    }
+}
+
+/// Caché de avatares compartida por todas las ventanas
+#[derive(Debug)]
+pub struct AvatarStore {
+    state: Mutex<AvatarState>,
+    cache_dir: PathBuf,
+    http: reqwest::Client,
+}
+
+impl AvatarStore {
+    pub fn new(cache_dir: PathBuf) -> Self {
+        Self {
+            state: Mutex::new(AvatarState::default()),
+            cache_dir,
+            http: reqwest::Client::builder()
+                .timeout(Duration::from_secs(10))
+                .build()
+                .unwrap_or_default(),
+        }
+    }
+
+    pub fn global() -> &'static AvatarStore {
+        GLOBAL_AVATARS
+            .get_or_init(|| Self::new(std::env::temp_dir().join("overlay-native").join("avatars")))
+    }
+
+    /// Toma las credenciales de la plataforma Twitch para consultar Helix; sin
+    /// ellas los usuarios de Twitch se muestran sin avatar
+    pub fn configure(&self, platforms: &HashMap<String, PlatformConfig>) {
+        let twitch = platforms
+            .values()
+            .filter(|platform| matches!(platform.platform_type, PlatformType::Twitch))
+            .find_map(|platform| EventSubAuth::from_credentials(&platform.credentials));
+        let mut state = self.lock();
+        if state.twitch != twitch {
+            state.twitch = twitch;
+            state.missing.clear();
+        }
+    }
+
+    /// Avatar ya descargado (PNG recortado en círculo)
+    pub fn cached(&self, key: &str) -> Option<Vec<u8>> {
+        let mut state = self.lock();
+        let png = state.images.get(key).cloned()?;
+        state.touch(key);
+        Some(png)
+    }
+
+    /// Avatar del autor del mensaje si ya está en caché; si no, lanza la descarga
+    /// en segundo plano para los siguientes mensajes sin esperarla
+    pub fn avatar_for(&'static self, message: &ChatMessage) -> Option<Vec<u8>> {
+        let (key, lookup) = lookup_for(message)?;
+        if let Some(png) = self.cached(&key) {
+            return Some(png);
+        }
+        {
+            let mut state = self.lock();
+            let recently_missing = state
+                .missing
+                .get(&key)
+                .is_some_and(|since| since.elapsed() < MISSING_RETRY);
+            if recently_missing || !state.pending.insert(key.clone()) {
+                return None;
+            }
+        }
+
+        tokio::spawn(async move {
+            let png = self.resolve(&key, lookup).await;
+            let mut state = self.lock();
+            state.pending.remove(&key);
+            match png {
+                Some(png) => state.insert(key, png),
+                None => {
+                    state.missing.insert(key, Instant::now());
+                }
+            }
+        });
+        None
+    }
+
+    fn disk_path(&self, key: &str) -> PathBuf {
+        let name: String = key
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || c == '-' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        self.cache_dir.join(format!("{}.png", name))
+    }
+
+    async fn resolve(&self, key: &str, lookup: AvatarLookup) -> Option<Vec<u8>> {
+        let path = self.disk_path(key);
+        if let Ok(metadata) = tokio::fs::metadata(&path).await {
+            let fresh = metadata
+                .modified()
+                .ok()
+                .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+                .is_some_and(|age| age < DISK_TTL);
+            if fresh {
+                if let Ok(png) = tokio::fs::read(&path).await {
+                    return Some(png);
+                }
+            }
+        }
+
+        let url = match self.profile_image_url(lookup).await {
+            Ok(url) => url?,
+            Err(e) => {
+                eprintln!("⚠️ Cannot resolve avatar for {}: {}", key, e);
+                return None;
+            }
+        };
+        let bytes = match self.get(&url).await {
+            Ok(response) => response.bytes().await.ok()?,
+            Err(e) => {
+                eprintln!("⚠️ Cannot download avatar for {}: {}", key, e);
+                return None;
+            }
+        };
+        let png = tokio::task::spawn_blocking(move || circular_avatar(&bytes, RENDER_SIZE))
+            .await
+            .ok()??;
+
+        if tokio::fs::create_dir_all(&self.cache_dir).await.is_ok() {
+            if let Err(e) = tokio::fs::write(&path, &png).await {
+                eprintln!("⚠️ Cannot cache avatar {}: {}", path.display(), e);
+            }
+        }
+        Some(png)
+    }
+
+    async fn get(&self, url: &str) -> Result<reqwest::Response, reqwest::Error> {
+        self.http.get(url).send().await?.error_for_status()
+    }
+
+    /// URL de la foto de perfil; `Ok(None)` si el usuario no tiene o no se
+    /// puede consultar
+    async fn profile_image_url(
+        &self,
+        lookup: AvatarLookup,
+    ) -> Result<Option<String>, reqwest::Error> {
+        let (request, pointer) = match lookup {
+            AvatarLookup::Url(url) => return Ok(Some(url)),
+            AvatarLookup::Twitch { user_id } => {
+                let Some(auth) = self.lock().twitch.clone() else {
+                    return Ok(None);
+                };
+                let request = self
+                    .http
+                    .get("https://api.twitch.tv/helix/users")
+                    .query(&[("id", user_id)])
+                    .bearer_auth(&auth.token)
+                    .header("Client-Id", &auth.client_id);
+                (request, "/data/0/profile_image_url")
+            }
+            AvatarLookup::Kick { username } => {
+                let request = self
+                    .http
+                    .get(format!("https://kick.com/api/v2/channels/{}", username))
+                    .header("Accept", "application/json");
+                (request, "/user/profile_pic")
+            }
+        };
+
+        let body: serde_json::Value = request.send().await?.error_for_status()?.json().await?;
+        Ok(body
+            .pointer(pointer)
+            .and_then(|url| url.as_str())
+            .filter(|url| !url.is_empty())
+            .map(str::to_string))
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, AvatarState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lookup_by_platform_and_user_id() {
+        assert_eq!(
+            lookup_for(&ChatMessage::test("Alice", "hi").with_data("user_id", "123")),
+            Some((
+                "twitch:123".to_string(),
+                AvatarLookup::Twitch {
+                    user_id: "123".to_string()
+                }
+            ))
+        );
+        assert_eq!(lookup_for(&ChatMessage::test("alice", "hi")), None);
+        assert_eq!(
+            lookup_for(&ChatMessage::test("Bob", "hi").on("Kick", "chan")),
+            Some((
+                "kick:bob".to_string(),
+                AvatarLookup::Kick {
+                    username: "bob".to_string()
+                }
+            ))
+        );
+
+        let custom = ChatMessage::test("carol", "hi")
+            .on("custom", "chan")
+            .with_data(AVATAR_URL_KEY, "https://example.com/carol.png");
+        assert!(matches!(
+            lookup_for(&custom),
+            Some((_, AvatarLookup::Url(_)))
+        ));
+        assert_eq!(
+            lookup_for(&ChatMessage::test("dave", "hi").on("custom", "chan")),
+            None
+        );
+        assert_eq!(
+            lookup_for(&ChatMessage::test("system", "hi").with_data("user_id", "1")),
+            None
+        );
+    }
+
+    #[test]
+    fn test_memory_cache_evicts_least_recently_used() {
+        let mut state = AvatarState::default();
+        for i in 0..MAX_MEMORY_AVATARS {
+            state.insert(format!("twitch:{}", i), vec![i as u8]);
+        }
+        state.touch("twitch:0");
+        state.insert("kick:new".to_string(), vec![1]);
+        assert_eq!(state.images.len(), MAX_MEMORY_AVATARS);
+        assert!(state.images.contains_key("twitch:0"));
+        assert!(!state.images.contains_key("twitch:1"));
+    }
+
+    #[test]
+    fn test_circular_avatar_clears_the_corners() {
+        let photo = RgbaImage::from_pixel(40, 30, Rgba([200, 10, 10, 255]));
+        let mut bytes = Vec::new();
+        photo
+            .write_to(&mut Cursor::new(&mut bytes), ImageOutputFormat::Png)
+            .unwrap();
+
+        let png = circular_avatar(&bytes, 16).unwrap();
+        let avatar = image::load_from_memory(&png).unwrap().to_rgba8();
+        assert_eq!(avatar.dimensions(), (16, 16));
+        assert_eq!(avatar.get_pixel(0, 0)[3], 0);
+        assert_eq!(avatar.get_pixel(8, 8)[3], 255);
+
+        let bgra = flatten_bgra(&png, 8, Rgb(0, 0, 255)).unwrap();
+        assert_eq!(bgra.len(), 8 * 8 * 4);
+        // Esquina: color de fondo (azul en BGRA)
+        assert!(bgra[0] > 240 && bgra[2] < 16);
+    }
+}
@@ -0,0 +1,329 @@
+//! Prueba de carga del pipeline de mensajes
+//!
+//! Genera mensajes sintéticos (los del modo demo) a `--rate` por segundo y
+//! los pasa por el mismo camino que el overlay: emotes de terceros con
+//! `EmoteSystem`, mapeo con `MappingSystem` y colocación con la estrategia de
+//! `display.placement_mode`. No abre ventanas ni conexiones: las ventanas se
+//! simulan con su posición y su duración, y los emotes de canal salen de un
+//! proveedor local en lugar de BTTV.
+//!
+//! Como en la aplicación, los dos sistemas van detrás de un `RwLock` que cada
+//! mensaje toma en escritura, así que el informe separa la espera por el
+//! cerrojo del trabajo de cada etapa.
+//!
+//! `cargo run --release --bin bench -- --rate 500 --seconds 10 --workers 8`
+
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use clap::Parser;
+use tokio::sync::{Mutex, RwLock, Semaphore};
+
+use overlay_native::config::Config;
+use overlay_native::connection::{ChatMessage, Emote, EmoteSource};
+use overlay_native::demo::{DemoConfig, DemoGenerator};
+use overlay_native::emotes::{EmoteData, EmoteError, EmoteProvider, EmoteSystem};
+use overlay_native::mapping::{MappingSystem, RawPlatformMessage};
+use overlay_native::placement::{self, PlacementMode, PlacementStrategy};
+
+const MONITOR: (i32, i32) = (1920, 1080);
+
+#[derive(Parser, Debug)]
+#[command(about = "Load test for the message pipeline (emotes, mapping, placement)")]
+struct Args {
+    /// Mensajes por segundo
+    #[arg(long, default_value_t = 200)]
+    rate: u32,
+    /// Duración de la prueba
+    #[arg(long, default_value_t = 10)]
+    seconds: u64,
+    /// Mensajes que se procesan a la vez
+    #[arg(long, default_value_t = 4)]
+    workers: usize,
+    /// Emotes del set de canal sintético
+    #[arg(long, default_value_t = 300)]
+    emotes: usize,
+    /// `display.placement_mode` (grid, column, random, follow_cursor, anchor)
+    #[arg(long, default_value = "grid", value_parser = parse_placement)]
+    placement: PlacementMode,
+}
+
+fn parse_placement(value: &str) -> Result<PlacementMode, String> {
+    serde_json::from_value(serde_json::Value::String(value.to_string()))
+        .map_err(|_| format!("unknown placement mode '{}'", value))
+}
+
+/// Set de canal fijo, sin red, en el lugar de BTTV
+struct SyntheticEmotes {
+    emotes: Vec<EmoteData>,
+}
+
+impl SyntheticEmotes {
+    fn new(count: usize) -> Self {
+        let emotes = (0..count)
+            .map(|i| EmoteData {
+                id: format!("bench-{}", i),
+                name: format!("Bench{}", i),
+                url: None,
+                is_animated: i % 5 == 0,
+                width: Some(28),
+                height: Some(28),
+                is_zero_width: false,
+                modifier: false,
+                emote_set_id: None,
+            })
+            .collect();
+        Self { emotes }
+    }
+
+    /// Los mismos emotes en el formato que mezcla el generador de demo
+    fn demo_emotes(&self) -> Vec<Emote> {
+        self.emotes
+            .iter()
+            .map(|emote| Emote {
+                id: emote.id.clone(),
+                name: emote.name.clone(),
+                source: EmoteSource::BTTV,
+                ..Emote::default()
+            })
+            .collect()
+    }
+}
+
+#[async_trait::async_trait]
+impl EmoteProvider for SyntheticEmotes {
+    async fn parse_emotes(
+        &self,
+        _message: &str,
+        _emote_data: &str,
+    ) -> Result<Vec<Emote>, EmoteError> {
+        Ok(Vec::new())
+    }
+
+    async fn get_channel_emotes(
+        &self,
+        _platform: &str,
+        _channel: &str,
+    ) -> Result<Vec<EmoteData>, EmoteError> {
+        Ok(self.emotes.clone())
+    }
+
+    async fn get_global_emotes(&self) -> Result<Vec<EmoteData>, EmoteError> {
+        Ok(Vec::new())
+    }
+
+    fn provider_name(&self) -> &str {
+        "bttv"
+    }
+}
+
+/// Ventanas simuladas: posición y momento en que expiran
+struct MockWindows {
+    strategy: Box<dyn PlacementStrategy>,
+    visible: VecDeque<(Instant, (i32, i32))>,
+    duration: Duration,
+}
+
+impl MockWindows {
+    fn place(&mut self, now: Instant) -> (i32, i32) {
+        while self
+            .visible
+            .front()
+            .is_some_and(|(expires, _)| *expires <= now)
+        {
+            self.visible.pop_front();
+        }
+        let occupied: Vec<(i32, i32)> = self.visible.iter().map(|(_, pos)| *pos).collect();
+        let position = self.strategy.next_position(&occupied);
+        self.visible.push_back((now + self.duration, position));
+        position
+    }
+}
+
+/// Tiempos de un mensaje, de la llegada al final de la colocación
+#[derive(Default, Clone, Copy)]
+struct Timings {
+    emote_wait: Duration,
+    emotes: Duration,
+    mapping_wait: Duration,
+    mapping: Duration,
+    placement: Duration,
+    end_to_end: Duration,
+}
+
+struct Pipeline {
+    emote_system: Arc<RwLock<EmoteSystem>>,
+    mapping_system: Arc<RwLock<MappingSystem>>,
+    windows: Mutex<MockWindows>,
+}
+
+impl Pipeline {
+    /// Emotes, mapeo y colocación, como `process_message` y el bucle principal
+    async fn process(&self, mut message: ChatMessage, arrived: Instant) -> Result<Timings, String> {
+        let mut timings = Timings::default();
+
+        let start = Instant::now();
+        let mut emote_system = self.emote_system.write().await;
+        timings.emote_wait = start.elapsed();
+        if let Ok(emotes) = emote_system
+            .parse_message_emotes(&message.content, &message.platform, &message.channel, "")
+            .await
+        {
+            message.emotes.extend(emotes);
+        }
+        emote_system.apply_blocklist(&mut message);
+        drop(emote_system);
+        timings.emotes = start.elapsed();
+
+        let start = Instant::now();
+        let mut mapping_system = self.mapping_system.write().await;
+        timings.mapping_wait = start.elapsed();
+        let raw_message = RawPlatformMessage {
+            platform: message.platform.clone(),
+            channel: message.channel.clone(),
+            raw_data: serde_json::to_value(&message).map_err(|e| e.to_string())?,
+            timestamp: chrono::Utc::now(),
+            message_id: Some(message.id.clone()),
+        };
+        mapping_system
+            .map_message(&raw_message)
+            .await
+            .map_err(|e| e.to_string())?;
+        drop(mapping_system);
+        timings.mapping = start.elapsed();
+
+        let start = Instant::now();
+        self.windows.lock().await.place(Instant::now());
+        timings.placement = start.elapsed();
+
+        timings.end_to_end = arrived.elapsed();
+        Ok(timings)
+    }
+}
+
+/// Percentil `p` (0-1) de una lista ordenada
+fn percentile(sorted: &[Duration], p: f64) -> Duration {
+    if sorted.is_empty() {
+        return Duration::ZERO;
+    }
+    sorted[((sorted.len() - 1) as f64 * p).round() as usize]
+}
+
+fn format_duration(duration: Duration) -> String {
+    let micros = duration.as_secs_f64() * 1_000_000.0;
+    if micros >= 1000.0 {
+        format!("{:.2}ms", micros / 1000.0)
+    } else {
+        format!("{:.0}µs", micros)
+    }
+}
+
+fn print_row(name: &str, mut samples: Vec<Duration>) {
+    samples.sort();
+    println!(
+        "  {:<14} {:>10} {:>10} {:>10} {:>10}",
+        name,
+        format_duration(percentile(&samples, 0.50)),
+        format_duration(percentile(&samples, 0.90)),
+        format_duration(percentile(&samples, 0.99)),
+        format_duration(samples.last().copied().unwrap_or_default()),
+    );
+}
+
+#[tokio::main]
+async fn main() {
+    let args = Args::parse();
+    let config = Config::default();
+
+    let mut emote_config = config.emotes.clone();
+    emote_config.enable_bttv = true;
+    emote_config.enable_ffz = false;
+    emote_config.enable_7tv = false;
+    emote_config.seventv_live_updates = false;
+    emote_config.custom_providers.clear();
+    let synthetic = SyntheticEmotes::new(args.emotes);
+    let demo_emotes = synthetic.demo_emotes();
+    let mut emote_system = EmoteSystem::new(emote_config);
+    emote_system.register_provider("bttv".to_string(), Box::new(synthetic));
+
+    let mut display = config.display.clone();
+    display.placement_mode = args.placement;
+    let pipeline = Arc::new(Pipeline {
+        emote_system: Arc::new(RwLock::new(emote_system)),
+        mapping_system: Arc::new(RwLock::new(MappingSystem::default())),
+        windows: Mutex::new(MockWindows {
+            strategy: placement::strategy_for(&display, MONITOR, || None),
+            visible: VecDeque::new(),
+            duration: Duration::from_secs(config.window.message_duration_seconds),
+        }),
+    });
+
+    println!(
+        "🏁 Feeding {} messages/s for {}s ({} workers, {} channel emotes, {:?} placement)",
+        args.rate, args.seconds, args.workers, args.emotes, args.placement
+    );
+
+    let mut generator = DemoGenerator::new(DemoConfig::default(), demo_emotes);
+    let mut rng = rand::thread_rng();
+    let workers = Arc::new(Semaphore::new(args.workers.max(1)));
+    let (results_tx, mut results_rx) = tokio::sync::mpsc::unbounded_channel();
+    let mut interval =
+        tokio::time::interval(Duration::from_secs_f64(1.0 / f64::from(args.rate.max(1))));
+    let total = u64::from(args.rate) * args.seconds;
+    let started = Instant::now();
+
+    for _ in 0..total {
+        interval.tick().await;
+        let mut message = generator.next_message(&mut rng);
+        // El proveedor sintético tiene que encontrar los emotes en el texto
+        message.platform = "twitch".to_string();
+        message.emotes.clear();
+
+        let arrived = Instant::now();
+        let pipeline = pipeline.clone();
+        let workers = workers.clone();
+        let results_tx = results_tx.clone();
+        tokio::spawn(async move {
+            let Ok(_permit) = workers.acquire().await else {
+                return;
+            };
+            let _ = results_tx.send(pipeline.process(message, arrived).await);
+        });
+    }
+    drop(results_tx);
+
+    let mut timings = Vec::new();
+    let mut errors = 0;
+    while let Some(result) = results_rx.recv().await {
+        match result {
+            Ok(result) => timings.push(result),
+            Err(e) => {
+                if errors == 0 {
+                    eprintln!("⚠️ Pipeline error: {}", e);
+                }
+                errors += 1;
+            }
+        }
+    }
+    let elapsed = started.elapsed();
+
+    println!(
+        "📊 {} messages in {:.2}s: {:.1} messages/s ({} errors)",
+        timings.len(),
+        elapsed.as_secs_f64(),
+        timings.len() as f64 / elapsed.as_secs_f64(),
+        errors
+    );
+    println!(
+        "  {:<14} {:>10} {:>10} {:>10} {:>10}",
+        "stage", "p50", "p90", "p99", "max"
+    );
+    let column = |stage: fn(&Timings) -> Duration| timings.iter().map(stage).collect::<Vec<_>>();
+    print_row("emote lock", column(|t| t.emote_wait));
+    print_row("emotes", column(|t| t.emotes));
+    print_row("mapping lock", column(|t| t.mapping_wait));
+    print_row("mapping", column(|t| t.mapping));
+    print_row("placement", column(|t| t.placement));
+    print_row("end-to-end", column(|t| t.end_to_end));
+}
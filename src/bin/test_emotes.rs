@@ -5,7 +5,8 @@
 use overlay_native::emotes::providers::{
     BTTVEmoteProvider, FFZEmoteProvider, SevenTVEmoteProvider, TwitchEmoteProvider,
 };
-use overlay_native::emotes::EmoteProvider;
+use overlay_native::config::EmoteHttpConfig;
+use overlay_native::emotes::{EmoteHttpClient, EmoteProvider};
 
 #[tokio::main]
 async fn main() {
@@ -16,10 +17,11 @@ async fn main() {
     let mut total_success = 0;
     let mut total_failed = 0;
     let mut results = Vec::new();
+    let http = EmoteHttpClient::new(&EmoteHttpConfig::default());
 
     // Test BTTV
     println!("📦 Testing BTTV Provider...");
-    let bttv = BTTVEmoteProvider::new();
+    let bttv = BTTVEmoteProvider::with_http_client(http.clone());
     match test_provider("BTTV", &bttv).await {
         Ok(count) => {
             results.push(("BTTV", true, count, String::new()));
@@ -34,7 +36,7 @@ async fn main() {
 
     // Test FFZ
     println!("📦 Testing FFZ Provider...");
-    let ffz = FFZEmoteProvider::new();
+    let ffz = FFZEmoteProvider::with_http_client(http.clone());
     match test_provider("FFZ", &ffz).await {
         Ok(count) => {
             results.push(("FFZ", true, count, String::new()));
@@ -49,7 +51,7 @@ async fn main() {
 
     // Test 7TV
     println!("📦 Testing 7TV Provider...");
-    let seven_tv = SevenTVEmoteProvider::new();
+    let seven_tv = SevenTVEmoteProvider::with_http_client(http.clone());
     match test_provider("7TV", &seven_tv).await {
         Ok(count) => {
             results.push(("7TV", true, count, String::new()));
@@ -64,7 +66,7 @@ async fn main() {
 
     // Test Twitch (note: requires auth for global emotes)
     println!("📦 Testing Twitch Provider...");
-    let twitch = TwitchEmoteProvider::new();
+    let twitch = TwitchEmoteProvider::with_http_client(http.clone());
     println!("   ℹ️  Twitch provider initialized (auth required for global emotes)");
     println!("   Provider name: {}", twitch.provider_name());
     results.push(("Twitch", true, 0, "Auth required".to_string()));
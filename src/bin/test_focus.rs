@@ -0,0 +1,137 @@
+//! Regression test: overlay windows must never take foreground focus
+//!
+//! Opens several overlay windows, pumps the event loop and checks that:
+//! - they are excluded from the taskbar / Alt-Tab / pager
+//! - they refuse focus and never become the foreground/active window
+//!
+//! Exits with status 1 if any check fails. Without a display (headless CI on
+//! Linux) the test is skipped.
+//!
+//! Run with: cargo run --bin test_focus
+
+use overlay_native::config::WindowStyle;
+use std::time::{Duration, Instant};
+
+const WINDOWS: usize = 3;
+const SETTLE: Duration = Duration::from_millis(500);
+
+#[cfg(windows)]
+fn run() -> Result<(), String> {
+    use overlay_native::windows::{process_messages, WindowsWindow, OVERLAY_EX_STYLE};
+    use winapi::um::winuser::{
+        GetForegroundWindow, GetWindowLongPtrW, GWL_EXSTYLE, WS_EX_APPWINDOW, WS_EX_NOACTIVATE,
+        WS_EX_TOOLWINDOW,
+    };
+
+    let runtime = tokio::runtime::Runtime::new().map_err(|e| e.to_string())?;
+    let foreground_before = unsafe { GetForegroundWindow() };
+    let style = WindowStyle::default();
+    let windows: Vec<WindowsWindow> = (0..WINDOWS)
+        .map(|i| {
+            runtime.block_on(WindowsWindow::new(
+                "focus_test",
+                &format!("Focus regression window {}", i),
+                &[],
+                (100 + i as i32 * 40, 100 + i as i32 * 40),
+                &style,
+                None,
+            ))
+        })
+        .collect();
+
+    let start = Instant::now();
+    while start.elapsed() < SETTLE {
+        process_messages();
+        std::thread::sleep(Duration::from_millis(10));
+    }
+
+    let mut failures = Vec::new();
+    let foreground = unsafe { GetForegroundWindow() };
+    for (i, window) in windows.iter().enumerate() {
+        let ex_style = unsafe { GetWindowLongPtrW(window.hwnd.0, GWL_EXSTYLE) } as u32;
+        if ex_style & OVERLAY_EX_STYLE != OVERLAY_EX_STYLE {
+            failures.push(format!("window {} is missing overlay styles", i));
+        }
+        if ex_style & WS_EX_NOACTIVATE == 0 {
+            failures.push(format!("window {} lacks WS_EX_NOACTIVATE", i));
+        }
+        if ex_style & WS_EX_TOOLWINDOW == 0 || ex_style & WS_EX_APPWINDOW != 0 {
+            failures.push(format!("window {} would appear in the taskbar/Alt-Tab", i));
+        }
+        if foreground == window.hwnd.0 {
+            failures.push(format!("window {} took foreground focus", i));
+        }
+    }
+    if foreground != foreground_before && windows.iter().any(|w| w.hwnd.0 == foreground) {
+        failures.push("foreground window changed to an overlay".to_string());
+    }
+
+    for window in &windows {
+        window.close();
+    }
+    if failures.is_empty() {
+        Ok(())
+    } else {
+        Err(failures.join("\n"))
+    }
+}
+
+#[cfg(unix)]
+fn run() -> Result<(), String> {
+    use overlay_native::window::{get_gdk_monitor, is_focus_safe, spawn_window};
+
+    if gtk::init().is_err() {
+        println!("⏭️ No display available, skipping focus test");
+        return Ok(());
+    }
+
+    let runtime = tokio::runtime::Runtime::new().map_err(|e| e.to_string())?;
+    let monitor_geometry = gdk::prelude::MonitorExt::geometry(&get_gdk_monitor());
+    let style = WindowStyle::default();
+    let windows: Vec<_> = (0..WINDOWS)
+        .map(|i| {
+            runtime.block_on(spawn_window(
+                "focus_test",
+                &format!("Focus regression window {}", i),
+                &[],
+                (i as i32 * 40, i as i32 * 40),
+                monitor_geometry,
+                &style,
+                None,
+            ))
+        })
+        .collect();
+
+    let start = Instant::now();
+    while start.elapsed() < SETTLE {
+        gtk::main_iteration_do(false);
+        std::thread::sleep(Duration::from_millis(10));
+    }
+
+    let failures: Vec<String> = windows
+        .iter()
+        .enumerate()
+        .filter(|(_, spawned)| !is_focus_safe(&spawned.w))
+        .map(|(i, _)| format!("window {} accepts focus or appears in the taskbar", i))
+        .collect();
+
+    for spawned in &windows {
+        gtk::prelude::WidgetExt::hide(&spawned.w);
+    }
+    if failures.is_empty() {
+        Ok(())
+    } else {
+        Err(failures.join("\n"))
+    }
+}
+
+fn main() {
+    println!("🧪 Overlay Native - Focus regression test");
+    match run() {
+        Ok(()) => println!("✅ Overlay windows never took focus"),
+        Err(e) => {
+            println!("❌ Focus regression:\n{}", e);
+            std::process::exit(1);
+        }
+    }
+}
@@ -12,7 +12,7 @@
 
 #[cfg(unix)]
 use gdk::Rectangle;
-use overlay_native::config::{Config, DisplayConfig, WindowConfig};
+use overlay_native::config::{Config, DisplayConfig, WindowConfig, WindowStyle};
 #[cfg(unix)]
 use overlay_native::window::{
     get_gdk_monitor, AnchorAlignment, AnchorPoint, Coords, WindowGeometry,
@@ -62,6 +62,7 @@ async fn test_window_config_validation() -> Result<(), Box<dyn std::error::Error
         animation_enabled: true,
         fade_in_duration_ms: 300,
         fade_out_duration_ms: 500,
+        pool_size: 8,
     };
 
     println!(
@@ -77,6 +78,7 @@ async fn test_window_config_validation() -> Result<(), Box<dyn std::error::Error
         animation_enabled: false,      // No animation
         fade_in_duration_ms: 0,        // Instant fade in
         fade_out_duration_ms: 0,       // Instant fade out
+        pool_size: 0,                  // No window reuse
     };
 
     println!("   ✅ Edge case configuration tested");
@@ -93,6 +95,24 @@ async fn test_window_config_validation() -> Result<(), Box<dyn std::error::Error
         username_color: "#00ff00".to_string(),
         border_radius: 8,
         opacity: 0.9,
+        message_type_overrides: Default::default(),
+        theme: Default::default(),
+        min_font_size: 0,
+        themes: Default::default(),
+        active_theme: None,
+        event_templates: Default::default(),
+        area: Default::default(),
+        alerts_only: false,
+        user_levels: Default::default(),
+        show_avatars: false,
+        show_platform_icons: false,
+        placement_mode: Default::default(),
+        placement_anchor: Default::default(),
+        placement_spacing: 80,
+        template: Default::default(),
+        precomposed: false,
+        click_through: true,
+        layout_file: "layout.json".to_string(),
     };
 
     println!(
@@ -500,11 +520,19 @@ async fn test_windows_window_creation() -> Result<(), Box<dyn std::error::Error>
         println!("   🪟 Creating test window {}...", i + 1);
 
         // Create window
-        let window = WindowsWindow::new(username, message, &[], *position);
+        let window = WindowsWindow::new(
+            username,
+            message,
+            &[],
+            *position,
+            &WindowStyle::default(),
+            None,
+        )
+        .await;
 
         // Verify window was created
-        assert!(!window.hwnd.is_null(), "Window handle should not be null");
-        println!("     ✅ Window handle created: {:p}", window.hwnd);
+        assert!(!window.hwnd.0.is_null(), "Window handle should not be null");
+        println!("     ✅ Window handle created: {:p}", window.hwnd.0);
 
         // Verify window properties
         assert!(
@@ -519,7 +547,7 @@ async fn test_windows_window_creation() -> Result<(), Box<dyn std::error::Error>
         // Test window positioning (verify window exists)
         unsafe {
             let mut rect = std::mem::zeroed();
-            let result = winapi::um::winuser::GetWindowRect(window.hwnd, &mut rect);
+            let result = winapi::um::winuser::GetWindowRect(window.hwnd.0, &mut rect);
             assert!(result != 0, "Should be able to get window rect");
             println!(
                 "     ✅ Window position: ({}, {}), size: {}x{}",
@@ -544,7 +572,15 @@ async fn test_windows_window_properties() -> Result<(), Box<dyn std::error::Erro
     println!("\n🔍 Testing Windows Window Properties...");
 
     // Create a test window
-    let mut window = WindowsWindow::new("test_user", "Test message", &[], (150, 150));
+    let mut window = WindowsWindow::new(
+        "test_user",
+        "Test message",
+        &[],
+        (150, 150),
+        &WindowStyle::default(),
+        None,
+    )
+    .await;
 
     // Test progress updates
     let test_progress_values = vec![0.0, 0.25, 0.5, 0.75, 1.0];
@@ -568,7 +604,7 @@ async fn test_windows_window_properties() -> Result<(), Box<dyn std::error::Erro
     unsafe {
         // Check if window has layered attribute
         let ex_style =
-            winapi::um::winuser::GetWindowLongW(window.hwnd, winapi::um::winuser::GWL_EXSTYLE);
+            winapi::um::winuser::GetWindowLongW(window.hwnd.0, winapi::um::winuser::GWL_EXSTYLE);
         assert!(
             (ex_style & winapi::um::winuser::WS_EX_LAYERED as i32) != 0,
             "Window should have layered style"
@@ -587,7 +623,7 @@ async fn test_windows_window_properties() -> Result<(), Box<dyn std::error::Erro
         let mut alpha: u8 = 0;
         let mut flags: u32 = 0;
         let result = winapi::um::winuser::GetLayeredWindowAttributes(
-            window.hwnd,
+            window.hwnd.0,
             std::ptr::null_mut(),
             &mut alpha,
             &mut flags,
@@ -621,7 +657,10 @@ async fn test_windows_window_lifecycle() -> Result<(), Box<dyn std::error::Error
             &format!("Message {}", i),
             &[],
             (100 + i * 50, 100 + i * 30),
-        );
+            &WindowStyle::default(),
+            None,
+        )
+        .await;
         windows.push(window);
         println!("     ✅ Window {} created", i + 1);
     }
@@ -647,11 +686,13 @@ async fn test_windows_window_lifecycle() -> Result<(), Box<dyn std::error::Error
         window.close();
         println!("     ✅ Window {} closed", 5 - i);
     }
+    // Los cierres los ejecuta el hilo de la interfaz
+    overlay_native::windows::flush_ui();
 
     // Verify all windows are closed
     unsafe {
         for (i, window) in windows.iter().enumerate() {
-            let is_window = winapi::um::winuser::IsWindow(window.hwnd);
+            let is_window = winapi::um::winuser::IsWindow(window.hwnd.0);
             assert!(is_window == 0, "Window {} should be destroyed", i + 1);
         }
         println!("   ✅ All windows properly destroyed");
@@ -659,9 +700,17 @@ async fn test_windows_window_lifecycle() -> Result<(), Box<dyn std::error::Error
 
     // Test window creation after cleanup
     println!("   🪟 Testing window creation after cleanup...");
-    let final_window = WindowsWindow::new("final_user", "Final test", &[], (300, 300));
+    let final_window = WindowsWindow::new(
+        "final_user",
+        "Final test",
+        &[],
+        (300, 300),
+        &WindowStyle::default(),
+        None,
+    )
+    .await;
     assert!(
-        !final_window.hwnd.is_null(),
+        !final_window.hwnd.0.is_null(),
         "Should be able to create window after cleanup"
     );
     println!("     ✅ Window creation after cleanup successful");
@@ -671,6 +720,54 @@ async fn test_windows_window_lifecycle() -> Result<(), Box<dyn std::error::Error
     Ok(())
 }
 
+/// Crea y cierra ventanas desde varias tareas del runtime a la vez; ninguna
+/// bloquea su hilo mientras espera al de la interfaz
+#[cfg(windows)]
+async fn test_windows_window_tasks() -> Result<(), Box<dyn std::error::Error>> {
+    println!("\n🧵 Testing Windows windows from several tokio tasks...");
+
+    let tasks: Vec<_> = (0..8)
+        .map(|i| {
+            tokio::spawn(async move {
+                let window = WindowsWindow::new(
+                    &format!("task_{}", i),
+                    &format!("Message from task {}", i),
+                    &[],
+                    (100 + i * 30, 100 + i * 30),
+                    &WindowStyle::default(),
+                    None,
+                )
+                .await;
+                let hwnd = window.hwnd;
+                window.close();
+                hwnd
+            })
+        })
+        .collect();
+
+    let mut handles = Vec::new();
+    for task in tasks {
+        let hwnd = task.await?;
+        assert!(!hwnd.0.is_null(), "Window handle should not be null");
+        handles.push(hwnd);
+    }
+    println!(
+        "   ✅ {} windows created from separate tasks",
+        handles.len()
+    );
+
+    // Los cierres los ejecuta el hilo de la interfaz
+    overlay_native::windows::flush_ui();
+    unsafe {
+        for (i, hwnd) in handles.iter().enumerate() {
+            let is_window = winapi::um::winuser::IsWindow(hwnd.0);
+            assert!(is_window == 0, "Window {} should be destroyed", i + 1);
+        }
+    }
+    println!("   ✅ Windows windows from tokio tasks test completed");
+    Ok(())
+}
+
 async fn run_all_window_tests() -> Result<(), Box<dyn std::error::Error>> {
     println!("\n━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
     println!("🧪 OVERLAY NATIVE - COMPREHENSIVE WINDOW TEST SUITE");
@@ -710,6 +807,10 @@ async fn run_all_window_tests() -> Result<(), Box<dyn std::error::Error>> {
         ("Windows Window Lifecycle", || {
             Box::pin(test_windows_window_lifecycle())
         }),
+        #[cfg(windows)]
+        ("Windows Windows From Tasks", || {
+            Box::pin(test_windows_window_tasks())
+        }),
     ];
 
     for (test_name, test_func) in test_functions {
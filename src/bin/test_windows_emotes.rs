@@ -1,4 +1,6 @@
 #[cfg(windows)]
+use overlay_native::config::WindowStyle;
+#[cfg(windows)]
 use overlay_native::windows::WindowsWindow;
 use twitch_irc::message::Emote;
 
@@ -26,12 +28,15 @@ fn main() {
     ];
 
     // Create a test window
-    let window = WindowsWindow::new(
+    let runtime = tokio::runtime::Runtime::new().expect("Cannot start the tokio runtime");
+    let window = runtime.block_on(WindowsWindow::new(
         "TestUser",
         "Testing emotes: Kappa FeelsGoodMan PepeLaugh",
         &test_emotes,
         (100, 100),
-    );
+        &WindowStyle::default(),
+        None,
+    ));
 
     println!("✅ Test window created successfully!");
     println!("📊 Window handle: {:?}", window.hwnd);
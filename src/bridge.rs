@@ -0,0 +1,480 @@
+//! Puente WebSocket para fuentes de navegador (OBS)
+//!
+//! Sirve en `ws://<bind>` cada `MappedMessage` procesado, como JSON, para que
+//! una fuente de navegador reciba el mismo flujo unificado y con emotes que
+//! las ventanas nativas. Cada mensaje se envía como
+//! `{"type": "message", "data": { ...MappedMessage }}`.
+//!
+//! Los bots que consumen canales con mucho tráfico pueden pedir frames binarios
+//! con la misma estructura, en MessagePack o CBOR, con el subprotocolo
+//! WebSocket `overlay.msgpack`/`overlay.cbor` o con `?format=msgpack|cbor`.
+//! Cada formato se serializa una sola vez por mensaje, sea cual sea el número
+//! de clientes que lo piden, y `bytes_per_second` limita lo que recibe cada
+//! cliente: los frames que no caben en el presupuesto se descartan.
+
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, Instant};
+
+use futures::{SinkExt, StreamExt};
+use serde::Serialize;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::broadcast;
+use tokio_tungstenite::tungstenite::handshake::server::{
+    ErrorResponse, Request as HandshakeRequest, Response as HandshakeResponse,
+};
+use tokio_tungstenite::tungstenite::http::HeaderValue;
+use tokio_tungstenite::tungstenite::Message;
+
+use crate::config::BridgeConfig;
+use crate::mapping::MappedMessage;
+
+/// Codificación de los frames que recibe un cliente
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum FrameFormat {
+    /// Frames de texto con JSON (por defecto, para fuentes de navegador)
+    #[default]
+    Json,
+    MessagePack,
+    Cbor,
+}
+
+impl FrameFormat {
+    /// Subprotocolos WebSocket aceptados, en orden de preferencia
+    const SUBPROTOCOLS: [(&'static str, FrameFormat); 3] = [
+        ("overlay.msgpack", FrameFormat::MessagePack),
+        ("overlay.cbor", FrameFormat::Cbor),
+        ("overlay.json", FrameFormat::Json),
+    ];
+
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "json" => Some(FrameFormat::Json),
+            "msgpack" | "messagepack" => Some(FrameFormat::MessagePack),
+            "cbor" => Some(FrameFormat::Cbor),
+            _ => None,
+        }
+    }
+
+    /// Formato pedido en el handshake: primero `?format=`, después el primer
+    /// subprotocolo reconocido; devuelve también el subprotocolo a confirmar
+    pub fn negotiate(
+        query: Option<&str>,
+        protocols: Option<&str>,
+    ) -> (FrameFormat, Option<&'static str>) {
+        let from_query = query.into_iter().flat_map(|q| q.split('&')).find_map(|pair| {
+            pair.strip_prefix("format=")
+                .and_then(FrameFormat::parse)
+        });
+        if let Some(format) = from_query {
+            return (format, None);
+        }
+
+        protocols
+            .into_iter()
+            .flat_map(|p| p.split(','))
+            .find_map(|requested| {
+                Self::SUBPROTOCOLS
+                    .iter()
+                    .find(|(name, _)| requested.trim().eq_ignore_ascii_case(name))
+                    .map(|(name, format)| (*format, Some(*name)))
+            })
+            .unwrap_or_default()
+    }
+}
+
+/// Tipo del frame con un mensaje procesado
+const MESSAGE_FRAME: &str = "message";
+
+/// Tipos de frame que envía el puente y su contenido, para `/docs`
+pub const FRAME_TYPES: &[(&str, &str)] = &[(MESSAGE_FRAME, "Mensaje procesado (MappedMessage)")];
+
+/// Sobre común a todos los formatos
+#[derive(Debug, Serialize)]
+struct Envelope<'a> {
+    #[serde(rename = "type")]
+    kind: &'static str,
+    data: &'a MappedMessage,
+}
+
+/// Mensaje publicado, con cada formato serializado como mucho una vez
+#[derive(Debug)]
+pub struct BridgeFrame {
+    message: MappedMessage,
+    json: OnceLock<Arc<str>>,
+    msgpack: OnceLock<Arc<[u8]>>,
+    cbor: OnceLock<Arc<[u8]>>,
+}
+
+impl BridgeFrame {
+    pub fn new(message: MappedMessage) -> Self {
+        Self {
+            message,
+            json: OnceLock::new(),
+            msgpack: OnceLock::new(),
+            cbor: OnceLock::new(),
+        }
+    }
+
+    /// Frame WebSocket en `format`; los errores de serialización se registran
+    /// y el mensaje se omite para ese cliente
+    pub fn encode(&self, format: FrameFormat) -> Option<Message> {
+        let envelope = Envelope {
+            kind: MESSAGE_FRAME,
+            data: &self.message,
+        };
+        match format {
+            FrameFormat::Json => {
+                let json = self.json.get_or_init(|| {
+                    serde_json::to_string(&envelope)
+                        .unwrap_or_else(|e| {
+                            eprintln!("⚠️ Bridge JSON encoding failed: {}", e);
+                            String::new()
+                        })
+                        .into()
+                });
+                (!json.is_empty()).then(|| Message::Text(json.to_string()))
+            }
+            FrameFormat::MessagePack => {
+                let bytes = self.msgpack.get_or_init(|| {
+                    rmp_serde::to_vec_named(&envelope)
+                        .unwrap_or_else(|e| {
+                            eprintln!("⚠️ Bridge MessagePack encoding failed: {}", e);
+                            Vec::new()
+                        })
+                        .into()
+                });
+                (!bytes.is_empty()).then(|| Message::Binary(bytes.to_vec()))
+            }
+            FrameFormat::Cbor => {
+                let bytes = self.cbor.get_or_init(|| {
+                    let mut buffer = Vec::new();
+                    if let Err(e) = ciborium::into_writer(&envelope, &mut buffer) {
+                        eprintln!("⚠️ Bridge CBOR encoding failed: {}", e);
+                        buffer.clear();
+                    }
+                    buffer.into()
+                });
+                (!bytes.is_empty()).then(|| Message::Binary(bytes.to_vec()))
+            }
+        }
+    }
+}
+
+/// Bytes por segundo que puede recibir un cliente; 0 = sin límite
+#[derive(Debug)]
+struct ByteBudget {
+    bytes_per_second: u64,
+    window_start: Instant,
+    spent: u64,
+    /// Frames descartados en la ventana actual
+    dropped: u64,
+}
+
+impl ByteBudget {
+    fn new(bytes_per_second: u64) -> Self {
+        Self {
+            bytes_per_second,
+            window_start: Instant::now(),
+            spent: 0,
+            dropped: 0,
+        }
+    }
+
+    /// Descuenta `len` bytes si caben en la ventana de un segundo en curso;
+    /// al empezar una ventana nueva devuelve también los descartes de la anterior
+    fn try_spend(&mut self, len: usize, now: Instant) -> (bool, u64) {
+        if self.bytes_per_second == 0 {
+            return (true, 0);
+        }
+        let mut previous_dropped = 0;
+        if now.duration_since(self.window_start) >= Duration::from_secs(1) {
+            previous_dropped = std::mem::take(&mut self.dropped);
+            self.window_start = now;
+            self.spent = 0;
+        }
+        if self.spent + len as u64 > self.bytes_per_second {
+            self.dropped += 1;
+            return (false, previous_dropped);
+        }
+        self.spent += len as u64;
+        (true, previous_dropped)
+    }
+}
+
+/// Difusión de mensajes a los clientes conectados al puente
+#[derive(Debug, Clone)]
+pub struct MessageBridge {
+    sender: broadcast::Sender<Arc<BridgeFrame>>,
+    bytes_per_second: u64,
+}
+
+impl MessageBridge {
+    /// `capacity` mensajes pendientes por cliente antes de que empiece a perderlos
+    pub fn new(capacity: usize) -> Self {
+        let (sender, _) = broadcast::channel(capacity.max(1));
+        Self {
+            sender,
+            bytes_per_second: 0,
+        }
+    }
+
+    /// Límite de bytes por segundo para cada cliente; 0 = sin límite
+    pub fn with_byte_budget(mut self, bytes_per_second: u64) -> Self {
+        self.bytes_per_second = bytes_per_second;
+        self
+    }
+
+    /// Envía el mensaje a todos los clientes; devuelve a cuántos
+    pub fn publish(&self, message: &MappedMessage) -> usize {
+        // Sin clientes no merece la pena copiar el mensaje
+        if self.sender.receiver_count() == 0 {
+            return 0;
+        }
+        // Cada cliente serializa en su formato bajo demanda, una vez por formato
+        let frame = Arc::new(BridgeFrame::new(message.clone()));
+        self.sender.send(frame).unwrap_or(0)
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<Arc<BridgeFrame>> {
+        self.sender.subscribe()
+    }
+
+    pub fn client_count(&self) -> usize {
+        self.sender.receiver_count()
+    }
+}
+
+/// Inicia el servidor WebSocket en `config.bind`
+pub async fn serve(
+    config: BridgeConfig,
+    bridge: MessageBridge,
+) -> std::io::Result<tokio::task::JoinHandle<()>> {
+    let listener = TcpListener::bind(&config.bind).await?;
+    println!(
+        "🌉 WebSocket bridge listening on ws://{}",
+        listener.local_addr()?
+    );
+    Ok(serve_listener(listener, bridge))
+}
+
+/// Acepta clientes en un listener ya abierto
+pub fn serve_listener(listener: TcpListener, bridge: MessageBridge) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        loop {
+            let (stream, peer) = match listener.accept().await {
+                Ok(accepted) => accepted,
+                Err(e) => {
+                    eprintln!("⚠️ Bridge accept failed: {}", e);
+                    continue;
+                }
+            };
+            let receiver = bridge.subscribe();
+            let budget = ByteBudget::new(bridge.bytes_per_second);
+            tokio::spawn(async move {
+                if let Err(e) = handle_client(stream, receiver, budget).await {
+                    eprintln!("⚠️ Bridge client {} disconnected: {}", peer, e);
+                }
+            });
+        }
+    })
+}
+
+async fn handle_client(
+    stream: TcpStream,
+    mut receiver: broadcast::Receiver<Arc<BridgeFrame>>,
+    mut budget: ByteBudget,
+) -> Result<(), tokio_tungstenite::tungstenite::Error> {
+    let mut format = FrameFormat::Json;
+    // `ErrorResponse` lo impone el callback de tungstenite
+    #[allow(clippy::result_large_err)]
+    let negotiate = |request: &HandshakeRequest,
+                     mut response: HandshakeResponse|
+     -> Result<HandshakeResponse, ErrorResponse> {
+        let protocols = request
+            .headers()
+            .get("Sec-WebSocket-Protocol")
+            .and_then(|value| value.to_str().ok());
+        let (negotiated, protocol) = FrameFormat::negotiate(request.uri().query(), protocols);
+        format = negotiated;
+        if let Some(protocol) = protocol {
+            response
+                .headers_mut()
+                .insert("Sec-WebSocket-Protocol", HeaderValue::from_static(protocol));
+        }
+        Ok(response)
+    };
+    let websocket = tokio_tungstenite::accept_hdr_async(stream, negotiate).await?;
+    let (mut sink, mut incoming) = websocket.split();
+
+    loop {
+        tokio::select! {
+            frame = receiver.recv() => match frame {
+                Ok(frame) => {
+                    let Some(message) = frame.encode(format) else { continue };
+                    let (allowed, dropped) = budget.try_spend(message.len(), Instant::now());
+                    if dropped > 0 {
+                        eprintln!("⚠️ Bridge client over byte budget, dropped {} frames", dropped);
+                    }
+                    if allowed {
+                        sink.send(message).await?;
+                    }
+                }
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    eprintln!("⚠️ Bridge client too slow, skipped {} messages", skipped);
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            },
+            // Se leen los mensajes del cliente para responder a pings y detectar el cierre
+            frame = incoming.next() => match frame {
+                Some(Ok(Message::Close(_))) | None => break,
+                Some(Ok(_)) => {}
+                Some(Err(e)) => return Err(e),
+            },
+        }
+    }
+    let _ = sink.close().await;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mapping::{MappedMessageType, MappedMetadata, UserLevel};
+
+    fn mapped(content: &str) -> MappedMessage {
+        MappedMessage {
+            id: "1".to_string(),
+            platform: "twitch".to_string(),
+            channel: "chan".to_string(),
+            username: "viewer".to_string(),
+            display_name: None,
+            content: content.to_string(),
+            emotes: Vec::new(),
+            badges: Vec::new(),
+            timestamp: chrono::Utc::now(),
+            user_level: UserLevel::Normal,
+            message_type: MappedMessageType::Normal,
+            metadata: MappedMetadata::default(),
+        }
+    }
+
+    #[test]
+    fn test_publish_without_clients_is_a_no_op() {
+        let bridge = MessageBridge::new(8);
+        assert_eq!(bridge.publish(&mapped("hola")), 0);
+
+        let mut receiver = bridge.subscribe();
+        assert_eq!(bridge.publish(&mapped("hola")), 1);
+        let frame = receiver.try_recv().unwrap();
+        let payload: serde_json::Value =
+            serde_json::from_str(&frame.encode(FrameFormat::Json).unwrap().into_text().unwrap())
+                .unwrap();
+        assert_eq!(payload["type"], "message");
+        assert_eq!(payload["data"]["content"], "hola");
+    }
+
+    #[test]
+    fn test_binary_formats_carry_the_same_envelope() {
+        let frame = BridgeFrame::new(mapped("Kappa"));
+
+        let msgpack = frame.encode(FrameFormat::MessagePack).unwrap().into_data();
+        let payload: serde_json::Value = rmp_serde::from_slice(&msgpack).unwrap();
+        assert_eq!(payload["type"], "message");
+        assert_eq!(payload["data"]["content"], "Kappa");
+
+        let cbor = frame.encode(FrameFormat::Cbor).unwrap().into_data();
+        let payload: serde_json::Value = ciborium::from_reader(cbor.as_slice()).unwrap();
+        assert_eq!(payload["data"]["username"], "viewer");
+
+        let json = frame.encode(FrameFormat::Json).unwrap().into_data();
+        assert!(msgpack.len() < json.len());
+    }
+
+    #[test]
+    fn test_format_negotiation() {
+        assert_eq!(FrameFormat::negotiate(None, None), (FrameFormat::Json, None));
+        assert_eq!(
+            FrameFormat::negotiate(Some("token=x&format=cbor"), None),
+            (FrameFormat::Cbor, None)
+        );
+        assert_eq!(
+            FrameFormat::negotiate(None, Some("chat, overlay.msgpack")),
+            (FrameFormat::MessagePack, Some("overlay.msgpack"))
+        );
+        // Un formato desconocido en la query no impide usar el subprotocolo
+        assert_eq!(
+            FrameFormat::negotiate(Some("format=xml"), Some("overlay.cbor")),
+            (FrameFormat::Cbor, Some("overlay.cbor"))
+        );
+    }
+
+    #[test]
+    fn test_byte_budget_drops_frames_over_the_limit() {
+        let mut budget = ByteBudget::new(100);
+        let start = Instant::now();
+        assert_eq!(budget.try_spend(60, start), (true, 0));
+        assert_eq!(budget.try_spend(60, start), (false, 0));
+        assert_eq!(budget.try_spend(40, start), (true, 0));
+        // La ventana siguiente empieza de cero e informa de los descartes
+        assert_eq!(
+            budget.try_spend(60, start + Duration::from_secs(1)),
+            (true, 1)
+        );
+
+        let mut unlimited = ByteBudget::new(0);
+        assert_eq!(unlimited.try_spend(usize::MAX, start), (true, 0));
+    }
+
+    #[tokio::test]
+    async fn test_websocket_client_receives_messages() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let bridge = MessageBridge::new(8);
+        let server = serve_listener(listener, bridge.clone());
+
+        let (mut client, _) = tokio_tungstenite::connect_async(format!("ws://{}", addr))
+            .await
+            .unwrap();
+        // El cliente se suscribe al aceptar la conexión
+        while bridge.client_count() == 0 {
+            tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+        }
+        bridge.publish(&mapped("Kappa"));
+
+        let frame = tokio::time::timeout(std::time::Duration::from_secs(5), client.next())
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+        let payload: serde_json::Value = serde_json::from_str(&frame.into_text().unwrap()).unwrap();
+        assert_eq!(payload["data"]["content"], "Kappa");
+        server.abort();
+    }
+
+    #[tokio::test]
+    async fn test_websocket_client_negotiates_msgpack() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let bridge = MessageBridge::new(8);
+        let server = serve_listener(listener, bridge.clone());
+
+        let (mut client, _) =
+            tokio_tungstenite::connect_async(format!("ws://{}/?format=msgpack", addr))
+                .await
+                .unwrap();
+        while bridge.client_count() == 0 {
+            tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+        }
+        bridge.publish(&mapped("Kappa"));
+
+        let frame = tokio::time::timeout(std::time::Duration::from_secs(5), client.next())
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+        assert!(frame.is_binary());
+        let payload: serde_json::Value = rmp_serde::from_slice(&frame.into_data()).unwrap();
+        assert_eq!(payload["data"]["content"], "Kappa");
+        server.abort();
+    }
+}
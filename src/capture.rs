@@ -0,0 +1,276 @@
+//! Captura de payloads crudos de las plataformas para depuración
+//!
+//! Cada conexión escribe en su propio fichero anillo (`<clave>.capture.log`,
+//! `<clave>.capture.log.1`, ...) con tamaño limitado. Los tokens y credenciales
+//! se redactan antes de escribir. La captura se puede activar y desactivar en
+//! tiempo de ejecución con [`PayloadCapture::set_enabled`].
+
+use regex::Regex;
+use std::collections::HashMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, OnceLock, RwLock};
+
+use crate::config::CaptureConfig;
+
+static GLOBAL_CAPTURE: OnceLock<PayloadCapture> = OnceLock::new();
+
+/// Escritor de payloads crudos por conexión
+pub struct PayloadCapture {
+    config: RwLock<CaptureConfig>,
+    enabled: AtomicBool,
+    files: Mutex<HashMap<String, RingFile>>,
+}
+
+impl PayloadCapture {
+    pub fn new(config: CaptureConfig) -> Self {
+        Self {
+            enabled: AtomicBool::new(config.enabled),
+            config: RwLock::new(config),
+            files: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Captura global del proceso, usada por los adaptadores de plataforma
+    pub fn global() -> &'static PayloadCapture {
+        GLOBAL_CAPTURE.get_or_init(|| Self::new(CaptureConfig::default()))
+    }
+
+    /// Aplica una nueva configuración y reabre los ficheros en el nuevo directorio
+    pub fn configure(&self, config: CaptureConfig) {
+        self.enabled.store(config.enabled, Ordering::Relaxed);
+        *self.config.write().unwrap_or_else(|e| e.into_inner()) = config;
+        self.files.lock().unwrap_or_else(|e| e.into_inner()).clear();
+    }
+
+    pub fn set_enabled(&self, enabled: bool) {
+        self.enabled.store(enabled, Ordering::Relaxed);
+        if !enabled {
+            self.files.lock().unwrap_or_else(|e| e.into_inner()).clear();
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+
+    /// Registra un payload crudo (línea IRC, frame de Pusher, ...) para una conexión
+    pub fn record(&self, platform: &str, channel: &str, payload: &str) {
+        if !self.is_enabled() {
+            return;
+        }
+
+        let config = self
+            .config
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone();
+        let payload = if config.redact {
+            redact(payload)
+        } else {
+            payload.to_string()
+        };
+        // Un payload por línea para que el fichero se pueda reproducir línea a línea
+        let line = format!(
+            "{} {}\n",
+            chrono::Utc::now().to_rfc3339(),
+            payload.replace(['\r', '\n'], " ").trim_end()
+        );
+
+        let key = capture_key(platform, channel);
+        let mut files = self.files.lock().unwrap_or_else(|e| e.into_inner());
+        let file = files.entry(key.clone()).or_insert_with(|| {
+            RingFile::new(config.directory.join(format!("{}.capture.log", key)))
+        });
+
+        if let Err(e) = file.write_line(&line, config.max_file_bytes, config.max_files) {
+            eprintln!("[CAPTURE] ⚠️ Failed to write capture for {}: {}", key, e);
+        }
+    }
+}
+
+/// Nombre de fichero seguro para una conexión (`plataforma_canal`)
+pub fn capture_key(platform: &str, channel: &str) -> String {
+    format!("{}_{}", platform, channel.trim_start_matches('#'))
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.' {
+                c.to_ascii_lowercase()
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+fn redaction_patterns() -> &'static [(Regex, &'static str)] {
+    static PATTERNS: OnceLock<Vec<(Regex, &'static str)>> = OnceLock::new();
+    PATTERNS.get_or_init(|| {
+        vec![
+            // IRC: PASS oauth:xxxx y cualquier token oauth: suelto
+            (Regex::new(r"(?i)oauth:[a-z0-9]+").unwrap(), "oauth:[REDACTED]"),
+            // Cabeceras Authorization: Bearer xxxx
+            (
+                Regex::new(r"(?i)(bearer\s+)[a-z0-9._~+/=-]+").unwrap(),
+                "${1}[REDACTED]",
+            ),
+            // Campos JSON con credenciales
+            (
+                Regex::new(
+                    r#"(?i)("(?:[a-z_]*token|auth|authorization|password|secret|client_secret|api_key|cookie)"\s*:\s*)"(?:[^"\\]|\\.)*""#,
+                )
+                .unwrap(),
+                "${1}\"[REDACTED]\"",
+            ),
+        ]
+    })
+}
+
+/// Elimina tokens y credenciales de un payload
+pub fn redact(payload: &str) -> String {
+    redaction_patterns()
+        .iter()
+        .fold(payload.to_string(), |text, (pattern, replacement)| {
+            pattern.replace_all(&text, *replacement).into_owned()
+        })
+}
+
+/// Fichero con rotación: `path`, `path.1`, ..., `path.(max_files - 1)`
+struct RingFile {
+    path: PathBuf,
+    file: Option<File>,
+    size: u64,
+}
+
+impl RingFile {
+    fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            file: None,
+            size: 0,
+        }
+    }
+
+    fn write_line(&mut self, line: &str, max_bytes: u64, max_files: usize) -> std::io::Result<()> {
+        if self.file.is_none() {
+            self.open()?;
+        }
+
+        if self.size > 0 && self.size + line.len() as u64 > max_bytes {
+            self.rotate(max_files)?;
+        }
+
+        if let Some(file) = self.file.as_mut() {
+            file.write_all(line.as_bytes())?;
+            self.size += line.len() as u64;
+        }
+        Ok(())
+    }
+
+    fn open(&mut self) -> std::io::Result<()> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        self.size = file.metadata().map(|m| m.len()).unwrap_or(0);
+        self.file = Some(file);
+        Ok(())
+    }
+
+    fn rotate(&mut self, max_files: usize) -> std::io::Result<()> {
+        self.file = None;
+        let rotated = |n: usize| -> PathBuf {
+            let mut name = self.path.clone().into_os_string();
+            name.push(format!(".{}", n));
+            name.into()
+        };
+
+        if max_files <= 1 {
+            let _ = fs::remove_file(&self.path);
+        } else {
+            let _ = fs::remove_file(rotated(max_files - 1));
+            for n in (1..max_files - 1).rev() {
+                let from = rotated(n);
+                if Path::new(&from).exists() {
+                    fs::rename(&from, rotated(n + 1))?;
+                }
+            }
+            fs::rename(&self.path, rotated(1))?;
+        }
+        self.open()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_config(dir: &Path) -> CaptureConfig {
+        CaptureConfig {
+            enabled: true,
+            directory: dir.to_path_buf(),
+            max_file_bytes: 200,
+            max_files: 3,
+            redact: true,
+        }
+    }
+
+    #[test]
+    fn test_redact_tokens() {
+        assert_eq!(redact("PASS oauth:abc123def456"), "PASS oauth:[REDACTED]");
+        let json = r#"{"event":"pusher:subscribe","data":{"auth":"key:sig","channel":"chatrooms.1.v2","access_token":"s3cr\"et"}}"#;
+        let redacted = redact(json);
+        assert!(!redacted.contains("key:sig"));
+        assert!(!redacted.contains("s3cr"));
+        assert!(redacted.contains(r#""channel":"chatrooms.1.v2""#));
+        assert_eq!(
+            redact("Authorization: Bearer abc.def-ghi"),
+            "Authorization: Bearer [REDACTED]"
+        );
+    }
+
+    #[test]
+    fn test_ring_files_respect_size_caps() {
+        let dir = tempfile::tempdir().unwrap();
+        let capture = PayloadCapture::new(test_config(dir.path()));
+
+        for i in 0..50 {
+            capture.record(
+                "twitch",
+                "#Foo",
+                &format!("PRIVMSG #foo :message number {}", i),
+            );
+        }
+
+        let base = dir.path().join("twitch_foo.capture.log");
+        assert!(base.exists());
+        assert!(dir.path().join("twitch_foo.capture.log.2").exists());
+        assert!(!dir.path().join("twitch_foo.capture.log.3").exists());
+        for entry in fs::read_dir(dir.path()).unwrap() {
+            assert!(entry.unwrap().metadata().unwrap().len() <= 200);
+        }
+        let latest = fs::read_to_string(base).unwrap();
+        assert!(latest.contains("message number 49"));
+    }
+
+    #[test]
+    fn test_toggle_at_runtime() {
+        let dir = tempfile::tempdir().unwrap();
+        let capture = PayloadCapture::new(CaptureConfig {
+            enabled: false,
+            ..test_config(dir.path())
+        });
+
+        capture.record("kick", "chatrooms.1.v2", "{}");
+        assert!(fs::read_dir(dir.path()).unwrap().next().is_none());
+
+        capture.set_enabled(true);
+        capture.record("kick", "chatrooms.1.v2", "{}");
+        assert!(dir.path().join("kick_chatrooms.1.v2.capture.log").exists());
+    }
+}
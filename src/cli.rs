@@ -0,0 +1,345 @@
+//! Línea de comandos
+//!
+//! Sin subcomando se arranca el overlay, igual que con `run`. El resto son
+//! herramientas de diagnóstico que no abren conexiones ni la interfaz
+//! principal; `connections status` consulta un overlay ya en marcha a través
+//! de la API de control.
+
+use std::path::PathBuf;
+use std::time::Duration;
+
+use clap::{Args, Parser, Subcommand};
+
+use crate::config::DEFAULT_CONFIG_PATH;
+use crate::presets::DisplayPreset;
+use crate::storage::ExportFormat;
+use crate::terminal::RendererKind;
+
+#[derive(Debug, Parser)]
+#[command(
+    name = "overlay-native",
+    version,
+    about = "Native multi-platform chat overlay",
+    args_conflicts_with_subcommands = true
+)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+    /// Opciones de `run` sin escribir el subcomando (`overlay-native --stdin`)
+    #[command(flatten)]
+    pub run: RunArgs,
+}
+
+impl Cli {
+    /// Subcomando pedido; `run` si no se indicó ninguno
+    pub fn into_command(self) -> Command {
+        self.command.unwrap_or(Command::Run(self.run))
+    }
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Start the overlay (default)
+    Run(RunArgs),
+    /// Show a single window with the current configuration and exit
+    TestWindow(TestWindowArgs),
+    /// Check a configuration file without starting the overlay
+    ValidateConfig(ConfigArgs),
+    /// List the emotes available in a channel
+    ListEmotes(ListEmotesArgs),
+    /// Inspect the connections of a running overlay (requires the control API)
+    #[command(subcommand)]
+    Connections(ConnectionsCommand),
+    /// Emote set tools
+    #[command(subcommand)]
+    Emotes(EmotesCommand),
+    /// Theme tools
+    #[command(subcommand)]
+    Theme(ThemeCommand),
+    /// Create config.json from a display preset sized for this monitor
+    Init(InitArgs),
+    /// Manage secrets referenced as `keyring:<key>` in the configuration
+    #[command(subcommand)]
+    Keyring(KeyringCommand),
+    /// Export the archived chat (`storage`) to JSON or CSV
+    Export(ExportArgs),
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Args)]
+pub struct RunArgs {
+    /// Read NDJSON messages from stdin instead of connecting to platforms
+    #[arg(long)]
+    pub stdin: bool,
+    /// Generate sample chat messages instead of connecting to platforms
+    #[arg(long, conflicts_with = "stdin")]
+    pub demo: bool,
+    /// auto, native or terminal; auto prints to the terminal without a display server
+    #[arg(long, default_value = "auto", value_parser = parse_renderer)]
+    pub renderer: RendererKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Args)]
+pub struct ConfigArgs {
+    /// Configuration file
+    #[arg(long, default_value = DEFAULT_CONFIG_PATH)]
+    pub config: PathBuf,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Args)]
+pub struct TestWindowArgs {
+    /// Message text; defaults to `window.test_message`
+    #[arg(long)]
+    pub text: Option<String>,
+    /// Username shown in the window; defaults to the diagnostics user
+    #[arg(long)]
+    pub user: Option<String>,
+    #[command(flatten)]
+    pub config: ConfigArgs,
+}
+
+/// Formato y destino de un listado de emotes
+#[derive(Debug, Clone, PartialEq, Eq, Args)]
+pub struct EmoteListOptions {
+    #[arg(long, default_value = "twitch")]
+    pub platform: String,
+    /// Print JSON instead of a table
+    #[arg(long)]
+    pub json: bool,
+    /// Write to a file instead of stdout
+    #[arg(long)]
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Args)]
+pub struct ListEmotesArgs {
+    pub channel: String,
+    #[command(flatten)]
+    pub options: EmoteListOptions,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum EmotesCommand {
+    /// Dump the emote set of a channel
+    Dump {
+        #[arg(long)]
+        channel: String,
+        #[command(flatten)]
+        options: EmoteListOptions,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum ConnectionsCommand {
+    /// Show the state of every connection
+    Status(ConnectionsStatusArgs),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Args)]
+pub struct ConnectionsStatusArgs {
+    /// Control API address; defaults to `control.bind` in the configuration
+    #[arg(long)]
+    pub url: Option<String>,
+    #[command(flatten)]
+    pub config: ConfigArgs,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum ThemeCommand {
+    /// Import a StreamElements or Streamlabs chat widget export
+    Import {
+        export: PathBuf,
+        #[command(flatten)]
+        config: ConfigArgs,
+        /// Print the resulting display settings without saving them
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Args)]
+pub struct InitArgs {
+    /// minimal_corner, full_side_feed or center_alerts_only; asks if omitted
+    #[arg(long, value_parser = parse_preset)]
+    pub preset: Option<DisplayPreset>,
+    #[command(flatten)]
+    pub config: ConfigArgs,
+    /// Overwrite an existing configuration file
+    #[arg(long)]
+    pub force: bool,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum KeyringCommand {
+    /// Store a secret read from stdin (e.g. `twitch/oauth_token`)
+    Set { key: String },
+    /// Delete a stored secret
+    Remove { key: String },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Args)]
+pub struct ExportArgs {
+    /// Only messages from the last 30m, 2h, 7d...
+    #[arg(long, value_parser = parse_age)]
+    pub since: Option<Duration>,
+    /// json or csv
+    #[arg(long, default_value = "json", value_parser = parse_export_format)]
+    pub format: ExportFormat,
+    /// Output file; defaults to chat-export.<format>
+    #[arg(long)]
+    pub output: Option<PathBuf>,
+    #[command(flatten)]
+    pub config: ConfigArgs,
+}
+
+/// `30s`, `15m`, `2h` o `7d`
+fn parse_age(value: &str) -> Result<Duration, String> {
+    let split = value.len() - value.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+    let (amount, unit) = value.split_at(split);
+    let amount: u64 = amount
+        .parse()
+        .map_err(|_| format!("invalid duration '{}' (e.g. 30m, 2h, 7d)", value))?;
+    let secs = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 3600,
+        "d" => 86400,
+        _ => return Err(format!("invalid duration '{}' (e.g. 30m, 2h, 7d)", value)),
+    };
+    Ok(Duration::from_secs(amount * secs))
+}
+
+fn parse_export_format(name: &str) -> Result<ExportFormat, String> {
+    ExportFormat::from_name(name).ok_or_else(|| {
+        let names: Vec<&str> = ExportFormat::ALL.iter().map(|f| f.name()).collect();
+        format!("unknown format '{}' (expected {})", name, names.join(", "))
+    })
+}
+
+fn parse_renderer(name: &str) -> Result<RendererKind, String> {
+    RendererKind::from_name(name).ok_or_else(|| {
+        let names: Vec<&str> = RendererKind::ALL.iter().map(|r| r.name()).collect();
+        format!("unknown renderer '{}' (expected {})", name, names.join(", "))
+    })
+}
+
+fn parse_preset(name: &str) -> Result<DisplayPreset, String> {
+    DisplayPreset::from_name(name).ok_or_else(|| {
+        let names: Vec<&str> = DisplayPreset::ALL.iter().map(|p| p.name()).collect();
+        format!("unknown preset '{}' (expected {})", name, names.join(", "))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::CommandFactory;
+
+    fn parse(args: &[&str]) -> Result<Command, clap::Error> {
+        Cli::try_parse_from(std::iter::once("overlay-native").chain(args.iter().copied()))
+            .map(Cli::into_command)
+    }
+
+    #[test]
+    fn test_run_is_the_default_command() {
+        Cli::command().debug_assert();
+        assert!(matches!(
+            parse(&[]).unwrap(),
+            Command::Run(RunArgs {
+                stdin: false,
+                demo: false,
+                renderer: RendererKind::Auto,
+            })
+        ));
+        assert!(matches!(
+            parse(&["--stdin"]).unwrap(),
+            Command::Run(RunArgs { stdin: true, .. })
+        ));
+        assert!(matches!(
+            parse(&["run", "--stdin"]).unwrap(),
+            Command::Run(RunArgs { stdin: true, .. })
+        ));
+        assert!(matches!(
+            parse(&["--demo"]).unwrap(),
+            Command::Run(RunArgs { demo: true, .. })
+        ));
+        assert!(matches!(
+            parse(&["--demo", "--renderer", "terminal"]).unwrap(),
+            Command::Run(RunArgs {
+                demo: true,
+                renderer: RendererKind::Terminal,
+                ..
+            })
+        ));
+        assert!(parse(&["--renderer", "curses"]).is_err());
+        assert!(parse(&["--stdin", "--demo"]).is_err());
+        assert!(parse(&["--test-windows"]).is_err());
+    }
+
+    #[test]
+    fn test_subcommands_and_their_arguments() {
+        match parse(&["test-window", "--text", "hi"]).unwrap() {
+            Command::TestWindow(args) => {
+                assert_eq!(args.text.as_deref(), Some("hi"));
+                assert_eq!(args.config.config, PathBuf::from(DEFAULT_CONFIG_PATH));
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+        match parse(&["list-emotes", "xqc", "--platform", "kick", "--json"]).unwrap() {
+            Command::ListEmotes(args) => {
+                assert_eq!(args.channel, "xqc");
+                assert_eq!(args.options.platform, "kick");
+                assert!(args.options.json);
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+        assert!(matches!(
+            parse(&["connections", "status", "--url", "127.0.0.1:9000"]).unwrap(),
+            Command::Connections(ConnectionsCommand::Status(ConnectionsStatusArgs {
+                url: Some(_),
+                ..
+            }))
+        ));
+        assert!(matches!(
+            parse(&["validate-config", "--config", "other.json"]).unwrap(),
+            Command::ValidateConfig(_)
+        ));
+        assert!(matches!(
+            parse(&["init", "--preset", "center_alerts_only"]).unwrap(),
+            Command::Init(InitArgs {
+                preset: Some(DisplayPreset::CenterAlertsOnly),
+                ..
+            })
+        ));
+        assert!(parse(&["init", "--preset", "huge"]).is_err());
+        assert!(matches!(
+            parse(&["keyring", "set", "twitch/oauth_token"]).unwrap(),
+            Command::Keyring(KeyringCommand::Set { key }) if key == "twitch/oauth_token"
+        ));
+        assert!(parse(&["list-emotes"]).is_err());
+    }
+
+    #[test]
+    fn test_export_arguments() {
+        match parse(&["export", "--since", "2h", "--format", "csv"]).unwrap() {
+            Command::Export(args) => {
+                assert_eq!(args.since, Some(Duration::from_secs(7200)));
+                assert_eq!(args.format, ExportFormat::Csv);
+                assert_eq!(args.output, None);
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+        assert!(matches!(
+            parse(&["export"]).unwrap(),
+            Command::Export(ExportArgs {
+                since: None,
+                format: ExportFormat::Json,
+                ..
+            })
+        ));
+        assert_eq!(parse_age("45m"), Ok(Duration::from_secs(2700)));
+        for invalid in ["2", "h", "2w", "-1h"] {
+            assert!(parse_age(invalid).is_err());
+        }
+        assert!(parse(&["export", "--format", "xml"]).is_err());
+    }
+}
@@ -0,0 +1,204 @@
+//! Modo «solo comandos» conmutable en caliente por conexión
+//!
+//! Durante una ronda de preguntas se puede activar `filters.commands_only` y
+//! limitar los prefijos aceptados (p. ej. solo `!question`) desde la API de
+//! control sin editar `config.json`. Las sobrescrituras viven en memoria y se
+//! pierden al reiniciar.
+
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::config::MessageFilters;
+
+static GLOBAL_MODES: OnceLock<CommandModes> = OnceLock::new();
+
+/// Modo efectivo de una conexión tal y como se expone en la API
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct CommandMode {
+    pub commands_only: bool,
+    /// Prefijos aceptados con `commands_only`; vacío = `!` y `/`
+    pub prefixes: Vec<String>,
+    /// `true` si se cambió en caliente y no coincide con `config.json`
+    pub overridden: bool,
+}
+
+/// Cuerpo de `POST /connections/{id}/commands-only`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct CommandModeRequest {
+    /// Estado deseado; sin indicar, invierte el actual
+    pub enabled: Option<bool>,
+    /// Nuevos prefijos; sin indicar, se conservan los actuales
+    pub prefixes: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct CommandModeOverride {
+    commands_only: bool,
+    prefixes: Vec<String>,
+}
+
+/// Sobrescrituras en caliente de `commands_only` por id de conexión
+#[derive(Debug, Default)]
+pub struct CommandModes {
+    overrides: Mutex<HashMap<String, CommandModeOverride>>,
+}
+
+impl CommandModes {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn global() -> &'static CommandModes {
+        GLOBAL_MODES.get_or_init(Self::new)
+    }
+
+    /// Modo efectivo de la conexión sobre sus filtros configurados
+    pub fn mode(&self, connection_id: &str, filters: &MessageFilters) -> CommandMode {
+        match self.lock().get(connection_id) {
+            Some(mode) => CommandMode {
+                commands_only: mode.commands_only,
+                prefixes: mode.prefixes.clone(),
+                overridden: true,
+            },
+            None => CommandMode {
+                commands_only: filters.commands_only,
+                prefixes: filters.command_prefixes.clone(),
+                overridden: false,
+            },
+        }
+    }
+
+    /// Aplica `request` sobre el modo actual y devuelve el resultante
+    pub fn set(
+        &self,
+        connection_id: &str,
+        filters: &MessageFilters,
+        request: CommandModeRequest,
+    ) -> CommandMode {
+        let current = self.mode(connection_id, filters);
+        let mode = CommandModeOverride {
+            commands_only: request.enabled.unwrap_or(!current.commands_only),
+            prefixes: request
+                .prefixes
+                .map(|prefixes| {
+                    prefixes
+                        .into_iter()
+                        .map(|prefix| prefix.trim().to_string())
+                        .filter(|prefix| !prefix.is_empty())
+                        .collect()
+                })
+                .unwrap_or(current.prefixes),
+        };
+
+        let mut overrides = self.lock();
+        // Volver a lo configurado equivale a quitar la sobrescritura
+        if mode.commands_only == filters.commands_only && mode.prefixes == filters.command_prefixes
+        {
+            overrides.remove(connection_id);
+        } else {
+            overrides.insert(connection_id.to_string(), mode);
+        }
+        drop(overrides);
+        self.mode(connection_id, filters)
+    }
+
+    /// Vuelve a los filtros de `config.json`; `false` si no había sobrescritura
+    pub fn reset(&self, connection_id: &str) -> bool {
+        self.lock().remove(connection_id).is_some()
+    }
+
+    /// Filtros de la conexión con la sobrescritura aplicada
+    pub fn filters_for(&self, connection_id: &str, filters: &MessageFilters) -> MessageFilters {
+        let mut filters = filters.clone();
+        if let Some(mode) = self.lock().get(connection_id) {
+            filters.commands_only = mode.commands_only;
+            filters.command_prefixes = mode.prefixes.clone();
+        }
+        filters
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, CommandModeOverride>> {
+        self.overrides.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl std::fmt::Display for CommandMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if !self.commands_only {
+            return f.write_str("all messages");
+        }
+        if self.prefixes.is_empty() {
+            f.write_str("commands only")
+        } else {
+            write!(f, "commands only ({})", self.prefixes.join(", "))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn filters() -> MessageFilters {
+        MessageFilters {
+            min_message_length: None,
+            max_message_length: None,
+            blocked_users: vec![],
+            allowed_users: vec![],
+            allowed_users_only: true,
+            ignore_bots: false,
+            bot_users: vec![],
+            blocked_words: vec![],
+            hide_link_only: false,
+            commands_only: false,
+            command_prefixes: vec![],
+            subscribers_only: false,
+            vip_only: false,
+        }
+    }
+
+    #[test]
+    fn test_toggle_and_prefixes_override_config() {
+        let modes = CommandModes::new();
+        let base = filters();
+
+        let mode = modes.set(
+            "main",
+            &base,
+            CommandModeRequest {
+                enabled: None,
+                prefixes: Some(vec![" !question ".to_string(), "".to_string()]),
+            },
+        );
+        assert!(mode.commands_only);
+        assert!(mode.overridden);
+        assert_eq!(mode.prefixes, vec!["!question"]);
+        assert_eq!(mode.to_string(), "commands only (!question)");
+
+        let effective = modes.filters_for("main", &base);
+        assert!(effective.commands_only);
+        assert_eq!(effective.command_prefixes, vec!["!question"]);
+        // Otras conexiones no se ven afectadas
+        assert!(!modes.filters_for("other", &base).commands_only);
+
+        assert!(modes.reset("main"));
+        assert!(!modes.reset("main"));
+        assert!(!modes.mode("main", &base).overridden);
+    }
+
+    #[test]
+    fn test_returning_to_config_clears_override() {
+        let modes = CommandModes::new();
+        let base = filters();
+
+        modes.set("main", &base, CommandModeRequest::default());
+        let mode = modes.set("main", &base, CommandModeRequest::default());
+        assert!(!mode.commands_only);
+        assert!(!mode.overridden);
+        assert_eq!(mode.to_string(), "all messages");
+    }
+}
@@ -0,0 +1,191 @@
+//! Control del overlay desde el chat con `!overlay <orden>`
+//!
+//! Los usuarios con al menos `commands.min_user_level` (el nivel que asigna el
+//! sistema de mapeo) pueden limpiar las ventanas, pausar y reanudar el
+//! renderizado o cambiar la duración de los mensajes sin salir del chat:
+//!
+//! ```text
+//! !overlay clear
+//! !overlay pause
+//! !overlay resume
+//! !overlay duration 15
+//! ```
+//!
+//! Los comandos reconocidos no se muestran. Los de usuarios sin permiso se
+//! muestran como un mensaje más, igual que `!pin`.
+
+use serde::{Deserialize, Serialize};
+
+use crate::connection::ChatMessage;
+use crate::mapping::UserLevel;
+use crate::pin::user_level_rank;
+
+/// Duración máxima que se puede fijar con `duration`
+pub const MAX_COMMAND_DURATION_SECS: u64 = 600;
+
+/// `commands`
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct ChatCommandsConfig {
+    /// Desactivado por defecto
+    pub enabled: bool,
+    /// Prefijos que abren un comando (sin distinguir mayúsculas)
+    pub prefixes: Vec<String>,
+    /// Nivel mínimo para usar los comandos
+    pub min_user_level: UserLevel,
+}
+
+impl Default for ChatCommandsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            prefixes: vec!["!overlay".to_string()],
+            min_user_level: UserLevel::Moderator,
+        }
+    }
+}
+
+/// Orden reconocida en el chat
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OverlayCommand {
+    /// Cierra todas las ventanas visibles, incluida la fijada
+    Clear,
+    Pause,
+    Resume,
+    /// Nueva duración en segundos de las ventanas de mensajes
+    Duration(u64),
+}
+
+/// Qué hacer con un mensaje tras pasar por el router
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CommandAction {
+    /// No es un comando (o el autor no tiene permiso): se muestra
+    Display,
+    Run(OverlayCommand),
+    /// Comando mal formado: no se muestra ni se ejecuta
+    Invalid(String),
+}
+
+/// Reconoce `<prefijo> <orden> [argumentos]`; `None` si no empieza por un prefijo
+pub fn parse_command(content: &str, prefixes: &[String]) -> Option<Result<OverlayCommand, String>> {
+    let mut words = content.split_whitespace();
+    let first = words.next()?;
+    if !prefixes
+        .iter()
+        .any(|prefix| !prefix.is_empty() && first.eq_ignore_ascii_case(prefix))
+    {
+        return None;
+    }
+
+    let command = match words.next().map(str::to_lowercase).as_deref() {
+        Some("clear") => Ok(OverlayCommand::Clear),
+        Some("pause") => Ok(OverlayCommand::Pause),
+        Some("resume") => Ok(OverlayCommand::Resume),
+        Some("duration") => match words.next().map(str::parse::<u64>) {
+            Some(Ok(secs)) if (1..=MAX_COMMAND_DURATION_SECS).contains(&secs) => {
+                Ok(OverlayCommand::Duration(secs))
+            }
+            _ => Err(format!(
+                "duration expects seconds between 1 and {}",
+                MAX_COMMAND_DURATION_SECS
+            )),
+        },
+        Some(other) => Err(format!("unknown command '{}'", other)),
+        None => Err("missing command (clear, pause, resume, duration)".to_string()),
+    };
+    Some(command)
+}
+
+/// Decide si un mensaje ya procesado es un comando que hay que ejecutar
+pub fn route(message: &ChatMessage, config: &ChatCommandsConfig) -> CommandAction {
+    if !config.enabled {
+        return CommandAction::Display;
+    }
+    let Some(command) = parse_command(&message.content, &config.prefixes) else {
+        return CommandAction::Display;
+    };
+    if user_level_rank(&UserLevel::of(message)) < user_level_rank(&config.min_user_level) {
+        return CommandAction::Display;
+    }
+    match command {
+        Ok(command) => CommandAction::Run(command),
+        Err(error) => CommandAction::Invalid(error),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_overlay_commands() {
+        let prefixes = vec!["!overlay".to_string(), "!ov".to_string()];
+        assert_eq!(
+            parse_command("!overlay clear", &prefixes),
+            Some(Ok(OverlayCommand::Clear))
+        );
+        assert_eq!(
+            parse_command("!OV Pause now", &prefixes),
+            Some(Ok(OverlayCommand::Pause))
+        );
+        assert_eq!(
+            parse_command("!overlay duration 15", &prefixes),
+            Some(Ok(OverlayCommand::Duration(15)))
+        );
+        for invalid in [
+            "!overlay duration 0",
+            "!overlay duration abc",
+            "!overlay dance",
+            "!overlay",
+        ] {
+            assert!(matches!(parse_command(invalid, &prefixes), Some(Err(_))));
+        }
+        assert_eq!(parse_command("!overlays clear", &prefixes), None);
+        assert_eq!(parse_command("hola !overlay clear", &prefixes), None);
+    }
+
+    #[test]
+    fn test_route_requires_mapped_user_level() {
+        let config = ChatCommandsConfig {
+            enabled: true,
+            ..ChatCommandsConfig::default()
+        };
+        assert_eq!(
+            route(
+                &ChatMessage::test("viewer", "!overlay resume").with_level(UserLevel::Moderator),
+                &config
+            ),
+            CommandAction::Run(OverlayCommand::Resume)
+        );
+        assert_eq!(
+            route(
+                &ChatMessage::test("viewer", "!overlay resume").with_level(UserLevel::Vip),
+                &config
+            ),
+            CommandAction::Display
+        );
+        assert!(matches!(
+            route(
+                &ChatMessage::test("viewer", "!overlay nope").with_level(UserLevel::Broadcaster),
+                &config
+            ),
+            CommandAction::Invalid(_)
+        ));
+        assert_eq!(
+            route(
+                &ChatMessage::test("viewer", "gg").with_level(UserLevel::Moderator),
+                &config
+            ),
+            CommandAction::Display
+        );
+
+        let disabled = ChatCommandsConfig::default();
+        assert_eq!(
+            route(
+                &ChatMessage::test("viewer", "!overlay clear").with_level(UserLevel::Broadcaster),
+                &disabled
+            ),
+            CommandAction::Display
+        );
+    }
+}
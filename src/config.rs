@@ -1,9 +1,34 @@
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::time::Duration;
 
+use crate::aggregate::AggregateConfig;
+use crate::commands::ChatCommandsConfig;
+use crate::dedupe::DedupeConfig;
+use crate::demo::DemoConfig;
+use crate::highlight::{HighlightConfig, HighlightKind};
+use crate::hype::HypeConfig;
+use crate::ipc::IpcConfig;
+use crate::pause::PauseConfig;
+use crate::stats::StatsConfig;
+use crate::links::{LinkConfig, MIN_LINK_LENGTH};
+use crate::obs::ObsConfig;
+use crate::mapping::rules::MappingRulesConfig;
+use crate::mapping::UserLevel;
+use crate::placement::{PlacementAnchor, PlacementMode};
+use crate::presets::DisplayArea;
+use crate::profanity::ProfanityConfig;
+use crate::sound::SoundConfig;
+use crate::storage::StorageConfig;
+use crate::theme::events::{EventCard, EventTemplate, OverlayEventKind};
+use crate::theme::levels::UserLevelStyles;
+use crate::theme::named::NamedTheme;
+use crate::theme::template::WindowTemplate;
+use crate::theme::{Rgb, ThemeName, ThemePalette};
+use crate::translation::TranslationConfig;
+
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct Config {
     pub platforms: HashMap<String, PlatformConfig>,
@@ -12,6 +37,64 @@ pub struct Config {
     pub display: DisplayConfig,
     pub emotes: EmoteConfig,
     pub logging: LoggingConfig,
+    #[serde(default)]
+    pub load: LoadConfig,
+    #[serde(default)]
+    pub capture: CaptureConfig,
+    #[serde(default)]
+    pub merge: MergeConfig,
+    #[serde(default)]
+    pub idle: IdleConfig,
+    #[serde(default)]
+    pub pin: PinConfig,
+    #[serde(default)]
+    pub commands: ChatCommandsConfig,
+    #[serde(default)]
+    pub timestamps: TimestampConfig,
+    #[serde(default)]
+    pub control: ControlConfig,
+    #[serde(default)]
+    pub error_budget: ErrorBudgetConfig,
+    #[serde(default)]
+    pub system_users: SystemUsersConfig,
+    #[serde(default)]
+    pub theme_schedule: ThemeScheduleConfig,
+    #[serde(default)]
+    pub moderation_lane: ModerationLaneConfig,
+    #[serde(default)]
+    pub bridge: BridgeConfig,
+    #[serde(default)]
+    pub history: HistoryConfig,
+    #[serde(default)]
+    pub profanity: ProfanityConfig,
+    #[serde(default)]
+    pub dedupe: DedupeConfig,
+    #[serde(default)]
+    pub hype: HypeConfig,
+    #[serde(default)]
+    pub aggregate: AggregateConfig,
+    #[serde(default)]
+    pub pause: PauseConfig,
+    #[serde(default)]
+    pub highlight: HighlightConfig,
+    #[serde(default)]
+    pub ipc: IpcConfig,
+    #[serde(default)]
+    pub obs: ObsConfig,
+    #[serde(default)]
+    pub sound: SoundConfig,
+    #[serde(default)]
+    pub demo: DemoConfig,
+    #[serde(default)]
+    pub storage: StorageConfig,
+    #[serde(default)]
+    pub stats: StatsConfig,
+    #[serde(default)]
+    pub mapping: MappingRulesConfig,
+    #[serde(default)]
+    pub translation: TranslationConfig,
+    #[serde(default)]
+    pub pipeline: PipelineConfig,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -41,6 +124,9 @@ pub enum PlatformType {
     Kick,
     Trovo,
     Facebook,
+    Custom,
+    /// Donaciones de StreamElements o Streamlabs
+    Donations,
 }
 
 impl std::fmt::Display for PlatformType {
@@ -51,6 +137,8 @@ impl std::fmt::Display for PlatformType {
             PlatformType::Kick => write!(f, "kick"),
             PlatformType::Trovo => write!(f, "trovo"),
             PlatformType::Facebook => write!(f, "facebook"),
+            PlatformType::Custom => write!(f, "custom"),
+            PlatformType::Donations => write!(f, "donations"),
         }
     }
 }
@@ -113,20 +201,65 @@ pub struct ConnectionConfig {
     pub enabled: bool,
     pub filters: MessageFilters,
     pub display_name: Option<String>,
+    /// Desplazamiento de visualización en ms para sincronizar co-streams con
+    /// distinta latencia (positivo retrasa, negativo adelanta)
+    #[serde(default)]
+    pub display_offset_ms: i64,
+    /// Acortado y ocultación de enlaces en los mensajes
+    #[serde(default)]
+    pub links: LinkConfig,
+    /// Silencia los sonidos de alerta de esta conexión
+    #[serde(default)]
+    pub mute_sounds: bool,
 }
 
+/// Máximo desplazamiento de visualización por conexión
+pub const MAX_DISPLAY_OFFSET_MS: i64 = 60_000;
+
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct MessageFilters {
     pub min_message_length: Option<usize>,
     pub max_message_length: Option<usize>,
     pub blocked_users: Vec<String>,
     pub allowed_users: Vec<String>,
+    /// Con `allowed_users` no vacío, solo se muestran esos usuarios; con
+    /// `false`, `allowed_users` se saltan el resto de filtros de la conexión
+    #[serde(default = "default_allowed_users_only")]
+    pub allowed_users_only: bool,
+    /// Ignora los bots conocidos (Nightbot, StreamElements...) y `bot_users`
+    #[serde(default)]
+    pub ignore_bots: bool,
+    /// Cuentas de bot propias del canal, además de las conocidas
+    #[serde(default)]
+    pub bot_users: Vec<String>,
     pub blocked_words: Vec<String>,
+    /// Oculta los mensajes que solo contienen enlaces
+    #[serde(default)]
+    pub hide_link_only: bool,
     pub commands_only: bool,
+    /// Prefijos aceptados con `commands_only` (p. ej. `!question`); vacío = `!` y `/`
+    #[serde(default)]
+    pub command_prefixes: Vec<String>,
     pub subscribers_only: bool,
     pub vip_only: bool,
 }
 
+fn default_allowed_users_only() -> bool {
+    true
+}
+
+impl MessageFilters {
+    /// Si `content` empieza por alguno de los prefijos de comando
+    pub fn is_command(&self, content: &str) -> bool {
+        if self.command_prefixes.is_empty() {
+            return content.starts_with('!') || content.starts_with('/');
+        }
+        self.command_prefixes
+            .iter()
+            .any(|prefix| content.starts_with(prefix.as_str()))
+    }
+}
+
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct WindowConfig {
     pub message_duration_seconds: u64,
@@ -135,6 +268,48 @@ pub struct WindowConfig {
     pub animation_enabled: bool,
     pub fade_in_duration_ms: u64,
     pub fade_out_duration_ms: u64,
+    /// Ventanas ocultas que se guardan para reutilizarlas; 0 las destruye siempre
+    #[serde(default = "default_window_pool_size")]
+    pub pool_size: usize,
+}
+
+fn default_window_pool_size() -> usize {
+    8
+}
+
+impl WindowConfig {
+    /// Fundidos de entrada y salida; nulos sin `animation_enabled`
+    pub fn fade_timing(&self) -> FadeTiming {
+        if !self.animation_enabled {
+            return FadeTiming::default();
+        }
+        FadeTiming {
+            fade_in: Duration::from_millis(self.fade_in_duration_ms),
+            fade_out: Duration::from_millis(self.fade_out_duration_ms),
+        }
+    }
+}
+
+/// Duración de los fundidos de una ventana
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FadeTiming {
+    pub fade_in: Duration,
+    pub fade_out: Duration,
+}
+
+impl FadeTiming {
+    /// Fracción de la opacidad final de una ventana visible desde hace
+    /// `visible_for` y que se cierra dentro de `remaining`
+    pub fn factor(&self, visible_for: Duration, remaining: Duration) -> f64 {
+        let ramp = |elapsed: Duration, length: Duration| {
+            if length.is_zero() {
+                1.0
+            } else {
+                (elapsed.as_secs_f64() / length.as_secs_f64()).min(1.0)
+            }
+        };
+        ramp(visible_for, self.fade_in).min(ramp(remaining, self.fade_out))
+    }
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -149,6 +324,208 @@ pub struct DisplayConfig {
     pub username_color: String,
     pub border_radius: u32,
     pub opacity: f32,
+    /// Ajustes de opacidad/escala/duración por tipo de mensaje mapeado
+    #[serde(default)]
+    pub message_type_overrides: HashMap<crate::mapping::MappedMessageType, MessageTypeOverride>,
+    /// Tema integrado (`default`, `high_contrast` o `custom`)
+    #[serde(default)]
+    pub theme: ThemeName,
+    /// Tamaño mínimo efectivo de fuente (tras escala y DPI); 0 desactiva el límite
+    #[serde(default)]
+    pub min_font_size: u32,
+    /// Temas con nombre propios, además de los integrados (ver `theme::named`)
+    #[serde(default)]
+    pub themes: BTreeMap<String, NamedTheme>,
+    /// Tema con nombre aplicado sobre estos valores al arrancar
+    #[serde(default)]
+    pub active_theme: Option<String>,
+    /// Colores, duración e icono por evento; se combinan con las plantillas integradas
+    #[serde(default)]
+    pub event_templates: HashMap<OverlayEventKind, EventTemplate>,
+    /// Zona del monitor donde aparecen las ventanas
+    #[serde(default)]
+    pub area: DisplayArea,
+    /// Muestra solo eventos (subs, raids, cheers) y oculta el chat normal
+    #[serde(default)]
+    pub alerts_only: bool,
+    /// Colores del nombre y del borde según el nivel del usuario en el chat
+    #[serde(default)]
+    pub user_levels: UserLevelStyles,
+    /// Foto de perfil del usuario, en círculo, junto al nombre
+    #[serde(default)]
+    pub show_avatars: bool,
+    /// Icono de la plataforma del mensaje junto al nombre
+    #[serde(default)]
+    pub show_platform_icons: bool,
+    /// Estrategia de colocación de las ventanas (ver `placement`)
+    #[serde(default)]
+    pub placement_mode: PlacementMode,
+    /// Esquina desde la que se apilan las ventanas con `placement_mode: anchor`
+    #[serde(default)]
+    pub placement_anchor: PlacementAnchor,
+    /// Separación vertical entre ventanas apiladas, en píxeles
+    #[serde(default = "default_placement_spacing")]
+    pub placement_spacing: i32,
+    /// Contenido de las ventanas (`{username}`, `{content}`...; ver `theme::template`)
+    #[serde(default)]
+    pub template: WindowTemplate,
+    /// Compone cada mensaje en una sola imagen fuera del hilo de la interfaz
+    /// (ver `emotes::renderer::compose_message`); los emotes animados se ven estáticos
+    #[serde(default)]
+    pub precomposed: bool,
+    /// Los clics pasan a la ventana de debajo; con `false` las ventanas se
+    /// pueden arrastrar y su posición se recuerda por conexión (ver `layout`)
+    #[serde(default = "default_click_through")]
+    pub click_through: bool,
+    /// Fichero donde se guardan los desplazamientos de las ventanas arrastradas
+    #[serde(default = "default_layout_file")]
+    pub layout_file: String,
+}
+
+fn default_placement_spacing() -> i32 {
+    80
+}
+
+fn default_click_through() -> bool {
+    true
+}
+
+fn default_layout_file() -> String {
+    "layout.json".to_string()
+}
+
+impl DisplayConfig {
+    /// Mínimo de fuente exigido por la configuración o por el tema activo
+    pub fn required_min_font_size(&self) -> u32 {
+        self.min_font_size.max(self.theme.min_font_size())
+    }
+
+    /// Paleta efectiva: la del tema, o la de los colores configurados con `custom`
+    pub fn palette(&self) -> Option<ThemePalette> {
+        match self.theme {
+            ThemeName::Custom => Some(ThemePalette::from_colors(
+                Rgb::parse_hex(&self.background_color)?,
+                Rgb::parse_hex(&self.text_color)?,
+                Rgb::parse_hex(&self.username_color)?,
+            )),
+            theme => theme.palette(),
+        }
+    }
+
+    /// Hoja de estilos GTK del tema: la paleta y, con un tema con nombre
+    /// activo, también la fuente y el radio del borde
+    pub fn theme_css(&self, font_size: u32) -> String {
+        let mut css = self
+            .palette()
+            .map(|palette| palette.to_css(font_size))
+            .unwrap_or_default();
+        if self.active_theme.is_some() {
+            css.push_str(&format!(
+                "\nwindow {{\n  border-radius: {}px;\n}}\n\n\
+                 label, textview text {{\n  font-family: \"{}\";\n}}\n",
+                self.border_radius,
+                self.font_family.replace(['"', '\\'], ""),
+            ));
+        }
+        css
+    }
+
+    /// Plantilla efectiva de un evento: la configurada sobre la integrada
+    pub fn event_template(&self, event: OverlayEventKind) -> EventTemplate {
+        let builtin = event.builtin_template();
+        match self.event_templates.get(&event) {
+            Some(configured) => configured.clone().or(builtin),
+            None => builtin,
+        }
+    }
+
+    /// Paleta de los colores configurados, con los por defecto si no son válidos
+    pub fn configured_palette(&self) -> ThemePalette {
+        let color = |value: &str, fallback: Rgb| Rgb::parse_hex(value).unwrap_or(fallback);
+        ThemePalette::from_colors(
+            color(&self.background_color, Rgb(30, 30, 30)),
+            color(&self.text_color, Rgb(255, 255, 255)),
+            color(&self.username_color, Rgb(0, 255, 0)),
+        )
+    }
+}
+
+/// Sobrescrituras de estilo para un tipo de mensaje concreto
+#[derive(Debug, Deserialize, Serialize, Clone, Default, PartialEq)]
+#[serde(default)]
+pub struct MessageTypeOverride {
+    pub opacity: Option<f32>,
+    pub scale: Option<f32>,
+    pub duration_seconds: Option<u64>,
+}
+
+/// Estilo final de una ventana tras aplicar las sobrescrituras por tipo
+#[derive(Debug, Clone, PartialEq)]
+pub struct WindowStyle {
+    pub opacity: f32,
+    pub scale: f32,
+    pub duration: Duration,
+    /// Tamaño de fuente efectivo, ya limitado por `min_font_size`
+    pub font_size: u32,
+    /// Paleta del tema; `None` usa los colores por defecto del backend
+    pub palette: Option<ThemePalette>,
+    /// Permite emotes animados; se desactiva en modo degradado
+    pub animated: bool,
+    pub event: OverlayEventKind,
+    /// Icono de la plantilla del evento, antepuesto al nombre de usuario
+    pub icon: Option<String>,
+    /// Cabecera destacada (meses de un resub) entre el usuario y el mensaje
+    pub card: Option<EventCard>,
+    /// Nivel cuyo estilo se aplicó a la paleta (`display.user_levels`)
+    pub user_level: Option<UserLevel>,
+    /// Motivo por el que se resaltó el mensaje (`highlight`)
+    pub highlight: Option<HighlightKind>,
+    /// Avatar del usuario (PNG recortado en círculo) con `display.show_avatars`
+    pub avatar: Option<Vec<u8>>,
+    /// Icono integrado de la plataforma con `display.show_platform_icons`
+    pub platform_icon: Option<&'static crate::assets::Asset>,
+    /// Fundidos de `window`; la ventana aparece transparente si hay fundido de entrada
+    pub fade: FadeTiming,
+    /// Mensaje ya compuesto con `display.precomposed`: el backend solo lo copia
+    pub bitmap: Option<image::RgbaImage>,
+    /// Con `display.click_through` desactivado la ventana se puede arrastrar
+    pub draggable: bool,
+    /// Conexión del mensaje, cuyo desplazamiento actualiza el arrastre
+    pub connection_id: Option<String>,
+}
+
+impl WindowStyle {
+    /// Nombre de usuario tal como se muestra, con el icono del evento si lo hay
+    pub fn username_label(&self, username: &str) -> String {
+        match &self.icon {
+            Some(icon) => format!("{} {}", icon, username),
+            None => username.to_string(),
+        }
+    }
+}
+
+impl Default for WindowStyle {
+    fn default() -> Self {
+        Self {
+            opacity: 0.9,
+            scale: 1.0,
+            duration: Duration::from_secs(10),
+            font_size: 14,
+            palette: None,
+            animated: true,
+            event: OverlayEventKind::Chat,
+            icon: None,
+            card: None,
+            user_level: None,
+            highlight: None,
+            avatar: None,
+            platform_icon: None,
+            fade: FadeTiming::default(),
+            bitmap: None,
+            draggable: false,
+            connection_id: None,
+        }
+    }
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -164,6 +541,39 @@ pub struct EmoteConfig {
     pub max_emotes_per_message: usize,
     pub cache_enabled: bool,
     pub cache_ttl_hours: u64,
+    #[serde(default)]
+    pub http: EmoteHttpConfig,
+    #[serde(default)]
+    pub blocklist: EmoteBlocklistConfig,
+    /// Proveedores HTTP genéricos (servicios propios o poco comunes)
+    #[serde(default)]
+    pub custom_providers: Vec<CustomEmoteProviderConfig>,
+    /// Sigue los cambios del emote set de 7TV de cada canal por su EventAPI
+    #[serde(default = "default_seventv_live_updates")]
+    pub seventv_live_updates: bool,
+    #[serde(default)]
+    pub prefetch: EmotePrefetchConfig,
+    /// Minutos que se reutilizan los sets de emotes de canal de terceros
+    #[serde(default = "default_channel_set_ttl_minutes")]
+    pub channel_set_ttl_minutes: u64,
+    #[serde(default)]
+    pub quality: EmoteQualityConfig,
+    #[serde(default)]
+    pub usage: EmoteUsageConfig,
+}
+
+fn default_seventv_live_updates() -> bool {
+    true
+}
+
+fn default_channel_set_ttl_minutes() -> u64 {
+    30
+}
+
+impl EmoteConfig {
+    pub fn channel_set_ttl(&self) -> Duration {
+        Duration::from_secs(self.channel_set_ttl_minutes * 60)
+    }
 }
 
 impl Default for EmoteConfig {
@@ -180,6 +590,210 @@ impl Default for EmoteConfig {
             max_emotes_per_message: 50,
             cache_enabled: true,
             cache_ttl_hours: 24,
+            http: EmoteHttpConfig::default(),
+            blocklist: EmoteBlocklistConfig::default(),
+            custom_providers: Vec::new(),
+            seventv_live_updates: default_seventv_live_updates(),
+            prefetch: EmotePrefetchConfig::default(),
+            channel_set_ttl_minutes: default_channel_set_ttl_minutes(),
+            quality: EmoteQualityConfig::default(),
+            usage: EmoteUsageConfig::default(),
+        }
+    }
+}
+
+/// Emotes bloqueados (p.ej. emotes NSFW de 7TV)
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+#[serde(default)]
+pub struct EmoteBlocklistConfig {
+    /// Ids de emote bloqueados
+    pub ids: Vec<String>,
+    /// Nombres de emote bloqueados (distinguen mayúsculas)
+    pub names: Vec<String>,
+    /// Texto que sustituye al emote; `None` conserva el nombre como texto
+    pub replacement: Option<String>,
+    /// Muestra al salir cuántas veces se intentó usar cada emote bloqueado
+    pub report_attempts: bool,
+}
+
+/// Ajustes del cliente HTTP compartido para APIs y CDNs de emotes
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(default)]
+pub struct EmoteHttpConfig {
+    pub http2: bool,
+    pub pool_idle_timeout_secs: u64,
+    pub pool_max_idle_per_host: usize,
+    pub max_concurrent_per_host: usize,
+    pub request_timeout_secs: u64,
+    /// Peticiones por segundo a cada host (token bucket); 0 no limita
+    pub requests_per_second_per_host: f64,
+    /// Peticiones seguidas que admite un host antes de esperar
+    pub burst_per_host: u32,
+    /// Respuestas de las APIs guardadas para revalidar con ETag/Last-Modified
+    pub response_cache_entries: usize,
+}
+
+impl Default for EmoteHttpConfig {
+    fn default() -> Self {
+        Self {
+            http2: true,
+            pool_idle_timeout_secs: 90,
+            pool_max_idle_per_host: 8,
+            max_concurrent_per_host: 6,
+            request_timeout_secs: 10,
+            requests_per_second_per_host: 10.0,
+            burst_per_host: 10,
+            response_cache_entries: 256,
+        }
+    }
+}
+
+/// Descarga de imágenes de emotes en segundo plano (`emotes::prefetch`)
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct EmotePrefetchConfig {
+    /// Descargas en cola como máximo; las que no caben se descartan
+    pub queue_size: usize,
+    /// Descargas simultáneas (además del límite por host de `http`)
+    pub workers: usize,
+    /// Emotes de canal que se descargan al conectar; 0 lo desactiva
+    pub top_emotes: usize,
+}
+
+impl Default for EmotePrefetchConfig {
+    fn default() -> Self {
+        Self {
+            queue_size: 256,
+            workers: 4,
+            top_emotes: 50,
+        }
+    }
+}
+
+/// Resolución de las imágenes que se piden a los CDN de emotes
+///
+/// Twitch no pasa de 3x: con `4x` se pide su versión 3.0.
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EmoteScale {
+    #[default]
+    #[serde(rename = "1x")]
+    X1,
+    #[serde(rename = "2x")]
+    X2,
+    #[serde(rename = "4x")]
+    X4,
+}
+
+/// Formato de imagen preferido cuando el CDN lo ofrece
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum EmoteImageFormat {
+    /// El que sirve el CDN sin pedir nada (PNG/GIF, WebP en 7TV)
+    #[default]
+    Default,
+    Webp,
+}
+
+/// Preferencias de un proveedor concreto; lo que falte sale de las generales
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(default)]
+pub struct EmoteQualityOverride {
+    pub scale: Option<EmoteScale>,
+    pub format: Option<EmoteImageFormat>,
+}
+
+/// Tamaño y formato de las imágenes de emotes (`emotes::quality`)
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Default)]
+#[serde(default)]
+pub struct EmoteQualityConfig {
+    /// Sin indicar, cada CDN en el tamaño de siempre: 1x en Twitch y el mayor
+    /// en BTTV, FFZ y 7TV
+    pub scale: Option<EmoteScale>,
+    pub format: EmoteImageFormat,
+    /// Por proveedor: `twitch`, `bttv`, `ffz` o `7tv`
+    pub providers: HashMap<String, EmoteQualityOverride>,
+}
+
+/// Resumen periódico del uso de emotes por canal (`emotes::usage`)
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct EmoteUsageConfig {
+    /// Minutos entre resúmenes en el log; 0 los desactiva
+    pub report_interval_minutes: u64,
+    /// Emotes que se listan por canal
+    pub report_top: usize,
+}
+
+impl Default for EmoteUsageConfig {
+    fn default() -> Self {
+        Self {
+            report_interval_minutes: 30,
+            report_top: 10,
+        }
+    }
+}
+
+impl EmoteUsageConfig {
+    pub fn report_interval(&self) -> Option<Duration> {
+        (self.report_interval_minutes > 0)
+            .then(|| Duration::from_secs(self.report_interval_minutes * 60))
+    }
+}
+
+/// Proveedor de `emotes.custom_providers`: una API JSON cualquiera
+///
+/// En las URLs `{platform}` y `{channel}` se sustituyen por los de la
+/// conexión, y en `image_url` `{id}` y `{name}` por los del emote. Las rutas
+/// van separadas por puntos (`data.emotes`, `images.0.url`).
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct CustomEmoteProviderConfig {
+    pub name: String,
+    pub enabled: bool,
+    /// URL de los emotes de un canal; sin ella el proveedor solo tiene globales
+    pub channel_url: Option<String>,
+    pub global_url: Option<String>,
+    /// Plataformas cuyos canales se consultan; vacía para todas
+    pub platforms: Vec<String>,
+    /// Ruta hasta la lista de emotes en la respuesta; vacía si es la raíz
+    pub list_path: String,
+    pub fields: EmoteFieldMapping,
+    /// Plantilla de la imagen cuando la respuesta no trae la URL
+    pub image_url: Option<String>,
+}
+
+impl Default for CustomEmoteProviderConfig {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            enabled: true,
+            channel_url: None,
+            global_url: None,
+            platforms: Vec::new(),
+            list_path: String::new(),
+            fields: EmoteFieldMapping::default(),
+            image_url: None,
+        }
+    }
+}
+
+/// Rutas de cada campo dentro de un emote de la respuesta
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct EmoteFieldMapping {
+    pub id: String,
+    pub name: String,
+    pub url: String,
+    pub animated: String,
+}
+
+impl Default for EmoteFieldMapping {
+    fn default() -> Self {
+        Self {
+            id: "id".to_string(),
+            name: "name".to_string(),
+            url: "url".to_string(),
+            animated: "animated".to_string(),
         }
     }
 }
@@ -193,9 +807,390 @@ pub enum EmoteSize {
     ExtraLarge,
 }
 
-impl Default for EmoteSize {
+impl Default for EmoteSize {
+    fn default() -> Self {
+        EmoteSize::Medium
+    }
+}
+
+/// Umbrales del controlador de carga (degradación automática)
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(default)]
+pub struct LoadConfig {
+    pub enabled: bool,
+    pub max_messages_per_second: f64,
+    pub max_cpu_percent: f32,
+    /// En modo degradado se muestra 1 de cada `sample_rate` mensajes
+    pub sample_rate: u32,
+    /// Fracción de los umbrales por debajo de la cual se considera que la carga bajó
+    pub recovery_ratio: f64,
+    /// Segundos que la carga debe mantenerse baja antes de restaurar
+    pub recovery_delay_secs: u64,
+    /// Máximo de ventanas simultáneas en modo feed
+    pub feed_max_windows: usize,
+}
+
+impl Default for LoadConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_messages_per_second: 20.0,
+            max_cpu_percent: 80.0,
+            sample_rate: 4,
+            recovery_ratio: 0.5,
+            recovery_delay_secs: 10,
+            feed_max_windows: 8,
+        }
+    }
+}
+
+/// Pausa del renderizado cuando el equipo está inactivo (streamer AFK)
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(default)]
+pub struct IdleConfig {
+    pub enabled: bool,
+    /// Minutos sin entrada de teclado/ratón antes de pausar
+    pub idle_minutes: u64,
+    /// Muestra una ventana con los mensajes perdidos al volver
+    pub show_summary: bool,
+}
+
+impl Default for IdleConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            idle_minutes: 5,
+            show_summary: true,
+        }
+    }
+}
+
+/// Cambio de tema según la hora del día (p. ej. más oscuro por la noche)
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(default)]
+pub struct ThemeScheduleConfig {
+    pub enabled: bool,
+    /// Cada cuántos segundos se comprueba la hora
+    pub check_interval_secs: u64,
+    /// Franjas horarias; gana la primera que contiene la hora actual y fuera
+    /// de todas se usa la apariencia de `display`
+    pub entries: Vec<ThemeScheduleEntry>,
+}
+
+impl Default for ThemeScheduleConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            check_interval_secs: 30,
+            entries: Vec::new(),
+        }
+    }
+}
+
+/// Franja horaria `start`–`end` (hora local, `HH:MM`) con su tema; si `end`
+/// es anterior a `start` la franja cruza la medianoche
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct ThemeScheduleEntry {
+    pub start: String,
+    pub end: String,
+    pub theme: ThemeName,
+    /// Colores que sustituyen a los de `display` durante la franja (útil con `custom`)
+    #[serde(default)]
+    pub background_color: Option<String>,
+    #[serde(default)]
+    pub text_color: Option<String>,
+    #[serde(default)]
+    pub username_color: Option<String>,
+}
+
+/// Puente WebSocket con los mensajes procesados para fuentes de navegador
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(default)]
+pub struct BridgeConfig {
+    pub enabled: bool,
+    /// Dirección de escucha; por defecto solo local
+    pub bind: String,
+    /// Mensajes pendientes por cliente antes de descartar los más antiguos
+    pub buffer_size: usize,
+    /// Bytes por segundo que puede recibir cada cliente; 0 = sin límite
+    pub bytes_per_second: u64,
+}
+
+impl Default for BridgeConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            bind: "127.0.0.1:7879".to_string(),
+            buffer_size: 256,
+            bytes_per_second: 0,
+        }
+    }
+}
+
+/// Registro de mensajes descartados por filtros o errores de mapeo
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct ModerationLaneConfig {
+    pub enabled: bool,
+    /// Escribe cada descarte en el log
+    pub log: bool,
+    /// Descartes recientes que devuelve `GET /moderation`
+    pub recent_limit: usize,
+    /// Muestra periódicamente una ventana pequeña con el contador
+    pub show_counter: bool,
+    pub counter_interval_secs: u64,
+}
+
+impl Default for ModerationLaneConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            log: true,
+            recent_limit: 50,
+            show_counter: false,
+            counter_interval_secs: 60,
+        }
+    }
+}
+
+/// Historial en memoria de la sesión para buscar mensajes recientes
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct HistoryConfig {
+    pub enabled: bool,
+    /// Mensajes guardados antes de descartar los más antiguos
+    pub size: usize,
+    /// Últimos mensajes que se guardan en `file` y se vuelven a mostrar al
+    /// arrancar; 0 desactiva el guardado
+    pub replay_on_startup: usize,
+    pub file: String,
+}
+
+impl Default for HistoryConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            size: 500,
+            replay_on_startup: 0,
+            file: "history.json".to_string(),
+        }
+    }
+}
+
+/// Pool de tareas que filtra, enriquece y mapea los mensajes antes de mostrarlos
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct PipelineConfig {
+    /// Mensajes que se procesan a la vez; se muestran igualmente en el orden
+    /// en que llegaron
+    pub workers: usize,
+}
+
+impl Default for PipelineConfig {
+    fn default() -> Self {
+        Self { workers: 4 }
+    }
+}
+
+/// API HTTP de control (Stream Deck, scripts de OBS...)
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(default)]
+pub struct ControlConfig {
+    pub enabled: bool,
+    /// Dirección de escucha; por defecto solo local
+    pub bind: String,
+    /// Si se define, se exige `Authorization: Bearer <token>`
+    pub token: Option<String>,
+}
+
+impl Default for ControlConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            bind: "127.0.0.1:7878".to_string(),
+            token: None,
+        }
+    }
+}
+
+/// Alertas cuando una plataforma o proveedor falla repetidamente
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(default)]
+pub struct ErrorBudgetConfig {
+    pub enabled: bool,
+    /// Fallos tolerados por origen dentro de la ventana
+    pub max_failures: usize,
+    pub window_secs: u64,
+    /// Muestra una notificación en el overlay además del log
+    pub show_notification: bool,
+}
+
+impl Default for ErrorBudgetConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_failures: 10,
+            window_secs: 300,
+            show_notification: true,
+        }
+    }
+}
+
+/// Usuario virtual que firma los mensajes generados por el overlay
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct SystemUser {
+    pub name: String,
+    /// Color del nombre en formato `#rrggbb`
+    pub color: Option<String>,
+    /// Insignia integrada (`broadcaster`, `moderator`, `vip`...) o ruta/URL a un icono
+    pub badge: Option<String>,
+}
+
+impl Default for SystemUser {
+    fn default() -> Self {
+        Self {
+            name: "Overlay".to_string(),
+            color: None,
+            badge: None,
+        }
+    }
+}
+
+impl SystemUser {
+    fn new(name: &str, color: &str, badge: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            color: Some(color.to_string()),
+            badge: Some(badge.to_string()),
+        }
+    }
+}
+
+/// Usuarios virtuales por tipo de mensaje interno
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct SystemUsersConfig {
+    /// Anuncios y resúmenes (p.ej. mensajes perdidos durante la inactividad)
+    pub announcements: SystemUser,
+    /// Mensajes programados
+    pub scheduler: SystemUser,
+    /// Alertas de salud y ventanas de prueba
+    pub diagnostics: SystemUser,
+}
+
+impl Default for SystemUsersConfig {
+    fn default() -> Self {
+        Self {
+            announcements: SystemUser::new("Overlay", "#ffd166", "broadcaster"),
+            scheduler: SystemUser::new("Scheduler", "#06d6a0", "vip"),
+            diagnostics: SystemUser::new("Diagnostics", "#ef476f", "moderator"),
+        }
+    }
+}
+
+/// Corrección del desfase de reloj de las plataformas y orden de visualización
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(default)]
+pub struct TimestampConfig {
+    /// Corrige los timestamps remotos a hora local
+    pub normalize: bool,
+    /// Muestras por conexión para estimar el desfase
+    pub offset_samples: usize,
+    /// Desfases mayores se consideran timestamps inválidos y se ignoran
+    pub max_offset_secs: u64,
+    /// Tiempo que se retiene cada mensaje para ordenarlo por timestamp corregido
+    pub reorder_window_ms: u64,
+}
+
+impl Default for TimestampConfig {
+    fn default() -> Self {
+        Self {
+            normalize: true,
+            offset_samples: 32,
+            max_offset_secs: 300,
+            reorder_window_ms: 200,
+        }
+    }
+}
+
+/// Mensajes fijados con `!pin` / `!unpin`
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(default)]
+pub struct PinConfig {
+    pub enabled: bool,
+    /// Nivel mínimo para usar los comandos
+    pub min_user_level: UserLevel,
+    /// Segundos que permanece fijado (0 = hasta `!unpin`)
+    pub timeout_secs: u64,
+    /// Mensajes recientes que se pueden fijar por respuesta o id
+    pub history_size: usize,
+    /// Refleja los mensajes fijados por la plataforma (Kick)
+    pub follow_platform_pins: bool,
+}
+
+impl Default for PinConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            min_user_level: UserLevel::Moderator,
+            timeout_secs: 600,
+            history_size: 200,
+            follow_platform_pins: true,
+        }
+    }
+}
+
+/// Modo watch-party: fusiona varias conexiones en un único flujo deduplicado
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(default)]
+pub struct MergeConfig {
+    pub enabled: bool,
+    /// Nombre del flujo fusionado, añadido a los metadatos de cada mensaje
+    pub name: String,
+    /// IDs de las conexiones que forman el grupo
+    pub connections: Vec<String>,
+    /// Tiempo que se retiene cada mensaje esperando duplicados de otros canales
+    pub hold_ms: u64,
+    /// Ventana durante la que un mensaje idéntico de otro canal se considera duplicado
+    pub dedupe_window_ms: u64,
+}
+
+impl Default for MergeConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            name: "watch_party".to_string(),
+            connections: Vec::new(),
+            hold_ms: 300,
+            dedupe_window_ms: 5000,
+        }
+    }
+}
+
+/// Captura de payloads crudos de las plataformas (depuración)
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(default)]
+pub struct CaptureConfig {
+    pub enabled: bool,
+    pub directory: PathBuf,
+    /// Tamaño máximo de cada fichero antes de rotar
+    pub max_file_bytes: u64,
+    /// Ficheros conservados por conexión, incluido el actual
+    pub max_files: usize,
+    /// Sustituye tokens y credenciales por `[REDACTED]`
+    pub redact: bool,
+}
+
+impl Default for CaptureConfig {
     fn default() -> Self {
-        EmoteSize::Medium
+        Self {
+            enabled: false,
+            directory: PathBuf::from("captures"),
+            max_file_bytes: 1024 * 1024,
+            max_files: 3,
+            redact: true,
+        }
     }
 }
 
@@ -219,21 +1214,107 @@ pub enum LogLevel {
     Error,
 }
 
+/// Fichero de configuración junto al ejecutable
+pub const DEFAULT_CONFIG_PATH: &str = "config.json";
+
 impl Config {
     pub fn load_from_file<P: AsRef<Path>>(path: P) -> Result<Self, ConfigError> {
         let content =
             fs::read_to_string(path).map_err(|e| ConfigError::FileError(e.to_string()))?;
 
+        Self::parse(&content)
+    }
+
+    /// Parsea y valida el contenido de un fichero de configuración
+    pub fn parse(content: &str) -> Result<Self, ConfigError> {
         let config: Config =
-            serde_json::from_str(&content).map_err(|e| ConfigError::ParseError(e.to_string()))?;
+            serde_json::from_str(content).map_err(|e| ConfigError::ParseError(e.to_string()))?;
 
         config.validate()?;
 
         Ok(config)
     }
 
+    /// Vigila el fichero y envía cada versión válida que se guarde
+    ///
+    /// Se vigila el directorio y no el fichero porque muchos editores guardan
+    /// escribiendo un fichero nuevo y renombrándolo. Los cambios inválidos se
+    /// ignoran y se mantiene la configuración anterior. La vigilancia dura
+    /// mientras viva el `ConfigWatcher` devuelto.
+    pub fn watch<P: AsRef<Path>>(
+        path: P,
+        sender: tokio::sync::mpsc::UnboundedSender<Config>,
+    ) -> Result<ConfigWatcher, ConfigError> {
+        use notify::Watcher;
+
+        let path = path.as_ref().to_path_buf();
+        let file_name = path
+            .file_name()
+            .map(|name| name.to_os_string())
+            .ok_or_else(|| ConfigError::FileError(format!("{:?} is not a file", path)))?;
+        let dir = match path.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+            _ => PathBuf::from("."),
+        };
+
+        let mut last_content = fs::read_to_string(&path).unwrap_or_default();
+        let watched = path.clone();
+        let mut watcher =
+            notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+                let Ok(event) = event else {
+                    return;
+                };
+                if !(event.kind.is_modify() || event.kind.is_create())
+                    || !event
+                        .paths
+                        .iter()
+                        .any(|p| p.file_name() == Some(file_name.as_os_str()))
+                {
+                    return;
+                }
+
+                // Un guardado genera varios eventos: solo cuenta si cambió el contenido
+                let Ok(content) = fs::read_to_string(&watched) else {
+                    return;
+                };
+                if content == last_content {
+                    return;
+                }
+                match Config::parse(&content) {
+                    Ok(config) => {
+                        last_content = content;
+                        let _ = sender.send(config);
+                    }
+                    Err(e) => {
+                        eprintln!("[CONFIG] ⚠️ Ignoring invalid change in {:?}: {}", watched, e)
+                    }
+                }
+            })
+            .map_err(|e| ConfigError::FileError(e.to_string()))?;
+
+        watcher
+            .watch(&dir, notify::RecursiveMode::NonRecursive)
+            .map_err(|e| ConfigError::FileError(e.to_string()))?;
+
+        Ok(ConfigWatcher { _watcher: watcher })
+    }
+
+    /// Conexiones habilitadas en el formato de `PlatformManager`
+    pub fn connection_infos(&self) -> Vec<crate::connection::ConnectionInfo> {
+        self.get_enabled_connections()
+            .into_iter()
+            .map(|connection| crate::connection::ConnectionInfo {
+                id: connection.id.clone(),
+                platform: connection.platform.clone(),
+                channel: connection.channel.clone(),
+                enabled: connection.enabled,
+                display_name: connection.display_name.clone(),
+            })
+            .collect()
+    }
+
     pub fn load_default() -> Result<Self, ConfigError> {
-        Self::load_with_fallback("config.json")
+        Self::load_with_fallback(DEFAULT_CONFIG_PATH)
     }
 
     pub fn load_with_fallback<P: AsRef<Path>>(external_path: P) -> Result<Self, ConfigError> {
@@ -277,6 +1358,115 @@ impl Config {
         Duration::from_secs(self.window.message_duration_seconds)
     }
 
+    /// Resuelve opacidad, escala y duración para un tipo de mensaje
+    pub fn window_style_for(&self, message_type: &crate::mapping::MappedMessageType) -> WindowStyle {
+        let overrides = self
+            .display
+            .message_type_overrides
+            .get(message_type)
+            .cloned()
+            .unwrap_or_default();
+
+        let scale = overrides.scale.unwrap_or(1.0);
+        let event = OverlayEventKind::of(message_type);
+        let template = self.display.event_template(event);
+        let palette = match self.display.palette() {
+            // Los temas de accesibilidad mantienen su paleta
+            Some(palette) if self.display.theme.palette().is_some() => Some(palette),
+            palette if template.has_colors() => Some(
+                template.apply_to(palette.unwrap_or_else(|| self.display.configured_palette())),
+            ),
+            palette => palette,
+        };
+        let opacity = if self.display.theme.palette().is_some() {
+            // Los temas de accesibilidad no admiten transparencia
+            1.0
+        } else {
+            overrides.opacity.unwrap_or(self.display.opacity).clamp(0.0, 1.0)
+        };
+
+        WindowStyle {
+            opacity,
+            scale,
+            duration: Duration::from_secs(
+                overrides
+                    .duration_seconds
+                    .or(template.duration_seconds)
+                    .unwrap_or(self.window.message_duration_seconds),
+            ),
+            font_size: self.effective_font_size(scale, 1.0),
+            palette,
+            animated: self.window.animation_enabled && self.emotes.emote_animation,
+            event,
+            icon: template.icon.filter(|icon| !icon.is_empty()),
+            card: None,
+            user_level: None,
+            highlight: None,
+            avatar: None,
+            platform_icon: None,
+            fade: self.window.fade_timing(),
+            bitmap: None,
+            draggable: !self.display.click_through,
+            connection_id: None,
+        }
+    }
+
+    /// `true` si la conexión del mensaje tiene `mute_sounds`
+    pub fn sounds_muted(&self, message: &crate::connection::ChatMessage) -> bool {
+        self.connections
+            .iter()
+            .find(|conn| conn.platform == message.platform && conn.channel == message.channel)
+            .is_some_and(|conn| conn.mute_sounds)
+    }
+
+    /// Estilo de la ventana de un mensaje concreto, con su tarjeta de evento
+    pub fn window_style_for_message(&self, message: &crate::connection::ChatMessage) -> WindowStyle {
+        let mut style = self.window_style_for(&crate::mapping::MappedMessageType::of(message));
+        style.connection_id = self
+            .connections
+            .iter()
+            .find(|conn| conn.platform == message.platform && conn.channel == message.channel)
+            .map(|conn| conn.id.clone());
+        if style.event == OverlayEventKind::Subscription {
+            style.card = EventCard::from_message(message);
+        }
+        if self.display.show_platform_icons {
+            style.platform_icon = Some(crate::assets::platform_icon(&message.platform));
+        }
+
+        // Solo el chat: los eventos conservan su plantilla y los temas de
+        // accesibilidad su paleta
+        let level = UserLevel::of(message);
+        if style.event == OverlayEventKind::Chat && self.display.theme.palette().is_none() {
+            if let Some(level_style) = self.display.user_levels.style_for(&level) {
+                let palette = style
+                    .palette
+                    .unwrap_or_else(|| self.display.configured_palette());
+                style.palette = Some(level_style.apply_to(palette));
+                style.user_level = Some(level);
+            }
+        }
+
+        // Primeras participaciones y menciones: color propio y más tiempo en pantalla
+        if let Some(kind) = HighlightKind::of(message).filter(|_| self.highlight.enabled) {
+            if self.display.theme.palette().is_none() {
+                let palette = style
+                    .palette
+                    .unwrap_or_else(|| self.display.configured_palette());
+                style.palette = Some(self.highlight.style_for(kind).apply_to(palette));
+            }
+            style.duration += Duration::from_secs(self.highlight.extra_duration_secs);
+            style.highlight = Some(kind);
+        }
+        style
+    }
+
+    /// Tamaño de fuente tras aplicar escala y DPI, nunca menor que el mínimo configurado
+    pub fn effective_font_size(&self, scale: f32, dpi_scale: f32) -> u32 {
+        let size = (self.display.font_size as f32 * scale * dpi_scale).round() as u32;
+        size.max(self.display.required_min_font_size())
+    }
+
     pub fn get_enabled_platforms(&self) -> Vec<&str> {
         self.platforms
             .iter()
@@ -344,6 +1534,28 @@ impl Config {
                     conn.id, conn.platform
                 )));
             }
+            if conn.display_offset_ms.abs() > MAX_DISPLAY_OFFSET_MS {
+                return Err(ConfigError::ValidationError(format!(
+                    "Connection '{}' display_offset_ms must be within ±{}",
+                    conn.id, MAX_DISPLAY_OFFSET_MS
+                )));
+            }
+            if conn.links.max_length < MIN_LINK_LENGTH {
+                return Err(ConfigError::ValidationError(format!(
+                    "Connection '{}' links.max_length must be at least {}",
+                    conn.id, MIN_LINK_LENGTH
+                )));
+            }
+        }
+
+        // Validar que las conexiones del watch-party existan
+        for id in &self.merge.connections {
+            if !self.connections.iter().any(|conn| &conn.id == id) {
+                return Err(ConfigError::ValidationError(format!(
+                    "Merge group '{}' references non-existent connection '{}'",
+                    self.merge.name, id
+                )));
+            }
         }
 
         // Validar configuraciones de ventana
@@ -359,6 +1571,319 @@ impl Config {
             ));
         }
 
+        // Validar controlador de carga
+        if self.load.sample_rate == 0 {
+            return Err(ConfigError::ValidationError(
+                "load.sample_rate must be greater than 0".to_string(),
+            ));
+        }
+
+        if !(self.load.recovery_ratio > 0.0 && self.load.recovery_ratio <= 1.0) {
+            return Err(ConfigError::ValidationError(
+                "load.recovery_ratio must be between 0.0 and 1.0".to_string(),
+            ));
+        }
+
+        if self.control.enabled && self.control.bind.parse::<std::net::SocketAddr>().is_err() {
+            return Err(ConfigError::ValidationError(format!(
+                "control.bind '{}' is not a valid socket address",
+                self.control.bind
+            )));
+        }
+
+        if self.error_budget.enabled && self.error_budget.window_secs == 0 {
+            return Err(ConfigError::ValidationError(
+                "error_budget.window_secs must be greater than 0".to_string(),
+            ));
+        }
+
+        if self.display.theme == ThemeName::Custom {
+            for (field, color) in [
+                ("background_color", &self.display.background_color),
+                ("text_color", &self.display.text_color),
+                ("username_color", &self.display.username_color),
+            ] {
+                if Rgb::parse_hex(color).is_none() {
+                    return Err(ConfigError::ValidationError(format!(
+                        "display.{} '{}' is not a #rrggbb color (required by the custom theme)",
+                        field, color
+                    )));
+                }
+            }
+        }
+
+        if let Some(name) = &self.display.active_theme {
+            if !crate::theme::named::theme_exists(&self.display, name) {
+                return Err(ConfigError::ValidationError(format!(
+                    "display.active_theme '{}' is not a built-in theme or one of display.themes",
+                    name
+                )));
+            }
+        }
+        for (name, theme) in &self.display.themes {
+            if name == crate::theme::named::NEXT_THEME {
+                return Err(ConfigError::ValidationError(format!(
+                    "display.themes.{} is reserved for POST /themes/{}",
+                    name, name
+                )));
+            }
+            for (field, color) in theme.color_fields() {
+                if Rgb::parse_hex(color).is_none() {
+                    return Err(ConfigError::ValidationError(format!(
+                        "display.themes.{}.{} '{}' is not a #rrggbb color",
+                        name, field, color
+                    )));
+                }
+            }
+            if theme.opacity.is_some_and(|opacity| !(0.0..=1.0).contains(&opacity)) {
+                return Err(ConfigError::ValidationError(format!(
+                    "display.themes.{}.opacity must be between 0 and 1",
+                    name
+                )));
+            }
+            if theme.font_size == Some(0) {
+                return Err(ConfigError::ValidationError(format!(
+                    "display.themes.{}.font_size must be greater than 0",
+                    name
+                )));
+            }
+        }
+
+        for (role, user) in [
+            ("announcements", &self.system_users.announcements),
+            ("scheduler", &self.system_users.scheduler),
+            ("diagnostics", &self.system_users.diagnostics),
+        ] {
+            if user.name.trim().is_empty() {
+                return Err(ConfigError::ValidationError(format!(
+                    "system_users.{}.name must not be empty",
+                    role
+                )));
+            }
+            if let Some(color) = &user.color {
+                if Rgb::parse_hex(color).is_none() {
+                    return Err(ConfigError::ValidationError(format!(
+                        "system_users.{}.color '{}' is not a #rrggbb color",
+                        role, color
+                    )));
+                }
+            }
+        }
+
+        if self.bridge.enabled && self.bridge.buffer_size == 0 {
+            return Err(ConfigError::ValidationError(
+                "bridge.buffer_size must be greater than 0".to_string(),
+            ));
+        }
+
+        if self.dedupe.enabled && self.dedupe.window_secs == 0 {
+            return Err(ConfigError::ValidationError(
+                "dedupe.window_secs must be greater than 0".to_string(),
+            ));
+        }
+
+        if self.emotes.prefetch.queue_size == 0 || self.emotes.prefetch.workers == 0 {
+            return Err(ConfigError::ValidationError(
+                "emotes.prefetch.queue_size and emotes.prefetch.workers must be greater than 0"
+                    .to_string(),
+            ));
+        }
+
+        if self.pipeline.workers == 0 {
+            return Err(ConfigError::ValidationError(
+                "pipeline.workers must be greater than 0".to_string(),
+            ));
+        }
+
+        if self.emotes.channel_set_ttl_minutes == 0 {
+            return Err(ConfigError::ValidationError(
+                "emotes.channel_set_ttl_minutes must be greater than 0".to_string(),
+            ));
+        }
+
+        if self.hype.enabled && (self.hype.window_secs == 0 || self.hype.min_events == 0) {
+            return Err(ConfigError::ValidationError(
+                "hype.window_secs and hype.min_events must be greater than 0".to_string(),
+            ));
+        }
+
+        if self.aggregate.enabled
+            && (self.aggregate.window_secs == 0 || self.aggregate.refresh_secs == 0)
+        {
+            return Err(ConfigError::ValidationError(
+                "aggregate.window_secs and aggregate.refresh_secs must be greater than 0"
+                    .to_string(),
+            ));
+        }
+
+        if let Some((pattern, e)) = crate::profanity::invalid_pattern(&self.profanity) {
+            return Err(ConfigError::ValidationError(format!(
+                "profanity.patterns '{}' is not a valid regex: {}",
+                pattern, e
+            )));
+        }
+
+        if self.moderation_lane.show_counter && self.moderation_lane.counter_interval_secs == 0 {
+            return Err(ConfigError::ValidationError(
+                "moderation_lane.counter_interval_secs must be greater than 0".to_string(),
+            ));
+        }
+
+        if self.theme_schedule.enabled && self.theme_schedule.check_interval_secs == 0 {
+            return Err(ConfigError::ValidationError(
+                "theme_schedule.check_interval_secs must be greater than 0".to_string(),
+            ));
+        }
+        for (i, entry) in self.theme_schedule.entries.iter().enumerate() {
+            for time in [&entry.start, &entry.end] {
+                if crate::theme::schedule::parse_time(time).is_none() {
+                    return Err(ConfigError::ValidationError(format!(
+                        "theme_schedule.entries[{}] time '{}' is not HH:MM",
+                        i, time
+                    )));
+                }
+            }
+            for color in [&entry.background_color, &entry.text_color, &entry.username_color]
+                .into_iter()
+                .flatten()
+            {
+                if Rgb::parse_hex(color).is_none() {
+                    return Err(ConfigError::ValidationError(format!(
+                        "theme_schedule.entries[{}] color '{}' is not a #rrggbb color",
+                        i, color
+                    )));
+                }
+            }
+        }
+
+        if self.idle.enabled && self.idle.idle_minutes == 0 {
+            return Err(ConfigError::ValidationError(
+                "idle.idle_minutes must be greater than 0".to_string(),
+            ));
+        }
+
+        if self.capture.max_file_bytes == 0 || self.capture.max_files == 0 {
+            return Err(ConfigError::ValidationError(
+                "capture.max_file_bytes and capture.max_files must be greater than 0".to_string(),
+            ));
+        }
+
+        // Validar tamaño mínimo de fuente
+        let min_font_size = self.display.required_min_font_size();
+        if self.display.font_size < min_font_size {
+            return Err(ConfigError::ValidationError(format!(
+                "font_size {} is below the minimum readable size {}",
+                self.display.font_size, min_font_size
+            )));
+        }
+
+        // Validar sobrescrituras por tipo de mensaje
+        for (message_type, overrides) in &self.display.message_type_overrides {
+            if let Some(opacity) = overrides.opacity {
+                if !(0.0..=1.0).contains(&opacity) {
+                    return Err(ConfigError::ValidationError(format!(
+                        "opacity override for {:?} must be between 0.0 and 1.0",
+                        message_type
+                    )));
+                }
+            }
+            if let Some(scale) = overrides.scale {
+                if !(0.25..=4.0).contains(&scale) {
+                    return Err(ConfigError::ValidationError(format!(
+                        "scale override for {:?} must be between 0.25 and 4.0",
+                        message_type
+                    )));
+                }
+            }
+            if overrides.duration_seconds == Some(0) {
+                return Err(ConfigError::ValidationError(format!(
+                    "duration override for {:?} must be greater than 0",
+                    message_type
+                )));
+            }
+        }
+
+        for (level, style) in &self.display.user_levels.styles {
+            if let Some((field, color)) = style.invalid_color() {
+                return Err(ConfigError::ValidationError(format!(
+                    "display.user_levels.styles.{:?}.{} '{}' is not a #rrggbb color",
+                    level, field, color
+                )));
+            }
+        }
+
+        for (field, style) in [
+            ("first_message_style", &self.highlight.first_message_style),
+            ("mention_style", &self.highlight.mention_style),
+        ] {
+            if let Some((color_field, color)) = style.invalid_color() {
+                return Err(ConfigError::ValidationError(format!(
+                    "highlight.{}.{} '{}' is not a #rrggbb color",
+                    field, color_field, color
+                )));
+            }
+        }
+
+        if self.commands.enabled
+            && self.commands.prefixes.iter().all(|prefix| prefix.trim().is_empty())
+        {
+            return Err(ConfigError::ValidationError(
+                "commands.prefixes must contain at least one prefix".to_string(),
+            ));
+        }
+
+        if !(0.0..=1.0).contains(&self.sound.volume) {
+            return Err(ConfigError::ValidationError(
+                "sound.volume must be between 0.0 and 1.0".to_string(),
+            ));
+        }
+        for (event, file) in &self.sound.sounds {
+            if file.volume.is_some_and(|volume| !(0.0..=1.0).contains(&volume)) {
+                return Err(ConfigError::ValidationError(format!(
+                    "sound.sounds.{:?}.volume must be between 0.0 and 1.0",
+                    event
+                )));
+            }
+        }
+
+        if self.storage.enabled && self.storage.path.as_os_str().is_empty() {
+            return Err(ConfigError::ValidationError(
+                "storage.path cannot be empty".to_string(),
+            ));
+        }
+
+        if self.demo.messages_per_minute == 0 {
+            return Err(ConfigError::ValidationError(
+                "demo.messages_per_minute must be greater than 0".to_string(),
+            ));
+        }
+        for (name, chance) in [
+            ("event_chance", self.demo.event_chance),
+            ("emote_chance", self.demo.emote_chance),
+        ] {
+            if !(0.0..=1.0).contains(&chance) {
+                return Err(ConfigError::ValidationError(format!(
+                    "demo.{} must be between 0.0 and 1.0",
+                    name
+                )));
+            }
+        }
+
+        for (event, template) in &self.display.event_templates {
+            if let Some((field, color)) = template.invalid_color() {
+                return Err(ConfigError::ValidationError(format!(
+                    "display.event_templates.{:?}.{} '{}' is not a #rrggbb color",
+                    event, field, color
+                )));
+            }
+            if template.duration_seconds == Some(0) {
+                return Err(ConfigError::ValidationError(format!(
+                    "display.event_templates.{:?}.duration_seconds must be greater than 0",
+                    event
+                )));
+            }
+        }
+
         Ok(())
     }
 }
@@ -422,12 +1947,20 @@ impl Default for Config {
                         max_message_length: Some(500),
                         blocked_users: vec![],
                         allowed_users: vec![],
+                        allowed_users_only: true,
+                        ignore_bots: false,
+                        bot_users: vec![],
                         blocked_words: vec![],
+                        hide_link_only: false,
                         commands_only: false,
+                        command_prefixes: vec![],
                         subscribers_only: false,
                         vip_only: false,
                     },
                     display_name: Some("Main Twitch Chat".to_string()),
+                    display_offset_ms: 0,
+                    links: LinkConfig::default(),
+                    mute_sounds: false,
                 },
                 ConnectionConfig {
                     id: "kick_main".to_string(),
@@ -439,12 +1972,20 @@ impl Default for Config {
                         max_message_length: Some(500),
                         blocked_users: vec![],
                         allowed_users: vec![],
+                        allowed_users_only: true,
+                        ignore_bots: false,
+                        bot_users: vec![],
                         blocked_words: vec![],
+                        hide_link_only: false,
                         commands_only: false,
+                        command_prefixes: vec![],
                         subscribers_only: false,
                         vip_only: false,
                     },
                     display_name: Some("Kick Chat".to_string()),
+                    display_offset_ms: 0,
+                    links: LinkConfig::default(),
+                    mute_sounds: false,
                 },
             ],
             window: WindowConfig {
@@ -454,6 +1995,7 @@ impl Default for Config {
                 animation_enabled: true,
                 fade_in_duration_ms: 300,
                 fade_out_duration_ms: 500,
+                pool_size: default_window_pool_size(),
             },
             display: DisplayConfig {
                 monitor_margin: 40,
@@ -466,6 +2008,24 @@ impl Default for Config {
                 username_color: "#00ff00".to_string(),
                 border_radius: 8,
                 opacity: 0.9,
+                message_type_overrides: HashMap::new(),
+                theme: ThemeName::Default,
+                min_font_size: 0,
+                themes: BTreeMap::new(),
+                active_theme: None,
+                event_templates: HashMap::new(),
+                area: DisplayArea::Full,
+                alerts_only: false,
+                user_levels: UserLevelStyles::default(),
+                show_avatars: false,
+                show_platform_icons: false,
+                placement_mode: PlacementMode::default(),
+                placement_anchor: PlacementAnchor::default(),
+                placement_spacing: default_placement_spacing(),
+                template: WindowTemplate::default(),
+                precomposed: false,
+                click_through: default_click_through(),
+                layout_file: default_layout_file(),
             },
             emotes: EmoteConfig {
                 enable_global_emotes: true,
@@ -479,6 +2039,14 @@ impl Default for Config {
                 max_emotes_per_message: 50,
                 cache_enabled: true,
                 cache_ttl_hours: 24,
+                http: EmoteHttpConfig::default(),
+                blocklist: EmoteBlocklistConfig::default(),
+                custom_providers: Vec::new(),
+                seventv_live_updates: default_seventv_live_updates(),
+                prefetch: EmotePrefetchConfig::default(),
+                channel_set_ttl_minutes: default_channel_set_ttl_minutes(),
+                quality: EmoteQualityConfig::default(),
+                usage: EmoteUsageConfig::default(),
             },
             logging: LoggingConfig {
                 level: LogLevel::Info,
@@ -488,10 +2056,44 @@ impl Default for Config {
                 max_file_size_mb: 10,
                 max_files: 5,
             },
+            load: LoadConfig::default(),
+            capture: CaptureConfig::default(),
+            merge: MergeConfig::default(),
+            idle: IdleConfig::default(),
+            pin: PinConfig::default(),
+            commands: ChatCommandsConfig::default(),
+            timestamps: TimestampConfig::default(),
+            control: ControlConfig::default(),
+            error_budget: ErrorBudgetConfig::default(),
+            system_users: SystemUsersConfig::default(),
+            theme_schedule: ThemeScheduleConfig::default(),
+            moderation_lane: ModerationLaneConfig::default(),
+            bridge: BridgeConfig::default(),
+            history: HistoryConfig::default(),
+            profanity: ProfanityConfig::default(),
+            dedupe: DedupeConfig::default(),
+            hype: HypeConfig::default(),
+            aggregate: AggregateConfig::default(),
+            pause: PauseConfig::default(),
+            highlight: HighlightConfig::default(),
+            ipc: IpcConfig::default(),
+            obs: ObsConfig::default(),
+            sound: SoundConfig::default(),
+            demo: DemoConfig::default(),
+            storage: StorageConfig::default(),
+            stats: StatsConfig::default(),
+            mapping: MappingRulesConfig::default(),
+            translation: TranslationConfig::default(),
+            pipeline: PipelineConfig::default(),
         }
     }
 }
 
+/// Vigilancia activa del fichero de configuración (se detiene al soltarlo)
+pub struct ConfigWatcher {
+    _watcher: notify::RecommendedWatcher,
+}
+
 #[derive(Debug)]
 pub enum ConfigError {
     FileError(String),
@@ -512,3 +2114,241 @@ impl std::fmt::Display for ConfigError {
 }
 
 impl std::error::Error for ConfigError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mapping::MappedMessageType;
+
+    #[test]
+    fn test_window_style_defaults_to_display_settings() {
+        let config = Config::default();
+        let style = config.window_style_for(&MappedMessageType::Normal);
+        assert_eq!(style.opacity, config.display.opacity);
+        assert_eq!(style.scale, 1.0);
+        assert_eq!(style.duration, config.message_duration());
+    }
+
+    #[test]
+    fn test_fade_timing_ramps_in_and_out() {
+        let mut config = Config::default();
+        let fade = config.window_style_for(&MappedMessageType::Normal).fade;
+        assert_eq!(fade.fade_in, Duration::from_millis(300));
+        assert_eq!(fade.factor(Duration::ZERO, Duration::from_secs(10)), 0.0);
+        assert_eq!(
+            fade.factor(Duration::from_millis(150), Duration::from_secs(10)),
+            0.5
+        );
+        assert_eq!(fade.factor(Duration::from_secs(2), Duration::from_secs(8)), 1.0);
+        assert_eq!(
+            fade.factor(Duration::from_secs(9), Duration::from_millis(250)),
+            0.5
+        );
+        assert_eq!(fade.factor(Duration::from_secs(10), Duration::ZERO), 0.0);
+
+        config.window.animation_enabled = false;
+        let fade = config.window_style_for(&MappedMessageType::Normal).fade;
+        assert_eq!(fade, FadeTiming::default());
+        assert_eq!(fade.factor(Duration::ZERO, Duration::ZERO), 1.0);
+    }
+
+    #[test]
+    fn test_window_style_applies_type_override() {
+        let mut config = Config::default();
+        config.display.message_type_overrides.insert(
+            MappedMessageType::Raid,
+            MessageTypeOverride {
+                opacity: Some(1.0),
+                scale: Some(1.5),
+                duration_seconds: Some(20),
+            },
+        );
+
+        let style = config.window_style_for(&MappedMessageType::Raid);
+        assert_eq!(style.opacity, 1.0);
+        assert_eq!(style.scale, 1.5);
+        assert_eq!(style.duration, Duration::from_secs(20));
+        assert!(config.validate().is_ok());
+
+        config
+            .display
+            .message_type_overrides
+            .get_mut(&MappedMessageType::Raid)
+            .unwrap()
+            .scale = Some(10.0);
+        assert!(config.validate().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_watch_sends_valid_changes_only() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.json");
+        Config::default().save_to_file(&path).unwrap();
+
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let _watcher = Config::watch(&path, tx).unwrap();
+
+        fs::write(&path, "{ not json").unwrap();
+        let mut config = Config::default();
+        config.window.message_duration_seconds = 42;
+        config.save_to_file(&path).unwrap();
+
+        let reloaded = tokio::time::timeout(Duration::from_secs(5), rx.recv())
+            .await
+            .expect("no reload event")
+            .unwrap();
+        assert_eq!(reloaded.window.message_duration_seconds, 42);
+    }
+
+    #[test]
+    fn test_high_contrast_enforces_min_font_size() {
+        let mut config = Config::default();
+        config.display.theme = ThemeName::HighContrast;
+        assert!(config.validate().is_err());
+
+        config.display.font_size = 20;
+        assert!(config.validate().is_ok());
+
+        config.display.message_type_overrides.insert(
+            MappedMessageType::System,
+            MessageTypeOverride {
+                scale: Some(0.5),
+                ..Default::default()
+            },
+        );
+        let style = config.window_style_for(&MappedMessageType::System);
+        assert_eq!(style.font_size, crate::theme::HIGH_CONTRAST_MIN_FONT_SIZE);
+        assert_eq!(style.opacity, 1.0);
+        assert!(style.palette.is_some());
+        assert_eq!(config.effective_font_size(1.0, 2.0), 40);
+    }
+
+    #[test]
+    fn test_event_templates_style_sub_raid_and_cheer() {
+        let mut config = Config::default();
+        let chat = config.window_style_for(&MappedMessageType::Normal);
+        let sub = config.window_style_for(&MappedMessageType::Subscription);
+        assert_eq!(sub.event, OverlayEventKind::Subscription);
+        assert_eq!(sub.duration, Duration::from_secs(15));
+        assert_eq!(sub.username_label("alice"), "⭐ alice");
+        assert_ne!(sub.palette, chat.palette);
+        assert_eq!(chat.username_label("alice"), "alice");
+
+        config.display.event_templates.insert(
+            OverlayEventKind::Raid,
+            EventTemplate {
+                accent_color: Some("#00ff00".to_string()),
+                icon: Some(String::new()),
+                ..Default::default()
+            },
+        );
+        let raid = config.window_style_for(&MappedMessageType::Raid);
+        assert_eq!(raid.icon, None);
+        assert_eq!(raid.palette.unwrap().progress, Rgb(0, 255, 0));
+        assert!(config.validate().is_ok());
+
+        config
+            .display
+            .event_templates
+            .get_mut(&OverlayEventKind::Raid)
+            .unwrap()
+            .background_color = Some("red".to_string());
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_highlighted_messages_get_accent_and_longer_duration() {
+        let mut config = Config::default();
+        let mut message = crate::system_users::system_message(
+            &crate::config::SystemUser::default(),
+            "diagnostics",
+            "hola @streamer",
+            HashMap::new(),
+        );
+        message.metadata.custom_data.insert(
+            HighlightKind::METADATA_KEY.to_string(),
+            serde_json::json!("mention"),
+        );
+        let plain = config.window_style_for_message(&message);
+        assert_eq!(plain.highlight, None);
+
+        config.highlight.enabled = true;
+        let highlighted = config.window_style_for_message(&message);
+        assert_eq!(highlighted.highlight, Some(HighlightKind::Mention));
+        assert_eq!(highlighted.palette.unwrap().border, Rgb(255, 159, 28));
+        assert_eq!(
+            highlighted.duration,
+            plain.duration + Duration::from_secs(config.highlight.extra_duration_secs)
+        );
+
+        config.highlight.mention_style.border_color = Some("orange".to_string());
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_user_levels_color_chat_but_not_events() {
+        let mut config = Config::default();
+        let mut message = crate::system_users::system_message(
+            &crate::config::SystemUser::default(),
+            "diagnostics",
+            "hola",
+            HashMap::new(),
+        );
+        message.metadata.custom_data.insert(
+            UserLevel::METADATA_KEY.to_string(),
+            serde_json::json!("moderator"),
+        );
+        let plain = config.window_style_for_message(&message);
+        assert_eq!(plain.user_level, None);
+
+        config.display.user_levels.enabled = true;
+        let styled = config.window_style_for_message(&message);
+        assert_eq!(styled.user_level, Some(UserLevel::Moderator));
+        assert_eq!(styled.palette.unwrap().border, Rgb(0, 173, 3));
+
+        message.message_type = crate::connection::MessageType::Raid;
+        message
+            .metadata
+            .custom_data
+            .remove(crate::mapping::MappedMessageType::METADATA_KEY);
+        let raid = config.window_style_for_message(&message);
+        assert_eq!(raid.user_level, None);
+        assert!(config.validate().is_ok());
+
+        config.display.user_levels.styles.insert(
+            UserLevel::Vip,
+            crate::theme::levels::UserLevelStyle {
+                username_color: Some("pink".to_string()),
+                ..Default::default()
+            },
+        );
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_platform_icon_only_with_show_platform_icons() {
+        let mut config = Config::default();
+        let message = crate::connection::ChatMessage::test("viewer", "hola").on("kick", "chan");
+        assert_eq!(
+            config.window_style_for_message(&message).platform_icon,
+            None
+        );
+
+        config.display.show_platform_icons = true;
+        let icon = config
+            .window_style_for_message(&message)
+            .platform_icon
+            .unwrap();
+        assert_eq!(icon.name, "platforms/kick.png");
+    }
+
+    #[test]
+    fn test_message_type_overrides_deserialize() {
+        let json = r#"{"system": {"opacity": 0.5, "scale": 0.8}}"#;
+        let overrides: HashMap<MappedMessageType, MessageTypeOverride> =
+            serde_json::from_str(json).unwrap();
+        let system = &overrides[&MappedMessageType::System];
+        assert_eq!(system.opacity, Some(0.5));
+        assert_eq!(system.duration_seconds, None);
+    }
+}
@@ -1,9 +1,17 @@
 use async_trait::async_trait;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use serde_with::serde_as;
 use std::collections::HashMap;
+use std::sync::atomic::AtomicU64;
+use std::sync::Arc;
 use std::time::SystemTime;
-use tokio::sync::mpsc;
+use tokio::sync::{broadcast, mpsc};
+
+use crate::reconnect::{
+    ConnectionState, ConnectionStateChange, ConnectionStates, ReconnectPolicy,
+    SupervisedConnection,
+};
 
 /// Type alias for platform errors to simplify trait bounds
 pub type PlatformError = Box<dyn std::error::Error + Send + Sync>;
@@ -12,8 +20,28 @@ fn system_time_now() -> SystemTime {
     SystemTime::now()
 }
 
+/// Clave de `custom_data` con el id del mensaje que la plataforma borró
+pub const DELETED_MESSAGE_KEY: &str = "deleted_message_id";
+
+/// Clave de `custom_data` de los avisos de limpieza del chat (`"chat"`)
+pub const CLEAR_TYPE_KEY: &str = "clear_type";
+
+/// Clave de `custom_data` con el usuario cuyos mensajes se retiraron
+pub const CLEARED_USER_KEY: &str = "cleared_user";
+
+/// Mensajes que la plataforma retiró y que no deben seguir en pantalla
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Retraction {
+    /// Un mensaje concreto, que el aviso no sustituye en pantalla
+    Message(String),
+    /// Todos los del usuario en el canal (timeout o ban)
+    User(String),
+    /// Todo el canal (`/clear`)
+    Channel,
+}
+
 #[serde_as]
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ChatMessage {
     pub id: String,
     pub platform: String,
@@ -25,13 +53,127 @@ pub struct ChatMessage {
     pub badges: Vec<Badge>,
     #[serde(default = "system_time_now")]
     #[serde_as(as = "serde_with::TimestampSeconds<i64>")]
+    #[schemars(with = "i64")]
     pub timestamp: SystemTime,
     pub user_color: Option<String>,
     pub message_type: MessageType,
     pub metadata: MessageMetadata,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+impl ChatMessage {
+    /// Qué retira este aviso de moderación; `None` para el resto de mensajes
+    pub fn retraction(&self) -> Option<Retraction> {
+        if !matches!(self.message_type, MessageType::System) {
+            return None;
+        }
+        let data = &self.metadata.custom_data;
+        if let Some(id) = data.get(DELETED_MESSAGE_KEY).and_then(|id| id.as_str()) {
+            return Some(Retraction::Message(id.to_string()));
+        }
+        if data.get(CLEAR_TYPE_KEY).and_then(|t| t.as_str()) != Some("chat") {
+            return None;
+        }
+        Some(match data.get(CLEARED_USER_KEY).and_then(|u| u.as_str()) {
+            Some(user) => Retraction::User(user.to_string()),
+            None => Retraction::Channel,
+        })
+    }
+}
+
+/// Constructor de mensajes para los tests de todos los módulos
+#[cfg(test)]
+impl ChatMessage {
+    /// Mensaje normal de `username` en el canal `chan` de Twitch
+    pub(crate) fn test(username: &str, content: &str) -> Self {
+        ChatMessage {
+            id: format!("{}-1", username),
+            platform: "twitch".to_string(),
+            channel: "chan".to_string(),
+            username: username.to_string(),
+            display_name: None,
+            content: content.to_string(),
+            emotes: Vec::new(),
+            badges: Vec::new(),
+            timestamp: SystemTime::now(),
+            user_color: None,
+            message_type: MessageType::Normal,
+            metadata: MessageMetadata {
+                is_action: false,
+                is_whisper: false,
+                is_highlighted: false,
+                is_me_message: false,
+                reply_to: None,
+                thread_id: None,
+                custom_data: HashMap::new(),
+            },
+        }
+    }
+
+    pub(crate) fn with_id(mut self, id: impl Into<String>) -> Self {
+        self.id = id.into();
+        self
+    }
+
+    pub(crate) fn on(mut self, platform: &str, channel: &str) -> Self {
+        self.platform = platform.to_string();
+        self.channel = channel.to_string();
+        self
+    }
+
+    pub(crate) fn with_type(mut self, message_type: MessageType) -> Self {
+        self.message_type = message_type;
+        self
+    }
+
+    pub(crate) fn at(mut self, timestamp: SystemTime) -> Self {
+        self.timestamp = timestamp;
+        self
+    }
+
+    pub(crate) fn highlighted(mut self, highlighted: bool) -> Self {
+        self.metadata.is_highlighted = highlighted;
+        self
+    }
+
+    pub(crate) fn with_data(mut self, key: &str, value: impl Into<serde_json::Value>) -> Self {
+        self.metadata
+            .custom_data
+            .insert(key.to_string(), value.into());
+        self
+    }
+
+    /// Añade la insignia de Twitch `name` (`moderator`, `subscriber`...)
+    pub(crate) fn with_badge(mut self, name: &str) -> Self {
+        self.badges.push(Badge {
+            id: name.to_string(),
+            name: name.to_string(),
+            version: "1".to_string(),
+            url: None,
+            title: None,
+            source: EmoteSource::Twitch,
+        });
+        self
+    }
+
+    /// Guarda el nivel de usuario que leen filtros, plantillas y comandos
+    pub(crate) fn with_level(self, level: crate::mapping::UserLevel) -> Self {
+        let level = serde_json::to_value(level).unwrap();
+        self.with_data(crate::mapping::UserLevel::METADATA_KEY, level)
+    }
+
+    /// Añade el emote de 7TV `name` sin posiciones en el texto
+    pub(crate) fn with_emote(mut self, name: &str) -> Self {
+        self.emotes.push(Emote {
+            id: name.to_string(),
+            name: name.to_string(),
+            source: EmoteSource::SevenTV,
+            ..Emote::default()
+        });
+        self
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Emote {
     pub id: String,
     pub name: String,
@@ -60,7 +202,7 @@ impl Default for Emote {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Badge {
     pub id: String,
     pub name: String,
@@ -70,13 +212,13 @@ pub struct Badge {
     pub source: EmoteSource,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct TextPosition {
     pub start: usize,
     pub end: usize,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct EmoteMetadata {
     pub is_zero_width: bool,
     pub modifier: bool,
@@ -95,7 +237,7 @@ impl Default for EmoteMetadata {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct MessageMetadata {
     pub is_action: bool,
     pub is_whisper: bool,
@@ -106,7 +248,7 @@ pub struct MessageMetadata {
     pub custom_data: HashMap<String, serde_json::Value>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "lowercase")]
 pub enum MessageType {
     Normal,
@@ -119,10 +261,22 @@ pub enum MessageType {
     Cheer,
     Poll,
     Prediction,
+    /// La plataforma fijó este mensaje
+    Pinned,
+    /// La plataforma quitó el mensaje fijado
+    Unpinned,
+    /// Nuevo seguidor (EventSub de Twitch)
+    Follow,
+    /// Canje de una recompensa de puntos del canal
+    Redemption,
+    /// Inicio o fin de un Hype Train
+    HypeTrain,
+    /// Un moderador expulsó (o silenció temporalmente) a un usuario
+    Ban,
     Unknown,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "lowercase")]
 #[derive(PartialEq, Eq, Hash)]
 pub enum EmoteSource {
@@ -212,7 +366,8 @@ pub trait StreamingPlatform {
 
 pub struct PlatformManager {
     message_sender: mpsc::UnboundedSender<ChatMessage>,
-    message_receiver: mpsc::UnboundedReceiver<ChatMessage>,
+    /// `None` una vez que el procesador de mensajes se lo ha llevado
+    message_receiver: Option<mpsc::UnboundedReceiver<ChatMessage>>,
     platforms: HashMap<
         String,
         std::sync::Arc<
@@ -226,9 +381,23 @@ pub struct PlatformManager {
         >,
     >,
     connections: HashMap<String, ConnectionInfo>,
+    /// Tareas de lectura de mensajes de las conexiones iniciadas
+    tasks: HashMap<String, tokio::task::JoinHandle<()>>,
+    states: ConnectionStates,
+    /// Reconexiones de cada plataforma, para no reconectarla dos veces
+    generations: HashMap<String, Arc<AtomicU64>>,
+    reconnect_policies: HashMap<String, ReconnectPolicy>,
 }
 
-#[derive(Debug, Clone)]
+/// Resultado de `PlatformManager::sync_connections`
+#[derive(Debug, Default)]
+pub struct ConnectionSync {
+    pub started: Vec<String>,
+    pub stopped: Vec<String>,
+    pub failed: Vec<(String, PlatformError)>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct ConnectionInfo {
     pub id: String,
     pub platform: String,
@@ -242,9 +411,13 @@ impl PlatformManager {
         let (sender, receiver) = mpsc::unbounded_channel();
         Self {
             message_sender: sender,
-            message_receiver: receiver,
+            message_receiver: Some(receiver),
             platforms: HashMap::new(),
             connections: HashMap::new(),
+            tasks: HashMap::new(),
+            states: ConnectionStates::new(),
+            generations: HashMap::new(),
+            reconnect_policies: HashMap::new(),
         }
     }
 
@@ -252,8 +425,19 @@ impl PlatformManager {
         self.message_sender.clone()
     }
 
+    /// Siguiente mensaje de cualquier conexión; `None` si el receptor ya se
+    /// entregó con [`take_message_receiver`](Self::take_message_receiver)
     pub async fn next_message(&mut self) -> Option<ChatMessage> {
-        self.message_receiver.recv().await
+        match &mut self.message_receiver {
+            Some(receiver) => receiver.recv().await,
+            None => None,
+        }
+    }
+
+    /// Entrega el receptor al que las tareas de lectura envían los mensajes,
+    /// para esperarlos sin mantener el lock del manager
+    pub fn take_message_receiver(&mut self) -> Option<mpsc::UnboundedReceiver<ChatMessage>> {
+        self.message_receiver.take()
     }
 
     pub fn register_platform(
@@ -267,6 +451,7 @@ impl PlatformManager {
         >,
     ) {
         eprintln!("[DEBUG] Registering platform: {}", name);
+        self.generations.entry(name.clone()).or_default();
         self.platforms
             .insert(name, std::sync::Arc::new(tokio::sync::Mutex::new(platform)));
         eprintln!(
@@ -275,6 +460,27 @@ impl PlatformManager {
         );
     }
 
+    /// Reintentos de las conexiones de `platform_name` cuando se cae su flujo
+    pub fn set_reconnect_policy(&mut self, platform_name: &str, policy: ReconnectPolicy) {
+        self.reconnect_policies
+            .insert(platform_name.to_string(), policy);
+    }
+
+    /// Cambios de estado de todas las conexiones
+    pub fn subscribe_state_changes(&self) -> broadcast::Receiver<ConnectionStateChange> {
+        self.states.subscribe()
+    }
+
+    /// Últimos `limit` mensajes mostrados de `channel`, del más antiguo al más
+    /// reciente (anillo de [`crate::history::MessageHistory`])
+    pub fn get_recent_messages(&self, channel: &str, limit: usize) -> Vec<ChatMessage> {
+        crate::history::MessageHistory::global().recent(Some(channel), limit)
+    }
+
+    pub fn connection_state(&self, connection_id: &str) -> Option<ConnectionState> {
+        self.states.get(connection_id)
+    }
+
     pub fn add_connection(&mut self, info: ConnectionInfo) {
         eprintln!("[DEBUG] Adding connection: {:?}", info);
         self.connections.insert(info.id.clone(), info);
@@ -322,6 +528,10 @@ impl PlatformManager {
             return Err("Connection is disabled".into());
         }
 
+        if self.is_connection_running(connection_id) {
+            return Err("Connection is already running".into());
+        }
+
         eprintln!("[DEBUG] Looking for platform: {}", connection_info.platform);
         eprintln!(
             "[DEBUG] Available platforms: {:?}",
@@ -333,7 +543,16 @@ impl PlatformManager {
             .ok_or("Platform not found")?
             .clone();
 
-        {
+        let set_state = |state| {
+            self.states.set(
+                connection_id,
+                &connection_info.platform,
+                &connection_info.channel,
+                state,
+            )
+        };
+        set_state(ConnectionState::Connecting);
+        let joined = async {
             let mut platform = platform_arc.lock().await;
             if !platform.is_connected() {
                 eprintln!("[DEBUG] Platform not connected, connecting...");
@@ -342,66 +561,122 @@ impl PlatformManager {
             } else {
                 eprintln!("[DEBUG] Platform already connected.");
             }
-        }
 
-        eprintln!(
-            "[DEBUG] Joining channel: {}",
-            connection_info.channel.clone()
-        );
-        {
-            let mut platform = platform_arc.lock().await;
+            eprintln!("[DEBUG] Joining channel: {}", connection_info.channel);
             platform
                 .join_channel(connection_info.channel.clone())
                 .await?;
+            Ok::<(), PlatformError>(())
+        }
+        .await;
+        if let Err(e) = joined {
+            set_state(ConnectionState::Failed {
+                reason: e.to_string(),
+            });
+            return Err(e);
         }
         eprintln!("[DEBUG] Joined channel: {}", connection_info.channel);
 
-        let sender = self.message_sender.clone();
-        let platform_name = connection_info.platform.clone();
-        let channel = connection_info.channel.clone();
+        let supervised = SupervisedConnection {
+            connection_id: connection_id.to_string(),
+            platform_name: connection_info.platform.clone(),
+            channel: connection_info.channel.clone(),
+            platform: platform_arc,
+            generation: self
+                .generations
+                .entry(connection_info.platform.clone())
+                .or_default()
+                .clone(),
+            policy: self
+                .reconnect_policies
+                .get(&connection_info.platform)
+                .copied()
+                .unwrap_or_default(),
+        };
+        let task = tokio::spawn(supervised.run(self.message_sender.clone(), self.states.clone()));
+        self.tasks.insert(connection_id.to_string(), task);
 
-        tokio::spawn(async move {
-            eprintln!(
-                "[DEBUG] Spawned task for connection {} on channel {}. Starting message loop...",
-                platform_name, channel
-            );
+        Ok(())
+    }
 
-            let mut message_count = 0;
-            loop {
-                // Get message without holding the lock for too long
-                let message = {
-                    let mut platform = platform_arc.lock().await;
-                    platform.next_message().await
-                };
-
-                if let Some(mut message) = message {
-                    message_count += 1;
-                    eprintln!(
-                        "[DEBUG] Received message #{} from {}: {} - {}",
-                        message_count, platform_name, message.username, message.content
-                    );
-                    message.platform = platform_name.clone();
-                    message.channel = channel.clone();
+    /// Detiene la lectura de mensajes de una conexión y abandona su canal
+    pub async fn stop_connection(
+        &mut self,
+        connection_id: &str,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let connection_info = self
+            .connections
+            .get(connection_id)
+            .ok_or("Connection not found")?
+            .clone();
+        let task = self
+            .tasks
+            .remove(connection_id)
+            .ok_or("Connection is not running")?;
+        task.abort();
+        self.states.set(
+            connection_id,
+            &connection_info.platform,
+            &connection_info.channel,
+            ConnectionState::Stopped,
+        );
 
-                    if sender.send(message).is_err() {
-                        eprintln!("[DEBUG] Failed to send message, breaking loop");
-                        break;
-                    } else {
-                        eprintln!("[DEBUG] Message sent successfully to manager");
-                    }
-                } else {
-                    eprintln!("[DEBUG] No message received from platform");
-                    // Small delay to prevent busy waiting
-                    tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+        if let Some(platform) = self.platforms.get(&connection_info.platform) {
+            platform
+                .lock()
+                .await
+                .leave_channel(connection_info.channel.clone())
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Indica si la conexión tiene una tarea de lectura activa
+    pub fn is_connection_running(&self, connection_id: &str) -> bool {
+        self.tasks
+            .get(connection_id)
+            .is_some_and(|task| !task.is_finished())
+    }
+
+    /// Ajusta las conexiones a `desired`: detiene y quita las que desaparecieron o
+    /// cambiaron, y añade e inicia las nuevas habilitadas
+    pub async fn sync_connections(&mut self, desired: Vec<ConnectionInfo>) -> ConnectionSync {
+        let mut sync = ConnectionSync::default();
+
+        let stale: Vec<String> = self
+            .connections
+            .values()
+            .filter(|current| !desired.contains(current))
+            .map(|current| current.id.clone())
+            .collect();
+        for id in stale {
+            if self.is_connection_running(&id) {
+                match self.stop_connection(&id).await {
+                    Ok(()) => sync.stopped.push(id.clone()),
+                    Err(e) => sync.failed.push((id.clone(), e)),
                 }
             }
-            eprintln!(
-                "[DEBUG] Message loop ended for {} on channel {} (total messages: {})",
-                platform_name, channel, message_count
-            );
-        });
+            self.tasks.remove(&id);
+            self.connections.remove(&id);
+            self.states.remove(&id);
+        }
 
-        Ok(())
+        for info in desired {
+            if self.connections.contains_key(&info.id) {
+                continue;
+            }
+            let id = info.id.clone();
+            let enabled = info.enabled;
+            self.add_connection(info);
+            if enabled {
+                match self.start_connection(&id).await {
+                    Ok(()) => sync.started.push(id),
+                    Err(e) => sync.failed.push((id, e)),
+                }
+            }
+        }
+
+        sync
     }
 
     pub async fn run_platform<P: StreamingPlatform + Send + 'static>(
@@ -489,11 +764,31 @@ impl PlatformManager {
             .collect()
     }
 
+    /// Sale de los canales y desconecta todas las plataformas
+    ///
+    /// Un fallo no impide cerrar las demás; se devuelve el primero.
     pub async fn shutdown(&mut self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        for (_, platform) in &mut self.platforms {
-            platform.lock().await.disconnect().await?;
+        for conn in self.connections.values().filter(|conn| conn.enabled) {
+            let Some(platform) = self.platforms.get(&conn.platform) else {
+                continue;
+            };
+            let mut platform = platform.lock().await;
+            if !platform.is_connected() {
+                continue;
+            }
+            if let Err(e) = platform.leave_channel(conn.channel.clone()).await {
+                eprintln!("⚠️ Failed to leave {}:{}: {}", conn.platform, conn.channel, e);
+            }
         }
-        Ok(())
+
+        let mut first_error: Option<Box<dyn std::error::Error + Send + Sync>> = None;
+        for (name, platform) in &self.platforms {
+            if let Err(e) = platform.lock().await.disconnect().await {
+                eprintln!("⚠️ Failed to disconnect {}: {}", name, e);
+                first_error.get_or_insert(Box::new(e));
+            }
+        }
+        first_error.map_or(Ok(()), Err)
     }
 }
 
@@ -511,26 +806,24 @@ pub mod utils {
         emotes.iter().flat_map(|e| e.positions.clone()).collect()
     }
 
+    /// Sustituye cada aparición (en caracteres, final incluido) por `:nombre`
     pub fn replace_emotes_with_placeholders(content: &str, emotes: &[Emote]) -> String {
-        let mut result = content.to_string();
-
-        // Ordenar emotes por posición de inicio en orden descendente
-        let mut sorted_emotes = emotes.to_vec();
-        sorted_emotes.sort_by(|a, b| {
-            let a_start = a.positions.first().map(|p| p.start).unwrap_or(0);
-            let b_start = b.positions.first().map(|p| p.start).unwrap_or(0);
-            b_start.cmp(&a_start)
-        });
-
-        for emote in sorted_emotes {
-            for position in &emote.positions {
-                if position.start < result.len() && position.end <= result.len() {
-                    result.replace_range(position.start..position.end, &format!(":{}", emote.name));
-                }
-            }
-        }
-
-        result
+        let mut occurrences: Vec<(&TextPosition, &str)> = emotes
+            .iter()
+            .flat_map(|emote| emote.positions.iter().map(|pos| (pos, emote.name.as_str())))
+            .collect();
+        occurrences.sort_by_key(|(position, _)| position.start);
+
+        let ranges = occurrences
+            .iter()
+            .map(|(position, _)| position.start..position.end.saturating_add(1));
+        crate::text::interleave_runs(content, ranges)
+            .into_iter()
+            .map(|run| match run {
+                crate::text::TextRun::Text(text) => text.to_string(),
+                crate::text::TextRun::Emote(index) => format!(":{}", occurrences[index].1),
+            })
+            .collect()
     }
 
     pub fn calculate_message_metrics(message: &ChatMessage) -> MessageMetrics {
@@ -545,7 +838,7 @@ pub mod utils {
     }
 
     fn contains_links(content: &str) -> bool {
-        content.contains("http://") || content.contains("https://")
+        !crate::links::find_links(content).is_empty()
     }
 
     fn is_mentioned(content: &str) -> bool {
@@ -710,6 +1003,14 @@ mod tests {
         ) -> bool {
             true
         }
+
+        fn as_any(&self) -> &dyn std::any::Any {
+            self
+        }
+
+        fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+            self
+        }
     }
 
     #[tokio::test]
@@ -811,6 +1112,100 @@ mod tests {
         assert!(manager.get_platform_names().contains(&"twitch".to_string()));
     }
 
+    #[tokio::test]
+    async fn test_sync_connections_starts_added_and_stops_removed() {
+        let mut manager = PlatformManager::new();
+        let handle = MockPlatformHandle::new();
+        manager.register_platform("mock".to_string(), Box::new(handle.platform));
+
+        let info = |id: &str, channel: &str| ConnectionInfo {
+            id: id.to_string(),
+            platform: "mock".to_string(),
+            channel: channel.to_string(),
+            enabled: true,
+            display_name: None,
+        };
+
+        let sync = manager
+            .sync_connections(vec![info("a", "one"), info("b", "two")])
+            .await;
+        assert_eq!(sync.started.len(), 2);
+        assert!(manager.is_connection_running("a"));
+
+        // "a" desaparece y "b" cambia de canal: se reinicia
+        let sync = manager.sync_connections(vec![info("b", "three")]).await;
+        assert_eq!(sync.stopped.len(), 2);
+        assert_eq!(sync.started, vec!["b".to_string()]);
+        assert!(!manager.is_connection_running("a"));
+        assert_eq!(manager.get_connections().len(), 1);
+        assert!(sync.failed.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_closed_stream_is_retried_with_backoff() {
+        let mut manager = PlatformManager::new();
+        let handle = MockPlatformHandle::new();
+        manager.register_platform("mock".to_string(), Box::new(handle.platform));
+        manager.set_reconnect_policy(
+            "mock",
+            ReconnectPolicy {
+                max_attempts: 2,
+                base_delay: std::time::Duration::from_millis(1),
+                max_delay: std::time::Duration::from_millis(5),
+            },
+        );
+        manager.add_connection(ConnectionInfo {
+            id: "main".to_string(),
+            platform: "mock".to_string(),
+            channel: "chan".to_string(),
+            enabled: true,
+            display_name: None,
+        });
+        let mut changes = manager.subscribe_state_changes();
+
+        // Sin emisor el flujo de la plataforma se cierra en cuanto se lee
+        drop(handle.message_sender);
+        manager.start_connection("main").await.unwrap();
+
+        let mut states = Vec::new();
+        loop {
+            let change = tokio::time::timeout(std::time::Duration::from_secs(5), changes.recv())
+                .await
+                .unwrap()
+                .unwrap();
+            let failed = matches!(change.state, ConnectionState::Failed { .. });
+            states.push(change.state);
+            if failed {
+                break;
+            }
+        }
+
+        assert_eq!(states[0], ConnectionState::Connecting);
+        assert_eq!(states[1], ConnectionState::Connected);
+        let attempts: Vec<u32> = states
+            .iter()
+            .filter_map(|state| match state {
+                ConnectionState::Reconnecting { attempt, .. } => Some(*attempt),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(attempts, vec![1, 2]);
+        // Cada intento vuelve a unirse al canal antes de rendirse
+        assert_eq!(
+            states
+                .iter()
+                .filter(|state| **state == ConnectionState::Connected)
+                .count(),
+            3
+        );
+        assert_eq!(
+            manager.connection_state("main"),
+            Some(ConnectionState::Failed {
+                reason: "gave up after 2 attempts".to_string()
+            })
+        );
+    }
+
     #[tokio::test]
     async fn test_message_flow_with_multiple_messages() {
         let mut manager = PlatformManager::new();
@@ -871,6 +1266,41 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_taken_receiver_gets_messages_without_the_manager_lock() {
+        let manager = Arc::new(tokio::sync::RwLock::new(PlatformManager::new()));
+        let handle = MockPlatformHandle::new();
+        let mut receiver = manager.write().await.take_message_receiver().unwrap();
+        assert!(manager.write().await.take_message_receiver().is_none());
+
+        manager
+            .write()
+            .await
+            .run_platform("mock".to_string(), handle.platform, "chan".to_string())
+            .await
+            .unwrap();
+        handle
+            .message_sender
+            .send(
+                ChatMessage::test("user", "pushed")
+                    .with_id("pushed")
+                    .on("", ""),
+            )
+            .unwrap();
+
+        // Otro escritor puede tener el manager mientras llega el mensaje
+        let guard = manager.write().await;
+        let message = tokio::time::timeout(std::time::Duration::from_secs(5), receiver.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(message.content, "pushed");
+        assert_eq!(message.channel, "chan");
+        drop(guard);
+
+        assert!(manager.write().await.next_message().await.is_none());
+    }
+
     #[tokio::test]
     async fn test_connection_disabled() {
         let mut manager = PlatformManager::new();
@@ -0,0 +1,1334 @@
+//! API HTTP de control para gestionar el overlay en caliente
+//!
+//! Pensada para herramientas externas (Stream Deck, scripts de OBS). Endpoints:
+//!
+//! - `GET  /health`
+//! - `GET  /connections`
+//! - `POST /connections/{id}/start`
+//! - `POST /connections/{id}/stop`
+//! - `GET  /connections/{id}/commands-only`
+//! - `POST /connections/{id}/commands-only` (cuerpo opcional
+//!   `{"enabled": true, "prefixes": ["!question"]}`; sin cuerpo, invierte el modo)
+//! - `DELETE /connections/{id}/commands-only` (vuelve a `config.json`)
+//! - `GET  /connections/{id}/users` (listas de usuarios y filtro de bots)
+//! - `POST /connections/{id}/users` (cuerpo
+//!   `{"block": ["..."], "allow": ["..."], "ignore_bots": true}`, ver [`crate::user_lists`])
+//! - `DELETE /connections/{id}/users` (vuelve a `config.json`)
+//! - `GET  /emotes/cache` (estadísticas)
+//! - `POST /emotes/cache/clear`
+//! - `POST /emotes/{id}/invalidate`
+//! - `POST /emotes/providers/{provider}/clear`
+//! - `POST /emotes/channels/{platform}/{channel}/refresh`
+//! - `DELETE /emotes/channels/{platform}/{channel}` (se vuelven a pedir con el
+//!   siguiente mensaje)
+//! - `POST /test-window` (cuerpo opcional `{"username": "...", "message": "..."}`)
+//! - `GET  /moderation` (mensajes descartados y motivos)
+//! - `POST /messages/search` (cuerpo opcional
+//!   `{"text": "\\?$", "regex": true, "user": "...", "type": "normal", "limit": 20}`)
+//! - `POST /messages/replay` (cuerpo `{"ids": ["..."]}`; vuelve a mostrarlos)
+//! - `GET  /rendering` (pausa del renderizado, ver [`crate::pause`])
+//! - `POST /rendering/pause` y `POST /rendering/resume`
+//! - `GET  /themes` (tema con nombre activo y temas disponibles)
+//! - `POST /themes/next` (siguiente tema por orden alfabético)
+//! - `POST /themes/{name}` (tema integrado o de `display.themes`; `next` no
+//!   puede usarse como nombre)
+//! - `DELETE /themes` (vuelve a la apariencia de `config.json`)
+//! - `GET  /metrics` (contadores de [`crate::stats`] en formato Prometheus)
+//! - `GET  /openapi.json` y `GET /docs` (ver [`crate::api_docs`])
+//!
+//! Si `token` está configurado, todas las peticiones deben llevar
+//! `Authorization: Bearer <token>`.
+
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::sync::Arc;
+
+use http_body_util::{BodyExt, Full};
+use hyper::body::{Bytes, Incoming};
+use hyper::server::conn::http1;
+use hyper::service::service_fn;
+use hyper::{Request, Response, StatusCode};
+use hyper_util::rt::TokioIo;
+use schemars::gen::SchemaGenerator;
+use schemars::schema::Schema;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tokio::net::TcpListener;
+
+use crate::command_mode::{CommandMode, CommandModeRequest};
+use crate::config::{ControlConfig, SystemUser};
+use crate::connection::ChatMessage;
+use crate::emotes::cache::CacheStats;
+use crate::emotes::ChannelRefresh;
+use crate::history::HistoryQuery;
+use crate::moderation::ModerationSummary;
+use crate::pause::RenderingStatus;
+use crate::reconnect::ConnectionState;
+use crate::system_users;
+use crate::theme::named::{ThemeStatus, NEXT_THEME};
+use crate::user_lists::{UserLists, UserListsRequest};
+
+/// Estado de una conexión expuesto por la API
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+pub struct ConnectionStatus {
+    pub id: String,
+    pub platform: String,
+    pub channel: String,
+    pub enabled: bool,
+    pub running: bool,
+    /// Último estado conocido (`connected`, `reconnecting`...), si llegó a iniciarse
+    #[serde(flatten)]
+    pub state: Option<ConnectionState>,
+    /// Modo «solo comandos» efectivo, incluidos los cambios en caliente
+    pub command_mode: CommandMode,
+}
+
+/// Cuerpo de `POST /test-window`
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct TestWindowRequest {
+    /// Nombre a mostrar; por defecto el del usuario virtual de diagnóstico
+    pub username: Option<String>,
+    pub message: String,
+}
+
+impl Default for TestWindowRequest {
+    fn default() -> Self {
+        Self {
+            username: None,
+            message: "Test overlay window".to_string(),
+        }
+    }
+}
+
+impl TestWindowRequest {
+    /// Mensaje de sistema que se muestra como ventana de prueba
+    pub fn to_message(&self, user: &SystemUser) -> ChatMessage {
+        let user = SystemUser {
+            name: self.username.clone().unwrap_or_else(|| user.name.clone()),
+            ..user.clone()
+        };
+        system_users::system_message(&user, "diagnostics", self.message.clone(), HashMap::new())
+    }
+}
+
+/// Cuerpo de `POST /messages/replay`
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+pub struct ReplayRequest {
+    /// Ids de mensajes del historial, en el orden en que se mostrarán
+    pub ids: Vec<String>,
+}
+
+/// Operaciones que la aplicación expone a la API de control
+#[async_trait::async_trait]
+pub trait ControlHandler: Send + Sync {
+    async fn list_connections(&self) -> Vec<ConnectionStatus>;
+
+    async fn start_connection(&self, id: &str) -> Result<(), String>;
+
+    async fn stop_connection(&self, id: &str) -> Result<(), String>;
+
+    async fn command_mode(&self, id: &str) -> Result<CommandMode, String>;
+
+    async fn set_command_mode(
+        &self,
+        id: &str,
+        request: CommandModeRequest,
+    ) -> Result<CommandMode, String>;
+
+    /// Descarta el cambio en caliente y vuelve al modo de `config.json`
+    async fn reset_command_mode(&self, id: &str) -> Result<CommandMode, String>;
+
+    async fn user_lists(&self, id: &str) -> Result<UserLists, String>;
+
+    async fn set_user_lists(
+        &self,
+        id: &str,
+        request: UserListsRequest,
+    ) -> Result<UserLists, String>;
+
+    /// Descarta los cambios en caliente y vuelve a las listas de `config.json`
+    async fn reset_user_lists(&self, id: &str) -> Result<UserLists, String>;
+
+    async fn clear_emote_cache(&self) -> Result<(), String>;
+
+    async fn emote_cache_stats(&self) -> CacheStats;
+
+    /// `Ok(false)` si el emote no estaba en cache
+    async fn invalidate_emote(&self, id: &str) -> Result<bool, String>;
+
+    /// Número de emotes eliminados del proveedor
+    async fn clear_emote_provider(&self, provider: &str) -> Result<usize, String>;
+
+    async fn refresh_channel_emotes(
+        &self,
+        platform: &str,
+        channel: &str,
+    ) -> Result<ChannelRefresh, String>;
+
+    /// `Ok(false)` si los emotes del canal no estaban cargados
+    async fn invalidate_channel_emotes(
+        &self,
+        platform: &str,
+        channel: &str,
+    ) -> Result<bool, String>;
+
+    async fn show_test_window(&self, request: TestWindowRequest) -> Result<(), String>;
+
+    async fn moderation_summary(&self) -> ModerationSummary;
+
+    /// Mensajes recientes de la sesión que cumplen la búsqueda
+    async fn search_messages(&self, query: HistoryQuery) -> Result<Vec<ChatMessage>, String>;
+
+    /// Vuelve a mostrar mensajes del historial; devuelve cuántos se encontraron
+    async fn replay_messages(&self, ids: Vec<String>) -> Result<usize, String>;
+
+    async fn rendering_status(&self) -> RenderingStatus;
+
+    /// Pausa o reanuda el renderizado; las conexiones siguen abiertas
+    async fn set_rendering_paused(&self, paused: bool) -> Result<RenderingStatus, String>;
+
+    async fn themes(&self) -> ThemeStatus;
+
+    /// Activa el tema `name`; con `None` vuelve a la apariencia de `config.json`
+    async fn switch_theme(&self, name: Option<String>) -> Result<ThemeStatus, String>;
+
+    /// Activa el siguiente tema por orden alfabético
+    async fn next_theme(&self) -> Result<ThemeStatus, String>;
+}
+
+type SchemaFn = fn(&mut SchemaGenerator) -> Schema;
+
+fn schema<T: JsonSchema>(generator: &mut SchemaGenerator) -> Schema {
+    generator.subschema_for::<T>()
+}
+
+/// Endpoint de la API; `/openapi.json` y `/docs` se generan a partir de [`ENDPOINTS`]
+#[derive(Debug, Clone, Copy)]
+pub struct Endpoint {
+    pub method: &'static str,
+    /// Ruta con los parámetros entre llaves (`/connections/{id}/start`)
+    pub path: &'static str,
+    pub summary: &'static str,
+    /// Esquema del cuerpo JSON, si acepta uno
+    pub request: Option<SchemaFn>,
+    /// Clave principal de la respuesta y su esquema
+    pub response: Option<(&'static str, SchemaFn)>,
+}
+
+/// Endpoints que atiende [`route`]
+pub const ENDPOINTS: &[Endpoint] = &[
+    Endpoint {
+        method: "GET",
+        path: "/health",
+        summary: "Estado del servidor",
+        request: None,
+        response: Some(("status", schema::<String>)),
+    },
+    Endpoint {
+        method: "GET",
+        path: "/connections",
+        summary: "Conexiones configuradas, su estado y su modo «solo comandos»",
+        request: None,
+        response: Some(("connections", schema::<Vec<ConnectionStatus>>)),
+    },
+    Endpoint {
+        method: "POST",
+        path: "/connections/{id}/start",
+        summary: "Inicia una conexión",
+        request: None,
+        response: Some(("action", schema::<String>)),
+    },
+    Endpoint {
+        method: "POST",
+        path: "/connections/{id}/stop",
+        summary: "Detiene una conexión",
+        request: None,
+        response: Some(("action", schema::<String>)),
+    },
+    Endpoint {
+        method: "GET",
+        path: "/connections/{id}/commands-only",
+        summary: "Modo «solo comandos» efectivo y sus prefijos",
+        request: None,
+        response: Some(("command_mode", schema::<CommandMode>)),
+    },
+    Endpoint {
+        method: "POST",
+        path: "/connections/{id}/commands-only",
+        summary: "Cambia el modo «solo comandos»; sin cuerpo lo invierte",
+        request: Some(schema::<CommandModeRequest>),
+        response: Some(("command_mode", schema::<CommandMode>)),
+    },
+    Endpoint {
+        method: "DELETE",
+        path: "/connections/{id}/commands-only",
+        summary: "Vuelve al modo de config.json",
+        request: None,
+        response: Some(("command_mode", schema::<CommandMode>)),
+    },
+    Endpoint {
+        method: "GET",
+        path: "/connections/{id}/users",
+        summary: "Usuarios bloqueados y permitidos y filtro de bots efectivos",
+        request: None,
+        response: Some(("users", schema::<UserLists>)),
+    },
+    Endpoint {
+        method: "POST",
+        path: "/connections/{id}/users",
+        summary: "Bloquea o permite usuarios y activa el filtro de bots",
+        request: Some(schema::<UserListsRequest>),
+        response: Some(("users", schema::<UserLists>)),
+    },
+    Endpoint {
+        method: "DELETE",
+        path: "/connections/{id}/users",
+        summary: "Vuelve a las listas de config.json",
+        request: None,
+        response: Some(("users", schema::<UserLists>)),
+    },
+    Endpoint {
+        method: "GET",
+        path: "/emotes/cache",
+        summary: "Estadísticas de la caché de emotes",
+        request: None,
+        response: Some(("cache", schema::<CacheStats>)),
+    },
+    Endpoint {
+        method: "POST",
+        path: "/emotes/cache/clear",
+        summary: "Vacía la caché de emotes",
+        request: None,
+        response: Some(("cleared", schema::<bool>)),
+    },
+    Endpoint {
+        method: "POST",
+        path: "/emotes/{id}/invalidate",
+        summary: "Invalida un emote (caché e imagen descargada)",
+        request: None,
+        response: Some(("invalidated", schema::<bool>)),
+    },
+    Endpoint {
+        method: "POST",
+        path: "/emotes/providers/{provider}/clear",
+        summary: "Elimina de la caché los emotes de un proveedor",
+        request: None,
+        response: Some(("removed", schema::<usize>)),
+    },
+    Endpoint {
+        method: "POST",
+        path: "/emotes/channels/{platform}/{channel}/refresh",
+        summary: "Vuelve a descargar los emotes del canal",
+        request: None,
+        response: Some(("refresh", schema::<ChannelRefresh>)),
+    },
+    Endpoint {
+        method: "DELETE",
+        path: "/emotes/channels/{platform}/{channel}",
+        summary: "Olvida los sets de emotes del canal; el siguiente mensaje los vuelve a pedir",
+        request: None,
+        response: Some(("invalidated", schema::<bool>)),
+    },
+    Endpoint {
+        method: "POST",
+        path: "/test-window",
+        summary: "Muestra una ventana de prueba",
+        request: Some(schema::<TestWindowRequest>),
+        response: Some(("shown", schema::<bool>)),
+    },
+    Endpoint {
+        method: "GET",
+        path: "/moderation",
+        summary: "Mensajes descartados: total, recuento por motivo y los más recientes",
+        request: None,
+        response: Some(("moderation", schema::<ModerationSummary>)),
+    },
+    Endpoint {
+        method: "POST",
+        path: "/messages/search",
+        summary: "Busca en el historial de la sesión",
+        request: Some(schema::<HistoryQuery>),
+        response: Some(("messages", schema::<Vec<ChatMessage>>)),
+    },
+    Endpoint {
+        method: "POST",
+        path: "/messages/replay",
+        summary: "Vuelve a mostrar mensajes del historial",
+        request: Some(schema::<ReplayRequest>),
+        response: Some(("replayed", schema::<usize>)),
+    },
+    Endpoint {
+        method: "GET",
+        path: "/rendering",
+        summary: "Si el renderizado está en pausa y cuántos mensajes esperan",
+        request: None,
+        response: Some(("rendering", schema::<RenderingStatus>)),
+    },
+    Endpoint {
+        method: "POST",
+        path: "/rendering/pause",
+        summary: "Deja de abrir ventanas y guarda los mensajes en un búfer",
+        request: None,
+        response: Some(("rendering", schema::<RenderingStatus>)),
+    },
+    Endpoint {
+        method: "POST",
+        path: "/rendering/resume",
+        summary: "Reanuda el renderizado y muestra los mensajes guardados",
+        request: None,
+        response: Some(("rendering", schema::<RenderingStatus>)),
+    },
+    Endpoint {
+        method: "GET",
+        path: "/themes",
+        summary: "Tema con nombre activo y temas disponibles",
+        request: None,
+        response: Some(("themes", schema::<ThemeStatus>)),
+    },
+    Endpoint {
+        method: "POST",
+        path: "/themes/next",
+        summary: "Activa el siguiente tema por orden alfabético",
+        request: None,
+        response: Some(("themes", schema::<ThemeStatus>)),
+    },
+    Endpoint {
+        method: "POST",
+        path: "/themes/{name}",
+        summary: "Activa un tema integrado o de `display.themes`",
+        request: None,
+        response: Some(("themes", schema::<ThemeStatus>)),
+    },
+    Endpoint {
+        method: "DELETE",
+        path: "/themes",
+        summary: "Vuelve a la apariencia de `config.json`",
+        request: None,
+        response: Some(("themes", schema::<ThemeStatus>)),
+    },
+    Endpoint {
+        method: "GET",
+        path: "/metrics",
+        summary: "Estadísticas en el formato de texto de Prometheus",
+        request: None,
+        response: None,
+    },
+    Endpoint {
+        method: "GET",
+        path: "/openapi.json",
+        summary: "Este documento OpenAPI",
+        request: None,
+        response: None,
+    },
+    Endpoint {
+        method: "GET",
+        path: "/docs",
+        summary: "Página HTML con los endpoints y los tipos de frame",
+        request: None,
+        response: None,
+    },
+];
+
+/// Respuesta de la API antes de serializarla a HTTP
+#[derive(Debug, Clone, PartialEq)]
+pub struct ControlResponse {
+    pub status: u16,
+    pub body: serde_json::Value,
+    /// `application/json` salvo la página de `/docs`, cuyo cuerpo es un string
+    pub content_type: &'static str,
+}
+
+impl ControlResponse {
+    fn ok(body: serde_json::Value) -> Self {
+        Self {
+            status: 200,
+            body,
+            content_type: "application/json",
+        }
+    }
+
+    fn html(page: String) -> Self {
+        Self::text(page, "text/html; charset=utf-8")
+    }
+
+    fn text(body: String, content_type: &'static str) -> Self {
+        Self {
+            status: 200,
+            body: serde_json::Value::String(body),
+            content_type,
+        }
+    }
+
+    fn error(status: u16, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            body: json!({ "error": message.into() }),
+            content_type: "application/json",
+        }
+    }
+
+    fn into_bytes(self) -> Bytes {
+        match self.body {
+            serde_json::Value::String(page) if self.content_type != "application/json" => {
+                Bytes::from(page)
+            }
+            body => Bytes::from(body.to_string()),
+        }
+    }
+}
+
+/// Resuelve una petición contra el `ControlHandler` (sin detalles de HTTP)
+pub async fn route(
+    handler: &dyn ControlHandler,
+    method: &str,
+    path: &str,
+    body: &[u8],
+) -> ControlResponse {
+    let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+
+    match (method, segments.as_slice()) {
+        ("GET", ["health"]) => ControlResponse::ok(json!({ "status": "ok" })),
+        ("GET", ["connections"]) => {
+            ControlResponse::ok(json!({ "connections": handler.list_connections().await }))
+        }
+        ("POST", ["connections", id, action @ ("start" | "stop")]) => {
+            let result = if *action == "start" {
+                handler.start_connection(id).await
+            } else {
+                handler.stop_connection(id).await
+            };
+            match result {
+                Ok(()) => ControlResponse::ok(json!({ "id": id, "action": action })),
+                Err(e) if e.contains("not found") => ControlResponse::error(404, e),
+                Err(e) => ControlResponse::error(409, e),
+            }
+        }
+        (method, ["connections", id, "commands-only"]) => {
+            let result = match method {
+                "GET" => handler.command_mode(id).await,
+                "POST" => {
+                    let request = if body.iter().all(u8::is_ascii_whitespace) {
+                        CommandModeRequest::default()
+                    } else {
+                        match serde_json::from_slice(body) {
+                            Ok(request) => request,
+                            Err(e) => {
+                                return ControlResponse::error(400, format!("Invalid body: {}", e))
+                            }
+                        }
+                    };
+                    handler.set_command_mode(id, request).await
+                }
+                "DELETE" => handler.reset_command_mode(id).await,
+                _ => return ControlResponse::error(405, "Method not allowed"),
+            };
+            match result {
+                Ok(mode) => ControlResponse::ok(json!({ "id": id, "command_mode": mode })),
+                Err(e) if e.contains("not found") => ControlResponse::error(404, e),
+                Err(e) => ControlResponse::error(500, e),
+            }
+        }
+        (method, ["connections", id, "users"]) => {
+            let result = match method {
+                "GET" => handler.user_lists(id).await,
+                "POST" => {
+                    let request = if body.iter().all(u8::is_ascii_whitespace) {
+                        UserListsRequest::default()
+                    } else {
+                        match serde_json::from_slice(body) {
+                            Ok(request) => request,
+                            Err(e) => {
+                                return ControlResponse::error(400, format!("Invalid body: {}", e))
+                            }
+                        }
+                    };
+                    handler.set_user_lists(id, request).await
+                }
+                "DELETE" => handler.reset_user_lists(id).await,
+                _ => return ControlResponse::error(405, "Method not allowed"),
+            };
+            match result {
+                Ok(users) => ControlResponse::ok(json!({ "id": id, "users": users })),
+                Err(e) if e.contains("not found") => ControlResponse::error(404, e),
+                Err(e) => ControlResponse::error(500, e),
+            }
+        }
+        ("GET", ["emotes", "cache"]) => {
+            ControlResponse::ok(json!({ "cache": handler.emote_cache_stats().await }))
+        }
+        ("POST", ["emotes", "cache", "clear"]) => match handler.clear_emote_cache().await {
+            Ok(()) => ControlResponse::ok(json!({ "cleared": true })),
+            Err(e) => ControlResponse::error(500, e),
+        },
+        ("POST", ["emotes", "providers", provider, "clear"]) => {
+            match handler.clear_emote_provider(provider).await {
+                Ok(removed) => {
+                    ControlResponse::ok(json!({ "provider": provider, "removed": removed }))
+                }
+                Err(e) if e.contains("not found") => ControlResponse::error(404, e),
+                Err(e) => ControlResponse::error(500, e),
+            }
+        }
+        ("POST", ["emotes", "channels", platform, channel, "refresh"]) => {
+            match handler.refresh_channel_emotes(platform, channel).await {
+                Ok(refresh) => ControlResponse::ok(json!({ "refresh": refresh })),
+                Err(e) => ControlResponse::error(500, e),
+            }
+        }
+        ("DELETE", ["emotes", "channels", platform, channel]) => {
+            match handler.invalidate_channel_emotes(platform, channel).await {
+                Ok(true) => ControlResponse::ok(json!({ "invalidated": true })),
+                Ok(false) => ControlResponse::error(
+                    404,
+                    format!("Emotes of {}:{} not loaded", platform, channel),
+                ),
+                Err(e) => ControlResponse::error(500, e),
+            }
+        }
+        ("POST", ["emotes", id, "invalidate"]) => match handler.invalidate_emote(id).await {
+            Ok(true) => ControlResponse::ok(json!({ "id": id, "invalidated": true })),
+            Ok(false) => ControlResponse::error(404, format!("Emote '{}' not cached", id)),
+            Err(e) => ControlResponse::error(500, e),
+        },
+        ("POST", ["test-window"]) => {
+            let request = if body.iter().all(u8::is_ascii_whitespace) {
+                TestWindowRequest::default()
+            } else {
+                match serde_json::from_slice(body) {
+                    Ok(request) => request,
+                    Err(e) => return ControlResponse::error(400, format!("Invalid body: {}", e)),
+                }
+            };
+            match handler.show_test_window(request).await {
+                Ok(()) => ControlResponse::ok(json!({ "shown": true })),
+                Err(e) => ControlResponse::error(500, e),
+            }
+        }
+        ("GET", ["moderation"]) => {
+            ControlResponse::ok(json!({ "moderation": handler.moderation_summary().await }))
+        }
+        ("POST", ["messages", "search"]) => {
+            let query = if body.iter().all(u8::is_ascii_whitespace) {
+                HistoryQuery::default()
+            } else {
+                match serde_json::from_slice(body) {
+                    Ok(query) => query,
+                    Err(e) => return ControlResponse::error(400, format!("Invalid body: {}", e)),
+                }
+            };
+            match handler.search_messages(query).await {
+                Ok(messages) => ControlResponse::ok(json!({
+                    "count": messages.len(),
+                    "messages": messages,
+                })),
+                Err(e) => ControlResponse::error(400, e),
+            }
+        }
+        ("POST", ["messages", "replay"]) => {
+            let request: ReplayRequest = match serde_json::from_slice(body) {
+                Ok(request) => request,
+                Err(e) => return ControlResponse::error(400, format!("Invalid body: {}", e)),
+            };
+            if request.ids.is_empty() {
+                return ControlResponse::error(400, "No message ids given");
+            }
+            match handler.replay_messages(request.ids).await {
+                Ok(0) => ControlResponse::error(404, "No messages found in history"),
+                Ok(replayed) => ControlResponse::ok(json!({ "replayed": replayed })),
+                Err(e) => ControlResponse::error(500, e),
+            }
+        }
+        ("GET", ["rendering"]) => {
+            ControlResponse::ok(json!({ "rendering": handler.rendering_status().await }))
+        }
+        ("POST", ["rendering", action @ ("pause" | "resume")]) => {
+            match handler.set_rendering_paused(*action == "pause").await {
+                Ok(status) => ControlResponse::ok(json!({ "rendering": status })),
+                Err(e) => ControlResponse::error(500, e),
+            }
+        }
+        ("GET", ["themes"]) => ControlResponse::ok(json!({ "themes": handler.themes().await })),
+        ("POST", ["themes", NEXT_THEME]) => match handler.next_theme().await {
+            Ok(themes) => ControlResponse::ok(json!({ "themes": themes })),
+            Err(e) => ControlResponse::error(500, e),
+        },
+        ("POST", ["themes", name]) => {
+            match handler.switch_theme(Some(name.to_string())).await {
+                Ok(themes) => ControlResponse::ok(json!({ "themes": themes })),
+                Err(e) if e.contains("not found") => ControlResponse::error(404, e),
+                Err(e) => ControlResponse::error(500, e),
+            }
+        }
+        ("DELETE", ["themes"]) => match handler.switch_theme(None).await {
+            Ok(themes) => ControlResponse::ok(json!({ "themes": themes })),
+            Err(e) => ControlResponse::error(500, e),
+        },
+        ("GET", ["metrics"]) => ControlResponse::text(
+            crate::stats::Stats::global().snapshot().to_prometheus(),
+            "text/plain; version=0.0.4; charset=utf-8",
+        ),
+        ("GET", ["openapi.json"]) => ControlResponse::ok(crate::api_docs::openapi()),
+        ("GET", ["docs"]) => ControlResponse::html(crate::api_docs::html_page()),
+        (
+            _,
+            ["health"]
+            | ["connections", ..]
+            | ["emotes", ..]
+            | ["test-window"]
+            | ["moderation"]
+            | ["messages", ..]
+            | ["rendering", ..]
+            | ["themes", ..]
+            | ["metrics"]
+            | ["openapi.json"]
+            | ["docs"],
+        ) => ControlResponse::error(405, "Method not allowed"),
+        _ => ControlResponse::error(404, "Not found"),
+    }
+}
+
+fn is_authorized(token: Option<&str>, header: Option<&str>) -> bool {
+    match token {
+        None => true,
+        Some(token) => header
+            .and_then(|h| h.strip_prefix("Bearer "))
+            .is_some_and(|provided| provided == token),
+    }
+}
+
+async fn respond(
+    handler: Arc<dyn ControlHandler>,
+    token: Option<String>,
+    request: Request<Incoming>,
+) -> Response<Full<Bytes>> {
+    let authorization = request
+        .headers()
+        .get(hyper::header::AUTHORIZATION)
+        .and_then(|h| h.to_str().ok())
+        .map(str::to_string);
+
+    let response = if !is_authorized(token.as_deref(), authorization.as_deref()) {
+        ControlResponse::error(401, "Unauthorized")
+    } else {
+        let method = request.method().as_str().to_string();
+        let path = request.uri().path().to_string();
+        match request.into_body().collect().await {
+            Ok(body) => route(handler.as_ref(), &method, &path, &body.to_bytes()).await,
+            Err(e) => ControlResponse::error(400, format!("Failed to read body: {}", e)),
+        }
+    };
+
+    let status = StatusCode::from_u16(response.status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+    let content_type = hyper::header::HeaderValue::from_static(response.content_type);
+    let mut http_response = Response::new(Full::new(response.into_bytes()));
+    *http_response.status_mut() = status;
+    http_response
+        .headers_mut()
+        .insert(hyper::header::CONTENT_TYPE, content_type);
+    http_response
+}
+
+/// Inicia el servidor de control en segundo plano
+pub async fn serve(
+    config: ControlConfig,
+    handler: Arc<dyn ControlHandler>,
+) -> std::io::Result<tokio::task::JoinHandle<()>> {
+    let listener = TcpListener::bind(&config.bind).await?;
+    println!(
+        "🎛️ Control API listening on http://{}",
+        listener.local_addr()?
+    );
+
+    Ok(tokio::spawn(async move {
+        loop {
+            let stream = match listener.accept().await {
+                Ok((stream, _)) => stream,
+                Err(e) => {
+                    eprintln!("⚠️ Control API accept failed: {}", e);
+                    continue;
+                }
+            };
+
+            let handler = handler.clone();
+            let token = config.token.clone();
+            tokio::spawn(async move {
+                let service = service_fn(move |request| {
+                    let handler = handler.clone();
+                    let token = token.clone();
+                    async move { Ok::<_, Infallible>(respond(handler, token, request).await) }
+                });
+                if let Err(e) = http1::Builder::new()
+                    .serve_connection(TokioIo::new(stream), service)
+                    .await
+                {
+                    eprintln!("⚠️ Control API connection error: {}", e);
+                }
+            });
+        }
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct MockHandler {
+        calls: Mutex<Vec<String>>,
+    }
+
+    #[async_trait::async_trait]
+    impl ControlHandler for MockHandler {
+        async fn list_connections(&self) -> Vec<ConnectionStatus> {
+            vec![ConnectionStatus {
+                id: "main".to_string(),
+                platform: "twitch".to_string(),
+                channel: "foo".to_string(),
+                enabled: true,
+                running: true,
+                state: Some(ConnectionState::Reconnecting {
+                    attempt: 2,
+                    delay_ms: 2000,
+                }),
+                command_mode: CommandMode {
+                    commands_only: false,
+                    prefixes: vec![],
+                    overridden: false,
+                },
+            }]
+        }
+
+        async fn start_connection(&self, id: &str) -> Result<(), String> {
+            self.calls.lock().unwrap().push(format!("start {}", id));
+            if id == "main" {
+                Ok(())
+            } else {
+                Err("Connection not found".to_string())
+            }
+        }
+
+        async fn stop_connection(&self, id: &str) -> Result<(), String> {
+            self.calls.lock().unwrap().push(format!("stop {}", id));
+            Ok(())
+        }
+
+        async fn command_mode(&self, id: &str) -> Result<CommandMode, String> {
+            if id != "main" {
+                return Err("Connection not found".to_string());
+            }
+            Ok(CommandMode {
+                commands_only: false,
+                prefixes: vec![],
+                overridden: false,
+            })
+        }
+
+        async fn set_command_mode(
+            &self,
+            id: &str,
+            request: CommandModeRequest,
+        ) -> Result<CommandMode, String> {
+            self.calls
+                .lock()
+                .unwrap()
+                .push(format!("commands-only {} {:?}", id, request.enabled));
+            Ok(CommandMode {
+                commands_only: request.enabled.unwrap_or(true),
+                prefixes: request.prefixes.unwrap_or_default(),
+                overridden: true,
+            })
+        }
+
+        async fn reset_command_mode(&self, id: &str) -> Result<CommandMode, String> {
+            self.calls.lock().unwrap().push(format!("reset {}", id));
+            self.command_mode(id).await
+        }
+
+        async fn user_lists(&self, id: &str) -> Result<UserLists, String> {
+            if id != "main" {
+                return Err("Connection not found".to_string());
+            }
+            Ok(UserLists {
+                blocked_users: vec![],
+                allowed_users: vec![],
+                allowed_users_only: true,
+                ignore_bots: false,
+                overridden: false,
+            })
+        }
+
+        async fn set_user_lists(
+            &self,
+            id: &str,
+            request: UserListsRequest,
+        ) -> Result<UserLists, String> {
+            self.calls
+                .lock()
+                .unwrap()
+                .push(format!("users {} {:?}", id, request.block));
+            Ok(UserLists {
+                blocked_users: request.block,
+                allowed_users: request.allow,
+                allowed_users_only: request.allowed_users_only.unwrap_or(true),
+                ignore_bots: request.ignore_bots.unwrap_or(false),
+                overridden: true,
+            })
+        }
+
+        async fn reset_user_lists(&self, id: &str) -> Result<UserLists, String> {
+            self.calls
+                .lock()
+                .unwrap()
+                .push(format!("reset users {}", id));
+            self.user_lists(id).await
+        }
+
+        async fn clear_emote_cache(&self) -> Result<(), String> {
+            self.calls.lock().unwrap().push("clear".to_string());
+            Ok(())
+        }
+
+        async fn emote_cache_stats(&self) -> CacheStats {
+            CacheStats::default()
+        }
+
+        async fn invalidate_emote(&self, id: &str) -> Result<bool, String> {
+            self.calls.lock().unwrap().push(format!("invalidate {}", id));
+            Ok(id == "25")
+        }
+
+        async fn clear_emote_provider(&self, provider: &str) -> Result<usize, String> {
+            self.calls
+                .lock()
+                .unwrap()
+                .push(format!("clear {}", provider));
+            match provider {
+                "7tv" => Ok(3),
+                _ => Err(format!("Provider '{}' not found", provider)),
+            }
+        }
+
+        async fn refresh_channel_emotes(
+            &self,
+            platform: &str,
+            channel: &str,
+        ) -> Result<ChannelRefresh, String> {
+            Ok(ChannelRefresh {
+                platform: platform.to_string(),
+                channel: channel.to_string(),
+                ..ChannelRefresh::default()
+            })
+        }
+
+        async fn invalidate_channel_emotes(
+            &self,
+            _platform: &str,
+            channel: &str,
+        ) -> Result<bool, String> {
+            Ok(channel == "foo")
+        }
+
+        async fn show_test_window(&self, request: TestWindowRequest) -> Result<(), String> {
+            self.calls
+                .lock()
+                .unwrap()
+                .push(format!("test {}", request.message));
+            Ok(())
+        }
+
+        async fn moderation_summary(&self) -> ModerationSummary {
+            ModerationSummary {
+                total: 2,
+                by_reason: [("too_long".to_string(), 2)].into_iter().collect(),
+                recent: Vec::new(),
+            }
+        }
+
+        async fn search_messages(&self, query: HistoryQuery) -> Result<Vec<ChatMessage>, String> {
+            let user = SystemUser::default();
+            let message = system_users::system_message(&user, "diagnostics", "¿hola?", HashMap::new());
+            match query.text.as_deref() {
+                Some("(") => Err("Invalid regex".to_string()),
+                Some(text) if !message.content.contains(text) => Ok(Vec::new()),
+                _ => Ok(vec![message]),
+            }
+        }
+
+        async fn replay_messages(&self, ids: Vec<String>) -> Result<usize, String> {
+            self.calls
+                .lock()
+                .unwrap()
+                .push(format!("replay {}", ids.join(",")));
+            Ok(ids.iter().filter(|id| id.starts_with("msg-")).count())
+        }
+
+        async fn rendering_status(&self) -> RenderingStatus {
+            RenderingStatus::default()
+        }
+
+        async fn set_rendering_paused(&self, paused: bool) -> Result<RenderingStatus, String> {
+            self.calls
+                .lock()
+                .unwrap()
+                .push(format!("paused {}", paused));
+            Ok(RenderingStatus {
+                paused,
+                ..RenderingStatus::default()
+            })
+        }
+
+        async fn themes(&self) -> ThemeStatus {
+            ThemeStatus {
+                active: None,
+                available: vec!["dark".to_string(), "light".to_string()],
+            }
+        }
+
+        async fn switch_theme(&self, name: Option<String>) -> Result<ThemeStatus, String> {
+            match name.as_deref() {
+                Some(name @ ("dark" | "light" | "next")) => {
+                    self.calls.lock().unwrap().push(format!("theme {}", name));
+                    Ok(ThemeStatus {
+                        active: Some(name.to_string()),
+                        ..self.themes().await
+                    })
+                }
+                Some(name) => Err(format!("Theme '{}' not found", name)),
+                None => Ok(self.themes().await),
+            }
+        }
+
+        async fn next_theme(&self) -> Result<ThemeStatus, String> {
+            self.switch_theme(Some("next".to_string())).await
+        }
+    }
+
+    #[tokio::test]
+    async fn test_routes_dispatch_to_handler() {
+        let handler = MockHandler::default();
+
+        let list = route(&handler, "GET", "/connections", b"").await;
+        assert_eq!(list.status, 200);
+        assert_eq!(list.body["connections"][0]["id"], "main");
+        assert_eq!(list.body["connections"][0]["state"], "reconnecting");
+        assert_eq!(list.body["connections"][0]["attempt"], 2);
+
+        assert_eq!(
+            route(&handler, "POST", "/connections/main/stop", b"")
+                .await
+                .status,
+            200
+        );
+        assert_eq!(
+            route(&handler, "POST", "/connections/other/start", b"")
+                .await
+                .status,
+            404
+        );
+        assert_eq!(
+            route(&handler, "POST", "/emotes/cache/clear", b"")
+                .await
+                .status,
+            200
+        );
+        assert_eq!(
+            route(&handler, "POST", "/test-window", br#"{"message":"hi"}"#)
+                .await
+                .status,
+            200
+        );
+
+        assert_eq!(
+            *handler.calls.lock().unwrap(),
+            vec!["stop main", "start other", "clear", "test hi"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_user_lists_routes() {
+        let handler = MockHandler::default();
+
+        let current = route(&handler, "GET", "/connections/main/users", b"").await;
+        assert_eq!(current.status, 200);
+        assert_eq!(current.body["users"]["ignore_bots"], false);
+        assert_eq!(
+            route(&handler, "GET", "/connections/other/users", b"")
+                .await
+                .status,
+            404
+        );
+
+        let blocked = route(
+            &handler,
+            "POST",
+            "/connections/main/users",
+            br#"{"block":["troll"],"ignore_bots":true}"#,
+        )
+        .await;
+        assert_eq!(blocked.body["users"]["blocked_users"][0], "troll");
+        assert_eq!(blocked.body["users"]["ignore_bots"], true);
+        assert_eq!(
+            route(&handler, "POST", "/connections/main/users", b"{oops")
+                .await
+                .status,
+            400
+        );
+
+        let reset = route(&handler, "DELETE", "/connections/main/users", b"").await;
+        assert_eq!(reset.body["users"]["overridden"], false);
+        assert_eq!(
+            *handler.calls.lock().unwrap(),
+            vec!["users main [\"troll\"]", "reset users main"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_command_mode_routes() {
+        let handler = MockHandler::default();
+
+        let current = route(&handler, "GET", "/connections/main/commands-only", b"").await;
+        assert_eq!(current.status, 200);
+        assert_eq!(current.body["command_mode"]["commands_only"], false);
+        assert_eq!(
+            route(&handler, "GET", "/connections/other/commands-only", b"")
+                .await
+                .status,
+            404
+        );
+
+        let toggled = route(&handler, "POST", "/connections/main/commands-only", b"").await;
+        assert_eq!(toggled.body["command_mode"]["commands_only"], true);
+
+        let questions = route(
+            &handler,
+            "POST",
+            "/connections/main/commands-only",
+            br#"{"enabled":true,"prefixes":["!question"]}"#,
+        )
+        .await;
+        assert_eq!(questions.body["command_mode"]["prefixes"][0], "!question");
+        assert_eq!(
+            route(&handler, "POST", "/connections/main/commands-only", b"{oops")
+                .await
+                .status,
+            400
+        );
+
+        let reset = route(&handler, "DELETE", "/connections/main/commands-only", b"").await;
+        assert_eq!(reset.body["command_mode"]["overridden"], false);
+        assert_eq!(
+            *handler.calls.lock().unwrap(),
+            vec![
+                "commands-only main None",
+                "commands-only main Some(true)",
+                "reset main"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_emote_cache_routes() {
+        let handler = MockHandler::default();
+
+        let stats = route(&handler, "GET", "/emotes/cache", b"").await;
+        assert_eq!(stats.status, 200);
+        assert!(stats.body["cache"]["by_source"].is_object());
+
+        assert_eq!(
+            route(&handler, "POST", "/emotes/25/invalidate", b"").await.status,
+            200
+        );
+        assert_eq!(
+            route(&handler, "POST", "/emotes/missing/invalidate", b"")
+                .await
+                .status,
+            404
+        );
+
+        let cleared = route(&handler, "POST", "/emotes/providers/7tv/clear", b"").await;
+        assert_eq!(cleared.body["removed"], 3);
+        assert_eq!(
+            route(&handler, "POST", "/emotes/providers/nope/clear", b"")
+                .await
+                .status,
+            404
+        );
+
+        let refresh = route(&handler, "POST", "/emotes/channels/twitch/foo/refresh", b"").await;
+        assert_eq!(refresh.status, 200);
+        assert_eq!(refresh.body["refresh"]["channel"], "foo");
+
+        let invalidated = route(&handler, "DELETE", "/emotes/channels/twitch/foo", b"").await;
+        assert_eq!(invalidated.body["invalidated"], true);
+        assert_eq!(
+            route(&handler, "DELETE", "/emotes/channels/twitch/bar", b"")
+                .await
+                .status,
+            404
+        );
+    }
+
+    #[tokio::test]
+    async fn test_unknown_routes_and_bad_bodies() {
+        let handler = MockHandler::default();
+        assert_eq!(route(&handler, "GET", "/nope", b"").await.status, 404);
+        assert_eq!(
+            route(&handler, "DELETE", "/connections", b"").await.status,
+            405
+        );
+        assert_eq!(
+            route(&handler, "POST", "/test-window", b"{oops")
+                .await
+                .status,
+            400
+        );
+        assert_eq!(
+            route(&handler, "POST", "/moderation", b"").await.status,
+            405
+        );
+    }
+
+    #[tokio::test]
+    async fn test_moderation_summary_route() {
+        let handler = MockHandler::default();
+        let response = route(&handler, "GET", "/moderation", b"").await;
+        assert_eq!(response.status, 200);
+        assert_eq!(response.body["moderation"]["total"], 2);
+        assert_eq!(response.body["moderation"]["by_reason"]["too_long"], 2);
+    }
+
+    #[tokio::test]
+    async fn test_documented_endpoints_are_routed() {
+        let handler = MockHandler::default();
+        for endpoint in ENDPOINTS {
+            // `25` es el emote que conoce el mock
+            let path = endpoint
+                .path
+                .replace("/emotes/{id}", "/emotes/25")
+                .replace("{id}", "main")
+                .replace("{provider}", "7tv")
+                .replace("{platform}", "twitch")
+                .replace("{channel}", "foo")
+                .replace("{name}", "dark");
+            let body: &[u8] = if endpoint.path == "/messages/replay" {
+                br#"{"ids":["msg-1"]}"#
+            } else {
+                b""
+            };
+            let response = route(&handler, endpoint.method, &path, body).await;
+            assert_eq!(response.status, 200, "{} {}", endpoint.method, path);
+        }
+
+        let metrics = route(&handler, "GET", "/metrics", b"").await;
+        assert!(metrics.content_type.starts_with("text/plain"));
+        assert!(metrics.into_bytes().starts_with(b"# HELP overlay_uptime_seconds"));
+
+        let docs = route(&handler, "GET", "/docs", b"").await;
+        assert_eq!(docs.content_type, "text/html; charset=utf-8");
+        assert!(docs.into_bytes().starts_with(b"<!DOCTYPE html>"));
+    }
+
+    #[tokio::test]
+    async fn test_message_search_and_replay_routes() {
+        let handler = MockHandler::default();
+        let all = route(&handler, "POST", "/messages/search", b"").await;
+        assert_eq!(all.status, 200);
+        assert_eq!(all.body["count"], 1);
+        assert_eq!(all.body["messages"][0]["content"], "¿hola?");
+
+        let none = route(&handler, "POST", "/messages/search", br#"{"text":"bye"}"#).await;
+        assert_eq!(none.body["count"], 0);
+        assert_eq!(
+            route(&handler, "POST", "/messages/search", br#"{"text":"(","regex":true}"#)
+                .await
+                .status,
+            400
+        );
+
+        let replayed = route(
+            &handler,
+            "POST",
+            "/messages/replay",
+            br#"{"ids":["msg-1","msg-2"]}"#,
+        )
+        .await;
+        assert_eq!(replayed.body["replayed"], 2);
+        assert_eq!(
+            route(&handler, "POST", "/messages/replay", br#"{"ids":["gone"]}"#)
+                .await
+                .status,
+            404
+        );
+        assert_eq!(
+            route(&handler, "POST", "/messages/replay", br#"{"ids":[]}"#)
+                .await
+                .status,
+            400
+        );
+        assert_eq!(
+            route(&handler, "GET", "/messages/search", b"").await.status,
+            405
+        );
+        assert!(handler
+            .calls
+            .lock()
+            .unwrap()
+            .contains(&"replay msg-1,msg-2".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_rendering_routes() {
+        let handler = MockHandler::default();
+        let status = route(&handler, "GET", "/rendering", b"").await;
+        assert_eq!(status.body["rendering"]["paused"], false);
+
+        let paused = route(&handler, "POST", "/rendering/pause", b"").await;
+        assert_eq!(paused.body["rendering"]["paused"], true);
+        let resumed = route(&handler, "POST", "/rendering/resume", b"").await;
+        assert_eq!(resumed.body["rendering"]["paused"], false);
+        assert_eq!(
+            route(&handler, "POST", "/rendering/stop", b"").await.status,
+            405
+        );
+        assert_eq!(
+            *handler.calls.lock().unwrap(),
+            vec!["paused true", "paused false"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_theme_routes() {
+        let handler = MockHandler::default();
+        let themes = route(&handler, "GET", "/themes", b"").await;
+        assert_eq!(themes.body["themes"]["available"][1], "light");
+
+        let light = route(&handler, "POST", "/themes/light", b"").await;
+        assert_eq!(light.body["themes"]["active"], "light");
+        assert_eq!(
+            route(&handler, "POST", "/themes/neon", b"").await.status,
+            404
+        );
+        let reset = route(&handler, "DELETE", "/themes", b"").await;
+        assert!(reset.body["themes"]["active"].is_null());
+        route(&handler, "POST", "/themes/next", b"").await;
+        assert_eq!(
+            route(&handler, "PUT", "/themes", b"").await.status,
+            405
+        );
+        assert_eq!(
+            *handler.calls.lock().unwrap(),
+            vec!["theme light", "theme next"]
+        );
+    }
+
+    #[test]
+    fn test_bearer_token_authorization() {
+        assert!(is_authorized(None, None));
+        assert!(is_authorized(Some("secret"), Some("Bearer secret")));
+        assert!(!is_authorized(Some("secret"), Some("Bearer wrong")));
+        assert!(!is_authorized(Some("secret"), None));
+    }
+}
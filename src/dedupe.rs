@@ -0,0 +1,285 @@
+//! Agrupación de mensajes repetidos y copypastas
+//!
+//! Un mensaje de chat igual o casi igual (mismas letras sin contar
+//! mayúsculas, espacios, puntuación ni letras alargadas como `LOOOL`) a otro
+//! mostrado hace menos de `window_secs` no abre ventana nueva: la ventana del
+//! primero muestra un contador `×12` y sigue visible mientras llegan
+//! repeticiones. Con `across_users` se agrupan también las copypastas que
+//! pegan muchos usuarios a la vez.
+
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::connection::{ChatMessage, MessageType};
+
+static GLOBAL_DEDUPLICATOR: OnceLock<MessageDeduplicator> = OnceLock::new();
+
+/// `dedupe`
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct DedupeConfig {
+    /// Desactivado por defecto: cada mensaje abre su ventana
+    pub enabled: bool,
+    /// Segundos desde la última repetición durante los que se sigue agrupando
+    pub window_secs: u64,
+    /// Agrupa también el mismo texto enviado por usuarios distintos
+    pub across_users: bool,
+}
+
+impl Default for DedupeConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            window_secs: 10,
+            across_users: true,
+        }
+    }
+}
+
+/// Qué hacer con un mensaje
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DedupeVerdict {
+    /// Mensaje nuevo: se muestra en su propia ventana
+    Display,
+    /// Repetición de `message_id`, que ya suma `count` apariciones
+    Repeat { message_id: String, count: u32 },
+}
+
+#[derive(Debug)]
+struct Entry {
+    message_id: String,
+    count: u32,
+    last_seen: Instant,
+}
+
+#[derive(Debug, Default)]
+struct DedupeState {
+    config: DedupeConfig,
+    /// Clave normalizada -> mensaje mostrado
+    entries: HashMap<String, Entry>,
+}
+
+impl DedupeState {
+    fn key(&self, message: &ChatMessage) -> Option<String> {
+        // Los eventos (suscripciones, raids...) merecen cada uno su alerta
+        if !matches!(
+            message.message_type,
+            MessageType::Normal | MessageType::Action
+        ) {
+            return None;
+        }
+        let text = normalize(&message.content)?;
+        Some(if self.config.across_users {
+            text
+        } else {
+            format!("{}\n{}", message.username.to_lowercase(), text)
+        })
+    }
+
+    fn window(&self) -> Duration {
+        Duration::from_secs(self.config.window_secs)
+    }
+}
+
+/// Texto comparable: minúsculas, solo letras y números, sin letras repetidas
+/// seguidas; los mensajes sin letras (solo emojis) conservan sus símbolos
+fn normalize(content: &str) -> Option<String> {
+    let lower = content.to_lowercase();
+    let text = collapse_repeats(lower.chars().filter(|c| c.is_alphanumeric()));
+    if !text.is_empty() {
+        return Some(text);
+    }
+    let symbols = collapse_repeats(lower.chars().filter(|c| !c.is_whitespace()));
+    (!symbols.is_empty()).then_some(symbols)
+}
+
+fn collapse_repeats(chars: impl Iterator<Item = char>) -> String {
+    let mut text = String::new();
+    for c in chars {
+        if !text.ends_with(c) {
+            text.push(c);
+        }
+    }
+    text
+}
+
+/// Detector de repeticiones, compartido por todas las conexiones
+#[derive(Debug, Default)]
+pub struct MessageDeduplicator {
+    state: Mutex<DedupeState>,
+}
+
+impl MessageDeduplicator {
+    pub fn new(config: DedupeConfig) -> Self {
+        Self {
+            state: Mutex::new(DedupeState {
+                config,
+                entries: HashMap::new(),
+            }),
+        }
+    }
+
+    pub fn global() -> &'static MessageDeduplicator {
+        GLOBAL_DEDUPLICATOR.get_or_init(|| Self::new(DedupeConfig::default()))
+    }
+
+    pub fn configure(&self, config: DedupeConfig) {
+        let mut state = self.lock();
+        state.config = config;
+        state.entries.clear();
+    }
+
+    /// Registra el mensaje y decide si es una repetición de otro reciente
+    pub fn observe(&self, message: &ChatMessage, now: Instant) -> DedupeVerdict {
+        let mut state = self.lock();
+        if !state.config.enabled {
+            return DedupeVerdict::Display;
+        }
+        let Some(key) = state.key(message) else {
+            return DedupeVerdict::Display;
+        };
+
+        let window = state.window();
+        state
+            .entries
+            .retain(|_, entry| now.saturating_duration_since(entry.last_seen) < window);
+
+        match state.entries.get_mut(&key) {
+            Some(entry) => {
+                entry.count += 1;
+                entry.last_seen = now;
+                DedupeVerdict::Repeat {
+                    message_id: entry.message_id.clone(),
+                    count: entry.count,
+                }
+            }
+            None => {
+                state.entries.insert(
+                    key,
+                    Entry {
+                        message_id: message.id.clone(),
+                        count: 1,
+                        last_seen: now,
+                    },
+                );
+                DedupeVerdict::Display
+            }
+        }
+    }
+
+    /// Empieza un grupo nuevo con `message` como original, p. ej. porque la
+    /// ventana del anterior ya se cerró
+    pub fn restart(&self, message: &ChatMessage, now: Instant) {
+        let mut state = self.lock();
+        if let Some(key) = state.key(message) {
+            state.entries.insert(
+                key,
+                Entry {
+                    message_id: message.id.clone(),
+                    count: 1,
+                    last_seen: now,
+                },
+            );
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, DedupeState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn enabled(across_users: bool) -> MessageDeduplicator {
+        MessageDeduplicator::new(DedupeConfig {
+            enabled: true,
+            window_secs: 10,
+            across_users,
+        })
+    }
+
+    #[test]
+    fn test_copypasta_from_many_users_is_counted_on_the_first_message() {
+        let dedupe = enabled(true);
+        let start = Instant::now();
+        assert_eq!(
+            dedupe.observe(
+                &ChatMessage::test("alice", "KEKW what a play").with_id("m1"),
+                start
+            ),
+            DedupeVerdict::Display
+        );
+        assert_eq!(
+            dedupe.observe(
+                &ChatMessage::test("bob", "kekw WHAT a plaaay!!").with_id("m2"),
+                start
+            ),
+            DedupeVerdict::Repeat {
+                message_id: "m1".to_string(),
+                count: 2
+            }
+        );
+        // Cada repetición alarga el plazo
+        let later = start + Duration::from_secs(8);
+        assert!(matches!(
+            dedupe.observe(
+                &ChatMessage::test("carol", "KEKW what a play").with_id("m3"),
+                later
+            ),
+            DedupeVerdict::Repeat { count: 3, .. }
+        ));
+        assert_eq!(
+            dedupe.observe(&ChatMessage::test("dave", "🔥🔥").with_id("m4"), later),
+            DedupeVerdict::Display
+        );
+        assert!(matches!(
+            dedupe.observe(&ChatMessage::test("erin", "🔥 🔥 🔥").with_id("m5"), later),
+            DedupeVerdict::Repeat { .. }
+        ));
+
+        let expired = later + Duration::from_secs(10);
+        assert_eq!(
+            dedupe.observe(
+                &ChatMessage::test("frank", "KEKW what a play").with_id("m6"),
+                expired
+            ),
+            DedupeVerdict::Display
+        );
+    }
+
+    #[test]
+    fn test_per_user_mode_and_restart() {
+        let dedupe = enabled(false);
+        let now = Instant::now();
+        assert_eq!(
+            dedupe.observe(&ChatMessage::test("alice", "first").with_id("m1"), now),
+            DedupeVerdict::Display
+        );
+        assert_eq!(
+            dedupe.observe(&ChatMessage::test("bob", "first").with_id("m2"), now),
+            DedupeVerdict::Display
+        );
+        assert!(matches!(
+            dedupe.observe(&ChatMessage::test("Alice", "FIRST").with_id("m3"), now),
+            DedupeVerdict::Repeat { count: 2, .. }
+        ));
+
+        dedupe.restart(&ChatMessage::test("alice", "first").with_id("m4"), now);
+        assert_eq!(
+            dedupe.observe(&ChatMessage::test("alice", "first").with_id("m5"), now),
+            DedupeVerdict::Repeat {
+                message_id: "m4".to_string(),
+                count: 2
+            }
+        );
+
+        let mut sub = ChatMessage::test("alice", "first").with_id("m6");
+        sub.message_type = MessageType::Subscription;
+        assert_eq!(dedupe.observe(&sub, now), DedupeVerdict::Display);
+    }
+}
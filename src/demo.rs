@@ -0,0 +1,292 @@
+//! Modo demo: chat sintético para ajustar el diseño sin conectarse
+//!
+//! Con `overlay-native --demo` (o `demo.enabled`) no se abre ninguna conexión
+//! y se generan mensajes con usuarios, colores e insignias al azar, longitudes
+//! variadas, emotes del cache y, de vez en cuando, suscripciones, raids y
+//! cheers. Los mensajes recorren el mismo camino que los reales (filtros,
+//! mapeo, temas, sonidos), así que sirven para probar la configuración.
+
+use std::collections::HashMap;
+use std::time::{Duration, SystemTime};
+
+use rand::seq::SliceRandom;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::connection::{
+    Badge, ChatMessage, Emote, EmoteSource, MessageMetadata, MessageType, TextPosition,
+};
+
+/// Plataforma de los mensajes generados
+pub const DEMO_PLATFORM: &str = "demo";
+
+const USERNAMES: &[&str] = &[
+    "pixel_pirate",
+    "LunaByte",
+    "xX_Sniper_Xx",
+    "cafe_con_leche",
+    "NightOwl42",
+    "the_real_bob",
+    "GlitchWizard",
+    "mar_y_sol",
+    "TurboTortuga",
+    "quietviewer",
+    "SpeedrunSam",
+    "chaos_kitten",
+];
+
+const COLORS: &[&str] = &[
+    "#FF4500", "#1E90FF", "#9ACD32", "#FF69B4", "#DAA520", "#00FF7F", "#8A2BE2", "#D2691E",
+];
+
+const PHRASES: &[&str] = &[
+    "hola chat",
+    "gg",
+    "LOL",
+    "que jugada",
+    "first time here, love the stream",
+    "how long have you been playing this game?",
+    "eso no lo vi venir",
+    "F",
+    "clip it!",
+    "the music today is great, what playlist is this?",
+    "buenas noches desde Buenos Aires",
+    "no way that worked",
+    "can you show your settings again? I missed the part about the overlay layout",
+    "jajajaja",
+    "hydrate check",
+];
+
+/// `demo`
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct DemoConfig {
+    /// Arranca en modo demo aunque no se pase `--demo`
+    pub enabled: bool,
+    /// Ritmo de mensajes generados
+    pub messages_per_minute: u32,
+    /// Probabilidad (0.0 - 1.0) de que un mensaje sea un evento (sub, raid, cheer)
+    pub event_chance: f64,
+    /// Probabilidad (0.0 - 1.0) de que un mensaje lleve emotes
+    pub emote_chance: f64,
+    /// Canal que figura en los mensajes
+    pub channel: String,
+}
+
+impl Default for DemoConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            messages_per_minute: 30,
+            event_chance: 0.05,
+            emote_chance: 0.4,
+            channel: "demo".to_string(),
+        }
+    }
+}
+
+impl DemoConfig {
+    /// Pausa entre mensajes
+    pub fn interval(&self) -> Duration {
+        Duration::from_millis(60_000 / u64::from(self.messages_per_minute.max(1)))
+    }
+}
+
+/// Generador de mensajes de demo
+#[derive(Debug)]
+pub struct DemoGenerator {
+    config: DemoConfig,
+    /// Emotes del cache que se mezclan en el texto
+    emotes: Vec<Emote>,
+    sequence: u64,
+}
+
+impl DemoGenerator {
+    pub fn new(config: DemoConfig, mut emotes: Vec<Emote>) -> Self {
+        emotes.retain(|emote| !emote.name.is_empty());
+        Self {
+            config,
+            emotes,
+            sequence: 0,
+        }
+    }
+
+    pub fn next_message(&mut self, rng: &mut impl Rng) -> ChatMessage {
+        self.sequence += 1;
+        let username = *USERNAMES.choose(rng).unwrap_or(&"viewer");
+        let mut custom_data = HashMap::new();
+
+        let message_type = if rng.gen_bool(self.config.event_chance.clamp(0.0, 1.0)) {
+            match rng.gen_range(0..3) {
+                0 => {
+                    let months: u64 = rng.gen_range(1..=36);
+                    custom_data.insert("cumulative_months".to_string(), months.into());
+                    custom_data.insert("is_resub".to_string(), (months > 1).into());
+                    custom_data.insert("sub_plan".to_string(), "1000".into());
+                    MessageType::Subscription
+                }
+                1 => {
+                    let viewers: u64 = rng.gen_range(5..=500);
+                    custom_data.insert("viewer_count".to_string(), viewers.into());
+                    MessageType::Raid
+                }
+                _ => {
+                    let bits: u64 = [100, 500, 1000, 5000][rng.gen_range(0..4)];
+                    custom_data.insert(crate::hype::BITS_KEY.to_string(), bits.into());
+                    MessageType::Cheer
+                }
+            }
+        } else {
+            MessageType::Normal
+        };
+
+        let (content, emotes) = match message_type {
+            MessageType::Raid => (
+                format!(
+                    "{} is raiding with {} viewers!",
+                    username, custom_data["viewer_count"]
+                ),
+                Vec::new(),
+            ),
+            _ => self.chat_text(rng),
+        };
+
+        ChatMessage {
+            id: format!("{}-{}", DEMO_PLATFORM, self.sequence),
+            platform: DEMO_PLATFORM.to_string(),
+            channel: self.config.channel.clone(),
+            username: username.to_lowercase(),
+            display_name: Some(username.to_string()),
+            content,
+            emotes,
+            badges: random_badges(rng),
+            timestamp: SystemTime::now(),
+            user_color: COLORS.choose(rng).map(|color| color.to_string()),
+            message_type,
+            metadata: MessageMetadata {
+                is_action: false,
+                is_whisper: false,
+                is_highlighted: false,
+                is_me_message: false,
+                reply_to: None,
+                thread_id: None,
+                custom_data,
+            },
+        }
+    }
+
+    /// Una a tres frases con emotes intercalados; las posiciones son de
+    /// caracteres con el final incluido, como las del parser
+    fn chat_text(&self, rng: &mut impl Rng) -> (String, Vec<Emote>) {
+        let with_emotes =
+            !self.emotes.is_empty() && rng.gen_bool(self.config.emote_chance.clamp(0.0, 1.0));
+        let mut content = String::new();
+        let mut emotes: Vec<Emote> = Vec::new();
+
+        for _ in 0..rng.gen_range(1..=3) {
+            if !content.is_empty() {
+                content.push(' ');
+            }
+            content.push_str(PHRASES.choose(rng).unwrap_or(&"hola"));
+
+            if with_emotes && rng.gen_bool(0.6) {
+                for _ in 0..rng.gen_range(1..=2) {
+                    let Some(emote) = self.emotes.choose(rng) else {
+                        break;
+                    };
+                    content.push(' ');
+                    let start = content.chars().count();
+                    let position = TextPosition {
+                        start,
+                        end: start + emote.name.chars().count() - 1,
+                    };
+                    content.push_str(&emote.name);
+                    match emotes.iter_mut().find(|known| known.id == emote.id) {
+                        Some(known) => known.positions.push(position),
+                        None => emotes.push(Emote {
+                            positions: vec![position],
+                            ..emote.clone()
+                        }),
+                    }
+                }
+            }
+        }
+        (content, emotes)
+    }
+}
+
+/// Sin insignias la mayoría de las veces; si no, una o dos del paquete integrado
+fn random_badges(rng: &mut impl Rng) -> Vec<Badge> {
+    let mut names = Vec::new();
+    if rng.gen_bool(0.4) {
+        names.push("subscriber");
+    }
+    match rng.gen_range(0..10) {
+        0 => names.push("moderator"),
+        1 => names.push("vip"),
+        _ => {}
+    }
+    names
+        .into_iter()
+        .map(|name| Badge {
+            id: name.to_string(),
+            name: name.to_string(),
+            version: "1".to_string(),
+            url: None,
+            title: Some(name.to_string()),
+            source: EmoteSource::Local,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_generated_messages_place_emotes_and_events() {
+        let emote = Emote {
+            id: "25".to_string(),
+            name: "Kappa".to_string(),
+            ..Emote::default()
+        };
+        let config = DemoConfig {
+            event_chance: 0.0,
+            emote_chance: 1.0,
+            ..DemoConfig::default()
+        };
+        let mut generator = DemoGenerator::new(config, vec![emote]);
+        let mut rng = StdRng::seed_from_u64(7);
+
+        let mut with_emotes = 0;
+        for _ in 0..50 {
+            let message = generator.next_message(&mut rng);
+            assert_eq!(message.platform, DEMO_PLATFORM);
+            assert!(matches!(message.message_type, MessageType::Normal));
+            for emote in &message.emotes {
+                for position in &emote.positions {
+                    assert_eq!(
+                        crate::text::slice_chars_inclusive(
+                            &message.content,
+                            position.start,
+                            position.end
+                        ),
+                        Some("Kappa")
+                    );
+                }
+                with_emotes += 1;
+            }
+        }
+        assert!(with_emotes > 0);
+
+        generator.config.event_chance = 1.0;
+        let message = generator.next_message(&mut rng);
+        assert!(matches!(
+            message.message_type,
+            MessageType::Subscription | MessageType::Raid | MessageType::Cheer
+        ));
+        assert_ne!(generator.next_message(&mut rng).id, message.id);
+    }
+}
@@ -0,0 +1,206 @@
+//! Bloqueo de emotes concretos (p.ej. emotes NSFW de canales de 7TV)
+//!
+//! Los emotes bloqueados se eliminan de la lista de emotes del mensaje, de modo
+//! que su nombre se muestra como texto plano, u opcionalmente se sustituye por
+//! `replacement`.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+
+use crate::config::EmoteBlocklistConfig;
+use crate::connection::Emote;
+
+/// Lista de emotes bloqueados por id o por nombre
+#[derive(Debug, Default)]
+pub struct EmoteBlocklist {
+    ids: HashSet<String>,
+    names: HashSet<String>,
+    replacement: Option<String>,
+    /// Intentos de uso de cada emote bloqueado (por nombre)
+    attempts: Mutex<HashMap<String, u64>>,
+}
+
+impl EmoteBlocklist {
+    pub fn new(config: &EmoteBlocklistConfig) -> Self {
+        Self {
+            ids: config.ids.iter().cloned().collect(),
+            names: config.names.iter().cloned().collect(),
+            replacement: config.replacement.clone(),
+            attempts: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ids.is_empty() && self.names.is_empty()
+    }
+
+    /// Los nombres distinguen mayúsculas, igual que en las plataformas
+    pub fn is_blocked(&self, emote: &Emote) -> bool {
+        self.ids.contains(&emote.id) || self.names.contains(&emote.name)
+    }
+
+    /// Quita los emotes bloqueados de `emotes` y, si hay `replacement`, sustituye
+    /// su nombre en `content`. Devuelve el número de apariciones bloqueadas.
+    pub fn apply(&self, content: &mut String, emotes: &mut Vec<Emote>) -> usize {
+        if self.is_empty() {
+            return 0;
+        }
+
+        let (blocked, kept): (Vec<Emote>, Vec<Emote>) =
+            emotes.drain(..).partition(|e| self.is_blocked(e));
+        *emotes = kept;
+
+        let mut blocked_count = 0;
+        {
+            let mut attempts = self.attempts.lock().unwrap_or_else(|e| e.into_inner());
+            for emote in &blocked {
+                let count = emote.positions.len().max(1);
+                blocked_count += count;
+                *attempts.entry(emote.name.clone()).or_insert(0) += count as u64;
+            }
+        }
+
+        if let Some(replacement) = &self.replacement {
+            let names: HashSet<&str> = blocked.iter().map(|e| e.name.as_str()).collect();
+            if !names.is_empty() {
+                replace_words(content, &names, replacement, emotes);
+            }
+        }
+
+        blocked_count
+    }
+
+    /// Intentos de uso por emote bloqueado, de más a menos frecuente
+    pub fn attempts(&self) -> Vec<(String, u64)> {
+        let attempts = self.attempts.lock().unwrap_or_else(|e| e.into_inner());
+        let mut result: Vec<(String, u64)> =
+            attempts.iter().map(|(k, v)| (k.clone(), *v)).collect();
+        result.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        result
+    }
+
+    /// Resumen legible de los intentos, `None` si no hubo ninguno
+    pub fn report(&self) -> Option<String> {
+        let attempts = self.attempts();
+        if attempts.is_empty() {
+            return None;
+        }
+        let lines = attempts
+            .iter()
+            .map(|(name, count)| format!("  {}: {}", name, count))
+            .collect::<Vec<_>>()
+            .join("\n");
+        Some(format!("Blocked emote attempts:\n{}", lines))
+    }
+}
+
+/// Sustituye las palabras bloqueadas y desplaza las posiciones de los emotes restantes
+fn replace_words(
+    content: &mut String,
+    names: &HashSet<&str>,
+    replacement: &str,
+    emotes: &mut [Emote],
+) {
+    let mut result = String::with_capacity(content.len());
+    // (posición original, diferencia de longitud) de cada sustitución
+    let mut shifts: Vec<(usize, isize)> = Vec::new();
+    let mut offset = 0;
+
+    for (i, word) in content.split(' ').enumerate() {
+        if i > 0 {
+            result.push(' ');
+        }
+        if names.contains(word) {
+            result.push_str(replacement);
+            shifts.push((
+                offset,
+                replacement.chars().count() as isize - word.chars().count() as isize,
+            ));
+        } else {
+            result.push_str(word);
+        }
+        offset += word.chars().count() + 1;
+    }
+
+    if shifts.is_empty() {
+        return;
+    }
+
+    for emote in emotes.iter_mut() {
+        for position in &mut emote.positions {
+            let delta: isize = shifts
+                .iter()
+                .filter(|(at, _)| *at < position.start)
+                .map(|(_, delta)| delta)
+                .sum();
+            position.start = position.start.saturating_add_signed(delta);
+            position.end = position.end.saturating_add_signed(delta);
+        }
+    }
+
+    *content = result;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::connection::TextPosition;
+
+    fn emote(id: &str, name: &str, start: usize) -> Emote {
+        Emote {
+            id: id.to_string(),
+            name: name.to_string(),
+            positions: vec![TextPosition {
+                start,
+                end: start + name.len() - 1,
+            }],
+            ..Default::default()
+        }
+    }
+
+    fn blocklist(replacement: Option<&str>) -> EmoteBlocklist {
+        EmoteBlocklist::new(&EmoteBlocklistConfig {
+            ids: vec!["nsfw-id".to_string()],
+            names: vec!["gachiHYPER".to_string()],
+            replacement: replacement.map(str::to_string),
+            report_attempts: true,
+        })
+    }
+
+    #[test]
+    fn test_blocked_emotes_are_stripped_and_text_kept() {
+        let blocklist = blocklist(None);
+        let mut content = "hi gachiHYPER Kappa".to_string();
+        let mut emotes = vec![emote("other", "gachiHYPER", 3), emote("25", "Kappa", 14)];
+
+        assert_eq!(blocklist.apply(&mut content, &mut emotes), 1);
+        assert_eq!(content, "hi gachiHYPER Kappa");
+        assert_eq!(emotes.len(), 1);
+        assert_eq!(emotes[0].name, "Kappa");
+    }
+
+    #[test]
+    fn test_replacement_shifts_remaining_positions() {
+        let blocklist = blocklist(Some("***"));
+        let mut content = "lewd Kappa".to_string();
+        let mut emotes = vec![emote("nsfw-id", "lewd", 0), emote("25", "Kappa", 5)];
+
+        blocklist.apply(&mut content, &mut emotes);
+        assert_eq!(content, "*** Kappa");
+        assert_eq!(emotes[0].positions[0].start, 4);
+        assert_eq!(emotes[0].positions[0].end, 8);
+    }
+
+    #[test]
+    fn test_attempts_are_counted_per_emote() {
+        let blocklist = blocklist(None);
+        for _ in 0..3 {
+            let mut content = "gachiHYPER".to_string();
+            let mut emotes = vec![emote("x", "gachiHYPER", 0)];
+            blocklist.apply(&mut content, &mut emotes);
+        }
+
+        assert_eq!(blocklist.attempts(), vec![("gachiHYPER".to_string(), 3)]);
+        assert!(blocklist.report().unwrap().contains("gachiHYPER: 3"));
+    }
+}
@@ -1,4 +1,5 @@
 use crate::connection::Emote;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::time::{Duration, Instant};
@@ -56,12 +57,14 @@ impl EmoteCache {
             cached.created_at.elapsed() > self.ttl
         } else {
             self.miss_count += 1;
+            crate::stats::Stats::global().record_emote_cache(false);
             return None;
         };
 
         if is_expired {
             self.cache.remove(key);
             self.miss_count += 1;
+            crate::stats::Stats::global().record_emote_cache(false);
             return None;
         }
 
@@ -69,6 +72,7 @@ impl EmoteCache {
         cached.last_accessed = Instant::now();
         cached.access_count += 1;
         self.hit_count += 1;
+        crate::stats::Stats::global().record_emote_cache(true);
         Some(&cached.emote)
     }
 
@@ -96,6 +100,25 @@ impl EmoteCache {
         self.cache.remove(key).map(|cached| cached.emote)
     }
 
+    /// Consulta un emote sin contar acceso ni comprobar expiración
+    pub fn peek(&self, key: &str) -> Option<&Emote> {
+        self.cache.get(key).map(|cached| &cached.emote)
+    }
+
+    /// Elimina todos los emotes de un proveedor y devuelve sus claves
+    pub fn remove_by_source(&mut self, source: &crate::connection::EmoteSource) -> Vec<String> {
+        let keys: Vec<String> = self
+            .cache
+            .iter()
+            .filter(|(_, cached)| &cached.emote.source == source)
+            .map(|(key, _)| key.clone())
+            .collect();
+        for key in &keys {
+            self.cache.remove(key);
+        }
+        keys
+    }
+
     /// Verifica si el cache está vacío
     pub fn is_empty(&self) -> bool {
         self.cache.is_empty()
@@ -156,6 +179,10 @@ impl EmoteCache {
             },
             ttl_seconds: self.ttl.as_secs(),
             last_cleanup: self.last_cleanup,
+            by_source: self.cache.values().fold(HashMap::new(), |mut counts, cached| {
+                *counts.entry(cached.emote.source.to_string()).or_insert(0) += 1;
+                counts
+            }),
         };
         stats
     }
@@ -265,7 +292,7 @@ impl EmoteCache {
 }
 
 /// Estadísticas del cache
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, JsonSchema)]
 pub struct CacheStats {
     pub size: usize,
     pub max_size: usize,
@@ -275,6 +302,8 @@ pub struct CacheStats {
     pub ttl_seconds: u64,
     #[serde(skip)]
     pub last_cleanup: Instant,
+    /// Emotes en cache por proveedor
+    pub by_source: HashMap<String, usize>,
 }
 
 impl Default for CacheStats {
@@ -287,6 +316,7 @@ impl Default for CacheStats {
             hit_rate: 0.0,
             ttl_seconds: 86400,
             last_cleanup: Instant::now(),
+            by_source: HashMap::new(),
         }
     }
 }
@@ -360,4 +390,19 @@ mod tests {
         assert_eq!(stats.miss_count, 1);
         assert_eq!(stats.hit_rate, 2.0 / 3.0);
     }
+
+    #[test]
+    fn test_remove_by_source() {
+        let mut cache = EmoteCache::new(1);
+        let mut bttv = create_test_emote("abc", "catJAM");
+        bttv.source = EmoteSource::BTTV;
+        cache.insert("abc".to_string(), bttv);
+        cache.insert("123".to_string(), create_test_emote("123", "test"));
+        assert_eq!(cache.stats().by_source["bttv"], 1);
+
+        assert_eq!(cache.remove_by_source(&EmoteSource::BTTV), vec!["abc"]);
+        assert!(cache.peek("abc").is_none());
+        assert!(cache.peek("123").is_some());
+        assert!(!cache.stats().by_source.contains_key("bttv"));
+    }
 }
@@ -0,0 +1,210 @@
+//! Proveedor de emotes genérico configurable por HTTP
+//!
+//! Cada entrada de `emotes.custom_providers` se registra como un proveedor más
+//! del `EmoteSystem`, así que servicios propios o poco comunes se pueden usar
+//! sin recompilar: basta con la URL de la API y las rutas de cada campo.
+
+use async_trait::async_trait;
+use serde_json::Value;
+
+use super::{EmoteApiClient, EmoteData, EmoteError, EmoteHttpClient, EmoteProvider};
+use crate::config::CustomEmoteProviderConfig;
+use crate::connection::Emote;
+
+/// Proveedor definido en `emotes.custom_providers`
+pub struct CustomEmoteProvider {
+    api_client: EmoteApiClient,
+    config: CustomEmoteProviderConfig,
+}
+
+impl CustomEmoteProvider {
+    pub fn with_http_client(config: CustomEmoteProviderConfig, http: EmoteHttpClient) -> Self {
+        Self {
+            api_client: EmoteApiClient::with_http_client(http),
+            config,
+        }
+    }
+
+    /// Consulta una URL y convierte la respuesta en emotes
+    async fn fetch(&self, url: &str) -> Result<Vec<EmoteData>, EmoteError> {
+        let body: Value = self.api_client.get_json(url).await?;
+        extract_emotes(&self.config, &body)
+    }
+
+    fn serves(&self, platform: &str) -> bool {
+        self.config.platforms.is_empty()
+            || self
+                .config
+                .platforms
+                .iter()
+                .any(|p| p.eq_ignore_ascii_case(platform))
+    }
+}
+
+#[async_trait]
+impl EmoteProvider for CustomEmoteProvider {
+    async fn parse_emotes(
+        &self,
+        _message: &str,
+        _emote_data: &str,
+    ) -> Result<Vec<Emote>, EmoteError> {
+        // Como BTTV/FFZ/7TV: los emotes se buscan por nombre en el sistema principal
+        Ok(vec![])
+    }
+
+    async fn get_channel_emotes(
+        &self,
+        platform: &str,
+        channel: &str,
+    ) -> Result<Vec<EmoteData>, EmoteError> {
+        let Some(template) = self.config.channel_url.as_deref() else {
+            return Ok(vec![]);
+        };
+        if !self.serves(platform) {
+            return Ok(vec![]);
+        }
+
+        let url = template
+            .replace("{platform}", &encode_component(platform))
+            .replace("{channel}", &encode_component(channel));
+        self.fetch(&url).await
+    }
+
+    async fn get_global_emotes(&self) -> Result<Vec<EmoteData>, EmoteError> {
+        match self.config.global_url.as_deref() {
+            Some(url) => self.fetch(url).await,
+            None => Ok(vec![]),
+        }
+    }
+
+    fn provider_name(&self) -> &str {
+        &self.config.name
+    }
+}
+
+/// Codifica un valor para sustituirlo en una URL; solo se dejan tal cual los
+/// caracteres no reservados de RFC 3986
+fn encode_component(value: &str) -> String {
+    value
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                (byte as char).to_string()
+            }
+            _ => format!("%{:02X}", byte),
+        })
+        .collect()
+}
+
+/// Valor en una ruta separada por puntos (`data.emotes`, `images.0.url`)
+fn lookup<'a>(value: &'a Value, path: &str) -> Option<&'a Value> {
+    if path.is_empty() {
+        return Some(value);
+    }
+    value.pointer(&format!("/{}", path.replace('.', "/")))
+}
+
+fn lookup_string(value: &Value, path: &str) -> Option<String> {
+    match lookup(value, path)? {
+        Value::String(s) if !s.is_empty() => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    }
+}
+
+/// Convierte una respuesta de la API en emotes según el mapeo de campos
+///
+/// Las entradas sin id o sin nombre se descartan; una respuesta sin lista en
+/// `list_path` es un error de parseo.
+pub fn extract_emotes(
+    config: &CustomEmoteProviderConfig,
+    body: &Value,
+) -> Result<Vec<EmoteData>, EmoteError> {
+    let list = lookup(body, &config.list_path)
+        .and_then(Value::as_array)
+        .ok_or_else(|| {
+            EmoteError::ParseError(format!(
+                "{}: no emote list at '{}'",
+                config.name, config.list_path
+            ))
+        })?;
+
+    let fields = &config.fields;
+    Ok(list
+        .iter()
+        .filter_map(|entry| {
+            let id = lookup_string(entry, &fields.id)?;
+            let name = lookup_string(entry, &fields.name)?;
+            let url = lookup_string(entry, &fields.url).or_else(|| {
+                config
+                    .image_url
+                    .as_ref()
+                    .map(|template| template.replace("{id}", &id).replace("{name}", &name))
+            });
+            let is_animated = lookup(entry, &fields.animated)
+                .and_then(Value::as_bool)
+                .unwrap_or(false);
+
+            Some(EmoteData {
+                id: format!("{}:{}", config.name, id),
+                name,
+                url,
+                is_animated,
+                width: None,
+                height: None,
+                is_zero_width: false,
+                modifier: false,
+                emote_set_id: None,
+            })
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::EmoteFieldMapping;
+
+    #[test]
+    fn test_emotes_are_mapped_from_nested_paths() {
+        let config = CustomEmoteProviderConfig {
+            name: "mine".to_string(),
+            list_path: "data.emotes".to_string(),
+            fields: EmoteFieldMapping {
+                id: "code".to_string(),
+                name: "token".to_string(),
+                url: "images.0.src".to_string(),
+                animated: "flags.gif".to_string(),
+            },
+            image_url: Some("https://cdn.example/{id}.png".to_string()),
+            ..CustomEmoteProviderConfig::default()
+        };
+        let body = serde_json::json!({
+            "data": { "emotes": [
+                { "code": 7, "token": "wave", "images": [{ "src": "https://x/wave.gif" }], "flags": { "gif": true } },
+                { "code": "abc", "token": "hi" },
+                { "token": "no-id" }
+            ]}
+        });
+
+        let emotes = extract_emotes(&config, &body).unwrap();
+        assert_eq!(emotes.len(), 2);
+        assert_eq!(emotes[0].id, "mine:7");
+        assert_eq!(emotes[0].url.as_deref(), Some("https://x/wave.gif"));
+        assert!(emotes[0].is_animated);
+        assert_eq!(emotes[1].name, "hi");
+        assert_eq!(
+            emotes[1].url.as_deref(),
+            Some("https://cdn.example/abc.png")
+        );
+        assert!(!emotes[1].is_animated);
+
+        assert!(extract_emotes(&config, &serde_json::json!({ "data": {} })).is_err());
+    }
+
+    #[test]
+    fn test_channel_is_percent_encoded() {
+        assert_eq!(encode_component("some_channel"), "some_channel");
+        assert_eq!(encode_component("a b/c?d&é"), "a%20b%2Fc%3Fd%26%C3%A9");
+    }
+}
@@ -0,0 +1,232 @@
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fmt::Write as _;
+
+use super::EmoteData;
+
+/// Orden de prioridad entre proveedores cuando dos emotes comparten nombre
+///
+/// Los emotes nativos de la plataforma ganan siempre; entre terceros gana 7TV,
+/// luego BTTV y por último FFZ.
+pub const PROVIDER_PRECEDENCE: &[&str] = &["twitch", "kick", "7tv", "bttv", "ffz"];
+
+/// Ámbito de un conjunto de emotes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EmoteScope {
+    Channel,
+    Global,
+}
+
+impl std::fmt::Display for EmoteScope {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EmoteScope::Channel => write!(f, "channel"),
+            EmoteScope::Global => write!(f, "global"),
+        }
+    }
+}
+
+/// Emote efectivo tras resolver conflictos de nombre
+#[derive(Debug, Clone, Serialize)]
+pub struct ResolvedEmote {
+    pub name: String,
+    pub id: String,
+    pub provider: String,
+    pub scope: EmoteScope,
+    pub url: Option<String>,
+    pub animated: bool,
+    pub zero_width: bool,
+    pub modifier: bool,
+    /// Proveedores (`proveedor/ámbito`) cuyo emote con el mismo nombre quedó oculto
+    pub shadowed: Vec<String>,
+}
+
+/// Conjunto de emotes efectivo de un canal
+#[derive(Debug, Clone, Serialize)]
+pub struct EmoteSetDump {
+    pub platform: String,
+    pub channel: String,
+    pub emotes: Vec<ResolvedEmote>,
+    /// Proveedores que fallaron al resolver (`proveedor/ámbito`, error)
+    pub errors: Vec<(String, String)>,
+}
+
+impl EmoteSetDump {
+    /// Tabla legible para la terminal
+    pub fn to_table(&self) -> String {
+        let name_width = self
+            .emotes
+            .iter()
+            .map(|e| crate::text::grapheme_len(&e.name))
+            .max()
+            .unwrap_or(4)
+            .max(4);
+
+        let mut out = String::new();
+        let _ = writeln!(
+            out,
+            "Effective emote set for {} on {} ({} emotes)",
+            self.channel,
+            self.platform,
+            self.emotes.len()
+        );
+        let _ = writeln!(
+            out,
+            "{:<name_width$}  {:<26}  {:<13}  {:<5}  URL",
+            "NAME", "ID", "PROVIDER", "FLAGS"
+        );
+        for emote in &self.emotes {
+            let padding = name_width - crate::text::grapheme_len(&emote.name);
+            let _ = writeln!(
+                out,
+                "{}{}  {:<26}  {:<13}  {:<5}  {}",
+                emote.name,
+                " ".repeat(padding),
+                emote.id,
+                format!("{}/{}", emote.provider, emote.scope),
+                emote_flags(emote),
+                emote.url.as_deref().unwrap_or("-")
+            );
+            if !emote.shadowed.is_empty() {
+                let _ = writeln!(out, "    overrides: {}", emote.shadowed.join(", "));
+            }
+        }
+        for (source, error) in &self.errors {
+            let _ = writeln!(out, "! {} failed: {}", source, error);
+        }
+        out
+    }
+}
+
+/// Flags compactos: `A` animado, `Z` zero-width, `M` modificador
+fn emote_flags(emote: &ResolvedEmote) -> String {
+    let mut flags = String::new();
+    if emote.animated {
+        flags.push('A');
+    }
+    if emote.zero_width {
+        flags.push('Z');
+    }
+    if emote.modifier {
+        flags.push('M');
+    }
+    if flags.is_empty() {
+        flags.push('-');
+    }
+    flags
+}
+
+fn provider_rank(provider: &str) -> usize {
+    PROVIDER_PRECEDENCE
+        .iter()
+        .position(|p| *p == provider)
+        .unwrap_or(PROVIDER_PRECEDENCE.len())
+}
+
+/// Fusiona los conjuntos de cada proveedor aplicando la resolución de conflictos
+///
+/// Un emote de canal siempre oculta a uno global; dentro del mismo ámbito decide
+/// [`PROVIDER_PRECEDENCE`].
+pub fn merge_emote_sets(mut sets: Vec<(String, EmoteScope, Vec<EmoteData>)>) -> Vec<ResolvedEmote> {
+    sets.sort_by_key(|(provider, scope, _)| (*scope, provider_rank(provider)));
+
+    let mut merged: HashMap<String, ResolvedEmote> = HashMap::new();
+    for (provider, scope, emotes) in sets {
+        for data in emotes {
+            match merged.get_mut(&data.name) {
+                Some(winner) => winner.shadowed.push(format!("{}/{}", provider, scope)),
+                None => {
+                    merged.insert(
+                        data.name.clone(),
+                        ResolvedEmote {
+                            name: data.name,
+                            id: data.id,
+                            provider: provider.clone(),
+                            scope,
+                            url: data.url,
+                            animated: data.is_animated,
+                            zero_width: data.is_zero_width,
+                            modifier: data.modifier,
+                            shadowed: Vec::new(),
+                        },
+                    );
+                }
+            }
+        }
+    }
+
+    let mut emotes: Vec<ResolvedEmote> = merged.into_values().collect();
+    emotes.sort_by_key(|e| e.name.to_lowercase());
+    emotes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn emote(id: &str, name: &str) -> EmoteData {
+        EmoteData {
+            id: id.to_string(),
+            name: name.to_string(),
+            url: None,
+            is_animated: false,
+            width: None,
+            height: None,
+            is_zero_width: false,
+            modifier: false,
+            emote_set_id: None,
+        }
+    }
+
+    #[test]
+    fn test_channel_scope_beats_global_and_precedence_breaks_ties() {
+        let merged = merge_emote_sets(vec![
+            (
+                "ffz".to_string(),
+                EmoteScope::Channel,
+                vec![emote("f1", "LUL")],
+            ),
+            (
+                "bttv".to_string(),
+                EmoteScope::Global,
+                vec![emote("b1", "LUL"), emote("b2", "catJAM")],
+            ),
+            (
+                "7tv".to_string(),
+                EmoteScope::Channel,
+                vec![emote("s1", "LUL")],
+            ),
+        ]);
+
+        assert_eq!(merged.len(), 2);
+        let lul = merged.iter().find(|e| e.name == "LUL").unwrap();
+        assert_eq!(lul.id, "s1");
+        assert_eq!(lul.shadowed, vec!["ffz/channel", "bttv/global"]);
+        let cat = merged.iter().find(|e| e.name == "catJAM").unwrap();
+        assert_eq!(cat.provider, "bttv");
+        assert_eq!(cat.scope, EmoteScope::Global);
+    }
+
+    #[test]
+    fn test_dump_table_lists_flags_and_errors() {
+        let mut animated = emote("s1", "catJAM");
+        animated.is_animated = true;
+        animated.is_zero_width = true;
+        let dump = EmoteSetDump {
+            platform: "twitch".to_string(),
+            channel: "foo".to_string(),
+            emotes: merge_emote_sets(vec![(
+                "7tv".to_string(),
+                EmoteScope::Channel,
+                vec![animated],
+            )]),
+            errors: vec![("ffz/channel".to_string(), "HTTP 404".to_string())],
+        };
+
+        let table = dump.to_table();
+        assert!(table.contains("7tv/channel"));
+        assert!(table.contains("AZ"));
+        assert!(table.contains("! ffz/channel failed: HTTP 404"));
+    }
+}
@@ -0,0 +1,196 @@
+//! Decodificación de emotes animados (GIF/WebP) a una línea de tiempo de frames
+//!
+//! Los renderers sin soporte nativo de animación (Windows/GDI) decodifican cada
+//! emote una sola vez al tamaño de destino y eligen el frame según el tiempo
+//! transcurrido.
+
+use std::io::Cursor;
+use std::time::Duration;
+
+use image::codecs::gif::GifDecoder;
+use image::codecs::webp::WebPDecoder;
+use image::{AnimationDecoder, Frame, ImageFormat, RgbaImage};
+
+/// Los navegadores tratan retardos menores de 20 ms como 100 ms; se hace igual
+const MIN_FRAME_DELAY: Duration = Duration::from_millis(20);
+const DEFAULT_FRAME_DELAY: Duration = Duration::from_millis(100);
+
+/// Frame ya escalado en formato BGRA (el que espera GDI)
+#[cfg(windows)]
+#[derive(Debug, Clone)]
+pub struct DecodedFrame {
+    pub bgra: Vec<u8>,
+    pub delay: Duration,
+}
+
+/// Frames de un emote con sus retardos
+#[cfg(windows)]
+#[derive(Debug, Clone)]
+pub struct FrameTimeline {
+    pub width: u32,
+    pub height: u32,
+    frames: Vec<DecodedFrame>,
+    total: Duration,
+}
+
+#[cfg(windows)]
+impl FrameTimeline {
+    /// Decodifica `bytes` escalando a `width`x`height`; las imágenes estáticas
+    /// producen un único frame
+    pub fn decode(bytes: &[u8], width: u32, height: u32) -> Option<Self> {
+        let frames: Vec<DecodedFrame> = decode_rgba_frames(bytes)?
+            .into_iter()
+            .map(|(image, delay)| DecodedFrame {
+                bgra: to_bgra(&resize(image, width, height)),
+                delay,
+            })
+            .collect();
+
+        let total = frames.iter().map(|frame| frame.delay).sum();
+        Some(Self {
+            width,
+            height,
+            frames,
+            total,
+        })
+    }
+
+    pub fn is_animated(&self) -> bool {
+        self.frames.len() > 1
+    }
+
+    pub fn frame_count(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// Frame visible tras `elapsed` desde el inicio (la animación se repite)
+    pub fn frame_at(&self, elapsed: Duration) -> &DecodedFrame {
+        if !self.is_animated() || self.total.is_zero() {
+            return &self.frames[0];
+        }
+
+        let mut offset = Duration::from_nanos((elapsed.as_nanos() % self.total.as_nanos()) as u64);
+        for frame in &self.frames {
+            if offset < frame.delay {
+                return frame;
+            }
+            offset -= frame.delay;
+        }
+        &self.frames[self.frames.len() - 1]
+    }
+}
+
+/// Frames RGBA de `bytes` a su tamaño original con sus retardos; las imágenes
+/// estáticas producen un único frame con retardo cero
+pub fn decode_rgba_frames(bytes: &[u8]) -> Option<Vec<(RgbaImage, Duration)>> {
+    let frames = match image::guess_format(bytes).ok()? {
+        ImageFormat::Gif => GifDecoder::new(Cursor::new(bytes))
+            .ok()
+            .and_then(|decoder| decoder.into_frames().collect_frames().ok()),
+        ImageFormat::WebP => WebPDecoder::new(Cursor::new(bytes))
+            .ok()
+            .filter(|decoder| decoder.has_animation())
+            .and_then(|decoder| decoder.into_frames().collect_frames().ok()),
+        _ => None,
+    };
+
+    match frames {
+        Some(frames) if !frames.is_empty() => Some(
+            frames
+                .into_iter()
+                .map(|frame| {
+                    let delay = frame_delay(&frame);
+                    (frame.into_buffer(), delay)
+                })
+                .collect(),
+        ),
+        _ => {
+            let image = image::load_from_memory(bytes).ok()?.to_rgba8();
+            Some(vec![(image, Duration::ZERO)])
+        }
+    }
+}
+
+fn frame_delay(frame: &Frame) -> Duration {
+    let (numer, denom) = frame.delay().numer_denom_ms();
+    match Duration::from_millis(numer as u64) / denom.max(1) {
+        d if d < MIN_FRAME_DELAY => DEFAULT_FRAME_DELAY,
+        d => d,
+    }
+}
+
+#[cfg(windows)]
+fn resize(image: RgbaImage, width: u32, height: u32) -> RgbaImage {
+    if image.dimensions() == (width, height) {
+        image
+    } else {
+        image::imageops::resize(&image, width, height, image::imageops::FilterType::Lanczos3)
+    }
+}
+
+#[cfg(windows)]
+fn to_bgra(image: &RgbaImage) -> Vec<u8> {
+    image
+        .pixels()
+        .flat_map(|pixel| [pixel[2], pixel[1], pixel[0], pixel[3]])
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::codecs::gif::GifEncoder;
+    use image::{Delay, Rgba};
+
+    fn two_frame_gif() -> Vec<u8> {
+        let mut bytes = Vec::new();
+        {
+            let mut encoder = GifEncoder::new(&mut bytes);
+            for (color, ms) in [([255, 0, 0, 255], 50), ([0, 0, 255, 255], 150)] {
+                let buffer = RgbaImage::from_pixel(4, 4, Rgba(color));
+                let frame = Frame::from_parts(buffer, 0, 0, Delay::from_numer_denom_ms(ms, 1));
+                encoder.encode_frame(frame).unwrap();
+            }
+        }
+        bytes
+    }
+
+    #[test]
+    fn test_decoded_frames_keep_size_and_delays() {
+        let frames = decode_rgba_frames(&two_frame_gif()).unwrap();
+        let delays: Vec<Duration> = frames.iter().map(|(_, delay)| *delay).collect();
+        assert_eq!(
+            delays,
+            [Duration::from_millis(50), Duration::from_millis(150)]
+        );
+        assert_eq!(frames[0].0.dimensions(), (4, 4));
+        assert!(decode_rgba_frames(b"not an image").is_none());
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn test_gif_frames_follow_delays_and_loop() {
+        let timeline = FrameTimeline::decode(&two_frame_gif(), 8, 8).unwrap();
+        assert!(timeline.is_animated());
+        assert_eq!(timeline.frame_count(), 2);
+        assert_eq!(timeline.frame_at(Duration::ZERO).bgra.len(), 8 * 8 * 4);
+
+        // BGRA: rojo -> [0, 0, 255, 255], azul -> [255, 0, 0, 255]
+        assert_eq!(timeline.frame_at(Duration::from_millis(10)).bgra[2], 255);
+        assert_eq!(timeline.frame_at(Duration::from_millis(60)).bgra[0], 255);
+        assert_eq!(timeline.frame_at(Duration::from_millis(210)).bgra[2], 255);
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn test_static_images_have_a_single_frame() {
+        let placeholder = crate::assets::emote_placeholder();
+        let timeline = FrameTimeline::decode(placeholder.bytes, 16, 16).unwrap();
+        assert!(!timeline.is_animated());
+        assert_eq!(
+            timeline.frame_at(Duration::from_secs(5)).bgra.len(),
+            16 * 16 * 4
+        );
+        assert!(FrameTimeline::decode(b"not an image", 16, 16).is_none());
+    }
+}
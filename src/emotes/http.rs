@@ -0,0 +1,553 @@
+use reqwest::header::{
+    HeaderMap, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED, RETRY_AFTER,
+};
+use reqwest::{Client, StatusCode};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::Semaphore;
+
+use super::EmoteError;
+use crate::config::EmoteHttpConfig;
+
+/// Cliente HTTP compartido para APIs y CDNs de emotes
+///
+/// Reutiliza un único pool de conexiones (HTTP/2 cuando el servidor lo negocia),
+/// limita las peticiones simultáneas y por segundo de cada host y acumula
+/// métricas de descarga. Los clones comparten límites y caché de respuestas,
+/// así que al unirse a un canal los proveedores no saturan la misma API.
+#[derive(Clone)]
+pub struct EmoteHttpClient {
+    client: Client,
+    limiter: Arc<HostLimiter>,
+    rate: Arc<HostRateLimiter>,
+    responses: Arc<ResponseCache>,
+    metrics: Arc<DownloadMetrics>,
+}
+
+impl EmoteHttpClient {
+    pub fn new(config: &EmoteHttpConfig) -> Self {
+        Self {
+            client: build_client(config),
+            limiter: Arc::new(HostLimiter::new(config.max_concurrent_per_host)),
+            rate: Arc::new(HostRateLimiter::new(
+                config.requests_per_second_per_host,
+                config.burst_per_host,
+            )),
+            responses: Arc::new(ResponseCache::new(config.response_cache_entries)),
+            metrics: Arc::new(DownloadMetrics::default()),
+        }
+    }
+
+    /// Acceso al cliente reqwest subyacente
+    pub fn inner(&self) -> &Client {
+        &self.client
+    }
+
+    /// Descarga el cuerpo completo de una URL respetando los límites por host
+    pub async fn get_bytes(
+        &self,
+        url: &str,
+        timeout: Option<Duration>,
+    ) -> Result<Vec<u8>, EmoteError> {
+        self.get(url, timeout, false).await
+    }
+
+    /// Como [`get_bytes`](Self::get_bytes), pero guarda la respuesta y la
+    /// revalida con `If-None-Match`/`If-Modified-Since`: un `304` devuelve
+    /// el cuerpo guardado sin volver a descargarlo
+    pub async fn get_revalidated(
+        &self,
+        url: &str,
+        timeout: Option<Duration>,
+    ) -> Result<Vec<u8>, EmoteError> {
+        self.get(url, timeout, true).await
+    }
+
+    async fn get(
+        &self,
+        url: &str,
+        timeout: Option<Duration>,
+        revalidate: bool,
+    ) -> Result<Vec<u8>, EmoteError> {
+        let host = host_of(url);
+        self.rate.acquire(&host).await;
+        let _permit = self.limiter.acquire(&host).await;
+        let start = Instant::now();
+
+        let result = self.fetch(url, &host, timeout, revalidate).await;
+        match &result {
+            Ok((bytes, false)) => self
+                .metrics
+                .record_success(bytes.len() as u64, start.elapsed()),
+            Ok((_, true)) => self.metrics.record_not_modified(start.elapsed()),
+            Err(e) => {
+                self.metrics.record_failure(start.elapsed());
+                crate::health::ErrorBudget::global()
+                    .record_failure(&format!("provider:{}", host), &e.to_string());
+            }
+        }
+        result.map(|(bytes, _)| bytes)
+    }
+
+    /// Cuerpo de la respuesta y si se sirvió desde la caché (`304`)
+    async fn fetch(
+        &self,
+        url: &str,
+        host: &str,
+        timeout: Option<Duration>,
+        revalidate: bool,
+    ) -> Result<(Vec<u8>, bool), EmoteError> {
+        let mut request = self.client.get(url);
+        if let Some(timeout) = timeout {
+            request = request.timeout(timeout);
+        }
+        let cached = if revalidate {
+            self.responses.get(url)
+        } else {
+            None
+        };
+        if let Some(cached) = &cached {
+            if let Some(etag) = &cached.etag {
+                request = request.header(IF_NONE_MATCH, etag);
+            }
+            if let Some(last_modified) = &cached.last_modified {
+                request = request.header(IF_MODIFIED_SINCE, last_modified);
+            }
+        }
+
+        let response = request
+            .send()
+            .await
+            .map_err(|e| EmoteError::NetworkError(format!("Failed to fetch {}: {}", url, e)))?;
+
+        let status = response.status();
+        if status == StatusCode::NOT_MODIFIED {
+            if let Some(cached) = cached {
+                return Ok((cached.body, true));
+            }
+        }
+        if status == StatusCode::TOO_MANY_REQUESTS {
+            // El resto de peticiones al host esperan lo que pida el servidor
+            let delay = retry_after(response.headers()).unwrap_or(Duration::from_secs(1));
+            eprintln!(
+                "⚠️ Emote API {} rate limited, backing off {:?}",
+                host, delay
+            );
+            self.rate.back_off(host, delay, Instant::now());
+        }
+        if !status.is_success() {
+            return Err(EmoteError::NetworkError(format!(
+                "HTTP {} from {}: {}",
+                status.as_u16(),
+                url,
+                response
+                    .text()
+                    .await
+                    .unwrap_or_else(|_| "Unable to read response body".to_string())
+            )));
+        }
+
+        let etag = header_value(response.headers(), ETAG);
+        let last_modified = header_value(response.headers(), LAST_MODIFIED);
+        let body = response
+            .bytes()
+            .await
+            .map(|bytes| bytes.to_vec())
+            .map_err(|e| {
+                EmoteError::NetworkError(format!("Failed to read body from {}: {}", url, e))
+            })?;
+        if revalidate && (etag.is_some() || last_modified.is_some()) {
+            self.responses.insert(
+                url,
+                CachedResponse {
+                    etag,
+                    last_modified,
+                    body: body.clone(),
+                },
+            );
+        }
+        Ok((body, false))
+    }
+
+    /// Obtiene una instantánea de las métricas de descarga
+    pub fn stats(&self) -> DownloadStats {
+        self.metrics.snapshot()
+    }
+
+    /// Número de peticiones en curso hacia un host
+    pub fn in_flight(&self, host: &str) -> usize {
+        self.limiter.in_flight(host)
+    }
+}
+
+fn build_client(config: &EmoteHttpConfig) -> Client {
+    let mut builder = Client::builder()
+        .timeout(Duration::from_secs(config.request_timeout_secs))
+        .user_agent("Overlay-Native/1.0")
+        .pool_max_idle_per_host(config.pool_max_idle_per_host)
+        .pool_idle_timeout(Duration::from_secs(config.pool_idle_timeout_secs))
+        .tcp_keepalive(Duration::from_secs(60));
+
+    if config.http2 {
+        builder = builder
+            .http2_adaptive_window(true)
+            .http2_keep_alive_interval(Duration::from_secs(30))
+            .http2_keep_alive_while_idle(true);
+    } else {
+        builder = builder.http1_only();
+    }
+
+    builder.build().unwrap_or_default()
+}
+
+/// Extrae el host de una URL (vacío si no se puede parsear)
+fn host_of(url: &str) -> String {
+    url::Url::parse(url)
+        .ok()
+        .and_then(|u| u.host_str().map(|h| h.to_string()))
+        .unwrap_or_default()
+}
+
+fn header_value(headers: &HeaderMap, name: reqwest::header::HeaderName) -> Option<String> {
+    headers
+        .get(name)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string)
+}
+
+/// Espera que pide un `429`; solo se entiende la forma en segundos
+fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    header_value(headers, RETRY_AFTER)?
+        .trim()
+        .parse::<u64>()
+        .ok()
+        .map(Duration::from_secs)
+}
+
+/// Limita el número de peticiones simultáneas por host
+struct HostLimiter {
+    max_per_host: usize,
+    hosts: Mutex<HashMap<String, Arc<Semaphore>>>,
+}
+
+impl HostLimiter {
+    fn new(max_per_host: usize) -> Self {
+        Self {
+            max_per_host: max_per_host.max(1),
+            hosts: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn semaphore(&self, host: &str) -> Arc<Semaphore> {
+        let mut hosts = self.hosts.lock().unwrap_or_else(|e| e.into_inner());
+        hosts
+            .entry(host.to_string())
+            .or_insert_with(|| Arc::new(Semaphore::new(self.max_per_host)))
+            .clone()
+    }
+
+    async fn acquire(&self, host: &str) -> Option<tokio::sync::OwnedSemaphorePermit> {
+        self.semaphore(host).acquire_owned().await.ok()
+    }
+
+    fn in_flight(&self, host: &str) -> usize {
+        let hosts = self.hosts.lock().unwrap_or_else(|e| e.into_inner());
+        hosts
+            .get(host)
+            .map(|s| self.max_per_host - s.available_permits())
+            .unwrap_or(0)
+    }
+}
+
+/// Token bucket por host: admite `burst` peticiones seguidas y luego `rate`
+/// por segundo
+struct HostRateLimiter {
+    rate: f64,
+    burst: f64,
+    buckets: Mutex<HashMap<String, TokenBucket>>,
+}
+
+struct TokenBucket {
+    /// Negativo cuando hay peticiones esperando turno
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+impl HostRateLimiter {
+    fn new(rate: f64, burst: u32) -> Self {
+        Self {
+            rate,
+            burst: burst.max(1) as f64,
+            buckets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn bucket<'a>(
+        &self,
+        buckets: &'a mut HashMap<String, TokenBucket>,
+        host: &str,
+        now: Instant,
+    ) -> &'a mut TokenBucket {
+        let bucket = buckets
+            .entry(host.to_string())
+            .or_insert_with(|| TokenBucket {
+                tokens: self.burst,
+                refilled_at: now,
+            });
+        let elapsed = now.saturating_duration_since(bucket.refilled_at);
+        bucket.tokens = (bucket.tokens + elapsed.as_secs_f64() * self.rate).min(self.burst);
+        bucket.refilled_at = now;
+        bucket
+    }
+
+    /// Reserva un turno para `host` y devuelve cuánto hay que esperarlo
+    fn reserve(&self, host: &str, now: Instant) -> Duration {
+        if self.rate <= 0.0 {
+            return Duration::ZERO;
+        }
+        let mut buckets = self.buckets.lock().unwrap_or_else(|e| e.into_inner());
+        let bucket = self.bucket(&mut buckets, host, now);
+        bucket.tokens -= 1.0;
+        if bucket.tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-bucket.tokens / self.rate)
+        }
+    }
+
+    async fn acquire(&self, host: &str) {
+        let wait = self.reserve(host, Instant::now());
+        if !wait.is_zero() {
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    /// Retrasa las siguientes peticiones a `host` al menos `delay`
+    fn back_off(&self, host: &str, delay: Duration, now: Instant) {
+        if self.rate <= 0.0 {
+            return;
+        }
+        let mut buckets = self.buckets.lock().unwrap_or_else(|e| e.into_inner());
+        let bucket = self.bucket(&mut buckets, host, now);
+        bucket.tokens = bucket.tokens.min(1.0 - delay.as_secs_f64() * self.rate);
+    }
+}
+
+/// Respuesta guardada con sus validadores
+#[derive(Clone)]
+struct CachedResponse {
+    etag: Option<String>,
+    last_modified: Option<String>,
+    body: Vec<u8>,
+}
+
+/// Respuestas de las APIs por URL; al llenarse se descarta la más antigua
+struct ResponseCache {
+    max_entries: usize,
+    entries: Mutex<HashMap<String, (CachedResponse, Instant)>>,
+}
+
+impl ResponseCache {
+    fn new(max_entries: usize) -> Self {
+        Self {
+            max_entries,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn get(&self, url: &str) -> Option<CachedResponse> {
+        let entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries.get(url).map(|(response, _)| response.clone())
+    }
+
+    fn insert(&self, url: &str, response: CachedResponse) {
+        if self.max_entries == 0 {
+            return;
+        }
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        if entries.len() >= self.max_entries && !entries.contains_key(url) {
+            if let Some(oldest) = entries
+                .iter()
+                .min_by_key(|(_, (_, stored_at))| *stored_at)
+                .map(|(url, _)| url.clone())
+            {
+                entries.remove(&oldest);
+            }
+        }
+        entries.insert(url.to_string(), (response, Instant::now()));
+    }
+}
+
+/// Contadores de descargas acumulados
+#[derive(Default)]
+struct DownloadMetrics {
+    requests: AtomicU64,
+    failures: AtomicU64,
+    not_modified: AtomicU64,
+    bytes: AtomicU64,
+    elapsed_micros: AtomicU64,
+}
+
+impl DownloadMetrics {
+    fn record_success(&self, bytes: u64, elapsed: Duration) {
+        self.requests.fetch_add(1, Ordering::Relaxed);
+        self.bytes.fetch_add(bytes, Ordering::Relaxed);
+        self.elapsed_micros
+            .fetch_add(elapsed.as_micros() as u64, Ordering::Relaxed);
+    }
+
+    fn record_not_modified(&self, elapsed: Duration) {
+        self.requests.fetch_add(1, Ordering::Relaxed);
+        self.not_modified.fetch_add(1, Ordering::Relaxed);
+        self.elapsed_micros
+            .fetch_add(elapsed.as_micros() as u64, Ordering::Relaxed);
+    }
+
+    fn record_failure(&self, elapsed: Duration) {
+        self.requests.fetch_add(1, Ordering::Relaxed);
+        self.failures.fetch_add(1, Ordering::Relaxed);
+        self.elapsed_micros
+            .fetch_add(elapsed.as_micros() as u64, Ordering::Relaxed);
+    }
+
+    fn snapshot(&self) -> DownloadStats {
+        DownloadStats {
+            requests: self.requests.load(Ordering::Relaxed),
+            failures: self.failures.load(Ordering::Relaxed),
+            not_modified: self.not_modified.load(Ordering::Relaxed),
+            bytes: self.bytes.load(Ordering::Relaxed),
+            elapsed: Duration::from_micros(self.elapsed_micros.load(Ordering::Relaxed)),
+        }
+    }
+}
+
+/// Estadísticas de descarga del cliente HTTP
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DownloadStats {
+    pub requests: u64,
+    pub failures: u64,
+    /// Respuestas `304` servidas desde la caché de respuestas
+    pub not_modified: u64,
+    pub bytes: u64,
+    pub elapsed: Duration,
+}
+
+impl DownloadStats {
+    /// Throughput medio en bytes por segundo
+    pub fn throughput_bytes_per_sec(&self) -> f64 {
+        let secs = self.elapsed.as_secs_f64();
+        if secs > 0.0 {
+            self.bytes as f64 / secs
+        } else {
+            0.0
+        }
+    }
+
+    /// Latencia media por petición
+    pub fn average_latency(&self) -> Duration {
+        if self.requests == 0 {
+            Duration::ZERO
+        } else {
+            self.elapsed / self.requests as u32
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_host_of() {
+        assert_eq!(host_of("https://cdn.7tv.app/emote/abc/4x"), "cdn.7tv.app");
+        assert_eq!(host_of("not a url"), "");
+    }
+
+    #[tokio::test]
+    async fn test_host_limiter_caps_concurrency() {
+        let limiter = HostLimiter::new(2);
+        let a = limiter.acquire("cdn.betterttv.net").await;
+        let _b = limiter.acquire("cdn.betterttv.net").await;
+        assert_eq!(limiter.in_flight("cdn.betterttv.net"), 2);
+        assert_eq!(limiter.in_flight("cdn.7tv.app"), 0);
+
+        let third = tokio::time::timeout(
+            Duration::from_millis(50),
+            limiter.acquire("cdn.betterttv.net"),
+        )
+        .await;
+        assert!(third.is_err());
+
+        drop(a);
+        assert_eq!(limiter.in_flight("cdn.betterttv.net"), 1);
+    }
+
+    #[test]
+    fn test_rate_limiter_spaces_requests_after_burst() {
+        let limiter = HostRateLimiter::new(2.0, 2);
+        let now = Instant::now();
+        assert_eq!(limiter.reserve("api.7tv.app", now), Duration::ZERO);
+        assert_eq!(limiter.reserve("api.7tv.app", now), Duration::ZERO);
+        // Tras la ráfaga, cada petición espera medio segundo más que la anterior
+        assert_eq!(
+            limiter.reserve("api.7tv.app", now),
+            Duration::from_millis(500)
+        );
+        assert_eq!(limiter.reserve("api.7tv.app", now), Duration::from_secs(1));
+        // Otros hosts tienen su propio bucket
+        assert_eq!(limiter.reserve("api.betterttv.net", now), Duration::ZERO);
+
+        let later = now + Duration::from_secs(3);
+        assert_eq!(limiter.reserve("api.7tv.app", later), Duration::ZERO);
+
+        // Un 429 con Retry-After retrasa las siguientes peticiones al host
+        limiter.back_off("api.betterttv.net", Duration::from_secs(5), now);
+        assert!(limiter.reserve("api.betterttv.net", now) >= Duration::from_secs(5));
+
+        let unlimited = HostRateLimiter::new(0.0, 1);
+        for _ in 0..10 {
+            assert_eq!(unlimited.reserve("api.7tv.app", now), Duration::ZERO);
+        }
+    }
+
+    #[test]
+    fn test_response_cache_evicts_oldest() {
+        let cache = ResponseCache::new(2);
+        let response = |etag: &str| CachedResponse {
+            etag: Some(etag.to_string()),
+            last_modified: None,
+            body: b"[]".to_vec(),
+        };
+        cache.insert("https://api.7tv.app/a", response("\"a\""));
+        cache.insert("https://api.7tv.app/b", response("\"b\""));
+        cache.insert("https://api.7tv.app/a", response("\"a2\""));
+        cache.insert("https://api.7tv.app/c", response("\"c\""));
+
+        assert!(cache.get("https://api.7tv.app/b").is_none());
+        assert_eq!(
+            cache.get("https://api.7tv.app/a").unwrap().etag.as_deref(),
+            Some("\"a2\"")
+        );
+        assert!(cache.get("https://api.7tv.app/c").is_some());
+
+        let mut headers = HeaderMap::new();
+        headers.insert(RETRY_AFTER, "30".parse().unwrap());
+        assert_eq!(retry_after(&headers), Some(Duration::from_secs(30)));
+    }
+
+    #[test]
+    fn test_download_stats_throughput() {
+        let metrics = DownloadMetrics::default();
+        metrics.record_success(2048, Duration::from_millis(500));
+        metrics.record_success(2048, Duration::from_millis(500));
+        metrics.record_failure(Duration::from_millis(0));
+
+        let stats = metrics.snapshot();
+        assert_eq!(stats.requests, 3);
+        assert_eq!(stats.failures, 1);
+        assert_eq!(stats.bytes, 4096);
+        assert!((stats.throughput_bytes_per_sec() - 4096.0).abs() < 1.0);
+    }
+}
@@ -1,52 +1,184 @@
+pub mod blocklist;
 pub mod cache;
+pub mod custom;
+pub mod dump;
+pub mod frames;
+pub mod http;
 pub mod parser;
+pub mod prefetch;
 pub mod providers;
+pub mod quality;
+pub mod registry;
 pub mod renderer;
+pub mod usage;
 
+pub use blocklist::*;
 pub use cache::*;
+pub use custom::*;
+pub use dump::*;
+pub use http::*;
 pub use parser::*;
+pub use prefetch::EmoteDownloader;
 pub use providers::*;
+pub use quality::EmoteQuality;
+pub use registry::ChannelEmoteRegistry;
 pub use renderer::*;
+pub use usage::{EmoteUsage, EmoteUsageCount};
 
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::sync::Arc;
 
 /// Sistema unificado de manejo de emotes para todas las plataformas
 pub struct EmoteSystem {
     pub cache: EmoteCache,
-    providers: HashMap<String, Box<dyn EmoteProvider>>,
+    providers: HashMap<String, Arc<dyn EmoteProvider>>,
     parser: EmoteParser,
     renderer: EmoteRenderer,
+    http: EmoteHttpClient,
+    blocklist: EmoteBlocklist,
+    /// Sets de canal de 7TV que actualiza la EventAPI (`seventv_live_updates`)
+    seventv_sets: Option<seventv_events::SevenTvChannelSets>,
+    /// Emotes de canal de terceros que consulta el parseo
+    channel_sets: ChannelEmoteRegistry,
+    /// Proveedores de `register_provider` que no sustituyen a uno incluido
+    registered: Vec<String>,
+    /// Usos de cada emote por canal en esta sesión
+    usage: EmoteUsage,
     config: crate::config::EmoteConfig,
 }
 
 impl EmoteSystem {
     pub fn new(config: crate::config::EmoteConfig) -> Self {
-        let mut providers: HashMap<String, Box<dyn EmoteProvider>> = HashMap::new();
-
-        // Registrar proveedores por defecto
-        providers.insert("twitch".to_string(), Box::new(TwitchEmoteProvider::new()));
-        providers.insert("bttv".to_string(), Box::new(BTTVEmoteProvider::new()));
-        providers.insert("ffz".to_string(), Box::new(FFZEmoteProvider::new()));
-        providers.insert("7tv".to_string(), Box::new(SevenTVEmoteProvider::new()));
+        let mut providers: HashMap<String, Arc<dyn EmoteProvider>> = HashMap::new();
+        let http = EmoteHttpClient::new(&config.http);
+
+        // Registrar proveedores por defecto (todos comparten el mismo pool de conexiones)
+        providers.insert(
+            "twitch".to_string(),
+            Arc::new(TwitchEmoteProvider::with_http_client(http.clone())),
+        );
+        providers.insert(
+            "bttv".to_string(),
+            Arc::new(BTTVEmoteProvider::with_http_client(http.clone())),
+        );
+        providers.insert(
+            "ffz".to_string(),
+            Arc::new(FFZEmoteProvider::with_http_client(http.clone())),
+        );
+        let seventv_sets = (config.enable_7tv && config.seventv_live_updates)
+            .then(seventv_events::SevenTvChannelSets::default);
+        let mut seventv = SevenTVEmoteProvider::with_http_client(http.clone());
+        if let Some(sets) = &seventv_sets {
+            seventv = seventv.with_live_sets(sets.clone());
+        }
+        providers.insert("7tv".to_string(), Arc::new(seventv));
+        register_custom_providers(&mut providers, &config, &http);
 
         Self {
             cache: EmoteCache::new(config.cache_ttl_hours),
             providers,
             parser: EmoteParser::new(),
-            renderer: EmoteRenderer::new(
-                std::env::temp_dir().join("overlay-native").join("emotes"),
-            ),
+            renderer: EmoteRenderer::new(default_cache_dir(), http.clone()),
+            http,
+            blocklist: EmoteBlocklist::new(&config.blocklist),
+            seventv_sets,
+            channel_sets: ChannelEmoteRegistry::new(config.channel_set_ttl()),
+            registered: Vec::new(),
+            usage: EmoteUsage::default(),
             config,
         }
     }
 
+    /// Cliente HTTP compartido por proveedores y renderer
+    pub fn http_client(&self) -> &EmoteHttpClient {
+        &self.http
+    }
+
+    /// Métricas de descarga (peticiones, bytes, throughput)
+    pub fn download_stats(&self) -> DownloadStats {
+        self.http.stats()
+    }
+
+    /// Sets de 7TV que debe seguir [`seventv_events::follow_emote_sets`]
+    pub fn seventv_live_sets(&self) -> Option<seventv_events::SevenTvChannelSets> {
+        self.seventv_sets.clone()
+    }
+
+    /// Aplica al cache un cambio en vivo del emote set de un canal
+    ///
+    /// Devuelve el id cuya imagen descargada deja de servir (emote quitado).
+    pub async fn apply_emote_set_change(
+        &mut self,
+        change: &seventv_events::EmoteSetChange,
+    ) -> Option<String> {
+        let live_set = self.seventv_sets.as_ref().and_then(|sets| sets.get(&change.channel));
+        if let Some(emotes) = live_set {
+            // 7TV solo tiene sets de canal de Twitch
+            self.channel_sets.replace_set("twitch", &change.channel, "7tv", emotes);
+        }
+        match &change.kind {
+            seventv_events::EmoteSetChangeKind::Added(emote)
+            | seventv_events::EmoteSetChangeKind::Renamed { emote, .. } => {
+                if self.config.cache_enabled {
+                    let emote = self.emote_from_data(emote.clone(), "7tv");
+                    self.cache.insert(emote.id.clone(), emote);
+                }
+                None
+            }
+            seventv_events::EmoteSetChangeKind::Removed(emote) => {
+                self.cache.remove(&emote.id);
+                self.renderer.remove_cached(&emote.id).await;
+                Some(emote.id.clone())
+            }
+        }
+    }
+
     /// Registra un nuevo proveedor de emotes
+    ///
+    /// Con un nombre incluido (`bttv`, `7tv`...) sustituye a ese proveedor;
+    /// con otro nombre se consulta siempre, como los de terceros.
     pub fn register_provider(&mut self, name: String, provider: Box<dyn EmoteProvider>) {
-        self.providers.insert(name, provider);
+        if !is_builtin_provider(&name) && !self.registered.contains(&name) {
+            self.registered.push(name.clone());
+        }
+        self.providers.insert(name, Arc::from(provider));
+    }
+
+    /// Elimina los emotes bloqueados de un mensaje (y sustituye su texto si procede)
+    pub fn apply_blocklist(&self, message: &mut crate::connection::ChatMessage) -> usize {
+        self.blocklist.apply(&mut message.content, &mut message.emotes)
+    }
+
+    /// Informe de intentos de emotes bloqueados, si está habilitado
+    pub fn blocked_emote_report(&self) -> Option<String> {
+        if !self.config.blocklist.report_attempts {
+            return None;
+        }
+        self.blocklist.report()
+    }
+
+    /// Cuenta los emotes de un mensaje ya filtrado por la lista de bloqueo
+    pub fn record_usage(&self, message: &crate::connection::ChatMessage) {
+        self.usage.record(&message.channel, &message.emotes);
+    }
+
+    /// Los `n` emotes más usados en `channel` durante la sesión
+    pub fn top_emotes(&self, channel: &str, n: usize) -> Vec<EmoteUsageCount> {
+        self.usage.top_emotes(channel, n)
+    }
+
+    /// Resumen de los emotes más usados por canal para el log
+    pub fn usage_report(&self) -> Option<String> {
+        self.usage.report(self.config.usage.report_top)
     }
 
     /// Parsea emotes en un mensaje de chat
+    ///
+    /// Retiene el sistema durante las peticiones de red; el pipeline usa por
+    /// separado [`Self::emote_request`], [`EmoteRequest::fetch`] y
+    /// [`Self::resolve_emotes`] para no bloquear a los demás mensajes.
     pub async fn parse_message_emotes(
         &mut self,
         message: &str,
@@ -54,37 +186,60 @@ impl EmoteSystem {
         channel: &str,
         raw_emote_data: &str,
     ) -> Result<Vec<crate::connection::Emote>, EmoteError> {
-        let mut emotes = Vec::new();
+        let fetched = self
+            .emote_request(platform, channel)
+            .fetch(message, raw_emote_data)
+            .await;
+        self.resolve_emotes(message, platform, channel, fetched)
+    }
 
-        // Obtener emotes del parser específico de la plataforma
-        if let Some(provider) = self.providers.get(platform) {
-            match provider.parse_emotes(message, raw_emote_data).await {
-                Ok(platform_emotes) => {
-                    for mut emote in platform_emotes {
-                        // Enriquecer con datos del cache si es necesario
-                        if let Some(cached) = self.cache.get(&emote.id) {
-                            emote.url = cached.url.clone();
-                            emote.is_animated = cached.is_animated;
-                            emote.width = cached.width;
-                            emote.height = cached.height;
-                        }
+    /// Proveedores que hay que consultar para un mensaje de `channel`; basta
+    /// con leer el sistema
+    pub fn emote_request(&self, platform: &str, channel: &str) -> EmoteRequest {
+        let third_party = self.third_party_providers();
+        let needs_sets = !third_party.is_empty()
+            && self
+                .channel_sets
+                .get(platform, channel, std::time::Instant::now())
+                .is_none();
+
+        EmoteRequest {
+            platform: platform.to_string(),
+            channel: channel.to_string(),
+            platform_provider: self.providers.get(platform).cloned(),
+            channel_providers: needs_sets.then(|| self.named_providers(&third_party)),
+        }
+    }
 
-                        emotes.push(emote);
-                    }
-                }
-                Err(_) => {
-                    // Handle provider errors gracefully - continue with empty emotes
-                    // This allows the system to remain functional even when providers fail
-                }
+    /// Completa los emotes descargados con el cache y los sets de canal, y
+    /// guarda los sets nuevos; no hace peticiones de red
+    pub fn resolve_emotes(
+        &mut self,
+        message: &str,
+        platform: &str,
+        channel: &str,
+        fetched: FetchedEmotes,
+    ) -> Result<Vec<crate::connection::Emote>, EmoteError> {
+        if let Some(sets) = fetched.channel_sets {
+            self.store_channel_sets(platform, channel, sets);
+        }
+
+        let mut emotes = Vec::new();
+        for mut emote in fetched.platform_emotes {
+            // Enriquecer con datos del cache si es necesario
+            if let Some(cached) = self.cache.get(&emote.id) {
+                emote.url = cached.url.clone();
+                emote.is_animated = cached.is_animated;
+                emote.width = cached.width;
+                emote.height = cached.height;
             }
+
+            emotes.push(emote);
         }
 
-        // Buscar emotes de terceros (BTTV, FFZ, 7TV) si está habilitado
-        if self.config.enable_bttv || self.config.enable_ffz || self.config.enable_7tv {
-            let third_party_emotes = self
-                .parse_third_party_emotes(message, platform, channel)
-                .await?;
-            emotes.extend(third_party_emotes);
+        // Buscar emotes de terceros (BTTV, FFZ, 7TV y personalizados) si hay alguno habilitado
+        if !self.third_party_providers().is_empty() {
+            emotes.extend(self.parse_third_party_emotes(message, platform, channel));
         }
 
         // Limitar número de emotes por mensaje
@@ -95,34 +250,38 @@ impl EmoteSystem {
         Ok(emotes)
     }
 
-    /// Parsea emotes de terceros (BTTV, FFZ, 7TV)
-    async fn parse_third_party_emotes(
-        &mut self,
+    /// Busca en el mensaje los emotes de terceros (BTTV, FFZ, 7TV) del canal
+    fn parse_third_party_emotes(
+        &self,
         message: &str,
         platform: &str,
         channel: &str,
-    ) -> Result<Vec<crate::connection::Emote>, EmoteError> {
+    ) -> Vec<crate::connection::Emote> {
         let mut emotes = Vec::new();
-        let known_emotes = self.get_known_third_party_emotes(platform, channel).await?;
-
+        let Some(known_emotes) = self
+            .channel_sets
+            .get(platform, channel, std::time::Instant::now())
+        else {
+            return emotes;
+        };
         for (provider_name, provider_emotes) in known_emotes {
             for emote_data in provider_emotes {
                 let positions = self.parser.find_emote_positions(message, &emote_data.name);
 
                 if !positions.is_empty() {
                     emotes.push(crate::connection::Emote {
-                        id: emote_data.id,
-                        name: emote_data.name,
-                        source: self.map_provider_to_source(&provider_name),
+                        id: emote_data.id.clone(),
+                        name: emote_data.name.clone(),
+                        source: self.map_provider_to_source(provider_name),
                         positions,
-                        url: emote_data.url,
+                        url: emote_data.url.clone(),
                         is_animated: emote_data.is_animated,
                         width: emote_data.width,
                         height: emote_data.height,
                         metadata: crate::connection::EmoteMetadata {
                             is_zero_width: emote_data.is_zero_width,
                             modifier: emote_data.modifier,
-                            emote_set_id: emote_data.emote_set_id,
+                            emote_set_id: emote_data.emote_set_id.clone(),
                             tier: None,
                         },
                     });
@@ -130,39 +289,114 @@ impl EmoteSystem {
             }
         }
 
-        Ok(emotes)
+        emotes
     }
 
-    /// Obtiene emotes conocidos de terceros para un canal
-    async fn get_known_third_party_emotes(
+    /// Descarga los sets de canal de los proveedores de terceros y los guarda
+    /// en el registro; devuelve los que fallaron (proveedor, error)
+    pub async fn refresh_channel(&mut self, platform: &str, channel: &str) -> Vec<(String, String)> {
+        let providers = self.named_providers(&self.third_party_providers());
+        let sets = fetch_channel_sets(&providers, platform, channel).await;
+        self.store_channel_sets(platform, channel, sets)
+    }
+
+    /// Guarda en el registro los sets descargados; devuelve los que fallaron
+    fn store_channel_sets(
         &mut self,
         platform: &str,
         channel: &str,
-    ) -> Result<HashMap<String, Vec<EmoteData>>, EmoteError> {
-        let mut result = HashMap::new();
+        sets: ChannelSets,
+    ) -> Vec<(String, String)> {
+        self.channel_sets.store(
+            platform,
+            channel,
+            sets.sets,
+            !sets.errors.is_empty(),
+            std::time::Instant::now(),
+        );
+        sets.errors
+    }
 
-        if self.config.enable_bttv {
-            if let Some(provider) = self.providers.get("bttv") {
-                let emotes = provider.get_channel_emotes(platform, channel).await?;
-                result.insert("bttv".to_string(), emotes);
-            }
+    fn named_providers(&self, names: &[String]) -> Vec<(String, Arc<dyn EmoteProvider>)> {
+        names
+            .iter()
+            .filter_map(|name| Some((name.clone(), self.providers.get(name)?.clone())))
+            .collect()
+    }
+
+    /// Olvida los sets de canal guardados; el próximo mensaje los vuelve a
+    /// pedir. `false` si el canal no estaba cargado
+    pub fn invalidate_channel(&mut self, platform: &str, channel: &str) -> bool {
+        if let Some(sets) = &self.seventv_sets {
+            sets.forget(channel);
         }
+        self.channel_sets.invalidate(platform, channel)
+    }
 
-        if self.config.enable_ffz {
-            if let Some(provider) = self.providers.get("ffz") {
-                let emotes = provider.get_channel_emotes(platform, channel).await?;
-                result.insert("ffz".to_string(), emotes);
+    /// Proveedores de terceros habilitados: BTTV, FFZ, 7TV, los personalizados
+    /// y los registrados desde código
+    fn third_party_providers(&self) -> Vec<String> {
+        let builtin = [
+            ("bttv", self.config.enable_bttv),
+            ("ffz", self.config.enable_ffz),
+            ("7tv", self.config.enable_7tv),
+        ];
+        let mut names: Vec<String> = builtin
+            .into_iter()
+            .filter(|(_, enabled)| *enabled)
+            .map(|(name, _)| name.to_string())
+            .chain(
+                self.config
+                    .custom_providers
+                    .iter()
+                    .filter(|custom| custom.enabled && !is_builtin_provider(&custom.name))
+                    .map(|custom| custom.name.clone()),
+            )
+            .collect();
+        for name in &self.registered {
+            if !names.contains(name) {
+                names.push(name.clone());
             }
         }
+        names
+    }
 
-        if self.config.enable_7tv {
-            if let Some(provider) = self.providers.get("7tv") {
-                let emotes = provider.get_channel_emotes(platform, channel).await?;
-                result.insert("7tv".to_string(), emotes);
+    /// Resuelve el conjunto de emotes efectivo de un canal (todos los proveedores habilitados)
+    ///
+    /// Los fallos de un proveedor no abortan la resolución; quedan en `errors`.
+    pub async fn dump_emote_set(&self, platform: &str, channel: &str) -> EmoteSetDump {
+        let mut enabled = self.third_party_providers();
+        if platform == "twitch" {
+            enabled.insert(0, "twitch".to_string());
+        }
+
+        let mut sets = Vec::new();
+        let mut errors = Vec::new();
+        for name in &enabled {
+            let Some(provider) = self.providers.get(name) else {
+                continue;
+            };
+
+            if self.config.enable_channel_emotes {
+                match provider.get_channel_emotes(platform, channel).await {
+                    Ok(emotes) => sets.push((name.to_string(), EmoteScope::Channel, emotes)),
+                    Err(e) => errors.push((format!("{}/channel", name), e.to_string())),
+                }
+            }
+            if self.config.enable_global_emotes {
+                match provider.get_global_emotes().await {
+                    Ok(emotes) => sets.push((name.to_string(), EmoteScope::Global, emotes)),
+                    Err(e) => errors.push((format!("{}/global", name), e.to_string())),
+                }
             }
         }
 
-        Ok(result)
+        EmoteSetDump {
+            platform: platform.to_string(),
+            channel: channel.to_string(),
+            emotes: merge_emote_sets(sets),
+            errors,
+        }
     }
 
     /// Mapea nombre de proveedor a source de emote
@@ -177,6 +411,33 @@ impl EmoteSystem {
         }
     }
 
+    /// Pone en cola la descarga de los emotes de canal más usados
+    /// (`prefetch.top_emotes`); devuelve cuántos se encolaron
+    pub async fn prefetch_channel_emotes(&self, platform: &str, channel: &str) -> usize {
+        let downloader = EmoteDownloader::global();
+        let limit = downloader.top_emotes();
+        if limit == 0 {
+            return 0;
+        }
+
+        let dump = self.dump_emote_set(platform, channel).await;
+        let uses: HashMap<String, u64> = self
+            .cache
+            .get_most_accessed(usize::MAX)
+            .into_iter()
+            .map(|(_, emote, count)| (emote.id.clone(), count))
+            .collect();
+        prefetch::top_channel_emotes(&dump, &uses, limit)
+            .into_iter()
+            .filter(|emote| {
+                emote
+                    .url
+                    .as_deref()
+                    .is_some_and(|url| downloader.request(&emote.id, url))
+            })
+            .count()
+    }
+
     /// Precarga emotes globales
     pub async fn preload_global_emotes(&mut self) -> Result<(), EmoteError> {
         let mut total_emotes = 0;
@@ -191,25 +452,8 @@ impl EmoteSystem {
 
                     for emote_data in global_emotes {
                         if self.config.cache_enabled {
-                            self.cache.insert(
-                                emote_data.id.clone(),
-                                crate::connection::Emote {
-                                    id: emote_data.id.clone(),
-                                    name: emote_data.name.clone(),
-                                    source: self.map_provider_to_source(name),
-                                    positions: Vec::new(),
-                                    url: emote_data.url.clone(),
-                                    is_animated: emote_data.is_animated,
-                                    width: emote_data.width,
-                                    height: emote_data.height,
-                                    metadata: crate::connection::EmoteMetadata {
-                                        is_zero_width: emote_data.is_zero_width,
-                                        modifier: emote_data.modifier,
-                                        emote_set_id: emote_data.emote_set_id,
-                                        tier: None,
-                                    },
-                                },
-                            );
+                            let emote = self.emote_from_data(emote_data, name);
+                            self.cache.insert(emote.id.clone(), emote);
                         }
                     }
 
@@ -218,6 +462,8 @@ impl EmoteSystem {
                 }
                 Err(e) => {
                     eprintln!("   ⚠️  Failed to load {} emotes: {}", name, e);
+                    crate::health::ErrorBudget::global()
+                        .record_failure(&format!("provider:{}", name), &e.to_string());
                     failed_providers.push((name.clone(), e.to_string()));
                 }
             }
@@ -235,11 +481,121 @@ impl EmoteSystem {
         Ok(())
     }
 
+    /// Emote cacheable (sin posiciones) a partir de los datos de un proveedor
+    fn emote_from_data(&self, emote_data: EmoteData, provider: &str) -> crate::connection::Emote {
+        crate::connection::Emote {
+            id: emote_data.id,
+            name: emote_data.name,
+            source: self.map_provider_to_source(provider),
+            positions: Vec::new(),
+            url: emote_data.url,
+            is_animated: emote_data.is_animated,
+            width: emote_data.width,
+            height: emote_data.height,
+            metadata: crate::connection::EmoteMetadata {
+                is_zero_width: emote_data.is_zero_width,
+                modifier: emote_data.modifier,
+                emote_set_id: emote_data.emote_set_id,
+                tier: None,
+            },
+        }
+    }
+
     /// Limpia el cache de emotes
     pub fn clear_cache(&mut self) {
         self.cache.clear();
     }
 
+    /// Estadísticas del cache en memoria
+    pub fn cache_stats(&mut self) -> cache::CacheStats {
+        self.cache.stats()
+    }
+
+    /// Invalida un emote por id (cache en memoria e imagen en disco)
+    ///
+    /// Devuelve `false` si el emote no estaba en ninguno de los dos.
+    pub async fn invalidate_emote(&mut self, id: &str) -> bool {
+        let cached = self.cache.remove(id).is_some();
+        let files = self.renderer.remove_cached(id).await;
+        cached || files > 0
+    }
+
+    /// Elimina del cache todos los emotes de un proveedor y devuelve sus ids
+    pub async fn clear_provider(&mut self, provider: &str) -> Result<Vec<String>, EmoteError> {
+        if !self.providers.contains_key(provider) {
+            return Err(EmoteError::ProviderNotFound(provider.to_string()));
+        }
+
+        let ids = self
+            .cache
+            .remove_by_source(&self.map_provider_to_source(provider));
+        for id in &ids {
+            self.renderer.remove_cached(id).await;
+        }
+        Ok(ids)
+    }
+
+    /// Vuelve a descargar los emotes de canal de los proveedores habilitados
+    ///
+    /// Los emotes cuya URL cambió (reemplazados en BTTV/7TV) pierden también su
+    /// imagen en disco y se listan en `changed`.
+    pub async fn refresh_channel_emotes(&mut self, platform: &str, channel: &str) -> ChannelRefresh {
+        let mut enabled = self.third_party_providers();
+        if platform == "twitch" {
+            enabled.insert(0, "twitch".to_string());
+        }
+
+        let mut refresh = ChannelRefresh {
+            platform: platform.to_string(),
+            channel: channel.to_string(),
+            ..ChannelRefresh::default()
+        };
+        if let Some(sets) = &self.seventv_sets {
+            sets.forget(channel);
+        }
+        let mut channel_sets = Vec::new();
+        for name in &enabled {
+            let Some(provider) = self.providers.get(name) else {
+                continue;
+            };
+
+            let emotes = match provider.get_channel_emotes(platform, channel).await {
+                Ok(emotes) => emotes,
+                Err(e) => {
+                    refresh.errors.push((name.to_string(), e.to_string()));
+                    continue;
+                }
+            };
+
+            refresh.refreshed.insert(name.to_string(), emotes.len());
+            if name != "twitch" {
+                channel_sets.push((name.to_string(), emotes.clone()));
+            }
+            for emote_data in emotes {
+                let emote = self.emote_from_data(emote_data, name);
+                let changed = self
+                    .cache
+                    .peek(&emote.id)
+                    .is_some_and(|cached| cached.url != emote.url);
+                if changed {
+                    self.renderer.remove_cached(&emote.id).await;
+                    refresh.changed.push(emote.id.clone());
+                }
+                if self.config.cache_enabled {
+                    self.cache.insert(emote.id.clone(), emote);
+                }
+            }
+        }
+        self.channel_sets.store(
+            platform,
+            channel,
+            channel_sets,
+            !refresh.errors.is_empty(),
+            std::time::Instant::now(),
+        );
+        refresh
+    }
+
     /// Verifica si el cache está expirado
     pub fn is_cache_expired(&self) -> bool {
         self.cache.is_expired()
@@ -247,11 +603,134 @@ impl EmoteSystem {
 
     /// Actualiza la configuración
     pub fn update_config(&mut self, config: crate::config::EmoteConfig) {
+        for custom in &self.config.custom_providers {
+            if !is_builtin_provider(&custom.name) {
+                self.providers.remove(&custom.name);
+            }
+        }
+        register_custom_providers(&mut self.providers, &config, &self.http);
         self.config = config;
         self.cache = EmoteCache::new(self.config.cache_ttl_hours);
+        // Los proveedores habilitados pueden haber cambiado
+        self.channel_sets = ChannelEmoteRegistry::new(self.config.channel_set_ttl());
+        self.blocklist = EmoteBlocklist::new(&self.config.blocklist);
+    }
+}
+
+/// Nombres reservados por los proveedores incluidos
+const BUILTIN_PROVIDERS: &[&str] = &["twitch", "kick", "youtube", "bttv", "ffz", "7tv"];
+
+fn is_builtin_provider(name: &str) -> bool {
+    BUILTIN_PROVIDERS.contains(&name)
+}
+
+/// Registra los proveedores habilitados de `emotes.custom_providers`
+fn register_custom_providers(
+    providers: &mut HashMap<String, Arc<dyn EmoteProvider>>,
+    config: &crate::config::EmoteConfig,
+    http: &EmoteHttpClient,
+) {
+    for custom in config.custom_providers.iter().filter(|c| c.enabled) {
+        if custom.name.is_empty() || is_builtin_provider(&custom.name) {
+            eprintln!(
+                "⚠️  Skipping custom emote provider '{}': name is empty or reserved",
+                custom.name
+            );
+            continue;
+        }
+        providers.insert(
+            custom.name.clone(),
+            Arc::new(CustomEmoteProvider::with_http_client(
+                custom.clone(),
+                http.clone(),
+            )),
+        );
+    }
+}
+
+/// Proveedores a consultar para un mensaje, copiados fuera del sistema
+pub struct EmoteRequest {
+    platform: String,
+    channel: String,
+    platform_provider: Option<Arc<dyn EmoteProvider>>,
+    /// Proveedores de terceros si el canal no tiene sets vigentes
+    channel_providers: Option<Vec<(String, Arc<dyn EmoteProvider>)>>,
+}
+
+impl EmoteRequest {
+    /// Hace las peticiones de red del parseo sin tocar el sistema
+    pub async fn fetch(self, message: &str, raw_emote_data: &str) -> FetchedEmotes {
+        // Si el proveedor falla el mensaje sigue, sin emotes de la plataforma
+        let platform_emotes = match &self.platform_provider {
+            Some(provider) => provider
+                .parse_emotes(message, raw_emote_data)
+                .await
+                .unwrap_or_default(),
+            None => Vec::new(),
+        };
+        let channel_sets = match &self.channel_providers {
+            Some(providers) => {
+                Some(fetch_channel_sets(providers, &self.platform, &self.channel).await)
+            }
+            None => None,
+        };
+
+        FetchedEmotes {
+            platform_emotes,
+            channel_sets,
+        }
     }
 }
 
+/// Respuesta de [`EmoteRequest::fetch`] para [`EmoteSystem::resolve_emotes`]
+pub struct FetchedEmotes {
+    platform_emotes: Vec<crate::connection::Emote>,
+    channel_sets: Option<ChannelSets>,
+}
+
+/// Sets de canal descargados y proveedores que fallaron (proveedor, error)
+struct ChannelSets {
+    sets: Vec<(String, Vec<EmoteData>)>,
+    errors: Vec<(String, String)>,
+}
+
+async fn fetch_channel_sets(
+    providers: &[(String, Arc<dyn EmoteProvider>)],
+    platform: &str,
+    channel: &str,
+) -> ChannelSets {
+    let mut sets = Vec::new();
+    let mut errors = Vec::new();
+    for (name, provider) in providers {
+        match provider.get_channel_emotes(platform, channel).await {
+            Ok(emotes) => sets.push((name.clone(), emotes)),
+            Err(e) => {
+                eprintln!(
+                    "[EMOTES] ⚠️ Failed to load {} channel emotes for {}: {}",
+                    name, channel, e
+                );
+                crate::health::ErrorBudget::global()
+                    .record_failure(&format!("provider:{}", name), &e.to_string());
+                errors.push((name.clone(), e.to_string()));
+            }
+        }
+    }
+    ChannelSets { sets, errors }
+}
+
+/// Resultado de `refresh_channel_emotes`
+#[derive(Debug, Clone, Default, Serialize, JsonSchema)]
+pub struct ChannelRefresh {
+    pub platform: String,
+    pub channel: String,
+    /// Emotes descargados por proveedor
+    pub refreshed: HashMap<String, usize>,
+    /// Ids cuya URL cambió respecto a la versión en cache
+    pub changed: Vec<String>,
+    /// Proveedores que fallaron: (proveedor, error)
+    pub errors: Vec<(String, String)>,
+}
+
 /// Trait para proveedores de emotes
 #[async_trait::async_trait]
 pub trait EmoteProvider: Send + Sync {
@@ -292,6 +771,7 @@ pub struct EmoteData {
 
 /// Errores del sistema de emotes
 #[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
 pub enum EmoteError {
     #[error("Error de parseo: {0}")]
     ParseError(String),
@@ -76,8 +76,10 @@ impl EmoteParser {
                 if let (Ok(start), Ok(end)) =
                     (pos_parts[0].parse::<usize>(), pos_parts[1].parse::<usize>())
                 {
-                    if start < message.len() && end < message.len() && start <= end {
-                        let emote_name = message[start..=end].to_string();
+                    if let Some(emote_name) =
+                        crate::text::slice_chars_inclusive(message, start, end)
+                    {
+                        let emote_name = emote_name.to_string();
 
                         let source = if emote_id.starts_with("emotesv2_") {
                             EmoteSource::TwitchGlobal
@@ -92,10 +94,10 @@ impl EmoteParser {
                             name: emote_name,
                             source,
                             positions: vec![TextPosition { start, end }],
-                            url: Some(format!(
-                                "https://static-cdn.jtvnw.net/emoticons/v2/{}/default/dark/1.0",
-                                emote_id
-                            )),
+                            url: Some(
+                                crate::emotes::EmoteQuality::global()
+                                    .twitch_url(emote_id, "default"),
+                            ),
                             is_animated: false,
                             width: Some(28),
                             height: Some(28),
@@ -114,25 +116,15 @@ impl EmoteParser {
         emotes
     }
 
-    /// Detecta emotes de terceros en un mensaje
+    /// Detecta emotes de terceros en un mensaje (palabras completas, posiciones
+    /// en caracteres)
     pub fn detect_third_party_emotes(&self, message: &str) -> Vec<Emote> {
         let mut emotes = Vec::new();
-        let words: Vec<&str> = message.split_whitespace().collect();
 
-        for (word_index, word) in words.iter().enumerate() {
+        for (start_pos, word) in crate::text::words(message) {
             // Buscar en emotes conocidos
-            if let Some(emote_info) = self.known_emotes.get(*word) {
-                // Calcular posición del emote en el mensaje original
-                let start_pos = if word_index == 0 {
-                    0
-                } else {
-                    // Encontrar la posición sumando las longitudes de las palabras anteriores + espacios
-                    words[..word_index]
-                        .iter()
-                        .map(|w| w.len() + 1) // +1 por el espacio
-                        .sum::<usize>()
-                };
-                let end_pos = start_pos + word.len() - 1;
+            if let Some(emote_info) = self.known_emotes.get(word) {
+                let end_pos = start_pos + word.chars().count() - 1;
 
                 emotes.push(Emote {
                     id: emote_info.id.clone(),
@@ -159,30 +151,22 @@ impl EmoteParser {
         emotes
     }
 
-    /// Encuentra posiciones de un emote específico en el texto
+    /// Encuentra las apariciones de un emote como palabra completa; las
+    /// posiciones son índices de carácter con el final incluido, como las de Twitch
     pub fn find_emote_positions(&self, text: &str, emote_name: &str) -> Vec<TextPosition> {
-        let mut positions = Vec::new();
-        let mut start = 0;
-
-        // Handle empty emote name
         if emote_name.is_empty() {
             return Vec::new();
         }
-
-        while let Some(pos) = text[start..].find(emote_name) {
-            let actual_start = start + pos;
-            let actual_end = actual_start + emote_name.len() - 1;
-
-            // Add position for any match (not just word boundaries)
-            positions.push(TextPosition {
-                start: actual_start,
-                end: actual_end,
-            });
-
-            start = actual_end + 1;
-        }
-
-        positions
+        let length = emote_name.chars().count();
+
+        crate::text::words(text)
+            .into_iter()
+            .filter(|(_, word)| *word == emote_name)
+            .map(|(start, _)| TextPosition {
+                start,
+                end: start + length - 1,
+            })
+            .collect()
     }
 
     /// Parsea emotes usando todos los métodos disponibles
@@ -227,26 +211,7 @@ impl EmoteParser {
 
     /// Extrae texto plano del mensaje reemplazando emotes con placeholders
     pub fn extract_plain_text(&self, message: &str, emotes: &[Emote]) -> String {
-        let mut result = message.to_string();
-
-        // Ordenar emotes por posición de inicio en orden descendente
-        let mut sorted_emotes = emotes.to_vec();
-        sorted_emotes.sort_by(|a, b| {
-            let a_start = a.positions.first().map(|p| p.start).unwrap_or(0);
-            let b_start = b.positions.first().map(|p| p.start).unwrap_or(0);
-            b_start.cmp(&a_start)
-        });
-
-        for emote in sorted_emotes {
-            for position in &emote.positions {
-                if position.start < result.len() && position.end < result.len() {
-                    result
-                        .replace_range(position.start..=position.end, &format!(":{}", emote.name));
-                }
-            }
-        }
-
-        result
+        crate::connection::utils::replace_emotes_with_placeholders(message, emotes)
     }
 
     /// Valida si un texto es un emote válido
@@ -361,6 +326,11 @@ mod tests {
         assert_eq!(positions[0].end, 10);
         assert_eq!(positions[1].start, 18);
         assert_eq!(positions[1].end, 22);
+
+        // Solo palabras completas, en caracteres
+        let positions = parser.find_emote_positions("¡ñ Kappa KappaPride Kappa, Kappa", "Kappa");
+        let positions: Vec<_> = positions.iter().map(|p| (p.start, p.end)).collect();
+        assert_eq!(positions, vec![(3, 7), (27, 31)]);
     }
 
     #[test]
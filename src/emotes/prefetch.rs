@@ -0,0 +1,259 @@
+//! Descarga de imágenes de emotes en segundo plano
+//!
+//! Un único descargador compartido con una cola acotada
+//! (`emotes.prefetch.queue_size`): las ventanas piden las imágenes que les
+//! faltan, cada emote se descarga una sola vez aunque lo pidan varias a la vez,
+//! y al conectar a un canal se precargan sus emotes más usados para que la
+//! primera ventana que los muestre ya los tenga.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Duration;
+
+use tokio::sync::mpsc;
+
+use super::{EmoteHttpClient, EmoteScope, EmoteSetDump, ResolvedEmote};
+use crate::config::EmotePrefetchConfig;
+
+static GLOBAL_DOWNLOADER: OnceLock<EmoteDownloader> = OnceLock::new();
+
+/// Tiempo máximo de una descarga
+const DOWNLOAD_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Imagen pendiente de descargar
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DownloadJob {
+    pub id: String,
+    pub url: String,
+}
+
+#[derive(Debug, Default)]
+struct DownloaderState {
+    config: EmotePrefetchConfig,
+    sender: Option<mpsc::Sender<DownloadJob>>,
+    /// Imágenes descargadas por id de emote
+    images: HashMap<String, Arc<Vec<u8>>>,
+    /// En cola o descargándose
+    pending: HashSet<String>,
+    /// Peticiones descartadas con la cola llena
+    dropped: u64,
+}
+
+/// Descargador de imágenes de emotes, compartido por todas las ventanas
+#[derive(Debug, Default)]
+pub struct EmoteDownloader {
+    state: Mutex<DownloaderState>,
+}
+
+impl EmoteDownloader {
+    pub fn new(config: EmotePrefetchConfig) -> Self {
+        Self {
+            state: Mutex::new(DownloaderState {
+                config,
+                ..DownloaderState::default()
+            }),
+        }
+    }
+
+    pub fn global() -> &'static EmoteDownloader {
+        GLOBAL_DOWNLOADER.get_or_init(|| Self::new(EmotePrefetchConfig::default()))
+    }
+
+    /// Aplica la configuración; el tamaño de la cola y los workers solo
+    /// cambian al reiniciar
+    pub fn configure(&self, config: EmotePrefetchConfig) {
+        self.lock().config = config;
+    }
+
+    /// Emotes de canal que se precargan al conectar
+    pub fn top_emotes(&self) -> usize {
+        self.lock().config.top_emotes
+    }
+
+    /// Arranca los workers en el runtime actual; no hace nada si ya estaban
+    pub fn start(&'static self, http: EmoteHttpClient) {
+        let Some(receiver) = self.open_queue() else {
+            return;
+        };
+        let receiver = Arc::new(tokio::sync::Mutex::new(receiver));
+        for _ in 0..self.lock().config.workers.max(1) {
+            let receiver = receiver.clone();
+            let http = http.clone();
+            tokio::spawn(async move {
+                loop {
+                    // El lock se suelta antes de descargar
+                    let Some(job) = receiver.lock().await.recv().await else {
+                        break;
+                    };
+                    let result = http.get_bytes(&job.url, Some(DOWNLOAD_TIMEOUT)).await;
+                    if let Err(e) = &result {
+                        eprintln!("[EMOTES] ⚠️ Failed to download emote {}: {}", job.id, e);
+                    }
+                    self.complete(&job.id, result.ok());
+                }
+            });
+        }
+    }
+
+    fn open_queue(&self) -> Option<mpsc::Receiver<DownloadJob>> {
+        let mut state = self.lock();
+        if state.sender.is_some() {
+            return None;
+        }
+        let (sender, receiver) = mpsc::channel(state.config.queue_size.max(1));
+        state.sender = Some(sender);
+        Some(receiver)
+    }
+
+    /// Pone en cola la imagen si no está descargada ni pedida; `false` si no
+    /// se encoló (ya estaba, cola llena o descargador sin arrancar)
+    ///
+    /// No bloquea: se puede llamar desde el hilo de las ventanas.
+    pub fn request(&self, id: &str, url: &str) -> bool {
+        let mut state = self.lock();
+        if state.images.contains_key(id) || state.pending.contains(id) {
+            return false;
+        }
+        let Some(sender) = &state.sender else {
+            return false;
+        };
+        let job = DownloadJob {
+            id: id.to_string(),
+            url: url.to_string(),
+        };
+        if sender.try_send(job).is_err() {
+            state.dropped += 1;
+            return false;
+        }
+        state.pending.insert(id.to_string());
+        true
+    }
+
+    fn complete(&self, id: &str, bytes: Option<Vec<u8>>) {
+        let mut state = self.lock();
+        state.pending.remove(id);
+        if let Some(bytes) = bytes {
+            state.images.insert(id.to_string(), Arc::new(bytes));
+        }
+    }
+
+    /// Imagen ya descargada del emote
+    pub fn image(&self, id: &str) -> Option<Arc<Vec<u8>>> {
+        self.lock().images.get(id).cloned()
+    }
+
+    /// Olvida las imágenes de estos emotes para que se vuelvan a descargar;
+    /// devuelve cuántas había
+    pub fn forget(&self, ids: &[String]) -> usize {
+        let mut state = self.lock();
+        ids.iter()
+            .filter(|id| state.images.remove(*id).is_some())
+            .count()
+    }
+
+    /// Peticiones descartadas por tener la cola llena
+    pub fn dropped(&self) -> u64 {
+        self.lock().dropped
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, DownloaderState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Los `limit` emotes de canal con URL que más se han usado (`uses` por id);
+/// a igualdad, en el orden del set
+pub fn top_channel_emotes<'a>(
+    dump: &'a EmoteSetDump,
+    uses: &HashMap<String, u64>,
+    limit: usize,
+) -> Vec<&'a ResolvedEmote> {
+    let mut emotes: Vec<_> = dump
+        .emotes
+        .iter()
+        .filter(|emote| emote.scope == EmoteScope::Channel && emote.url.is_some())
+        .collect();
+    // sort_by es estable: los que no se han usado conservan el orden del set
+    emotes.sort_by(|a, b| {
+        let uses_of = |emote: &ResolvedEmote| uses.get(&emote.id).copied().unwrap_or(0);
+        uses_of(b).cmp(&uses_of(a))
+    });
+    emotes.truncate(limit);
+    emotes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn channel_emote(id: &str, url: Option<&str>) -> ResolvedEmote {
+        ResolvedEmote {
+            name: id.to_uppercase(),
+            id: id.to_string(),
+            provider: "7tv".to_string(),
+            scope: EmoteScope::Channel,
+            url: url.map(str::to_string),
+            animated: false,
+            zero_width: false,
+            modifier: false,
+            shadowed: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_requests_are_deduplicated_and_bounded() {
+        let downloader = EmoteDownloader::new(EmotePrefetchConfig {
+            queue_size: 2,
+            ..EmotePrefetchConfig::default()
+        });
+        assert!(!downloader.request("a", "https://cdn/a"));
+
+        let mut queue = downloader.open_queue().unwrap();
+        assert!(downloader.open_queue().is_none());
+        assert!(downloader.request("a", "https://cdn/a"));
+        assert!(!downloader.request("a", "https://cdn/a"));
+        assert!(downloader.request("b", "https://cdn/b"));
+        assert!(!downloader.request("c", "https://cdn/c"));
+        assert_eq!(downloader.dropped(), 1);
+
+        let job = queue.try_recv().unwrap();
+        assert_eq!(job.id, "a");
+        downloader.complete(&job.id, Some(vec![1, 2, 3]));
+        assert_eq!(downloader.image("a").unwrap().as_slice(), [1, 2, 3]);
+        assert!(!downloader.request("a", "https://cdn/a"));
+
+        // Una descarga fallida se puede volver a pedir
+        downloader.complete("b", None);
+        let _ = queue.try_recv();
+        assert!(downloader.request("b", "https://cdn/b"));
+
+        assert_eq!(downloader.forget(&["a".to_string(), "x".to_string()]), 1);
+        assert!(downloader.image("a").is_none());
+        assert!(downloader.request("a", "https://cdn/a"));
+    }
+
+    #[test]
+    fn test_top_channel_emotes_prefers_most_used() {
+        let mut global = channel_emote("g", Some("https://cdn/g"));
+        global.scope = EmoteScope::Global;
+        let dump = EmoteSetDump {
+            platform: "twitch".to_string(),
+            channel: "chan".to_string(),
+            emotes: vec![
+                channel_emote("a", Some("https://cdn/a")),
+                channel_emote("b", Some("https://cdn/b")),
+                channel_emote("nourl", None),
+                global,
+                channel_emote("c", Some("https://cdn/c")),
+            ],
+            errors: Vec::new(),
+        };
+        let uses = HashMap::from([("c".to_string(), 5), ("g".to_string(), 9)]);
+
+        let top: Vec<_> = top_channel_emotes(&dump, &uses, 2)
+            .iter()
+            .map(|emote| emote.id.as_str())
+            .collect();
+        assert_eq!(top, ["c", "a"]);
+    }
+}
@@ -1,10 +1,12 @@
+pub mod seventv_events;
+
 use async_trait::async_trait;
-use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::time::Duration;
 
-use super::{EmoteData, EmoteError, EmoteProvider};
+use self::seventv_events::{ActiveEmote, SevenTvChannelSets};
+use super::{EmoteData, EmoteError, EmoteHttpClient, EmoteProvider, EmoteQuality};
 use crate::connection::{Emote, EmoteSource, TextPosition};
 
 /// BTTV Emote structure
@@ -28,31 +30,27 @@ type BTTVGlobalResponse = Vec<BTTVEmote>;
 /// Cliente HTTP para APIs de emotes
 #[derive(Clone)]
 pub struct EmoteApiClient {
-    client: Client,
-    timeout: Duration,
+    http: EmoteHttpClient,
+    timeout: Option<Duration>,
 }
 
 impl EmoteApiClient {
-    pub fn new() -> Self {
+    /// Crea un cliente que usa el pool de conexiones indicado
+    pub fn with_http_client(http: EmoteHttpClient) -> Self {
         Self {
-            client: Client::builder()
-                .timeout(Duration::from_secs(10))
-                .user_agent("Overlay-Native/1.0")
-                .build()
-                .unwrap_or_default(),
-            timeout: Duration::from_secs(10),
+            http,
+            timeout: None,
         }
     }
 
-    pub fn with_timeout(timeout_secs: u64) -> Self {
-        Self {
-            client: Client::builder()
-                .timeout(Duration::from_secs(timeout_secs))
-                .user_agent("Overlay-Native/1.0")
-                .build()
-                .unwrap_or_default(),
-            timeout: Duration::from_secs(timeout_secs),
-        }
+    pub fn with_timeout(mut self, timeout_secs: u64) -> Self {
+        self.timeout = Some(Duration::from_secs(timeout_secs));
+        self
+    }
+
+    /// Cliente HTTP compartido subyacente
+    pub fn http_client(&self) -> &EmoteHttpClient {
+        &self.http
     }
 
     pub fn with_retry_config(mut self, max_retries: u32, base_delay_ms: u64) -> Self {
@@ -93,53 +91,23 @@ impl EmoteApiClient {
     }
 
     async fn try_get_json<T: for<'de> Deserialize<'de>>(&self, url: &str) -> Result<T, EmoteError> {
-        let response = self
-            .client
-            .get(url)
-            .send()
-            .await
-            .map_err(|e| EmoteError::NetworkError(format!("Failed to fetch {}: {}", url, e)))?;
-
-        let status = response.status();
-        if !status.is_success() {
-            return Err(EmoteError::NetworkError(format!(
-                "HTTP {} from {}: {}",
-                status.as_u16(),
-                url,
-                response
-                    .text()
-                    .await
-                    .unwrap_or_else(|_| "Unable to read response body".to_string())
-            )));
-        }
-
-        response.json().await.map_err(|e| {
+        let body = self.http.get_revalidated(url, self.timeout).await?;
+
+        serde_json::from_slice(&body).map_err(|e| {
             EmoteError::NetworkError(format!("Failed to parse JSON from {}: {}", url, e))
         })
     }
 }
 
-impl Default for EmoteApiClient {
-    fn default() -> Self {
-        Self::new()
-    }
-}
-
 /// Proveedor de emotes de Twitch
 pub struct TwitchEmoteProvider {
     api_client: EmoteApiClient,
 }
 
 impl TwitchEmoteProvider {
-    pub fn new() -> Self {
+    pub fn with_http_client(http: EmoteHttpClient) -> Self {
         Self {
-            api_client: EmoteApiClient::new(),
-        }
-    }
-
-    pub fn with_timeout(timeout_secs: u64) -> Self {
-        Self {
-            api_client: EmoteApiClient::with_timeout(timeout_secs),
+            api_client: EmoteApiClient::with_http_client(http),
         }
     }
 
@@ -169,8 +137,10 @@ impl TwitchEmoteProvider {
                 if let (Ok(start), Ok(end)) =
                     (pos_parts[0].parse::<usize>(), pos_parts[1].parse::<usize>())
                 {
-                    if start < message.len() && end < message.len() {
-                        let emote_name = message[start..=end].to_string();
+                    if let Some(emote_name) =
+                        crate::text::slice_chars_inclusive(message, start, end)
+                    {
+                        let emote_name = emote_name.to_string();
 
                         let source = if emote_id.starts_with("emotesv2_") {
                             EmoteSource::TwitchGlobal
@@ -185,10 +155,7 @@ impl TwitchEmoteProvider {
                             name: emote_name,
                             source,
                             positions: vec![TextPosition { start, end }],
-                            url: Some(format!(
-                                "https://static-cdn.jtvnw.net/emoticons/v2/{}/default/dark/1.0",
-                                emote_id
-                            )),
+                            url: Some(EmoteQuality::global().twitch_url(emote_id, "default")),
                             is_animated: false,
                             width: Some(28),
                             height: Some(28),
@@ -268,11 +235,6 @@ impl EmoteProvider for TwitchEmoteProvider {
     }
 }
 
-impl Default for TwitchEmoteProvider {
-    fn default() -> Self {
-        Self::new()
-    }
-}
 
 /// Proveedor de emotes BetterTTV
 pub struct BTTVEmoteProvider {
@@ -280,15 +242,9 @@ pub struct BTTVEmoteProvider {
 }
 
 impl BTTVEmoteProvider {
-    pub fn new() -> Self {
-        Self {
-            api_client: EmoteApiClient::new(),
-        }
-    }
-
-    pub fn with_timeout(timeout_secs: u64) -> Self {
+    pub fn with_http_client(http: EmoteHttpClient) -> Self {
         Self {
-            api_client: EmoteApiClient::with_timeout(timeout_secs),
+            api_client: EmoteApiClient::with_http_client(http),
         }
     }
 
@@ -312,7 +268,7 @@ impl BTTVEmoteProvider {
             emotes.push(EmoteData {
                 id: emote_id.clone(),
                 name: emote.code,
-                url: Some(format!("https://cdn.betterttv.net/emote/{}/3x", emote_id)),
+                url: Some(EmoteQuality::global().bttv_url(&emote_id)),
                 is_animated: emote.animated,
                 width: emote.width,
                 height: emote.height,
@@ -327,7 +283,7 @@ impl BTTVEmoteProvider {
             emotes.push(EmoteData {
                 id: emote_id.clone(),
                 name: emote.code,
-                url: Some(format!("https://cdn.betterttv.net/emote/{}/3x", emote_id)),
+                url: Some(EmoteQuality::global().bttv_url(&emote_id)),
                 is_animated: emote.animated,
                 width: emote.width,
                 height: emote.height,
@@ -351,7 +307,7 @@ impl BTTVEmoteProvider {
             emotes.push(EmoteData {
                 id: emote_id.clone(),
                 name: emote.code,
-                url: Some(format!("https://cdn.betterttv.net/emote/{}/3x", emote_id)),
+                url: Some(EmoteQuality::global().bttv_url(&emote_id)),
                 is_animated: emote.animated,
                 width: emote.width,
                 height: emote.height,
@@ -398,11 +354,6 @@ impl EmoteProvider for BTTVEmoteProvider {
     }
 }
 
-impl Default for BTTVEmoteProvider {
-    fn default() -> Self {
-        Self::new()
-    }
-}
 
 /// Proveedor de emotes FrankerFaceZ
 pub struct FFZEmoteProvider {
@@ -410,15 +361,9 @@ pub struct FFZEmoteProvider {
 }
 
 impl FFZEmoteProvider {
-    pub fn new() -> Self {
+    pub fn with_http_client(http: EmoteHttpClient) -> Self {
         Self {
-            api_client: EmoteApiClient::new(),
-        }
-    }
-
-    pub fn with_timeout(timeout_secs: u64) -> Self {
-        Self {
-            api_client: EmoteApiClient::with_timeout(timeout_secs),
+            api_client: EmoteApiClient::with_http_client(http),
         }
     }
 
@@ -455,12 +400,7 @@ impl FFZEmoteProvider {
 
         if let Some(set) = response.sets.get(&response.room.set.to_string()) {
             for emote in &set.emoticons {
-                let url = emote
-                    .urls
-                    .get("4")
-                    .or_else(|| emote.urls.get("2"))
-                    .or_else(|| emote.urls.get("1"))
-                    .cloned();
+                let url = EmoteQuality::global().pick_ffz_url(&emote.urls);
 
                 emotes.push(EmoteData {
                     id: emote.id.to_string(),
@@ -505,12 +445,7 @@ impl FFZEmoteProvider {
 
         for set in response.sets.values() {
             for emote in &set.emoticons {
-                let url = emote
-                    .urls
-                    .get("4")
-                    .or_else(|| emote.urls.get("2"))
-                    .or_else(|| emote.urls.get("1"))
-                    .cloned();
+                let url = EmoteQuality::global().pick_ffz_url(&emote.urls);
 
                 emotes.push(EmoteData {
                     id: emote.id.to_string(),
@@ -562,31 +497,33 @@ impl EmoteProvider for FFZEmoteProvider {
     }
 }
 
-impl Default for FFZEmoteProvider {
-    fn default() -> Self {
-        Self::new()
-    }
-}
 
 /// Proveedor de emotes 7TV
 pub struct SevenTVEmoteProvider {
     api_client: EmoteApiClient,
+    /// Sets de canal que mantiene al día la EventAPI
+    live_sets: Option<SevenTvChannelSets>,
 }
 
 impl SevenTVEmoteProvider {
-    pub fn new() -> Self {
+    pub fn with_http_client(http: EmoteHttpClient) -> Self {
         Self {
-            api_client: EmoteApiClient::new(),
+            api_client: EmoteApiClient::with_http_client(http),
+            live_sets: None,
         }
     }
 
-    pub fn with_timeout(timeout_secs: u64) -> Self {
-        Self {
-            api_client: EmoteApiClient::with_timeout(timeout_secs),
-        }
+    /// Guarda el set de cada canal en `sets` y lo sirve desde ahí
+    pub fn with_live_sets(mut self, sets: SevenTvChannelSets) -> Self {
+        self.live_sets = Some(sets);
+        self
     }
 
     async fn get_7tv_channel_emotes(&self, channel: &str) -> Result<Vec<EmoteData>, EmoteError> {
+        if let Some(emotes) = self.live_sets.as_ref().and_then(|sets| sets.get(channel)) {
+            return Ok(emotes);
+        }
+
         let url = format!("https://7tv.io/v3/users/twitch/{}", channel);
 
         #[derive(Deserialize)]
@@ -596,43 +533,23 @@ impl SevenTVEmoteProvider {
 
         #[derive(Deserialize)]
         struct SevenTVEmoteSet {
-            emotes: Vec<SevenTVEmote>,
-        }
-
-        #[derive(Deserialize)]
-        struct SevenTVEmote {
             id: String,
-            name: String,
-            data: SevenTVEmoteData,
-        }
-
-        #[derive(Deserialize)]
-        struct SevenTVEmoteData {
-            name: String,
-            flags: u32,
-            animated: bool,
+            emotes: Vec<ActiveEmote>,
         }
 
         let response: SevenTVUserResponse = self.api_client.get_json(&url).await?;
-        let mut emotes = Vec::new();
-
-        if let Some(set) = response.emote_set {
-            for emote in set.emotes {
-                let emote_id = emote.id.clone();
-                emotes.push(EmoteData {
-                    id: emote_id.clone(),
-                    name: emote.name,
-                    url: Some(format!("https://cdn.7tv.app/emote/{}/4x", emote_id)),
-                    is_animated: emote.data.animated,
-                    width: None,
-                    height: None,
-                    is_zero_width: (emote.data.flags & 1) != 0, // Zero width flag
-                    modifier: (emote.data.flags & 2) != 0,      // Modifier flag
-                    emote_set_id: None,
-                });
-            }
+        let Some(set) = response.emote_set else {
+            return Ok(Vec::new());
+        };
+
+        let emotes: Vec<EmoteData> = set
+            .emotes
+            .into_iter()
+            .map(|emote| emote.into_emote_data(Some(&set.id)))
+            .collect();
+        if let Some(sets) = &self.live_sets {
+            sets.store(channel, set.id, emotes.clone());
         }
-
         Ok(emotes)
     }
 
@@ -666,7 +583,7 @@ impl SevenTVEmoteProvider {
             emotes.push(EmoteData {
                 id: emote_id.clone(),
                 name: emote.name,
-                url: Some(format!("https://cdn.7tv.app/emote/{}/4x", emote_id)),
+                url: Some(EmoteQuality::global().seventv_url(&emote_id)),
                 is_animated: emote.data.animated,
                 width: None,
                 height: None,
@@ -712,19 +629,19 @@ impl EmoteProvider for SevenTVEmoteProvider {
     }
 }
 
-impl Default for SevenTVEmoteProvider {
-    fn default() -> Self {
-        Self::new()
-    }
-}
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::EmoteHttpConfig;
+
+    fn http() -> EmoteHttpClient {
+        EmoteHttpClient::new(&EmoteHttpConfig::default())
+    }
 
     #[tokio::test]
     async fn test_api_client_timeout() {
-        let client = EmoteApiClient::with_timeout(1); // 1 second timeout
+        let client = EmoteApiClient::with_http_client(http()).with_timeout(1); // 1 second timeout
 
         // Test with a URL that will timeout
         let result: Result<serde_json::Value, _> =
@@ -747,7 +664,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_api_client_retry() {
-        let client = EmoteApiClient::new().with_retry_config(2, 100);
+        let client = EmoteApiClient::with_http_client(http()).with_retry_config(2, 100);
 
         // Test with a flaky endpoint
         let result: Result<serde_json::Value, _> =
@@ -759,7 +676,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_api_client_success() {
-        let client = EmoteApiClient::new();
+        let client = EmoteApiClient::with_http_client(http());
 
         let result: Result<serde_json::Value, _> =
             client.get_json("https://httpbin.org/json").await;
@@ -769,7 +686,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_bttv_global_emotes() {
-        let provider = BTTVEmoteProvider::new();
+        let provider = BTTVEmoteProvider::with_http_client(http());
         match provider.get_global_emotes().await {
             Ok(emotes) => {
                 println!("✅ BTTV: Loaded {} global emotes", emotes.len());
@@ -788,7 +705,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_ffz_global_emotes() {
-        let provider = FFZEmoteProvider::new();
+        let provider = FFZEmoteProvider::with_http_client(http());
         match provider.get_global_emotes().await {
             Ok(emotes) => {
                 println!("✅ FFZ: Loaded {} global emotes", emotes.len());
@@ -807,7 +724,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_7tv_global_emotes() {
-        let provider = SevenTVEmoteProvider::new();
+        let provider = SevenTVEmoteProvider::with_http_client(http());
         match provider.get_global_emotes().await {
             Ok(emotes) => {
                 println!("✅ 7TV: Loaded {} global emotes", emotes.len());
@@ -826,7 +743,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_twitch_provider() {
-        let provider = TwitchEmoteProvider::new();
+        let provider = TwitchEmoteProvider::with_http_client(http());
         // Twitch doesn't have a public global emotes endpoint without auth
         // Just test that the provider exists
         assert_eq!(provider.provider_name(), "twitch");
@@ -835,7 +752,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_api_client() {
-        let client = EmoteApiClient::new();
+        let client = EmoteApiClient::with_http_client(http());
 
         // Test with a simple endpoint
         let url = "https://api.betterttv.net/3/cached/emotes/global";
@@ -855,9 +772,9 @@ mod tests {
         println!("\n🔍 Testing all providers individually...\n");
 
         let providers: Vec<(&str, Box<dyn EmoteProvider>)> = vec![
-            ("BTTV", Box::new(BTTVEmoteProvider::new())),
-            ("FFZ", Box::new(FFZEmoteProvider::new())),
-            ("7TV", Box::new(SevenTVEmoteProvider::new())),
+            ("BTTV", Box::new(BTTVEmoteProvider::with_http_client(http()))),
+            ("FFZ", Box::new(FFZEmoteProvider::with_http_client(http()))),
+            ("7TV", Box::new(SevenTVEmoteProvider::with_http_client(http()))),
         ];
 
         let mut results = Vec::new();
@@ -0,0 +1,409 @@
+//! Cambios en vivo de los emote sets de 7TV (EventAPI)
+//!
+//! Con `emotes.seventv_live_updates` el proveedor 7TV guarda en memoria el
+//! emote set de cada canal en vez de pedirlo en cada mensaje, y este cliente
+//! se suscribe a `emote_set.update` de esos sets: los emotes añadidos,
+//! renombrados o quitados a mitad de directo se aplican al momento y se
+//! envían como [`EmoteSetChange`] para actualizar el `EmoteCache`.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use futures::{SinkExt, StreamExt};
+use serde::Deserialize;
+use tokio::sync::mpsc;
+use tokio_tungstenite::tungstenite::Message;
+
+use crate::emotes::{EmoteData, EmoteError, EmoteQuality};
+
+pub const EVENTAPI_URL: &str = "wss://events.7tv.io/v3";
+
+/// Latido por defecto hasta que llega el `hello`
+const DEFAULT_HEARTBEAT: Duration = Duration::from_secs(45);
+/// Margen antes de dar la conexión por muerta
+const HEARTBEAT_GRACE: Duration = Duration::from_secs(15);
+/// Cada cuánto se suscriben los sets de canales cargados desde la última vez
+const SUBSCRIBE_INTERVAL: Duration = Duration::from_secs(5);
+const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(60);
+
+/// Emote de un set, tal como lo devuelven la API REST y la EventAPI
+#[derive(Debug, Clone, Deserialize)]
+pub(crate) struct ActiveEmote {
+    id: String,
+    /// Nombre en el set (puede ser un alias del original)
+    name: String,
+    data: ActiveEmoteData,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct ActiveEmoteData {
+    #[serde(default)]
+    flags: u32,
+    #[serde(default)]
+    animated: bool,
+}
+
+impl ActiveEmote {
+    pub(crate) fn into_emote_data(self, set_id: Option<&str>) -> EmoteData {
+        EmoteData {
+            url: Some(EmoteQuality::global().seventv_url(&self.id)),
+            id: self.id,
+            name: self.name,
+            is_animated: self.data.animated,
+            width: None,
+            height: None,
+            is_zero_width: (self.data.flags & 1) != 0,
+            modifier: (self.data.flags & 2) != 0,
+            emote_set_id: set_id.map(str::to_string),
+        }
+    }
+}
+
+/// Cambio en el emote set de un canal
+#[derive(Debug, Clone)]
+pub struct EmoteSetChange {
+    pub channel: String,
+    pub kind: EmoteSetChangeKind,
+}
+
+#[derive(Debug, Clone)]
+pub enum EmoteSetChangeKind {
+    Added(EmoteData),
+    Renamed { old_name: String, emote: EmoteData },
+    Removed(EmoteData),
+}
+
+impl std::fmt::Display for EmoteSetChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.kind {
+            EmoteSetChangeKind::Added(emote) => {
+                write!(f, "{}: added {}", self.channel, emote.name)
+            }
+            EmoteSetChangeKind::Renamed { old_name, emote } => {
+                write!(
+                    f,
+                    "{}: renamed {} to {}",
+                    self.channel, old_name, emote.name
+                )
+            }
+            EmoteSetChangeKind::Removed(emote) => {
+                write!(f, "{}: removed {}", self.channel, emote.name)
+            }
+        }
+    }
+}
+
+/// Cuerpo de un `emote_set.update`
+#[derive(Debug, Clone, Deserialize)]
+pub struct EmoteSetUpdate {
+    /// Id del set
+    pub id: String,
+    #[serde(default)]
+    pushed: Vec<ChangeField>,
+    #[serde(default)]
+    pulled: Vec<ChangeField>,
+    #[serde(default)]
+    updated: Vec<ChangeField>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct ChangeField {
+    key: String,
+    #[serde(default)]
+    value: serde_json::Value,
+    #[serde(default)]
+    old_value: serde_json::Value,
+}
+
+impl ChangeField {
+    fn emote(value: &serde_json::Value) -> Option<ActiveEmote> {
+        serde_json::from_value(value.clone()).ok()
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct Frame {
+    op: u8,
+    #[serde(default)]
+    d: serde_json::Value,
+}
+
+/// Cambio de un set si el texto es un dispatch `emote_set.update`
+pub fn parse_dispatch(text: &str) -> Option<EmoteSetUpdate> {
+    let frame: Frame = serde_json::from_str(text).ok()?;
+    if frame.op != 0 || frame.d["type"] != "emote_set.update" {
+        return None;
+    }
+    serde_json::from_value(frame.d["body"].clone()).ok()
+}
+
+fn subscribe_frame(set_id: &str) -> String {
+    serde_json::json!({
+        "op": 35,
+        "d": {
+            "type": "emote_set.update",
+            "condition": { "object_id": set_id },
+        },
+    })
+    .to_string()
+}
+
+#[derive(Debug, Default)]
+struct ChannelSet {
+    set_id: String,
+    emotes: Vec<EmoteData>,
+}
+
+/// Emote sets de canal de 7TV en memoria, compartidos entre el proveedor y
+/// el cliente de la EventAPI
+#[derive(Debug, Clone, Default)]
+pub struct SevenTvChannelSets {
+    channels: Arc<Mutex<HashMap<String, ChannelSet>>>,
+}
+
+impl SevenTvChannelSets {
+    pub fn get(&self, channel: &str) -> Option<Vec<EmoteData>> {
+        self.lock()
+            .get(&channel.to_lowercase())
+            .map(|set| set.emotes.clone())
+    }
+
+    pub fn store(&self, channel: &str, set_id: String, emotes: Vec<EmoteData>) {
+        self.lock()
+            .insert(channel.to_lowercase(), ChannelSet { set_id, emotes });
+    }
+
+    /// El próximo mensaje del canal vuelve a pedir el set por REST
+    pub fn forget(&self, channel: &str) {
+        self.lock().remove(&channel.to_lowercase());
+    }
+
+    pub fn set_ids(&self) -> Vec<String> {
+        self.lock().values().map(|set| set.set_id.clone()).collect()
+    }
+
+    /// Aplica un `emote_set.update` a los canales que usan el set
+    pub fn apply(&self, update: &EmoteSetUpdate) -> Vec<EmoteSetChange> {
+        let mut changes = Vec::new();
+        let mut channels = self.lock();
+        for (channel, set) in channels.iter_mut().filter(|(_, s)| s.set_id == update.id) {
+            let mut kinds = Vec::new();
+            let emotes = update.pulled.iter().filter(|field| field.key == "emotes");
+            for removed in emotes.filter_map(|field| ChangeField::emote(&field.old_value)) {
+                if let Some(index) = set.emotes.iter().position(|e| e.id == removed.id) {
+                    kinds.push(EmoteSetChangeKind::Removed(set.emotes.remove(index)));
+                }
+            }
+            for field in update.updated.iter().filter(|field| field.key == "emotes") {
+                let Some(emote) = ChangeField::emote(&field.value) else {
+                    continue;
+                };
+                let emote = emote.into_emote_data(Some(&set.set_id));
+                let old_id = ChangeField::emote(&field.old_value).map(|old| old.id);
+                let old_id = old_id.as_deref().unwrap_or(&emote.id);
+                match set.emotes.iter_mut().find(|e| e.id == old_id) {
+                    Some(existing) => {
+                        let old = std::mem::replace(existing, emote.clone());
+                        if old.id != emote.id {
+                            kinds.push(EmoteSetChangeKind::Removed(old));
+                            kinds.push(EmoteSetChangeKind::Added(emote));
+                        } else if old.name != emote.name {
+                            kinds.push(EmoteSetChangeKind::Renamed {
+                                old_name: old.name,
+                                emote,
+                            });
+                        }
+                    }
+                    None => {
+                        set.emotes.push(emote.clone());
+                        kinds.push(EmoteSetChangeKind::Added(emote));
+                    }
+                }
+            }
+            let emotes = update.pushed.iter().filter(|field| field.key == "emotes");
+            for added in emotes.filter_map(|field| ChangeField::emote(&field.value)) {
+                if set.emotes.iter().any(|e| e.id == added.id) {
+                    continue;
+                }
+                let emote = added.into_emote_data(Some(&set.set_id));
+                set.emotes.push(emote.clone());
+                kinds.push(EmoteSetChangeKind::Added(emote));
+            }
+            changes.extend(kinds.into_iter().map(|kind| EmoteSetChange {
+                channel: channel.clone(),
+                kind,
+            }));
+        }
+        changes
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, ChannelSet>> {
+        self.channels.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Sigue los sets de `sets` hasta que se cierre `sender`, reconectando tras
+/// cada corte con espera creciente
+pub async fn follow_emote_sets(
+    sets: SevenTvChannelSets,
+    sender: mpsc::UnboundedSender<EmoteSetChange>,
+) {
+    let mut delay = SUBSCRIBE_INTERVAL;
+    while !sender.is_closed() {
+        match read_updates(&sets, &sender).await {
+            Ok(()) => delay = SUBSCRIBE_INTERVAL,
+            Err(e) => {
+                eprintln!("[7TV] ⚠️ EventAPI connection failed: {}", e);
+                crate::health::ErrorBudget::global().record_failure("provider:7tv", &e.to_string());
+                delay = (delay * 2).min(MAX_RECONNECT_DELAY);
+            }
+        }
+        tokio::time::sleep(delay).await;
+    }
+}
+
+/// Una sesión de la EventAPI; termina bien con `reconnect` o `end_of_stream`
+async fn read_updates(
+    sets: &SevenTvChannelSets,
+    sender: &mpsc::UnboundedSender<EmoteSetChange>,
+) -> Result<(), EmoteError> {
+    let (stream, _) = tokio_tungstenite::connect_async(EVENTAPI_URL)
+        .await
+        .map_err(|e| EmoteError::NetworkError(e.to_string()))?;
+    let (mut write, mut read) = stream.split();
+    let mut subscribed = HashSet::new();
+    let mut heartbeat = DEFAULT_HEARTBEAT;
+    let mut last_frame = Instant::now();
+    let mut check = tokio::time::interval(SUBSCRIBE_INTERVAL);
+
+    loop {
+        tokio::select! {
+            frame = read.next() => {
+                let text = match frame {
+                    Some(Ok(Message::Text(text))) => text,
+                    Some(Ok(Message::Close(_))) | None => return Ok(()),
+                    Some(Ok(_)) => continue,
+                    Some(Err(e)) => return Err(EmoteError::NetworkError(e.to_string())),
+                };
+                last_frame = Instant::now();
+
+                if let Some(update) = parse_dispatch(&text) {
+                    for change in sets.apply(&update) {
+                        if sender.send(change).is_err() {
+                            return Ok(());
+                        }
+                    }
+                    continue;
+                }
+                let Ok(frame) = serde_json::from_str::<Frame>(&text) else {
+                    continue;
+                };
+                match frame.op {
+                    // hello
+                    1 => {
+                        if let Some(ms) = frame.d["heartbeat_interval"].as_u64() {
+                            heartbeat = Duration::from_millis(ms.max(1000));
+                        }
+                    }
+                    // reconnect / end_of_stream
+                    4 | 7 => return Ok(()),
+                    // error
+                    6 => eprintln!("[7TV] ⚠️ EventAPI error: {}", frame.d["message"]),
+                    _ => {}
+                }
+            }
+            _ = check.tick() => {
+                if sender.is_closed() {
+                    return Ok(());
+                }
+                if last_frame.elapsed() > heartbeat + HEARTBEAT_GRACE {
+                    return Err(EmoteError::NetworkError("EventAPI heartbeat timed out".to_string()));
+                }
+                for set_id in sets.set_ids() {
+                    if subscribed.insert(set_id.clone()) {
+                        write
+                            .send(Message::Text(subscribe_frame(&set_id)))
+                            .await
+                            .map_err(|e| EmoteError::NetworkError(e.to_string()))?;
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn active(id: &str, name: &str) -> serde_json::Value {
+        serde_json::json!({ "id": id, "name": name, "data": { "flags": 0, "animated": false } })
+    }
+
+    fn emote(id: &str, name: &str) -> EmoteData {
+        ActiveEmote {
+            id: id.to_string(),
+            name: name.to_string(),
+            data: ActiveEmoteData {
+                flags: 0,
+                animated: false,
+            },
+        }
+        .into_emote_data(Some("set1"))
+    }
+
+    #[test]
+    fn test_set_updates_add_rename_and_remove_emotes() {
+        let sets = SevenTvChannelSets::default();
+        sets.store(
+            "Streamer",
+            "set1".to_string(),
+            vec![emote("a", "catJAM"), emote("b", "pepeD")],
+        );
+        sets.store("other", "set2".to_string(), vec![emote("a", "catJAM")]);
+
+        let frame = serde_json::json!({
+            "op": 0,
+            "d": {
+                "type": "emote_set.update",
+                "body": {
+                    "id": "set1",
+                    "pushed": [{ "key": "emotes", "index": 2, "value": active("c", "WAYTOODANK") }],
+                    "pulled": [{ "key": "emotes", "index": 1, "old_value": active("b", "pepeD") }],
+                    "updated": [{
+                        "key": "emotes",
+                        "index": 0,
+                        "old_value": active("a", "catJAM"),
+                        "value": active("a", "vibe")
+                    }]
+                }
+            }
+        });
+        let update = parse_dispatch(&frame.to_string()).unwrap();
+        let changes: Vec<String> = sets.apply(&update).iter().map(|c| c.to_string()).collect();
+        assert_eq!(
+            changes,
+            [
+                "streamer: removed pepeD",
+                "streamer: renamed catJAM to vibe",
+                "streamer: added WAYTOODANK"
+            ]
+        );
+
+        let names: Vec<String> = sets
+            .get("streamer")
+            .unwrap()
+            .into_iter()
+            .map(|e| e.name)
+            .collect();
+        assert_eq!(names, ["vibe", "WAYTOODANK"]);
+        // Otro set no cambia
+        assert_eq!(sets.get("other").unwrap()[0].name, "catJAM");
+
+        // Otros tipos de frame no son cambios
+        assert!(parse_dispatch(r#"{"op":1,"d":{"heartbeat_interval":45000}}"#).is_none());
+        // Un dispatch repetido no vuelve a aplicar nada
+        assert!(sets.apply(&update).is_empty());
+    }
+}
@@ -0,0 +1,213 @@
+//! Tamaño y formato de las imágenes de emotes que se piden a los CDN
+//!
+//! Los proveedores construyen las URLs con `emotes.quality` (general y por
+//! proveedor) y las ventanas ajustan después la imagen al alto del emote a 1x
+//! por la escala del estilo. Así `4x` da emotes nítidos en pantallas 4K y `1x`
+//! ahorra ancho de banda en equipos modestos sin cambiar el tamaño con el que
+//! se muestran. Sin configurar se piden las URLs de siempre: 1x en Twitch y la
+//! mayor en BTTV, FFZ y 7TV.
+
+use std::collections::HashMap;
+use std::sync::{OnceLock, RwLock};
+
+use crate::config::{EmoteImageFormat, EmoteQualityConfig, EmoteScale};
+use crate::connection::EmoteSource;
+
+static GLOBAL_QUALITY: OnceLock<EmoteQuality> = OnceLock::new();
+
+/// Alto en píxeles de un emote a 1x (igual en Twitch, BTTV, FFZ y 7TV)
+pub const BASE_EMOTE_SIZE: u32 = 28;
+
+/// Preferencias de `emotes.quality`, compartidas por proveedores y renderers
+#[derive(Debug, Default)]
+pub struct EmoteQuality {
+    config: RwLock<EmoteQualityConfig>,
+}
+
+impl EmoteQuality {
+    pub fn new(config: EmoteQualityConfig) -> Self {
+        Self {
+            config: RwLock::new(config),
+        }
+    }
+
+    pub fn global() -> &'static EmoteQuality {
+        GLOBAL_QUALITY.get_or_init(Self::default)
+    }
+
+    /// Aplica la configuración; los sets de canal ya cargados conservan sus
+    /// URLs hasta que caducan
+    pub fn configure(&self, config: EmoteQualityConfig) {
+        *self.config.write().unwrap_or_else(|e| e.into_inner()) = config;
+    }
+
+    /// Escala y formato para `provider` (`twitch`, `bttv`, `ffz` o `7tv`)
+    pub fn preference(&self, provider: &str) -> (EmoteScale, EmoteImageFormat) {
+        let config = self.config.read().unwrap_or_else(|e| e.into_inner());
+        let overrides = config.providers.get(provider).copied().unwrap_or_default();
+        (
+            overrides
+                .scale
+                .or(config.scale)
+                .unwrap_or_else(|| default_scale(provider)),
+            overrides.format.unwrap_or(config.format),
+        )
+    }
+
+    /// Imagen de Twitch; `variant` es `default`, `static` o `animated`
+    pub fn twitch_url(&self, id: &str, variant: &str) -> String {
+        let size = match self.preference("twitch").0 {
+            EmoteScale::X1 => "1.0",
+            EmoteScale::X2 => "2.0",
+            EmoteScale::X4 => "3.0",
+        };
+        format!(
+            "https://static-cdn.jtvnw.net/emoticons/v2/{}/{}/dark/{}",
+            id, variant, size
+        )
+    }
+
+    pub fn bttv_url(&self, id: &str) -> String {
+        let (scale, format) = self.preference("bttv");
+        let size = match scale {
+            EmoteScale::X1 => "1x",
+            EmoteScale::X2 => "2x",
+            EmoteScale::X4 => "3x",
+        };
+        let extension = match format {
+            EmoteImageFormat::Webp => ".webp",
+            EmoteImageFormat::Default => "",
+        };
+        format!(
+            "https://cdn.betterttv.net/emote/{}/{}{}",
+            id, size, extension
+        )
+    }
+
+    pub fn ffz_url(&self, id: &str) -> String {
+        format!(
+            "https://cdn.frankerfacez.com/emote/{}/{}",
+            id,
+            ffz_size(self.preference("ffz").0)
+        )
+    }
+
+    /// De las URLs por tamaño de la API de FFZ (`1`, `2`, `4`), la de la
+    /// preferencia o la mayor de las que no la superan
+    pub fn pick_ffz_url(&self, urls: &HashMap<String, String>) -> Option<String> {
+        let preferred = ffz_size(self.preference("ffz").0);
+        ["4", "2", "1"]
+            .into_iter()
+            .skip_while(|size| *size != preferred)
+            .find_map(|size| urls.get(size))
+            .or_else(|| urls.values().next())
+            .cloned()
+    }
+
+    pub fn seventv_url(&self, id: &str) -> String {
+        let (scale, format) = self.preference("7tv");
+        let size = match scale {
+            EmoteScale::X1 => "1x",
+            EmoteScale::X2 => "2x",
+            EmoteScale::X4 => "4x",
+        };
+        let extension = match format {
+            EmoteImageFormat::Default => "",
+            EmoteImageFormat::Webp => ".webp",
+        };
+        format!("https://cdn.7tv.app/emote/{}/{}{}", id, size, extension)
+    }
+
+    /// URL de un emote que no la trae, según su origen
+    pub fn url_for(&self, source: &EmoteSource, id: &str) -> Option<String> {
+        match source {
+            EmoteSource::Twitch | EmoteSource::TwitchGlobal | EmoteSource::TwitchSubscriber => {
+                Some(self.twitch_url(id, "default"))
+            }
+            EmoteSource::BTTV => Some(self.bttv_url(id)),
+            EmoteSource::FFZ => Some(self.ffz_url(id)),
+            EmoteSource::SevenTV => Some(self.seventv_url(id)),
+            _ => None,
+        }
+    }
+}
+
+/// Escala sin `emotes.quality`: la que pedía cada proveedor antes de poder
+/// elegirla
+fn default_scale(provider: &str) -> EmoteScale {
+    match provider {
+        "twitch" => EmoteScale::X1,
+        _ => EmoteScale::X4,
+    }
+}
+
+fn ffz_size(scale: EmoteScale) -> &'static str {
+    match scale {
+        EmoteScale::X1 => "1",
+        EmoteScale::X2 => "2",
+        EmoteScale::X4 => "4",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::EmoteQualityOverride;
+
+    #[test]
+    fn test_urls_follow_provider_overrides() {
+        let quality = EmoteQuality::new(EmoteQualityConfig {
+            scale: Some(EmoteScale::X2),
+            format: EmoteImageFormat::Webp,
+            providers: HashMap::from([(
+                "twitch".to_string(),
+                EmoteQualityOverride {
+                    scale: Some(EmoteScale::X4),
+                    format: None,
+                },
+            )]),
+        });
+
+        assert_eq!(
+            quality.twitch_url("25", "static"),
+            "https://static-cdn.jtvnw.net/emoticons/v2/25/static/dark/3.0"
+        );
+        assert_eq!(
+            quality.seventv_url("abc"),
+            "https://cdn.7tv.app/emote/abc/2x.webp"
+        );
+        assert_eq!(
+            quality.bttv_url("abc"),
+            "https://cdn.betterttv.net/emote/abc/2x.webp"
+        );
+        assert_eq!(quality.url_for(&EmoteSource::Kick, "1"), None);
+    }
+
+    #[test]
+    fn test_ffz_url_never_exceeds_preference() {
+        let quality = EmoteQuality::new(EmoteQualityConfig {
+            scale: Some(EmoteScale::X2),
+            ..EmoteQualityConfig::default()
+        });
+        let urls = |sizes: &[&str]| {
+            sizes
+                .iter()
+                .map(|size| (size.to_string(), format!("https://cdn/{}", size)))
+                .collect::<HashMap<_, _>>()
+        };
+
+        assert_eq!(
+            quality.pick_ffz_url(&urls(&["1", "2", "4"])).as_deref(),
+            Some("https://cdn/2")
+        );
+        assert_eq!(
+            quality.pick_ffz_url(&urls(&["1", "4"])).as_deref(),
+            Some("https://cdn/1")
+        );
+        // Si solo hay tamaños mayores se usa el que haya
+        assert_eq!(
+            quality.pick_ffz_url(&urls(&["4"])).as_deref(),
+            Some("https://cdn/4")
+        );
+    }
+}
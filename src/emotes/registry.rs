@@ -0,0 +1,170 @@
+//! Sets de emotes de canal en memoria, con caducidad
+//!
+//! Cada proveedor de terceros (BTTV, FFZ, 7TV y personalizados) se consulta
+//! una vez por canal y `emotes.channel_set_ttl_minutes`; el parseo de los
+//! mensajes busca en el registro sin hacer peticiones. Si algún proveedor
+//! falla, el canal se vuelve a pedir pasado [`ERROR_RETRY`].
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use super::EmoteData;
+
+/// Vigencia de un canal cuyo set no se pudo descargar entero
+pub const ERROR_RETRY: Duration = Duration::from_secs(60);
+
+#[derive(Debug)]
+struct ChannelEntry {
+    /// (proveedor, emotes) en el orden de los proveedores habilitados
+    sets: Vec<(String, Vec<EmoteData>)>,
+    expires_at: Instant,
+}
+
+/// Emotes de canal por plataforma y canal
+#[derive(Debug, Default)]
+pub struct ChannelEmoteRegistry {
+    ttl: Duration,
+    channels: HashMap<String, ChannelEntry>,
+}
+
+fn key(platform: &str, channel: &str) -> String {
+    format!("{}\n{}", platform, channel.to_lowercase())
+}
+
+impl ChannelEmoteRegistry {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            channels: HashMap::new(),
+        }
+    }
+
+    /// Sets vigentes del canal; `None` si hay que (volver a) pedirlos
+    pub fn get(
+        &self,
+        platform: &str,
+        channel: &str,
+        now: Instant,
+    ) -> Option<&[(String, Vec<EmoteData>)]> {
+        self.channels
+            .get(&key(platform, channel))
+            .filter(|entry| now < entry.expires_at)
+            .map(|entry| entry.sets.as_slice())
+    }
+
+    /// Guarda los sets de un canal; con `failed` caduca en [`ERROR_RETRY`]
+    pub fn store(
+        &mut self,
+        platform: &str,
+        channel: &str,
+        sets: Vec<(String, Vec<EmoteData>)>,
+        failed: bool,
+        now: Instant,
+    ) {
+        let ttl = if failed {
+            ERROR_RETRY.min(self.ttl)
+        } else {
+            self.ttl
+        };
+        self.channels.insert(
+            key(platform, channel),
+            ChannelEntry {
+                sets,
+                expires_at: now + ttl,
+            },
+        );
+    }
+
+    /// Sustituye el set de un proveedor (p. ej. tras un cambio en vivo de
+    /// 7TV) sin alargar la vigencia; `false` si el canal no está cargado
+    pub fn replace_set(
+        &mut self,
+        platform: &str,
+        channel: &str,
+        provider: &str,
+        emotes: Vec<EmoteData>,
+    ) -> bool {
+        let Some(entry) = self.channels.get_mut(&key(platform, channel)) else {
+            return false;
+        };
+        match entry.sets.iter_mut().find(|(name, _)| name == provider) {
+            Some((_, set)) => *set = emotes,
+            None => entry.sets.push((provider.to_string(), emotes)),
+        }
+        true
+    }
+
+    /// Olvida el canal: el próximo mensaje vuelve a pedir sus sets
+    pub fn invalidate(&mut self, platform: &str, channel: &str) -> bool {
+        self.channels.remove(&key(platform, channel)).is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn emote(id: &str) -> EmoteData {
+        EmoteData {
+            id: id.to_string(),
+            name: id.to_uppercase(),
+            url: None,
+            is_animated: false,
+            width: None,
+            height: None,
+            is_zero_width: false,
+            modifier: false,
+            emote_set_id: None,
+        }
+    }
+
+    #[test]
+    fn test_channel_sets_expire_after_ttl() {
+        let mut registry = ChannelEmoteRegistry::new(Duration::from_secs(600));
+        let now = Instant::now();
+        assert!(registry.get("twitch", "chan", now).is_none());
+
+        registry.store(
+            "twitch",
+            "Chan",
+            vec![("bttv".to_string(), vec![emote("a")])],
+            false,
+            now,
+        );
+        let sets = registry.get("twitch", "chan", now).unwrap();
+        assert_eq!(sets[0].1[0].id, "a");
+        assert!(registry.get("kick", "chan", now).is_none());
+        assert!(registry
+            .get("twitch", "chan", now + Duration::from_secs(600))
+            .is_none());
+
+        // Un fallo parcial se reintenta antes
+        registry.store("twitch", "other", Vec::new(), true, now);
+        assert!(registry.get("twitch", "other", now).is_some());
+        assert!(registry.get("twitch", "other", now + ERROR_RETRY).is_none());
+    }
+
+    #[test]
+    fn test_replace_and_invalidate_channel() {
+        let mut registry = ChannelEmoteRegistry::new(Duration::from_secs(600));
+        let now = Instant::now();
+        assert!(!registry.replace_set("twitch", "chan", "7tv", vec![emote("x")]));
+
+        registry.store(
+            "twitch",
+            "chan",
+            vec![("7tv".to_string(), vec![emote("a")])],
+            false,
+            now,
+        );
+        assert!(registry.replace_set("twitch", "chan", "7tv", vec![emote("b")]));
+        assert!(registry.replace_set("twitch", "chan", "ffz", vec![emote("c")]));
+        let sets = registry.get("twitch", "chan", now).unwrap();
+        assert_eq!(sets[0].1[0].id, "b");
+        assert_eq!(sets[1].0, "ffz");
+
+        assert!(registry.invalidate("twitch", "CHAN"));
+        assert!(!registry.invalidate("twitch", "chan"));
+        assert!(registry.get("twitch", "chan", now).is_none());
+    }
+}
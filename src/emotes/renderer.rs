@@ -1,6 +1,32 @@
+use super::frames::decode_rgba_frames;
+use super::EmoteHttpClient;
 use crate::connection::{Emote, EmoteMetadata, EmoteSource, TextPosition};
-use std::collections::HashMap;
+use crate::flow::FlowContent;
+use crate::theme::events::EventCard;
+use crate::theme::{Rgb, ThemePalette};
+use image::codecs::gif::{GifEncoder, Repeat};
+use image::{imageops, Delay, Frame, ImageOutputFormat, RgbaImage};
+use std::collections::{HashMap, VecDeque};
+use std::io::Cursor;
+use std::ops::Range;
 use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+
+/// Prefijo de los ids de las imágenes compuestas
+pub const COMPOSITE_ID_PREFIX: &str = "stack:";
+/// Composiciones guardadas antes de descartar las más antiguas
+const MAX_COMPOSITED_IMAGES: usize = 256;
+/// Duración máxima de una composición animada
+const MAX_COMPOSITE_DURATION: Duration = Duration::from_secs(10);
+const MAX_COMPOSITE_FRAMES: usize = 300;
+
+/// Medidas de [`compose_message`] antes de aplicar la escala, las mismas que
+/// usa el renderer de Windows
+const BITMAP_PADDING: i32 = 10;
+const BITMAP_EMOTE_SIDE: i32 = 32;
+const BITMAP_MIN_WIDTH: i32 = 200;
+const BITMAP_MAX_WIDTH: i32 = 400;
 
 /// Renderer de emotes que maneja la obtención y procesamiento de imágenes
 pub struct EmoteRenderer {
@@ -9,6 +35,7 @@ pub struct EmoteRenderer {
     supported_formats: Vec<String>,
     scaling_factor: f32,
     default_size: (u32, u32),
+    http: EmoteHttpClient,
 }
 
 #[derive(Debug, Clone)]
@@ -32,17 +59,29 @@ pub enum RenderError {
     SizeError(String),
 }
 
+/// Carpeta de la caché de imágenes en disco
+pub fn default_cache_dir() -> PathBuf {
+    std::env::temp_dir().join("overlay-native").join("emotes")
+}
+
 impl EmoteRenderer {
-    pub fn new(cache_dir: PathBuf) -> Self {
+    /// Descarga las imágenes con `http`, el cliente compartido del sistema de emotes
+    pub fn new(cache_dir: PathBuf, http: EmoteHttpClient) -> Self {
         Self {
             cache_dir,
             max_cache_size_mb: 100,
             supported_formats: vec!["png".to_string(), "gif".to_string(), "webp".to_string()],
             scaling_factor: 1.0,
             default_size: (32, 32),
+            http,
         }
     }
 
+    /// Estadísticas de descarga de imágenes
+    pub fn download_stats(&self) -> super::DownloadStats {
+        self.http.stats()
+    }
+
     /// Renderiza un emote obteniendo su imagen y procesándola
     pub async fn render_emote(&self, emote: &Emote) -> Result<RenderedEmote, RenderError> {
         let start_time = std::time::Instant::now();
@@ -98,56 +137,73 @@ impl EmoteRenderer {
         results
     }
 
+    /// Compone la imagen de una pila con capas o modificadores y la guarda
+    /// para los renderers de cada plataforma (ver [`composited_image`])
+    ///
+    /// Devuelve el id con el que hay que dibujarla; el del emote base si la
+    /// pila no necesita composición.
+    pub async fn render_stack(&self, stack: &EmoteStack) -> Result<String, RenderError> {
+        let id = stack.image_id();
+        if !stack.is_composite() || composited_image(&id).is_some() {
+            return Ok(id);
+        }
+
+        let base = self
+            .fetch_emote_image(&self.resolve_emote_url(&stack.base)?)
+            .await?;
+        let overlays = futures::future::try_join_all(stack.overlays.iter().map(|emote| async {
+            self.fetch_emote_image(&self.resolve_emote_url(emote)?)
+                .await
+        }))
+        .await?;
+
+        let height = (stack.base.height.unwrap_or(self.default_size.1) as f32
+            * self.scaling_factor)
+            .round()
+            .max(1.0) as u32;
+        let modifiers = stack.modifiers.clone();
+        let bytes = tokio::task::spawn_blocking(move || {
+            composite_stack(&base, &modifiers, &overlays, height)
+        })
+        .await
+        .ok()
+        .flatten()
+        .ok_or_else(|| RenderError::FormatError("Cannot composite emote stack".to_string()))?;
+
+        store_composited(id.clone(), bytes);
+        Ok(id)
+    }
+
+    /// Compone de antemano las pilas del mensaje para que la ventana solo
+    /// tenga que buscarlas (ver [`EmoteStack::drawn_id`])
+    pub async fn render_message(&self, content: &str, emotes: &[Emote]) {
+        for stack in layout_emotes(content, emotes) {
+            if let Err(e) = self.render_stack(&stack).await {
+                eprintln!("[EMOTES] ⚠️ Cannot composite {}: {:?}", stack.base.name, e);
+            }
+        }
+    }
+
     /// Resuelve la URL de un emote basado en su source
     pub fn resolve_emote_url(&self, emote: &Emote) -> Result<String, RenderError> {
         if let Some(url) = &emote.url {
             return Ok(url.clone());
         }
 
-        // Construir URL basada en el source
-        match emote.source {
-            EmoteSource::Twitch | EmoteSource::TwitchGlobal | EmoteSource::TwitchSubscriber => {
-                Ok(format!(
-                    "https://static-cdn.jtvnw.net/emoticons/v2/{}/default/dark/1.0",
-                    emote.id
-                ))
-            }
-            EmoteSource::BTTV => Ok(format!("https://cdn.betterttv.net/emote/{}/3x", emote.id)),
-            EmoteSource::FFZ => Ok(format!("https://cdn.frankerfacez.com/emote/{}/4", emote.id)),
-            EmoteSource::SevenTV => Ok(format!("https://cdn.7tv.app/emote/{}/4x", emote.id)),
-            _ => Err(RenderError::FormatError(
-                "Cannot determine URL for emote source".to_string(),
-            )),
-        }
+        // Construir URL basada en el source, con la calidad de `emotes.quality`
+        super::EmoteQuality::global()
+            .url_for(&emote.source, &emote.id)
+            .ok_or_else(|| {
+                RenderError::FormatError("Cannot determine URL for emote source".to_string())
+            })
     }
 
     /// Obtiene la imagen de un emote desde la URL
     async fn fetch_emote_image(&self, url: &str) -> Result<Vec<u8>, RenderError> {
-        let client = reqwest::Client::builder()
-            .timeout(std::time::Duration::from_secs(10))
-            .user_agent("Overlay-Native/1.0")
-            .build()
-            .map_err(|e| RenderError::NetworkError(e.to_string()))?;
-
-        let response = client
-            .get(url)
-            .send()
-            .await
-            .map_err(|e| RenderError::NetworkError(e.to_string()))?;
-
-        if !response.status().is_success() {
-            return Err(RenderError::NetworkError(format!(
-                "HTTP {}: {}",
-                response.status(),
-                response.status().canonical_reason().unwrap_or("Unknown")
-            )));
-        }
-
-        response
-            .bytes()
+        self.http
+            .get_bytes(url, None)
             .await
             .map_err(|e| RenderError::NetworkError(e.to_string()))
-            .map(|bytes| bytes.to_vec())
     }
 
     /// Detecta el formato de imagen
@@ -264,6 +320,26 @@ impl EmoteRenderer {
         Ok(total_size)
     }
 
+    /// Elimina los ficheros en cache de un emote (`<source>_<id>.png`)
+    pub async fn remove_cached(&self, emote_id: &str) -> usize {
+        let Ok(mut entries) = tokio::fs::read_dir(&self.cache_dir).await else {
+            return 0;
+        };
+
+        let suffix = format!("_{}.png", emote_id);
+        let mut removed = 0;
+        while let Ok(Some(entry)) = entries.next_entry().await {
+            let matches = entry
+                .file_name()
+                .to_str()
+                .is_some_and(|name| name.ends_with(&suffix));
+            if matches && tokio::fs::remove_file(entry.path()).await.is_ok() {
+                removed += 1;
+            }
+        }
+        removed
+    }
+
     /// Obtiene estadísticas del cache
     pub async fn get_cache_stats(&self) -> Result<CacheStats, RenderError> {
         if !self.cache_dir.exists() {
@@ -347,6 +423,585 @@ impl EmoteRenderer {
     }
 }
 
+/// Modificadores de BTTV que transforman el emote siguiente (`w! KEKW`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EmoteModifier {
+    /// `w!`: el doble de ancho
+    Wide,
+    /// `h!`
+    FlipHorizontal,
+    /// `v!`
+    FlipVertical,
+    /// `l!`: 90° a la izquierda
+    RotateLeft,
+    /// `r!`: 90° a la derecha
+    RotateRight,
+    /// `z!`: el emote siguiente se superpone al anterior
+    ZeroWidth,
+}
+
+impl EmoteModifier {
+    pub fn from_code(code: &str) -> Option<Self> {
+        Some(match code.to_lowercase().as_str() {
+            "w!" => Self::Wide,
+            "h!" => Self::FlipHorizontal,
+            "v!" => Self::FlipVertical,
+            "l!" => Self::RotateLeft,
+            "r!" => Self::RotateRight,
+            "z!" => Self::ZeroWidth,
+            _ => return None,
+        })
+    }
+
+    pub fn code(self) -> &'static str {
+        match self {
+            Self::Wide => "w!",
+            Self::FlipHorizontal => "h!",
+            Self::FlipVertical => "v!",
+            Self::RotateLeft => "l!",
+            Self::RotateRight => "r!",
+            Self::ZeroWidth => "z!",
+        }
+    }
+
+    fn apply(self, image: &RgbaImage) -> RgbaImage {
+        match self {
+            Self::Wide => imageops::resize(
+                image,
+                image.width() * 2,
+                image.height(),
+                imageops::FilterType::Triangle,
+            ),
+            Self::FlipHorizontal => imageops::flip_horizontal(image),
+            Self::FlipVertical => imageops::flip_vertical(image),
+            Self::RotateLeft => imageops::rotate270(image),
+            Self::RotateRight => imageops::rotate90(image),
+            Self::ZeroWidth => image.clone(),
+        }
+    }
+}
+
+/// Emote tal y como se dibuja: el emote base con sus modificadores y los
+/// emotes zero-width superpuestos
+#[derive(Debug, Clone)]
+pub struct EmoteStack {
+    pub base: Emote,
+    pub modifiers: Vec<EmoteModifier>,
+    pub overlays: Vec<Emote>,
+    /// Caracteres que ocupa en el texto, modificadores y capas incluidos
+    /// (fin exclusivo)
+    pub char_range: Range<usize>,
+}
+
+impl EmoteStack {
+    pub fn is_composite(&self) -> bool {
+        !self.modifiers.is_empty() || !self.overlays.is_empty()
+    }
+
+    /// Id de la imagen a dibujar; las pilas compuestas usan una clave propia
+    /// (`stack:w!:<base>+<capa>`) para no confundirse con el emote base
+    pub fn image_id(&self) -> String {
+        if !self.is_composite() {
+            return self.base.id.clone();
+        }
+        let modifiers: String = self.modifiers.iter().map(|m| m.code()).collect();
+        let mut id = format!("{}{}:{}", COMPOSITE_ID_PREFIX, modifiers, self.base.id);
+        for overlay in &self.overlays {
+            id.push('+');
+            id.push_str(&overlay.id);
+        }
+        id
+    }
+
+    /// Id con el que se dibuja: el de la imagen compuesta si ya está guardada,
+    /// si no el del emote base
+    pub fn drawn_id(&self) -> String {
+        let id = self.image_id();
+        if !self.is_composite() || is_composited(&id) {
+            id
+        } else {
+            self.base.id.clone()
+        }
+    }
+}
+
+/// Rango (fin exclusivo) de una aparición; algunas fuentes incluyen el último
+/// carácter en la posición, así que se mide con el nombre del emote
+fn occurrence_range(content: &str, emote: &Emote, pos: &TextPosition) -> Range<usize> {
+    let end = pos.start + emote.name.chars().count();
+    if crate::text::slice_chars(content, pos.start..end) == Some(emote.name.as_str()) {
+        pos.start..end
+    } else {
+        pos.start..pos.end
+    }
+}
+
+/// Agrupa los emotes del mensaje en pilas, en orden de aparición
+///
+/// Un emote zero-width separado del anterior solo por espacios se superpone a
+/// él. Los modificadores se aplican al emote siguiente (`z!` lo convierte en
+/// capa del anterior); si no hay emote a continuación se quedan como texto.
+pub fn layout_emotes(content: &str, emotes: &[Emote]) -> Vec<EmoteStack> {
+    let mut occurrences: Vec<(Range<usize>, &Emote)> = emotes
+        .iter()
+        .flat_map(|emote| {
+            emote
+                .positions
+                .iter()
+                .map(move |pos| (occurrence_range(content, emote, pos), emote))
+        })
+        .collect();
+    occurrences.sort_by_key(|(range, _)| range.start);
+
+    let only_spaces = |range: Range<usize>| {
+        crate::text::slice_chars(content, range).is_some_and(|gap| gap.trim().is_empty())
+    };
+
+    let mut stacks: Vec<EmoteStack> = Vec::new();
+    // Inicio, modificadores y fin de los modificadores aún sin emote
+    let mut pending: Option<(usize, Vec<EmoteModifier>, usize)> = None;
+    for (range, emote) in occurrences {
+        if stacks
+            .last()
+            .is_some_and(|last| range.start < last.char_range.end)
+        {
+            continue;
+        }
+        let modifiers = pending
+            .take()
+            .filter(|(_, _, end)| only_spaces(*end..range.start))
+            .map(|(start, modifiers, _)| (start, modifiers));
+
+        if emote.metadata.modifier {
+            if let Some(modifier) = EmoteModifier::from_code(&emote.name) {
+                let (start, mut modifiers) = modifiers.unwrap_or((range.start, Vec::new()));
+                modifiers.push(modifier);
+                pending = Some((start, modifiers, range.end));
+                continue;
+            }
+        }
+
+        let (start, mut modifiers) = modifiers.unwrap_or((range.start, Vec::new()));
+        let zero_width =
+            emote.metadata.is_zero_width || modifiers.contains(&EmoteModifier::ZeroWidth);
+        modifiers.retain(|modifier| *modifier != EmoteModifier::ZeroWidth);
+        if zero_width {
+            if let Some(last) = stacks
+                .last_mut()
+                .filter(|last| only_spaces(last.char_range.end..start))
+            {
+                last.overlays.push(emote.clone());
+                last.char_range.end = range.end;
+                continue;
+            }
+        }
+
+        stacks.push(EmoteStack {
+            base: emote.clone(),
+            modifiers,
+            overlays: Vec::new(),
+            char_range: start..range.end,
+        });
+    }
+    stacks
+}
+
+/// Frames de una capa ya escalados a la altura de la composición
+struct Layer {
+    frames: Vec<(RgbaImage, Duration)>,
+    total: Duration,
+}
+
+impl Layer {
+    fn new(frames: Vec<(RgbaImage, Duration)>, height: u32) -> Self {
+        let frames: Vec<(RgbaImage, Duration)> = frames
+            .into_iter()
+            .map(|(image, delay)| {
+                let width = (image.width() as u64 * height as u64 / image.height().max(1) as u64)
+                    .max(1) as u32;
+                let image = if image.dimensions() == (width, height) {
+                    image
+                } else {
+                    imageops::resize(&image, width, height, imageops::FilterType::Lanczos3)
+                };
+                (image, delay)
+            })
+            .collect();
+        let total = frames.iter().map(|(_, delay)| *delay).sum();
+        Self { frames, total }
+    }
+
+    fn width(&self) -> u32 {
+        self.frames[0].0.width()
+    }
+
+    /// Frame visible en `time` (la animación se repite)
+    fn frame_at(&self, time: Duration) -> &RgbaImage {
+        if self.total.is_zero() {
+            return &self.frames[0].0;
+        }
+        let mut offset = Duration::from_nanos((time.as_nanos() % self.total.as_nanos()) as u64);
+        for (image, delay) in &self.frames {
+            if offset < *delay {
+                return image;
+            }
+            offset -= *delay;
+        }
+        &self.frames[self.frames.len() - 1].0
+    }
+
+    /// Instantes en los que cambia de frame dentro de `period`
+    fn frame_starts(&self, period: Duration) -> Vec<Duration> {
+        let mut starts = Vec::new();
+        if self.total.is_zero() {
+            return starts;
+        }
+        let mut time = Duration::ZERO;
+        'outer: loop {
+            for (_, delay) in &self.frames {
+                if time >= period {
+                    break 'outer;
+                }
+                starts.push(time);
+                time += *delay;
+            }
+        }
+        starts
+    }
+}
+
+/// Compone una pila: el emote base con sus modificadores a `height` píxeles de
+/// alto y cada capa centrada encima
+///
+/// Devuelve un PNG si todas las imágenes son estáticas o un GIF que recorre la
+/// animación más larga (hasta 10 s); `None` si alguna no se puede decodificar.
+pub fn composite_stack(
+    base: &[u8],
+    modifiers: &[EmoteModifier],
+    overlays: &[Vec<u8>],
+    height: u32,
+) -> Option<Vec<u8>> {
+    let base_frames = decode_rgba_frames(base)?
+        .into_iter()
+        .map(|(image, delay)| {
+            let image = modifiers
+                .iter()
+                .fold(image, |image, modifier| modifier.apply(&image));
+            (image, delay)
+        })
+        .collect();
+    let mut layers = vec![Layer::new(base_frames, height)];
+    for overlay in overlays {
+        layers.push(Layer::new(decode_rgba_frames(overlay)?, height));
+    }
+
+    let width = layers.iter().map(Layer::width).max()?;
+    let period = layers
+        .iter()
+        .map(|layer| layer.total)
+        .max()
+        .unwrap_or_default()
+        .min(MAX_COMPOSITE_DURATION);
+    let mut starts: Vec<Duration> = layers
+        .iter()
+        .flat_map(|layer| layer.frame_starts(period))
+        .chain([Duration::ZERO])
+        .collect();
+    starts.sort();
+    starts.dedup();
+    starts.truncate(MAX_COMPOSITE_FRAMES);
+
+    let frames: Vec<(RgbaImage, Duration)> = starts
+        .iter()
+        .enumerate()
+        .map(|(i, start)| {
+            let mut canvas = RgbaImage::new(width, height);
+            for layer in &layers {
+                let image = layer.frame_at(*start);
+                let x = (width - image.width()) / 2;
+                imageops::overlay(&mut canvas, image, x as i64, 0);
+            }
+            let end = starts.get(i + 1).copied().unwrap_or(period);
+            (canvas, end.saturating_sub(*start))
+        })
+        .collect();
+
+    let mut bytes = Vec::new();
+    if let [(image, _)] = frames.as_slice() {
+        image
+            .write_to(&mut Cursor::new(&mut bytes), ImageOutputFormat::Png)
+            .ok()?;
+    } else {
+        let mut encoder = GifEncoder::new(&mut bytes);
+        encoder.set_repeat(Repeat::Infinite).ok()?;
+        encoder
+            .encode_frames(frames.into_iter().map(|(image, delay)| {
+                Frame::from_parts(image, 0, 0, Delay::from_saturating_duration(delay))
+            }))
+            .ok()?;
+    }
+    Some(bytes)
+}
+
+#[derive(Default)]
+struct CompositeStore {
+    images: HashMap<String, Vec<u8>>,
+    order: VecDeque<String>,
+}
+
+fn composite_store() -> &'static Mutex<CompositeStore> {
+    static STORE: OnceLock<Mutex<CompositeStore>> = OnceLock::new();
+    STORE.get_or_init(Default::default)
+}
+
+fn store_composited(id: String, bytes: Vec<u8>) {
+    let mut store = composite_store().lock().unwrap_or_else(|e| e.into_inner());
+    if store.images.insert(id.clone(), bytes).is_none() {
+        store.order.push_back(id);
+    }
+    while store.order.len() > MAX_COMPOSITED_IMAGES {
+        if let Some(oldest) = store.order.pop_front() {
+            store.images.remove(&oldest);
+        }
+    }
+}
+
+/// Imagen compuesta (PNG o GIF) guardada por [`EmoteRenderer::render_stack`]
+pub fn composited_image(id: &str) -> Option<Vec<u8>> {
+    composite_store()
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .images
+        .get(id)
+        .cloned()
+}
+
+fn is_composited(id: &str) -> bool {
+    composite_store()
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .images
+        .contains_key(id)
+}
+
+/// Rasterizador de texto del backend para [`compose_message`] (Cairo en GTK,
+/// GDI en Windows); tiene que poder usarse fuera del hilo de la interfaz
+pub trait TextRasterizer {
+    /// Ancho en píxeles del texto en una sola línea
+    fn measure(&mut self, text: &str, bold: bool) -> i32;
+    /// Texto en una línea de [`line_height`](Self::line_height) de alto sobre
+    /// fondo transparente
+    fn rasterize(&mut self, text: &str, bold: bool, color: Rgb) -> RgbaImage;
+    fn line_height(&self) -> i32;
+    /// El mismo rasterizador con la fuente multiplicada por `factor`
+    fn resized(&self, factor: f32) -> Self
+    where
+        Self: Sized;
+}
+
+/// Mensaje completo para componer en una sola imagen (`display.precomposed`)
+#[derive(Debug, Clone)]
+pub struct MessageBitmapSpec {
+    /// Cabecera en negrita tras las insignias; vacía si la plantilla no tiene
+    pub header: String,
+    pub body: String,
+    /// Emotes del cuerpo ya compuestos (rangos de carácter, fin exclusivo, en orden)
+    pub emotes: Vec<twitch_irc::message::Emote>,
+    /// Insignias (PNG) delante de la cabecera
+    pub badges: Vec<Vec<u8>>,
+    /// Avatar (PNG) delante de las insignias
+    pub avatar: Option<Vec<u8>>,
+    /// Cabecera del evento entre la del mensaje y el cuerpo
+    pub card: Option<EventCard>,
+    pub palette: ThemePalette,
+    /// Escala de la ventana (estilo y DPI)
+    pub scale: f32,
+}
+
+/// Primer frame de una imagen estática o animada
+fn first_frame(bytes: &[u8]) -> Option<RgbaImage> {
+    decode_rgba_frames(bytes)?
+        .into_iter()
+        .next()
+        .map(|(image, _)| image)
+}
+
+/// Escala la imagen a `height` píxeles de alto manteniendo la proporción
+fn fit_height(image: RgbaImage, height: u32) -> RgbaImage {
+    let width = (image.width() as u64 * height as u64 / image.height().max(1) as u64).max(1) as u32;
+    if image.dimensions() == (width, height) {
+        image
+    } else {
+        imageops::resize(&image, width, height, imageops::FilterType::Lanczos3)
+    }
+}
+
+fn rgba(color: Rgb) -> image::Rgba<u8> {
+    image::Rgba([color.0, color.1, color.2, 255])
+}
+
+/// Tamaño de los meses de la cabecera del evento respecto al texto, como en
+/// las ventanas de GTK y Win32
+const CARD_HEADLINE_FACTOR: f32 = 1.6;
+
+/// Dibuja el mensaje entero (avatar, insignias, cabecera, cabecera del
+/// evento, texto y emotes) en una imagen opaca con los colores de la paleta
+///
+/// Los emotes animados se quedan en su primer frame; los que no tienen imagen
+/// en `image` se dibujan con el arte de reserva.
+pub fn compose_message(
+    spec: &MessageBitmapSpec,
+    text: &mut impl TextRasterizer,
+    image: impl Fn(&str) -> Option<Vec<u8>>,
+) -> RgbaImage {
+    let scaled = |value: i32| (value as f32 * spec.scale).round() as i32;
+    let padding = scaled(BITMAP_PADDING);
+    let spacing = scaled(4);
+    let line_height = text.line_height().max(1);
+
+    let placeholder = || {
+        first_frame(crate::assets::emote_placeholder().bytes)
+            .unwrap_or_else(|| RgbaImage::new(1, 1))
+    };
+    let emote_side = scaled(BITMAP_EMOTE_SIDE).max(1) as u32;
+    let emote_images: Vec<RgbaImage> = spec
+        .emotes
+        .iter()
+        .map(|emote| {
+            let frame = image(&emote.id).and_then(|bytes| first_frame(&bytes));
+            fit_height(frame.unwrap_or_else(placeholder), emote_side)
+        })
+        .collect();
+    let badges: Vec<RgbaImage> = spec
+        .avatar
+        .iter()
+        .chain(&spec.badges)
+        .filter_map(|png| first_frame(png))
+        .map(|badge| fit_height(badge, line_height as u32))
+        .collect();
+
+    let runs = crate::text::interleave_runs(
+        &spec.body,
+        spec.emotes.iter().map(|emote| emote.char_range.clone()),
+    );
+    let layout = crate::flow::flow_runs(
+        &runs,
+        crate::flow::FlowMetrics {
+            measure: |fragment: &str| text.measure(fragment, false),
+            emote_size: |index: usize| {
+                let image = &emote_images[index];
+                (image.width() as i32, image.height() as i32)
+            },
+            max_width: scaled(BITMAP_MAX_WIDTH) - padding * 2,
+            line_height,
+            emote_margin: scaled(2),
+        },
+    );
+
+    let header_width = badges
+        .iter()
+        .map(|badge| badge.width() as i32 + spacing)
+        .sum::<i32>()
+        + if spec.header.is_empty() {
+            0
+        } else {
+            text.measure(&spec.header, true)
+        };
+    let header_height = if header_width > 0 {
+        line_height + scaled(5)
+    } else {
+        0
+    };
+
+    let mut headline_text = text.resized(CARD_HEADLINE_FACTOR);
+    let (card_width, card_height) = match &spec.card {
+        Some(card) => {
+            let detail = card
+                .detail
+                .as_deref()
+                .map_or((0, 0), |detail| (text.measure(detail, false), line_height));
+            (
+                headline_text.measure(&card.headline, true).max(detail.0),
+                headline_text.line_height() + detail.1 + scaled(4),
+            )
+        }
+        None => (0, 0),
+    };
+
+    let width = (layout.width.max(header_width).max(card_width) + padding * 2)
+        .clamp(scaled(BITMAP_MIN_WIDTH), scaled(BITMAP_MAX_WIDTH))
+        .max(1) as u32;
+    let height = (padding * 2 + header_height + card_height + layout.height).max(1) as u32;
+    let mut canvas = RgbaImage::from_pixel(width, height, rgba(spec.palette.background));
+    if spec.palette.border != spec.palette.background {
+        let border = rgba(spec.palette.border);
+        for x in 0..width {
+            canvas.put_pixel(x, 0, border);
+            canvas.put_pixel(x, height - 1, border);
+        }
+        for y in 0..height {
+            canvas.put_pixel(0, y, border);
+            canvas.put_pixel(width - 1, y, border);
+        }
+    }
+
+    let mut x = padding;
+    for badge in &badges {
+        let y = padding + (line_height - badge.height() as i32) / 2;
+        imageops::overlay(&mut canvas, badge, x as i64, y as i64);
+        x += badge.width() as i32 + spacing;
+    }
+    if !spec.header.is_empty() {
+        let header = text.rasterize(&spec.header, true, spec.palette.username);
+        imageops::overlay(&mut canvas, &header, x as i64, padding as i64);
+    }
+
+    if let Some(card) = &spec.card {
+        let top = padding + header_height;
+        let headline = headline_text.rasterize(&card.headline, true, spec.palette.progress);
+        imageops::overlay(&mut canvas, &headline, padding as i64, top as i64);
+        if let Some(detail) = &card.detail {
+            let detail = text.rasterize(detail, false, spec.palette.text);
+            let top = top + headline_text.line_height();
+            imageops::overlay(&mut canvas, &detail, padding as i64, top as i64);
+        }
+    }
+
+    let top = padding + header_height + card_height;
+    for item in &layout.items {
+        let (x, y) = ((padding + item.x) as i64, (top + item.y) as i64);
+        match &item.content {
+            FlowContent::Text(fragment) if !fragment.trim().is_empty() => {
+                let run = text.rasterize(fragment, false, spec.palette.text);
+                imageops::overlay(&mut canvas, &run, x, y);
+            }
+            FlowContent::Text(_) => {}
+            FlowContent::Emote(index) => {
+                imageops::overlay(&mut canvas, &emote_images[*index], x, y);
+            }
+        }
+    }
+    canvas
+}
+
+/// [`compose_message`] en un hilo de trabajo, con las imágenes de emotes que
+/// ya estén descargadas o compuestas
+pub async fn render_message_bitmap<R>(spec: MessageBitmapSpec, mut text: R) -> Option<RgbaImage>
+where
+    R: TextRasterizer + Send + 'static,
+{
+    tokio::task::spawn_blocking(move || {
+        compose_message(&spec, &mut text, |id| {
+            super::EmoteDownloader::global()
+                .image(id)
+                .map(|bytes| bytes.to_vec())
+                .or_else(|| composited_image(id))
+        })
+    })
+    .await
+    .ok()
+}
+
 /// Estadísticas del cache
 #[derive(Debug, Clone)]
 pub struct CacheStats {
@@ -357,18 +1012,16 @@ pub struct CacheStats {
     pub newest_file: Option<String>,
 }
 
-impl Default for EmoteRenderer {
-    fn default() -> Self {
-        Self::new(std::env::temp_dir().join("overlay-native").join("emotes"))
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::connection::{EmoteMetadata, TextPosition};
     use tempfile::TempDir;
 
+    fn test_http() -> EmoteHttpClient {
+        EmoteHttpClient::new(&crate::config::EmoteHttpConfig::default())
+    }
+
     fn create_test_emote(id: &str, name: &str, source: EmoteSource) -> Emote {
         Emote {
             id: id.to_string(),
@@ -388,7 +1041,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_resolve_emote_url() {
-        let renderer = EmoteRenderer::new(PathBuf::from("/tmp"));
+        let renderer = EmoteRenderer::new(PathBuf::from("/tmp"), test_http());
 
         let twitch_emote = create_test_emote("25", "Kappa", EmoteSource::Twitch);
         let url = renderer.resolve_emote_url(&twitch_emote).unwrap();
@@ -408,7 +1061,7 @@ mod tests {
 
     #[test]
     fn test_detect_image_format() {
-        let renderer = EmoteRenderer::new(PathBuf::from("/tmp"));
+        let renderer = EmoteRenderer::new(PathBuf::from("/tmp"), test_http());
 
         // PNG magic bytes
         let png_data = vec![0x89, 0x50, 0x4E, 0x47, 0x0D, 0x0A, 0x1A, 0x0A];
@@ -419,10 +1072,224 @@ mod tests {
         assert_eq!(renderer.detect_image_format(&gif_data).unwrap(), "gif");
     }
 
+    /// Emote en `starts` con el fin inclusivo, como lo envían algunas fuentes
+    fn placed(id: &str, name: &str, starts: &[usize], metadata: EmoteMetadata) -> Emote {
+        Emote {
+            positions: starts
+                .iter()
+                .map(|&start| TextPosition {
+                    start,
+                    end: start + name.chars().count() - 1,
+                })
+                .collect(),
+            metadata,
+            ..create_test_emote(id, name, EmoteSource::SevenTV)
+        }
+    }
+
+    #[test]
+    fn test_layout_stacks_zero_width_and_modifiers() {
+        let zero_width = EmoteMetadata {
+            is_zero_width: true,
+            ..EmoteMetadata::default()
+        };
+        let modifier = EmoteMetadata {
+            modifier: true,
+            ..EmoteMetadata::default()
+        };
+        let content = "RainTime hola KEKW RainTime w! h! PogU w!";
+        let emotes = vec![
+            placed("rain", "RainTime", &[0, 19], zero_width),
+            placed("kekw", "KEKW", &[14], EmoteMetadata::default()),
+            placed("pogu", "PogU", &[34], EmoteMetadata::default()),
+            placed("wide", "w!", &[28, 39], modifier.clone()),
+            placed("flip", "h!", &[31], modifier),
+        ];
+
+        let stacks = layout_emotes(content, &emotes);
+        assert_eq!(stacks.len(), 3);
+
+        // Sin emote previo, el zero-width se dibuja solo
+        assert_eq!(stacks[0].base.id, "rain");
+        assert!(!stacks[0].is_composite());
+        assert_eq!(stacks[0].char_range, 0..8);
+
+        assert_eq!(stacks[1].base.id, "kekw");
+        assert_eq!(stacks[1].overlays.len(), 1);
+        assert_eq!(stacks[1].char_range, 14..27);
+        assert_eq!(stacks[1].image_id(), "stack::kekw+rain");
+
+        // El `w!` final no tiene emote detrás y se queda como texto
+        assert_eq!(
+            stacks[2].modifiers,
+            vec![EmoteModifier::Wide, EmoteModifier::FlipHorizontal]
+        );
+        assert_eq!(stacks[2].char_range, 28..38);
+        assert_eq!(stacks[2].image_id(), "stack:w!h!:pogu");
+    }
+
+    #[test]
+    fn test_drawn_id_falls_back_to_base_until_composited() {
+        let modifier = EmoteMetadata {
+            modifier: true,
+            ..EmoteMetadata::default()
+        };
+        let emotes = vec![
+            placed("drawn-flip", "h!", &[0], modifier),
+            placed("drawn-base", "Base", &[3], EmoteMetadata::default()),
+        ];
+        let stack = &layout_emotes("h! Base", &emotes)[0];
+        assert_eq!(stack.drawn_id(), "drawn-base");
+
+        store_composited(stack.image_id(), vec![1]);
+        assert_eq!(stack.drawn_id(), "stack:h!:drawn-base");
+    }
+
+    fn solid_png(width: u32, height: u32, color: [u8; 4]) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        RgbaImage::from_pixel(width, height, image::Rgba(color))
+            .write_to(&mut Cursor::new(&mut bytes), ImageOutputFormat::Png)
+            .unwrap();
+        bytes
+    }
+
+    #[test]
+    fn test_composite_centers_overlays_on_the_modified_base() {
+        let base = solid_png(16, 16, [255, 0, 0, 255]);
+        let overlay = solid_png(8, 16, [0, 0, 255, 255]);
+
+        let bytes = composite_stack(&base, &[EmoteModifier::Wide], &[overlay.clone()], 32).unwrap();
+        let image = image::load_from_memory(&bytes).unwrap().to_rgba8();
+        assert_eq!(image.dimensions(), (64, 32));
+        assert_eq!(image.get_pixel(0, 0).0, [255, 0, 0, 255]);
+        assert_eq!(image.get_pixel(32, 16).0, [0, 0, 255, 255]);
+
+        // Con una capa animada, el resultado es un GIF con sus frames
+        let mut gif = Vec::new();
+        {
+            let mut encoder = GifEncoder::new(&mut gif);
+            for color in [[0, 255, 0, 255], [0, 0, 0, 0]] {
+                let frame = RgbaImage::from_pixel(16, 16, image::Rgba(color));
+                encoder
+                    .encode_frame(Frame::from_parts(
+                        frame,
+                        0,
+                        0,
+                        Delay::from_numer_denom_ms(100, 1),
+                    ))
+                    .unwrap();
+            }
+        }
+        let bytes = composite_stack(&base, &[], &[gif], 16).unwrap();
+        let frames = decode_rgba_frames(&bytes).unwrap();
+        assert_eq!(frames.len(), 2);
+        assert_eq!(frames[0].0.get_pixel(8, 8).0, [0, 255, 0, 255]);
+        assert_eq!(frames[1].0.get_pixel(8, 8).0, [255, 0, 0, 255]);
+
+        assert!(composite_stack(b"not an image", &[], &[overlay], 16).is_none());
+    }
+
+    /// Cada carácter es un bloque de 6 píxeles del color pedido
+    struct BlockText;
+
+    impl TextRasterizer for BlockText {
+        fn measure(&mut self, text: &str, _bold: bool) -> i32 {
+            text.chars().count() as i32 * 6
+        }
+
+        fn rasterize(&mut self, text: &str, bold: bool, color: Rgb) -> RgbaImage {
+            let width = self.measure(text, bold).max(1) as u32;
+            RgbaImage::from_pixel(width, 10, rgba(color))
+        }
+
+        fn line_height(&self) -> i32 {
+            14
+        }
+
+        fn resized(&self, _factor: f32) -> Self {
+            BlockText
+        }
+    }
+
+    #[test]
+    fn test_compose_message_draws_header_text_and_emotes() {
+        let palette = ThemePalette {
+            background: Rgb(10, 10, 10),
+            border: Rgb(10, 10, 10),
+            text: Rgb(200, 200, 200),
+            username: Rgb(0, 200, 0),
+            progress: Rgb(0, 0, 200),
+            progress_background: Rgb(60, 60, 60),
+        };
+        let red = solid_png(28, 28, [255, 0, 0, 255]);
+        let spec = MessageBitmapSpec {
+            header: "Viewer".to_string(),
+            body: "hola Kappa".to_string(),
+            emotes: vec![twitch_irc::message::Emote {
+                id: "25".to_string(),
+                code: "Kappa".to_string(),
+                char_range: 5..10,
+            }],
+            badges: vec![crate::assets::badge("vip").bytes.to_vec()],
+            avatar: None,
+            card: None,
+            palette,
+            scale: 1.0,
+        };
+
+        let canvas = compose_message(&spec, &mut BlockText, |id| {
+            (id == "25").then(|| red.clone())
+        });
+        // Ancho mínimo; márgenes, cabecera (14 + 5) y una línea de 32 (el emote)
+        assert_eq!(canvas.dimensions(), (200, 10 + 19 + 32 + 10));
+        assert_eq!(*canvas.get_pixel(2, 2), rgba(palette.background));
+        let colors: Vec<_> = canvas.pixels().collect();
+        assert!(colors.contains(&&rgba(palette.username)));
+        assert!(colors.contains(&&rgba(palette.text)));
+        assert!(colors.contains(&&image::Rgba([255, 0, 0, 255])));
+
+        // Sin imagen el emote se dibuja con el arte de reserva
+        let fallback = compose_message(&spec, &mut BlockText, |_| None);
+        assert_eq!(fallback.dimensions(), canvas.dimensions());
+        assert_ne!(fallback, canvas);
+    }
+
+    #[test]
+    fn test_compose_message_draws_avatar_and_event_card() {
+        let palette = ThemePalette {
+            background: Rgb(10, 10, 10),
+            border: Rgb(10, 10, 10),
+            text: Rgb(200, 200, 200),
+            username: Rgb(0, 200, 0),
+            progress: Rgb(0, 0, 200),
+            progress_background: Rgb(60, 60, 60),
+        };
+        let spec = MessageBitmapSpec {
+            header: "Viewer".to_string(),
+            body: "hola".to_string(),
+            emotes: Vec::new(),
+            badges: Vec::new(),
+            avatar: Some(solid_png(28, 28, [255, 0, 0, 255])),
+            card: Some(EventCard {
+                headline: "12 months".to_string(),
+                detail: Some("Tier 1".to_string()),
+            }),
+            palette,
+            scale: 1.0,
+        };
+
+        let canvas = compose_message(&spec, &mut BlockText, |_| None);
+        // Márgenes, cabecera (14 + 5), meses y detalle (14 + 14 + 4) y una línea
+        assert_eq!(canvas.dimensions(), (200, 10 + 19 + 32 + 14 + 10));
+        let colors: Vec<_> = canvas.pixels().collect();
+        assert!(colors.contains(&&image::Rgba([255, 0, 0, 255])));
+        assert!(colors.contains(&&rgba(palette.progress)));
+    }
+
     #[tokio::test]
     async fn test_cache_stats() {
         let temp_dir = TempDir::new().unwrap();
-        let renderer = EmoteRenderer::new(temp_dir.path().to_path_buf());
+        let renderer = EmoteRenderer::new(temp_dir.path().to_path_buf(), test_http());
 
         let stats = renderer.get_cache_stats().await.unwrap();
         assert_eq!(stats.file_count, 0);
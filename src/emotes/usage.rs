@@ -0,0 +1,149 @@
+//! Uso de emotes por canal durante la sesión
+//!
+//! Cuenta cuántas veces aparece cada emote en los mensajes de cada canal
+//! desde que arrancó el overlay, para saber qué emotes de 7TV, BTTV o FFZ se
+//! usan de verdad y cuáles sobran en el set del canal.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::connection::{Emote, EmoteSource};
+
+/// Usos de un emote en un canal
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, JsonSchema)]
+pub struct EmoteUsageCount {
+    pub id: String,
+    pub name: String,
+    pub source: EmoteSource,
+    pub uses: u64,
+}
+
+/// Contadores de uso de emotes por canal
+#[derive(Debug, Default)]
+pub struct EmoteUsage {
+    /// canal -> id del emote -> usos
+    channels: Mutex<HashMap<String, HashMap<String, EmoteUsageCount>>>,
+}
+
+impl EmoteUsage {
+    /// Suma los emotes de un mensaje de `channel`; cada aparición cuenta
+    pub fn record(&self, channel: &str, emotes: &[Emote]) {
+        if emotes.is_empty() {
+            return;
+        }
+        let mut channels = self.lock();
+        let counts = channels.entry(channel.to_string()).or_default();
+        for emote in emotes {
+            let count = counts
+                .entry(emote.id.clone())
+                .or_insert_with(|| EmoteUsageCount {
+                    id: emote.id.clone(),
+                    name: emote.name.clone(),
+                    source: emote.source.clone(),
+                    uses: 0,
+                });
+            // Un emote renombrado en el set conserva su cuenta
+            count.name.clone_from(&emote.name);
+            count.uses += emote.positions.len().max(1) as u64;
+        }
+    }
+
+    /// Los `n` emotes más usados en `channel`, de más a menos usado
+    pub fn top_emotes(&self, channel: &str, n: usize) -> Vec<EmoteUsageCount> {
+        let channels = self.lock();
+        let Some(counts) = channels.get(channel) else {
+            return Vec::new();
+        };
+        let mut top: Vec<EmoteUsageCount> = counts.values().cloned().collect();
+        top.sort_by(|a, b| b.uses.cmp(&a.uses).then_with(|| a.name.cmp(&b.name)));
+        top.truncate(n);
+        top
+    }
+
+    /// Canales con algún emote usado, en orden alfabético
+    pub fn channels(&self) -> Vec<String> {
+        let mut channels: Vec<String> = self.lock().keys().cloned().collect();
+        channels.sort();
+        channels
+    }
+
+    /// Resumen legible con los `n` más usados de cada canal, `None` si no se
+    /// ha usado ninguno
+    pub fn report(&self, n: usize) -> Option<String> {
+        let channels = self.channels();
+        if channels.is_empty() {
+            return None;
+        }
+        let lines = channels
+            .iter()
+            .map(|channel| {
+                let top = self
+                    .top_emotes(channel, n)
+                    .iter()
+                    .map(|emote| format!("{} ({}) {}", emote.name, emote.source, emote.uses))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                format!("  {}: {}", channel, top)
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        Some(format!("😀 Top emotes this session:\n{}", lines))
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, HashMap<String, EmoteUsageCount>>> {
+        self.channels.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::connection::TextPosition;
+
+    fn emote(id: &str, name: &str, source: EmoteSource, uses: usize) -> Emote {
+        Emote {
+            id: id.to_string(),
+            name: name.to_string(),
+            source,
+            positions: (0..uses)
+                .map(|i| TextPosition {
+                    start: i * 10,
+                    end: i * 10 + name.len() - 1,
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_top_emotes_per_channel() {
+        let usage = EmoteUsage::default();
+        usage.record(
+            "foo",
+            &[
+                emote("1", "KEKW", EmoteSource::SevenTV, 2),
+                emote("2", "Kappa", EmoteSource::Twitch, 1),
+            ],
+        );
+        usage.record("foo", &[emote("1", "KEKW", EmoteSource::SevenTV, 1)]);
+        usage.record("foo", &[emote("3", "catJAM", EmoteSource::BTTV, 1)]);
+        usage.record("bar", &[emote("2", "Kappa", EmoteSource::Twitch, 5)]);
+
+        let top = usage.top_emotes("foo", 2);
+        assert_eq!(
+            top.iter()
+                .map(|e| (e.name.as_str(), e.uses))
+                .collect::<Vec<_>>(),
+            vec![("KEKW", 3), ("Kappa", 1)]
+        );
+        assert!(usage.top_emotes("missing", 5).is_empty());
+        assert_eq!(usage.channels(), vec!["bar", "foo"]);
+        assert_eq!(
+            usage.report(1).as_deref(),
+            Some("😀 Top emotes this session:\n  bar: Kappa (twitch) 5\n  foo: KEKW (7tv) 3")
+        );
+    }
+}
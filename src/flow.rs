@@ -0,0 +1,275 @@
+//! Maquetación en línea de texto y emotes para renderers sin motor de texto
+//!
+//! GTK delega en Pango; en Windows el texto se mide con GDI y aquí se reparte
+//! en líneas junto con los emotes, partiendo por palabras y, si una palabra no
+//! cabe sola en una línea, por grafemas.
+
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::text::TextRun;
+
+/// Contenido de un elemento colocado
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FlowContent {
+    Text(String),
+    /// Índice del emote en la lista original
+    Emote(usize),
+}
+
+/// Elemento colocado, en coordenadas relativas al inicio del mensaje
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FlowItem {
+    pub content: FlowContent,
+    pub x: i32,
+    pub y: i32,
+    pub width: i32,
+    pub height: i32,
+}
+
+/// Resultado de [`flow_runs`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FlowLayout {
+    pub items: Vec<FlowItem>,
+    /// Ancho de la línea más larga
+    pub width: i32,
+    pub height: i32,
+}
+
+/// Medidas que necesita la maquetación
+pub struct FlowMetrics<M, E> {
+    /// Ancho en píxeles de un fragmento de texto
+    pub measure: M,
+    /// Ancho y alto de un emote
+    pub emote_size: E,
+    pub max_width: i32,
+    pub line_height: i32,
+    /// Separación horizontal a cada lado de un emote
+    pub emote_margin: i32,
+}
+
+/// Reparte los tramos en líneas de como mucho `max_width` píxeles
+///
+/// Cada línea mide lo que su elemento más alto; el texto y los emotes se
+/// centran verticalmente en ella.
+pub fn flow_runs<M, E>(runs: &[TextRun<'_>], mut metrics: FlowMetrics<M, E>) -> FlowLayout
+where
+    M: FnMut(&str) -> i32,
+    E: FnMut(usize) -> (i32, i32),
+{
+    let mut flow = Flow::new(metrics.max_width, metrics.line_height);
+    for run in runs {
+        match *run {
+            TextRun::Text(text) => {
+                for word in text.split_word_bounds() {
+                    if word == "\n" || word == "\r\n" {
+                        flow.break_line();
+                        continue;
+                    }
+                    let width = (metrics.measure)(word);
+                    if width <= metrics.max_width || word.trim().is_empty() {
+                        let ink = (metrics.measure)(word.trim_end());
+                        flow.place_text(word, width, ink);
+                        continue;
+                    }
+                    // Palabra más ancha que la línea: se parte por grafemas
+                    for grapheme in word.graphemes(true) {
+                        let width = (metrics.measure)(grapheme);
+                        flow.place_text(grapheme, width, width);
+                    }
+                }
+            }
+            TextRun::Emote(index) => {
+                let (width, height) = (metrics.emote_size)(index);
+                flow.place_emote(index, width, height, metrics.emote_margin);
+            }
+        }
+    }
+    flow.finish()
+}
+
+/// Estado de la maquetación: línea en curso y líneas ya cerradas
+struct Flow {
+    max_width: i32,
+    line_height: i32,
+    layout: FlowLayout,
+    line: Vec<FlowItem>,
+    x: i32,
+    /// Fin de lo visible en la línea en curso
+    ink: i32,
+}
+
+impl Flow {
+    fn new(max_width: i32, line_height: i32) -> Self {
+        Self {
+            max_width,
+            line_height,
+            layout: FlowLayout::default(),
+            line: Vec::new(),
+            x: 0,
+            ink: 0,
+        }
+    }
+
+    /// Coloca texto de `width` píxeles, de los que `ink` son visibles (sin los
+    /// espacios finales, que pueden quedar fuera de la línea)
+    fn place_text(&mut self, text: &str, width: i32, ink: i32) {
+        if self.x > 0 && self.x + ink > self.max_width {
+            self.break_line();
+            if text.trim().is_empty() {
+                return;
+            }
+        }
+        let end = self.x + ink;
+        match self.line.last_mut() {
+            // Texto seguido del anterior: un solo elemento hasta el próximo emote
+            Some(FlowItem {
+                content: FlowContent::Text(previous),
+                x,
+                width: item_width,
+                ..
+            }) => {
+                previous.push_str(text);
+                if ink > 0 {
+                    *item_width = end - *x;
+                }
+            }
+            _ => self.line.push(FlowItem {
+                content: FlowContent::Text(text.to_string()),
+                x: self.x,
+                y: 0,
+                width: ink,
+                height: self.line_height,
+            }),
+        }
+        if ink > 0 {
+            self.ink = end;
+        }
+        self.x += width;
+    }
+
+    fn place_emote(&mut self, index: usize, width: i32, height: i32, margin: i32) {
+        if self.x > 0 && self.x + width + margin * 2 > self.max_width {
+            self.break_line();
+        }
+        self.line.push(FlowItem {
+            content: FlowContent::Emote(index),
+            x: self.x + margin,
+            y: 0,
+            width,
+            height,
+        });
+        self.x += width + margin * 2;
+        self.ink = self.x;
+    }
+
+    fn break_line(&mut self) {
+        let top = self.layout.height;
+        let line_height = self
+            .line
+            .iter()
+            .map(|item| item.height)
+            .max()
+            .unwrap_or(self.line_height)
+            .max(self.line_height);
+        for mut item in self.line.drain(..) {
+            item.y = top + (line_height - item.height) / 2;
+            self.layout.items.push(item);
+        }
+        self.layout.width = self.layout.width.max(self.ink);
+        self.layout.height += line_height;
+        self.x = 0;
+        self.ink = 0;
+    }
+
+    fn finish(mut self) -> FlowLayout {
+        if !self.line.is_empty() {
+            self.break_line();
+        }
+        // Los espacios al final de una línea no se dibujan
+        for item in &mut self.layout.items {
+            if let FlowContent::Text(text) = &mut item.content {
+                text.truncate(text.trim_end().len());
+            }
+        }
+        self.layout.items.retain(|item| match &item.content {
+            FlowContent::Text(text) => !text.is_empty(),
+            FlowContent::Emote(_) => true,
+        });
+        self.layout
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 10 píxeles por carácter; emotes de 20x30 con 2 de margen
+    fn layout(runs: &[TextRun<'_>], max_width: i32) -> FlowLayout {
+        flow_runs(
+            runs,
+            FlowMetrics {
+                measure: |text: &str| text.chars().count() as i32 * 10,
+                emote_size: |_| (20, 30),
+                max_width,
+                line_height: 16,
+                emote_margin: 2,
+            },
+        )
+    }
+
+    fn text_at(layout: &FlowLayout, text: &str) -> (i32, i32) {
+        let item = layout
+            .items
+            .iter()
+            .find(|item| item.content == FlowContent::Text(text.to_string()))
+            .unwrap_or_else(|| panic!("{:?} not placed in {:?}", text, layout.items));
+        (item.x, item.y)
+    }
+
+    #[test]
+    fn test_emotes_flow_inline_with_the_text() {
+        let runs = [
+            TextRun::Text("hola "),
+            TextRun::Emote(0),
+            TextRun::Text(" que tal"),
+        ];
+        let flow = layout(&runs, 500);
+        assert_eq!(flow.height, 30);
+        assert_eq!(flow.width, 50 + 24 + 80);
+        // Texto centrado en la línea, a la altura del emote
+        assert_eq!(text_at(&flow, "hola"), (0, 7));
+        assert_eq!(
+            flow.items[1],
+            FlowItem {
+                content: FlowContent::Emote(0),
+                x: 52,
+                y: 0,
+                width: 20,
+                height: 30,
+            }
+        );
+        assert_eq!(text_at(&flow, " que tal"), (74, 7));
+    }
+
+    #[test]
+    fn test_lines_wrap_at_words_and_long_words_split() {
+        let flow = layout(&[TextRun::Text("uno dos tres")], 75);
+        assert_eq!(text_at(&flow, "uno dos"), (0, 0));
+        assert_eq!(text_at(&flow, "tres"), (0, 16));
+        assert_eq!(flow.height, 32);
+
+        let flow = layout(&[TextRun::Text("abcdefghij")], 40);
+        assert_eq!(text_at(&flow, "abcd"), (0, 0));
+        assert_eq!(text_at(&flow, "efgh"), (0, 16));
+        assert_eq!(text_at(&flow, "ij"), (0, 32));
+
+        // Un emote que no cabe pasa a la línea siguiente
+        let flow = layout(&[TextRun::Text("abcde"), TextRun::Emote(3)], 60);
+        assert_eq!((flow.items[1].x, flow.items[1].y), (2, 16));
+
+        // Un salto de línea explícito (la traducción) empieza otra línea
+        let flow = layout(&[TextRun::Text("hola\nhello")], 200);
+        assert_eq!(text_at(&flow, "hola"), (0, 0));
+        assert_eq!(text_at(&flow, "hello"), (0, 16));
+    }
+}
@@ -0,0 +1,223 @@
+//! Presupuesto de errores por plataforma y proveedor de emotes
+//!
+//! Los fallos (conexión, autenticación, descargas de emotes...) se registran por
+//! origen (`platform:twitch`, `provider:7tv`, ...). Si un origen supera
+//! `max_failures` dentro de `window_secs` se genera una alerta, para que el
+//! streamer sepa p.ej. que su token de Twitch caducó antes de que se lo digan
+//! los espectadores.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use crate::config::{ErrorBudgetConfig, SystemUser};
+use crate::connection::ChatMessage;
+use crate::system_users;
+
+static GLOBAL_BUDGET: OnceLock<ErrorBudget> = OnceLock::new();
+
+/// Se superó el presupuesto de errores de un origen
+#[derive(Debug, Clone, PartialEq)]
+pub struct BudgetAlert {
+    /// Origen del fallo, p.ej. `platform:twitch`
+    pub source: String,
+    /// Fallos dentro de la ventana
+    pub failures: usize,
+    pub window: Duration,
+    pub last_error: String,
+}
+
+impl BudgetAlert {
+    /// Texto corto para logs y la notificación del overlay
+    pub fn summary(&self) -> String {
+        format!(
+            "{} failed {} times in {} min (last error: {})",
+            self.source,
+            self.failures,
+            self.window.as_secs().div_ceil(60),
+            self.last_error
+        )
+    }
+
+    /// Mensaje de sistema para la notificación de autodiagnóstico
+    pub fn to_message(&self, user: &SystemUser) -> ChatMessage {
+        let mut custom_data = HashMap::new();
+        custom_data.insert("error_source".to_string(), self.source.clone().into());
+        custom_data.insert("error_count".to_string(), self.failures.into());
+
+        system_users::system_message(
+            user,
+            "diagnostics",
+            format!("⚠ {}", self.summary()),
+            custom_data,
+        )
+    }
+}
+
+#[derive(Debug, Default)]
+struct SourceState {
+    failures: VecDeque<Instant>,
+    last_error: String,
+    alerted_at: Option<Instant>,
+}
+
+#[derive(Debug)]
+struct BudgetState {
+    config: ErrorBudgetConfig,
+    sources: HashMap<String, SourceState>,
+    pending: Vec<BudgetAlert>,
+}
+
+/// Contador de fallos por origen con ventana deslizante
+#[derive(Debug)]
+pub struct ErrorBudget {
+    state: Mutex<BudgetState>,
+}
+
+impl ErrorBudget {
+    pub fn new(config: ErrorBudgetConfig) -> Self {
+        Self {
+            state: Mutex::new(BudgetState {
+                config,
+                sources: HashMap::new(),
+                pending: Vec::new(),
+            }),
+        }
+    }
+
+    /// Presupuesto global del proceso, usado por plataformas y proveedores
+    pub fn global() -> &'static ErrorBudget {
+        GLOBAL_BUDGET.get_or_init(|| Self::new(ErrorBudgetConfig::default()))
+    }
+
+    pub fn configure(&self, config: ErrorBudgetConfig) {
+        self.lock().config = config;
+    }
+
+    /// Registra un fallo de `source`
+    pub fn record_failure(&self, source: &str, error: &str) {
+        self.record_failure_at(source, error, Instant::now());
+    }
+
+    pub fn record_failure_at(&self, source: &str, error: &str, now: Instant) {
+        let mut state = self.lock();
+        if !state.config.enabled {
+            return;
+        }
+        let window = Duration::from_secs(state.config.window_secs);
+        let max_failures = state.config.max_failures;
+
+        let entry = state.sources.entry(source.to_string()).or_default();
+        entry.failures.push_back(now);
+        while entry
+            .failures
+            .front()
+            .is_some_and(|at| now.saturating_duration_since(*at) > window)
+        {
+            entry.failures.pop_front();
+        }
+        entry.last_error = error.to_string();
+
+        // Una alerta por ventana y origen
+        let cooled_down = entry
+            .alerted_at
+            .is_none_or(|at| now.saturating_duration_since(at) >= window);
+        if entry.failures.len() > max_failures && cooled_down {
+            entry.alerted_at = Some(now);
+            let alert = BudgetAlert {
+                source: source.to_string(),
+                failures: entry.failures.len(),
+                window,
+                last_error: entry.last_error.clone(),
+            };
+            state.pending.push(alert);
+        }
+    }
+
+    /// Fallos de `source` dentro de la ventana actual
+    pub fn failure_count(&self, source: &str, now: Instant) -> usize {
+        let state = self.lock();
+        let window = Duration::from_secs(state.config.window_secs);
+        state.sources.get(source).map_or(0, |entry| {
+            entry
+                .failures
+                .iter()
+                .filter(|at| now.saturating_duration_since(**at) <= window)
+                .count()
+        })
+    }
+
+    /// Alertas generadas desde la última llamada
+    pub fn take_alerts(&self) -> Vec<BudgetAlert> {
+        std::mem::take(&mut self.lock().pending)
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, BudgetState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn budget() -> ErrorBudget {
+        ErrorBudget::new(ErrorBudgetConfig {
+            max_failures: 3,
+            window_secs: 60,
+            ..ErrorBudgetConfig::default()
+        })
+    }
+
+    #[test]
+    fn test_alert_when_budget_exceeded_once_per_window() {
+        let budget = budget();
+        let now = Instant::now();
+        for i in 0..3 {
+            budget.record_failure_at("platform:twitch", "auth", now + Duration::from_secs(i));
+        }
+        assert!(budget.take_alerts().is_empty());
+
+        budget.record_failure_at(
+            "platform:twitch",
+            "Login authentication failed",
+            now + Duration::from_secs(4),
+        );
+        let alerts = budget.take_alerts();
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].failures, 4);
+        assert!(alerts[0].summary().contains("Login authentication failed"));
+
+        // Sigue fallando: no se repite la alerta hasta que pase la ventana
+        budget.record_failure_at("platform:twitch", "auth", now + Duration::from_secs(5));
+        assert!(budget.take_alerts().is_empty());
+    }
+
+    #[test]
+    fn test_old_failures_leave_the_window() {
+        let budget = budget();
+        let now = Instant::now();
+        for i in 0..3 {
+            budget.record_failure_at("provider:7tv", "timeout", now + Duration::from_secs(i * 40));
+        }
+        assert_eq!(
+            budget.failure_count("provider:7tv", now + Duration::from_secs(80)),
+            2
+        );
+        budget.record_failure_at("provider:7tv", "timeout", now + Duration::from_secs(121));
+        assert!(budget.take_alerts().is_empty());
+    }
+
+    #[test]
+    fn test_disabled_budget_records_nothing() {
+        let budget = ErrorBudget::new(ErrorBudgetConfig {
+            enabled: false,
+            max_failures: 0,
+            ..ErrorBudgetConfig::default()
+        });
+        let now = Instant::now();
+        budget.record_failure_at("platform:kick", "closed", now);
+        assert_eq!(budget.failure_count("platform:kick", now), 0);
+        assert!(budget.take_alerts().is_empty());
+    }
+}
@@ -0,0 +1,231 @@
+//! Resaltado de primeras participaciones y menciones al streamer
+//!
+//! El primer mensaje de cada usuario en un canal (desde que arrancó el
+//! overlay) y los mensajes que nombran al streamer (`@canal` o `canal`) se
+//! marcan como destacados: se dibujan con el color de `first_message_style` o
+//! `mention_style` y duran `extra_duration_secs` más.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::{Mutex, OnceLock};
+
+use serde::{Deserialize, Serialize};
+
+use crate::connection::{ChatMessage, MessageType};
+use crate::theme::levels::UserLevelStyle;
+
+static GLOBAL_HIGHLIGHTER: OnceLock<Highlighter> = OnceLock::new();
+
+/// `highlight`
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct HighlightConfig {
+    /// Desactivado por defecto para no cambiar la apariencia de overlays existentes
+    pub enabled: bool,
+    /// Resalta el primer mensaje de cada usuario en el canal
+    pub first_message: bool,
+    /// Resalta los mensajes que mencionan al streamer
+    pub mentions: bool,
+    pub first_message_style: UserLevelStyle,
+    pub mention_style: UserLevelStyle,
+    /// Segundos que se suman a la duración de la ventana
+    pub extra_duration_secs: u64,
+}
+
+impl Default for HighlightConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            first_message: true,
+            mentions: true,
+            first_message_style: UserLevelStyle {
+                username_color: None,
+                border_color: Some("#00c8ff".to_string()),
+            },
+            mention_style: UserLevelStyle {
+                username_color: None,
+                border_color: Some("#ff9f1c".to_string()),
+            },
+            extra_duration_secs: 5,
+        }
+    }
+}
+
+impl HighlightConfig {
+    /// Estilo de un tipo de resaltado
+    pub fn style_for(&self, kind: HighlightKind) -> &UserLevelStyle {
+        match kind {
+            HighlightKind::FirstMessage => &self.first_message_style,
+            HighlightKind::Mention => &self.mention_style,
+        }
+    }
+}
+
+/// Motivo por el que se resaltó un mensaje
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HighlightKind {
+    FirstMessage,
+    Mention,
+}
+
+impl HighlightKind {
+    /// Clave en `custom_data` con el motivo del resaltado
+    pub const METADATA_KEY: &'static str = "highlight";
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HighlightKind::FirstMessage => "first_message",
+            HighlightKind::Mention => "mention",
+        }
+    }
+
+    /// Resaltado que se guardó en el mensaje
+    pub fn of(message: &ChatMessage) -> Option<Self> {
+        match message
+            .metadata
+            .custom_data
+            .get(Self::METADATA_KEY)?
+            .as_str()?
+        {
+            "first_message" => Some(HighlightKind::FirstMessage),
+            "mention" => Some(HighlightKind::Mention),
+            _ => None,
+        }
+    }
+}
+
+/// `true` si el texto nombra a `name` como palabra suelta, con o sin `@`
+pub fn mentions(content: &str, name: &str) -> bool {
+    let name = name.trim_start_matches('#');
+    !name.is_empty()
+        && content
+            .split(|c: char| !(c.is_alphanumeric() || c == '_'))
+            .any(|word| word.eq_ignore_ascii_case(name))
+}
+
+#[derive(Debug, Default)]
+struct HighlightState {
+    config: HighlightConfig,
+    /// `plataforma\ncanal` -> usuarios que ya escribieron
+    seen: HashMap<String, HashSet<String>>,
+}
+
+/// Detector de resaltados, compartido por todas las conexiones
+#[derive(Debug, Default)]
+pub struct Highlighter {
+    state: Mutex<HighlightState>,
+}
+
+impl Highlighter {
+    pub fn new(config: HighlightConfig) -> Self {
+        Self {
+            state: Mutex::new(HighlightState {
+                config,
+                seen: HashMap::new(),
+            }),
+        }
+    }
+
+    pub fn global() -> &'static Highlighter {
+        GLOBAL_HIGHLIGHTER.get_or_init(|| Self::new(HighlightConfig::default()))
+    }
+
+    /// Conserva los usuarios ya vistos para no volver a resaltarlos al recargar
+    pub fn configure(&self, config: HighlightConfig) {
+        self.lock().config = config;
+    }
+
+    /// Registra al autor y marca el mensaje si hay que resaltarlo
+    pub fn observe(&self, message: &mut ChatMessage) -> Option<HighlightKind> {
+        let mut state = self.lock();
+        if !state.config.enabled
+            || !matches!(
+                message.message_type,
+                MessageType::Normal | MessageType::Action
+            )
+        {
+            return None;
+        }
+
+        let first = state
+            .seen
+            .entry(format!("{}\n{}", message.platform, message.channel))
+            .or_default()
+            .insert(message.username.to_lowercase());
+        let own_channel = message.username.eq_ignore_ascii_case(&message.channel);
+        let kind = if first && !own_channel && state.config.first_message {
+            HighlightKind::FirstMessage
+        } else if !own_channel
+            && state.config.mentions
+            && mentions(&message.content, &message.channel)
+        {
+            HighlightKind::Mention
+        } else {
+            return None;
+        };
+
+        message.metadata.is_highlighted = true;
+        message.metadata.custom_data.insert(
+            HighlightKind::METADATA_KEY.to_string(),
+            kind.as_str().into(),
+        );
+        Some(kind)
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HighlightState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_first_message_then_mentions_are_highlighted() {
+        let highlighter = Highlighter::new(HighlightConfig {
+            enabled: true,
+            ..HighlightConfig::default()
+        });
+
+        let mut first = ChatMessage::test("viewer", "hola").on("twitch", "Streamer");
+        assert_eq!(
+            highlighter.observe(&mut first),
+            Some(HighlightKind::FirstMessage)
+        );
+        assert!(first.metadata.is_highlighted);
+        assert_eq!(HighlightKind::of(&first), Some(HighlightKind::FirstMessage));
+
+        assert_eq!(
+            highlighter
+                .observe(&mut ChatMessage::test("Viewer", "otra vez").on("twitch", "Streamer")),
+            None
+        );
+        let mut mention = ChatMessage::test("viewer", "gg @streamer!").on("twitch", "Streamer");
+        assert_eq!(
+            highlighter.observe(&mut mention),
+            Some(HighlightKind::Mention)
+        );
+        assert_eq!(HighlightKind::of(&mention), Some(HighlightKind::Mention));
+        assert_eq!(
+            highlighter
+                .observe(&mut ChatMessage::test("viewer", "streamers").on("twitch", "Streamer")),
+            None
+        );
+
+        // El propio streamer no se resalta
+        assert_eq!(
+            highlighter.observe(
+                &mut ChatMessage::test("streamer", "hi streamer").on("twitch", "Streamer")
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn test_disabled_highlighter_leaves_messages_untouched() {
+        let highlighter = Highlighter::new(HighlightConfig::default());
+        let mut message = ChatMessage::test("viewer", "@streamer").on("twitch", "Streamer");
+        assert_eq!(highlighter.observe(&mut message), None);
+        assert!(HighlightKind::of(&message).is_none());
+    }
+}
@@ -0,0 +1,382 @@
+//! Historial en memoria de los mensajes de la sesión
+//!
+//! Los últimos `history.size` mensajes que superan el pipeline se guardan en un
+//! anillo para buscarlos desde `POST /messages/search` (texto o expresión
+//! regular, usuario, tipo, plataforma) y volver a mostrarlos con
+//! `POST /messages/replay`, p. ej. para recuperar una pregunta que se pasó por
+//! alto hace diez minutos. Con `replay_on_startup` los últimos mensajes se
+//! guardan en `history.file` y se vuelven a mostrar al arrancar, para que un
+//! reinicio no deje la pantalla vacía; el resto se pierde al reiniciar.
+
+use std::collections::VecDeque;
+use std::path::Path;
+use std::sync::{Mutex, OnceLock};
+
+use regex::{Regex, RegexBuilder};
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+use crate::config::HistoryConfig;
+use crate::connection::ChatMessage;
+use crate::mapping::MappedMessageType;
+
+static GLOBAL_HISTORY: OnceLock<MessageHistory> = OnceLock::new();
+
+/// Resultados que devuelve una búsqueda sin `limit`
+const DEFAULT_SEARCH_LIMIT: usize = 20;
+
+/// Cuerpo de `POST /messages/search`; los criterios indicados se combinan
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct HistoryQuery {
+    /// Texto a buscar en el contenido, sin distinguir mayúsculas
+    pub text: Option<String>,
+    /// Interpreta `text` como expresión regular
+    pub regex: bool,
+    /// Nombre de usuario o nombre visible exacto, sin distinguir mayúsculas
+    pub user: Option<String>,
+    /// Tipo mapeado (`normal`, `subscription`, `cheer`...)
+    #[serde(rename = "type")]
+    pub message_type: Option<String>,
+    pub platform: Option<String>,
+    /// Máximo de resultados (los más recientes); 0 = 20
+    pub limit: usize,
+}
+
+/// Búsqueda no válida (expresión regular o tipo desconocido)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HistoryQueryError(pub String);
+
+impl std::fmt::Display for HistoryQueryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for HistoryQueryError {}
+
+enum TextMatcher {
+    Substring(String),
+    Regex(Regex),
+}
+
+impl TextMatcher {
+    fn matches(&self, content: &str) -> bool {
+        match self {
+            TextMatcher::Substring(text) => content.to_lowercase().contains(text),
+            TextMatcher::Regex(regex) => regex.is_match(content),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct HistoryState {
+    config: HistoryConfig,
+    messages: VecDeque<ChatMessage>,
+    /// Mensajes registrados desde el último [`MessageHistory::save`]
+    unsaved: usize,
+}
+
+/// Anillo con los mensajes recientes de la sesión
+#[derive(Debug, Default)]
+pub struct MessageHistory {
+    state: Mutex<HistoryState>,
+}
+
+impl MessageHistory {
+    pub fn new(config: HistoryConfig) -> Self {
+        Self {
+            state: Mutex::new(HistoryState {
+                config,
+                messages: VecDeque::new(),
+                unsaved: 0,
+            }),
+        }
+    }
+
+    pub fn global() -> &'static MessageHistory {
+        GLOBAL_HISTORY.get_or_init(|| Self::new(HistoryConfig::default()))
+    }
+
+    pub fn configure(&self, config: HistoryConfig) {
+        let mut state = self.lock();
+        let limit = if config.enabled { config.size } else { 0 };
+        while state.messages.len() > limit {
+            state.messages.pop_front();
+        }
+        state.config = config;
+    }
+
+    pub fn record(&self, message: &ChatMessage) {
+        let mut state = self.lock();
+        if !state.config.enabled || state.config.size == 0 {
+            return;
+        }
+        if state.messages.len() >= state.config.size {
+            state.messages.pop_front();
+        }
+        state.messages.push_back(message.clone());
+        state.unsaved += 1;
+    }
+
+    /// Últimos `limit` mensajes, de todos los canales o de uno, del más
+    /// antiguo al más reciente
+    pub fn recent(&self, channel: Option<&str>, limit: usize) -> Vec<ChatMessage> {
+        let state = self.lock();
+        let mut recent: Vec<ChatMessage> = state
+            .messages
+            .iter()
+            .rev()
+            .filter(|m| channel.is_none_or(|channel| m.channel.eq_ignore_ascii_case(channel)))
+            .take(limit)
+            .cloned()
+            .collect();
+        recent.reverse();
+        recent
+    }
+
+    /// Escribe en `history.file` los mensajes de `replay_on_startup` si hubo
+    /// alguno nuevo desde la última vez; devuelve cuántos se guardaron
+    pub fn save(&self) -> std::io::Result<usize> {
+        let (file, messages) = {
+            let mut state = self.lock();
+            let limit = state.config.replay_on_startup;
+            if limit == 0 || state.unsaved == 0 {
+                return Ok(0);
+            }
+            state.unsaved = 0;
+            let skip = state.messages.len().saturating_sub(limit);
+            let messages: Vec<ChatMessage> = state.messages.iter().skip(skip).cloned().collect();
+            (state.config.file.clone(), messages)
+        };
+        std::fs::write(&file, serde_json::to_vec(&messages)?)?;
+        Ok(messages.len())
+    }
+
+    /// Lee los mensajes guardados por [`MessageHistory::save`] y los vuelve a
+    /// añadir al historial; sin `replay_on_startup` o sin fichero no devuelve nada
+    pub fn restore(&self) -> Vec<ChatMessage> {
+        let (file, limit) = {
+            let state = self.lock();
+            (state.config.file.clone(), state.config.replay_on_startup)
+        };
+        if limit == 0 || !Path::new(&file).exists() {
+            return Vec::new();
+        }
+        let mut messages: Vec<ChatMessage> = match std::fs::read(&file)
+            .map_err(|e| e.to_string())
+            .and_then(|bytes| serde_json::from_slice(&bytes).map_err(|e| e.to_string()))
+        {
+            Ok(messages) => messages,
+            Err(e) => {
+                eprintln!("[HISTORY] ⚠️ Cannot read {}: {}", file, e);
+                return Vec::new();
+            }
+        };
+        let skip = messages.len().saturating_sub(limit);
+        messages.drain(..skip);
+        for message in &messages {
+            self.record(message);
+        }
+        // Lo restaurado ya está en el fichero
+        self.lock().unsaved = 0;
+        messages
+    }
+
+    /// Mensaje guardado con ese id
+    pub fn get(&self, id: &str) -> Option<ChatMessage> {
+        self.lock()
+            .messages
+            .iter()
+            .rev()
+            .find(|m| m.id == id)
+            .cloned()
+    }
+
+    /// Mensajes que cumplen `query`, del más antiguo al más reciente
+    pub fn search(&self, query: &HistoryQuery) -> Result<Vec<ChatMessage>, HistoryQueryError> {
+        let text = match query.text.as_deref().filter(|text| !text.is_empty()) {
+            None => None,
+            Some(pattern) if query.regex => Some(TextMatcher::Regex(
+                RegexBuilder::new(pattern)
+                    .case_insensitive(true)
+                    .build()
+                    .map_err(|e| HistoryQueryError(format!("Invalid regex: {}", e)))?,
+            )),
+            Some(text) => Some(TextMatcher::Substring(text.to_lowercase())),
+        };
+        let message_type = query
+            .message_type
+            .as_deref()
+            .map(|kind| {
+                serde_json::from_value::<MappedMessageType>(kind.to_lowercase().into())
+                    .map_err(|_| HistoryQueryError(format!("Unknown message type '{}'", kind)))
+            })
+            .transpose()?;
+        let user = query.user.as_deref().map(str::to_lowercase);
+        let platform = query.platform.as_deref().map(str::to_lowercase);
+        let limit = match query.limit {
+            0 => DEFAULT_SEARCH_LIMIT,
+            limit => limit,
+        };
+
+        let state = self.lock();
+        let mut found: Vec<ChatMessage> = state
+            .messages
+            .iter()
+            .rev()
+            .filter(|m| text.as_ref().is_none_or(|text| text.matches(&m.content)))
+            .filter(|m| {
+                user.as_ref().is_none_or(|user| {
+                    m.username.to_lowercase() == *user
+                        || m.display_name
+                            .as_ref()
+                            .is_some_and(|name| name.to_lowercase() == *user)
+                })
+            })
+            .filter(|m| {
+                platform
+                    .as_ref()
+                    .is_none_or(|platform| m.platform.to_lowercase() == *platform)
+            })
+            .filter(|m| {
+                message_type
+                    .as_ref()
+                    .is_none_or(|kind| MappedMessageType::of(m) == *kind)
+            })
+            .take(limit)
+            .cloned()
+            .collect();
+        found.reverse();
+        Ok(found)
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HistoryState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::connection::MessageType;
+
+    fn history() -> MessageHistory {
+        let history = MessageHistory::new(HistoryConfig {
+            enabled: true,
+            size: 3,
+            ..HistoryConfig::default()
+        });
+        history.record(&ChatMessage::test("alice", "hola a todos").with_id("msg-1"));
+        history.record(&ChatMessage::test("bob", "¿Cuándo es el torneo?").with_id("msg-2"));
+        history.record(
+            &ChatMessage::test("carol", "gracias por la sub")
+                .with_id("msg-3")
+                .with_type(MessageType::Subscription),
+        );
+        history.record(&ChatMessage::test("Bob", "otra pregunta: ¿hay VOD?").with_id("msg-4"));
+        history
+    }
+
+    #[test]
+    fn test_ring_keeps_the_most_recent_messages() {
+        let history = history();
+        let all = HistoryQuery::default();
+        assert_eq!(history.search(&all).unwrap().len(), 3);
+        assert!(history.get("msg-1").is_none());
+        assert_eq!(history.get("msg-4").unwrap().username, "Bob");
+
+        history.configure(HistoryConfig {
+            enabled: true,
+            size: 1,
+            ..HistoryConfig::default()
+        });
+        assert_eq!(history.search(&all).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_recent_messages_survive_a_restart() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let config = HistoryConfig {
+            replay_on_startup: 2,
+            file: dir.path().join("history.json").display().to_string(),
+            ..HistoryConfig::default()
+        };
+
+        let history = MessageHistory::new(config.clone());
+        assert!(history.restore().is_empty());
+        for id in 1..=3 {
+            history.record(&ChatMessage::test("alice", "hola").with_id(format!("msg-{}", id)));
+        }
+        let other = ChatMessage::test("bob", "en otro canal")
+            .with_id("msg-4")
+            .on("twitch", "other");
+        history.record(&other);
+
+        let ids = |messages: Vec<ChatMessage>| -> Vec<String> {
+            messages.into_iter().map(|m| m.id).collect()
+        };
+        assert_eq!(ids(history.recent(Some("CHAN"), 2)), vec!["msg-2", "msg-3"]);
+        assert_eq!(ids(history.recent(None, 1)), vec!["msg-4"]);
+
+        assert_eq!(history.save().unwrap(), 2);
+        // Sin mensajes nuevos no se vuelve a escribir
+        assert_eq!(history.save().unwrap(), 0);
+
+        let restarted = MessageHistory::new(config);
+        assert_eq!(ids(restarted.restore()), vec!["msg-3", "msg-4"]);
+        assert_eq!(restarted.get("msg-4").unwrap().username, "bob");
+        assert_eq!(restarted.save().unwrap(), 0);
+    }
+
+    #[test]
+    fn test_search_combines_text_user_and_type() {
+        let history = history();
+        let ids = |query: &HistoryQuery| -> Vec<String> {
+            history
+                .search(query)
+                .unwrap()
+                .into_iter()
+                .map(|m| m.id)
+                .collect()
+        };
+
+        let by_user = HistoryQuery {
+            user: Some("bob".to_string()),
+            ..HistoryQuery::default()
+        };
+        assert_eq!(ids(&by_user), vec!["msg-2", "msg-4"]);
+
+        let questions = HistoryQuery {
+            text: Some(r"\?$".to_string()),
+            regex: true,
+            ..HistoryQuery::default()
+        };
+        assert_eq!(ids(&questions), vec!["msg-2", "msg-4"]);
+
+        let latest = HistoryQuery {
+            limit: 1,
+            ..questions.clone()
+        };
+        assert_eq!(ids(&latest), vec!["msg-4"]);
+
+        let subs = HistoryQuery {
+            text: Some("SUB".to_string()),
+            message_type: Some("subscription".to_string()),
+            ..HistoryQuery::default()
+        };
+        assert_eq!(ids(&subs), vec!["msg-3"]);
+
+        let invalid = HistoryQuery {
+            text: Some("(".to_string()),
+            regex: true,
+            ..HistoryQuery::default()
+        };
+        assert!(history.search(&invalid).is_err());
+        let unknown_type = HistoryQuery {
+            message_type: Some("nope".to_string()),
+            ..HistoryQuery::default()
+        };
+        assert!(history.search(&unknown_type).is_err());
+    }
+}
@@ -0,0 +1,272 @@
+//! Agrupación de ráfagas de suscripciones y cheers
+//!
+//! Cuando en un canal llegan `min_events` suscripciones o cheers con menos de
+//! `window_secs` entre uno y otro, las ventanas individuales se retiran y se
+//! muestra una sola ventana resumen ("Hype! 12 subs, 3500 bits") que se
+//! sustituye por otra actualizada con cada evento nuevo de la ráfaga.
+
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::connection::{ChatMessage, MessageType};
+
+static GLOBAL_AGGREGATOR: OnceLock<HypeAggregator> = OnceLock::new();
+
+/// Clave en `custom_data` con los bits de un cheer
+pub const BITS_KEY: &str = "bits";
+/// Clave en `custom_data` del anuncio de una tanda de subs regaladas, que
+/// llegan después una a una
+pub const MASS_GIFT_KEY: &str = "mass_gift_count";
+
+/// `hype`
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct HypeConfig {
+    /// Desactivado por defecto: cada evento abre su ventana
+    pub enabled: bool,
+    /// Segundos desde el último evento durante los que sigue la ráfaga
+    pub window_secs: u64,
+    /// Eventos a partir de los que se muestra el resumen
+    pub min_events: u32,
+}
+
+impl Default for HypeConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            window_secs: 15,
+            min_events: 3,
+        }
+    }
+}
+
+/// Qué hacer con un mensaje
+#[derive(Debug, Clone)]
+pub enum HypeVerdict {
+    /// Se muestra tal cual
+    Display,
+    /// Se muestra `summary` en su lugar y se cierran las ventanas de `retract`
+    Summary {
+        retract: Vec<String>,
+        summary: Box<ChatMessage>,
+    },
+}
+
+#[derive(Debug, Default)]
+struct Burst {
+    events: u32,
+    subs: u64,
+    bits: u64,
+    /// Ventanas abiertas de la ráfaga: eventos sueltos y el último resumen
+    shown: Vec<String>,
+    last_seen: Option<Instant>,
+}
+
+#[derive(Debug, Default)]
+struct HypeState {
+    config: HypeConfig,
+    /// `plataforma\ncanal` -> ráfaga en curso
+    bursts: HashMap<String, Burst>,
+}
+
+/// Subs y bits que aporta un evento; `None` si no forma parte de ráfagas
+fn contribution(message: &ChatMessage) -> Option<(u64, u64)> {
+    let data = &message.metadata.custom_data;
+    match message.message_type {
+        // El anuncio de una tanda regalada no suma: cada regalo llega aparte
+        MessageType::Subscription if data.contains_key(MASS_GIFT_KEY) => Some((0, 0)),
+        MessageType::Subscription => Some((1, 0)),
+        MessageType::Cheer => Some((0, data.get(BITS_KEY).and_then(|v| v.as_u64()).unwrap_or(0))),
+        _ => None,
+    }
+}
+
+/// Texto del resumen; omite lo que no ha llegado
+pub fn summary_text(subs: u64, bits: u64) -> String {
+    let mut parts = Vec::new();
+    if subs > 0 {
+        parts.push(format!(
+            "{} {}",
+            subs,
+            if subs == 1 { "sub" } else { "subs" }
+        ));
+    }
+    if bits > 0 {
+        parts.push(format!("{} bits", bits));
+    }
+    if parts.is_empty() {
+        "Hype!".to_string()
+    } else {
+        format!("Hype! {}", parts.join(", "))
+    }
+}
+
+/// Mensaje resumen de la ráfaga, en el canal de `trigger`
+fn summary_message(trigger: &ChatMessage, burst: &Burst) -> ChatMessage {
+    let mut summary = trigger.clone();
+    summary.id = format!("hype-{}", trigger.id);
+    summary.username = trigger.channel.clone();
+    summary.display_name = None;
+    summary.content = summary_text(burst.subs, burst.bits);
+    summary.emotes.clear();
+    summary.badges.clear();
+    summary.user_color = None;
+    summary.message_type = MessageType::HypeTrain;
+    summary.metadata.is_highlighted = true;
+    summary.metadata.reply_to = None;
+    summary.metadata.custom_data = HashMap::from([
+        ("hype_events".to_string(), burst.events.into()),
+        ("hype_subs".to_string(), burst.subs.into()),
+        ("hype_bits".to_string(), burst.bits.into()),
+    ]);
+    summary
+}
+
+/// Detector de ráfagas, compartido por todas las conexiones
+#[derive(Debug, Default)]
+pub struct HypeAggregator {
+    state: Mutex<HypeState>,
+}
+
+impl HypeAggregator {
+    pub fn new(config: HypeConfig) -> Self {
+        Self {
+            state: Mutex::new(HypeState {
+                config,
+                bursts: HashMap::new(),
+            }),
+        }
+    }
+
+    pub fn global() -> &'static HypeAggregator {
+        GLOBAL_AGGREGATOR.get_or_init(|| Self::new(HypeConfig::default()))
+    }
+
+    pub fn configure(&self, config: HypeConfig) {
+        let mut state = self.lock();
+        state.config = config;
+        state.bursts.clear();
+    }
+
+    /// Suma el evento a la ráfaga de su canal y decide si se resume
+    pub fn observe(&self, message: &ChatMessage, now: Instant) -> HypeVerdict {
+        let mut state = self.lock();
+        if !state.config.enabled {
+            return HypeVerdict::Display;
+        }
+        let Some((subs, bits)) = contribution(message) else {
+            return HypeVerdict::Display;
+        };
+
+        let window = Duration::from_secs(state.config.window_secs);
+        state.bursts.retain(|_, burst| {
+            burst
+                .last_seen
+                .is_some_and(|seen| now.saturating_duration_since(seen) < window)
+        });
+
+        let min_events = state.config.min_events.max(1);
+        let burst = state
+            .bursts
+            .entry(format!("{}\n{}", message.platform, message.channel))
+            .or_default();
+        burst.events += 1;
+        burst.subs += subs;
+        burst.bits += bits;
+        burst.last_seen = Some(now);
+
+        if burst.events < min_events {
+            burst.shown.push(message.id.clone());
+            return HypeVerdict::Display;
+        }
+
+        let summary = summary_message(message, burst);
+        let retract = std::mem::replace(&mut burst.shown, vec![summary.id.clone()]);
+        HypeVerdict::Summary {
+            retract,
+            summary: Box::new(summary),
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HypeState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_burst_of_subs_and_cheers_becomes_one_summary() {
+        let hype = HypeAggregator::new(HypeConfig {
+            enabled: true,
+            window_secs: 10,
+            min_events: 3,
+        });
+        let start = Instant::now();
+        let sub = |id: &str| {
+            ChatMessage::test(id, "event")
+                .with_id(id)
+                .with_type(MessageType::Subscription)
+                .highlighted(true)
+        };
+        let cheer = |id: &str, bits: u64| {
+            ChatMessage::test(id, "event")
+                .with_id(id)
+                .with_type(MessageType::Cheer)
+                .highlighted(true)
+                .with_data(BITS_KEY, bits)
+        };
+
+        assert!(matches!(
+            hype.observe(&sub("s1"), start),
+            HypeVerdict::Display
+        ));
+        // Otro canal lleva su propia cuenta
+        assert!(matches!(
+            hype.observe(&sub("o1").on("twitch", "other"), start),
+            HypeVerdict::Display
+        ));
+        assert!(matches!(
+            hype.observe(&cheer("c1", 500), start),
+            HypeVerdict::Display
+        ));
+
+        let HypeVerdict::Summary { retract, summary } = hype.observe(&sub("s2"), start) else {
+            panic!("third event should be summarized");
+        };
+        assert_eq!(retract, vec!["s1".to_string(), "c1".to_string()]);
+        assert_eq!(summary.content, "Hype! 2 subs, 500 bits");
+        assert!(matches!(summary.message_type, MessageType::HypeTrain));
+
+        // Cada evento nuevo sustituye el resumen anterior
+        let later = start + Duration::from_secs(8);
+        let HypeVerdict::Summary { retract: next, .. } = hype.observe(&cheer("c2", 100), later)
+        else {
+            panic!("burst should continue");
+        };
+        assert_eq!(next, vec![summary.id]);
+
+        // Pasado el plazo empieza una ráfaga nueva; el chat normal no cuenta
+        let quiet = later + Duration::from_secs(10);
+        assert!(matches!(
+            hype.observe(&sub("s3"), quiet),
+            HypeVerdict::Display
+        ));
+        assert!(matches!(
+            hype.observe(&ChatMessage::test("n1", "hola"), quiet),
+            HypeVerdict::Display
+        ));
+    }
+
+    #[test]
+    fn test_summary_text_skips_missing_parts() {
+        assert_eq!(summary_text(1, 0), "Hype! 1 sub");
+        assert_eq!(summary_text(0, 1200), "Hype! 1200 bits");
+        assert_eq!(summary_text(0, 0), "Hype!");
+    }
+}
@@ -0,0 +1,280 @@
+//! Detección de inactividad del equipo (streamer AFK)
+//!
+//! Cuando no hay entrada de teclado/ratón durante `idle_minutes` se pausa el
+//! renderizado; los mensajes se siguen registrando y al volver la actividad se
+//! muestra un resumen con los mensajes perdidos.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use crate::config::{IdleConfig, SystemUser};
+use crate::connection::ChatMessage;
+use crate::system_users;
+
+/// Cambio de estado de inactividad
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IdleEvent {
+    /// Se superó el umbral de inactividad: pausar el renderizado
+    Paused,
+    /// Volvió la actividad; `missed` mensajes no se mostraron
+    Resumed { missed: usize },
+}
+
+/// Reintento de la conexión con el servidor X tras un fallo
+#[cfg(target_os = "linux")]
+const X11_RETRY: Duration = Duration::from_secs(60);
+
+/// Consulta el tiempo desde la última entrada del usuario, si la plataforma
+/// lo permite
+#[derive(Debug, Default)]
+pub struct IdleClock {
+    /// Conexión X11 abierta en la primera consulta y reutilizada en las siguientes
+    #[cfg(target_os = "linux")]
+    x11: Option<X11Idle>,
+    /// Último intento fallido de conectar con el servidor X
+    #[cfg(target_os = "linux")]
+    failed_at: Option<std::time::Instant>,
+}
+
+impl IdleClock {
+    /// X11: extensión MIT-SCREEN-SAVER
+    #[cfg(target_os = "linux")]
+    pub fn idle_time(&mut self) -> Option<Duration> {
+        if self.x11.is_none() {
+            if self.failed_at.is_some_and(|at| at.elapsed() < X11_RETRY) {
+                return None;
+            }
+            self.x11 = X11Idle::connect();
+            self.failed_at = self.x11.is_none().then(std::time::Instant::now);
+        }
+
+        let idle = self.x11.as_ref()?.query();
+        if idle.is_none() {
+            // Conexión rota (servidor X reiniciado): se reabre en la siguiente consulta
+            self.x11 = None;
+        }
+        idle
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    pub fn idle_time(&mut self) -> Option<Duration> {
+        platform_idle_time()
+    }
+}
+
+#[cfg(target_os = "linux")]
+#[derive(Debug)]
+struct X11Idle {
+    conn: x11rb::rust_connection::RustConnection,
+    root: x11rb::protocol::xproto::Window,
+}
+
+#[cfg(target_os = "linux")]
+impl X11Idle {
+    fn connect() -> Option<Self> {
+        use x11rb::connection::Connection;
+
+        let (conn, screen_num) = x11rb::connect(None).ok()?;
+        let root = conn.setup().roots.get(screen_num)?.root;
+        Some(Self { conn, root })
+    }
+
+    fn query(&self) -> Option<Duration> {
+        use x11rb::protocol::screensaver::ConnectionExt as _;
+
+        let info = self
+            .conn
+            .screensaver_query_info(self.root)
+            .ok()?
+            .reply()
+            .ok()?;
+        Some(Duration::from_millis(info.ms_since_user_input as u64))
+    }
+}
+
+/// Windows: GetLastInputInfo
+#[cfg(windows)]
+fn platform_idle_time() -> Option<Duration> {
+    use winapi::um::sysinfoapi::GetTickCount;
+    use winapi::um::winuser::{GetLastInputInfo, LASTINPUTINFO};
+
+    let mut info = LASTINPUTINFO {
+        cbSize: std::mem::size_of::<LASTINPUTINFO>() as u32,
+        dwTime: 0,
+    };
+    unsafe {
+        if GetLastInputInfo(&mut info) == 0 {
+            return None;
+        }
+        // GetTickCount da la vuelta cada ~49 días; wrapping_sub lo compensa
+        let idle_ms = GetTickCount().wrapping_sub(info.dwTime);
+        Some(Duration::from_millis(idle_ms as u64))
+    }
+}
+
+#[cfg(not(any(target_os = "linux", windows)))]
+fn platform_idle_time() -> Option<Duration> {
+    None
+}
+
+/// Decide cuándo pausar y reanudar el renderizado según el tiempo de inactividad
+#[derive(Debug)]
+pub struct IdleMonitor {
+    config: IdleConfig,
+    clock: IdleClock,
+    paused: bool,
+    missed: usize,
+}
+
+impl IdleMonitor {
+    pub fn new(config: IdleConfig) -> Self {
+        Self {
+            config,
+            clock: IdleClock::default(),
+            paused: false,
+            missed: 0,
+        }
+    }
+
+    /// Aplica la configuración recargada conservando la conexión con el
+    /// sistema; si se desactiva en plena pausa, la siguiente consulta reanuda
+    pub fn configure(&mut self, config: IdleConfig) {
+        self.config = config;
+    }
+
+    /// Consulta el tiempo de inactividad del sistema y actualiza el estado
+    pub fn poll(&mut self) -> Option<IdleEvent> {
+        if !self.config.enabled {
+            return self.update(None);
+        }
+        let idle = self.clock.idle_time();
+        self.update(idle)
+    }
+
+    /// Aplica una lectura de inactividad (`None` si no se pudo medir)
+    pub fn update(&mut self, idle: Option<Duration>) -> Option<IdleEvent> {
+        if !self.config.enabled && !self.paused {
+            return None;
+        }
+
+        let threshold = Duration::from_secs(self.config.idle_minutes * 60);
+        // Sin lectura se asume actividad para no ocultar mensajes
+        // indefinidamente; desactivado, solo queda reanudar
+        let is_idle = self.config.enabled && idle.is_some_and(|idle| idle >= threshold);
+
+        match (self.paused, is_idle) {
+            (false, true) => {
+                self.paused = true;
+                self.missed = 0;
+                Some(IdleEvent::Paused)
+            }
+            (true, false) => {
+                self.paused = false;
+                Some(IdleEvent::Resumed {
+                    missed: std::mem::take(&mut self.missed),
+                })
+            }
+            _ => None,
+        }
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// Cuenta un mensaje recibido mientras el renderizado está en pausa
+    pub fn record_missed(&mut self) {
+        self.missed += 1;
+    }
+
+    /// Si al reanudar se debe mostrar la ventana de resumen
+    pub fn wants_summary(&self, missed: usize) -> bool {
+        self.config.show_summary && missed > 0
+    }
+}
+
+/// Texto de la ventana de resumen al volver
+pub fn missed_summary(missed: usize) -> String {
+    if missed == 1 {
+        "You missed 1 message while away".to_string()
+    } else {
+        format!("You missed {} messages while away", missed)
+    }
+}
+
+/// Mensaje de sistema para la ventana de resumen
+pub fn summary_message(missed: usize, user: &SystemUser) -> ChatMessage {
+    let mut custom_data = HashMap::new();
+    custom_data.insert("missed_messages".to_string(), missed.into());
+    system_users::system_message(user, "announcements", missed_summary(missed), custom_data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn monitor() -> IdleMonitor {
+        IdleMonitor::new(IdleConfig {
+            enabled: true,
+            idle_minutes: 5,
+            show_summary: true,
+        })
+    }
+
+    #[test]
+    fn test_pauses_after_threshold_and_reports_missed_on_resume() {
+        let mut monitor = monitor();
+        assert_eq!(monitor.update(Some(Duration::from_secs(60))), None);
+        assert_eq!(
+            monitor.update(Some(Duration::from_secs(5 * 60))),
+            Some(IdleEvent::Paused)
+        );
+        assert!(monitor.is_paused());
+
+        monitor.record_missed();
+        monitor.record_missed();
+        assert_eq!(monitor.update(Some(Duration::from_secs(6 * 60))), None);
+        assert_eq!(
+            monitor.update(Some(Duration::from_secs(1))),
+            Some(IdleEvent::Resumed { missed: 2 })
+        );
+        assert!(monitor.wants_summary(2));
+        assert_eq!(missed_summary(2), "You missed 2 messages while away");
+    }
+
+    #[test]
+    fn test_unknown_idle_time_counts_as_active() {
+        let mut monitor = monitor();
+        monitor.update(Some(Duration::from_secs(3600)));
+        assert_eq!(monitor.update(None), Some(IdleEvent::Resumed { missed: 0 }));
+        assert!(!monitor.wants_summary(0));
+    }
+
+    #[test]
+    fn test_disabled_monitor_never_pauses() {
+        let mut monitor = IdleMonitor::new(IdleConfig::default());
+        assert_eq!(monitor.update(Some(Duration::from_secs(3600))), None);
+        assert!(!monitor.is_paused());
+    }
+
+    #[test]
+    fn test_reloaded_config_changes_threshold_and_can_resume() {
+        let mut monitor = monitor();
+        assert_eq!(monitor.update(Some(Duration::from_secs(3 * 60))), None);
+
+        monitor.configure(IdleConfig {
+            idle_minutes: 2,
+            ..monitor.config.clone()
+        });
+        assert_eq!(
+            monitor.update(Some(Duration::from_secs(3 * 60))),
+            Some(IdleEvent::Paused)
+        );
+
+        // Desactivado en plena pausa: se reanuda sin esperar actividad
+        monitor.record_missed();
+        monitor.configure(IdleConfig::default());
+        assert_eq!(monitor.poll(), Some(IdleEvent::Resumed { missed: 1 }));
+        assert_eq!(monitor.poll(), None);
+    }
+}
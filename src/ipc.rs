@@ -0,0 +1,431 @@
+//! Órdenes locales por socket unix (Linux/macOS) o named pipe (Windows)
+//!
+//! Programas externos (contadores de donaciones, bots) escriben una orden JSON
+//! por línea y reciben otra línea con el resultado:
+//!
+//! ```text
+//! {"cmd":"show","user":"alerts","text":"Donation $5"}
+//! {"ok":true}
+//! ```
+//!
+//! Las ventanas de `show` son mensajes de sistema con el rol `ipc`, firmados
+//! por `user` con el estilo de `system_users.announcements`.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
+
+use crate::config::SystemUser;
+use crate::connection::ChatMessage;
+use crate::system_users;
+
+/// Rol de los mensajes de sistema que llegan por IPC
+pub const IPC_ROLE: &str = "ipc";
+
+/// `ipc`
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Default)]
+#[serde(default)]
+pub struct IpcConfig {
+    /// Desactivado por defecto
+    pub enabled: bool,
+    /// Ruta del socket o nombre del pipe; por defecto [`default_endpoint`]
+    pub path: Option<String>,
+}
+
+impl IpcConfig {
+    pub fn endpoint(&self) -> String {
+        self.path.clone().unwrap_or_else(default_endpoint)
+    }
+}
+
+/// `$XDG_RUNTIME_DIR/overlay-native.sock` (o en el directorio temporal)
+#[cfg(unix)]
+pub fn default_endpoint() -> String {
+    std::env::var_os("XDG_RUNTIME_DIR")
+        .map(std::path::PathBuf::from)
+        .unwrap_or_else(std::env::temp_dir)
+        .join("overlay-native.sock")
+        .to_string_lossy()
+        .into_owned()
+}
+
+#[cfg(windows)]
+pub fn default_endpoint() -> String {
+    r"\\.\pipe\overlay-native".to_string()
+}
+
+/// Orden recibida por IPC
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+#[serde(tag = "cmd", rename_all = "snake_case")]
+pub enum IpcCommand {
+    /// Muestra una ventana con `text`
+    Show {
+        /// Nombre a mostrar; por defecto el del usuario de anuncios
+        user: Option<String>,
+        text: String,
+        /// Color del nombre (`#rrggbb`)
+        color: Option<String>,
+    },
+    /// Pausa el renderizado, como `POST /rendering/pause`
+    Pause,
+    Resume,
+    /// Activa un tema con nombre; sin `name` vuelve al de la configuración
+    Theme { name: Option<String> },
+    /// Activa el siguiente tema, para asignarlo a un atajo de teclado
+    NextTheme,
+}
+
+impl IpcCommand {
+    /// Mensaje de sistema de una orden `show`
+    pub fn to_message(&self, base: &SystemUser) -> Option<ChatMessage> {
+        let IpcCommand::Show { user, text, color } = self else {
+            return None;
+        };
+        let user = SystemUser {
+            name: user.clone().unwrap_or_else(|| base.name.clone()),
+            color: color.clone().or_else(|| base.color.clone()),
+            ..base.clone()
+        };
+        Some(system_users::system_message(
+            &user,
+            IPC_ROLE,
+            text.clone(),
+            HashMap::new(),
+        ))
+    }
+}
+
+/// Respuesta a cada línea
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct IpcReply {
+    pub ok: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl IpcReply {
+    fn from_result(result: Result<(), String>) -> Self {
+        Self {
+            ok: result.is_ok(),
+            error: result.err(),
+        }
+    }
+}
+
+/// Operaciones que la aplicación expone por IPC
+#[async_trait::async_trait]
+pub trait IpcHandler: Send + Sync {
+    /// Muestra un mensaje en el overlay
+    async fn show_message(&self, message: ChatMessage) -> Result<(), String>;
+    async fn set_rendering_paused(&self, paused: bool) -> Result<(), String>;
+    async fn switch_theme(&self, name: Option<String>) -> Result<(), String>;
+    async fn next_theme(&self) -> Result<(), String>;
+}
+
+/// Ejecuta una línea del protocolo
+pub async fn handle_line(line: &str, handler: &dyn IpcHandler, user: &SystemUser) -> IpcReply {
+    let command = match serde_json::from_str::<IpcCommand>(line) {
+        Ok(command) => command,
+        Err(e) => return IpcReply::from_result(Err(format!("Invalid command: {}", e))),
+    };
+    let result = match &command {
+        IpcCommand::Show { text, .. } if text.trim().is_empty() => {
+            Err("'text' must not be empty".to_string())
+        }
+        IpcCommand::Show { .. } => match command.to_message(user) {
+            Some(message) => handler.show_message(message).await,
+            None => Ok(()),
+        },
+        IpcCommand::Pause => handler.set_rendering_paused(true).await,
+        IpcCommand::Resume => handler.set_rendering_paused(false).await,
+        IpcCommand::Theme { name } => handler.switch_theme(name.clone()).await,
+        IpcCommand::NextTheme => handler.next_theme().await,
+    };
+    IpcReply::from_result(result)
+}
+
+/// Escucha en marcha; [`IpcServer::shutdown`] la detiene al salir
+#[derive(Debug)]
+pub struct IpcServer {
+    task: tokio::task::JoinHandle<()>,
+    endpoint: String,
+}
+
+impl IpcServer {
+    pub fn endpoint(&self) -> &str {
+        &self.endpoint
+    }
+
+    /// Deja de aceptar clientes y borra el socket unix para que no quede
+    /// huérfano; los named pipes desaparecen solos
+    pub fn shutdown(self) {
+        self.task.abort();
+        #[cfg(unix)]
+        if let Err(e) = std::fs::remove_file(&self.endpoint) {
+            if e.kind() != std::io::ErrorKind::NotFound {
+                eprintln!("⚠️ Failed to remove IPC socket {}: {}", self.endpoint, e);
+            }
+        }
+    }
+}
+
+/// Atiende a un cliente hasta que cierra la conexión
+async fn handle_client<S>(stream: S, handler: Arc<dyn IpcHandler>, user: SystemUser)
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let (read, mut write) = tokio::io::split(stream);
+    let mut lines = BufReader::new(read).lines();
+    loop {
+        let line = match lines.next_line().await {
+            Ok(Some(line)) => line,
+            Ok(None) => break,
+            Err(e) => {
+                eprintln!("⚠️ IPC read failed: {}", e);
+                break;
+            }
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+        let reply = handle_line(&line, handler.as_ref(), &user).await;
+        if let Some(error) = &reply.error {
+            eprintln!("[IPC] ⚠️ {}", error);
+        }
+        let mut payload = serde_json::to_string(&reply).unwrap_or_default();
+        payload.push('\n');
+        if write.write_all(payload.as_bytes()).await.is_err() {
+            break;
+        }
+    }
+}
+
+/// Escucha en el socket unix de `config`
+///
+/// Un socket huérfano de una ejecución anterior se sustituye; si otra
+/// instancia sigue escuchando se devuelve `AddrInUse`.
+#[cfg(unix)]
+pub async fn serve(
+    config: IpcConfig,
+    user: SystemUser,
+    handler: Arc<dyn IpcHandler>,
+) -> std::io::Result<IpcServer> {
+    use std::os::unix::fs::PermissionsExt;
+    use tokio::net::{UnixListener, UnixStream};
+
+    let path = config.endpoint();
+    if std::path::Path::new(&path).exists() {
+        if UnixStream::connect(&path).await.is_ok() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::AddrInUse,
+                format!("{} is already in use", path),
+            ));
+        }
+        std::fs::remove_file(&path)?;
+    }
+    let listener = UnixListener::bind(&path)?;
+    // Solo el usuario que ejecuta el overlay
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600))?;
+    println!("🔌 IPC listening on {}", path);
+    Ok(IpcServer {
+        task: serve_listener(listener, user, handler),
+        endpoint: path,
+    })
+}
+
+#[cfg(unix)]
+fn serve_listener(
+    listener: tokio::net::UnixListener,
+    user: SystemUser,
+    handler: Arc<dyn IpcHandler>,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        loop {
+            match listener.accept().await {
+                Ok((stream, _)) => {
+                    tokio::spawn(handle_client(stream, handler.clone(), user.clone()));
+                }
+                Err(e) => eprintln!("⚠️ IPC accept failed: {}", e),
+            }
+        }
+    })
+}
+
+/// Escucha en el named pipe de `config`; cada cliente recibe su instancia
+#[cfg(windows)]
+pub async fn serve(
+    config: IpcConfig,
+    user: SystemUser,
+    handler: Arc<dyn IpcHandler>,
+) -> std::io::Result<IpcServer> {
+    use tokio::net::windows::named_pipe::ServerOptions;
+
+    let path = config.endpoint();
+    let mut server = ServerOptions::new()
+        .first_pipe_instance(true)
+        .create(&path)?;
+    println!("🔌 IPC listening on {}", path);
+    let endpoint = path.clone();
+    let task = tokio::spawn(async move {
+        loop {
+            if let Err(e) = server.connect().await {
+                eprintln!("⚠️ IPC accept failed: {}", e);
+                continue;
+            }
+            // La siguiente instancia se crea antes de atender a este cliente
+            let next = match ServerOptions::new().create(&path) {
+                Ok(next) => next,
+                Err(e) => {
+                    eprintln!("⚠️ IPC pipe creation failed: {}", e);
+                    break;
+                }
+            };
+            let client = std::mem::replace(&mut server, next);
+            tokio::spawn(handle_client(client, handler.clone(), user.clone()));
+        }
+    });
+    Ok(IpcServer { task, endpoint })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct MockHandler {
+        calls: Mutex<Vec<String>>,
+    }
+
+    #[async_trait::async_trait]
+    impl IpcHandler for MockHandler {
+        async fn show_message(&self, message: ChatMessage) -> Result<(), String> {
+            self.calls
+                .lock()
+                .unwrap()
+                .push(format!("show {}: {}", message.username, message.content));
+            Ok(())
+        }
+
+        async fn set_rendering_paused(&self, paused: bool) -> Result<(), String> {
+            self.calls
+                .lock()
+                .unwrap()
+                .push(format!("paused {}", paused));
+            Ok(())
+        }
+
+        async fn switch_theme(&self, name: Option<String>) -> Result<(), String> {
+            self.calls
+                .lock()
+                .unwrap()
+                .push(format!("theme {}", name.as_deref().unwrap_or("-")));
+            Ok(())
+        }
+
+        async fn next_theme(&self) -> Result<(), String> {
+            self.calls.lock().unwrap().push("next theme".to_string());
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_commands_are_dispatched_to_handler() {
+        let handler = MockHandler::default();
+        let user = SystemUser::default();
+
+        let reply = handle_line(
+            r#"{"cmd":"show","user":"alerts","text":"Donation $5"}"#,
+            &handler,
+            &user,
+        )
+        .await;
+        assert_eq!(
+            reply,
+            IpcReply {
+                ok: true,
+                error: None
+            }
+        );
+        assert!(handle_line(r#"{"cmd":"pause"}"#, &handler, &user).await.ok);
+        assert!(
+            handle_line(r#"{"cmd":"theme","name":"light"}"#, &handler, &user)
+                .await
+                .ok
+        );
+        assert!(handle_line(r#"{"cmd":"next_theme"}"#, &handler, &user).await.ok);
+
+        for invalid in [
+            r#"{"cmd":"show","text":" "}"#,
+            r#"{"cmd":"explode"}"#,
+            "nope",
+        ] {
+            let reply = handle_line(invalid, &handler, &user).await;
+            assert!(!reply.ok);
+            assert!(reply.error.is_some());
+        }
+        assert_eq!(
+            *handler.calls.lock().unwrap(),
+            [
+                "show alerts: Donation $5",
+                "paused true",
+                "theme light",
+                "next theme"
+            ]
+        );
+
+        let message = IpcCommand::Show {
+            user: None,
+            text: "hi".to_string(),
+            color: Some("#ff0000".to_string()),
+        }
+        .to_message(&user)
+        .unwrap();
+        assert_eq!(message.username, user.name);
+        assert_eq!(message.user_color.as_deref(), Some("#ff0000"));
+        assert_eq!(
+            message.metadata.custom_data[system_users::SYSTEM_ROLE_KEY],
+            IPC_ROLE
+        );
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_unix_socket_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = IpcConfig {
+            enabled: true,
+            path: Some(dir.path().join("ipc.sock").to_string_lossy().into_owned()),
+        };
+        let handler = Arc::new(MockHandler::default());
+        let server = serve(config.clone(), SystemUser::default(), handler.clone())
+            .await
+            .unwrap();
+        // Una segunda instancia no puede quitarle el socket a la primera
+        assert!(
+            serve(config.clone(), SystemUser::default(), handler.clone())
+                .await
+                .is_err()
+        );
+
+        let stream = tokio::net::UnixStream::connect(config.endpoint())
+            .await
+            .unwrap();
+        let (read, mut write) = tokio::io::split(stream);
+        write.write_all(b"{\"cmd\":\"resume\"}\n").await.unwrap();
+        let reply = BufReader::new(read)
+            .lines()
+            .next_line()
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(reply, r#"{"ok":true}"#);
+        assert_eq!(*handler.calls.lock().unwrap(), ["paused false"]);
+
+        assert_eq!(server.endpoint(), config.endpoint());
+        server.shutdown();
+        assert!(!std::path::Path::new(&config.endpoint()).exists());
+    }
+}
@@ -0,0 +1,187 @@
+//! Posición de las ventanas ajustada a mano, por conexión
+//!
+//! Con `display.click_through: false` las ventanas dejan de ignorar el ratón y
+//! se pueden arrastrar. Al soltar una, lo que se movió se suma al
+//! desplazamiento de su conexión y los siguientes mensajes de esa conexión
+//! aparecen igual de desplazados respecto a donde los coloca `placement`. Los
+//! desplazamientos se guardan en `display.layout_file` y se recuperan al
+//! arrancar, así el chat vuelve a salir donde se dejó.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::{Mutex, OnceLock};
+
+use serde::{Deserialize, Serialize};
+
+static GLOBAL_LAYOUT: OnceLock<LayoutMemory> = OnceLock::new();
+
+/// Desplazamiento en píxeles lógicos respecto a la posición de `placement`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LayoutOffset {
+    pub x: i32,
+    pub y: i32,
+}
+
+#[derive(Debug, Default)]
+struct LayoutState {
+    file: String,
+    offsets: BTreeMap<String, LayoutOffset>,
+    /// Hay arrastres sin guardar en `file`
+    dirty: bool,
+}
+
+/// Desplazamientos por id de conexión, persistidos en un fichero JSON
+#[derive(Debug, Default)]
+pub struct LayoutMemory {
+    state: Mutex<LayoutState>,
+}
+
+impl LayoutMemory {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn global() -> &'static LayoutMemory {
+        GLOBAL_LAYOUT.get_or_init(Self::new)
+    }
+
+    /// Usa `file` y lee los desplazamientos guardados en él; recargar la
+    /// configuración con el mismo fichero conserva los arrastres sin guardar
+    pub fn configure(&self, file: &str) {
+        let mut state = self.lock();
+        if state.file == file {
+            return;
+        }
+        state.file = file.to_string();
+        state.dirty = false;
+        state.offsets = match Self::load(file) {
+            Ok(offsets) => offsets,
+            Err(e) => {
+                eprintln!("[LAYOUT] ⚠️ Cannot read {}: {}", file, e);
+                BTreeMap::new()
+            }
+        };
+    }
+
+    fn load(file: &str) -> std::io::Result<BTreeMap<String, LayoutOffset>> {
+        if file.is_empty() || !Path::new(file).exists() {
+            return Ok(BTreeMap::new());
+        }
+        Ok(serde_json::from_slice(&std::fs::read(file)?)?)
+    }
+
+    pub fn offset(&self, connection_id: &str) -> LayoutOffset {
+        self.lock()
+            .offsets
+            .get(connection_id)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Posición de `placement` con el desplazamiento de la conexión, sin que
+    /// una ventana de `window_size` se salga de un monitor de `monitor`
+    pub fn adjust(
+        &self,
+        connection_id: Option<&str>,
+        pos: (i32, i32),
+        monitor: (i32, i32),
+        window_size: i32,
+    ) -> (i32, i32) {
+        let Some(connection_id) = connection_id else {
+            return pos;
+        };
+        let offset = self.offset(connection_id);
+        if offset == LayoutOffset::default() {
+            return pos;
+        }
+        let clamp = |value: i32, size: i32| value.clamp(0, (size - window_size).max(0));
+        (
+            clamp(pos.0 + offset.x, monitor.0),
+            clamp(pos.1 + offset.y, monitor.1),
+        )
+    }
+
+    /// Suma al desplazamiento de la conexión el arrastre de `from` a `to`
+    pub fn record_move(&self, connection_id: &str, from: (i32, i32), to: (i32, i32)) {
+        if from == to {
+            return;
+        }
+        let mut state = self.lock();
+        let offset = state.offsets.entry(connection_id.to_string()).or_default();
+        offset.x += to.0 - from.0;
+        offset.y += to.1 - from.1;
+        state.dirty = true;
+    }
+
+    /// Escribe los desplazamientos si cambiaron desde la última vez y
+    /// devuelve cuántas conexiones se guardaron
+    pub fn save(&self) -> std::io::Result<usize> {
+        let (file, offsets) = {
+            let mut state = self.lock();
+            if !state.dirty || state.file.is_empty() {
+                return Ok(0);
+            }
+            state.dirty = false;
+            (state.file.clone(), state.offsets.clone())
+        };
+        std::fs::write(&file, serde_json::to_vec_pretty(&offsets)?)?;
+        Ok(offsets.len())
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, LayoutState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_moves_accumulate_per_connection() {
+        let layout = LayoutMemory::new();
+        layout.record_move("main", (100, 100), (140, 60));
+        layout.record_move("main", (500, 500), (490, 500));
+        layout.record_move("main", (10, 10), (10, 10));
+
+        assert_eq!(layout.offset("main"), LayoutOffset { x: 30, y: -40 });
+        assert_eq!(
+            layout.adjust(Some("main"), (200, 200), (1920, 1080), 300),
+            (230, 160)
+        );
+        // Otras conexiones y los mensajes sin conexión no se mueven
+        assert_eq!(
+            layout.adjust(Some("other"), (200, 200), (1920, 1080), 300),
+            (200, 200)
+        );
+        assert_eq!(
+            layout.adjust(None, (200, 200), (1920, 1080), 300),
+            (200, 200)
+        );
+        // Nunca fuera del monitor
+        assert_eq!(
+            layout.adjust(Some("main"), (1900, 10), (1920, 1080), 300),
+            (1620, 0)
+        );
+    }
+
+    #[test]
+    fn test_save_and_restore() {
+        let file = std::env::temp_dir().join(format!("layout-test-{}.json", std::process::id()));
+        let file = file.to_string_lossy().to_string();
+        let _ = std::fs::remove_file(&file);
+
+        let layout = LayoutMemory::new();
+        layout.configure(&file);
+        assert_eq!(layout.save().unwrap(), 0);
+        layout.record_move("main", (0, 0), (25, -15));
+        assert_eq!(layout.save().unwrap(), 1);
+        // Sin cambios no se vuelve a escribir
+        assert_eq!(layout.save().unwrap(), 0);
+
+        let restored = LayoutMemory::new();
+        restored.configure(&file);
+        assert_eq!(restored.offset("main"), LayoutOffset { x: 25, y: -15 });
+        let _ = std::fs::remove_file(&file);
+    }
+}
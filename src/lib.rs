@@ -1,10 +1,55 @@
 //! Overlay Native - Library exports for testing and binaries
 
+pub mod aggregate;
+pub mod api_docs;
+pub mod app;
+pub mod assets;
+pub mod avatars;
+pub mod bridge;
+pub mod capture;
+pub mod cli;
+pub mod command_mode;
+pub mod commands;
 pub mod config;
 pub mod connection;
+pub mod control;
+pub mod dedupe;
+pub mod demo;
 pub mod emotes;
+pub mod flow;
+pub mod health;
+pub mod highlight;
+pub mod history;
+pub mod hype;
+pub mod idle;
+pub mod ipc;
+pub mod layout;
+pub mod links;
+pub mod load;
 pub mod mapping;
+pub mod merge;
+pub mod moderation;
+pub mod obs;
+pub mod overlay_window;
+pub mod pause;
+pub mod pin;
+pub mod placement;
 pub mod platforms;
+#[cfg(feature = "plugin-api")]
+pub mod plugin;
+pub mod presets;
+pub mod profanity;
+pub mod reconnect;
+pub mod secrets;
+pub mod sound;
+pub mod stats;
+pub mod storage;
+pub mod system_users;
+pub mod terminal;
+pub mod text;
+pub mod theme;
+pub mod translation;
+pub mod user_lists;
 
 #[cfg(unix)]
 pub mod window;
@@ -12,5 +57,8 @@ pub mod window;
 #[cfg(windows)]
 pub mod windows;
 
+#[cfg(target_os = "macos")]
+pub mod macos;
+
 #[cfg(target_os = "linux")]
 pub mod x11;
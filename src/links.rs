@@ -0,0 +1,267 @@
+//! Detección y acortado de enlaces en los mensajes
+//!
+//! Las URLs largas desbordan las ventanas: se muestran acortadas
+//! (`example.com/…`) o se ocultan para usuarios por debajo del nivel de
+//! confianza de la conexión. Las URLs completas se guardan en
+//! `custom_data["links"]` para los logs.
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::ops::Range;
+use std::sync::OnceLock;
+
+use crate::connection::ChatMessage;
+use crate::mapping::UserLevel;
+use crate::pin::{user_level_of, user_level_rank};
+use crate::text::slice_bytes;
+
+/// Clave en `custom_data` con las URLs completas del mensaje
+pub const LINKS_KEY: &str = "links";
+
+/// Tratamiento de enlaces de una conexión
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct LinkConfig {
+    /// Muestra solo el dominio si la URL supera `max_length` caracteres
+    pub shorten: bool,
+    pub max_length: usize,
+    /// Sustituye los enlaces de usuarios por debajo de `min_trusted_level`
+    pub hide_untrusted: bool,
+    pub min_trusted_level: UserLevel,
+    /// Texto que sustituye a los enlaces ocultos
+    pub hidden_text: String,
+}
+
+impl Default for LinkConfig {
+    fn default() -> Self {
+        Self {
+            shorten: true,
+            max_length: 32,
+            hide_untrusted: false,
+            min_trusted_level: UserLevel::Subscriber,
+            hidden_text: "[link]".to_string(),
+        }
+    }
+}
+
+/// Longitud mínima de `max_length`: menos no deja ver ni el dominio
+pub const MIN_LINK_LENGTH: usize = 8;
+
+fn link_regex() -> &'static Regex {
+    static LINK_REGEX: OnceLock<Regex> = OnceLock::new();
+    LINK_REGEX.get_or_init(|| {
+        Regex::new(r#"(?i)\b(?:https?://|www\.)[^\s<>"]+"#).expect("valid link regex")
+    })
+}
+
+/// Rangos en bytes de las URLs de `text`, sin la puntuación final
+pub fn find_links(text: &str) -> Vec<Range<usize>> {
+    link_regex()
+        .find_iter(text)
+        .filter_map(|found| {
+            let url = found
+                .as_str()
+                .trim_end_matches(['.', ',', ';', ':', '!', '?', ')', ']', '}', '\'']);
+            (url.len() > "www.".len()).then(|| found.start()..found.start() + url.len())
+        })
+        .collect()
+}
+
+/// Si `text` solo contiene enlaces (y espacios)
+pub fn is_link_only(text: &str) -> bool {
+    let links = find_links(text);
+    if links.is_empty() {
+        return false;
+    }
+    let mut rest = text.to_string();
+    for range in links.into_iter().rev() {
+        rest.replace_range(range, " ");
+    }
+    rest.trim_matches(|c: char| c.is_whitespace() || c.is_ascii_punctuation())
+        .is_empty()
+}
+
+/// Forma visible de una URL: sin esquema ni `www.`, y si sigue siendo más
+/// larga que `max_length`, solo el dominio seguido de `/…`
+pub fn shorten_link(url: &str, max_length: usize) -> String {
+    let without_scheme = url.split_once("://").map_or(url, |(_, rest)| rest);
+    let without_www = without_scheme
+        .strip_prefix("www.")
+        .unwrap_or(without_scheme)
+        .trim_end_matches('/');
+    if without_www.chars().count() <= max_length {
+        return without_www.to_string();
+    }
+
+    let host = without_www
+        .split(['/', '?', '#'])
+        .next()
+        .unwrap_or(without_www);
+    let host = if host.chars().count() > max_length {
+        let truncated: String = host.chars().take(max_length.saturating_sub(1)).collect();
+        format!("{}…", truncated)
+    } else {
+        host.to_string()
+    };
+    format!("{}/…", host)
+}
+
+/// Sustituye `range` (en bytes) del contenido por `replacement`
+///
+/// Las posiciones de los emotes (en caracteres) se desplazan para seguir
+/// apuntando al mismo texto; los que se solapan con el rango se descartan.
+/// Un rango fuera del contenido o que corta un carácter no cambia nada.
+pub fn replace_content(message: &mut ChatMessage, range: Range<usize>, replacement: &str) {
+    let (Some(before), Some(replaced)) = (
+        slice_bytes(&message.content, 0..range.start),
+        slice_bytes(&message.content, range.clone()),
+    ) else {
+        return;
+    };
+    let char_start = before.chars().count();
+    let char_end = char_start + replaced.chars().count();
+    let delta = replacement.chars().count() as isize - (char_end - char_start) as isize;
+
+    for emote in &mut message.emotes {
+        emote
+            .positions
+            .retain(|pos| pos.end < char_start || pos.start >= char_end);
+        for pos in &mut emote.positions {
+            if pos.start >= char_end {
+                pos.start = pos.start.saturating_add_signed(delta);
+                pos.end = pos.end.saturating_add_signed(delta);
+            }
+        }
+    }
+    message.emotes.retain(|emote| !emote.positions.is_empty());
+    message.content.replace_range(range, replacement);
+}
+
+/// Acorta u oculta los enlaces del mensaje y guarda las URLs originales
+///
+/// Las posiciones de los emotes (en caracteres) se desplazan para seguir
+/// apuntando al mismo texto; los que se solapan con un enlace se descartan.
+/// Devuelve cuántos enlaces había.
+pub fn apply_link_config(message: &mut ChatMessage, config: &LinkConfig) -> usize {
+    let links = find_links(&message.content);
+    if links.is_empty() {
+        return 0;
+    }
+
+    let trusted =
+        user_level_rank(&user_level_of(message)) >= user_level_rank(&config.min_trusted_level);
+    let urls: Vec<String> = links
+        .iter()
+        .map(|range| message.content[range.clone()].to_string())
+        .collect();
+
+    // De atrás hacia delante para que los rangos anteriores sigan siendo válidos
+    for range in links.iter().rev() {
+        let url = &message.content[range.clone()];
+        let replacement = if config.hide_untrusted && !trusted {
+            config.hidden_text.clone()
+        } else if config.shorten {
+            shorten_link(url, config.max_length)
+        } else {
+            continue;
+        };
+
+        replace_content(message, range.clone(), &replacement);
+    }
+
+    let count = urls.len();
+    message
+        .metadata
+        .custom_data
+        .insert(LINKS_KEY.to_string(), urls.into());
+    message
+        .metadata
+        .custom_data
+        .insert("has_links".to_string(), true.into());
+    count
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::connection::{Emote, TextPosition};
+
+    #[test]
+    fn test_find_and_shorten_links() {
+        let text = "mira https://www.example.com/some/very/long/path?x=1. y www.rust-lang.org";
+        let links = find_links(text);
+        assert_eq!(links.len(), 2);
+        assert_eq!(
+            &text[links[0].clone()],
+            "https://www.example.com/some/very/long/path?x=1"
+        );
+        assert_eq!(&text[links[1].clone()], "www.rust-lang.org");
+
+        assert_eq!(
+            shorten_link("https://www.example.com/some/very/long/path?x=1", 20),
+            "example.com/…"
+        );
+        assert_eq!(shorten_link("https://example.com/a/", 20), "example.com/a");
+        assert_eq!(
+            shorten_link("https://a-very-long-host-name.example", 8),
+            "a-very-…/…"
+        );
+    }
+
+    #[test]
+    fn test_shortening_keeps_emote_positions_and_full_urls() {
+        let url = "https://example.com/a/really/long/path/that/overflows";
+        let mut msg = ChatMessage::test("viewer", &format!("{} Kappa", url));
+        let start = url.chars().count() + 1;
+        msg.emotes.push(Emote {
+            id: "25".to_string(),
+            name: "Kappa".to_string(),
+            positions: vec![TextPosition {
+                start,
+                end: start + 5,
+            }],
+            ..Default::default()
+        });
+
+        assert_eq!(apply_link_config(&mut msg, &LinkConfig::default()), 1);
+        assert_eq!(msg.content, "example.com/… Kappa");
+        let pos = &msg.emotes[0].positions[0];
+        let chars: String = msg.content.chars().skip(pos.start).take(5).collect();
+        assert_eq!(chars, "Kappa");
+        assert_eq!(msg.metadata.custom_data[LINKS_KEY][0], url);
+        assert_eq!(msg.metadata.custom_data["has_links"], true);
+    }
+
+    #[test]
+    fn test_link_only_messages() {
+        assert!(is_link_only("https://example.com/a www.example.org"));
+        assert!(is_link_only("(https://example.com/a)!"));
+        assert!(!is_link_only("mira https://example.com/a"));
+        assert!(!is_link_only("hola"));
+    }
+
+    #[test]
+    fn test_replace_content_ignores_invalid_ranges() {
+        let mut message = ChatMessage::test("viewer", "ñandú");
+        // Corta la `ñ` o se sale del contenido
+        replace_content(&mut message, 1..3, "x");
+        replace_content(&mut message, 4..40, "x");
+        assert_eq!(message.content, "ñandú");
+    }
+
+    #[test]
+    fn test_untrusted_links_are_hidden() {
+        let config = LinkConfig {
+            hide_untrusted: true,
+            ..Default::default()
+        };
+        let mut viewer = ChatMessage::test("viewer", "free skins at https://scam.example/x");
+        apply_link_config(&mut viewer, &config);
+        assert_eq!(viewer.content, "free skins at [link]");
+
+        let mut moderator =
+            ChatMessage::test("viewer", "rules: https://example.com/r").with_badge("moderator");
+        apply_link_config(&mut moderator, &config);
+        assert_eq!(moderator.content, "rules: example.com/r");
+    }
+}
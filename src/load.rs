@@ -0,0 +1,359 @@
+//! Controlador de carga con degradación automática
+//!
+//! Cuando el ritmo de mensajes o el uso de CPU superan los umbrales configurados
+//! se reduce la fidelidad (sin animaciones, modo feed y muestreo 1 de cada N) y se
+//! restaura cuando la carga se mantiene por debajo del umbral de recuperación.
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use crate::config::LoadConfig;
+
+/// Métricas de carga observadas en un instante
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LoadMetrics {
+    pub messages_per_second: f64,
+    /// Uso de CPU del proceso en porcentaje; `None` si la plataforma no lo expone
+    pub cpu_percent: Option<f32>,
+}
+
+/// Motivo por el que se degradó la presentación
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DegradationReason {
+    MessageRate(f64),
+    Cpu(f32),
+}
+
+impl fmt::Display for DegradationReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DegradationReason::MessageRate(rate) => write!(f, "{:.1} messages/s", rate),
+            DegradationReason::Cpu(cpu) => write!(f, "{:.0}% CPU", cpu),
+        }
+    }
+}
+
+/// Cambio de estado emitido por el controlador
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LoadEvent {
+    Degraded(DegradationReason),
+    Restored,
+}
+
+/// Nivel de fidelidad con el que se deben presentar los mensajes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Fidelity {
+    pub animations: bool,
+    pub feed_mode: bool,
+    /// Se muestra 1 de cada `sample_every` mensajes
+    pub sample_every: u32,
+}
+
+impl Fidelity {
+    pub const FULL: Fidelity = Fidelity {
+        animations: true,
+        feed_mode: false,
+        sample_every: 1,
+    };
+}
+
+impl Default for Fidelity {
+    fn default() -> Self {
+        Self::FULL
+    }
+}
+
+/// Cuenta eventos dentro de una ventana deslizante
+#[derive(Debug)]
+pub struct RateMeter {
+    window: Duration,
+    events: VecDeque<Instant>,
+}
+
+impl RateMeter {
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            events: VecDeque::new(),
+        }
+    }
+
+    pub fn record(&mut self, now: Instant) {
+        self.events.push_back(now);
+        self.prune(now);
+    }
+
+    /// Eventos por segundo dentro de la ventana
+    pub fn rate(&mut self, now: Instant) -> f64 {
+        self.prune(now);
+        self.events.len() as f64 / self.window.as_secs_f64()
+    }
+
+    fn prune(&mut self, now: Instant) {
+        while let Some(&oldest) = self.events.front() {
+            if now.saturating_duration_since(oldest) > self.window {
+                self.events.pop_front();
+            } else {
+                break;
+            }
+        }
+    }
+}
+
+/// Mide el uso de CPU del propio proceso entre dos muestras
+#[derive(Debug, Default)]
+pub struct CpuSampler {
+    last: Option<(Instant, u64)>,
+}
+
+impl CpuSampler {
+    /// Porcentaje de CPU desde la muestra anterior (100% = un núcleo completo)
+    pub fn sample(&mut self, now: Instant) -> Option<f32> {
+        let ticks = process_cpu_ticks()?;
+        let previous = self.last.replace((now, ticks));
+        let (then, previous_ticks) = previous?;
+
+        let wall = now.saturating_duration_since(then).as_secs_f64();
+        if wall <= 0.0 {
+            return None;
+        }
+        let cpu_secs = ticks.saturating_sub(previous_ticks) as f64 / CLOCK_TICKS_PER_SEC;
+        Some((cpu_secs / wall * 100.0) as f32)
+    }
+}
+
+/// USER_HZ en Linux; fijo en 100 en todas las arquitecturas soportadas
+#[cfg(target_os = "linux")]
+const CLOCK_TICKS_PER_SEC: f64 = 100.0;
+#[cfg(not(target_os = "linux"))]
+const CLOCK_TICKS_PER_SEC: f64 = 1.0;
+
+/// Suma de utime + stime del proceso según /proc/self/stat
+#[cfg(target_os = "linux")]
+fn process_cpu_ticks() -> Option<u64> {
+    let stat = std::fs::read_to_string("/proc/self/stat").ok()?;
+    // El nombre del proceso puede contener espacios: los campos empiezan tras ')'
+    let rest = &stat[stat.rfind(')')? + 1..];
+    let fields: Vec<&str> = rest.split_whitespace().collect();
+    let utime: u64 = fields.get(11)?.parse().ok()?;
+    let stime: u64 = fields.get(12)?.parse().ok()?;
+    Some(utime + stime)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn process_cpu_ticks() -> Option<u64> {
+    None
+}
+
+/// Decide cuándo degradar y restaurar la presentación a partir de las métricas
+#[derive(Debug)]
+pub struct LoadController {
+    config: LoadConfig,
+    rate: RateMeter,
+    cpu: CpuSampler,
+    degraded: Option<DegradationReason>,
+    calm_since: Option<Instant>,
+    sample_counter: u32,
+}
+
+impl LoadController {
+    pub fn new(config: LoadConfig) -> Self {
+        Self {
+            config,
+            rate: RateMeter::new(Duration::from_secs(1)),
+            cpu: CpuSampler::default(),
+            degraded: None,
+            calm_since: None,
+            sample_counter: 0,
+        }
+    }
+
+    /// Registra un mensaje entrante y decide si debe mostrarse según el muestreo
+    pub fn should_display(&mut self, now: Instant) -> bool {
+        self.rate.record(now);
+
+        let sample_every = self.fidelity().sample_every.max(1);
+        let show = self.sample_counter.is_multiple_of(sample_every);
+        self.sample_counter = self.sample_counter.wrapping_add(1);
+        show
+    }
+
+    /// Toma una muestra de las métricas actuales
+    pub fn metrics(&mut self, now: Instant) -> LoadMetrics {
+        LoadMetrics {
+            messages_per_second: self.rate.rate(now),
+            cpu_percent: self.cpu.sample(now),
+        }
+    }
+
+    /// Muestrea las métricas y evalúa si cambia el nivel de fidelidad
+    pub fn tick(&mut self, now: Instant) -> Option<LoadEvent> {
+        let metrics = self.metrics(now);
+        self.evaluate(&metrics, now)
+    }
+
+    /// Aplica los umbrales (con histéresis) a unas métricas concretas
+    pub fn evaluate(&mut self, metrics: &LoadMetrics, now: Instant) -> Option<LoadEvent> {
+        if !self.config.enabled {
+            return None;
+        }
+
+        if self.degraded.is_none() {
+            let reason = self.exceeded(metrics)?;
+            self.degraded = Some(reason);
+            self.calm_since = None;
+            self.sample_counter = 0;
+            return Some(LoadEvent::Degraded(reason));
+        }
+
+        if !self.is_calm(metrics) {
+            self.calm_since = None;
+            return None;
+        }
+
+        let calm_since = *self.calm_since.get_or_insert(now);
+        if now.saturating_duration_since(calm_since)
+            >= Duration::from_secs(self.config.recovery_delay_secs)
+        {
+            self.degraded = None;
+            self.calm_since = None;
+            return Some(LoadEvent::Restored);
+        }
+        None
+    }
+
+    fn exceeded(&self, metrics: &LoadMetrics) -> Option<DegradationReason> {
+        if metrics.messages_per_second > self.config.max_messages_per_second {
+            return Some(DegradationReason::MessageRate(metrics.messages_per_second));
+        }
+        match metrics.cpu_percent {
+            Some(cpu) if cpu > self.config.max_cpu_percent => Some(DegradationReason::Cpu(cpu)),
+            _ => None,
+        }
+    }
+
+    fn is_calm(&self, metrics: &LoadMetrics) -> bool {
+        let ratio = self.config.recovery_ratio;
+        let rate_ok = metrics.messages_per_second <= self.config.max_messages_per_second * ratio;
+        let cpu_ok = metrics
+            .cpu_percent
+            .is_none_or(|cpu| cpu as f64 <= self.config.max_cpu_percent as f64 * ratio);
+        rate_ok && cpu_ok
+    }
+
+    pub fn is_degraded(&self) -> bool {
+        self.degraded.is_some()
+    }
+
+    /// Fidelidad a aplicar a los mensajes que se muestren ahora
+    pub fn fidelity(&self) -> Fidelity {
+        if self.degraded.is_some() {
+            Fidelity {
+                animations: false,
+                feed_mode: true,
+                sample_every: self.config.sample_rate.max(1),
+            }
+        } else {
+            Fidelity::FULL
+        }
+    }
+
+    /// Máximo de ventanas simultáneas en modo feed
+    pub fn feed_max_windows(&self) -> usize {
+        self.config.feed_max_windows
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn metrics(rate: f64, cpu: Option<f32>) -> LoadMetrics {
+        LoadMetrics {
+            messages_per_second: rate,
+            cpu_percent: cpu,
+        }
+    }
+
+    #[test]
+    fn test_degrades_on_message_rate_and_restores_after_delay() {
+        let config = LoadConfig::default();
+        let mut controller = LoadController::new(config.clone());
+        let start = Instant::now();
+
+        assert_eq!(controller.evaluate(&metrics(5.0, None), start), None);
+
+        let high = config.max_messages_per_second + 1.0;
+        assert!(matches!(
+            controller.evaluate(&metrics(high, None), start),
+            Some(LoadEvent::Degraded(DegradationReason::MessageRate(_)))
+        ));
+        assert!(controller.fidelity().feed_mode);
+        assert!(!controller.fidelity().animations);
+
+        // Por encima del umbral de recuperación no se restaura
+        let between = config.max_messages_per_second * (config.recovery_ratio + 1.0) / 2.0;
+        assert_eq!(controller.evaluate(&metrics(between, None), start), None);
+
+        let calm = metrics(0.0, None);
+        assert_eq!(controller.evaluate(&calm, start), None);
+        let later = start + Duration::from_secs(config.recovery_delay_secs);
+        assert_eq!(controller.evaluate(&calm, later), Some(LoadEvent::Restored));
+        assert_eq!(controller.fidelity(), Fidelity::FULL);
+    }
+
+    #[test]
+    fn test_degrades_on_cpu() {
+        let config = LoadConfig::default();
+        let mut controller = LoadController::new(config.clone());
+        let event = controller.evaluate(
+            &metrics(0.0, Some(config.max_cpu_percent + 5.0)),
+            Instant::now(),
+        );
+        assert!(matches!(
+            event,
+            Some(LoadEvent::Degraded(DegradationReason::Cpu(_)))
+        ));
+    }
+
+    #[test]
+    fn test_samples_one_in_n_while_degraded() {
+        let config = LoadConfig {
+            sample_rate: 3,
+            ..LoadConfig::default()
+        };
+        let mut controller = LoadController::new(config);
+        let now = Instant::now();
+        controller.evaluate(&metrics(1000.0, None), now);
+
+        let shown = (0..9).filter(|_| controller.should_display(now)).count();
+        assert_eq!(shown, 3);
+    }
+
+    #[test]
+    fn test_disabled_controller_never_degrades() {
+        let config = LoadConfig {
+            enabled: false,
+            ..LoadConfig::default()
+        };
+        let mut controller = LoadController::new(config);
+        let now = Instant::now();
+        assert_eq!(
+            controller.evaluate(&metrics(1000.0, Some(400.0)), now),
+            None
+        );
+        assert!((0..5).all(|_| controller.should_display(now)));
+    }
+
+    #[test]
+    fn test_rate_meter_window() {
+        let mut meter = RateMeter::new(Duration::from_secs(1));
+        let start = Instant::now();
+        for _ in 0..10 {
+            meter.record(start);
+        }
+        assert_eq!(meter.rate(start), 10.0);
+        assert_eq!(meter.rate(start + Duration::from_secs(2)), 0.0);
+    }
+}
@@ -0,0 +1,539 @@
+//! Backend de ventanas nativo para macOS (NSPanel vía objc2)
+//!
+//! [`MacBackend`] implementa [`WindowBackend`] para que el bucle principal
+//! funcione igual que con GTK o Win32. Las ventanas son `NSPanel` sin borde,
+//! transparentes, por encima de todo, visibles en todos los escritorios y que
+//! nunca se activan ni reciben clics.
+//!
+//! Toda la API de AppKit debe usarse desde el hilo principal. Por ahora los
+//! emotes se muestran con su código de texto y `display.precomposed` no
+//! compone el mensaje en un bitmap.
+
+use std::time::Duration;
+
+use objc2::rc::Retained;
+use objc2_app_kit::{
+    NSApplication, NSApplicationActivationPolicy, NSBackingStoreType, NSColor, NSEvent,
+    NSEventMask, NSFont, NSPanel, NSProgressIndicator, NSProgressIndicatorStyle, NSScreen,
+    NSTextField, NSView, NSWindowCollectionBehavior, NSWindowStyleMask,
+};
+use objc2_foundation::{
+    MainThreadMarker, NSDate, NSDefaultRunLoopMode, NSPoint, NSRect, NSSize, NSString,
+};
+use tokio::time::Instant;
+use twitch_irc::message::Emote;
+
+use crate::config::{FadeTiming, WindowStyle};
+use crate::overlay_window::{OverlayWindow, RecycledOverlayWindow, WindowBackend};
+use crate::theme::{Rgb, ThemePalette};
+
+/// Nivel de `NSStatusWindowLevel` (kCGStatusWindowLevel): por encima de las
+/// ventanas normales y de las flotantes
+const STATUS_WINDOW_LEVEL: isize = 25;
+const PADDING: f64 = 10.0;
+const PROGRESS_HEIGHT: f64 = 6.0;
+
+/// Geometría del monitor principal en coordenadas de pantalla (origen arriba a la izquierda)
+#[derive(Debug, Clone, Copy)]
+pub struct WindowGeometry {
+    pub x: i32,
+    pub y: i32,
+    pub width: i32,
+    pub height: i32,
+}
+
+pub struct MacWindow {
+    panel: Retained<NSPanel>,
+    /// Vista con el contenido del mensaje, dentro de la del panel
+    content: Retained<NSView>,
+    progress_bar: Retained<NSProgressIndicator>,
+    username_label: Retained<NSTextField>,
+    /// Texto del nombre sin el contador de repeticiones
+    username_text: String,
+    pub created: Instant,
+    pub duration: Duration,
+    pub progress: f64,
+    pub username: String,
+    pub message: String,
+    pub emotes: Vec<Emote>,
+    /// Opacidad configurada, sin fundido
+    opacity: f64,
+    fade: FadeTiming,
+    /// Momento en que se mostró; a diferencia de `created` no cambia con las repeticiones
+    shown: Instant,
+}
+
+/// Panel oculto y sin contenido, listo para mostrar otro mensaje
+pub struct RecycledWindow(Retained<NSPanel>);
+
+fn main_thread() -> MainThreadMarker {
+    MainThreadMarker::new().expect("macOS overlay windows must be used from the main thread")
+}
+
+fn ns_color(color: Rgb, alpha: f64) -> Retained<NSColor> {
+    unsafe {
+        NSColor::colorWithSRGBRed_green_blue_alpha(
+            color.0 as f64 / 255.0,
+            color.1 as f64 / 255.0,
+            color.2 as f64 / 255.0,
+            alpha,
+        )
+    }
+}
+
+fn label(
+    text: &str,
+    frame: NSRect,
+    color: Rgb,
+    font: &NSFont,
+    mtm: MainThreadMarker,
+) -> Retained<NSTextField> {
+    unsafe {
+        let label = NSTextField::labelWithString(&NSString::from_str(text), mtm);
+        label.setFrame(frame);
+        label.setTextColor(Some(&ns_color(color, 1.0)));
+        label.setFont(Some(font));
+        label
+    }
+}
+
+/// Contenido de la ventana de un mensaje, del tamaño que ocupa
+struct MessageContent {
+    view: Retained<NSView>,
+    progress_bar: Retained<NSProgressIndicator>,
+    username_label: Retained<NSTextField>,
+    size: NSSize,
+}
+
+/// Nombre, cabecera del evento, mensaje y barra de progreso, de abajo arriba
+/// porque AppKit tiene el origen abajo a la izquierda
+fn message_content(
+    user: &str,
+    message: &str,
+    style: &WindowStyle,
+    mtm: MainThreadMarker,
+) -> MessageContent {
+    let scale = style.scale as f64;
+    let font_size = style.font_size as f64;
+
+    let text_width =
+        (crate::text::grapheme_len(user) + crate::text::grapheme_len(message)).max(20) as f64 * 8.0
+            + 20.0;
+    let width = (text_width.clamp(200.0, 400.0) * scale).round();
+    let line_height = (font_size * 1.4).ceil();
+    // Cabecera del evento (meses de un resub) en una línea más grande
+    let card_font_size = (font_size * 1.6).round();
+    let card_height = if style.card.is_some() {
+        (card_font_size * 1.4).ceil()
+    } else {
+        0.0
+    };
+    let height = PADDING * 3.0 + line_height * 2.0 + card_height + PROGRESS_HEIGHT;
+    let size = NSSize::new(width, height);
+
+    let palette = style.palette.unwrap_or_else(ThemePalette::fallback);
+    let (username_color, text_color) = (palette.username, palette.text);
+
+    unsafe {
+        let view = NSView::initWithFrame(mtm.alloc(), NSRect::new(NSPoint::new(0.0, 0.0), size));
+
+        let content_width = width - PADDING * 2.0;
+        let progress_bar = NSProgressIndicator::initWithFrame(
+            mtm.alloc(),
+            NSRect::new(
+                NSPoint::new(PADDING, PADDING),
+                NSSize::new(content_width, PROGRESS_HEIGHT),
+            ),
+        );
+        progress_bar.setStyle(NSProgressIndicatorStyle::Bar);
+        progress_bar.setIndeterminate(false);
+        progress_bar.setMinValue(0.0);
+        progress_bar.setMaxValue(1.0);
+        progress_bar.setDoubleValue(0.0);
+
+        let message_label = label(
+            message,
+            NSRect::new(
+                NSPoint::new(PADDING, PADDING * 2.0 + PROGRESS_HEIGHT),
+                NSSize::new(content_width, line_height),
+            ),
+            text_color,
+            &NSFont::systemFontOfSize(font_size),
+            mtm,
+        );
+        let card_label = style.card.as_ref().map(|card| {
+            let text = match &card.detail {
+                Some(detail) => format!("{} · {}", card.headline, detail),
+                None => card.headline.clone(),
+            };
+            label(
+                &text,
+                NSRect::new(
+                    NSPoint::new(PADDING, PADDING * 2.0 + PROGRESS_HEIGHT + line_height),
+                    NSSize::new(content_width, card_height),
+                ),
+                style
+                    .palette
+                    .map_or(username_color, |palette| palette.progress),
+                &NSFont::boldSystemFontOfSize(card_font_size),
+                mtm,
+            )
+        });
+        let username_label = label(
+            &style.username_label(user),
+            NSRect::new(
+                NSPoint::new(
+                    PADDING,
+                    PADDING * 2.0 + PROGRESS_HEIGHT + line_height + card_height,
+                ),
+                NSSize::new(content_width, line_height),
+            ),
+            username_color,
+            &NSFont::boldSystemFontOfSize(font_size),
+            mtm,
+        );
+
+        view.addSubview(&progress_bar);
+        view.addSubview(&message_label);
+        if let Some(card_label) = &card_label {
+            view.addSubview(card_label);
+        }
+        view.addSubview(&username_label);
+
+        MessageContent {
+            view,
+            progress_bar,
+            username_label,
+            size,
+        }
+    }
+}
+
+/// Panel sin borde, por encima de todo y que nunca se activa
+fn new_panel(frame: NSRect, mtm: MainThreadMarker) -> Retained<NSPanel> {
+    unsafe {
+        let panel = NSPanel::initWithContentRect_styleMask_backing_defer(
+            mtm.alloc(),
+            frame,
+            NSWindowStyleMask::Borderless | NSWindowStyleMask::NonactivatingPanel,
+            NSBackingStoreType::NSBackingStoreBuffered,
+            false,
+        );
+        panel.setReleasedWhenClosed(false);
+        panel.setOpaque(false);
+        panel.setHasShadow(false);
+        panel.setLevel(STATUS_WINDOW_LEVEL);
+        panel.setFloatingPanel(true);
+        panel.setBecomesKeyOnlyIfNeeded(true);
+        panel.setHidesOnDeactivate(false);
+        // Todos los escritorios, también sobre apps a pantalla completa, y fuera de Cmd-Tab
+        panel.setCollectionBehavior(
+            NSWindowCollectionBehavior::CanJoinAllSpaces
+                | NSWindowCollectionBehavior::Stationary
+                | NSWindowCollectionBehavior::IgnoresCycle
+                | NSWindowCollectionBehavior::FullScreenAuxiliary,
+        );
+        panel
+    }
+}
+
+/// Colores, opacidad y arrastre del estilo
+fn apply_panel_style(panel: &NSPanel, style: &WindowStyle, alpha: f64) {
+    let palette = style.palette.unwrap_or_else(ThemePalette::fallback);
+    unsafe {
+        panel.setBackgroundColor(Some(&ns_color(palette.background, 0.9)));
+        panel.setAlphaValue(alpha);
+        // Con `display.click_through` desactivado se arrastra desde cualquier punto
+        panel.setIgnoresMouseEvents(!style.draggable);
+        panel.setMovableByWindowBackground(style.draggable);
+    }
+}
+
+/// Marco de `size` con la esquina superior izquierda en `pos` (origen arriba
+/// a la izquierda, como en los otros backends)
+fn panel_frame(pos: (i32, i32), size: NSSize) -> NSRect {
+    let screen = get_monitor_geometry();
+    let origin = NSPoint::new(
+        pos.0 as f64,
+        screen.height as f64 - pos.1 as f64 - size.height,
+    );
+    NSRect::new(origin, size)
+}
+
+/// Crea y muestra una ventana del overlay sin activarla, reutilizando el
+/// panel de `recycled` si lo hay
+pub fn spawn_window(
+    user: &str,
+    message: &str,
+    emotes: &[Emote],
+    pos: (i32, i32),
+    style: &WindowStyle,
+    recycled: Option<RecycledWindow>,
+) -> MacWindow {
+    let mtm = main_thread();
+    let content = message_content(user, message, style, mtm);
+    let frame = panel_frame(pos, content.size);
+    let panel = match recycled {
+        Some(RecycledWindow(panel)) => {
+            unsafe { panel.setFrame_display(frame, false) };
+            panel
+        }
+        None => new_panel(frame, mtm),
+    };
+
+    // Con fundido de entrada aparece transparente y el temporizador la revela
+    let opacity = style.opacity.clamp(0.0, 1.0) as f64;
+    let alpha = if style.fade.fade_in.is_zero() {
+        opacity
+    } else {
+        0.0
+    };
+    apply_panel_style(&panel, style, alpha);
+
+    unsafe {
+        if let Some(view) = panel.contentView() {
+            view.addSubview(&content.view);
+        }
+        // Muestra el panel sin activar la app ni robar el foco
+        panel.orderFrontRegardless();
+    }
+
+    MacWindow {
+        panel,
+        content: content.view,
+        progress_bar: content.progress_bar,
+        username_label: content.username_label,
+        username_text: style.username_label(user),
+        created: Instant::now(),
+        duration: style.duration,
+        progress: 0.0,
+        username: user.to_string(),
+        message: message.to_string(),
+        emotes: emotes.to_vec(),
+        opacity,
+        fade: style.fade,
+        shown: Instant::now(),
+    }
+}
+
+impl MacWindow {
+    pub fn close(&self) {
+        unsafe { self.panel.close() };
+    }
+
+    pub fn set_progress(&mut self, progress: f64) {
+        // Igual que en Windows: solo se redibuja con cambios de al menos un 2%
+        if (self.progress - progress).abs() < 0.02 {
+            return;
+        }
+        self.progress = progress;
+        unsafe { self.progress_bar.setDoubleValue(progress) };
+    }
+}
+
+/// Backend de AppKit; todo corre en el hilo principal
+pub struct MacBackend;
+
+#[async_trait::async_trait(?Send)]
+impl WindowBackend for MacBackend {
+    type Window = MacWindow;
+    type Monitor = WindowGeometry;
+
+    fn monitor_geometry() -> WindowGeometry {
+        get_monitor_geometry()
+    }
+
+    fn monitor_size(monitor: &WindowGeometry) -> (i32, i32) {
+        (monitor.width, monitor.height)
+    }
+
+    fn process_events() -> bool {
+        process_messages()
+    }
+
+    async fn precompose(
+        _spec: crate::emotes::MessageBitmapSpec,
+        _style: &WindowStyle,
+        _position: (i32, i32),
+        _font_family: &str,
+    ) -> Option<image::RgbaImage> {
+        None
+    }
+
+    async fn spawn(
+        header: &str,
+        body: &str,
+        emotes: &[Emote],
+        position: (i32, i32),
+        _monitor: WindowGeometry,
+        style: &WindowStyle,
+        recycled: Option<RecycledWindow>,
+    ) -> MacWindow {
+        spawn_window(header, body, emotes, position, style, recycled)
+    }
+}
+
+#[async_trait::async_trait(?Send)]
+impl OverlayWindow for MacWindow {
+    type Recycled = RecycledWindow;
+
+    fn created(&self) -> Instant {
+        self.created
+    }
+
+    fn duration(&self) -> Duration {
+        self.duration
+    }
+
+    fn shown(&self) -> Instant {
+        self.shown
+    }
+
+    fn fade(&self) -> FadeTiming {
+        self.fade
+    }
+
+    fn set_progress(&mut self, progress: f64) {
+        MacWindow::set_progress(self, progress);
+    }
+
+    fn set_fade(&mut self, factor: f64) {
+        let alpha = self.opacity * factor;
+        unsafe {
+            if (self.panel.alphaValue() - alpha).abs() > 0.005 {
+                self.panel.setAlphaValue(alpha);
+            }
+        }
+    }
+
+    fn set_repeat_count(&mut self, count: u32) {
+        let text = format!("{} ×{}", self.username_text, count);
+        unsafe {
+            self.username_label
+                .setStringValue(&NSString::from_str(&text))
+        };
+        self.created = Instant::now();
+    }
+
+    /// Cambia el contenido manteniendo la esquina superior izquierda
+    async fn update_content(
+        &mut self,
+        header: &str,
+        body: &str,
+        emotes: &[Emote],
+        style: &WindowStyle,
+    ) {
+        let mtm = main_thread();
+        let content = message_content(header, body, style, mtm);
+        unsafe {
+            let frame = self.panel.frame();
+            let top = frame.origin.y + frame.size.height;
+            self.panel.setFrame_display(
+                NSRect::new(
+                    NSPoint::new(frame.origin.x, top - content.size.height),
+                    content.size,
+                ),
+                true,
+            );
+            self.content.removeFromSuperview();
+            if let Some(view) = self.panel.contentView() {
+                view.addSubview(&content.view);
+            }
+        }
+        self.opacity = style.opacity.clamp(0.0, 1.0) as f64;
+        apply_panel_style(&self.panel, style, unsafe { self.panel.alphaValue() });
+
+        self.content = content.view;
+        self.progress_bar = content.progress_bar;
+        self.username_label = content.username_label;
+        self.username_text = style.username_label(header);
+        self.created = Instant::now();
+        self.duration = style.duration;
+        self.progress = 0.0;
+        self.username = header.to_string();
+        self.message = body.to_string();
+        self.emotes = emotes.to_vec();
+        self.fade = style.fade;
+    }
+
+    /// Oculta el panel y quita el contenido del mensaje
+    fn recycle(self) -> RecycledWindow {
+        unsafe {
+            self.panel.orderOut(None);
+            self.content.removeFromSuperview();
+        }
+        RecycledWindow(self.panel)
+    }
+
+    fn close(self) {
+        MacWindow::close(&self);
+    }
+}
+
+impl RecycledOverlayWindow for RecycledWindow {
+    fn close(self) {
+        unsafe { self.0.close() };
+    }
+}
+
+/// Geometría del monitor principal
+pub fn get_monitor_geometry() -> WindowGeometry {
+    let mtm = main_thread();
+    let frame = NSScreen::mainScreen(mtm)
+        .map(|screen| screen.frame())
+        .unwrap_or(NSRect::new(
+            NSPoint::new(0.0, 0.0),
+            NSSize::new(1920.0, 1080.0),
+        ));
+
+    WindowGeometry {
+        x: frame.origin.x as i32,
+        y: frame.origin.y as i32,
+        width: frame.size.width as i32,
+        height: frame.size.height as i32,
+    }
+}
+
+/// Escala del monitor principal (2.0 en pantallas Retina)
+pub fn get_dpi_scale() -> f64 {
+    NSScreen::mainScreen(main_thread()).map_or(1.0, |screen| screen.backingScaleFactor())
+}
+
+/// Posición del puntero relativa al monitor principal, con el origen arriba
+/// a la izquierda
+pub fn cursor_position() -> Option<(i32, i32)> {
+    let point = unsafe { NSEvent::mouseLocation() };
+    let screen = get_monitor_geometry();
+    Some((
+        point.x as i32 - screen.x,
+        screen.height - (point.y as i32 - screen.y),
+    ))
+}
+
+/// Procesa los eventos pendientes de AppKit sin bloquear
+///
+/// La primera llamada convierte el proceso en app "accessory" (sin icono en el
+/// Dock ni menú). Devuelve `false` cuando la aplicación debe terminar.
+pub fn process_messages() -> bool {
+    let mtm = main_thread();
+    let app = NSApplication::sharedApplication(mtm);
+
+    unsafe {
+        if app.activationPolicy() != NSApplicationActivationPolicy::Accessory {
+            app.setActivationPolicy(NSApplicationActivationPolicy::Accessory);
+            app.finishLaunching();
+        }
+
+        let past = NSDate::distantPast();
+        while let Some(event) = app.nextEventMatchingMask_untilDate_inMode_dequeue(
+            NSEventMask::Any,
+            Some(&past),
+            NSDefaultRunLoopMode,
+            true,
+        ) {
+            app.sendEvent(&event);
+        }
+        app.updateWindows();
+    }
+    true
+}
@@ -1,13 +1,58 @@
+mod aggregate;
+mod api_docs;
+mod app;
+mod assets;
+mod avatars;
+mod bridge;
+mod capture;
+mod cli;
+mod command_mode;
+mod commands;
 mod config;
 mod connection;
+mod control;
+mod dedupe;
+mod demo;
 mod emotes;
+mod flow;
+mod health;
+mod highlight;
+mod history;
+mod hype;
+mod idle;
+mod ipc;
+mod layout;
+mod links;
+mod load;
 mod mapping;
+mod merge;
+mod moderation;
+mod obs;
+mod overlay_window;
+mod pause;
+mod pin;
+mod placement;
 mod platforms;
-
-#[cfg(unix)]
+mod presets;
+mod profanity;
+mod reconnect;
+mod secrets;
+mod sound;
+mod stats;
+mod storage;
+mod system_users;
+mod terminal;
+mod text;
+mod theme;
+mod translation;
+mod user_lists;
+
+#[cfg(all(unix, not(target_os = "macos")))]
 mod window;
 #[cfg(windows)]
 mod windows;
+#[cfg(target_os = "macos")]
+mod macos;
 
 #[cfg(target_os = "linux")]
 pub mod x11;
@@ -17,8 +62,8 @@ extern crate gdkx11;
 #[cfg(target_os = "linux")]
 extern crate x11rb;
 
-use rand::seq::SliceRandom;
 
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::sync::RwLock;
@@ -30,17 +75,37 @@ use crate::mapping::MappingSystem;
 use crate::platforms::{CredentialManager, PlatformFactory};
 
 use anyhow::Result;
-use tokio::sync::broadcast;
+use clap::Parser;
+use futures::StreamExt;
+use tokio::sync::{broadcast, mpsc};
 
 #[cfg(windows)]
-use winapi::shared::windef::{HWND, RECT};
-#[cfg(windows)]
-use winapi::um::winuser::{GetClientRect, GetWindowLongPtrW, InvalidateRect, GWLP_USERDATA};
+use winapi::shared::windef::HWND;
+
+/// Cada cuánto se guardan los mensajes de `history.replay_on_startup`
+const HISTORY_SAVE_INTERVAL: Duration = Duration::from_secs(30);
+/// Emotes del cache que usa el modo demo
+const DEMO_EMOTES: usize = 50;
 
 /// Application events for the emitter system
 #[derive(Debug, Clone)]
 enum AppEvent {
-    MessageReceived(connection::ChatMessage),
+    /// Mensaje que ya pasó por el pool de [`MessageProcessor`]
+    MessageReceived(Box<ProcessedMessage>),
+    LoadChanged(load::LoadEvent),
+    /// Se guardó una configuración válida en disco
+    ConfigReloaded(Box<Config>),
+    /// Una conexión se cayó, se está reconectando o se recuperó
+    ConnectionStateChanged(reconnect::ConnectionStateChange),
+    /// Mensaje del historial que se vuelve a mostrar tal cual, sin procesarlo
+    ReplayMessage(Box<connection::ChatMessage>),
+    /// Un moderador retiró el mensaje con este id (borrado, timeout, ban o `/clear`)
+    MessageRetracted(String),
+    /// Deja de abrir ventanas; los mensajes se guardan hasta `ResumeRendering`
+    PauseRendering,
+    ResumeRendering,
+    /// Se cambió el tema con nombre; lleva la apariencia resultante
+    ThemeSwitched(Box<config::DisplayConfig>),
     WindowUpdate,
     Shutdown,
 }
@@ -48,116 +113,379 @@ enum AppEvent {
 /// Event emitter for decoupled communication
 struct EventEmitter {
     sender: broadcast::Sender<AppEvent>,
+    /// Mensajes sin procesar para el pool de [`AppState::start_message_pipeline`]
+    processing: mpsc::UnboundedSender<connection::ChatMessage>,
+    processing_receiver: std::sync::Mutex<Option<mpsc::UnboundedReceiver<connection::ChatMessage>>>,
+}
+
+/// Mensajes mostrados recientemente que se guardan para retirar sus ventanas
+const DISPLAYED_MESSAGES: usize = 256;
+
+/// Autor de un mensaje mostrado
+struct DisplayedMessage {
+    message_id: String,
+    platform: String,
+    channel: String,
+    username: String,
+}
+
+/// Últimos mensajes mostrados con su autor, para retirar las ventanas de un
+/// usuario o de un canal aunque el historial esté desactivado
+#[derive(Default)]
+struct DisplayedMessages {
+    recent: std::sync::Mutex<std::collections::VecDeque<DisplayedMessage>>,
+}
+
+impl DisplayedMessages {
+    fn record(&self, message: &connection::ChatMessage) {
+        let mut recent = self.recent.lock().unwrap_or_else(|e| e.into_inner());
+        if recent.len() >= DISPLAYED_MESSAGES {
+            recent.pop_front();
+        }
+        recent.push_back(DisplayedMessage {
+            message_id: message.id.clone(),
+            platform: message.platform.clone(),
+            channel: message.channel.clone(),
+            username: message.username.clone(),
+        });
+    }
+
+    /// Ids de los mensajes mostrados en el canal, solo los de `username` si se indica
+    fn ids_in(&self, platform: &str, channel: &str, username: Option<&str>) -> Vec<String> {
+        self.recent
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .filter(|m| {
+                m.platform.eq_ignore_ascii_case(platform)
+                    && m.channel.eq_ignore_ascii_case(channel)
+                    && username.is_none_or(|user| m.username.eq_ignore_ascii_case(user))
+            })
+            .map(|m| m.message_id.clone())
+            .collect()
+    }
 }
 
 /// Simple window tracker for basic management
 struct WindowTracker {
-    #[cfg(unix)]
-    windows: Arc<RwLock<Vec<SpawnedWindow>>>,
-    #[cfg(windows)]
-    windows: Arc<RwLock<Vec<WindowsWindow>>>,
+    windows: Arc<RwLock<Vec<(app::WindowHandle, PlatformWindow)>>>,
+    /// Hueco persistente del mensaje fijado con `!pin`
+    pinned: Arc<RwLock<Option<(app::WindowHandle, PlatformWindow)>>>,
+    /// Ventana persistente del modo resumen (`aggregate`)
+    aggregate: Arc<RwLock<Option<(app::WindowHandle, PlatformWindow)>>>,
+    /// Ventanas ocultas que se reutilizan en lugar de crear otras
+    pool: Arc<RwLock<Vec<PlatformRecycledWindow>>>,
+    /// `window.pool_size`
+    pool_size: Arc<AtomicUsize>,
+    /// Autores de los mensajes mostrados, compartido con el procesador
+    displayed: Arc<DisplayedMessages>,
+    observers: app::OverlayObservers,
 }
 
 impl WindowTracker {
-    fn new() -> Self {
-        #[cfg(unix)]
+    fn new(observers: app::OverlayObservers, pool_size: usize) -> Self {
+        Self {
+            windows: Arc::new(RwLock::new(Vec::new())),
+            pinned: Arc::new(RwLock::new(None)),
+            aggregate: Arc::new(RwLock::new(None)),
+            pool: Arc::new(RwLock::new(Vec::new())),
+            pool_size: Arc::new(AtomicUsize::new(pool_size)),
+            displayed: Arc::new(DisplayedMessages::default()),
+            observers,
+        }
+    }
+
+    /// Cambia el tamaño del pool, destruyendo las ventanas que sobren
+    async fn set_pool_size(&self, pool_size: usize) {
+        self.pool_size.store(pool_size, Ordering::Relaxed);
+        let mut pool = self.pool.write().await;
+        let excess = pool.len().saturating_sub(pool_size);
+        for w in pool.drain(..excess) {
+            w.close();
+        }
+    }
+
+    /// Ventana oculta para el próximo mensaje, si queda alguna en el pool
+    async fn recycled(&self) -> Option<PlatformRecycledWindow> {
+        self.pool.write().await.pop()
+    }
+
+    /// Oculta `window` y la guarda en el pool, o la destruye si está lleno
+    fn release(&self, pool: &mut Vec<PlatformRecycledWindow>, window: PlatformWindow) {
+        if pool.len() < self.pool_size.load(Ordering::Relaxed) {
+            pool.push(window.recycle());
+        } else {
+            window.close();
+        }
+    }
+
+    /// Notifica a los observadores la apertura de una ventana para `message`
+    fn track(
+        &self,
+        message: &connection::ChatMessage,
+        position: (i32, i32),
+        duration: Duration,
+    ) -> app::WindowHandle {
+        let handle = app::WindowHandle::new(message.id.clone(), position);
+        stats::Stats::global().record_window();
+        self.displayed.record(message);
+        self.observers.emit_window_created(&app::WindowCreatedEvent {
+            window_id: handle.id,
+            message_id: message.id.clone(),
+            platform: message.platform.clone(),
+            channel: message.channel.clone(),
+            position,
+            duration,
+        });
+        self.observers.emit_message_displayed(&app::MessageDisplayedEvent {
+            window_id: handle.id,
+            message: message.clone(),
+        });
+        handle
+    }
+
+    async fn add_window(
+        &self,
+        window: PlatformWindow,
+        message: &connection::ChatMessage,
+        position: (i32, i32),
+    ) {
+        let handle = self.track(message, position, window.duration());
+        let mut windows = self.windows.write().await;
+        windows.push((handle, window));
+    }
+
+    /// Muestra `window` en el hueco fijado, cerrando el mensaje fijado anterior
+    async fn set_pinned(
+        &self,
+        window: PlatformWindow,
+        message: &connection::ChatMessage,
+        position: (i32, i32),
+    ) {
+        let handle = self.track(message, position, window.duration());
+        self.clear_pinned(app::CloseReason::Evicted).await;
+        *self.pinned.write().await = Some((handle, window));
+    }
+
+    /// Cierra el mensaje fijado, si lo hay
+    async fn clear_pinned(&self, reason: app::CloseReason) {
+        if let Some((handle, w)) = self.pinned.write().await.take() {
+            self.release(&mut *self.pool.write().await, w);
+            self.observers.emit_window_closed(&handle.closed(reason));
+        }
+    }
+
+    /// Sustituye la ventana del modo resumen por `window`
+    async fn set_aggregate(
+        &self,
+        window: PlatformWindow,
+        message: &connection::ChatMessage,
+        position: (i32, i32),
+    ) {
+        let handle = self.track(message, position, window.duration());
+        self.clear_aggregate(app::CloseReason::Evicted).await;
+        *self.aggregate.write().await = Some((handle, window));
+    }
+
+    /// Muestra `message` en la ventana abierta del modo resumen sin cerrarla;
+    /// `false` si no hay ninguna
+    async fn update_aggregate(
+        &self,
+        message: &connection::ChatMessage,
+        monitor_geometry: MonitorGeometry,
+        config: &Config,
+    ) -> bool {
+        // Sin lock mientras se descargan emotes y avatar
+        let Some((window_id, position)) = self
+            .aggregate
+            .read()
+            .await
+            .as_ref()
+            .map(|(handle, _)| (handle.id, handle.position))
+        else {
+            return false;
+        };
+        let prepared = prepare_message(
+            message,
+            position,
+            monitor_geometry,
+            config,
+            load::Fidelity::FULL,
+        )
+        .await;
+
+        let mut aggregate = self.aggregate.write().await;
+        // Cerrada o sustituida mientras tanto: el llamador abre otra
+        let Some((handle, w)) = aggregate
+            .as_mut()
+            .filter(|(handle, _)| handle.id == window_id)
+        else {
+            return false;
+        };
+        w.update_content(
+            &prepared.rendered.header,
+            &prepared.rendered.body,
+            &prepared.emotes,
+            &prepared.style,
+        )
+        .await;
+        self.displayed.record(message);
+        self.observers.emit_message_displayed(&app::MessageDisplayedEvent {
+            window_id: handle.id,
+            message: message.clone(),
+        });
+        true
+    }
+
+    /// Cierra la ventana del modo resumen, si la hay
+    async fn clear_aggregate(&self, reason: app::CloseReason) {
+        if let Some((handle, w)) = self.aggregate.write().await.take() {
+            self.release(&mut *self.pool.write().await, w);
+            self.observers.emit_window_closed(&handle.closed(reason));
+        }
+    }
+
+    /// Muestra `×count` en la ventana del mensaje `message_id` y la mantiene
+    /// visible; `false` si ya no está abierta
+    async fn set_repeat_count(&self, message_id: &str, count: u32) -> bool {
+        let mut windows = self.windows.write().await;
+        match windows
+            .iter_mut()
+            .find(|(handle, _)| handle.message_id == message_id)
         {
-            Self {
-                windows: Arc::new(RwLock::new(Vec::new())),
+            Some((_, w)) => {
+                w.set_repeat_count(count);
+                true
             }
+            None => false,
         }
-        #[cfg(windows)]
+    }
+
+    /// Cierra la ventana del mensaje `message_id`; `false` si no está abierta
+    async fn remove_message(&self, message_id: &str) -> bool {
+        let mut pinned = self.pinned.write().await;
+        if pinned
+            .as_ref()
+            .is_some_and(|(handle, _)| handle.message_id == message_id)
         {
-            Self {
-                windows: Arc::new(RwLock::new(Vec::new())),
+            if let Some((handle, w)) = pinned.take() {
+                self.release(&mut *self.pool.write().await, w);
+                self.observers
+                    .emit_window_closed(&handle.closed(app::CloseReason::Deleted));
             }
+            return true;
         }
-    }
+        drop(pinned);
 
-    #[cfg(unix)]
-    async fn add_window(&self, window: SpawnedWindow) {
         let mut windows = self.windows.write().await;
-        windows.push(window);
+        let Some(index) = windows
+            .iter()
+            .position(|(handle, _)| handle.message_id == message_id)
+        else {
+            return false;
+        };
+        let (handle, w) = windows.remove(index);
+        self.release(&mut *self.pool.write().await, w);
+        self.observers
+            .emit_window_closed(&handle.closed(app::CloseReason::Deleted));
+        true
     }
 
-    #[cfg(windows)]
-    async fn add_window(&self, window: WindowsWindow) {
+    /// Posiciones de las ventanas visibles, incluidas la fijada y la del resumen
+    async fn occupied(&self) -> Vec<(i32, i32)> {
+        let mut occupied: Vec<(i32, i32)> = self
+            .windows
+            .read()
+            .await
+            .iter()
+            .map(|(handle, _)| handle.position)
+            .collect();
+        if let Some((handle, _)) = self.pinned.read().await.as_ref() {
+            occupied.push(handle.position);
+        }
+        if let Some((handle, _)) = self.aggregate.read().await.as_ref() {
+            occupied.push(handle.position);
+        }
+        occupied
+    }
+
+    /// Cierra las ventanas más antiguas hasta dejar como máximo `max`
+    async fn trim_to(&self, max: usize) {
         let mut windows = self.windows.write().await;
-        windows.push(window);
+        let mut pool = self.pool.write().await;
+        let excess = windows.len().saturating_sub(max);
+        for (handle, w) in windows.drain(..excess) {
+            self.release(&mut pool, w);
+            self.observers
+                .emit_window_closed(&handle.closed(app::CloseReason::Evicted));
+        }
     }
 
-    async fn cleanup_expired(&self) {
-        let now = tokio::time::Instant::now();
-        let max_time = Duration::from_secs(10);
+    /// Cierra todas las ventanas visibles, incluida la fijada; devuelve cuántas
+    async fn clear(&self) -> usize {
+        let pinned = self.pinned.read().await.is_some();
+        self.clear_pinned(app::CloseReason::Cleared).await;
+        let mut windows = self.windows.write().await;
+        let mut pool = self.pool.write().await;
+        let closed = windows.len() + usize::from(pinned);
+        for (handle, w) in windows.drain(..) {
+            self.release(&mut pool, w);
+            self.observers
+                .emit_window_closed(&handle.closed(app::CloseReason::Cleared));
+        }
+        closed
+    }
 
-        #[cfg(unix)]
-        {
-            let mut windows = self.windows.write().await;
-            windows.retain(|w| {
-                let elapsed = now - w.created;
-                if elapsed >= max_time {
-                    w.w.close();
-                    false
-                } else {
-                    let progress = elapsed.as_secs_f64() / max_time.as_secs_f64();
-                    w.progress.set_fraction(progress);
-                    true
-                }
-            });
+    /// Cierra todas las ventanas al salir
+    async fn close_all(&self) {
+        self.clear_pinned(app::CloseReason::Shutdown).await;
+        self.clear_aggregate(app::CloseReason::Shutdown).await;
+        let mut windows = self.windows.write().await;
+        for (handle, w) in windows.drain(..) {
+            w.close();
+            self.observers
+                .emit_window_closed(&handle.closed(app::CloseReason::Shutdown));
+        }
+        for w in self.pool.write().await.drain(..) {
+            w.close();
         }
+    }
 
-        #[cfg(windows)]
-        {
-            let mut windows = self.windows.write().await;
-            let mut windows_to_remove = Vec::new();
-
-            // Update progress for all windows and identify expired ones
-            for (i, w) in windows.iter_mut().enumerate() {
-                let elapsed = now - w.created;
-                if elapsed >= max_time {
-                    windows_to_remove.push(i);
-                } else {
-                    let progress = elapsed.as_secs_f64() / max_time.as_secs_f64();
-
-                    // Only update if progress changed significantly (2% or more)
-                    let progress_diff = (w.progress - progress).abs();
-                    if progress_diff >= 0.02 {
-                        // Update progress
-                        w.progress = progress;
-                        unsafe {
-                            // Update the stored window data
-                            let window_data_ptr = GetWindowLongPtrW(w.hwnd, GWLP_USERDATA)
-                                as *mut crate::windows::WindowData;
-                            if !window_data_ptr.is_null() {
-                                (*window_data_ptr).progress = progress;
-                            }
+    /// Avanza los fundidos de entrada y salida; el fijado y el resumen solo
+    /// tienen entrada
+    async fn animate_fades(&self) {
+        let now = tokio::time::Instant::now();
+        for (_, w) in self.windows.write().await.iter_mut() {
+            let remaining = w.duration().saturating_sub(now - w.created());
+            w.set_fade(w.fade().factor(now - w.shown(), remaining));
+        }
+        if let Some((_, w)) = self.pinned.write().await.as_mut() {
+            w.set_fade(w.fade().factor(now - w.shown(), Duration::MAX));
+        }
+        if let Some((_, w)) = self.aggregate.write().await.as_mut() {
+            w.set_fade(w.fade().factor(now - w.shown(), Duration::MAX));
+        }
+    }
 
-                            // Only invalidate the progress bar area to avoid flickering
-                            let mut client_rect = RECT {
-                                left: 0,
-                                top: 0,
-                                right: 0,
-                                bottom: 0,
-                            };
-                            GetClientRect(w.hwnd, &mut client_rect);
-
-                            let progress_rect = RECT {
-                                left: 10,
-                                top: client_rect.bottom - 15,
-                                right: client_rect.right - 10,
-                                bottom: client_rect.bottom - 5,
-                            };
-                            InvalidateRect(w.hwnd, &progress_rect, 0); // Don't erase background
-                        }
-                    }
-                }
-            }
+    async fn cleanup_expired(&self) {
+        let now = tokio::time::Instant::now();
+        let mut windows = self.windows.write().await;
+        let (expired, mut visible): (Vec<_>, Vec<_>) = windows
+            .drain(..)
+            .partition(|(_, w)| w.is_expired(now));
+
+        // En Windows `set_progress` también avanza los emotes animados; las
+        // ventanas tapadas no se repintan
+        for (_, w) in visible.iter_mut() {
+            w.set_progress(w.progress_at(now));
+        }
+        *windows = visible;
 
-            // Remove expired windows (in reverse order to maintain indices)
-            for &i in windows_to_remove.iter().rev() {
-                let w = windows.remove(i);
-                w.close();
-            }
+        let mut pool = self.pool.write().await;
+        for (handle, w) in expired {
+            self.release(&mut pool, w);
+            self.observers
+                .emit_window_closed(&handle.closed(app::CloseReason::Expired));
         }
     }
 }
@@ -166,6 +494,12 @@ impl Clone for WindowTracker {
     fn clone(&self) -> Self {
         Self {
             windows: self.windows.clone(),
+            pinned: self.pinned.clone(),
+            aggregate: self.aggregate.clone(),
+            pool: self.pool.clone(),
+            pool_size: self.pool_size.clone(),
+            displayed: self.displayed.clone(),
+            observers: self.observers.clone(),
         }
     }
 }
@@ -173,7 +507,12 @@ impl Clone for WindowTracker {
 impl EventEmitter {
     fn new() -> Self {
         let (sender, _) = broadcast::channel(1000);
-        Self { sender }
+        let (processing, processing_receiver) = mpsc::unbounded_channel();
+        Self {
+            sender,
+            processing,
+            processing_receiver: std::sync::Mutex::new(Some(processing_receiver)),
+        }
     }
 
     fn emit(&self, event: AppEvent) -> Result<()> {
@@ -181,41 +520,336 @@ impl EventEmitter {
         Ok(())
     }
 
+    /// Encola un mensaje recibido; sale como `MessageReceived` una vez procesado
+    fn submit(&self, message: connection::ChatMessage) -> Result<()> {
+        self.processing
+            .send(message)
+            .map_err(|_| anyhow::anyhow!("message pipeline stopped"))
+    }
+
+    fn take_processing_receiver(&self) -> Option<mpsc::UnboundedReceiver<connection::ChatMessage>> {
+        self.processing_receiver
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .take()
+    }
+
     fn subscribe(&self) -> broadcast::Receiver<AppEvent> {
         self.sender.subscribe()
     }
 }
 
-#[cfg(unix)]
+/// Mensaje filtrado, enriquecido y mapeado por [`MessageProcessor`]
+#[derive(Debug, Clone)]
+struct ProcessedMessage {
+    message: connection::ChatMessage,
+    /// `None` en plataformas sin adaptador (p. ej. mensajes del propio overlay)
+    mapped: Option<mapping::MappedMessage>,
+    /// Repetición de la ventana con este id y apariciones que suma
+    repeat_of: Option<(String, u32)>,
+    /// Ventanas que sustituye el resumen de una ráfaga de hype
+    retract: Vec<String>,
+}
+
+/// Filtros, enriquecimiento y mapeo de los mensajes fuera del bucle de la
+/// interfaz; cada tarea del pool lleva una copia
+#[derive(Clone)]
+struct MessageProcessor {
+    config: Arc<Config>,
+    platform_manager: Arc<RwLock<PlatformManager>>,
+    emote_system: Arc<RwLock<EmoteSystem>>,
+    emote_renderer: Arc<emotes::EmoteRenderer>,
+    mapping_system: Arc<RwLock<MappingSystem>>,
+    app: Arc<app::OverlayApp>,
+}
+
+impl MessageProcessor {
+    fn new(state: &AppState) -> Self {
+        Self {
+            config: Arc::new(state.config.clone()),
+            platform_manager: state.platform_manager.clone(),
+            emote_system: state.emote_system.clone(),
+            emote_renderer: state.emote_renderer.clone(),
+            mapping_system: state.mapping_system.clone(),
+            app: state.app.clone(),
+        }
+    }
+
+    /// Filtros y etapas que dependen del orden de llegada (repeticiones,
+    /// ráfagas y primeros mensajes); el pipeline las ejecuta de una en una
+    /// antes de repartir el mensaje entre las tareas. El error indica por qué
+    /// se descartó
+    async fn screen(
+        &self,
+        mut message: connection::ChatMessage,
+    ) -> std::result::Result<ProcessedMessage, moderation::DropReason> {
+        // Apply filters if necessary
+        if let Some(connection) = self
+            .config
+            .connections
+            .iter()
+            .find(|conn| conn.platform == message.platform && conn.channel == message.channel)
+        {
+            // Incluye los cambios en caliente de `POST /connections/{id}/commands-only`
+            // y `POST /connections/{id}/users`
+            let filters =
+                command_mode::CommandModes::global().filters_for(&connection.id, &connection.filters);
+            let filters =
+                user_lists::ConnectionUserLists::global().filters_for(&connection.id, &filters);
+            if let Some(reason) = moderation::filter_rejection(&message, &filters) {
+                return Err(reason);
+            }
+            let platform = self
+                .platform_manager
+                .read()
+                .await
+                .get_platform(&message.platform)
+                .cloned();
+            if let Some(platform) = platform {
+                // Filtros propios de la plataforma, además de los comunes
+                if !platform
+                    .lock()
+                    .await
+                    .apply_message_filters(&mut message, &filters)
+                {
+                    return Err(moderation::DropReason::PlatformFilter);
+                }
+            }
+
+            // Shorten or hide links before emotes are parsed on the final text
+            links::apply_link_config(&mut message, &connection.links);
+        }
+
+        // Lenguaje ofensivo: descarta o enmascara antes de buscar emotes en el texto
+        profanity::ProfanityFilter::global().apply(&mut message)?;
+
+        // Repeticiones y copypastas: el bucle principal decide con las
+        // ventanas abiertas (ver `AppState::finish_processing`)
+        let now = std::time::Instant::now();
+        let repeat_of = match dedupe::MessageDeduplicator::global().observe(&message, now) {
+            dedupe::DedupeVerdict::Repeat { message_id, count } => Some((message_id, count)),
+            dedupe::DedupeVerdict::Display => None,
+        };
+
+        // Ráfagas de subs y cheers: una sola ventana resumen en vez de una por
+        // evento; las repeticiones casi siempre se descartan y no cuentan
+        let mut retract = Vec::new();
+        if repeat_of.is_none() {
+            if let hype::HypeVerdict::Summary { retract: burst, summary } =
+                hype::HypeAggregator::global().observe(&message, now)
+            {
+                retract = burst;
+                message = *summary;
+            }
+        }
+
+        // Primer mensaje del usuario en el canal o mención al streamer
+        highlight::Highlighter::global().observe(&mut message);
+
+        Ok(ProcessedMessage {
+            message,
+            mapped: None,
+            repeat_of,
+            retract,
+        })
+    }
+
+    /// Traducción, emotes y mapeo de un mensaje ya filtrado; las tareas del
+    /// pool lo ejecutan en paralelo, así que cada lock se suelta al acabar
+    /// su etapa
+    async fn enrich(
+        &self,
+        processed: ProcessedMessage,
+    ) -> std::result::Result<ProcessedMessage, moderation::DropReason> {
+        let ProcessedMessage {
+            mut message,
+            repeat_of,
+            retract,
+            ..
+        } = processed;
+
+        // Traducción debajo del original si el mensaje está en otro idioma
+        translation::Translator::global().apply(&mut message).await;
+
+        // Parse additional emotes if necessary; las descargas de BTTV, FFZ
+        // y 7TV se hacen sin retener el sistema de emotes
+        let request = self
+            .emote_system
+            .read()
+            .await
+            .emote_request(&message.platform, &message.channel);
+        let fetched = request.fetch(&message.content, "").await;
+        {
+            let mut emote_system = self.emote_system.write().await;
+            if let Ok(additional_emotes) = emote_system.resolve_emotes(
+                &message.content,
+                &message.platform,
+                &message.channel,
+                fetched,
+            ) {
+                message.emotes.extend(additional_emotes);
+            }
+            emote_system.apply_blocklist(&mut message);
+            emote_system.record_usage(&message);
+        }
+
+        // Apply data mapping
+        let raw_message = mapping::RawPlatformMessage {
+            platform: message.platform.clone(),
+            channel: message.channel.clone(),
+            raw_data: serde_json::to_value(&message)
+                .map_err(|e| moderation::DropReason::MappingError(e.to_string()))?,
+            timestamp: chrono::Utc::now(),
+            message_id: Some(message.id.clone()),
+        };
+
+        // Solo las transformaciones necesitan el lock de escritura
+        let result = async {
+            let standardized = self
+                .mapping_system
+                .read()
+                .await
+                .standardize(&raw_message)
+                .await?;
+            let transformed = self.mapping_system.write().await.transform(standardized)?;
+            self.mapping_system.read().await.map_data(transformed).await
+        }
+        .await;
+        let mut mapped = None;
+        match result {
+            Ok(mapped_message) => {
+                // Actualizar mensaje con datos mapeados
+                mapped_message.apply_to(&mut message);
+                mapped = Some(mapped_message);
+            }
+            // Plataformas sin adaptador (p. ej. mensajes del propio overlay) se muestran sin mapear
+            Err(mapping::MappingError::UnsupportedPlatform(_)) => {}
+            Err(e) => return Err(moderation::DropReason::MappingError(e.to_string())),
+        }
+
+        // Preset de solo alertas: el chat normal no abre ventanas
+        if self.config.display.alerts_only
+            && message.platform != system_users::SYSTEM_PLATFORM
+            && theme::events::OverlayEventKind::of(&mapping::MappedMessageType::of(&message))
+                == theme::events::OverlayEventKind::Chat
+        {
+            return Err(moderation::DropReason::AlertsOnly);
+        }
+
+        // Los emotes con capas o modificadores se componen aquí y no en el
+        // bucle de la interfaz
+        self.emote_renderer
+            .render_message(&message.content, &message.emotes)
+            .await;
+
+        Ok(ProcessedMessage {
+            message,
+            mapped,
+            repeat_of,
+            retract,
+        })
+    }
+
+    /// Registra en el carril de moderación un mensaje descartado
+    fn record_dropped(&self, message: &connection::ChatMessage, reason: moderation::DropReason) {
+        if let Some(dropped) = moderation::ModerationLane::global().record(message, reason) {
+            self.app.observers().emit_message_dropped(&dropped);
+        }
+    }
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
 use gdk::prelude::MonitorExt;
-#[cfg(unix)]
-use gtk::prelude::{CssProviderExt, GtkWindowExt, ProgressBarExt};
-#[cfg(unix)]
-use window::{get_gdk_monitor, spawn_window, SpawnedWindow};
+#[cfg(all(unix, not(target_os = "macos")))]
+use gtk::prelude::CssProviderExt;
+#[cfg(all(unix, not(target_os = "macos")))]
+use window::{cursor_position, get_gdk_monitor};
 
 #[cfg(windows)]
-use windows::{get_monitor_geometry, process_messages, WindowsWindow};
+use windows::cursor_position;
+#[cfg(target_os = "macos")]
+use macos::cursor_position;
+
+use overlay_window::{
+    MonitorGeometry, OverlayWindow, PlatformBackend, PlatformRecycledWindow, PlatformWindow,
+    RecycledOverlayWindow, WindowBackend,
+};
 
 /// Estado principal de la aplicación
 struct AppState {
     config: Config,
+    /// Copia de `config` para la API de control y el IPC, que corren en sus
+    /// propias tareas; se actualiza con cada recarga
+    shared_config: Arc<std::sync::RwLock<Config>>,
     platform_manager: Arc<RwLock<PlatformManager>>,
     emote_system: Arc<RwLock<EmoteSystem>>,
+    /// Compone las pilas de emotes en el enriquecimiento de los mensajes
+    emote_renderer: Arc<emotes::EmoteRenderer>,
     mapping_system: Arc<RwLock<MappingSystem>>,
     platform_factory: Arc<PlatformFactory>,
     credential_manager: Arc<CredentialManager>,
     event_emitter: Arc<EventEmitter>,
     window_tracker: Arc<WindowTracker>,
+    app: Arc<app::OverlayApp>,
+    /// Difusión de los mensajes procesados al puente WebSocket
+    message_bridge: bridge::MessageBridge,
+    /// Chat generado por `start_demo`: la recarga no toca las conexiones
+    demo_mode: bool,
+}
+
+/// Registra los observadores del propio overlay; con `logging.level` en
+/// `debug` o `trace` escribe además el ciclo de vida de las ventanas
+fn log_overlay_events(app: &app::OverlayApp) {
+    app.on_error_budget_exceeded(|alert| {
+        eprintln!("🚨 Error budget exceeded: {}", alert.summary())
+    });
+    if !matches!(
+        app.config().logging.level,
+        config::LogLevel::Debug | config::LogLevel::Trace
+    ) {
+        return;
+    }
+    app.on_window_created(|e| {
+        eprintln!(
+            "[DEBUG] 🪟 Window {} opened for {} ({}/{}) at {:?} for {:?}",
+            e.window_id, e.message_id, e.platform, e.channel, e.position, e.duration
+        )
+    })
+    .on_message_displayed(|e| {
+        eprintln!(
+            "[DEBUG] 💬 Window {} shows {}: {}",
+            e.window_id, e.message.username, e.message.content
+        )
+    })
+    .on_window_closed(|e| {
+        eprintln!(
+            "[DEBUG] 🪟 Window {} closed ({:?}) after {:?}",
+            e.window_id, e.reason, e.visible_for
+        )
+    })
+    .on_message_dropped(|d| {
+        eprintln!(
+            "[DEBUG] 🚫 Dropped {} from {}/{} {}: {}",
+            d.id, d.platform, d.channel, d.username, d.reason
+        )
+    });
 }
 
 impl AppState {
     async fn new() -> Result<Self> {
         // Cargar configuración
         println!("[CONFIG] Loading configuration...");
-        let config = Config::load_default().unwrap_or_else(|e| {
+        let mut config = Config::load_default().unwrap_or_else(|e| {
             eprintln!("[CONFIG] Error loading config: {}, using defaults", e);
             Config::default()
         });
+        config.display = theme::named::ThemeSwitcher::global().configure(&config.display);
+
+        let credential_manager = Arc::new(CredentialManager::new());
+        configure_globals(&config, &credential_manager);
+        if config.capture.enabled {
+            println!("[CAPTURE] Raw payload capture enabled in {:?}", config.capture.directory);
+        }
 
         // Mostrar información de configuración cargada
         println!("[CONFIG] ✅ Configuration loaded successfully");
@@ -228,23 +862,42 @@ impl AppState {
 
         // Crear sistemas
         let platform_manager = Arc::new(RwLock::new(PlatformManager::new()));
-        let emote_system = Arc::new(RwLock::new(EmoteSystem::new(config.emotes.clone())));
-        let mapping_system = Arc::new(RwLock::new(MappingSystem::default()));
+        let emote_system = EmoteSystem::new(config.emotes.clone());
+        // Un único descargador de imágenes para todas las ventanas
+        emotes::EmoteDownloader::global().start(emote_system.http_client().clone());
+        let emote_renderer = Arc::new(emotes::EmoteRenderer::new(
+            emotes::default_cache_dir(),
+            emote_system.http_client().clone(),
+        ));
+        let emote_system = Arc::new(RwLock::new(emote_system));
         let platform_factory = Arc::new(PlatformFactory::new());
-        let credential_manager = Arc::new(CredentialManager::new());
 
         let event_emitter = Arc::new(EventEmitter::new());
-        let window_tracker = Arc::new(WindowTracker::new());
+        let app = Arc::new(app::OverlayApp::new(config.clone()));
+        log_overlay_events(&app);
+        // El mismo mapeo en el que `OverlayApp::register_transform` registra
+        let mapping_system = app.mapping_system();
+        if let Some(rules) = config.mapping.rules_path.as_deref().and_then(load_mapping_rules) {
+            mapping_system.write().await.set_rules(rules);
+        }
+        let window_tracker = Arc::new(WindowTracker::new(app.observers(), config.window.pool_size));
+        let message_bridge = bridge::MessageBridge::new(config.bridge.buffer_size)
+            .with_byte_budget(config.bridge.bytes_per_second);
 
         Ok(Self {
+            shared_config: Arc::new(std::sync::RwLock::new(config.clone())),
             config,
             platform_manager,
             emote_system,
+            emote_renderer,
             mapping_system,
             platform_factory,
             credential_manager,
             event_emitter,
             window_tracker,
+            app,
+            message_bridge,
+            demo_mode: false,
         })
     }
 
@@ -255,6 +908,13 @@ impl AppState {
 
         for platform_name in enabled_platforms {
             if let Some(platform_config) = self.config.get_platform_config(platform_name) {
+                // Secretos `keyring:` del almacén del sistema
+                let mut platform_config = platform_config.clone();
+                platform_config.credentials = self
+                    .credential_manager
+                    .resolve_credentials(platform_name, &platform_config.credentials)
+                    .map_err(|e| anyhow::anyhow!("{} credentials: {}", platform_name, e))?;
+
                 // Crear instancia de la plataforma
                 let platform = self
                     .platform_factory
@@ -266,6 +926,10 @@ impl AppState {
 
                 // Registrar plataforma en el manager
                 manager.register_platform(platform_name.to_string(), platform);
+                manager.set_reconnect_policy(
+                    platform_name,
+                    reconnect::ReconnectPolicy::from(&platform_config.settings),
+                );
 
                 // Guardar credenciales
                 self.credential_manager
@@ -314,12 +978,17 @@ impl AppState {
                         connection.channel, connection.platform, connection.id
                     );
                     println!("[CONNECTIONS] ✅ Successfully started connection: {}", connection.id);
+                    self.prefetch_channel_emotes(&connection.platform, &connection.channel);
                 }
                 Err(e) => {
                     eprintln!(
                         "❌ Failed to connect to '{}' on {}: {}",
                         connection.channel, connection.platform, e
                     );
+                    health::ErrorBudget::global().record_failure(
+                        &format!("platform:{}", connection.platform.to_lowercase()),
+                        &e.to_string(),
+                    );
                     eprintln!(
                         "[CONNECTIONS] ❌ Connection start failed for {}: {}",
                         connection.id, e
@@ -331,6 +1000,22 @@ impl AppState {
         Ok(())
     }
 
+    /// Descarga en segundo plano las imágenes de los emotes más usados del canal
+    fn prefetch_channel_emotes(&self, platform: &str, channel: &str) {
+        let emote_system = self.emote_system.clone();
+        let (platform, channel) = (platform.to_string(), channel.to_string());
+        tokio::spawn(async move {
+            let queued = emote_system
+                .read()
+                .await
+                .prefetch_channel_emotes(&platform, &channel)
+                .await;
+            if queued > 0 {
+                println!("[EMOTES] 📥 Prefetching {} emotes for {}:{}", queued, platform, channel);
+            }
+        });
+    }
+
     async fn preload_emotes(&self) -> Result<()> {
         let mut emote_system = self.emote_system.write().await;
 
@@ -346,159 +1031,782 @@ impl AppState {
         Ok(())
     }
 
-    async fn process_message(
-        &self,
-        mut message: connection::ChatMessage,
-    ) -> Result<connection::ChatMessage> {
-        // Apply filters if necessary
-        if let Some(connection) = self
-            .config
-            .connections
-            .iter()
-            .find(|conn| conn.platform == message.platform && conn.channel == message.channel)
-        {
-            let mut manager = self.platform_manager.write().await;
-            if let Some(platform) = manager.get_platform_mut(&message.platform) {
-                if !connection.filters.commands_only
-                    || message.content.starts_with('!')
-                    || message.content.starts_with('/')
-                {
-                    // Apply filters
-                    if !platform
-                        .lock()
-                        .await
-                        .apply_message_filters(&mut message, &connection.filters)
-                    {
-                        return Err(anyhow::anyhow!("Message filtered out"));
-                    }
-                } else {
-                    return Err(anyhow::anyhow!("Commands only filter active"));
+    /// Modo demo: genera mensajes al ritmo de `demo.messages_per_minute` con
+    /// los emotes que haya en el cache
+    async fn start_demo(&mut self) {
+        self.demo_mode = true;
+        let config = self.config.demo.clone();
+        let emotes: Vec<_> = self
+            .emote_system
+            .read()
+            .await
+            .cache
+            .get_most_accessed(DEMO_EMOTES)
+            .into_iter()
+            .map(|(_, emote, _)| emote.clone())
+            .collect();
+        let sender = self.platform_manager.read().await.get_sender();
+        println!(
+            "🎭 Demo mode: {} messages/min with {} cached emotes (platform connections disabled)",
+            config.messages_per_minute,
+            emotes.len()
+        );
+
+        tokio::spawn(async move {
+            use rand::SeedableRng;
+
+            let mut interval = tokio::time::interval(config.interval());
+            let mut generator = demo::DemoGenerator::new(config, emotes);
+            let mut rng = rand::rngs::StdRng::from_entropy();
+            loop {
+                interval.tick().await;
+                if sender.send(generator.next_message(&mut rng)).is_err() {
+                    break;
                 }
             }
-        }
+        });
+    }
 
-        // Parse additional emotes if necessary
-        let mut emote_system = self.emote_system.write().await;
-        if let Ok(additional_emotes) = emote_system
-            .parse_message_emotes(
-                &message.content,
-                &message.platform,
-                &message.channel,
-                "", // Raw emote data if exists
-            )
-            .await
-        {
-            message.emotes.extend(additional_emotes);
+    /// Parte del procesamiento que depende de las ventanas abiertas:
+    /// repeticiones, ventanas que sustituye un resumen y difusión del mensaje;
+    /// `None` si la repetición se sumó a la ventana del original
+    async fn finish_processing(
+        &self,
+        processed: ProcessedMessage,
+    ) -> Option<connection::ChatMessage> {
+        let ProcessedMessage {
+            message,
+            mapped,
+            repeat_of,
+            retract,
+        } = processed;
+
+        // Repeticiones y copypastas suman al contador de la ventana del original
+        if let Some((message_id, count)) = repeat_of {
+            if self.window_tracker.set_repeat_count(&message_id, count).await {
+                self.record_dropped(&message, moderation::DropReason::Duplicate);
+                return None;
+            }
+            dedupe::MessageDeduplicator::global().restart(&message, std::time::Instant::now());
+        }
+        for message_id in &retract {
+            self.window_tracker.remove_message(message_id).await;
         }
 
-        // Apply data mapping
-        let mut mapping_system = self.mapping_system.write().await;
-        let raw_message = mapping::RawPlatformMessage {
-            platform: message.platform.clone(),
-            channel: message.channel.clone(),
-            raw_data: serde_json::to_value(&message)?,
-            timestamp: chrono::Utc::now(),
-            message_id: Some(message.id.clone()),
-        };
-
-        if let Ok(mapped_message) = mapping_system.map_message(&raw_message).await {
-            // Actualizar mensaje con datos mapeados
-            message.emotes = mapped_message.emotes;
-            message.badges = mapped_message.badges;
-            message.user_color = mapped_message
-                .metadata
-                .custom_data
-                .get("user_color")
-                .and_then(|v| v.as_str())
-                .map(|s| s.to_string());
+        // Mismo flujo para las fuentes de navegador conectadas al puente
+        if let Some(mapped) = &mapped {
+            self.message_bridge.publish(mapped);
+            storage::MessageStore::global().store(mapped);
         }
+        history::MessageHistory::global().record(&message);
+        obs::ObsTextFeed::global().push(&message);
 
         eprintln!(
             "[DEBUG] Message processing complete: {} - {}",
             message.username, message.content
         );
-        Ok(message)
+        Some(message)
     }
 
-    /// Start background message processor that emits events
-    async fn start_message_processor(&self) {
-        let event_emitter = self.event_emitter.clone();
-        let platform_manager = self.platform_manager.clone();
+    /// Registra en el carril de moderación un mensaje descartado
+    fn record_dropped(&self, message: &connection::ChatMessage, reason: moderation::DropReason) {
+        if let Some(dropped) = moderation::ModerationLane::global().record(message, reason) {
+            self.app.observers().emit_message_dropped(&dropped);
+        }
+    }
 
-        tokio::spawn(async move {
-            let mut pm = platform_manager.write().await;
-            loop {
-                if let Some(message) = pm.next_message().await {
-                    // Emit event directly without complex processing
-                    if let Err(e) = event_emitter.emit(AppEvent::MessageReceived(message)) {
-                        eprintln!("⚠️ Failed to emit message event: {}", e);
-                    }
+    /// Ejecuta un comando `!overlay` del chat; la duración se pierde al recargar
+    async fn run_chat_command(&mut self, command: commands::OverlayCommand, author: &str) {
+        match command {
+            commands::OverlayCommand::Clear => {
+                let closed = self.window_tracker.clear().await;
+                println!("🧹 {} cleared the overlay ({} windows)", author, closed);
+            }
+            commands::OverlayCommand::Pause | commands::OverlayCommand::Resume => {
+                let event = if command == commands::OverlayCommand::Pause {
+                    AppEvent::PauseRendering
+                } else {
+                    AppEvent::ResumeRendering
+                };
+                if let Err(e) = self.event_emitter.emit(event) {
+                    eprintln!("⚠️ Failed to emit rendering event: {}", e);
                 }
-                tokio::time::sleep(Duration::from_millis(5)).await;
             }
-        });
+            commands::OverlayCommand::Duration(secs) => {
+                self.config.window.message_duration_seconds = secs;
+                println!("⏱️ {} set the message duration to {}s", author, secs);
+            }
+        }
+    }
+
+    /// Aplica en caliente una configuración recargada desde disco
+    async fn apply_reloaded_config(&mut self, mut config: Config) {
+        config.display = theme::named::ThemeSwitcher::global().configure(&config.display);
+        if !self.demo_mode {
+            self.sync_connections(&config).await;
+        }
+
+        self.emote_system
+            .write()
+            .await
+            .update_config(config.emotes.clone());
+        configure_globals(&config, &self.credential_manager);
+        self.window_tracker.set_pool_size(config.window.pool_size).await;
+        if config.mapping != self.config.mapping {
+            let rules = config.mapping.rules_path.as_deref().and_then(load_mapping_rules);
+            self.mapping_system
+                .write()
+                .await
+                .set_rules(rules.unwrap_or_default());
+        }
+
+        // Estilos, filtros y tiempos se leen de `self.config` en cada mensaje
+        *self
+            .shared_config
+            .write()
+            .unwrap_or_else(|e| e.into_inner()) = config.clone();
+        self.config = config;
+        println!("[CONFIG] ✅ Configuration reloaded");
+    }
+
+    /// Arranca y detiene conexiones según la configuración recargada
+    async fn sync_connections(&self, config: &Config) {
+        let sync = self
+            .platform_manager
+            .write()
+            .await
+            .sync_connections(config.connection_infos())
+            .await;
+        for id in &sync.stopped {
+            println!("[CONFIG] ⏹️ Stopped connection {}", id);
+        }
+        for id in &sync.started {
+            println!("[CONFIG] ▶️ Started connection {}", id);
+            if let Some(connection) = config.connections.iter().find(|conn| &conn.id == id) {
+                self.prefetch_channel_emotes(&connection.platform, &connection.channel);
+            }
+        }
+        for (id, e) in &sync.failed {
+            eprintln!("[CONFIG] ❌ Connection {} failed: {}", id, e);
+        }
+    }
+
+    /// Reenvía los cambios de estado de las conexiones como eventos
+    async fn start_state_forwarder(&self) {
+        let event_emitter = self.event_emitter.clone();
+        let mut changes = self.platform_manager.read().await.subscribe_state_changes();
+
+        tokio::spawn(async move {
+            loop {
+                match changes.recv().await {
+                    Ok(change) => {
+                        if let Err(e) = event_emitter.emit(AppEvent::ConnectionStateChanged(change)) {
+                            eprintln!("⚠️ Failed to emit connection state event: {}", e);
+                        }
+                    }
+                    Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        });
+    }
+
+    /// Procesa los mensajes encolados con `EventEmitter::submit` en paralelo,
+    /// hasta `pipeline.workers` a la vez, y los emite como `MessageReceived`
+    /// en el orden de llegada
+    fn start_message_pipeline(&self) {
+        let Some(mut queue) = self.event_emitter.take_processing_receiver() else {
+            eprintln!("⚠️ Message pipeline already running");
+            return;
+        };
+        let event_emitter = self.event_emitter.clone();
+        let mut events = self.event_emitter.subscribe();
+        let mut processor = MessageProcessor::new(self);
+
+        tokio::spawn(async move {
+            let mut pending = futures::stream::FuturesOrdered::new();
+            loop {
+                let workers = processor.config.pipeline.workers.max(1);
+                tokio::select! {
+                    message = queue.recv(), if pending.len() < workers => {
+                        let Some(message) = message else { break };
+                        // Repeticiones y ráfagas dependen del orden: se deciden
+                        // aquí, una a una, y solo el resto va al pool
+                        match processor.screen(message.clone()).await {
+                            Ok(screened) => {
+                                let processor = processor.clone();
+                                pending.push_back(tokio::spawn(async move {
+                                    let result = processor.enrich(screened).await;
+                                    (message, result)
+                                }));
+                            }
+                            Err(reason) => processor.record_dropped(&message, reason),
+                        }
+                    }
+                    Some(done) = pending.next() => match done {
+                        Ok((_, Ok(processed))) => {
+                            if let Err(e) =
+                                event_emitter.emit(AppEvent::MessageReceived(Box::new(processed)))
+                            {
+                                eprintln!("⚠️ Failed to emit message event: {}", e);
+                            }
+                        }
+                        Ok((message, Err(reason))) => processor.record_dropped(&message, reason),
+                        Err(e) => eprintln!("❌ Message processing task failed: {}", e),
+                    },
+                    event = events.recv() => match event {
+                        Ok(AppEvent::ConfigReloaded(config)) => {
+                            processor.config = Arc::new((*config).clone());
+                        }
+                        Err(broadcast::error::RecvError::Closed) => break,
+                        _ => {}
+                    },
+                }
+            }
+            eprintln!("⚠️ Message queue closed, pipeline stopped");
+        });
+    }
+
+    /// Start background message processor that emits events
+    async fn start_message_processor(&self) {
+        let event_emitter = self.event_emitter.clone();
+        let displayed = self.window_tracker.displayed.clone();
+        let mut events = self.event_emitter.subscribe();
+        let mut merger = merge::WatchPartyMerger::from_config(&self.config);
+        let mut timestamps = self.config.timestamps.clone();
+        let mut clock = mapping::clock::ClockOffsetEstimator::new(&timestamps);
+        let mut display_queue = mapping::clock::DisplayQueue::new(&timestamps)
+            .with_connection_offsets(&self.config.connections);
+
+        // Las tareas de lectura empujan los mensajes al canal; el procesador solo
+        // espera en el receptor y no vuelve a tocar el lock del manager
+        let Some(mut receiver) = self.platform_manager.write().await.take_message_receiver()
+        else {
+            eprintln!("⚠️ Message processor already running");
+            return;
+        };
+
+        tokio::spawn(async move {
+            loop {
+                // Sin mensajes retenidos se espera sin límite; si no, hasta que
+                // el primero deba mostrarse
+                let deadline = [merger.next_ready_at(), display_queue.next_ready_at()]
+                    .into_iter()
+                    .flatten()
+                    .min();
+                let next = async {
+                    match deadline {
+                        Some(deadline) => tokio::time::timeout_at(deadline.into(), receiver.recv())
+                            .await
+                            .ok(),
+                        None => Some(receiver.recv().await),
+                    }
+                };
+                let message = tokio::select! {
+                    // `None` si venció la retención; `Some(None)` si se cerró el canal
+                    next = next => match next {
+                        Some(None) => break,
+                        Some(message) => message,
+                        None => None,
+                    },
+                    event = events.recv() => match event {
+                        Ok(AppEvent::ConfigReloaded(config)) => {
+                            merger.reconfigure(&config);
+                            clock.reconfigure(&config.timestamps);
+                            display_queue.reconfigure(&config.timestamps, &config.connections);
+                            timestamps = config.timestamps.clone();
+                            continue;
+                        }
+                        Err(broadcast::error::RecvError::Closed) => break,
+                        _ => continue,
+                    },
+                };
+
+                let now = std::time::Instant::now();
+                // Los borrados de un solo mensaje no se muestran; los timeouts sí
+                let message =
+                    message.filter(|m| !emit_retractions(&event_emitter, &displayed, m));
+                if let Some(mut message) = message {
+                    stats::Stats::global().record_message(&message.platform, &message.channel);
+                    if timestamps.normalize {
+                        clock.normalize(&mut message, std::time::SystemTime::now());
+                    }
+                    merger.push(message, now);
+                }
+
+                for message in merger.drain_ready(now) {
+                    display_queue.push(message, now);
+                }
+
+                for message in display_queue.drain_ready(now) {
+                    if let Err(e) = event_emitter.submit(message) {
+                        eprintln!("⚠️ Failed to submit message: {}", e);
+                    }
+                }
+            }
+            eprintln!("⚠️ Message channel closed, processor stopped");
+        });
     }
 
     // Window management is now handled internally by WindowManager
 }
 
+/// Puente entre la API de control y el estado de la aplicación
+struct ControlBridge {
+    config: Arc<std::sync::RwLock<Config>>,
+    platform_manager: Arc<RwLock<PlatformManager>>,
+    emote_system: Arc<RwLock<EmoteSystem>>,
+    event_emitter: Arc<EventEmitter>,
+}
+
+#[async_trait::async_trait]
+impl control::ControlHandler for ControlBridge {
+    async fn list_connections(&self) -> Vec<control::ConnectionStatus> {
+        let manager = self.platform_manager.read().await;
+        self.config()
+            .connections
+            .iter()
+            .map(|conn| control::ConnectionStatus {
+                id: conn.id.clone(),
+                platform: conn.platform.clone(),
+                channel: conn.channel.clone(),
+                enabled: conn.enabled,
+                running: manager.is_connection_running(&conn.id),
+                state: manager.connection_state(&conn.id),
+                command_mode: command_mode::CommandModes::global().mode(&conn.id, &conn.filters),
+            })
+            .collect()
+    }
+
+    async fn start_connection(&self, id: &str) -> Result<(), String> {
+        let connection = self.connection_config(id)?;
+
+        let mut manager = self.platform_manager.write().await;
+        manager.add_connection(ConnectionInfo {
+            id: connection.id.clone(),
+            platform: connection.platform.clone(),
+            channel: connection.channel.clone(),
+            enabled: connection.enabled,
+            display_name: connection.display_name.clone(),
+        });
+        manager.start_connection(id).await.map_err(|e| e.to_string())?;
+        println!("[CONTROL] Started connection {}", id);
+        Ok(())
+    }
+
+    async fn stop_connection(&self, id: &str) -> Result<(), String> {
+        self.platform_manager
+            .write()
+            .await
+            .stop_connection(id)
+            .await
+            .map_err(|e| e.to_string())?;
+        println!("[CONTROL] Stopped connection {}", id);
+        Ok(())
+    }
+
+    async fn command_mode(&self, id: &str) -> Result<command_mode::CommandMode, String> {
+        let connection = self.connection_config(id)?;
+        Ok(command_mode::CommandModes::global().mode(id, &connection.filters))
+    }
+
+    async fn set_command_mode(
+        &self,
+        id: &str,
+        request: command_mode::CommandModeRequest,
+    ) -> Result<command_mode::CommandMode, String> {
+        let connection = self.connection_config(id)?;
+        let mode = command_mode::CommandModes::global().set(id, &connection.filters, request);
+        self.announce_command_mode(&connection, &mode);
+        Ok(mode)
+    }
+
+    async fn reset_command_mode(&self, id: &str) -> Result<command_mode::CommandMode, String> {
+        let connection = self.connection_config(id)?;
+        let modes = command_mode::CommandModes::global();
+        if modes.reset(id) {
+            let mode = modes.mode(id, &connection.filters);
+            self.announce_command_mode(&connection, &mode);
+        }
+        Ok(modes.mode(id, &connection.filters))
+    }
+
+    async fn user_lists(&self, id: &str) -> Result<user_lists::UserLists, String> {
+        let connection = self.connection_config(id)?;
+        Ok(user_lists::ConnectionUserLists::global().lists(id, &connection.filters))
+    }
+
+    async fn set_user_lists(
+        &self,
+        id: &str,
+        request: user_lists::UserListsRequest,
+    ) -> Result<user_lists::UserLists, String> {
+        let connection = self.connection_config(id)?;
+        let lists = user_lists::ConnectionUserLists::global().set(id, &connection.filters, request);
+        println!(
+            "[CONTROL] Connection {} now blocks {} and allows {} user(s){}",
+            id,
+            lists.blocked_users.len(),
+            lists.allowed_users.len(),
+            if lists.ignore_bots {
+                ", ignoring bots"
+            } else {
+                ""
+            }
+        );
+        Ok(lists)
+    }
+
+    async fn reset_user_lists(&self, id: &str) -> Result<user_lists::UserLists, String> {
+        let connection = self.connection_config(id)?;
+        let lists = user_lists::ConnectionUserLists::global();
+        if lists.reset(id) {
+            println!("[CONTROL] Connection {} user lists back to config", id);
+        }
+        Ok(lists.lists(id, &connection.filters))
+    }
+
+    async fn clear_emote_cache(&self) -> Result<(), String> {
+        self.emote_system.write().await.clear_cache();
+        println!("[CONTROL] Emote cache cleared");
+        Ok(())
+    }
+
+    async fn emote_cache_stats(&self) -> emotes::cache::CacheStats {
+        self.emote_system.write().await.cache_stats()
+    }
+
+    async fn invalidate_emote(&self, id: &str) -> Result<bool, String> {
+        let invalidated = self.emote_system.write().await.invalidate_emote(id).await;
+        let invalidated =
+            emotes::EmoteDownloader::global().forget(&[id.to_string()]) > 0 || invalidated;
+        println!("[CONTROL] Emote {} invalidated: {}", id, invalidated);
+        Ok(invalidated)
+    }
+
+    async fn clear_emote_provider(&self, provider: &str) -> Result<usize, String> {
+        let ids = self
+            .emote_system
+            .write()
+            .await
+            .clear_provider(provider)
+            .await
+            .map_err(|_| format!("Provider '{}' not found", provider))?;
+        emotes::EmoteDownloader::global().forget(&ids);
+        println!("[CONTROL] Cleared {} emotes from {}", ids.len(), provider);
+        Ok(ids.len())
+    }
+
+    async fn refresh_channel_emotes(
+        &self,
+        platform: &str,
+        channel: &str,
+    ) -> Result<emotes::ChannelRefresh, String> {
+        let refresh = self
+            .emote_system
+            .write()
+            .await
+            .refresh_channel_emotes(platform, channel)
+            .await;
+        emotes::EmoteDownloader::global().forget(&refresh.changed);
+        println!(
+            "[CONTROL] Refreshed {}:{} emotes ({} changed)",
+            platform,
+            channel,
+            refresh.changed.len()
+        );
+        Ok(refresh)
+    }
+
+    async fn invalidate_channel_emotes(
+        &self,
+        platform: &str,
+        channel: &str,
+    ) -> Result<bool, String> {
+        let invalidated = self
+            .emote_system
+            .write()
+            .await
+            .invalidate_channel(platform, channel);
+        if invalidated {
+            println!("[CONTROL] Invalidated {}:{} channel emotes", platform, channel);
+        }
+        Ok(invalidated)
+    }
+
+    async fn show_test_window(&self, request: control::TestWindowRequest) -> Result<(), String> {
+        self.event_emitter
+            .submit(request.to_message(&self.config().system_users.diagnostics))
+            .map_err(|e| e.to_string())
+    }
+
+    async fn moderation_summary(&self) -> moderation::ModerationSummary {
+        moderation::ModerationLane::global().summary()
+    }
+
+    async fn search_messages(
+        &self,
+        query: history::HistoryQuery,
+    ) -> Result<Vec<connection::ChatMessage>, String> {
+        history::MessageHistory::global()
+            .search(&query)
+            .map_err(|e| e.to_string())
+    }
+
+    async fn replay_messages(&self, ids: Vec<String>) -> Result<usize, String> {
+        let history = history::MessageHistory::global();
+        let mut replayed = 0;
+        for message in ids.iter().filter_map(|id| history.get(id)) {
+            println!("[CONTROL] 🔁 Replaying message from {}", message.username);
+            self.event_emitter
+                .emit(AppEvent::ReplayMessage(Box::new(message)))
+                .map_err(|e| e.to_string())?;
+            replayed += 1;
+        }
+        Ok(replayed)
+    }
+
+    async fn rendering_status(&self) -> pause::RenderingStatus {
+        pause::RenderPause::global().status()
+    }
+
+    async fn set_rendering_paused(&self, paused: bool) -> Result<pause::RenderingStatus, String> {
+        let event = if paused {
+            AppEvent::PauseRendering
+        } else {
+            AppEvent::ResumeRendering
+        };
+        self.event_emitter.emit(event).map_err(|e| e.to_string())?;
+        // El bucle principal aplica el cambio al recibir el evento
+        Ok(pause::RenderingStatus {
+            paused,
+            ..pause::RenderPause::global().status()
+        })
+    }
+
+    async fn themes(&self) -> theme::named::ThemeStatus {
+        theme::named::ThemeSwitcher::global().status()
+    }
+
+    async fn switch_theme(&self, name: Option<String>) -> Result<theme::named::ThemeStatus, String> {
+        let display = theme::named::ThemeSwitcher::global().switch(name.as_deref())?;
+        self.apply_theme(display)
+    }
+
+    async fn next_theme(&self) -> Result<theme::named::ThemeStatus, String> {
+        let display = theme::named::ThemeSwitcher::global().next()?;
+        self.apply_theme(display)
+    }
+}
+
+#[async_trait::async_trait]
+impl ipc::IpcHandler for ControlBridge {
+    async fn show_message(&self, message: connection::ChatMessage) -> Result<(), String> {
+        println!("[IPC] 📨 Showing message from {}", message.username);
+        self.event_emitter
+            .submit(message)
+            .map_err(|e| e.to_string())
+    }
+
+    async fn set_rendering_paused(&self, paused: bool) -> Result<(), String> {
+        control::ControlHandler::set_rendering_paused(self, paused)
+            .await
+            .map(|_| ())
+    }
+
+    async fn switch_theme(&self, name: Option<String>) -> Result<(), String> {
+        control::ControlHandler::switch_theme(self, name)
+            .await
+            .map(|_| ())
+    }
+
+    async fn next_theme(&self) -> Result<(), String> {
+        control::ControlHandler::next_theme(self).await.map(|_| ())
+    }
+}
+
+impl ControlBridge {
+    /// Envía la apariencia del tema al bucle principal, que la aplica a las
+    /// ventanas siguientes
+    fn apply_theme(
+        &self,
+        display: config::DisplayConfig,
+    ) -> Result<theme::named::ThemeStatus, String> {
+        self.event_emitter
+            .emit(AppEvent::ThemeSwitched(Box::new(display)))
+            .map_err(|e| e.to_string())?;
+        Ok(theme::named::ThemeSwitcher::global().status())
+    }
+
+    /// Configuración vigente, con los cambios de la última recarga
+    fn config(&self) -> std::sync::RwLockReadGuard<'_, Config> {
+        self.config.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn connection_config(&self, id: &str) -> Result<config::ConnectionConfig, String> {
+        self.config()
+            .connections
+            .iter()
+            .find(|conn| conn.id == id)
+            .cloned()
+            .ok_or_else(|| format!("Connection '{}' not found", id))
+    }
+
+    /// Muestra en el overlay el nuevo modo para que se vea sin mirar la API
+    fn announce_command_mode(
+        &self,
+        connection: &config::ConnectionConfig,
+        mode: &command_mode::CommandMode,
+    ) {
+        println!("[CONTROL] Connection {} now shows {}", connection.id, mode);
+        let label = connection.display_name.as_deref().unwrap_or(&connection.id);
+        let notice = system_users::system_message(
+            &self.config().system_users.diagnostics,
+            "command_mode",
+            format!("💬 {}: {}", label, mode),
+            std::collections::HashMap::new(),
+        );
+        if let Err(e) = self.event_emitter.submit(notice) {
+            eprintln!("⚠️ Failed to show command mode notice: {}", e);
+        }
+    }
+}
+
 impl Clone for AppState {
     fn clone(&self) -> Self {
         Self {
             config: self.config.clone(),
+            shared_config: self.shared_config.clone(),
             platform_manager: self.platform_manager.clone(),
             emote_system: self.emote_system.clone(),
+            emote_renderer: self.emote_renderer.clone(),
             mapping_system: self.mapping_system.clone(),
             platform_factory: self.platform_factory.clone(),
             credential_manager: self.credential_manager.clone(),
             event_emitter: self.event_emitter.clone(),
             window_tracker: self.window_tracker.clone(),
+            app: self.app.clone(),
+            message_bridge: self.message_bridge.clone(),
+            demo_mode: self.demo_mode,
         }
     }
 }
 
-#[tokio::main(flavor = "current_thread")]
-#[cfg(unix)]
-use gdk::Screen;
-#[cfg(unix)]
-use gtk::{StyleContext, STYLE_PROVIDER_PRIORITY_APPLICATION};
-use rand::prelude::*;
+/// Reglas de `mapping.rules_path`; un fichero inválido se registra y se ignora
+fn load_mapping_rules(path: &std::path::Path) -> Option<mapping::rules::MappingRules> {
+    match mapping::rules::MappingRules::load(path) {
+        Ok(rules) => {
+            println!("[MAPPING] ✅ Loaded mapping rules from {:?}", path);
+            Some(rules)
+        }
+        Err(e) => {
+            eprintln!("[MAPPING] ⚠️ {}", e);
+            None
+        }
+    }
+}
 
-#[cfg(unix)]
-fn get_gdk_monitor() -> gdk::Monitor {
-    let display = gdk::Display::default().expect("Cannot get default display");
-    display
-        .monitor_at_point(0, 0)
-        .expect("Cannot get monitor at point")
+/// Aplica `config` a los componentes globales (`X::global()`); se llama al
+/// arrancar y con cada recarga
+fn configure_globals(config: &Config, credential_manager: &CredentialManager) {
+    // Captura de payloads crudos (desactivada por defecto)
+    capture::PayloadCapture::global().configure(config.capture.clone());
+    health::ErrorBudget::global().configure(config.error_budget.clone());
+    moderation::ModerationLane::global().configure(config.moderation_lane.clone());
+    history::MessageHistory::global().configure(config.history.clone());
+    layout::LayoutMemory::global().configure(&config.display.layout_file);
+    profanity::ProfanityFilter::global().configure(config.profanity.clone());
+    dedupe::MessageDeduplicator::global().configure(config.dedupe.clone());
+    hype::HypeAggregator::global().configure(config.hype.clone());
+    aggregate::ChatAggregator::global().configure(config.aggregate.clone());
+    pause::RenderPause::global().configure(config.pause.clone());
+    highlight::Highlighter::global().configure(config.highlight.clone());
+    sound::SoundPlayer::global().configure(config.sound.clone());
+    storage::MessageStore::global().configure(config.storage.clone());
+    translation::Translator::global().configure(config.translation.clone());
+    emotes::EmoteDownloader::global().configure(config.emotes.prefetch.clone());
+    emotes::EmoteQuality::global().configure(config.emotes.quality.clone());
+    avatars::AvatarStore::global()
+        .configure(&resolved_platforms(credential_manager, &config.platforms));
 }
 
-#[cfg(unix)]
-fn get_monitor_geometry() -> gdk::Rectangle {
-    let monitor = get_gdk_monitor();
-    monitor.geometry()
+/// Copia de las plataformas con los secretos `keyring:` ya sustituidos, como
+/// las reciben las conexiones; las que no se pueden resolver se omiten
+fn resolved_platforms(
+    credential_manager: &CredentialManager,
+    platforms: &std::collections::HashMap<String, config::PlatformConfig>,
+) -> std::collections::HashMap<String, config::PlatformConfig> {
+    platforms
+        .iter()
+        .filter_map(|(name, platform)| {
+            match credential_manager.resolve_credentials(name, &platform.credentials) {
+                Ok(credentials) => Some((
+                    name.clone(),
+                    config::PlatformConfig {
+                        credentials,
+                        ..platform.clone()
+                    },
+                )),
+                Err(e) => {
+                    eprintln!("⚠️ {} credentials: {}", name, e);
+                    None
+                }
+            }
+        })
+        .collect()
 }
 
-#[cfg(unix)]
-fn spawn_window(
-    username: &str,
-    message: &str,
-    emotes: &[crate::emotes::RenderedEmote],
-    position: (i32, i32),
-    monitor_geometry: gdk::Rectangle,
-) -> SpawnedWindow {
-    // Stub implementation for Unix window spawning
-    SpawnedWindow {
-        w: gtk::Window::new(gtk::WindowType::Toplevel),
-        created: Instant::now(),
-        progress: gtk::ProgressBar::new(),
+/// Emite `MessageRetracted` por cada mensaje que retira un aviso de moderación;
+/// `true` si el aviso no debe mostrarse (borrado de un único mensaje)
+fn emit_retractions(
+    event_emitter: &EventEmitter,
+    displayed: &DisplayedMessages,
+    message: &connection::ChatMessage,
+) -> bool {
+    let Some(retraction) = message.retraction() else {
+        return false;
+    };
+    let (ids, hidden) = match retraction {
+        connection::Retraction::Message(id) => (vec![id], true),
+        connection::Retraction::User(user) => (
+            displayed.ids_in(&message.platform, &message.channel, Some(&user)),
+            false,
+        ),
+        connection::Retraction::Channel => (
+            displayed.ids_in(&message.platform, &message.channel, None),
+            false,
+        ),
+    };
+    for id in ids {
+        if let Err(e) = event_emitter.emit(AppEvent::MessageRetracted(id)) {
+            eprintln!("⚠️ Failed to emit retraction event: {}", e);
+        }
     }
+    hidden
 }
 
-#[cfg(unix)]
-struct SpawnedWindow {
-    w: gtk::Window,
-    created: Instant,
-    progress: gtk::ProgressBar,
+/// Registra en el log un cambio de estado de una conexión
+fn log_connection_state(change: &reconnect::ConnectionStateChange) {
+    let line = format!(
+        "🔌 Connection {} ({}:{}) {}",
+        change.connection_id, change.platform, change.channel, change.state
+    );
+    match change.state {
+        reconnect::ConnectionState::Failed { .. }
+        | reconnect::ConnectionState::Reconnecting { .. } => eprintln!("{}", line),
+        _ => println!("{}", line),
+    }
+}
+
+/// Carga en el proveedor la hoja de estilos del tema activo (vacía sin tema)
+#[cfg(all(unix, not(target_os = "macos")))]
+fn load_theme_css(provider: &gtk::CssProvider, config: &Config) {
+    let css = config
+        .display
+        .theme_css(config.effective_font_size(1.0, 1.0));
+    if let Err(e) = provider.load_from_data(css.as_bytes()) {
+        eprintln!("⚠️ Cannot load theme styles: {}", e);
+    }
 }
 
 #[cfg(windows)]
@@ -506,42 +1814,421 @@ struct PlatformMessage {
     // Stub struct for Windows platform messages
 }
 
-#[cfg(unix)]
-fn handle_message(
-    message: crate::connection::ChatMessage,
-    position: (i32, i32),
-    monitor_geometry: gdk::Rectangle,
-    _config: &crate::config::Config,
-) -> SpawnedWindow {
-    // Stub implementation for message handling
-    SpawnedWindow {
-        w: crate::window::Window::new(gtk::WindowType::Toplevel, position.0, position.1),
-        created: Instant::now(),
-        progress: gtk::ProgressBar::new(),
+
+/// `overlay-native list-emotes <channel>` / `emotes dump --channel <channel>`
+async fn run_list_emotes_command(channel: &str, options: cli::EmoteListOptions) -> Result<()> {
+    let config = Config::load_default().unwrap_or_else(|e| {
+        eprintln!("[CONFIG] Error loading config: {}, using defaults", e);
+        Config::default()
+    });
+    let emote_system = EmoteSystem::new(config.emotes.clone());
+    let dump = emote_system.dump_emote_set(&options.platform, channel).await;
+
+    let rendered = if options.json {
+        serde_json::to_string_pretty(&dump)?
+    } else {
+        dump.to_table()
+    };
+    match options.output {
+        Some(path) => {
+            std::fs::write(&path, rendered)?;
+            println!("✅ Wrote {} emotes to {}", dump.emotes.len(), path.display());
+        }
+        None => print!("{}", rendered),
+    }
+
+    Ok(())
+}
+
+/// `overlay-native theme import <export.json> [--config <file>] [--dry-run]`
+fn run_theme_import_command(
+    export: &std::path::Path,
+    config_path: &std::path::Path,
+    dry_run: bool,
+) -> Result<()> {
+    let theme = theme::import::import_theme(&std::fs::read_to_string(export)?)?;
+    // Con la configuración ilegible se guardarían los valores por defecto
+    // encima de la del usuario
+    let mut config = Config::load_from_file(config_path)
+        .map_err(|e| anyhow::anyhow!("Cannot load {}: {}", config_path.display(), e))?;
+    theme.apply_to(&mut config);
+
+    println!("🎨 Imported {:?} theme from {}", theme.source, export.display());
+    if dry_run {
+        println!("{}", serde_json::to_string_pretty(&config.display)?);
+    } else {
+        config.save_to_file(config_path)?;
+        println!("✅ Saved to {}", config_path.display());
+    }
+
+    Ok(())
+}
+
+/// `overlay-native init [--preset <name>] [--config <file>] [--force]`
+///
+/// Asistente de configuración: sin `--preset` pregunta cuál usar.
+fn run_init_command(args: cli::InitArgs) -> Result<()> {
+    let config_path = &args.config.config;
+    if config_path.exists() && !args.force {
+        anyhow::bail!(
+            "{} already exists, use --force to overwrite it",
+            config_path.display()
+        );
+    }
+
+    let preset = match args.preset {
+        Some(preset) => preset,
+        None => prompt_display_preset()?,
+    };
+    let monitor = detect_monitor();
+    println!(
+        "🖥️ Monitor: {}x{} at {:.0}% scale",
+        monitor.width,
+        monitor.height,
+        monitor.scale_factor * 100.0
+    );
+
+    let config = presets::initial_config(&monitor, preset);
+    config.save_to_file(config_path)?;
+    println!(
+        "✅ Saved '{}' preset to {} (window {}px, font {}px)",
+        preset.name(),
+        config_path.display(),
+        config.display.window_size,
+        config.display.font_size
+    );
+    Ok(())
+}
+
+/// `overlay-native keyring set|remove <key>`
+fn run_keyring_command(command: cli::KeyringCommand) -> Result<()> {
+    use std::io::{BufRead, Write};
+
+    let credentials = CredentialManager::new();
+    match command {
+        cli::KeyringCommand::Set { key } => {
+            print!("Secret for {}: ", key);
+            std::io::stdout().flush()?;
+            let mut line = String::new();
+            std::io::stdin().lock().read_line(&mut line)?;
+            let secret = line.trim_end_matches(['\r', '\n']);
+            if secret.is_empty() {
+                anyhow::bail!("Empty secret, nothing stored");
+            }
+            credentials.store_secret(&key, secret)?;
+            println!(
+                "🔐 Stored {}; use \"{}{}\" in config.json",
+                key,
+                secrets::KEYRING_PREFIX,
+                key
+            );
+        }
+        cli::KeyringCommand::Remove { key } => {
+            if credentials.remove_secret(&key)? {
+                println!("🗑️ Removed {} from the keyring", key);
+            } else {
+                println!("ℹ️ {} was not in the keyring", key);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// `overlay-native export [--since 2h] [--format csv] [--output <file>]`
+fn run_export_command(args: cli::ExportArgs) -> Result<()> {
+    let config = Config::load_from_file(&args.config.config).unwrap_or_else(|e| {
+        eprintln!(
+            "[CONFIG] Error loading {}: {}, using defaults",
+            args.config.config.display(),
+            e
+        );
+        Config::default()
+    });
+    let range = storage::ExportRange {
+        since: match args.since {
+            Some(age) => Some(chrono::Utc::now() - chrono::Duration::from_std(age)?),
+            None => None,
+        },
+        until: None,
+    };
+    let output = args
+        .output
+        .unwrap_or_else(|| format!("chat-export.{}", args.format.name()).into());
+
+    let exported = storage::export(&config.storage.path, range, args.format, &output)?;
+    println!("✅ Exported {} messages to {}", exported, output.display());
+    Ok(())
+}
+
+/// `overlay-native validate-config [--config <file>]`
+fn run_validate_config_command(args: cli::ConfigArgs) -> Result<()> {
+    let config = Config::load_from_file(&args.config)
+        .map_err(|e| anyhow::anyhow!("{} is not valid: {}", args.config.display(), e))?;
+    println!(
+        "✅ {} is valid ({} platforms, {} connections enabled)",
+        args.config.display(),
+        config.get_enabled_platforms().len(),
+        config.get_enabled_connections().len()
+    );
+    Ok(())
+}
+
+/// `overlay-native connections status [--url <host:port>]`
+///
+/// Pregunta a un overlay en marcha por `GET /connections` de la API de control.
+async fn run_connections_status_command(args: cli::ConnectionsStatusArgs) -> Result<()> {
+    let control = Config::load_from_file(&args.config.config)
+        .map(|config| config.control)
+        .unwrap_or_default();
+    let address = args.url.unwrap_or(control.bind);
+    let url = if address.starts_with("http") {
+        format!("{}/connections", address.trim_end_matches('/'))
+    } else {
+        format!("http://{}/connections", address)
+    };
+
+    let mut request = reqwest::Client::new().get(&url);
+    if let Some(token) = &control.token {
+        request = request.bearer_auth(token);
+    }
+    let response = request.send().await.map_err(|e| {
+        anyhow::anyhow!(
+            "Cannot reach the control API at {} ({}); is the overlay running with control.enabled?",
+            url,
+            e
+        )
+    })?;
+    let body: serde_json::Value = response.error_for_status()?.json().await?;
+    let connections: Vec<control::ConnectionStatus> =
+        serde_json::from_value(body["connections"].clone())?;
+
+    if connections.is_empty() {
+        println!("No connections configured");
+    }
+    for connection in connections {
+        let state = match (&connection.state, connection.enabled) {
+            (Some(state), _) => state.to_string(),
+            (None, true) => "not started".to_string(),
+            (None, false) => "disabled".to_string(),
+        };
+        println!(
+            "{:<20} {:<8} {:<20} {}{}",
+            connection.id,
+            connection.platform,
+            connection.channel,
+            state,
+            if connection.command_mode.commands_only { " [commands only]" } else { "" }
+        );
+    }
+    Ok(())
+}
+
+/// `overlay-native test-window [--text <text>] [--user <name>]`
+///
+/// Muestra una ventana con el estilo de la configuración y sale cuando expira.
+async fn run_test_window_command(args: cli::TestWindowArgs) -> Result<()> {
+    let config = Config::load_from_file(&args.config.config).unwrap_or_else(|e| {
+        eprintln!(
+            "[CONFIG] Error loading {}: {}, using defaults",
+            args.config.config.display(),
+            e
+        );
+        Config::default()
+    });
+    let request = control::TestWindowRequest {
+        username: args.user,
+        message: args.text.unwrap_or_else(|| config.window.test_message.clone()),
+    };
+    let message = request.to_message(&config.system_users.diagnostics);
+    let duration = config.window_style_for_message(&message).duration;
+
+    #[cfg(all(unix, not(target_os = "macos")))]
+    {
+        gtk::init()?;
+        let styles = gtk::CssProvider::new();
+        styles.load_from_data(include_bytes!("../style.css"))?;
+        let theme_styles = gtk::CssProvider::new();
+        load_theme_css(&theme_styles, &config);
+        let screen = gdk::Screen::default().ok_or_else(|| anyhow::anyhow!("No screen"))?;
+        gtk::StyleContext::add_provider_for_screen(
+            &screen,
+            &styles,
+            gtk::STYLE_PROVIDER_PRIORITY_APPLICATION,
+        );
+        gtk::StyleContext::add_provider_for_screen(
+            &screen,
+            &theme_styles,
+            gtk::STYLE_PROVIDER_PRIORITY_APPLICATION + 1,
+        );
+    }
+
+    let monitor_geometry = PlatformBackend::monitor_geometry();
+    let (width, height) = PlatformBackend::monitor_size(&monitor_geometry);
+    let position = ((width - config.display.window_size) / 2, height / 2);
+    let mut window = handle_message(
+        message,
+        position,
+        monitor_geometry,
+        &config,
+        load::Fidelity::FULL,
+        None,
+    )
+    .await;
+    while window.created().elapsed() < duration && PlatformBackend::process_events() {
+        window.set_progress(window.created().elapsed().as_secs_f64() / duration.as_secs_f64());
+        tokio::time::sleep(std::time::Duration::from_millis(16)).await;
+    }
+    window.close();
+
+    println!("✅ Test window closed after {:?}", duration);
+    Ok(())
+}
+
+/// Pregunta por stdin el preset de pantalla (número o nombre)
+fn prompt_display_preset() -> Result<presets::DisplayPreset> {
+    use std::io::{BufRead, Write};
+
+    let default = presets::DisplayPreset::default();
+    println!("Choose a display preset:");
+    for (i, preset) in presets::DisplayPreset::ALL.iter().enumerate() {
+        println!("  {}) {:<20} {}", i + 1, preset.name(), preset.description());
+    }
+    print!("Preset [{}]: ", default.name());
+    std::io::stdout().flush()?;
+
+    let mut line = String::new();
+    std::io::stdin().lock().read_line(&mut line)?;
+    let answer = line.trim();
+    if answer.is_empty() {
+        return Ok(default);
+    }
+    answer
+        .parse::<usize>()
+        .ok()
+        .and_then(|n| n.checked_sub(1))
+        .and_then(|i| presets::DisplayPreset::ALL.get(i).copied())
+        .or_else(|| presets::DisplayPreset::from_name(answer))
+        .ok_or_else(|| anyhow::anyhow!("Unknown preset '{}'", answer))
+}
+
+/// Resolución física y escala DPI del monitor principal
+#[cfg(all(unix, not(target_os = "macos")))]
+fn detect_monitor() -> presets::MonitorInfo {
+    if gtk::init().is_err() {
+        return presets::MonitorInfo::default();
+    }
+    let monitor = get_gdk_monitor();
+    let geometry = monitor.geometry();
+    let scale = monitor.scale_factor();
+    // GDK da la geometría en píxeles lógicos
+    presets::MonitorInfo {
+        width: geometry.width() * scale,
+        height: geometry.height() * scale,
+        scale_factor: scale as f64,
+    }
+}
+
+/// Resolución física y escala DPI del monitor principal
+#[cfg(any(windows, target_os = "macos"))]
+fn detect_monitor() -> presets::MonitorInfo {
+    // La geometría está en píxeles lógicos, como en GDK
+    let geometry = PlatformBackend::monitor_geometry();
+    #[cfg(windows)]
+    let scale = windows::get_dpi_scale();
+    #[cfg(target_os = "macos")]
+    let scale = macos::get_dpi_scale();
+    presets::MonitorInfo {
+        width: (geometry.width as f64 * scale).round() as i32,
+        height: (geometry.height as f64 * scale).round() as i32,
+        scale_factor: scale,
     }
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
+    // Antes de crear ventanas o medir el monitor: tamaños en píxeles físicos
+    #[cfg(windows)]
+    windows::enable_dpi_awareness();
+
+    // Subcomandos de diagnóstico: no inician conexiones ni la interfaz principal
+    let run = match cli::Cli::parse().into_command() {
+        cli::Command::Run(run) => run,
+        cli::Command::TestWindow(args) => return run_test_window_command(args).await,
+        cli::Command::ValidateConfig(args) => return run_validate_config_command(args),
+        cli::Command::ListEmotes(args) => {
+            return run_list_emotes_command(&args.channel, args.options).await
+        }
+        cli::Command::Emotes(cli::EmotesCommand::Dump { channel, options }) => {
+            return run_list_emotes_command(&channel, options).await
+        }
+        cli::Command::Connections(cli::ConnectionsCommand::Status(args)) => {
+            return run_connections_status_command(args).await
+        }
+        cli::Command::Theme(cli::ThemeCommand::Import {
+            export,
+            config,
+            dry_run,
+        }) => return run_theme_import_command(&export, &config.config, dry_run),
+        cli::Command::Init(args) => return run_init_command(args),
+        cli::Command::Keyring(command) => return run_keyring_command(command),
+        cli::Command::Export(args) => return run_export_command(args),
+    };
+
+    // `overlay-native [run] --stdin`: mensajes NDJSON en lugar de conexiones
+    let stdin_mode = run.stdin;
+
     println!("🚀 Starting Overlay Native...");
+
+    // Primer arranque: tamaños ajustados al monitor en lugar de valores fijos
+    if !std::path::Path::new(config::DEFAULT_CONFIG_PATH).exists() {
+        let config = presets::initial_config(&detect_monitor(), presets::DisplayPreset::default());
+        match config.save_to_file(config::DEFAULT_CONFIG_PATH) {
+            Ok(()) => println!(
+                "[CONFIG] ✅ Generated {} for this monitor (run `overlay-native init` to pick another preset)",
+                config::DEFAULT_CONFIG_PATH
+            ),
+            Err(e) => eprintln!("[CONFIG] ⚠️ Could not generate initial config: {}", e),
+        }
+    }
     eprintln!("[DEBUG] Main function started");
 
     // Inicializar estado de la aplicación
     eprintln!("[DEBUG] Creating AppState...");
-    let state = AppState::new().await?;
+    let mut state = AppState::new().await?;
     eprintln!("[DEBUG] AppState created successfully");
 
-    // Inicializar plataformas
-    state.initialize_platforms().await?;
-
-    // Precargar emotes
-    state.preload_emotes().await?;
+    // `--demo` o `demo.enabled`: chat generado en lugar de conexiones
+    let demo_mode = !stdin_mode && (run.demo || state.config.demo.enabled);
+
+    if stdin_mode {
+        println!("📥 Reading NDJSON messages from stdin (platform connections disabled)");
+    } else if demo_mode {
+        // Los emotes globales del cache aparecen en los mensajes generados
+        state.preload_emotes().await?;
+        state.start_demo().await;
+    } else {
+        // Inicializar plataformas
+        state.initialize_platforms().await?;
+
+        // Precargar emotes
+        state.preload_emotes().await?;
+
+        // Iniciar conexiones
+        state.start_state_forwarder().await;
+        state.start_connections().await?;
+    }
 
-    // Iniciar conexiones
-    state.start_connections().await?;
+    // Sin servidor gráfico (SSH, CI) o con `--renderer terminal`: el chat se
+    // imprime en la terminal en lugar de abrir ventanas
+    if run.renderer.uses_terminal() {
+        if run.renderer == terminal::RendererKind::Auto {
+            println!("🖥️ No display server found, printing chat to the terminal");
+        }
+        return run_terminal(state, stdin_mode).await;
+    }
 
     // Configuración de UI
-    #[cfg(unix)]
+    #[cfg(all(unix, not(target_os = "macos")))]
     {
         gtk::init().unwrap();
 
@@ -554,56 +2241,31 @@ async fn main() -> Result<()> {
             &styles,
             gtk::STYLE_PROVIDER_PRIORITY_APPLICATION,
         );
-    }
 
-    // Obtener geometría del monitor
-    #[cfg(unix)]
-    let monitor_geometry = get_monitor_geometry();
-    #[cfg(windows)]
-    let monitor_geometry = get_monitor_geometry();
-
-    println!("Monitor geometry: {:#?}", monitor_geometry);
+    }
 
-    // Calcular posiciones para ventanas
-    let mut position_idx = 0;
-    let positions = {
-        let (monitor_width, monitor_height) = if cfg!(unix) {
-            let mw = ((monitor_geometry.width as i32
-                - state.config.display.monitor_margin as i32
-                - state.config.display.window_size as i32)
-                / state.config.display.grid_size as i32)
-                .max(0);
-            let mh = ((monitor_geometry.height as i32
-                - state.config.display.monitor_margin as i32
-                - state.config.display.window_size as i32)
-                / state.config.display.grid_size as i32)
-                .max(0);
-            (mw, mh)
-        } else {
-            let mw = ((monitor_geometry.width as i32
-                - state.config.display.monitor_margin as i32
-                - state.config.display.window_size as i32)
-                / state.config.display.grid_size as i32)
-                .max(0);
-            let mh = ((monitor_geometry.height as i32
-                - state.config.display.monitor_margin as i32
-                - state.config.display.window_size as i32)
-                / state.config.display.grid_size as i32)
-                .max(0);
-            (mw, mh)
-        };
+    // Tema integrado (p. ej. alto contraste) por encima de style.css; se
+    // recarga cuando la programación horaria cambia de tema
+    #[cfg(all(unix, not(target_os = "macos")))]
+    let theme_styles = {
+        let theme_styles = gtk::CssProvider::new();
+        load_theme_css(&theme_styles, &state.config);
+        gtk::StyleContext::add_provider_for_screen(
+            &gdk::Screen::default().expect("Cannot get main screen for styling"),
+            &theme_styles,
+            gtk::STYLE_PROVIDER_PRIORITY_APPLICATION + 1,
+        );
+        theme_styles
+    };
 
-        let mut p = Vec::new();
+    // Obtener geometría del monitor
+    let monitor_geometry = PlatformBackend::monitor_geometry();
 
-        for x in 0..state.config.display.grid_size {
-            for y in 0..state.config.display.grid_size {
-                p.push((x * monitor_width, y * monitor_height));
-            }
-        }
+    println!("Monitor geometry: {:#?}", monitor_geometry);
+    let monitor_size = PlatformBackend::monitor_size(&monitor_geometry);
 
-        p.shuffle(&mut thread_rng());
-        p
-    };
+    // Estrategia de colocación de `display.placement_mode`
+    let mut placement = placement::strategy_for(&state.config.display, monitor_size, cursor_position);
 
     // Window management is now handled by AsyncWindowManager
     // No need for manual window arrays
@@ -614,6 +2276,39 @@ async fn main() -> Result<()> {
     let mut timer = tokio::time::interval(tokio::time::Duration::from_millis(100)); // 10 FPS for progress updates (less flickering)
     let mut cleanup_counter = 0;
 
+    // Degradación automática bajo carga
+    let mut load_controller = load::LoadController::new(state.config.load.clone());
+
+    // Pausa del renderizado mientras el streamer está AFK
+    let mut idle_monitor = idle::IdleMonitor::new(state.config.idle.clone());
+    let mut last_idle_poll = std::time::Instant::now();
+
+    // Contador de mensajes descartados
+    let mut last_moderation_counter = std::time::Instant::now();
+
+    // Resumen periódico de estadísticas
+    let mut last_stats_summary = std::time::Instant::now();
+    let mut last_emote_usage_report = std::time::Instant::now();
+
+    // Temas por hora del día
+    let mut theme_scheduler = theme::schedule::ThemeScheduler::new(&state.config);
+    let mut last_theme_check: Option<std::time::Instant> = None;
+
+    // Mensaje fijado con !pin: centrado arriba, fuera de la rejilla
+    let mut pin_board = pin::PinBoard::new(state.config.pin.clone());
+    let pin_position = (
+        ((monitor_size.0 - state.config.display.window_size as i32) / 2).max(0),
+        state.config.display.monitor_margin as i32,
+    );
+    // Modo resumen: arriba a la derecha, también fuera de la rejilla
+    let aggregate_position = (
+        (monitor_size.0
+            - state.config.display.window_size as i32
+            - state.config.display.monitor_margin as i32)
+            .max(0),
+        state.config.display.monitor_margin as i32,
+    );
+
     println!("✅ Overlay Native started successfully!");
     println!(
         "📊 Connected to {} platforms",
@@ -626,166 +2321,805 @@ async fn main() -> Result<()> {
 
     eprintln!("[DEBUG] Initialization completed, about to enter main loop");
 
-    // Reset progress timer at main loop start for proper initial timing
-    #[cfg(windows)]
     // Progress updates are now handled by AsyncWindowManager
 
     // Start background tasks
+    state.start_message_pipeline();
     state.start_message_processor().await;
+
+    // API HTTP de control (desactivada por defecto)
+    if state.config.control.enabled {
+        let bridge = Arc::new(ControlBridge {
+            config: state.shared_config.clone(),
+            platform_manager: state.platform_manager.clone(),
+            emote_system: state.emote_system.clone(),
+            event_emitter: state.event_emitter.clone(),
+        });
+        if let Err(e) = control::serve(state.config.control.clone(), bridge).await {
+            eprintln!("⚠️ Failed to start control API: {}", e);
+        }
+    }
+
+    // Órdenes de programas externos por socket unix / named pipe
+    let mut ipc_server = None;
+    if state.config.ipc.enabled {
+        let bridge = Arc::new(ControlBridge {
+            config: state.shared_config.clone(),
+            platform_manager: state.platform_manager.clone(),
+            emote_system: state.emote_system.clone(),
+            event_emitter: state.event_emitter.clone(),
+        });
+        match ipc::serve(
+            state.config.ipc.clone(),
+            state.config.system_users.announcements.clone(),
+            bridge,
+        )
+        .await
+        {
+            Ok(server) => ipc_server = Some(server),
+            Err(e) => eprintln!("⚠️ Failed to start IPC listener: {}", e),
+        }
+    }
+
+    // Puente WebSocket para fuentes de navegador (desactivado por defecto)
+    if state.config.bridge.enabled {
+        if let Err(e) =
+            bridge::serve(state.config.bridge.clone(), state.message_bridge.clone()).await
+        {
+            eprintln!("⚠️ Failed to start WebSocket bridge: {}", e);
+        }
+    }
+
+    if stdin_mode {
+        start_stdin_reader(&state).await;
+    }
+
+    // Recarga en caliente de config.json (sincroniza conexiones salvo en modo
+    // demo, no aplica con --stdin)
+    let (config_tx, mut config_rx) = tokio::sync::mpsc::unbounded_channel();
+    let _config_watcher = if stdin_mode {
+        None
+    } else {
+        match Config::watch(config::DEFAULT_CONFIG_PATH, config_tx) {
+            Ok(watcher) => Some(watcher),
+            Err(e) => {
+                eprintln!("⚠️ Config hot-reload disabled: {}", e);
+                None
+            }
+        }
+    };
+
+    // Recarga en caliente del fichero de reglas de mapeo
+    let (rules_tx, mut rules_rx) = tokio::sync::mpsc::unbounded_channel();
+    let _rules_watcher = state.config.mapping.rules_path.as_ref().and_then(|path| {
+        mapping::rules::watch_rules(path, rules_tx)
+            .map_err(|e| eprintln!("⚠️ Mapping rules hot-reload disabled: {}", e))
+            .ok()
+    });
+    let rules_mapping = state.mapping_system.clone();
+    tokio::spawn(async move {
+        while let Some(rules) = rules_rx.recv().await {
+            rules_mapping.write().await.set_rules(rules);
+            println!("[MAPPING] ✅ Mapping rules reloaded");
+        }
+    });
+
+    // Emotes de 7TV añadidos, renombrados o quitados a mitad de directo
+    if let Some(sets) = state.emote_system.read().await.seventv_live_sets() {
+        let (changes_tx, mut changes_rx) = tokio::sync::mpsc::unbounded_channel();
+        tokio::spawn(emotes::seventv_events::follow_emote_sets(sets, changes_tx));
+        let live_emotes = state.emote_system.clone();
+        tokio::spawn(async move {
+            while let Some(change) = changes_rx.recv().await {
+                println!("[7TV] 🔄 {}", change);
+                let removed = live_emotes.write().await.apply_emote_set_change(&change).await;
+                if let Some(id) = removed {
+                    emotes::EmoteDownloader::global().forget(&[id]);
+                }
+            }
+        });
+    }
+
+    // `kill -USR1 <pid>` pausa o reanuda el renderizado
+    #[cfg(unix)]
+    {
+        let signal_emitter = state.event_emitter.clone();
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::user_defined1()) {
+            Ok(mut usr1) => {
+                tokio::spawn(async move {
+                    while usr1.recv().await.is_some() {
+                        let event = if pause::RenderPause::global().is_paused() {
+                            AppEvent::ResumeRendering
+                        } else {
+                            AppEvent::PauseRendering
+                        };
+                        if let Err(e) = signal_emitter.emit(event) {
+                            eprintln!("⚠️ Failed to emit pause event: {}", e);
+                        }
+                    }
+                });
+            }
+            Err(e) => eprintln!("⚠️ SIGUSR1 pause toggle disabled: {}", e),
+        }
+    }
+
+    spawn_shutdown_listener(state.event_emitter.clone());
+
+    // OBS: pausa fuera de las escenas del overlay y fuente de texto con el chat
+    if state.config.obs.enabled {
+        let (scenes_tx, mut scenes_rx) = tokio::sync::mpsc::unbounded_channel();
+        let (texts_tx, texts_rx) = tokio::sync::mpsc::unbounded_channel();
+        if state.config.obs.text_source.is_some() {
+            obs::ObsTextFeed::global().attach(state.config.obs.text_lines, texts_tx);
+        }
+        tokio::spawn(obs::follow_obs(state.config.obs.clone(), scenes_tx, texts_rx));
+
+        let obs_emitter = state.event_emitter.clone();
+        let mut pauser = obs::ScenePauser::new(state.config.obs.scenes.clone());
+        tokio::spawn(async move {
+            while let Some(scene) = scenes_rx.recv().await {
+                println!("[OBS] 🎬 Program scene: {}", scene);
+                let event = match pauser.scene_changed(&scene, pause::RenderPause::global().is_paused()) {
+                    Some(true) => AppEvent::PauseRendering,
+                    Some(false) => AppEvent::ResumeRendering,
+                    None => continue,
+                };
+                if let Err(e) = obs_emitter.emit(event) {
+                    eprintln!("⚠️ Failed to emit pause event: {}", e);
+                }
+            }
+        });
+    }
+
+    let config_emitter = state.event_emitter.clone();
+    tokio::spawn(async move {
+        while let Some(config) = config_rx.recv().await {
+            if let Err(e) = config_emitter.emit(AppEvent::ConfigReloaded(Box::new(config))) {
+                eprintln!("⚠️ Failed to emit config reload event: {}", e);
+            }
+        }
+    });
     println!("📡 Background services started");
 
     // Subscribe to events before the loop
     let mut event_rx = state.event_emitter.subscribe();
 
-    // Position management for window placement
-    let mut position_idx = 0;
+    // Últimos mensajes de la sesión anterior, tal y como se mostraron
+    let restored = history::MessageHistory::global().restore();
+    if !restored.is_empty() {
+        println!("🕘 Replaying {} messages from the last session", restored.len());
+    }
+    for message in restored {
+        if let Err(e) = state
+            .event_emitter
+            .emit(AppEvent::ReplayMessage(Box::new(message)))
+        {
+            eprintln!("⚠️ Failed to emit replay event: {}", e);
+        }
+    }
+    let mut last_history_save = std::time::Instant::now();
 
     println!("🚀 Starting main event loop...");
     loop {
-        let continue_loop;
-        #[cfg(unix)]
-        {
-            continue_loop = gtk::main_iteration_do(false);
-        }
-        #[cfg(windows)]
-        {
-            continue_loop = process_messages();
-        }
-        if !continue_loop {
+        if !PlatformBackend::process_events() {
             break;
         }
 
-        // Add small delay to prevent CPU hogging and allow Windows to process messages
+        // Add small delay to prevent CPU hogging; en Windows los mensajes de
+        // las ventanas los atiende su propio hilo
         #[cfg(windows)]
         {
             tokio::time::sleep(tokio::time::Duration::from_millis(10)).await; // ~100 FPS main loop, progress updates at 20 FPS
         }
 
+        // Los fundidos avanzan en cada tick; el resto de la limpieza cada 5
+        state.window_tracker.animate_fades().await;
+
         // Clean up expired windows every 5 frames (every 500ms)
         cleanup_counter += 1;
         if cleanup_counter >= 5 {
             state.window_tracker.cleanup_expired().await;
             cleanup_counter = 0;
+
+            if let Some(load_event) = load_controller.tick(std::time::Instant::now()) {
+                if let Err(e) = state.event_emitter.emit(AppEvent::LoadChanged(load_event)) {
+                    eprintln!("⚠️ Failed to emit load event: {}", e);
+                }
+            }
+
+            if pin_board.expire(std::time::Instant::now()) {
+                state.window_tracker.clear_pinned(app::CloseReason::Expired).await;
+            }
+
+            // Nube de palabras y participantes del modo resumen
+            match aggregate::ChatAggregator::global().refresh(std::time::Instant::now(), &state.config.system_users.announcements) {
+                aggregate::AggregateRefresh::Unchanged => {}
+                aggregate::AggregateRefresh::Show(summary) => {
+                    if !state.window_tracker.update_aggregate(&summary, monitor_geometry, &state.config).await {
+                        let win = handle_message((*summary).clone(), aggregate_position, monitor_geometry, &state.config, load::Fidelity::FULL, state.window_tracker.recycled().await).await;
+                        state.window_tracker.set_aggregate(win, &summary, aggregate_position).await;
+                    }
+                }
+                aggregate::AggregateRefresh::Clear => {
+                    state.window_tracker.clear_aggregate(app::CloseReason::Expired).await;
+                }
+            }
+
+            // Alertas de plataformas/proveedores que fallan repetidamente
+            for alert in health::ErrorBudget::global().take_alerts() {
+                state.app.observers().emit_error_budget_exceeded(&alert);
+                if state.config.error_budget.show_notification {
+                    let notice = alert.to_message(&state.config.system_users.diagnostics);
+                    let pos = placement.next_position(&state.window_tracker.occupied().await);
+                    let win = handle_message(notice.clone(), pos, monitor_geometry, &state.config, load::Fidelity::FULL, state.window_tracker.recycled().await).await;
+                    state.window_tracker.add_window(win, &notice, pos).await;
+                }
+            }
+
+            // Contador de descartes del carril de moderación
+            if last_moderation_counter.elapsed()
+                >= Duration::from_secs(state.config.moderation_lane.counter_interval_secs)
+            {
+                last_moderation_counter = std::time::Instant::now();
+                if let Some(counter) = moderation::ModerationLane::global()
+                    .take_counter_message(&state.config.system_users.diagnostics)
+                {
+                    let pos = placement.next_position(&state.window_tracker.occupied().await);
+                    let win = handle_message(counter.clone(), pos, monitor_geometry, &state.config, load::Fidelity::FULL, state.window_tracker.recycled().await).await;
+                    state.window_tracker.add_window(win, &counter, pos).await;
+                }
+            }
+
+            if last_history_save.elapsed() >= HISTORY_SAVE_INTERVAL {
+                last_history_save = std::time::Instant::now();
+                if let Err(e) = history::MessageHistory::global().save() {
+                    eprintln!("[HISTORY] ⚠️ Cannot save recent messages: {}", e);
+                }
+                if let Err(e) = layout::LayoutMemory::global().save() {
+                    eprintln!("[LAYOUT] ⚠️ Cannot save window offsets: {}", e);
+                }
+            }
+
+            if let Some(interval) = state.config.stats.summary_interval() {
+                if last_stats_summary.elapsed() >= interval {
+                    last_stats_summary = std::time::Instant::now();
+                    println!("{}", stats::Stats::global().snapshot().summary());
+                }
+            }
+
+            if let Some(interval) = state.config.emotes.usage.report_interval() {
+                if last_emote_usage_report.elapsed() >= interval {
+                    last_emote_usage_report = std::time::Instant::now();
+                    if let Some(report) = state.emote_system.read().await.usage_report() {
+                        println!("{}", report);
+                    }
+                }
+            }
+
+            // Cambiar de tema al entrar o salir de una franja horaria
+            if last_theme_check.map_or(true, |checked| checked.elapsed() >= theme_scheduler.check_interval()) {
+                last_theme_check = Some(std::time::Instant::now());
+                if let Some(display) = theme_scheduler.update(chrono::Local::now().time()) {
+                    state.config.display = display;
+                    println!("🎨 Scheduled theme: {}", theme_scheduler.describe_active());
+                    #[cfg(all(unix, not(target_os = "macos")))]
+                    load_theme_css(&theme_styles, &state.config);
+                }
+            }
+
+            // Consultar inactividad cada 2 segundos
+            if last_idle_poll.elapsed() >= Duration::from_secs(2) {
+                last_idle_poll = std::time::Instant::now();
+                match idle_monitor.poll() {
+                    Some(idle::IdleEvent::Paused) => {
+                        println!("💤 No input for {} minutes, pausing overlay rendering", state.config.idle.idle_minutes);
+                    }
+                    Some(idle::IdleEvent::Resumed { missed }) => {
+                        println!("👋 Activity detected, resuming overlay rendering ({} messages missed)", missed);
+                        if idle_monitor.wants_summary(missed) {
+                            let summary = idle::summary_message(missed, &state.config.system_users.announcements);
+                            let pos = placement.next_position(&state.window_tracker.occupied().await);
+                            let win = handle_message(summary.clone(), pos, monitor_geometry, &state.config, load_controller.fidelity(), state.window_tracker.recycled().await).await;
+                            state.window_tracker.add_window(win, &summary, pos).await;
+                        }
+                    }
+                    None => {}
+                }
+            }
         }
 
         // Process messages and timer ticks using event system
-        #[cfg(unix)]
         tokio::select! {
             event = event_rx.recv() => {
-                if let Ok(AppEvent::MessageReceived(processed_message)) = event {
-                    // Create window asynchronously and add to window manager
-                    let message_clone = processed_message.clone();
-                    let pos = positions[position_idx];
-                    let monitor_geo = monitor_geometry;
-                    let config_clone = state.config.clone();
-                    let window_tracker = state.window_tracker.clone();
-
-                    // Create window directly (simpler approach to avoid Send issues)
-                    let win = handle_message(message_clone, pos, monitor_geo, &config_clone);
-                    window_tracker.add_window(win).await;
-
-                    position_idx = (position_idx + 1) % positions.len();
-                }
-            },
-            _ = timer.tick() => {
-                // Timer tick - progress bars are updated in the cleanup loop above
-            }
-        }
+                match event {
+                    Ok(AppEvent::MessageReceived(received)) => {
+                        // Filtros, emotes y mapeo ya aplicados por el pool de procesamiento
+                        let Some(processed_message) = state.finish_processing(*received).await else {
+                            continue;
+                        };
+
+                        // Comandos `!overlay` de moderadores; se atienden también en pausa
+                        match commands::route(&processed_message, &state.config.commands) {
+                            commands::CommandAction::Display => {}
+                            commands::CommandAction::Run(command) => {
+                                state.run_chat_command(command, &processed_message.username).await;
+                                continue;
+                            }
+                            commands::CommandAction::Invalid(error) => {
+                                eprintln!("⚠️ Ignoring command from {}: {}", processed_message.username, error);
+                                continue;
+                            }
+                        }
+
+                        // Pausa manual: se muestra al reanudar
+                        if pause::RenderPause::global().hold(&processed_message) {
+                            continue;
+                        }
+
+                        // En pausa por inactividad solo se registra el mensaje
+                        if idle_monitor.is_paused() {
+                            idle_monitor.record_missed();
+                            println!("[IDLE] {}: {}", processed_message.username, processed_message.content);
+                            continue;
+                        }
+
+                        // Comandos !pin / !unpin de moderadores
+                        match pin_board.observe(&processed_message, std::time::Instant::now()) {
+                            pin::PinAction::Display => {}
+                            pin::PinAction::Pin(pinned) => {
+                                println!("📌 {} pinned a message from {}", processed_message.username, pinned.username);
+                                let win = handle_message((*pinned).clone(), pin_position, monitor_geometry, &state.config, load::Fidelity::FULL, state.window_tracker.recycled().await).await;
+                                state.window_tracker.set_pinned(win, &pinned, pin_position).await;
+                                continue;
+                            }
+                            pin::PinAction::Unpin => {
+                                state.window_tracker.clear_pinned(app::CloseReason::Unpinned).await;
+                                continue;
+                            }
+                            pin::PinAction::Ignore => continue,
+                        }
+
+                        // Modo resumen: el chat solo alimenta la nube de palabras
+                        if aggregate::ChatAggregator::global().observe(&processed_message, std::time::Instant::now()) {
+                            continue;
+                        }
+
+                        // Muestreo 1 de cada N cuando la carga es alta
+                        if !load_controller.should_display(std::time::Instant::now()) {
+                            continue;
+                        }
+                        let fidelity = load_controller.fidelity();
+
+                        // Sonido de alerta junto con la ventana (subs, raids, cheers, destacados)
+                        sound::SoundPlayer::global().play_for(&processed_message, state.config.sounds_muted(&processed_message));
 
-        #[cfg(windows)]
-        {
-            tokio::select! {
-                event = event_rx.recv() => {
-                    if let Ok(AppEvent::MessageReceived(processed_message)) = event {
                         // Create window asynchronously and add to window manager
                         let message_clone = processed_message.clone();
-                        let pos = positions[position_idx];
+                        // En modo feed todas las ventanas comparten posición
+                        let pos = if fidelity.feed_mode { placement.feed_position() } else { placement.next_position(&state.window_tracker.occupied().await) };
                         let monitor_geo = monitor_geometry;
                         let config_clone = state.config.clone();
                         let window_tracker = state.window_tracker.clone();
 
                         // Create window directly (simpler approach to avoid Send issues)
-                        let win = handle_message(message_clone, pos, monitor_geo, &config_clone);
-                        window_tracker.add_window(win).await;
-
-                        position_idx = (position_idx + 1) % positions.len();
+                        let win = handle_message(message_clone, pos, monitor_geo, &config_clone, fidelity, window_tracker.recycled().await).await;
+                        window_tracker.add_window(win, &processed_message, pos).await;
+                        if fidelity.feed_mode {
+                            window_tracker.trim_to(load_controller.feed_max_windows()).await;
+                        }
+                    }
+                    Ok(AppEvent::LoadChanged(load::LoadEvent::Degraded(reason))) => {
+                        eprintln!("⚠️ High load ({}), degrading overlay: no animations, feed mode, sampling messages", reason);
+                    }
+                    Ok(AppEvent::LoadChanged(load::LoadEvent::Restored)) => {
+                        println!("✅ Load back to normal, restoring full fidelity");
+                    }
+                    Ok(AppEvent::ConnectionStateChanged(change)) => {
+                        log_connection_state(&change);
+                    }
+                    Ok(AppEvent::ReplayMessage(message)) => {
+                        let message = *message;
+                        if pause::RenderPause::global().hold(&message) {
+                            continue;
+                        }
+                        if aggregate::ChatAggregator::global().observe(&message, std::time::Instant::now()) {
+                            continue;
+                        }
+                        let pos = placement.next_position(&state.window_tracker.occupied().await);
+                        let win = handle_message(message.clone(), pos, monitor_geometry, &state.config, load::Fidelity::FULL, state.window_tracker.recycled().await).await;
+                        state.window_tracker.add_window(win, &message, pos).await;
                     }
-                },
-                _ = timer.tick() => {
-                    // Timer tick for Windows - progress bars are updated in the cleanup loop above
+                    Ok(AppEvent::MessageRetracted(message_id)) => {
+                        if state.window_tracker.remove_message(&message_id).await {
+                            println!("🗑️ Removed retracted message {}", message_id);
+                        }
+                    }
+                    Ok(AppEvent::PauseRendering) => {
+                        if pause::RenderPause::global().pause() {
+                            println!("⏸️ Rendering paused, buffering messages");
+                        }
+                    }
+                    Ok(AppEvent::ResumeRendering) => {
+                        if let Some(resumed) = pause::RenderPause::global().resume() {
+                            println!("▶️ Rendering resumed ({} buffered, {} dropped)", resumed.messages.len(), resumed.dropped);
+                            for message in resumed.messages {
+                                if let Err(e) = state.event_emitter.emit(AppEvent::ReplayMessage(Box::new(message))) {
+                                    eprintln!("⚠️ Failed to emit replay event: {}", e);
+                                }
+                            }
+                        }
+                    }
+                    Ok(AppEvent::Shutdown) => break,
+                    Ok(AppEvent::ConfigReloaded(config)) => {
+                        state.apply_reloaded_config(*config).await;
+                        placement = placement::strategy_for(&state.config.display, monitor_size, cursor_position);
+                        idle_monitor.configure(state.config.idle.clone());
+                        theme_scheduler.reset(&state.config);
+                        last_theme_check = None;
+                        #[cfg(all(unix, not(target_os = "macos")))]
+                        load_theme_css(&theme_styles, &state.config);
+                    }
+                    Ok(AppEvent::ThemeSwitched(display)) => {
+                        println!("🎨 Theme: {}", display.active_theme.as_deref().unwrap_or("config"));
+                        state.config.display = *display;
+                        theme_scheduler.reset(&state.config);
+                        last_theme_check = None;
+                        #[cfg(all(unix, not(target_os = "macos")))]
+                        load_theme_css(&theme_styles, &state.config);
+                    }
+                    _ => {}
                 }
+            },
+            _ = timer.tick() => {
+                // Timer tick - progress bars are updated in the cleanup loop above
             }
         }
     }
 
     // Limpieza al salir
     println!("🔄 Shutting down...");
-    state
-        .platform_manager
-        .write()
-        .await
-        .shutdown()
-        .await
-        .map_err(|e| anyhow::anyhow!(e))?;
-    println!("✅ Shutdown complete");
+    if let Err(e) = history::MessageHistory::global().save() {
+        eprintln!("[HISTORY] ⚠️ Cannot save recent messages: {}", e);
+    }
+    if let Err(e) = layout::LayoutMemory::global().save() {
+        eprintln!("[LAYOUT] ⚠️ Cannot save window offsets: {}", e);
+    }
+    storage::MessageStore::global().shutdown();
+    state.window_tracker.close_all().await;
+    // Procesa los cierres pendientes para que no queden ventanas huérfanas
+    #[cfg(all(unix, not(target_os = "macos")))]
+    while gtk::events_pending() {
+        gtk::main_iteration_do(false);
+    }
+    #[cfg(windows)]
+    windows::flush_ui();
+    if let Some(server) = ipc_server {
+        server.shutdown();
+    }
+    disconnect_platforms(&state).await;
 
     Ok(())
 }
 
-// Funciones de manejo de mensajes y ventanas
-#[cfg(unix)]
-fn handle_message(
-    message: connection::ChatMessage,
-    position: (i32, i32),
-    monitor_geometry: gtk::Rectangle,
-    config: &Config,
-) -> SpawnedWindow {
-    // Convertir emotes al formato esperado por spawn_window
-    let emotes: Vec<twitch_irc::message::Emote> = message
-        .emotes
-        .iter()
-        .map(|e| {
-            let char_range = if let Some(pos) = e.positions.first() {
-                pos.start..pos.end
-            } else {
-                0..0
-            };
-            twitch_irc::message::Emote {
-                id: e.id.clone(),
-                code: e.name.clone(),
-                char_range,
+/// Bucle de `--renderer terminal`: sin ventanas, cada mensaje se imprime como
+/// una línea en la salida estándar
+async fn run_terminal(state: AppState, stdin_mode: bool) -> Result<()> {
+    state.start_message_pipeline();
+    state.start_message_processor().await;
+    if stdin_mode {
+        start_stdin_reader(&state).await;
+    }
+    spawn_shutdown_listener(state.event_emitter.clone());
+
+    let renderer = terminal::TerminalRenderer::new(state.config.display.clone());
+    let mut event_rx = state.event_emitter.subscribe();
+    println!("✅ Overlay Native started in terminal mode");
+
+    loop {
+        match event_rx.recv().await {
+            Ok(AppEvent::MessageReceived(received)) => {
+                if let Some(message) = state.finish_processing(*received).await {
+                    renderer.print(&message);
+                }
+            }
+            Ok(AppEvent::ReplayMessage(message)) => renderer.print(&message),
+            Ok(AppEvent::ConnectionStateChanged(change)) => log_connection_state(&change),
+            Ok(AppEvent::Shutdown) | Err(broadcast::error::RecvError::Closed) => break,
+            Ok(_) | Err(broadcast::error::RecvError::Lagged(_)) => {}
+        }
+    }
+
+    println!("🔄 Shutting down...");
+    if let Err(e) = history::MessageHistory::global().save() {
+        eprintln!("[HISTORY] ⚠️ Cannot save recent messages: {}", e);
+    }
+    storage::MessageStore::global().shutdown();
+    disconnect_platforms(&state).await;
+
+    Ok(())
+}
+
+/// Entrada NDJSON: el hilo termina al cerrar stdin, el overlay sigue abierto
+async fn start_stdin_reader(state: &AppState) {
+    let sender = state.platform_manager.read().await.get_sender();
+    std::thread::spawn(move || {
+        let forwarded = platforms::forward_ndjson(std::io::stdin().lock(), &sender);
+        println!("📥 stdin closed after {} messages", forwarded);
+    });
+}
+
+/// Ctrl+C (y SIGTERM/SIGHUP en unix) cierran el overlay ordenadamente; un
+/// segundo Ctrl+C sale sin esperar a la limpieza
+fn spawn_shutdown_listener(emitter: Arc<EventEmitter>) {
+    tokio::spawn(async move {
+        wait_for_shutdown_signal().await;
+        println!("🛑 Shutdown requested (Ctrl+C again to force)");
+        if let Err(e) = emitter.emit(AppEvent::Shutdown) {
+            eprintln!("⚠️ Failed to emit shutdown event: {}", e);
+        }
+        wait_for_shutdown_signal().await;
+        eprintln!("⚠️ Forced exit");
+        std::process::exit(130);
+    });
+}
+
+/// Informes de emotes y salida de los canales al cerrar
+async fn disconnect_platforms(state: &AppState) {
+    if let Some(report) = state.emote_system.read().await.blocked_emote_report() {
+        println!("{}", report);
+    }
+    if let Some(report) = state.emote_system.read().await.usage_report() {
+        println!("{}", report);
+    }
+    let disconnect = async { state.platform_manager.write().await.shutdown().await };
+    match tokio::time::timeout(SHUTDOWN_TIMEOUT, disconnect).await {
+        Ok(Ok(())) => println!("👋 Left all channels"),
+        Ok(Err(e)) => eprintln!("⚠️ Some platforms did not disconnect cleanly: {}", e),
+        Err(_) => eprintln!(
+            "⚠️ Platforms did not disconnect within {}s",
+            SHUTDOWN_TIMEOUT.as_secs()
+        ),
+    }
+    println!("✅ Shutdown complete");
+}
+
+/// Tiempo máximo para salir de los canales y desconectar al cerrar
+const SHUTDOWN_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// Espera a Ctrl+C; en unix también a SIGTERM y SIGHUP, y en Windows al
+/// cierre de la consola
+async fn wait_for_shutdown_signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+        match (signal(SignalKind::terminate()), signal(SignalKind::hangup())) {
+            (Ok(mut term), Ok(mut hup)) => {
+                tokio::select! {
+                    _ = tokio::signal::ctrl_c() => {}
+                    _ = term.recv() => {}
+                    _ = hup.recv() => {}
+                }
+            }
+            _ => {
+                let _ = tokio::signal::ctrl_c().await;
+            }
+        }
+    }
+    #[cfg(windows)]
+    {
+        match tokio::signal::windows::ctrl_close() {
+            Ok(mut close) => {
+                tokio::select! {
+                    _ = tokio::signal::ctrl_c() => {}
+                    _ = close.recv() => {}
+                }
+            }
+            Err(_) => {
+                let _ = tokio::signal::ctrl_c().await;
             }
+        }
+    }
+}
+
+// Funciones de manejo de mensajes y ventanas
+
+/// Emotes en el formato que esperan los renderers, con los zero-width y los
+/// modificadores ya compuestos sobre su emote base en el enriquecimiento
+fn window_emotes(message: &connection::ChatMessage) -> Vec<twitch_irc::message::Emote> {
+    emotes::layout_emotes(&message.content, &message.emotes)
+        .into_iter()
+        .map(|stack| twitch_irc::message::Emote {
+            id: stack.drawn_id(),
+            code: stack.base.name.clone(),
+            char_range: stack.char_range,
         })
-        .collect();
+        .collect()
+}
 
-    crate::windows::WindowsWindow::new(&message.username, &message.content, &emotes, position)
+/// Lo que hace falta para componer el mensaje entero en un bitmap
+/// (`display.precomposed`)
+fn bitmap_spec(
+    rendered: &theme::template::RenderedWindow,
+    emotes: &[twitch_irc::message::Emote],
+    message: &connection::ChatMessage,
+    style: &config::WindowStyle,
+    config: &Config,
+) -> emotes::MessageBitmapSpec {
+    emotes::MessageBitmapSpec {
+        header: rendered.header.clone(),
+        body: rendered.body.clone(),
+        emotes: emotes.to_vec(),
+        badges: message
+            .badges
+            .iter()
+            .map(|badge| assets::badge(&badge.name).bytes.to_vec())
+            .collect(),
+        avatar: style.avatar.clone(),
+        card: style.card.clone(),
+        palette: style
+            .palette
+            .unwrap_or_else(|| config.display.configured_palette()),
+        scale: style.scale,
+    }
 }
 
-#[cfg(windows)]
-#[cfg(windows)]
-fn handle_message(
-    message: crate::connection::ChatMessage,
+/// Cabecera, cuerpo, emotes y estilo con los que se dibuja un mensaje
+struct PreparedMessage {
+    rendered: theme::template::RenderedWindow,
+    emotes: Vec<twitch_irc::message::Emote>,
+    style: config::WindowStyle,
+    /// Posición ya ajustada con el desplazamiento de la conexión
     position: (i32, i32),
-    _monitor_geometry: crate::windows::WindowGeometry,
-    _config: &crate::config::Config,
-) -> WindowsWindow {
-    // Convertir emotes al formato esperado por WindowsWindow
-    let emotes: Vec<twitch_irc::message::Emote> = message
-        .emotes
-        .iter()
-        .map(|e| {
-            let char_range = if let Some(pos) = e.positions.first() {
-                pos.start..pos.end
-            } else {
-                0..0
-            };
-            twitch_irc::message::Emote {
-                id: e.id.clone(),
-                code: e.name.clone(),
-                char_range,
+}
+
+async fn prepare_message(
+    message: &connection::ChatMessage,
+    position: (i32, i32),
+    monitor_geometry: MonitorGeometry,
+    config: &Config,
+    fidelity: load::Fidelity,
+) -> PreparedMessage {
+    let rendered = config.display.template.render(message);
+    let emotes = rendered.place_emotes(window_emotes(message));
+
+    let mut style = config.window_style_for_message(message);
+    style.animated &= fidelity.animations;
+    if config.display.show_avatars {
+        style.avatar = avatars::AvatarStore::global().avatar_for(message);
+    }
+    // Desplazamiento de la conexión arrastrado a mano (`display.click_through`)
+    let position = layout::LayoutMemory::global().adjust(
+        style.connection_id.as_deref(),
+        position,
+        PlatformBackend::monitor_size(&monitor_geometry),
+        config.display.window_size,
+    );
+    if config.display.precomposed {
+        let spec = bitmap_spec(&rendered, &emotes, message, &style, config);
+        style.bitmap =
+            PlatformBackend::precompose(spec, &style, position, &config.display.font_family).await;
+    }
+    PreparedMessage {
+        rendered,
+        emotes,
+        style,
+        position,
+    }
+}
+
+async fn handle_message(
+    message: connection::ChatMessage,
+    position: (i32, i32),
+    monitor_geometry: MonitorGeometry,
+    config: &Config,
+    fidelity: load::Fidelity,
+    recycled: Option<PlatformRecycledWindow>,
+) -> PlatformWindow {
+    let prepared = prepare_message(&message, position, monitor_geometry, config, fidelity).await;
+    PlatformBackend::spawn(
+        &prepared.rendered.header,
+        &prepared.rendered.body,
+        &prepared.emotes,
+        prepared.position,
+        monitor_geometry,
+        &prepared.style,
+        recycled,
+    )
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn processor(app: app::OverlayApp) -> MessageProcessor {
+        let mut config = Config::default();
+        // Sin emotes de terceros no se hace ninguna petición
+        config.emotes.enable_global_emotes = false;
+        config.emotes.enable_channel_emotes = false;
+        config.emotes.enable_bttv = false;
+        config.emotes.enable_ffz = false;
+        config.emotes.enable_7tv = false;
+        let emote_system = EmoteSystem::new(config.emotes.clone());
+        let emote_renderer =
+            emotes::EmoteRenderer::new(emotes::default_cache_dir(), emote_system.http_client().clone());
+        MessageProcessor {
+            config: Arc::new(config.clone()),
+            platform_manager: Arc::new(RwLock::new(PlatformManager::new())),
+            emote_system: Arc::new(RwLock::new(emote_system)),
+            emote_renderer: Arc::new(emote_renderer),
+            mapping_system: app.mapping_system(),
+            app: Arc::new(app),
+        }
+    }
+
+    #[test]
+    fn test_displayed_messages_are_retracted_by_user_and_channel() {
+        let displayed = DisplayedMessages::default();
+        displayed.record(&connection::ChatMessage::test("troll", "spam").with_id("1"));
+        displayed.record(&connection::ChatMessage::test("viewer", "hi").with_id("2"));
+        displayed.record(&connection::ChatMessage::test("troll", "other").on("twitch", "other"));
+
+        assert_eq!(displayed.ids_in("twitch", "chan", Some("Troll")), vec!["1"]);
+        assert_eq!(displayed.ids_in("twitch", "chan", None), vec!["1", "2"]);
+
+        for id in 0..DISPLAYED_MESSAGES {
+            displayed.record(&connection::ChatMessage::test("viewer", "hi").with_id(id.to_string()));
+        }
+        assert!(displayed.ids_in("twitch", "chan", Some("troll")).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_process_keeps_mapped_content() {
+        let rules = mapping::rules::MappingRules::parse(
+            r##"{ "content_transforms": [{ "type": "regex_replace", "pattern": "\\d+", "replacement": "#" }] }"##,
+            mapping::rules::RulesFormat::Json,
+        )
+        .unwrap();
+        let app = app::OverlayApp::new(Config::default());
+        app.mapping_system().write().await.set_rules(rules);
+
+        let message = connection::ChatMessage {
+            display_name: Some("Viewer".to_string()),
+            ..connection::ChatMessage::test("viewer", "room 101")
+        };
+        let processor = processor(app);
+        let screened = processor.screen(message).await.unwrap();
+        let processed = processor.enrich(screened).await.unwrap();
+        assert_eq!(processed.message.content, "room #");
+        assert_eq!(processed.message.username, "viewer");
+        assert_eq!(processed.message.display_name.as_deref(), Some("Viewer"));
+        assert_eq!(processed.mapped.unwrap().content, "room #");
+    }
+
+    #[cfg(feature = "plugin-api")]
+    #[tokio::test]
+    async fn test_registered_transform_changes_displayed_message() {
+        struct Shout;
+
+        impl mapping::MessageTransform for Shout {
+            fn transform(
+                &mut self,
+                mut message: mapping::StandardizedMessage,
+            ) -> std::result::Result<mapping::StandardizedMessage, mapping::MappingError> {
+                message.content = message.content.to_uppercase();
+                Ok(message)
             }
-        })
-        .collect();
 
-    WindowsWindow::new(&message.username, &message.content, &emotes, position)
+            fn name(&self) -> &str {
+                "shout"
+            }
+        }
+
+        let app = app::OverlayApp::new(Config::default());
+        let processor = processor(app);
+        let message = connection::ChatMessage::test("viewer", "hello");
+        let screened = processor.screen(message.clone()).await.unwrap();
+        assert_eq!(processor.enrich(screened).await.unwrap().message.content, "hello");
+
+        // Con el procesador ya creado, como haría un plugin con el overlay en marcha
+        processor.app.register_transform(Box::new(Shout)).await;
+        let screened = processor.screen(message.with_id("2")).await.unwrap();
+        assert_eq!(processor.enrich(screened).await.unwrap().message.content, "HELLO");
+    }
 }
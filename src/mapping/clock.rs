@@ -0,0 +1,315 @@
+//! Normalización de timestamps con tolerancia a desfase de reloj
+//!
+//! Los timestamps de Kick/YouTube a veces llegan con segundos de desfase respecto
+//! al reloj local. Por cada conexión se estima el desfase como el mínimo de
+//! `recibido - enviado` sobre las últimas muestras (la latencia de red nunca es
+//! negativa, así que el mínimo se acerca al desfase real) y se corrige el
+//! timestamp para que sea comparable con la hora local.
+
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant, SystemTime};
+
+use crate::config::{ConnectionConfig, TimestampConfig};
+use crate::connection::ChatMessage;
+
+/// Clave en `custom_data` con el timestamp original de la plataforma (ms Unix)
+pub const REMOTE_TIMESTAMP_KEY: &str = "remote_timestamp_ms";
+/// Clave en `custom_data` con el desfase aplicado (ms)
+pub const CLOCK_OFFSET_KEY: &str = "clock_offset_ms";
+
+/// Estimador de desfase de reloj por conexión (`plataforma:canal`)
+#[derive(Debug)]
+pub struct ClockOffsetEstimator {
+    samples: usize,
+    max_offset: Duration,
+    offsets: HashMap<String, VecDeque<i64>>,
+}
+
+impl ClockOffsetEstimator {
+    pub fn new(config: &TimestampConfig) -> Self {
+        Self {
+            samples: config.offset_samples.max(1),
+            max_offset: Duration::from_secs(config.max_offset_secs),
+            offsets: HashMap::new(),
+        }
+    }
+
+    /// Aplica una configuración recargada conservando las muestras recientes
+    pub fn reconfigure(&mut self, config: &TimestampConfig) {
+        self.samples = config.offset_samples.max(1);
+        self.max_offset = Duration::from_secs(config.max_offset_secs);
+        for window in self.offsets.values_mut() {
+            let excess = window.len().saturating_sub(self.samples);
+            window.drain(..excess);
+        }
+    }
+
+    /// Registra una muestra y devuelve el desfase estimado en ms
+    pub fn observe(&mut self, key: &str, remote: SystemTime, received: SystemTime) -> i64 {
+        let sample = signed_millis_between(remote, received);
+        // Timestamps absurdos (zona horaria mal parseada, etc.) no se usan
+        if sample.unsigned_abs() > self.max_offset.as_millis() as u64 {
+            return self.offset(key).unwrap_or(0);
+        }
+
+        let window = self.offsets.entry(key.to_string()).or_default();
+        if window.len() >= self.samples {
+            window.pop_front();
+        }
+        window.push_back(sample);
+        self.offset(key).unwrap_or(0)
+    }
+
+    /// Desfase estimado actual de una conexión
+    pub fn offset(&self, key: &str) -> Option<i64> {
+        self.offsets.get(key)?.iter().copied().min()
+    }
+
+    /// Corrige `message.timestamp` a hora local (nunca posterior a la recepción)
+    pub fn normalize(&mut self, message: &mut ChatMessage, received: SystemTime) {
+        let key = format!("{}:{}", message.platform.to_lowercase(), message.channel);
+        let remote = message.timestamp;
+        let offset = self.observe(&key, remote, received);
+
+        let corrected = apply_offset(remote, offset).min(received);
+        message.timestamp = corrected;
+
+        let custom_data = &mut message.metadata.custom_data;
+        custom_data.insert(REMOTE_TIMESTAMP_KEY.to_string(), unix_millis(remote).into());
+        custom_data.insert(CLOCK_OFFSET_KEY.to_string(), offset.into());
+    }
+}
+
+/// Cola de visualización ordenada por timestamp corregido
+///
+/// Retiene cada mensaje `reorder_window` para que uno con timestamp anterior que
+/// llegue tarde (p.ej. de otra conexión) pueda adelantarlo. Con
+/// `display_offset_ms` por conexión se retienen además los chats de los
+/// co-streams con menos latencia; como un mensaje no puede mostrarse antes de
+/// llegar, los desplazamientos negativos retrasan a todas las demás conexiones.
+#[derive(Debug)]
+pub struct DisplayQueue {
+    reorder_window: Duration,
+    /// Retención extra por conexión (`plataforma:canal`)
+    holds: HashMap<String, Duration>,
+    /// Retención de las conexiones sin desplazamiento
+    baseline: Duration,
+    pending: Vec<PendingDisplay>,
+}
+
+#[derive(Debug)]
+struct PendingDisplay {
+    ready_at: Instant,
+    /// Timestamp desplazado con el que se ordena
+    order: SystemTime,
+    message: ChatMessage,
+}
+
+impl DisplayQueue {
+    pub fn new(config: &TimestampConfig) -> Self {
+        Self {
+            reorder_window: Duration::from_millis(config.reorder_window_ms),
+            holds: HashMap::new(),
+            baseline: Duration::ZERO,
+            pending: Vec::new(),
+        }
+    }
+
+    /// Aplica los `display_offset_ms` de las conexiones habilitadas
+    pub fn with_connection_offsets(mut self, connections: &[ConnectionConfig]) -> Self {
+        self.set_connection_offsets(connections);
+        self
+    }
+
+    /// Aplica una configuración recargada; los mensajes ya retenidos salen
+    /// cuando estaba previsto
+    pub fn reconfigure(&mut self, config: &TimestampConfig, connections: &[ConnectionConfig]) {
+        self.reorder_window = Duration::from_millis(config.reorder_window_ms);
+        self.set_connection_offsets(connections);
+    }
+
+    fn set_connection_offsets(&mut self, connections: &[ConnectionConfig]) {
+        let offsets: Vec<(String, i64)> = connections
+            .iter()
+            .filter(|conn| conn.enabled && conn.display_offset_ms != 0)
+            .map(|conn| {
+                let key = connection_key(&conn.platform, &conn.channel);
+                (key, conn.display_offset_ms)
+            })
+            .collect();
+
+        let baseline = offsets
+            .iter()
+            .map(|(_, offset)| -offset)
+            .max()
+            .unwrap_or(0)
+            .max(0);
+        self.baseline = Duration::from_millis(baseline as u64);
+        self.holds = offsets
+            .into_iter()
+            .map(|(key, offset)| (key, Duration::from_millis((baseline + offset) as u64)))
+            .collect();
+    }
+
+    fn hold_for(&self, message: &ChatMessage) -> Duration {
+        self.holds
+            .get(&connection_key(&message.platform, &message.channel))
+            .copied()
+            .unwrap_or(self.baseline)
+    }
+
+    pub fn push(&mut self, message: ChatMessage, now: Instant) {
+        let hold = self.hold_for(&message);
+        self.pending.push(PendingDisplay {
+            ready_at: now + self.reorder_window + hold,
+            order: message.timestamp + hold,
+            message,
+        });
+    }
+
+    pub fn has_pending(&self) -> bool {
+        !self.pending.is_empty()
+    }
+
+    /// Instante en que termina la retención más próxima
+    pub fn next_ready_at(&self) -> Option<Instant> {
+        self.pending.iter().map(|pending| pending.ready_at).min()
+    }
+
+    /// Mensajes cuya retención terminó, ordenados por timestamp desplazado
+    pub fn drain_ready(&mut self, now: Instant) -> Vec<ChatMessage> {
+        let (mut ready, pending): (Vec<_>, Vec<_>) = self
+            .pending
+            .drain(..)
+            .partition(|pending| pending.ready_at <= now);
+        self.pending = pending;
+
+        ready.sort_by_key(|pending| pending.order);
+        ready.into_iter().map(|pending| pending.message).collect()
+    }
+}
+
+fn connection_key(platform: &str, channel: &str) -> String {
+    format!(
+        "{}:{}",
+        platform.to_lowercase(),
+        channel.trim_start_matches('#').to_lowercase()
+    )
+}
+
+fn signed_millis_between(from: SystemTime, to: SystemTime) -> i64 {
+    match to.duration_since(from) {
+        Ok(d) => d.as_millis() as i64,
+        Err(e) => -(e.duration().as_millis() as i64),
+    }
+}
+
+fn apply_offset(time: SystemTime, offset_ms: i64) -> SystemTime {
+    let delta = Duration::from_millis(offset_ms.unsigned_abs());
+    if offset_ms >= 0 {
+        time + delta
+    } else {
+        time - delta
+    }
+}
+
+fn unix_millis(time: SystemTime) -> i64 {
+    signed_millis_between(SystemTime::UNIX_EPOCH, time)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_offset_is_minimum_delay_and_corrects_fast_clocks() {
+        let mut estimator = ClockOffsetEstimator::new(&TimestampConfig::default());
+        let local = SystemTime::now();
+
+        // Reloj remoto 5 s adelantado, latencia variable
+        let skew = Duration::from_secs(5);
+        estimator.observe("kick:foo", local + skew - Duration::from_millis(200), local);
+        estimator.observe("kick:foo", local + skew - Duration::from_millis(50), local);
+        assert_eq!(estimator.offset("kick:foo"), Some(-4950));
+
+        let mut msg = ChatMessage::test("user", "hi")
+            .with_id("foo")
+            .on("kick", "foo")
+            .at(local + skew);
+        estimator.normalize(&mut msg, local);
+        assert!(msg.timestamp <= local);
+        assert!(local.duration_since(msg.timestamp).unwrap() < Duration::from_millis(100));
+        assert!(msg.metadata.custom_data.contains_key(REMOTE_TIMESTAMP_KEY));
+    }
+
+    #[test]
+    fn test_outlier_samples_are_ignored() {
+        let mut estimator = ClockOffsetEstimator::new(&TimestampConfig::default());
+        let local = SystemTime::now();
+        estimator.observe("kick:foo", local - Duration::from_millis(100), local);
+        estimator.observe("kick:foo", local - Duration::from_secs(3 * 3600), local);
+        assert_eq!(estimator.offset("kick:foo"), Some(100));
+    }
+
+    #[test]
+    fn test_display_queue_orders_by_corrected_time() {
+        let mut queue = DisplayQueue::new(&TimestampConfig::default());
+        let start = Instant::now();
+        let base = SystemTime::now();
+
+        let late = ChatMessage::test("user", "hi")
+            .with_id("late")
+            .on("kick", "late")
+            .at(base + Duration::from_millis(20));
+        let early = ChatMessage::test("user", "hi")
+            .with_id("early")
+            .on("kick", "early")
+            .at(base);
+        queue.push(late, start);
+        queue.push(early, start + Duration::from_millis(10));
+        assert!(queue.drain_ready(start).is_empty());
+        let window = Duration::from_millis(TimestampConfig::default().reorder_window_ms);
+        assert_eq!(queue.next_ready_at(), Some(start + window));
+
+        let out = queue.drain_ready(start + Duration::from_secs(1));
+        let ids: Vec<_> = out.iter().map(|m| m.id.as_str()).collect();
+        assert_eq!(ids, vec!["early", "late"]);
+        assert!(!queue.has_pending());
+        assert_eq!(queue.next_ready_at(), None);
+    }
+
+    #[test]
+    fn test_negative_connection_offset_delays_the_other_streams() {
+        let mut connections = crate::config::Config::default().connections;
+        for conn in &mut connections {
+            conn.enabled = true;
+            if conn.platform == "kick" {
+                conn.display_offset_ms = -500;
+            }
+        }
+        let mut queue = DisplayQueue::new(&TimestampConfig::default())
+            .with_connection_offsets(&connections);
+        let start = Instant::now();
+        let base = SystemTime::now();
+
+        let twitch = ChatMessage::test("user", "hi")
+            .with_id("#GohuntLeo")
+            .on("twitch", "#GohuntLeo")
+            .at(base);
+        queue.push(twitch, start);
+        let kick = ChatMessage::test("user", "hi")
+            .with_id("spreen")
+            .on("kick", "spreen")
+            .at(base + Duration::from_millis(100));
+        queue.push(kick, start);
+
+        // Kick sale tras la ventana de reordenación; Twitch 500 ms después
+        let out = queue.drain_ready(start + Duration::from_millis(250));
+        assert_eq!(out.len(), 1);
+        assert_eq!(out[0].platform, "kick");
+        assert!(queue.has_pending());
+
+        let out = queue.drain_ready(start + Duration::from_millis(750));
+        assert_eq!(out[0].platform, "twitch");
+    }
+}
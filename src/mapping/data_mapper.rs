@@ -54,6 +54,20 @@ impl DataMapper {
         self.message_type_mappings
             .insert("twitch".to_string(), twitch_message_types);
 
+        // Mapeos de tipos de evento para Kick
+        let mut kick_message_types = HashMap::new();
+        kick_message_types.insert("message".to_string(), MappedMessageType::Normal);
+        kick_message_types.insert(
+            "pinned_message_created".to_string(),
+            MappedMessageType::Pinned,
+        );
+        kick_message_types.insert(
+            "pinned_message_deleted".to_string(),
+            MappedMessageType::Unpinned,
+        );
+        self.message_type_mappings
+            .insert("kick".to_string(), kick_message_types);
+
         // Mapeos de fuentes de emote
         self.emote_source_mappings
             .insert("twitch".to_string(), EmoteSource::Twitch);
@@ -71,7 +85,7 @@ impl DataMapper {
 
     /// Mapea un mensaje estandarizado a un mensaje completamente mapeado
     pub async fn map_data(
-        &mut self,
+        &self,
         standardized: StandardizedMessage,
     ) -> Result<MappedMessage, crate::mapping::MappingError> {
         // Aplicar mapeos personalizados si existen
@@ -100,6 +114,7 @@ impl DataMapper {
                 thread_id: self.extract_thread_id(&processed),
                 cheer_amount: self.extract_cheer_amount(&processed),
                 subscription_months: self.extract_subscription_months(&processed),
+                streak_months: self.raw_u32(&processed, "streak_months"),
                 raid_viewers: self.extract_raid_viewers(&processed),
                 timeout_duration: self.extract_timeout_duration(&processed),
                 custom_data: if processed.raw_data.is_object() {
@@ -317,22 +332,30 @@ impl DataMapper {
         }
     }
 
-    /// Extrae meses de suscripción
-    fn extract_subscription_months(&self, message: &StandardizedMessage) -> Option<u32> {
+    /// Campo numérico del mensaje crudo, en la raíz o en `metadata.custom_data`
+    /// (donde lo dejan las plataformas al serializar un `ChatMessage`)
+    fn raw_u32(&self, message: &StandardizedMessage, key: &str) -> Option<u32> {
         message
             .raw_data
-            .get("cumulative_months")
+            .get(key)
+            .or_else(|| {
+                message
+                    .raw_data
+                    .pointer("/metadata/custom_data")
+                    .and_then(|data| data.get(key))
+            })
             .and_then(|v| v.as_u64())
             .map(|v| v as u32)
     }
 
+    /// Extrae meses de suscripción
+    fn extract_subscription_months(&self, message: &StandardizedMessage) -> Option<u32> {
+        self.raw_u32(message, "cumulative_months")
+    }
+
     /// Extrae cantidad de viewers en un raid
     fn extract_raid_viewers(&self, message: &StandardizedMessage) -> Option<u32> {
-        message
-            .raw_data
-            .get("viewer_count")
-            .and_then(|v| v.as_u64())
-            .map(|v| v as u32)
+        self.raw_u32(message, "viewer_count")
     }
 
     /// Extrae duración de timeout
@@ -438,7 +461,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_map_data() {
-        let mut mapper = DataMapper::new();
+        let mapper = DataMapper::new();
         let standardized = create_test_standardized_message();
 
         let result = mapper.map_data(standardized).await.unwrap();
@@ -55,15 +55,13 @@ impl MessageTransformer {
         Ok(result)
     }
 
-    /// Normaliza el nombre de usuario
+    /// Normaliza el nombre de usuario; el nombre visible conserva sus
+    /// mayúsculas porque es el que se muestra en la ventana
     fn normalize_username(
         &self,
         mut message: StandardizedMessage,
     ) -> Result<StandardizedMessage, MappingError> {
         message.username = message.username.to_lowercase();
-        if let Some(display_name) = &mut message.display_name {
-            *display_name = display_name.to_lowercase();
-        }
         Ok(message)
     }
 
@@ -346,15 +344,16 @@ impl MessageTransformer {
         match key {
             "max_message_length" => {
                 if let Some(max_len) = value.as_u64() {
-                    if message.content.len() > max_len as usize {
-                        message.content.truncate(max_len as usize);
-                        message.content.push_str("...");
-                    }
+                    message.content = crate::text::truncate_with_ellipsis(
+                        &message.content,
+                        max_len as usize,
+                        "...",
+                    );
                 }
             }
             "min_message_length" => {
                 if let Some(min_len) = value.as_u64() {
-                    if message.content.len() < min_len as usize {
+                    if crate::text::grapheme_len(&message.content) < min_len as usize {
                         return Err(MappingError::ValidationError(
                             "Message too short".to_string(),
                         ));
@@ -391,10 +390,15 @@ impl MessageTransformer {
 
     /// Anonimiza un nombre de usuario
     fn anonymize_username(&self, username: &str) -> String {
-        if username.len() <= 2 {
-            "*".repeat(username.len())
+        let len = crate::text::grapheme_len(username);
+        if len <= 2 {
+            "*".repeat(len)
         } else {
-            format!("{}{}*", &username[..1], "*".repeat(username.len() - 2))
+            format!(
+                "{}{}*",
+                crate::text::first_grapheme(username),
+                "*".repeat(len - 2)
+            )
         }
     }
 
@@ -468,6 +472,14 @@ impl MessageTransformer {
         self.transformers.push(transformer);
     }
 
+    /// Nombres de los transformers registrados
+    pub fn transformer_names(&self) -> Vec<String> {
+        self.transformers
+            .iter()
+            .map(|transformer| transformer.name().to_string())
+            .collect()
+    }
+
     /// Aplica todos los transformers registrados
     pub fn apply_registered_transformers(
         &mut self,
@@ -619,7 +631,7 @@ mod tests {
         let message = create_test_message();
         let result = transformer.normalize_username(message).unwrap();
         assert_eq!(result.username, "testuser");
-        assert_eq!(result.display_name, Some("testuser".to_string()));
+        assert_eq!(result.display_name, Some("TestUser".to_string()));
     }
 
     #[test]
@@ -630,6 +642,20 @@ mod tests {
         assert_eq!(transformer.anonymize_username("a"), "*");
     }
 
+    #[test]
+    fn test_anonymize_and_truncate_multibyte() {
+        let mut transformer = MessageTransformer::new();
+        assert_eq!(transformer.anonymize_username("ñandú"), "ñ****");
+        assert_eq!(transformer.anonymize_username("🦀x"), "**");
+
+        let mut message = create_test_message();
+        message.content = "héllo 👋🏽 wörld".to_string();
+        let result = transformer
+            .apply_generic_transform(message, "max_message_length", &serde_json::json!(7))
+            .unwrap();
+        assert_eq!(result.content, "héllo 👋🏽...");
+    }
+
     #[test]
     fn test_content_filter() {
         let mut filter = ContentFilter::new(vec!["spam".to_string()], false);
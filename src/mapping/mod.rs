@@ -1,11 +1,14 @@
+pub mod clock;
 pub mod data_mapper;
 pub mod message_transformer;
 pub mod platform_adapter;
+pub mod rules;
 
 pub use data_mapper::*;
 pub use message_transformer::*;
 pub use platform_adapter::*;
 
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
@@ -35,28 +38,48 @@ impl MappingSystem {
     }
 
     /// Mapea un mensaje desde una plataforma a un formato unificado
+    ///
+    /// El pipeline llama a las etapas por separado para tomar el lock de
+    /// escritura solo en [`Self::transform`].
     pub async fn map_message(
         &mut self,
         raw_message: &RawPlatformMessage,
     ) -> Result<MappedMessage, MappingError> {
-        // Obtener adaptador para la plataforma
+        let standardized = self.standardize(raw_message).await?;
+        let transformed = self.transform(standardized)?;
+        self.map_data(transformed).await
+    }
+
+    /// Transforma el mensaje crudo con el adaptador de su plataforma
+    pub async fn standardize(
+        &self,
+        raw_message: &RawPlatformMessage,
+    ) -> Result<StandardizedMessage, MappingError> {
         let adapter = self
             .platform_adapters
             .get(&raw_message.platform)
             .ok_or_else(|| MappingError::UnsupportedPlatform(raw_message.platform.clone()))?;
+        adapter.transform_message(raw_message).await
+    }
 
-        // Transformar mensaje crudo a formato estandarizado
-        let standardized = adapter.transform_message(raw_message).await?;
-
-        // Aplicar transformaciones adicionales
+    /// Aplica las transformaciones configuradas y después las registradas
+    pub fn transform(
+        &mut self,
+        standardized: StandardizedMessage,
+    ) -> Result<StandardizedMessage, MappingError> {
         let transformed = self
             .message_transformer
             .transform(standardized, &self.config)?;
+        self.message_transformer
+            .apply_registered_transformers(transformed)
+    }
 
-        // Mapear datos adicionales
-        let mapped = self.data_mapper.map_data(transformed).await?;
-
-        Ok(mapped)
+    /// Mapea los datos adicionales del mensaje ya transformado
+    pub async fn map_data(
+        &self,
+        transformed: StandardizedMessage,
+    ) -> Result<MappedMessage, MappingError> {
+        self.data_mapper.map_data(transformed).await
     }
 
     /// Registra un nuevo adaptador de plataforma
@@ -64,6 +87,19 @@ impl MappingSystem {
         self.platform_adapters.insert(platform, adapter);
     }
 
+    /// Registra una transformación que se aplica a cada mensaje, en orden de
+    /// registro, después de las de la configuración
+    #[cfg(feature = "plugin-api")]
+    pub fn register_transform(&mut self, transform: Box<dyn MessageTransform>) {
+        self.message_transformer.register_transformer(transform);
+    }
+
+    /// Nombres de las transformaciones registradas, en el orden en que se aplican
+    #[cfg(feature = "plugin-api")]
+    pub fn transform_names(&self) -> Vec<String> {
+        self.message_transformer.transformer_names()
+    }
+
     /// Obtiene lista de plataformas soportadas
     pub fn supported_platforms(&self) -> Vec<String> {
         self.platform_adapters.keys().cloned().collect()
@@ -73,6 +109,11 @@ impl MappingSystem {
     pub fn update_config(&mut self, config: MappingConfig) {
         self.config = config;
     }
+
+    /// Sustituye las transformaciones personalizadas por las de un fichero de reglas
+    pub fn set_rules(&mut self, rules: rules::MappingRules) {
+        self.config.custom_mappings = rules.into_custom_mappings();
+    }
 }
 
 impl Default for MappingSystem {
@@ -91,8 +132,12 @@ pub struct RawPlatformMessage {
     pub message_id: Option<String>,
 }
 
+/// Versión del esquema de [`MappedMessage`] que reciben el puente y la API;
+/// solo cambia cuando un cambio rompe a los clientes existentes
+pub const MESSAGE_SCHEMA_VERSION: u32 = 1;
+
 /// Mensaje mapeado y estandarizado
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct MappedMessage {
     pub id: String,
     pub platform: String,
@@ -108,8 +153,39 @@ pub struct MappedMessage {
     pub metadata: MappedMetadata,
 }
 
+impl MappedMessage {
+    /// Copia al mensaje original lo que el mapeo puede haber cambiado:
+    /// texto, autor, emotes, insignias, color y el tipo y nivel mapeados
+    pub fn apply_to(&self, message: &mut crate::connection::ChatMessage) {
+        message.content = self.content.clone();
+        message.username = self.username.clone();
+        message.display_name = self.display_name.clone();
+        message.emotes = self.emotes.clone();
+        message.badges = self.badges.clone();
+        message.user_color = self
+            .metadata
+            .custom_data
+            .get("user_color")
+            .and_then(|v| v.as_str())
+            .map(|s| s.to_string());
+        if let Ok(mapped_type) = serde_json::to_value(&self.message_type) {
+            message
+                .metadata
+                .custom_data
+                .insert(MappedMessageType::METADATA_KEY.to_string(), mapped_type);
+        }
+        // Los renderers colorean según el nivel (`display.user_levels`)
+        if let Ok(user_level) = serde_json::to_value(&self.user_level) {
+            message
+                .metadata
+                .custom_data
+                .insert(UserLevel::METADATA_KEY.to_string(), user_level);
+        }
+    }
+}
+
 /// Nivel de usuario unificado
-#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "lowercase")]
 pub enum UserLevel {
     Normal,
@@ -123,8 +199,23 @@ pub enum UserLevel {
     Unknown,
 }
 
+impl UserLevel {
+    /// Clave en `MessageMetadata.custom_data` donde se guarda el nivel mapeado
+    pub const METADATA_KEY: &'static str = "user_level";
+
+    /// Nivel mapeado de un mensaje ya procesado, o el deducido de sus insignias
+    pub fn of(message: &crate::connection::ChatMessage) -> Self {
+        message
+            .metadata
+            .custom_data
+            .get(Self::METADATA_KEY)
+            .and_then(|v| serde_json::from_value(v.clone()).ok())
+            .unwrap_or_else(|| crate::pin::user_level_of(message))
+    }
+}
+
 /// Tipo de mensaje mapeado
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash, JsonSchema)]
 #[serde(rename_all = "lowercase")]
 pub enum MappedMessageType {
     Normal,
@@ -139,11 +230,56 @@ pub enum MappedMessageType {
     Prediction,
     Timeout,
     Ban,
+    Pinned,
+    Unpinned,
+    Follow,
+    Redemption,
+    HypeTrain,
     Unknown,
 }
 
+impl MappedMessageType {
+    /// Clave en `MessageMetadata.custom_data` donde se guarda el tipo mapeado
+    pub const METADATA_KEY: &'static str = "mapped_message_type";
+
+    /// Tipo mapeado de un mensaje ya procesado, o el derivado de su `MessageType`
+    pub fn of(message: &crate::connection::ChatMessage) -> Self {
+        message
+            .metadata
+            .custom_data
+            .get(Self::METADATA_KEY)
+            .and_then(|v| serde_json::from_value(v.clone()).ok())
+            .unwrap_or_else(|| Self::from(&message.message_type))
+    }
+}
+
+impl From<&crate::connection::MessageType> for MappedMessageType {
+    fn from(message_type: &crate::connection::MessageType) -> Self {
+        use crate::connection::MessageType;
+        match message_type {
+            MessageType::Normal => MappedMessageType::Normal,
+            MessageType::Action => MappedMessageType::Action,
+            MessageType::System => MappedMessageType::System,
+            MessageType::Whisper => MappedMessageType::Whisper,
+            MessageType::Highlight => MappedMessageType::Highlight,
+            MessageType::Subscription => MappedMessageType::Subscription,
+            MessageType::Raid => MappedMessageType::Raid,
+            MessageType::Cheer => MappedMessageType::Cheer,
+            MessageType::Poll => MappedMessageType::Poll,
+            MessageType::Prediction => MappedMessageType::Prediction,
+            MessageType::Pinned => MappedMessageType::Pinned,
+            MessageType::Unpinned => MappedMessageType::Unpinned,
+            MessageType::Follow => MappedMessageType::Follow,
+            MessageType::Redemption => MappedMessageType::Redemption,
+            MessageType::HypeTrain => MappedMessageType::HypeTrain,
+            MessageType::Ban => MappedMessageType::Ban,
+            MessageType::Unknown => MappedMessageType::Unknown,
+        }
+    }
+}
+
 /// Metadatos mapeados
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
 pub struct MappedMetadata {
     pub is_action: bool,
     pub is_whisper: bool,
@@ -154,6 +290,8 @@ pub struct MappedMetadata {
     pub thread_id: Option<String>,
     pub cheer_amount: Option<u32>,
     pub subscription_months: Option<u32>,
+    /// Racha de meses consecutivos, solo si el usuario decidió compartirla
+    pub streak_months: Option<u32>,
     pub raid_viewers: Option<u32>,
     pub timeout_duration: Option<u32>,
     pub custom_data: HashMap<String, serde_json::Value>,
@@ -187,6 +325,7 @@ impl Default for MappingConfig {
 
 /// Errores del sistema de mapeo
 #[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
 pub enum MappingError {
     #[error("Plataforma no soportada: {0}")]
     UnsupportedPlatform(String),
@@ -248,6 +387,31 @@ pub struct StandardizedMessage {
     pub raw_data: serde_json::Value,
 }
 
+impl StandardizedMessage {
+    /// Mensaje de chat normal, sin emotes ni insignias, con la hora actual
+    #[cfg(feature = "plugin-api")]
+    pub fn new(
+        platform: impl Into<String>,
+        channel: impl Into<String>,
+        username: impl Into<String>,
+        content: impl Into<String>,
+    ) -> Self {
+        Self {
+            platform: platform.into(),
+            channel: channel.into(),
+            username: username.into(),
+            display_name: None,
+            content: content.into(),
+            emotes: Vec::new(),
+            badges: Vec::new(),
+            timestamp: chrono::Utc::now(),
+            user_level: UserLevel::Normal,
+            message_type: MappedMessageType::Normal,
+            raw_data: serde_json::Value::Null,
+        }
+    }
+}
+
 /// Adaptador para Twitch
 pub struct TwitchAdapter;
 
@@ -305,7 +469,26 @@ impl TwitchAdapter {
 
             (username, display_name, user_level)
         } else {
-            ("unknown".to_string(), None, UserLevel::Normal)
+            // `ChatMessage` ya normalizado por la plataforma
+            let username = raw_data
+                .get("username")
+                .and_then(|v| v.as_str())
+                .unwrap_or("unknown")
+                .to_string();
+            let display_name = raw_data
+                .get("display_name")
+                .and_then(|v| v.as_str())
+                .map(|s| s.to_string());
+            let user_level = raw_data
+                .get("badges")
+                .and_then(|v| v.as_array())
+                .into_iter()
+                .flatten()
+                .filter_map(|badge| badge.get("id").and_then(|v| v.as_str()))
+                .map(|id| self.map_user_level(id))
+                .find(|level| *level != UserLevel::Normal)
+                .unwrap_or(UserLevel::Normal);
+            (username, display_name, user_level)
         }
     }
 
@@ -379,9 +562,11 @@ impl PlatformAdapter for TwitchAdapter {
         let (username, display_name, user_level) =
             self.extract_user_from_twitch_message(&raw_message.raw_data);
 
+        // Acepta tanto el formato IRC crudo como un `ChatMessage` serializado
         let content = raw_message
             .raw_data
             .get("message")
+            .or_else(|| raw_message.raw_data.get("content"))
             .and_then(|v| v.as_str())
             .unwrap_or("")
             .to_string();
@@ -389,12 +574,15 @@ impl PlatformAdapter for TwitchAdapter {
         let emotes = self.extract_emotes_from_twitch(&raw_message.raw_data);
         let badges = self.extract_badges(&raw_message.raw_data);
 
-        let message_type =
-            if let Some(msg_type) = raw_message.raw_data.get("type").and_then(|v| v.as_str()) {
-                self.map_message_type(msg_type)
-            } else {
-                MappedMessageType::Normal
-            };
+        let message_type = match raw_message
+            .raw_data
+            .get("type")
+            .or_else(|| raw_message.raw_data.get("message_type"))
+            .and_then(|v| v.as_str())
+        {
+            Some(msg_type) => self.map_message_type(msg_type),
+            None => MappedMessageType::Normal,
+        };
 
         Ok(StandardizedMessage {
             platform: raw_message.platform.clone(),
@@ -430,11 +618,17 @@ impl PlatformAdapter for TwitchAdapter {
 
     fn map_message_type(&self, platform_type: &str) -> MappedMessageType {
         match platform_type.to_lowercase().as_str() {
-            "privmsg" => MappedMessageType::Normal,
+            "privmsg" | "normal" => MappedMessageType::Normal,
             "action" => MappedMessageType::Action,
             "whisper" => MappedMessageType::Whisper,
-            "notice" => MappedMessageType::System,
-            "usernotice" => MappedMessageType::Subscription,
+            "notice" | "system" => MappedMessageType::System,
+            "highlight" => MappedMessageType::Highlight,
+            "usernotice" | "subscription" | "sub" | "resub" => MappedMessageType::Subscription,
+            "raid" => MappedMessageType::Raid,
+            "cheer" => MappedMessageType::Cheer,
+            "follow" | "channel.follow" => MappedMessageType::Follow,
+            "redemption" | "channel_points" => MappedMessageType::Redemption,
+            "hype_train" | "hypetrain" => MappedMessageType::HypeTrain,
             "clearchat" => MappedMessageType::Timeout,
             "clearmsg" => MappedMessageType::Ban,
             _ => MappedMessageType::Unknown,
@@ -496,6 +690,25 @@ impl PlatformAdapter for YouTubeAdapter {
         &self,
         raw_message: &RawPlatformMessage,
     ) -> Result<StandardizedMessage, MappingError> {
+        // `ChatMessage` ya normalizado por la plataforma
+        if let Ok(message) =
+            serde_json::from_value::<crate::connection::ChatMessage>(raw_message.raw_data.clone())
+        {
+            return Ok(StandardizedMessage {
+                platform: raw_message.platform.clone(),
+                channel: raw_message.channel.clone(),
+                username: message.username,
+                display_name: message.display_name,
+                content: message.content,
+                emotes: message.emotes,
+                badges: message.badges,
+                timestamp: raw_message.timestamp,
+                user_level: UserLevel::Normal,
+                message_type: MappedMessageType::from(&message.message_type),
+                raw_data: raw_message.raw_data.clone(),
+            });
+        }
+
         // Implementación básica para YouTube
         Ok(StandardizedMessage {
             platform: raw_message.platform.clone(),
@@ -539,13 +752,175 @@ impl Default for YouTubeAdapter {
     }
 }
 
-/// Adaptador para Kick (placeholder)
+/// Adaptador para Kick
+///
+/// Acepta el payload `ChatMessageData` de Pusher (`sender.identity` con color e
+/// insignias, emotes embebidos como `[emote:123:nombre]`) y también un
+/// `ChatMessage` ya normalizado por la plataforma.
 pub struct KickAdapter;
 
+/// Códigos de emote de Kick dentro del contenido
+static KICK_EMOTE_REGEX: std::sync::OnceLock<regex::Regex> = std::sync::OnceLock::new();
+
 impl KickAdapter {
     pub fn new() -> Self {
         Self
     }
+
+    /// Sustituye `[emote:id:nombre]` por `nombre` y devuelve los emotes con sus
+    /// posiciones (en caracteres) sobre el texto resultante
+    pub fn parse_emote_tags(content: &str) -> (String, Vec<crate::connection::Emote>) {
+        let regex = KICK_EMOTE_REGEX
+            .get_or_init(|| regex::Regex::new(r"\[emote:(\d+):([^\]\s]+)\]").unwrap());
+
+        let mut cleaned = String::with_capacity(content.len());
+        let mut emotes: Vec<crate::connection::Emote> = Vec::new();
+        let mut last = 0;
+        // Caracteres escritos en `cleaned`
+        let mut length = 0;
+        for captures in regex.captures_iter(content) {
+            let tag = captures.get(0).unwrap();
+            let id = &captures[1];
+            let name = &captures[2];
+            let before = &content[last..tag.start()];
+            cleaned.push_str(before);
+            length += before.chars().count();
+            let position = crate::connection::TextPosition {
+                start: length,
+                end: length + name.chars().count() - 1,
+            };
+            cleaned.push_str(name);
+            length += name.chars().count();
+            last = tag.end();
+
+            match emotes.iter_mut().find(|emote| emote.id == id) {
+                Some(emote) => emote.positions.push(position),
+                None => emotes.push(crate::connection::Emote {
+                    id: id.to_string(),
+                    name: name.to_string(),
+                    source: crate::connection::EmoteSource::Kick,
+                    positions: vec![position],
+                    url: Some(format!("https://files.kick.com/emotes/{}/fullsize", id)),
+                    is_animated: false,
+                    width: Some(28),
+                    height: Some(28),
+                    metadata: crate::connection::EmoteMetadata::default(),
+                }),
+            }
+        }
+        cleaned.push_str(&content[last..]);
+
+        (cleaned, emotes)
+    }
+
+    fn user_level_from_badges(&self, badges: &[crate::connection::Badge]) -> UserLevel {
+        badges
+            .iter()
+            .map(|badge| self.map_user_level(&badge.id))
+            .max_by_key(|level| match level {
+                UserLevel::Broadcaster => 5,
+                UserLevel::Staff | UserLevel::Admin => 4,
+                UserLevel::Moderator => 3,
+                UserLevel::Vip => 2,
+                UserLevel::Subscriber => 1,
+                _ => 0,
+            })
+            .unwrap_or(UserLevel::Normal)
+    }
+
+    fn transform_pusher_message(
+        &self,
+        raw_message: &RawPlatformMessage,
+        sender: &serde_json::Value,
+    ) -> StandardizedMessage {
+        let raw_data = &raw_message.raw_data;
+        let username = sender
+            .get("username")
+            .and_then(|v| v.as_str())
+            .unwrap_or("unknown")
+            .to_string();
+        let content = raw_data
+            .get("content")
+            .and_then(|v| v.as_str())
+            .unwrap_or("");
+        let (content, emotes) = Self::parse_emote_tags(content);
+        let badges = self.extract_badges(raw_data);
+        let user_level = self.user_level_from_badges(&badges);
+
+        let timestamp = raw_data
+            .get("created_at")
+            .and_then(|v| v.as_str())
+            .and_then(|v| chrono::DateTime::parse_from_rfc3339(v).ok())
+            .map(|t| t.with_timezone(&chrono::Utc))
+            .unwrap_or(raw_message.timestamp);
+
+        // El color viaja en raw_data para acabar en `custom_data.user_color`
+        let mut raw_data = raw_data.clone();
+        if let Some(color) = sender
+            .get("identity")
+            .and_then(|identity| identity.get("color"))
+            .and_then(|v| v.as_str())
+        {
+            if let Some(object) = raw_data.as_object_mut() {
+                object.insert("user_color".to_string(), color.into());
+            }
+        }
+
+        StandardizedMessage {
+            platform: raw_message.platform.clone(),
+            channel: raw_message.channel.clone(),
+            display_name: Some(username.clone()),
+            username,
+            content,
+            emotes,
+            badges,
+            timestamp,
+            user_level,
+            message_type: raw_message
+                .raw_data
+                .get("type")
+                .and_then(|v| v.as_str())
+                .map(|t| self.map_message_type(t))
+                .unwrap_or(MappedMessageType::Normal),
+            raw_data,
+        }
+    }
+
+    fn transform_chat_message(&self, raw_message: &RawPlatformMessage) -> StandardizedMessage {
+        let raw_data = &raw_message.raw_data;
+        let content = raw_data
+            .get("content")
+            .and_then(|v| v.as_str())
+            .unwrap_or("");
+        let (content, mut emotes) = Self::parse_emote_tags(content);
+        emotes.extend(self.extract_emotes(raw_data));
+        let badges = self.extract_badges(raw_data);
+
+        StandardizedMessage {
+            platform: raw_message.platform.clone(),
+            channel: raw_message.channel.clone(),
+            username: raw_data
+                .get("username")
+                .and_then(|v| v.as_str())
+                .unwrap_or("unknown")
+                .to_string(),
+            display_name: raw_data
+                .get("display_name")
+                .and_then(|v| v.as_str())
+                .map(|s| s.to_string()),
+            content,
+            emotes,
+            user_level: self.user_level_from_badges(&badges),
+            badges,
+            timestamp: raw_message.timestamp,
+            message_type: raw_data
+                .get("message_type")
+                .and_then(|v| v.as_str())
+                .map(|t| self.map_message_type(t))
+                .unwrap_or(MappedMessageType::Normal),
+            raw_data: raw_data.clone(),
+        }
+    }
 }
 
 #[async_trait::async_trait]
@@ -554,40 +929,80 @@ impl PlatformAdapter for KickAdapter {
         &self,
         raw_message: &RawPlatformMessage,
     ) -> Result<StandardizedMessage, MappingError> {
-        // Implementación básica para Kick
-        Ok(StandardizedMessage {
-            platform: raw_message.platform.clone(),
-            channel: raw_message.channel.clone(),
-            username: "kick_user".to_string(),
-            display_name: Some("Kick User".to_string()),
-            content: "Kick message".to_string(),
-            emotes: Vec::new(),
-            badges: Vec::new(),
-            timestamp: raw_message.timestamp,
-            user_level: UserLevel::Normal,
-            message_type: MappedMessageType::Normal,
-            raw_data: raw_message.raw_data.clone(),
-        })
+        match raw_message.raw_data.get("sender") {
+            Some(sender) if sender.is_object() => {
+                Ok(self.transform_pusher_message(raw_message, sender))
+            }
+            _ => Ok(self.transform_chat_message(raw_message)),
+        }
     }
 
     fn platform_name(&self) -> &str {
         "kick"
     }
 
-    fn map_user_level(&self, _platform_level: &str) -> UserLevel {
-        UserLevel::Normal
+    fn map_user_level(&self, platform_level: &str) -> UserLevel {
+        match platform_level.to_lowercase().as_str() {
+            "broadcaster" => UserLevel::Broadcaster,
+            "moderator" => UserLevel::Moderator,
+            "vip" => UserLevel::Vip,
+            "subscriber" | "founder" | "og" | "sub_gifter" => UserLevel::Subscriber,
+            "staff" => UserLevel::Staff,
+            _ => UserLevel::Normal,
+        }
     }
 
-    fn map_message_type(&self, _platform_type: &str) -> MappedMessageType {
-        MappedMessageType::Normal
+    fn map_message_type(&self, platform_type: &str) -> MappedMessageType {
+        match platform_type.to_lowercase().as_str() {
+            "pinned" | "pinned_message_created" => MappedMessageType::Pinned,
+            "unpinned" | "pinned_message_deleted" => MappedMessageType::Unpinned,
+            _ => MappedMessageType::Normal,
+        }
     }
 
-    fn extract_emotes(&self, _raw_data: &serde_json::Value) -> Vec<crate::connection::Emote> {
-        Vec::new()
+    fn extract_emotes(&self, raw_data: &serde_json::Value) -> Vec<crate::connection::Emote> {
+        // Emotes ya resueltos (mensaje normalizado)
+        raw_data
+            .get("emotes")
+            .and_then(|v| serde_json::from_value(v.clone()).ok())
+            .unwrap_or_default()
     }
 
-    fn extract_badges(&self, _raw_data: &serde_json::Value) -> Vec<crate::connection::Badge> {
-        Vec::new()
+    fn extract_badges(&self, raw_data: &serde_json::Value) -> Vec<crate::connection::Badge> {
+        let pusher_badges = raw_data
+            .get("sender")
+            .and_then(|sender| sender.get("identity"))
+            .and_then(|identity| identity.get("badges"))
+            .and_then(|v| v.as_array());
+
+        let Some(badges) = pusher_badges else {
+            return raw_data
+                .get("badges")
+                .and_then(|v| serde_json::from_value(v.clone()).ok())
+                .unwrap_or_default();
+        };
+
+        badges
+            .iter()
+            .filter_map(|badge| {
+                let kind = badge.get("type").and_then(|v| v.as_str())?;
+                Some(crate::connection::Badge {
+                    id: kind.to_string(),
+                    name: kind.to_string(),
+                    version: badge
+                        .get("count")
+                        .and_then(|v| v.as_u64())
+                        .unwrap_or(1)
+                        .to_string(),
+                    url: None,
+                    title: badge
+                        .get("text")
+                        .and_then(|v| v.as_str())
+                        .map(|s| s.to_string()),
+                    source: crate::connection::EmoteSource::Kick,
+                })
+            })
+            .collect()
     }
 }
 
@@ -596,3 +1011,126 @@ impl Default for KickAdapter {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kick_raw(raw_data: serde_json::Value) -> RawPlatformMessage {
+        RawPlatformMessage {
+            platform: "kick".to_string(),
+            channel: "xqc".to_string(),
+            raw_data,
+            timestamp: chrono::Utc::now(),
+            message_id: None,
+        }
+    }
+
+    #[test]
+    fn test_kick_emote_tags_are_replaced_with_positions() {
+        let (content, emotes) =
+            KickAdapter::parse_emote_tags("hi [emote:37226:KEKW] ñ [emote:37226:KEKW]");
+        assert_eq!(content, "hi KEKW ñ KEKW");
+        assert_eq!(emotes.len(), 1);
+        let positions: Vec<_> = emotes[0].positions.iter().map(|p| (p.start, p.end)).collect();
+        assert_eq!(positions, vec![(3, 6), (10, 13)]);
+        assert_eq!(
+            crate::text::slice_chars_inclusive(&content, 10, 13),
+            Some("KEKW")
+        );
+        assert_eq!(
+            emotes[0].url.as_deref(),
+            Some("https://files.kick.com/emotes/37226/fullsize")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_kick_pusher_payload_keeps_user_color_and_badges() {
+        let raw = kick_raw(serde_json::json!({
+            "id": "abc",
+            "chatroom_id": 1,
+            "content": "gg [emote:1:pog]",
+            "type": "message",
+            "created_at": "2024-01-01T00:00:00+00:00",
+            "sender": {
+                "id": 7,
+                "username": "Streamer",
+                "slug": "streamer",
+                "identity": {
+                    "color": "#FF9D00",
+                    "badges": [
+                        {"type": "subscriber", "text": "Subscriber", "count": 3},
+                        {"type": "moderator", "text": "Moderator"}
+                    ]
+                }
+            }
+        }));
+
+        let message = KickAdapter::new().transform_message(&raw).await.unwrap();
+        assert_eq!(message.username, "Streamer");
+        assert_eq!(message.content, "gg pog");
+        assert_eq!(message.emotes[0].name, "pog");
+        assert_eq!(message.badges.len(), 2);
+        assert_eq!(message.badges[0].version, "3");
+        assert_eq!(message.user_level, UserLevel::Moderator);
+        assert_eq!(message.raw_data["user_color"], "#FF9D00");
+        assert_eq!(message.timestamp.timestamp(), 1_704_067_200);
+    }
+
+    #[tokio::test]
+    async fn test_content_transform_reaches_the_chat_message() {
+        let rules = rules::MappingRules::parse(
+            r##"{ "content_transforms": [{ "type": "regex_replace", "pattern": "\\d+", "replacement": "#" }] }"##,
+            rules::RulesFormat::Json,
+        )
+        .unwrap();
+        let mut system = MappingSystem::default();
+        system.set_rules(rules);
+
+        for platform in ["twitch", "youtube", "kick"] {
+            let mut message = crate::connection::ChatMessage {
+                display_name: Some("Viewer".to_string()),
+                ..crate::connection::ChatMessage::test("viewer", "room 101").on(platform, "chan")
+            };
+            let raw = RawPlatformMessage {
+                platform: platform.to_string(),
+                channel: "chan".to_string(),
+                raw_data: serde_json::to_value(&message).unwrap(),
+                timestamp: chrono::Utc::now(),
+                message_id: None,
+            };
+            system
+                .map_message(&raw)
+                .await
+                .unwrap()
+                .apply_to(&mut message);
+            assert_eq!(message.content, "room #", "{}", platform);
+            assert_eq!(message.username, "viewer", "{}", platform);
+            assert_eq!(
+                message.display_name.as_deref(),
+                Some("Viewer"),
+                "{}",
+                platform
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_kick_normalized_message_keeps_existing_fields() {
+        let raw = kick_raw(serde_json::json!({
+            "username": "viewer",
+            "display_name": "Viewer",
+            "content": "hello",
+            "message_type": "pinned",
+            "user_color": "#00FF00",
+            "emotes": [],
+            "badges": []
+        }));
+
+        let message = KickAdapter::new().transform_message(&raw).await.unwrap();
+        assert_eq!(message.username, "viewer");
+        assert_eq!(message.display_name.as_deref(), Some("Viewer"));
+        assert_eq!(message.content, "hello");
+        assert_eq!(message.message_type, MappedMessageType::Pinned);
+    }
+}
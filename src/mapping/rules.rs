@@ -0,0 +1,327 @@
+//! Reglas de mapeo declarativas cargadas desde `mappings.json`/`mappings.toml`
+//!
+//! El fichero indicado en `mapping.rules_path` se valida contra el esquema de
+//! [`MappingRules`] y se convierte en `MappingConfig.custom_mappings`, el mismo
+//! formato que usa `MessageTransformer`. Un fichero inválido se rechaza entero
+//! con un `MappingError::ConfigError` que indica el campo y el motivo, y las
+//! reglas anteriores siguen en vigor.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use super::MappingError;
+
+/// `mapping`
+#[derive(Debug, Deserialize, Serialize, Clone, Default, PartialEq)]
+#[serde(default)]
+pub struct MappingRulesConfig {
+    /// Fichero de reglas (`.json` o `.toml`); sin él no hay reglas personalizadas
+    pub rules_path: Option<PathBuf>,
+}
+
+/// Contenido de un fichero de reglas de mapeo
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MappingRules {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub content_transforms: Vec<ContentTransform>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub user_transforms: Vec<UserTransform>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub emote_transforms: Vec<EmoteTransform>,
+    /// Longitud máxima en grafemas; el resto se corta con "..."
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_message_length: Option<u64>,
+    /// Los mensajes más cortos se descartan
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_message_length: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub required_user_level: Option<RequiredUserLevel>,
+}
+
+/// Transformación del texto del mensaje
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum ContentTransform {
+    Replace {
+        from: String,
+        to: String,
+    },
+    RegexReplace {
+        pattern: String,
+        replacement: String,
+    },
+    FilterWords {
+        words: Vec<String>,
+        /// `***` si no se indica
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        replacement: Option<String>,
+    },
+    CaseTransform {
+        case: TextCase,
+    },
+    Prepend {
+        prefix: String,
+    },
+    Append {
+        suffix: String,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TextCase {
+    Upper,
+    Lower,
+    Title,
+}
+
+/// Transformación del nombre de usuario
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum UserTransform {
+    Replace {
+        from: String,
+        to: String,
+    },
+    Prefix {
+        prefix: String,
+    },
+    Suffix {
+        suffix: String,
+    },
+    #[serde(alias = "anonymize")]
+    Anonimize,
+}
+
+/// Transformación de los emotes del mensaje
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum EmoteTransform {
+    Filter { emotes: Vec<String> },
+    Replace { from: String, to: String },
+    Scale { scale: f64 },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RequiredUserLevel {
+    Normal,
+    Subscriber,
+    Vip,
+    Moderator,
+    Broadcaster,
+}
+
+impl MappingRules {
+    /// Lee y valida un fichero de reglas; el formato depende de la extensión
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, MappingError> {
+        let path = path.as_ref();
+        let content = fs::read_to_string(path).map_err(|e| {
+            MappingError::ConfigError(format!("cannot read {}: {}", path.display(), e))
+        })?;
+        Self::parse(&content, RulesFormat::of(path))
+            .map_err(|e| MappingError::ConfigError(format!("{}: {}", path.display(), e)))
+    }
+
+    /// Parsea y valida reglas en el formato indicado
+    pub fn parse(content: &str, format: RulesFormat) -> Result<Self, String> {
+        let rules: Self = match format {
+            RulesFormat::Json => serde_json::from_str(content).map_err(|e| e.to_string())?,
+            RulesFormat::Toml => toml::from_str(content).map_err(|e| e.to_string())?,
+        };
+        rules.validate()?;
+        Ok(rules)
+    }
+
+    /// Comprobaciones que el esquema no expresa
+    fn validate(&self) -> Result<(), String> {
+        for (index, transform) in self.content_transforms.iter().enumerate() {
+            if let ContentTransform::RegexReplace { pattern, .. } = transform {
+                regex::Regex::new(pattern)
+                    .map_err(|e| format!("content_transforms[{}]: invalid regex: {}", index, e))?;
+            }
+        }
+        for (index, transform) in self.emote_transforms.iter().enumerate() {
+            if let EmoteTransform::Scale { scale } = transform {
+                if !(scale.is_finite() && *scale > 0.0) {
+                    return Err(format!(
+                        "emote_transforms[{}]: scale must be a positive number",
+                        index
+                    ));
+                }
+            }
+        }
+        if let (Some(min), Some(max)) = (self.min_message_length, self.max_message_length) {
+            if min > max {
+                return Err(format!(
+                    "min_message_length ({}) is greater than max_message_length ({})",
+                    min, max
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Reglas en el formato de `MappingConfig.custom_mappings`
+    pub fn into_custom_mappings(self) -> HashMap<String, serde_json::Value> {
+        match serde_json::to_value(self) {
+            Ok(serde_json::Value::Object(map)) => map.into_iter().collect(),
+            _ => HashMap::new(),
+        }
+    }
+}
+
+/// Formato de un fichero de reglas
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RulesFormat {
+    Json,
+    Toml,
+}
+
+impl RulesFormat {
+    /// `.toml` es TOML; cualquier otra extensión se lee como JSON
+    pub fn of(path: &Path) -> Self {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("toml") => RulesFormat::Toml,
+            _ => RulesFormat::Json,
+        }
+    }
+}
+
+/// Mantiene viva la vigilancia del fichero de reglas
+pub struct MappingRulesWatcher {
+    _watcher: notify::RecommendedWatcher,
+}
+
+/// Vigila el fichero de reglas y envía las reglas cada vez que cambia
+///
+/// Los cambios inválidos se registran y se ignoran.
+pub fn watch_rules<P: AsRef<Path>>(
+    path: P,
+    sender: tokio::sync::mpsc::UnboundedSender<MappingRules>,
+) -> Result<MappingRulesWatcher, MappingError> {
+    use notify::Watcher;
+
+    let path = path.as_ref().to_path_buf();
+    let file_name = path
+        .file_name()
+        .map(|name| name.to_os_string())
+        .ok_or_else(|| MappingError::ConfigError(format!("{:?} is not a file", path)))?;
+    let dir = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+        _ => PathBuf::from("."),
+    };
+
+    let format = RulesFormat::of(&path);
+    let mut last_content = fs::read_to_string(&path).unwrap_or_default();
+    let watched = path.clone();
+    let mut watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+        let Ok(event) = event else {
+            return;
+        };
+        if !(event.kind.is_modify() || event.kind.is_create())
+            || !event
+                .paths
+                .iter()
+                .any(|p| p.file_name() == Some(file_name.as_os_str()))
+        {
+            return;
+        }
+
+        let Ok(content) = fs::read_to_string(&watched) else {
+            return;
+        };
+        if content == last_content {
+            return;
+        }
+        match MappingRules::parse(&content, format) {
+            Ok(rules) => {
+                last_content = content;
+                let _ = sender.send(rules);
+            }
+            Err(e) => eprintln!(
+                "[MAPPING] ⚠️ Ignoring invalid change in {:?}: {}",
+                watched, e
+            ),
+        }
+    })
+    .map_err(|e| MappingError::ConfigError(e.to_string()))?;
+
+    watcher
+        .watch(&dir, notify::RecursiveMode::NonRecursive)
+        .map_err(|e| MappingError::ConfigError(e.to_string()))?;
+
+    Ok(MappingRulesWatcher { _watcher: watcher })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_json_and_toml_rules_share_the_schema() {
+        let json = r##"{
+            "content_transforms": [
+                { "type": "regex_replace", "pattern": "\\d+", "replacement": "#" },
+                { "type": "filter_words", "words": ["spoiler"] }
+            ],
+            "user_transforms": [{ "type": "anonymize" }],
+            "max_message_length": 200
+        }"##;
+        let toml = r##"
+            max_message_length = 200
+
+            [[content_transforms]]
+            type = "regex_replace"
+            pattern = '\d+'
+            replacement = "#"
+
+            [[content_transforms]]
+            type = "filter_words"
+            words = ["spoiler"]
+
+            [[user_transforms]]
+            type = "anonimize"
+        "##;
+
+        let from_json = MappingRules::parse(json, RulesFormat::Json).unwrap();
+        let from_toml = MappingRules::parse(toml, RulesFormat::Toml).unwrap();
+        assert_eq!(from_json, from_toml);
+
+        let mappings = from_json.into_custom_mappings();
+        assert_eq!(mappings.len(), 3);
+        assert_eq!(
+            mappings["content_transforms"][1],
+            serde_json::json!({ "type": "filter_words", "words": ["spoiler"] })
+        );
+        assert_eq!(mappings["user_transforms"][0]["type"], "anonimize");
+        assert_eq!(mappings["max_message_length"], 200);
+    }
+
+    #[test]
+    fn test_schema_errors_name_the_offending_field() {
+        let unknown = MappingRules::parse(
+            r#"{ "content_transforms": [{ "type": "shout" }] }"#,
+            RulesFormat::Json,
+        );
+        assert!(unknown.unwrap_err().contains("shout"));
+
+        let regex = MappingRules::parse(
+            r#"{ "content_transforms": [{ "type": "regex_replace", "pattern": "(", "replacement": "" }] }"#,
+            RulesFormat::Json,
+        );
+        assert!(regex.unwrap_err().starts_with("content_transforms[0]"));
+
+        let typo = MappingRules::parse(r#"{ "max_length": 10 }"#, RulesFormat::Json);
+        assert!(typo.unwrap_err().contains("max_length"));
+
+        assert!(matches!(
+            MappingRules::load("/nonexistent/mappings.toml"),
+            Err(MappingError::ConfigError(_))
+        ));
+    }
+}
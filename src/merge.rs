@@ -0,0 +1,309 @@
+//! Modo watch-party: fusiona los chats de varias conexiones en un único flujo
+//!
+//! Los mensajes idénticos publicados por el mismo usuario en varios canales del
+//! grupo se muestran una sola vez, etiquetados con todos los canales de origen.
+//! Cada mensaje se retiene `hold_ms` para poder agrupar sus duplicados antes de
+//! emitirlo; los duplicados que lleguen más tarde (dentro de `dedupe_window_ms`)
+//! se descartan.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::time::{Duration, Instant};
+
+use crate::config::{Config, MergeConfig};
+use crate::connection::ChatMessage;
+
+/// Clave en `custom_data` con la lista de canales de origen (`plataforma:canal`)
+pub const SOURCE_CHANNELS_KEY: &str = "source_channels";
+/// Clave en `custom_data` con el nombre del flujo fusionado
+pub const MERGED_STREAM_KEY: &str = "merged_stream";
+
+#[derive(Debug)]
+struct PendingMessage {
+    message: ChatMessage,
+    key: DedupeKey,
+    sources: Vec<String>,
+    received: Instant,
+}
+
+type DedupeKey = (String, String);
+
+/// Fusiona y deduplica mensajes de las conexiones de un grupo
+#[derive(Debug)]
+pub struct WatchPartyMerger {
+    config: MergeConfig,
+    /// (plataforma, canal) de las conexiones que forman el grupo
+    members: HashSet<(String, String)>,
+    pending: VecDeque<PendingMessage>,
+    ready: VecDeque<ChatMessage>,
+    /// Mensajes ya emitidos: clave -> (instante, canales)
+    emitted: HashMap<DedupeKey, (Instant, HashSet<String>)>,
+    duplicates_dropped: u64,
+}
+
+impl WatchPartyMerger {
+    pub fn new(config: MergeConfig, members: impl IntoIterator<Item = (String, String)>) -> Self {
+        Self {
+            config,
+            members: members
+                .into_iter()
+                .map(|(platform, channel)| (platform, normalize_channel(&channel)))
+                .collect(),
+            pending: VecDeque::new(),
+            ready: VecDeque::new(),
+            emitted: HashMap::new(),
+            duplicates_dropped: 0,
+        }
+    }
+
+    /// Construye el merger con las conexiones referenciadas en `config.merge`
+    pub fn from_config(config: &Config) -> Self {
+        let members = config
+            .connections
+            .iter()
+            .filter(|conn| conn.enabled && config.merge.connections.contains(&conn.id))
+            .map(|conn| (conn.platform.clone(), conn.channel.clone()))
+            .collect::<Vec<_>>();
+        Self::new(config.merge.clone(), members)
+    }
+
+    /// Aplica una configuración recargada sin perder los mensajes retenidos
+    pub fn reconfigure(&mut self, config: &Config) {
+        let fresh = Self::from_config(config);
+        self.config = fresh.config;
+        self.members = fresh.members;
+    }
+
+    fn is_member(&self, message: &ChatMessage) -> bool {
+        self.config.enabled
+            && self.members.contains(&(
+                message.platform.clone(),
+                normalize_channel(&message.channel),
+            ))
+    }
+
+    /// Añade un mensaje entrante; los que no pertenecen al grupo pasan directamente
+    pub fn push(&mut self, message: ChatMessage, now: Instant) {
+        if !self.is_member(&message) {
+            self.ready.push_back(message);
+            return;
+        }
+
+        let source = source_label(&message);
+        let key = dedupe_key(&message);
+
+        // Duplicado de un mensaje aún retenido: solo se añade el canal
+        if let Some(pending) = self.pending.iter_mut().find(|p| p.key == key) {
+            if !pending.sources.contains(&source) {
+                pending.sources.push(source);
+                self.duplicates_dropped += 1;
+                return;
+            }
+        } else if let Some((_, channels)) = self.emitted.get_mut(&key) {
+            // Duplicado tardío de otro canal: ya se mostró
+            if !channels.contains(&source) {
+                channels.insert(source);
+                self.duplicates_dropped += 1;
+                return;
+            }
+        }
+
+        self.pending.push_back(PendingMessage {
+            message,
+            key,
+            sources: vec![source],
+            received: now,
+        });
+    }
+
+    /// Hay mensajes retenidos esperando a sus posibles duplicados
+    pub fn has_pending(&self) -> bool {
+        !self.pending.is_empty()
+    }
+
+    /// Instante en que el mensaje retenido más antiguo estará listo
+    pub fn next_ready_at(&self) -> Option<Instant> {
+        let hold = Duration::from_millis(self.config.hold_ms);
+        self.pending.front().map(|pending| pending.received + hold)
+    }
+
+    /// Devuelve los mensajes listos para mostrarse, en orden de llegada
+    pub fn drain_ready(&mut self, now: Instant) -> Vec<ChatMessage> {
+        let hold = Duration::from_millis(self.config.hold_ms);
+        while let Some(front) = self.pending.front() {
+            if now.saturating_duration_since(front.received) < hold {
+                break;
+            }
+            let Some(pending) = self.pending.pop_front() else {
+                break;
+            };
+            let mut message = pending.message;
+            message.metadata.custom_data.insert(
+                SOURCE_CHANNELS_KEY.to_string(),
+                serde_json::json!(pending.sources),
+            );
+            message.metadata.custom_data.insert(
+                MERGED_STREAM_KEY.to_string(),
+                self.config.name.clone().into(),
+            );
+            self.emitted
+                .insert(pending.key, (now, pending.sources.into_iter().collect()));
+            self.ready.push_back(message);
+        }
+
+        let window = Duration::from_millis(self.config.dedupe_window_ms);
+        self.emitted
+            .retain(|_, (emitted_at, _)| now.saturating_duration_since(*emitted_at) < window);
+
+        self.ready.drain(..).collect()
+    }
+
+    /// Número de duplicados descartados desde el inicio
+    pub fn duplicates_dropped(&self) -> u64 {
+        self.duplicates_dropped
+    }
+}
+
+/// Canales de origen de un mensaje fusionado (vacío si no pasó por el merger)
+pub fn source_channels(message: &ChatMessage) -> Vec<String> {
+    message
+        .metadata
+        .custom_data
+        .get(SOURCE_CHANNELS_KEY)
+        .and_then(|v| serde_json::from_value(v.clone()).ok())
+        .unwrap_or_default()
+}
+
+fn source_label(message: &ChatMessage) -> String {
+    format!(
+        "{}:{}",
+        message.platform,
+        normalize_channel(&message.channel)
+    )
+}
+
+fn normalize_channel(channel: &str) -> String {
+    channel.trim_start_matches('#').to_lowercase()
+}
+
+/// Usuario y contenido normalizados (Twitch añade U+E0000 a los mensajes repetidos)
+fn dedupe_key(message: &ChatMessage) -> DedupeKey {
+    let content = message
+        .content
+        .replace('\u{E0000}', "")
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ");
+    (message.username.to_lowercase(), content)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn merger() -> WatchPartyMerger {
+        WatchPartyMerger::new(
+            MergeConfig {
+                enabled: true,
+                name: "party".to_string(),
+                connections: vec![],
+                hold_ms: 300,
+                dedupe_window_ms: 5000,
+            },
+            vec![
+                ("twitch".to_string(), "alice".to_string()),
+                ("twitch".to_string(), "bob".to_string()),
+            ],
+        )
+    }
+
+    #[test]
+    fn test_cross_posted_message_is_merged_and_tagged() {
+        let mut merger = merger();
+        let start = Instant::now();
+
+        merger.push(
+            ChatMessage::test("Carol", "hello both!").on("twitch", "alice"),
+            start,
+        );
+        merger.push(
+            ChatMessage::test("carol", "hello  both!").on("twitch", "#bob"),
+            start,
+        );
+        assert!(merger.drain_ready(start).is_empty());
+        assert_eq!(
+            merger.next_ready_at(),
+            Some(start + Duration::from_millis(300))
+        );
+
+        let out = merger.drain_ready(start + Duration::from_millis(300));
+        assert_eq!(out.len(), 1);
+        assert_eq!(
+            source_channels(&out[0]),
+            vec!["twitch:alice".to_string(), "twitch:bob".to_string()]
+        );
+        assert_eq!(out[0].metadata.custom_data[MERGED_STREAM_KEY], "party");
+
+        // Duplicado tardío de otro canal dentro de la ventana: descartado
+        let later = start + Duration::from_secs(1);
+        merger.push(ChatMessage::test("dave", "gg").on("twitch", "alice"), later);
+        assert_eq!(
+            merger.drain_ready(later + Duration::from_millis(300)).len(),
+            1
+        );
+        merger.push(
+            ChatMessage::test("dave", "gg").on("twitch", "bob"),
+            later + Duration::from_secs(2),
+        );
+        assert!(!merger.has_pending());
+        assert_eq!(merger.next_ready_at(), None);
+        assert_eq!(merger.duplicates_dropped(), 2);
+    }
+
+    #[test]
+    fn test_repeats_in_same_channel_are_kept() {
+        let mut merger = merger();
+        let start = Instant::now();
+        merger.push(
+            ChatMessage::test("carol", "spam").on("twitch", "alice"),
+            start,
+        );
+        merger.push(
+            ChatMessage::test("carol", "spam \u{E0000}").on("twitch", "alice"),
+            start,
+        );
+
+        let out = merger.drain_ready(start + Duration::from_secs(1));
+        assert_eq!(out.len(), 2);
+    }
+
+    #[test]
+    fn test_non_member_connections_pass_through() {
+        let mut merger = merger();
+        let now = Instant::now();
+        merger.push(ChatMessage::test("carol", "hi").on("kick", "alice"), now);
+
+        let out = merger.drain_ready(now);
+        assert_eq!(out.len(), 1);
+        assert!(source_channels(&out[0]).is_empty());
+    }
+
+    #[test]
+    fn test_reconfigure_keeps_held_messages() {
+        let mut merger = merger();
+        let start = Instant::now();
+        merger.push(
+            ChatMessage::test("carol", "hi").on("twitch", "alice"),
+            start,
+        );
+
+        // Sin `merge.enabled` los mensajes nuevos pasan directamente
+        merger.reconfigure(&Config::default());
+        merger.push(
+            ChatMessage::test("dave", "hey").on("twitch", "alice"),
+            start,
+        );
+        assert_eq!(merger.drain_ready(start).len(), 1);
+        assert!(merger.has_pending());
+        assert_eq!(merger.drain_ready(start + Duration::from_secs(1)).len(), 1);
+    }
+}
@@ -0,0 +1,483 @@
+//! Carril de moderación: mensajes descartados por el pipeline y su motivo
+//!
+//! Cada mensaje que rechaza `process_message` (filtros, solo comandos, solo
+//! alertas, errores de mapeo) se registra aquí: se escribe en el log, se
+//! notifica a los observadores de `OverlayApp`, se expone en `GET /moderation`
+//! y, opcionalmente, se resume en una ventana pequeña con el contador.
+
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::sync::{Mutex, OnceLock};
+
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::config::{MessageFilters, ModerationLaneConfig, SystemUser};
+use crate::connection::ChatMessage;
+use crate::system_users;
+
+static GLOBAL_LANE: OnceLock<ModerationLane> = OnceLock::new();
+
+/// Longitud máxima del contenido guardado de cada mensaje descartado
+const MAX_STORED_CONTENT: usize = 200;
+
+/// Bots de chat habituales que ignora `filters.ignore_bots`
+pub const KNOWN_BOTS: &[&str] = &[
+    "nightbot",
+    "streamelements",
+    "streamlabs",
+    "moobot",
+    "fossabot",
+    "wizebot",
+    "deepbot",
+    "phantombot",
+    "coebot",
+    "botisimo",
+    "sery_bot",
+    "soundalerts",
+    "streamstickers",
+    "pokemoncommunitygame",
+    "kofistreambot",
+    "botrix",
+    "botrixoficial",
+    "kicklet",
+];
+
+/// Motivo por el que un mensaje no se mostró
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, JsonSchema)]
+#[serde(tag = "reason", content = "detail", rename_all = "snake_case")]
+pub enum DropReason {
+    TooShort,
+    TooLong,
+    BlockedUser,
+    /// El usuario no está en `allowed_users`
+    NotAllowedUser,
+    /// Bot conocido o de `bot_users` con `ignore_bots`
+    Bot,
+    /// Palabra bloqueada encontrada
+    BlockedWord(String),
+    /// Coincidencia del filtro `profanity` con la acción `drop`
+    Profanity(String),
+    CommandsOnly,
+    /// Mensaje con solo enlaces y `hide_link_only`
+    LinkOnly,
+    /// Filtro propio de la plataforma
+    PlatformFilter,
+    /// Repetición agrupada en la ventana de un mensaje anterior (`dedupe`)
+    Duplicate,
+    /// `display.alerts_only` oculta el chat normal
+    AlertsOnly,
+    MappingError(String),
+}
+
+impl DropReason {
+    /// Clave estable para contadores y la API
+    pub fn key(&self) -> &'static str {
+        match self {
+            DropReason::TooShort => "too_short",
+            DropReason::TooLong => "too_long",
+            DropReason::BlockedUser => "blocked_user",
+            DropReason::NotAllowedUser => "not_allowed_user",
+            DropReason::Bot => "bot",
+            DropReason::BlockedWord(_) => "blocked_word",
+            DropReason::Profanity(_) => "profanity",
+            DropReason::CommandsOnly => "commands_only",
+            DropReason::LinkOnly => "link_only",
+            DropReason::PlatformFilter => "platform_filter",
+            DropReason::Duplicate => "duplicate",
+            DropReason::AlertsOnly => "alerts_only",
+            DropReason::MappingError(_) => "mapping_error",
+        }
+    }
+}
+
+impl std::fmt::Display for DropReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DropReason::BlockedWord(detail)
+            | DropReason::Profanity(detail)
+            | DropReason::MappingError(detail) => {
+                write!(f, "{} ({})", self.key(), detail)
+            }
+            _ => f.write_str(self.key()),
+        }
+    }
+}
+
+impl std::error::Error for DropReason {}
+
+/// Si `username` está en `users`, sin distinguir mayúsculas ni `@`
+fn lists_user(users: &[String], username: &str) -> bool {
+    users
+        .iter()
+        .any(|user| user.trim_start_matches('@').eq_ignore_ascii_case(username))
+}
+
+/// Si `username` es un bot conocido o uno de `filters.bot_users`
+pub fn is_bot(username: &str, filters: &MessageFilters) -> bool {
+    KNOWN_BOTS
+        .iter()
+        .any(|bot| bot.eq_ignore_ascii_case(username))
+        || lists_user(&filters.bot_users, username)
+}
+
+/// Primer filtro de la conexión que rechaza el mensaje
+pub fn filter_rejection(message: &ChatMessage, filters: &MessageFilters) -> Option<DropReason> {
+    let username = message.username.as_str();
+    if lists_user(&filters.blocked_users, username) {
+        return Some(DropReason::BlockedUser);
+    }
+    let allowed = lists_user(&filters.allowed_users, username);
+    if filters.allowed_users_only {
+        if !filters.allowed_users.is_empty() && !allowed {
+            return Some(DropReason::NotAllowedUser);
+        }
+    } else if allowed {
+        // Usuarios de confianza: solo les afecta el modo «solo comandos»
+        return (filters.commands_only && !filters.is_command(&message.content))
+            .then_some(DropReason::CommandsOnly);
+    }
+    if filters.ignore_bots && !allowed && is_bot(username, filters) {
+        return Some(DropReason::Bot);
+    }
+
+    let length = crate::text::grapheme_len(&message.content);
+    if filters.min_message_length.is_some_and(|min| length < min) {
+        return Some(DropReason::TooShort);
+    }
+    if filters.max_message_length.is_some_and(|max| length > max) {
+        return Some(DropReason::TooLong);
+    }
+
+    let content = message.content.to_lowercase();
+    if let Some(word) = filters
+        .blocked_words
+        .iter()
+        .find(|word| content.contains(&word.to_lowercase()))
+    {
+        return Some(DropReason::BlockedWord(word.clone()));
+    }
+
+    if filters.hide_link_only && crate::links::is_link_only(&message.content) {
+        return Some(DropReason::LinkOnly);
+    }
+
+    if filters.commands_only && !filters.is_command(&message.content) {
+        return Some(DropReason::CommandsOnly);
+    }
+
+    None
+}
+
+/// Mensaje descartado tal y como se expone en la API y a los observadores
+#[derive(Debug, Clone, PartialEq, Serialize, JsonSchema)]
+pub struct DroppedMessage {
+    pub id: String,
+    pub platform: String,
+    pub channel: String,
+    pub username: String,
+    pub content: String,
+    #[serde(flatten)]
+    pub reason: DropReason,
+    pub dropped_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Resumen de `GET /moderation`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, JsonSchema)]
+pub struct ModerationSummary {
+    pub total: u64,
+    pub by_reason: BTreeMap<String, u64>,
+    /// Últimos descartes, del más antiguo al más reciente
+    pub recent: Vec<DroppedMessage>,
+}
+
+#[derive(Debug, Default)]
+struct LaneState {
+    config: ModerationLaneConfig,
+    total: u64,
+    by_reason: BTreeMap<&'static str, u64>,
+    recent: VecDeque<DroppedMessage>,
+    /// Total mostrado en el último contador
+    counter_total: u64,
+}
+
+/// Registro de mensajes descartados
+#[derive(Debug, Default)]
+pub struct ModerationLane {
+    state: Mutex<LaneState>,
+}
+
+impl ModerationLane {
+    pub fn new(config: ModerationLaneConfig) -> Self {
+        Self {
+            state: Mutex::new(LaneState {
+                config,
+                ..LaneState::default()
+            }),
+        }
+    }
+
+    pub fn global() -> &'static ModerationLane {
+        GLOBAL_LANE.get_or_init(|| Self::new(ModerationLaneConfig::default()))
+    }
+
+    pub fn configure(&self, config: ModerationLaneConfig) {
+        let mut state = self.lock();
+        while state.recent.len() > config.recent_limit {
+            state.recent.pop_front();
+        }
+        state.config = config;
+    }
+
+    /// Registra un descarte; `None` si el carril está desactivado
+    pub fn record(&self, message: &ChatMessage, reason: DropReason) -> Option<DroppedMessage> {
+        let mut state = self.lock();
+        if !state.config.enabled {
+            return None;
+        }
+
+        if state.config.log {
+            println!(
+                "[MODERATION] 🚫 {}/{} {}: {}",
+                message.platform, message.channel, message.username, reason
+            );
+        }
+
+        let dropped = DroppedMessage {
+            id: message.id.clone(),
+            platform: message.platform.clone(),
+            channel: message.channel.clone(),
+            username: message.username.clone(),
+            content: crate::text::truncate_with_ellipsis(&message.content, MAX_STORED_CONTENT, "…"),
+            reason,
+            dropped_at: chrono::Utc::now(),
+        };
+        state.total += 1;
+        *state.by_reason.entry(dropped.reason.key()).or_default() += 1;
+        if state.config.recent_limit > 0 {
+            if state.recent.len() >= state.config.recent_limit {
+                state.recent.pop_front();
+            }
+            state.recent.push_back(dropped.clone());
+        }
+        Some(dropped)
+    }
+
+    pub fn summary(&self) -> ModerationSummary {
+        let state = self.lock();
+        ModerationSummary {
+            total: state.total,
+            by_reason: state
+                .by_reason
+                .iter()
+                .map(|(reason, count)| (reason.to_string(), *count))
+                .collect(),
+            recent: state.recent.iter().cloned().collect(),
+        }
+    }
+
+    /// Ventana con el contador si `show_counter` está activo y hubo descartes
+    /// desde la última vez
+    pub fn take_counter_message(&self, user: &SystemUser) -> Option<ChatMessage> {
+        let mut state = self.lock();
+        if !state.config.enabled || !state.config.show_counter || state.total == state.counter_total
+        {
+            return None;
+        }
+        state.counter_total = state.total;
+
+        let mut reasons: Vec<(&str, u64)> = state
+            .by_reason
+            .iter()
+            .map(|(reason, count)| (*reason, *count))
+            .collect();
+        reasons.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+        let breakdown: Vec<String> = reasons
+            .iter()
+            .take(3)
+            .map(|(reason, count)| format!("{} {}", reason, count))
+            .collect();
+
+        let mut custom_data = HashMap::new();
+        custom_data.insert("dropped_total".to_string(), state.total.into());
+        Some(system_users::system_message(
+            user,
+            "moderation",
+            format!("🚫 {} dropped · {}", state.total, breakdown.join(" · ")),
+            custom_data,
+        ))
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, LaneState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_filter_rejection_reports_the_failing_filter() {
+        let filters = MessageFilters {
+            min_message_length: Some(2),
+            max_message_length: Some(20),
+            blocked_users: vec!["spammer".to_string()],
+            allowed_users: vec![],
+            allowed_users_only: true,
+            ignore_bots: false,
+            bot_users: vec![],
+            blocked_words: vec!["Casino".to_string()],
+            hide_link_only: false,
+            commands_only: false,
+            command_prefixes: vec![],
+            subscribers_only: false,
+            vip_only: false,
+        };
+        assert_eq!(
+            filter_rejection(&ChatMessage::test("viewer", "hola"), &filters),
+            None
+        );
+        assert_eq!(
+            filter_rejection(&ChatMessage::test("viewer", "x"), &filters),
+            Some(DropReason::TooShort)
+        );
+        assert_eq!(
+            filter_rejection(&ChatMessage::test("Spammer", "hola"), &filters),
+            Some(DropReason::BlockedUser)
+        );
+        assert_eq!(
+            filter_rejection(&ChatMessage::test("viewer", "best casino here"), &filters),
+            Some(DropReason::BlockedWord("Casino".to_string()))
+        );
+
+        let links = MessageFilters {
+            hide_link_only: true,
+            ..filters.clone()
+        };
+        assert_eq!(
+            filter_rejection(&ChatMessage::test("viewer", "https://a.io"), &links),
+            Some(DropReason::LinkOnly)
+        );
+        assert_eq!(
+            filter_rejection(&ChatMessage::test("viewer", "mira https://a.io"), &links),
+            None
+        );
+
+        let questions = MessageFilters {
+            commands_only: true,
+            command_prefixes: vec!["!question".to_string()],
+            ..filters
+        };
+        assert_eq!(
+            filter_rejection(&ChatMessage::test("viewer", "!question ¿hola?"), &questions),
+            None
+        );
+        assert_eq!(
+            filter_rejection(&ChatMessage::test("viewer", "!dice"), &questions),
+            Some(DropReason::CommandsOnly)
+        );
+    }
+
+    #[test]
+    fn test_user_lists_and_bots() {
+        let filters = MessageFilters {
+            min_message_length: Some(3),
+            max_message_length: None,
+            blocked_users: vec!["@Spammer".to_string()],
+            allowed_users: vec!["Nightbot".to_string(), "Moderador".to_string()],
+            allowed_users_only: true,
+            ignore_bots: true,
+            bot_users: vec!["canal_bot".to_string()],
+            blocked_words: vec!["casino".to_string()],
+            hide_link_only: false,
+            commands_only: false,
+            command_prefixes: vec![],
+            subscribers_only: false,
+            vip_only: false,
+        };
+        assert_eq!(
+            filter_rejection(&ChatMessage::test("spammer", "hola"), &filters),
+            Some(DropReason::BlockedUser)
+        );
+        assert_eq!(
+            filter_rejection(&ChatMessage::test("viewer", "hola"), &filters),
+            Some(DropReason::NotAllowedUser)
+        );
+        // Un bot en `allowed_users` se sigue mostrando
+        assert_eq!(
+            filter_rejection(&ChatMessage::test("nightbot", "hola"), &filters),
+            None
+        );
+
+        let trusted = MessageFilters {
+            allowed_users_only: false,
+            ..filters
+        };
+        assert_eq!(
+            filter_rejection(&ChatMessage::test("viewer", "hola"), &trusted),
+            None
+        );
+        assert_eq!(
+            filter_rejection(&ChatMessage::test("StreamElements", "hola"), &trusted),
+            Some(DropReason::Bot)
+        );
+        assert_eq!(
+            filter_rejection(&ChatMessage::test("canal_bot", "hola"), &trusted),
+            Some(DropReason::Bot)
+        );
+        // Los usuarios de confianza se saltan longitud y palabras bloqueadas
+        assert_eq!(
+            filter_rejection(&ChatMessage::test("moderador", "ok"), &trusted),
+            None
+        );
+        assert_eq!(
+            filter_rejection(&ChatMessage::test("moderador", "casino night!"), &trusted),
+            None
+        );
+        assert_eq!(
+            filter_rejection(&ChatMessage::test("viewer", "casino night!"), &trusted),
+            Some(DropReason::BlockedWord("casino".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_lane_counts_and_keeps_recent_drops() {
+        let lane = ModerationLane::new(ModerationLaneConfig {
+            enabled: true,
+            log: false,
+            recent_limit: 2,
+            show_counter: true,
+            ..ModerationLaneConfig::default()
+        });
+        lane.record(&ChatMessage::test("a", "1"), DropReason::TooShort);
+        lane.record(
+            &ChatMessage::test("b", "spam"),
+            DropReason::BlockedWord("spam".to_string()),
+        );
+        lane.record(&ChatMessage::test("c", "3"), DropReason::TooShort);
+
+        let summary = lane.summary();
+        assert_eq!(summary.total, 3);
+        assert_eq!(summary.by_reason["too_short"], 2);
+        assert_eq!(summary.recent.len(), 2);
+        assert_eq!(summary.recent[0].username, "b");
+
+        let json = serde_json::to_value(&summary.recent[0]).unwrap();
+        assert_eq!(json["reason"], "blocked_word");
+        assert_eq!(json["detail"], "spam");
+
+        let user = SystemUser::default();
+        let counter = lane.take_counter_message(&user).unwrap();
+        assert!(counter.content.starts_with("🚫 3 dropped · too_short 2"));
+        assert!(lane.take_counter_message(&user).is_none());
+    }
+
+    #[test]
+    fn test_disabled_lane_records_nothing() {
+        let lane = ModerationLane::new(ModerationLaneConfig::default());
+        assert!(lane
+            .record(&ChatMessage::test("a", "1"), DropReason::TooShort)
+            .is_none());
+        assert_eq!(lane.summary().total, 0);
+    }
+}
@@ -0,0 +1,302 @@
+//! Integración con OBS WebSocket 5.x
+//!
+//! Con `obs.enabled` el overlay se conecta a OBS y:
+//!
+//! - pausa el renderizado (como `POST /rendering/pause`) mientras la escena
+//!   de programa no es una de `obs.scenes`, y lo reanuda al volver a ella;
+//! - escribe los últimos `obs.text_lines` mensajes mostrados en la fuente de
+//!   texto `obs.text_source`, para quien prefiera componer el chat en OBS.
+//!
+//! El cliente es `obws` y solo se compila con la feature `obs`.
+
+use std::collections::VecDeque;
+use std::sync::{Mutex, OnceLock};
+#[cfg(feature = "obs")]
+use std::time::Duration;
+
+#[cfg(feature = "obs")]
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc;
+
+use crate::connection::ChatMessage;
+
+static GLOBAL_FEED: OnceLock<ObsTextFeed> = OnceLock::new();
+
+#[cfg(feature = "obs")]
+const MIN_RECONNECT_DELAY: Duration = Duration::from_secs(2);
+#[cfg(feature = "obs")]
+const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(60);
+
+/// `obs`
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct ObsConfig {
+    /// Desactivado por defecto
+    pub enabled: bool,
+    /// Servidor de OBS WebSocket (Herramientas → Ajustes del servidor WebSocket)
+    pub url: String,
+    pub password: Option<String>,
+    /// Escenas en las que se ve el overlay; en las demás se pausa. Vacío:
+    /// nunca se pausa
+    pub scenes: Vec<String>,
+    /// Fuente de texto (GDI+/FreeType 2) que recibe los mensajes
+    pub text_source: Option<String>,
+    /// Mensajes que se muestran a la vez en la fuente de texto
+    pub text_lines: usize,
+}
+
+impl Default for ObsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            url: "ws://127.0.0.1:4455".to_string(),
+            password: None,
+            scenes: Vec::new(),
+            text_source: None,
+            text_lines: 5,
+        }
+    }
+}
+
+/// Host y puerto de `obs.url` (`ws://host:puerto`)
+#[cfg(feature = "obs")]
+pub fn server_address(url: &str) -> Result<(String, u16), String> {
+    let url = url::Url::parse(url).map_err(|e| format!("invalid OBS url '{}': {}", url, e))?;
+    let host = url
+        .host_str()
+        .ok_or_else(|| format!("OBS url '{}' has no host", url))?;
+    let port = url.port_or_known_default().unwrap_or(4455);
+    Ok((host.to_string(), port))
+}
+
+/// Pausas que decide la escena de programa de OBS
+///
+/// Solo reanuda lo que pausó él: una pausa manual sigue hasta que se reanuda
+/// a mano.
+#[derive(Debug, Default)]
+pub struct ScenePauser {
+    scenes: Vec<String>,
+    paused_by_obs: bool,
+}
+
+impl ScenePauser {
+    pub fn new(scenes: Vec<String>) -> Self {
+        Self {
+            scenes,
+            paused_by_obs: false,
+        }
+    }
+
+    /// `Some(true)` si hay que pausar, `Some(false)` si hay que reanudar
+    pub fn scene_changed(&mut self, scene: &str, already_paused: bool) -> Option<bool> {
+        if self.scenes.is_empty() {
+            return None;
+        }
+        let visible = self.scenes.iter().any(|s| s == scene);
+        match (visible, self.paused_by_obs) {
+            (false, false) if !already_paused => {
+                self.paused_by_obs = true;
+                Some(true)
+            }
+            (true, true) => {
+                self.paused_by_obs = false;
+                Some(false)
+            }
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct FeedState {
+    max_lines: usize,
+    lines: VecDeque<String>,
+    sender: Option<mpsc::UnboundedSender<String>>,
+}
+
+/// Últimos mensajes mostrados, como texto para la fuente de OBS
+#[derive(Debug, Default)]
+pub struct ObsTextFeed {
+    state: Mutex<FeedState>,
+}
+
+impl ObsTextFeed {
+    pub fn global() -> &'static ObsTextFeed {
+        GLOBAL_FEED.get_or_init(ObsTextFeed::default)
+    }
+
+    /// Envía el texto por `sender` con cada mensaje nuevo
+    pub fn attach(&self, max_lines: usize, sender: mpsc::UnboundedSender<String>) {
+        let mut state = self.lock();
+        state.max_lines = max_lines.max(1);
+        state.sender = Some(sender);
+    }
+
+    /// Añade el mensaje; no hace nada si no hay fuente de texto configurada
+    pub fn push(&self, message: &ChatMessage) {
+        let mut state = self.lock();
+        if state.sender.is_none() {
+            return;
+        }
+        let name = message.display_name.as_deref().unwrap_or(&message.username);
+        state
+            .lines
+            .push_back(format!("{}: {}", name, message.content));
+        while state.lines.len() > state.max_lines {
+            state.lines.pop_front();
+        }
+        let text = state.lines.iter().cloned().collect::<Vec<_>>().join("\n");
+        if let Some(sender) = &state.sender {
+            if sender.send(text).is_err() {
+                state.sender = None;
+            }
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, FeedState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Mantiene la conexión con OBS; envía por `scenes` cada cambio de escena y
+/// escribe en la fuente de texto lo que llegue por `texts`
+#[cfg(feature = "obs")]
+pub async fn follow_obs(
+    config: ObsConfig,
+    scenes: mpsc::UnboundedSender<String>,
+    mut texts: mpsc::UnboundedReceiver<String>,
+) {
+    let mut delay = MIN_RECONNECT_DELAY;
+    let mut last_text = None;
+    while !scenes.is_closed() {
+        match run_session(&config, &scenes, &mut texts, &mut last_text).await {
+            Ok(()) => delay = MIN_RECONNECT_DELAY,
+            Err(e) => {
+                eprintln!("[OBS] ⚠️ OBS WebSocket connection failed: {}", e);
+                delay = (delay * 2).min(MAX_RECONNECT_DELAY);
+            }
+        }
+        tokio::time::sleep(delay).await;
+    }
+}
+
+#[cfg(not(feature = "obs"))]
+pub async fn follow_obs(
+    _config: ObsConfig,
+    _scenes: mpsc::UnboundedSender<String>,
+    _texts: mpsc::UnboundedReceiver<String>,
+) {
+    eprintln!("⚠️ OBS integration needs the `obs` feature (build with --features obs)");
+}
+
+/// Una sesión con OBS; termina bien cuando OBS cierra la conexión
+#[cfg(feature = "obs")]
+async fn run_session(
+    config: &ObsConfig,
+    scenes: &mpsc::UnboundedSender<String>,
+    texts: &mut mpsc::UnboundedReceiver<String>,
+    last_text: &mut Option<String>,
+) -> Result<(), String> {
+    use obws::events::Event;
+
+    let (host, port) = server_address(&config.url)?;
+    let client = obws::Client::connect_with_config(obws::client::ConnectConfig {
+        host,
+        port,
+        dangerous: None,
+        password: config.password.as_deref(),
+        event_subscriptions: Some(obws::requests::EventSubscription::SCENES),
+        broadcast_capacity: obws::client::DEFAULT_BROADCAST_CAPACITY,
+        connect_timeout: obws::client::DEFAULT_CONNECT_TIMEOUT,
+    })
+    .await
+    .map_err(|e| e.to_string())?;
+    let events = client.events().map_err(|e| e.to_string())?;
+    futures::pin_mut!(events);
+    println!("[OBS] ✅ Connected to OBS WebSocket at {}", config.url);
+
+    let scene = client
+        .scenes()
+        .current_program_scene()
+        .await
+        .map_err(|e| e.to_string())?;
+    if scenes.send(scene.id.name).is_err() {
+        return Ok(());
+    }
+    if let (Some(source), Some(text)) = (&config.text_source, last_text.as_ref()) {
+        set_text(&client, source, text).await?;
+    }
+
+    loop {
+        tokio::select! {
+            event = events.next() => match event {
+                Some(Event::CurrentProgramSceneChanged { id }) => {
+                    if scenes.send(id.name).is_err() {
+                        return Ok(());
+                    }
+                }
+                Some(Event::ExitStarted | Event::ServerStopping) | None => return Ok(()),
+                Some(_) => {}
+            },
+            Some(mut text) = texts.recv() => {
+                // Solo importa el texto más reciente
+                while let Ok(next) = texts.try_recv() {
+                    text = next;
+                }
+                if let Some(source) = &config.text_source {
+                    set_text(&client, source, &text).await?;
+                }
+                *last_text = Some(text);
+            }
+        }
+    }
+}
+
+#[cfg(feature = "obs")]
+async fn set_text(client: &obws::Client, source: &str, text: &str) -> Result<(), String> {
+    client
+        .inputs()
+        .set_settings(obws::requests::inputs::SetSettings {
+            input: obws::requests::inputs::InputId::Name(source),
+            settings: &serde_json::json!({ "text": text }),
+            overlay: Some(true),
+        })
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scene_pauser_only_resumes_its_own_pause() {
+        let mut pauser = ScenePauser::new(vec!["Gameplay".to_string()]);
+        assert_eq!(pauser.scene_changed("Gameplay", false), None);
+        assert_eq!(pauser.scene_changed("BRB", false), Some(true));
+        assert_eq!(pauser.scene_changed("Cinematic", true), None);
+        assert_eq!(pauser.scene_changed("Gameplay", true), Some(false));
+
+        // Pausa manual: cambiar de escena no la levanta
+        assert_eq!(pauser.scene_changed("BRB", true), None);
+        assert_eq!(pauser.scene_changed("Gameplay", true), None);
+
+        let mut disabled = ScenePauser::new(Vec::new());
+        assert_eq!(disabled.scene_changed("BRB", false), None);
+    }
+
+    #[cfg(feature = "obs")]
+    #[test]
+    fn test_server_address_from_url() {
+        assert_eq!(
+            server_address("ws://127.0.0.1:4455").unwrap(),
+            ("127.0.0.1".to_string(), 4455)
+        );
+        assert_eq!(
+            server_address("ws://obs.lan").unwrap(),
+            ("obs.lan".to_string(), 80)
+        );
+        assert!(server_address("127.0.0.1:4455").is_err());
+    }
+}
@@ -0,0 +1,111 @@
+//! Ventanas del overlay independientes del backend
+//!
+//! `WindowTracker` y el bucle principal trabajan con [`OverlayWindow`], que
+//! implementan las ventanas de GTK (`window`), Win32 (`windows`) y AppKit
+//! (`macos`), y con
+//! [`WindowBackend`], que crea esas ventanas y atiende el sistema de
+//! ventanas. [`PlatformBackend`] es el único punto que elige el backend de la
+//! plataforma; uno nuevo solo tiene que implementar ambos traits y añadirse ahí.
+
+use std::time::Duration;
+
+use tokio::time::Instant;
+use twitch_irc::message::Emote;
+
+use crate::config::{FadeTiming, WindowStyle};
+
+/// Ventana de un mensaje en pantalla
+#[async_trait::async_trait(?Send)]
+pub trait OverlayWindow: Sized {
+    /// Ventana oculta que guarda el pool para el siguiente mensaje
+    type Recycled: RecycledOverlayWindow;
+
+    /// Inicio del tiempo visible; las repeticiones lo reinician
+    fn created(&self) -> Instant;
+    fn duration(&self) -> Duration;
+    /// Momento en que se mostró; no cambia con las repeticiones
+    fn shown(&self) -> Instant;
+    fn fade(&self) -> FadeTiming;
+
+    /// Fracción del tiempo visible ya transcurrida, de 0 a 1
+    fn set_progress(&mut self, progress: f64);
+    /// Aplica `factor` (ver [`FadeTiming::factor`]) a la opacidad configurada
+    fn set_fade(&mut self, factor: f64);
+    /// Muestra `×count` junto al nombre y vuelve a empezar el tiempo visible
+    fn set_repeat_count(&mut self, count: u32);
+    /// Sustituye el mensaje mostrado sin cerrar la ventana ni moverla y
+    /// vuelve a empezar el tiempo visible
+    async fn update_content(
+        &mut self,
+        header: &str,
+        body: &str,
+        emotes: &[Emote],
+        style: &WindowStyle,
+    );
+    /// Oculta la ventana y quita el contenido del mensaje
+    fn recycle(self) -> Self::Recycled;
+    fn close(self);
+
+    /// Si en `now` ya pasó su tiempo visible
+    fn is_expired(&self, now: Instant) -> bool {
+        now - self.created() >= self.duration()
+    }
+
+    /// Valor de [`set_progress`](OverlayWindow::set_progress) en `now`
+    fn progress_at(&self, now: Instant) -> f64 {
+        (now - self.created()).as_secs_f64() / self.duration().as_secs_f64()
+    }
+}
+
+/// Ventana del pool, oculta y sin mensaje
+pub trait RecycledOverlayWindow {
+    fn close(self);
+}
+
+/// Sistema de ventanas de una plataforma: crea las ventanas de los mensajes
+/// y atiende sus eventos desde el bucle principal
+#[async_trait::async_trait(?Send)]
+pub trait WindowBackend {
+    type Window: OverlayWindow;
+    /// Geometría del monitor en el que se colocan las ventanas
+    type Monitor: Copy;
+
+    /// Monitor principal, en píxeles lógicos
+    fn monitor_geometry() -> Self::Monitor;
+    /// Ancho y alto de `monitor`
+    fn monitor_size(monitor: &Self::Monitor) -> (i32, i32);
+    /// Atiende los eventos pendientes del sistema de ventanas sin bloquear;
+    /// `false` si el backend pide salir
+    fn process_events() -> bool;
+
+    /// Compone el mensaje en un bitmap con el texto rasterizado por el
+    /// backend (`display.precomposed`)
+    async fn precompose(
+        spec: crate::emotes::MessageBitmapSpec,
+        style: &WindowStyle,
+        position: (i32, i32),
+        font_family: &str,
+    ) -> Option<image::RgbaImage>;
+
+    /// Crea la ventana de un mensaje, reutilizando `recycled` si la hay
+    async fn spawn(
+        header: &str,
+        body: &str,
+        emotes: &[Emote],
+        position: (i32, i32),
+        monitor: Self::Monitor,
+        style: &WindowStyle,
+        recycled: Option<<Self::Window as OverlayWindow>::Recycled>,
+    ) -> Self::Window;
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+pub type PlatformBackend = crate::window::GtkBackend;
+#[cfg(target_os = "macos")]
+pub type PlatformBackend = crate::macos::MacBackend;
+#[cfg(windows)]
+pub type PlatformBackend = crate::windows::Win32Backend;
+
+pub type PlatformWindow = <PlatformBackend as WindowBackend>::Window;
+pub type PlatformRecycledWindow = <PlatformWindow as OverlayWindow>::Recycled;
+pub type MonitorGeometry = <PlatformBackend as WindowBackend>::Monitor;
@@ -0,0 +1,175 @@
+//! Pausa manual del renderizado (p. ej. durante una cinemática)
+//!
+//! Mientras está en pausa las conexiones siguen abiertas y los mensajes ya
+//! procesados se guardan en un búfer de `pause.buffer_size`; al reanudar se
+//! muestran en orden. Se controla con `POST /rendering/pause` y
+//! `/rendering/resume` de la API de control y, en unix, con `SIGUSR1`.
+
+use std::collections::VecDeque;
+use std::sync::{Mutex, OnceLock};
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::connection::ChatMessage;
+
+static GLOBAL_PAUSE: OnceLock<RenderPause> = OnceLock::new();
+
+/// `pause`
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct PauseConfig {
+    /// Mensajes que se guardan durante la pausa; los más antiguos se descartan
+    pub buffer_size: usize,
+}
+
+impl Default for PauseConfig {
+    fn default() -> Self {
+        Self { buffer_size: 50 }
+    }
+}
+
+/// Estado de la pausa que devuelve `GET /rendering`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, JsonSchema)]
+pub struct RenderingStatus {
+    pub paused: bool,
+    /// Mensajes en el búfer, pendientes de mostrar
+    pub buffered: usize,
+    /// Mensajes que no cupieron en el búfer desde que empezó la pausa
+    pub dropped: usize,
+}
+
+/// Mensajes acumulados durante la pausa
+#[derive(Debug, Default)]
+pub struct Resumed {
+    pub messages: Vec<ChatMessage>,
+    pub dropped: usize,
+}
+
+#[derive(Debug, Default)]
+struct PauseState {
+    config: PauseConfig,
+    paused: bool,
+    buffer: VecDeque<ChatMessage>,
+    dropped: usize,
+}
+
+/// Interruptor del renderizado, compartido por el bucle principal y la API
+#[derive(Debug, Default)]
+pub struct RenderPause {
+    state: Mutex<PauseState>,
+}
+
+impl RenderPause {
+    pub fn new(config: PauseConfig) -> Self {
+        Self {
+            state: Mutex::new(PauseState {
+                config,
+                ..PauseState::default()
+            }),
+        }
+    }
+
+    pub fn global() -> &'static RenderPause {
+        GLOBAL_PAUSE.get_or_init(|| Self::new(PauseConfig::default()))
+    }
+
+    /// Aplica la configuración sin salir de la pausa
+    pub fn configure(&self, config: PauseConfig) {
+        let mut state = self.lock();
+        state.config = config;
+        while state.buffer.len() > state.config.buffer_size {
+            state.buffer.pop_front();
+            state.dropped += 1;
+        }
+    }
+
+    /// `false` si ya estaba en pausa
+    pub fn pause(&self) -> bool {
+        let mut state = self.lock();
+        !std::mem::replace(&mut state.paused, true)
+    }
+
+    /// Sale de la pausa y devuelve lo acumulado; `None` si no estaba en pausa
+    pub fn resume(&self) -> Option<Resumed> {
+        let mut state = self.lock();
+        if !std::mem::replace(&mut state.paused, false) {
+            return None;
+        }
+        Some(Resumed {
+            messages: state.buffer.drain(..).collect(),
+            dropped: std::mem::take(&mut state.dropped),
+        })
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.lock().paused
+    }
+
+    /// Guarda el mensaje si está en pausa; `false` si hay que mostrarlo ya
+    pub fn hold(&self, message: &ChatMessage) -> bool {
+        let mut state = self.lock();
+        if !state.paused {
+            return false;
+        }
+        if state.config.buffer_size == 0 {
+            state.dropped += 1;
+            return true;
+        }
+        if state.buffer.len() >= state.config.buffer_size {
+            state.buffer.pop_front();
+            state.dropped += 1;
+        }
+        state.buffer.push_back(message.clone());
+        true
+    }
+
+    pub fn status(&self) -> RenderingStatus {
+        let state = self.lock();
+        RenderingStatus {
+            paused: state.paused,
+            buffered: state.buffer.len(),
+            dropped: state.dropped,
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, PauseState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_paused_messages_are_buffered_until_resume() {
+        let pause = RenderPause::new(PauseConfig { buffer_size: 2 });
+        assert!(!pause.hold(&ChatMessage::test("viewer", "live")));
+        assert!(pause.resume().is_none());
+
+        assert!(pause.pause());
+        assert!(!pause.pause());
+        for content in ["a", "b", "c"] {
+            assert!(pause.hold(&ChatMessage::test("viewer", content)));
+        }
+        assert_eq!(
+            pause.status(),
+            RenderingStatus {
+                paused: true,
+                buffered: 2,
+                dropped: 1,
+            }
+        );
+
+        let resumed = pause.resume().unwrap();
+        let contents: Vec<_> = resumed
+            .messages
+            .iter()
+            .map(|m| m.content.as_str())
+            .collect();
+        assert_eq!(contents, ["b", "c"]);
+        assert_eq!(resumed.dropped, 1);
+        assert_eq!(pause.status(), RenderingStatus::default());
+    }
+}
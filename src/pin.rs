@@ -0,0 +1,331 @@
+//! Mensajes fijados desde el chat con `!pin` / `!unpin`
+//!
+//! Un moderador (o el nivel mínimo configurado) puede fijar un mensaje
+//! respondiéndole con `!pin` o indicando su id (`!pin <message-id>`). El mensaje
+//! fijado ocupa un hueco persistente del overlay hasta `!unpin` o hasta que
+//! vence `timeout_secs`.
+//!
+//! Los mensajes fijados por la propia plataforma (`MessageType::Pinned` /
+//! `Unpinned`, p.ej. en Kick) ocupan el mismo hueco y solo se quitan cuando la
+//! plataforma los desfija.
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use crate::config::PinConfig;
+use crate::connection::{ChatMessage, MessageType};
+use crate::mapping::UserLevel;
+
+/// Comando de fijado reconocido en un mensaje
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PinCommand {
+    /// `!pin` en respuesta a otro mensaje, o `!pin <message-id>`
+    Pin {
+        message_id: String,
+    },
+    Unpin,
+}
+
+/// Qué hacer con un mensaje tras pasar por el `PinBoard`
+#[derive(Debug, Clone)]
+pub enum PinAction {
+    /// Mensaje normal: se muestra como siempre
+    Display,
+    /// Fijar este mensaje (sustituye al fijado anterior)
+    Pin(Box<ChatMessage>),
+    /// Quitar el mensaje fijado
+    Unpin,
+    /// Comando consumido sin efecto (mensaje no encontrado, sin argumentos...)
+    Ignore,
+}
+
+/// Reconoce `!pin` / `!unpin`, ignorando menciones iniciales de las respuestas
+pub fn parse_pin_command(message: &ChatMessage) -> Option<PinCommand> {
+    let mut words = message
+        .content
+        .split_whitespace()
+        .skip_while(|word| word.starts_with('@'));
+
+    match words.next()?.to_lowercase().as_str() {
+        "!pin" => {
+            let message_id = words
+                .next()
+                .map(str::to_string)
+                .or_else(|| message.metadata.reply_to.clone())?;
+            Some(PinCommand::Pin { message_id })
+        }
+        "!unpin" => Some(PinCommand::Unpin),
+        _ => None,
+    }
+}
+
+/// Nivel de usuario deducido de las insignias del mensaje
+pub fn user_level_of(message: &ChatMessage) -> UserLevel {
+    message
+        .badges
+        .iter()
+        .map(|badge| match badge.name.to_lowercase().as_str() {
+            "broadcaster" => UserLevel::Broadcaster,
+            "moderator" | "mod" => UserLevel::Moderator,
+            "global_mod" => UserLevel::GlobalModerator,
+            "admin" => UserLevel::Admin,
+            "staff" => UserLevel::Staff,
+            "vip" => UserLevel::Vip,
+            "subscriber" | "founder" | "og" => UserLevel::Subscriber,
+            _ => UserLevel::Normal,
+        })
+        .max_by_key(user_level_rank)
+        .unwrap_or(UserLevel::Normal)
+}
+
+/// Orden de privilegios entre niveles de usuario
+pub fn user_level_rank(level: &UserLevel) -> u8 {
+    match level {
+        UserLevel::Unknown | UserLevel::Normal => 0,
+        UserLevel::Subscriber => 1,
+        UserLevel::Vip => 2,
+        UserLevel::Moderator => 3,
+        UserLevel::GlobalModerator => 4,
+        UserLevel::Broadcaster => 5,
+        UserLevel::Staff | UserLevel::Admin => 6,
+    }
+}
+
+#[derive(Debug, Clone)]
+struct PinnedMessage {
+    message_id: String,
+    pinned_at: Instant,
+    /// Fijado por la plataforma: no vence por `timeout_secs`
+    from_platform: bool,
+}
+
+/// Estado del mensaje fijado y de los mensajes recientes que se pueden fijar
+#[derive(Debug)]
+pub struct PinBoard {
+    config: PinConfig,
+    recent: VecDeque<ChatMessage>,
+    pinned: Option<PinnedMessage>,
+}
+
+impl PinBoard {
+    pub fn new(config: PinConfig) -> Self {
+        Self {
+            recent: VecDeque::with_capacity(config.history_size),
+            config,
+            pinned: None,
+        }
+    }
+
+    /// Procesa un mensaje entrante y decide si es un comando de fijado
+    pub fn observe(&mut self, message: &ChatMessage, now: Instant) -> PinAction {
+        // Los eventos de fijado de la plataforma nunca se muestran como mensajes
+        match message.message_type {
+            MessageType::Pinned => return self.platform_pin(message, now),
+            MessageType::Unpinned => return self.platform_unpin(),
+            _ => {}
+        }
+
+        if !self.config.enabled {
+            return PinAction::Display;
+        }
+
+        let Some(command) = parse_pin_command(message) else {
+            self.remember(message);
+            return PinAction::Display;
+        };
+
+        let min_rank = user_level_rank(&self.config.min_user_level);
+        if user_level_rank(&user_level_of(message)) < min_rank {
+            // Sin permisos el comando se muestra como un mensaje más
+            self.remember(message);
+            return PinAction::Display;
+        }
+
+        match command {
+            PinCommand::Pin { message_id } => {
+                let Some(target) = self
+                    .recent
+                    .iter()
+                    .find(|m| m.id == message_id && m.channel == message.channel)
+                    .cloned()
+                else {
+                    return PinAction::Ignore;
+                };
+                self.pinned = Some(PinnedMessage {
+                    message_id,
+                    pinned_at: now,
+                    from_platform: false,
+                });
+                PinAction::Pin(Box::new(target))
+            }
+            PinCommand::Unpin => {
+                if self.pinned.take().is_some() {
+                    PinAction::Unpin
+                } else {
+                    PinAction::Ignore
+                }
+            }
+        }
+    }
+
+    fn platform_pin(&mut self, message: &ChatMessage, now: Instant) -> PinAction {
+        if !self.config.enabled || !self.config.follow_platform_pins {
+            return PinAction::Ignore;
+        }
+        self.pinned = Some(PinnedMessage {
+            message_id: message.id.clone(),
+            pinned_at: now,
+            from_platform: true,
+        });
+        // Se muestra con el estilo de un mensaje normal
+        let mut pinned = message.clone();
+        pinned.message_type = MessageType::Normal;
+        PinAction::Pin(Box::new(pinned))
+    }
+
+    fn platform_unpin(&mut self) -> PinAction {
+        match &self.pinned {
+            Some(pinned) if pinned.from_platform => {
+                self.pinned = None;
+                PinAction::Unpin
+            }
+            _ => PinAction::Ignore,
+        }
+    }
+
+    /// Id del mensaje fijado actualmente
+    pub fn pinned_message_id(&self) -> Option<&str> {
+        self.pinned.as_ref().map(|p| p.message_id.as_str())
+    }
+
+    /// Quita el mensaje fijado si venció su tiempo; devuelve `true` si lo quitó
+    pub fn expire(&mut self, now: Instant) -> bool {
+        if self.config.timeout_secs == 0 {
+            return false;
+        }
+        let timeout = Duration::from_secs(self.config.timeout_secs);
+        match &self.pinned {
+            Some(pinned)
+                if !pinned.from_platform
+                    && now.saturating_duration_since(pinned.pinned_at) >= timeout =>
+            {
+                self.pinned = None;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn remember(&mut self, message: &ChatMessage) {
+        if self.config.history_size == 0 {
+            return;
+        }
+        if self.recent.len() >= self.config.history_size {
+            self.recent.pop_front();
+        }
+        self.recent.push_back(message.clone());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::connection::MessageType;
+
+    fn board() -> PinBoard {
+        PinBoard::new(PinConfig {
+            timeout_secs: 60,
+            ..PinConfig::default()
+        })
+    }
+
+    #[test]
+    fn test_moderator_pins_reply_and_unpins() {
+        let mut board = board();
+        let now = Instant::now();
+        assert!(matches!(
+            board.observe(
+                &ChatMessage::test("user", "important info").with_id("m1"),
+                now
+            ),
+            PinAction::Display
+        ));
+
+        let mut reply = ChatMessage::test("user", "@user !pin")
+            .with_id("m2")
+            .with_badge("moderator");
+        reply.metadata.reply_to = Some("m1".to_string());
+        match board.observe(&reply, now) {
+            PinAction::Pin(pinned) => assert_eq!(pinned.content, "important info"),
+            other => panic!("expected pin, got {:?}", other),
+        }
+        assert_eq!(board.pinned_message_id(), Some("m1"));
+
+        let unpin = ChatMessage::test("user", "!unpin")
+            .with_id("m3")
+            .with_badge("broadcaster");
+        assert!(matches!(board.observe(&unpin, now), PinAction::Unpin));
+        assert_eq!(board.pinned_message_id(), None);
+    }
+
+    #[test]
+    fn test_regular_users_cannot_pin() {
+        let mut board = board();
+        let now = Instant::now();
+        board.observe(&ChatMessage::test("user", "hello").with_id("m1"), now);
+
+        let attempt = ChatMessage::test("user", "!pin m1")
+            .with_id("m2")
+            .with_badge("subscriber");
+        assert!(matches!(board.observe(&attempt, now), PinAction::Display));
+        assert_eq!(board.pinned_message_id(), None);
+    }
+
+    #[test]
+    fn test_pin_by_id_expires_after_timeout() {
+        let mut board = board();
+        let now = Instant::now();
+        board.observe(&ChatMessage::test("user", "hello").with_id("m1"), now);
+        assert!(matches!(
+            board.observe(
+                &ChatMessage::test("user", "!pin m1")
+                    .with_id("m2")
+                    .with_badge("moderator"),
+                now
+            ),
+            PinAction::Pin(_)
+        ));
+        assert!(matches!(
+            board.observe(
+                &ChatMessage::test("user", "!pin unknown")
+                    .with_id("m3")
+                    .with_badge("moderator"),
+                now
+            ),
+            PinAction::Ignore
+        ));
+
+        assert!(!board.expire(now + Duration::from_secs(30)));
+        assert!(board.expire(now + Duration::from_secs(60)));
+        assert_eq!(board.pinned_message_id(), None);
+    }
+
+    #[test]
+    fn test_platform_pins_follow_platform_unpin() {
+        let mut board = board();
+        let now = Instant::now();
+
+        let mut pinned = ChatMessage::test("user", "welcome!").with_id("k1");
+        pinned.message_type = MessageType::Pinned;
+        match board.observe(&pinned, now) {
+            PinAction::Pin(shown) => assert!(matches!(shown.message_type, MessageType::Normal)),
+            other => panic!("expected pin, got {:?}", other),
+        }
+        assert!(!board.expire(now + Duration::from_secs(3600)));
+
+        let mut unpinned = ChatMessage::test("user", "").with_id("");
+        unpinned.message_type = MessageType::Unpinned;
+        assert!(matches!(board.observe(&unpinned, now), PinAction::Unpin));
+        assert!(matches!(board.observe(&unpinned, now), PinAction::Ignore));
+    }
+}
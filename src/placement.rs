@@ -0,0 +1,409 @@
+//! Colocación de las ventanas de mensajes
+//!
+//! `display.placement_mode` elige la estrategia, siempre dentro de la zona de
+//! `display.area`:
+//!
+//! - `grid` (por defecto): rejilla de `grid_size`×`grid_size` barajada, en
+//!   turno rotatorio, saltando los huecos con una ventana visible
+//! - `column`: columna de chat, de arriba abajo y vuelta a empezar
+//! - `random`: posición aleatoria que no tape otra ventana si es posible
+//! - `follow_cursor`: junto al puntero del ratón, apiladas hacia abajo
+//! - `anchor`: apiladas desde la esquina `placement_anchor`, en el primer
+//!   hueco libre
+//!
+//! Las ventanas apiladas se separan `placement_spacing` píxeles en vertical.
+
+use rand::seq::SliceRandom;
+use rand::{thread_rng, Rng};
+use serde::{Deserialize, Serialize};
+
+use crate::config::DisplayConfig;
+
+/// `display.placement_mode`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PlacementMode {
+    #[default]
+    Grid,
+    Column,
+    Random,
+    FollowCursor,
+    Anchor,
+}
+
+/// Esquina de la zona desde la que se apilan las ventanas en modo `anchor`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PlacementAnchor {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    #[default]
+    BottomRight,
+}
+
+/// Posición del puntero relativa al monitor, si el backend la conoce
+pub type CursorPosition = fn() -> Option<(i32, i32)>;
+
+/// Estrategia de colocación
+pub trait PlacementStrategy: Send {
+    /// Posición de la próxima ventana; `occupied` son las de las ventanas visibles
+    fn next_position(&mut self, occupied: &[(i32, i32)]) -> (i32, i32);
+
+    /// Posición fija del modo feed (carga alta), donde se reemplazan los mensajes
+    fn feed_position(&self) -> (i32, i32);
+}
+
+/// Geometría compartida por las estrategias
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PlacementArea {
+    pub monitor: (i32, i32),
+    pub margin: i32,
+    /// Zona de `display.area`: x, y, ancho y alto
+    pub bounds: (i32, i32, i32, i32),
+    pub window_size: i32,
+    /// Distancia vertical entre ventanas apiladas
+    pub spacing: i32,
+}
+
+impl PlacementArea {
+    pub fn from_display(display: &DisplayConfig, monitor: (i32, i32)) -> Self {
+        Self {
+            monitor,
+            margin: display.monitor_margin,
+            bounds: display
+                .area
+                .bounds(monitor, display.monitor_margin, display.window_size),
+            window_size: display.window_size,
+            spacing: display.placement_spacing.max(1),
+        }
+    }
+
+    /// Parte inferior utilizable de la zona, sin salirse del monitor
+    fn bottom(&self) -> i32 {
+        let (_, y, _, h) = self.bounds;
+        (y + h)
+            .min(self.monitor.1 - self.margin)
+            .max(y + self.spacing)
+    }
+
+    fn right(&self) -> i32 {
+        let (x, _, w, _) = self.bounds;
+        (x + w)
+            .min(self.monitor.0 - self.margin)
+            .max(x + self.window_size)
+    }
+
+    /// Huecos de la columna `x`, de arriba abajo
+    fn column(&self, x: i32) -> Vec<(i32, i32)> {
+        let top = self.bounds.1;
+        let rows = ((self.bottom() - top) / self.spacing).max(1);
+        (0..rows).map(|row| (x, top + row * self.spacing)).collect()
+    }
+
+    fn collides(&self, position: (i32, i32), occupied: &[(i32, i32)]) -> bool {
+        occupied.iter().any(|&(x, y)| {
+            (x - position.0).abs() < self.window_size && (y - position.1).abs() < self.spacing
+        })
+    }
+
+    /// Limita `position` para que la ventana quede dentro del monitor
+    fn clamp(&self, (x, y): (i32, i32)) -> (i32, i32) {
+        let max_x = (self.monitor.0 - self.margin - self.window_size).max(0);
+        let max_y = (self.monitor.1 - self.margin - self.spacing).max(0);
+        (x.clamp(0, max_x), y.clamp(0, max_y))
+    }
+}
+
+/// Recorre `slots` desde `next` y devuelve el primero libre, o el de `next`
+/// si están todos ocupados
+fn first_free(
+    area: &PlacementArea,
+    slots: &[(i32, i32)],
+    next: &mut usize,
+    occupied: &[(i32, i32)],
+) -> (i32, i32) {
+    let index = (0..slots.len())
+        .map(|offset| (*next + offset) % slots.len())
+        .find(|&index| !area.collides(slots[index], occupied))
+        .unwrap_or(*next % slots.len());
+    *next = (index + 1) % slots.len();
+    slots[index]
+}
+
+/// Rejilla barajada en turno rotatorio
+pub struct GridPlacement {
+    area: PlacementArea,
+    positions: Vec<(i32, i32)>,
+    next: usize,
+}
+
+impl GridPlacement {
+    pub fn new(display: &DisplayConfig, monitor: (i32, i32)) -> Self {
+        let mut positions = display.area.grid_positions(
+            monitor,
+            display.monitor_margin,
+            display.window_size,
+            display.grid_size,
+        );
+        positions.shuffle(&mut thread_rng());
+        Self {
+            area: PlacementArea::from_display(display, monitor),
+            positions,
+            next: 0,
+        }
+    }
+}
+
+impl PlacementStrategy for GridPlacement {
+    fn next_position(&mut self, occupied: &[(i32, i32)]) -> (i32, i32) {
+        first_free(&self.area, &self.positions, &mut self.next, occupied)
+    }
+
+    fn feed_position(&self) -> (i32, i32) {
+        self.positions[0]
+    }
+}
+
+/// Columna de chat a la izquierda de la zona, en orden de llegada
+pub struct ColumnPlacement {
+    slots: Vec<(i32, i32)>,
+    next: usize,
+}
+
+impl ColumnPlacement {
+    pub fn new(area: PlacementArea) -> Self {
+        Self {
+            slots: area.column(area.bounds.0),
+            next: 0,
+        }
+    }
+}
+
+impl PlacementStrategy for ColumnPlacement {
+    fn next_position(&mut self, _occupied: &[(i32, i32)]) -> (i32, i32) {
+        let position = self.slots[self.next];
+        self.next = (self.next + 1) % self.slots.len();
+        position
+    }
+
+    fn feed_position(&self) -> (i32, i32) {
+        self.slots[self.slots.len() - 1]
+    }
+}
+
+/// Posiciones aleatorias dentro de la zona
+pub struct RandomPlacement {
+    area: PlacementArea,
+}
+
+/// Intentos de encontrar un hueco libre antes de aceptar uno ocupado
+const RANDOM_ATTEMPTS: usize = 16;
+
+impl RandomPlacement {
+    pub fn new(area: PlacementArea) -> Self {
+        Self { area }
+    }
+}
+
+impl PlacementStrategy for RandomPlacement {
+    fn next_position(&mut self, occupied: &[(i32, i32)]) -> (i32, i32) {
+        let (x, y, _, _) = self.area.bounds;
+        let max_x = (self.area.right() - self.area.window_size).max(x);
+        let max_y = (self.area.bottom() - self.area.spacing).max(y);
+        let mut rng = thread_rng();
+        let mut position = (x, y);
+        for _ in 0..RANDOM_ATTEMPTS {
+            position = (rng.gen_range(x..=max_x), rng.gen_range(y..=max_y));
+            if !self.area.collides(position, occupied) {
+                break;
+            }
+        }
+        position
+    }
+
+    fn feed_position(&self) -> (i32, i32) {
+        (self.area.bounds.0, self.area.bounds.1)
+    }
+}
+
+/// Junto al puntero; sin puntero conocido se usa la rejilla
+pub struct FollowCursorPlacement {
+    area: PlacementArea,
+    cursor: CursorPosition,
+    fallback: GridPlacement,
+}
+
+/// Separación entre el puntero y la esquina de la ventana
+const CURSOR_OFFSET: i32 = 16;
+
+impl FollowCursorPlacement {
+    pub fn new(display: &DisplayConfig, monitor: (i32, i32), cursor: CursorPosition) -> Self {
+        Self {
+            area: PlacementArea::from_display(display, monitor),
+            cursor,
+            fallback: GridPlacement::new(display, monitor),
+        }
+    }
+}
+
+impl PlacementStrategy for FollowCursorPlacement {
+    fn next_position(&mut self, occupied: &[(i32, i32)]) -> (i32, i32) {
+        let Some((x, y)) = (self.cursor)() else {
+            return self.fallback.next_position(occupied);
+        };
+        let mut position = self.area.clamp((x + CURSOR_OFFSET, y + CURSOR_OFFSET));
+        // Apila hacia abajo las ventanas que coinciden junto al puntero
+        for _ in 0..occupied.len() {
+            if !self.area.collides(position, occupied) {
+                break;
+            }
+            let below = self
+                .area
+                .clamp((position.0, position.1 + self.area.spacing));
+            if below == position {
+                break;
+            }
+            position = below;
+        }
+        position
+    }
+
+    fn feed_position(&self) -> (i32, i32) {
+        self.fallback.feed_position()
+    }
+}
+
+/// Apiladas desde una esquina de la zona, en el primer hueco libre
+pub struct AnchorPlacement {
+    area: PlacementArea,
+    slots: Vec<(i32, i32)>,
+    next: usize,
+}
+
+impl AnchorPlacement {
+    pub fn new(area: PlacementArea, anchor: PlacementAnchor) -> Self {
+        let x = match anchor {
+            PlacementAnchor::TopLeft | PlacementAnchor::BottomLeft => area.bounds.0,
+            PlacementAnchor::TopRight | PlacementAnchor::BottomRight => {
+                area.right() - area.window_size
+            }
+        };
+        let mut slots = area.column(x);
+        if matches!(
+            anchor,
+            PlacementAnchor::BottomLeft | PlacementAnchor::BottomRight
+        ) {
+            // Pegadas al borde inferior, de abajo arriba
+            let bottom = area.bottom();
+            for (row, slot) in slots.iter_mut().enumerate() {
+                slot.1 = bottom - (row as i32 + 1) * area.spacing;
+            }
+        }
+        Self {
+            area,
+            slots,
+            next: 0,
+        }
+    }
+}
+
+impl PlacementStrategy for AnchorPlacement {
+    fn next_position(&mut self, occupied: &[(i32, i32)]) -> (i32, i32) {
+        // Siempre desde la esquina: los huecos que se liberan se reutilizan antes
+        self.next = 0;
+        first_free(&self.area, &self.slots, &mut self.next, occupied)
+    }
+
+    fn feed_position(&self) -> (i32, i32) {
+        self.slots[0]
+    }
+}
+
+/// Estrategia de `display.placement_mode`
+pub fn strategy_for(
+    display: &DisplayConfig,
+    monitor: (i32, i32),
+    cursor: CursorPosition,
+) -> Box<dyn PlacementStrategy> {
+    let area = PlacementArea::from_display(display, monitor);
+    match display.placement_mode {
+        PlacementMode::Grid => Box::new(GridPlacement::new(display, monitor)),
+        PlacementMode::Column => Box::new(ColumnPlacement::new(area)),
+        PlacementMode::Random => Box::new(RandomPlacement::new(area)),
+        PlacementMode::FollowCursor => {
+            Box::new(FollowCursorPlacement::new(display, monitor, cursor))
+        }
+        PlacementMode::Anchor => Box::new(AnchorPlacement::new(area, display.placement_anchor)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::presets::DisplayArea;
+
+    const MONITOR: (i32, i32) = (1920, 1080);
+
+    fn display_config(mode: PlacementMode) -> DisplayConfig {
+        let mut display = Config::default().display;
+        display.placement_mode = mode;
+        display.area = DisplayArea::Side;
+        display.monitor_margin = 40;
+        display.window_size = 200;
+        display.placement_spacing = 100;
+        display
+    }
+
+    #[test]
+    fn test_anchor_stacks_from_the_corner_and_reuses_free_slots() {
+        let mut display = display_config(PlacementMode::Anchor);
+        display.placement_anchor = PlacementAnchor::BottomRight;
+        let mut placement = strategy_for(&display, MONITOR, || None);
+
+        let first = placement.next_position(&[]);
+        assert_eq!(first, (1920 - 40 - 200, 1040 - 100));
+        let second = placement.next_position(&[first]);
+        assert_eq!(second, (first.0, first.1 - 100));
+        let third = placement.next_position(&[first, second]);
+        assert_eq!(third.1, first.1 - 200);
+
+        // Se cerró la primera: su hueco es el siguiente
+        assert_eq!(placement.next_position(&[second, third]), first);
+        assert_eq!(placement.feed_position(), first);
+    }
+
+    #[test]
+    fn test_column_and_grid_take_turns() {
+        let mut column = strategy_for(&display_config(PlacementMode::Column), MONITOR, || None);
+        let positions: Vec<_> = (0..11).map(|_| column.next_position(&[])).collect();
+        assert_eq!(positions[0], (1440, 0));
+        assert_eq!(positions[1], (1440, 100));
+        // 1040 px de alto útil: diez huecos y vuelta a empezar
+        assert_eq!(positions[10], positions[0]);
+
+        let mut grid_display = display_config(PlacementMode::Grid);
+        grid_display.grid_size = 2;
+        let mut grid = strategy_for(&grid_display, MONITOR, || None);
+        let first = grid.next_position(&[]);
+        // Con la primera aún visible no se repite hasta agotar la rejilla
+        let others: Vec<_> = (0..3).map(|_| grid.next_position(&[first])).collect();
+        assert!(!others.contains(&first));
+    }
+
+    #[test]
+    fn test_follow_cursor_clamps_and_stacks() {
+        let display = display_config(PlacementMode::FollowCursor);
+        let mut placement = strategy_for(&display, MONITOR, || Some((1900, 100)));
+        let first = placement.next_position(&[]);
+        assert_eq!(first, (1920 - 40 - 200, 116));
+        assert_eq!(placement.next_position(&[first]), (first.0, 216));
+
+        let mut random = strategy_for(&display_config(PlacementMode::Random), MONITOR, || None);
+        for _ in 0..20 {
+            let (x, y) = random.next_position(&[]);
+            assert!((1440..=1680).contains(&x) && (0..=940).contains(&y));
+        }
+    }
+}
@@ -134,8 +134,10 @@ impl BasePlatform {
                 if let (Ok(start), Ok(end)) =
                     (pos_parts[0].parse::<usize>(), pos_parts[1].parse::<usize>())
                 {
-                    if start < content.len() && end <= content.len() {
-                        let emote_name = content[start..=end].to_string();
+                    if let Some(emote_name) =
+                        crate::text::slice_chars_inclusive(content, start, end)
+                    {
+                        let emote_name = emote_name.to_string();
 
                         emotes.push(Emote {
                             id: emote_id.to_string(),
@@ -229,6 +231,9 @@ impl BasePlatform {
             crate::config::PlatformType::Kick => EmoteSource::Kick,
             crate::config::PlatformType::Trovo => EmoteSource::Trovo,
             crate::config::PlatformType::Facebook => EmoteSource::Facebook,
+            crate::config::PlatformType::Custom | crate::config::PlatformType::Donations => {
+                EmoteSource::Local
+            }
         }
     }
 
@@ -258,53 +263,7 @@ impl BasePlatform {
         message: &mut ChatMessage,
         filters: &crate::config::MessageFilters,
     ) -> bool {
-        // Verificar longitud del mensaje
-        if let Some(min_len) = filters.min_message_length {
-            if message.content.len() < min_len {
-                return false;
-            }
-        }
-
-        if let Some(max_len) = filters.max_message_length {
-            if message.content.len() > max_len {
-                return false;
-            }
-        }
-
-        // Verificar usuarios bloqueados
-        if filters
-            .blocked_users
-            .contains(&message.username.to_lowercase())
-        {
-            return false;
-        }
-
-        // Verificar lista blanca (si existe)
-        if !filters.allowed_users.is_empty()
-            && !filters
-                .allowed_users
-                .contains(&message.username.to_lowercase())
-        {
-            return false;
-        }
-
-        // Verificar palabras bloqueadas
-        let content_lower = message.content.to_lowercase();
-        for blocked_word in &filters.blocked_words {
-            if content_lower.contains(&blocked_word.to_lowercase()) {
-                return false;
-            }
-        }
-
-        // Verificar si es comando
-        if filters.commands_only
-            && !message.content.starts_with('!')
-            && !message.content.starts_with('/')
-        {
-            return false;
-        }
-
-        true
+        crate::moderation::filter_rejection(message, filters).is_none()
     }
 
     /// Maneja reconexión automática
@@ -340,6 +299,72 @@ impl Default for BasePlatform {
     }
 }
 
+/// Tareas de lectura por canal que comparten un flujo de mensajes
+///
+/// Solo las tareas guardan emisores: cuando terminan todas (socket cerrado,
+/// error de autenticación...) `recv` devuelve `None` y el supervisor de
+/// conexiones reconecta. El siguiente `spawn` abre un flujo nuevo.
+pub struct ChannelReaders {
+    sender: mpsc::WeakUnboundedSender<ChatMessage>,
+    receiver: mpsc::UnboundedReceiver<ChatMessage>,
+    readers: HashMap<String, tokio::task::JoinHandle<()>>,
+}
+
+impl Default for ChannelReaders {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ChannelReaders {
+    pub fn new() -> Self {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        Self {
+            sender: sender.downgrade(),
+            receiver,
+            readers: HashMap::new(),
+        }
+    }
+
+    /// Lanza la tarea de lectura de `channel`, sustituyendo la anterior
+    pub fn spawn<F, Fut>(&mut self, channel: String, read: F)
+    where
+        F: FnOnce(mpsc::UnboundedSender<ChatMessage>) -> Fut,
+        Fut: std::future::Future<Output = ()> + Send + 'static,
+    {
+        let sender = match self.sender.upgrade() {
+            Some(sender) => sender,
+            None => {
+                let (sender, receiver) = mpsc::unbounded_channel();
+                self.sender = sender.downgrade();
+                self.receiver = receiver;
+                sender
+            }
+        };
+        let reader = tokio::spawn(read(sender));
+        if let Some(previous) = self.readers.insert(channel, reader) {
+            previous.abort();
+        }
+    }
+
+    pub fn stop(&mut self, channel: &str) {
+        if let Some(reader) = self.readers.remove(channel) {
+            reader.abort();
+        }
+    }
+
+    pub fn stop_all(&mut self) {
+        for (_, reader) in self.readers.drain() {
+            reader.abort();
+        }
+    }
+
+    /// Siguiente mensaje; `None` cuando no queda ninguna tarea de lectura viva
+    pub async fn recv(&mut self) -> Option<ChatMessage> {
+        self.receiver.recv().await
+    }
+}
+
 /// Utilidades para manejo de emotes multiplataforma
 pub mod emote_utils {
     use super::*;
@@ -383,46 +408,24 @@ pub mod emote_utils {
         pub tier: Option<String>,
     }
 
-    /// Detecta si un texto contiene emotes conocidos
+    /// Detecta emotes conocidos como palabras completas (posiciones en caracteres)
     pub fn detect_emotes_in_text(
         text: &str,
         known_emotes: &[String],
     ) -> Vec<(String, Vec<TextPosition>)> {
+        let words = crate::text::words(text);
         let mut found_emotes = Vec::new();
 
         for emote_name in known_emotes {
-            let mut positions = Vec::new();
-            let mut start = 0;
-
-            while let Some(pos) = text[start..].find(emote_name) {
-                let actual_start = start + pos;
-                let actual_end = actual_start + emote_name.len();
-
-                // Verificar que esté como palabra completa
-                let prev_char = if actual_start > 0 {
-                    text.chars().nth(actual_start - 1)
-                } else {
-                    None
-                };
-
-                let next_char = text.chars().nth(actual_end);
-
-                let is_word_boundary = match (prev_char, next_char) {
-                    (None, None) => true,
-                    (None, Some(c)) => !c.is_alphanumeric(),
-                    (Some(c), None) => !c.is_alphanumeric(),
-                    (Some(prev), Some(next)) => !prev.is_alphanumeric() && !next.is_alphanumeric(),
-                };
-
-                if is_word_boundary {
-                    positions.push(TextPosition {
-                        start: actual_start,
-                        end: actual_end - 1,
-                    });
-                }
-
-                start = actual_end;
-            }
+            let length = emote_name.chars().count();
+            let positions: Vec<TextPosition> = words
+                .iter()
+                .filter(|(_, word)| !word.is_empty() && word == emote_name)
+                .map(|(start, _)| TextPosition {
+                    start: *start,
+                    end: start + length - 1,
+                })
+                .collect();
 
             if !positions.is_empty() {
                 found_emotes.push((emote_name.clone(), positions));
@@ -0,0 +1,515 @@
+//! Plataforma genérica: mensajes JSON recibidos por WebSocket
+//!
+//! Permite conectar cualquier fuente (bridges de Discord, eventos de juegos,
+//! IRC...) sin escribir Rust. La URL se configura en
+//! `settings.custom_settings.url`; si contiene `{channel}` se sustituye por el
+//! canal de la conexión. Si hay `credentials.oauth_token` se envía como
+//! `Authorization: Bearer <token>`.
+//!
+//! Cada frame de texto es un mensaje o un array de mensajes con el esquema
+//! canónico:
+//!
+//! ```json
+//! {
+//!   "id": "opcional",
+//!   "platform": "opcional (por defecto custom)",
+//!   "username": "obligatorio",
+//!   "display_name": "opcional",
+//!   "content": "obligatorio (alias: message)",
+//!   "type": "normal | action | system | highlight | subscription | raid | cheer | ...",
+//!   "color": "#ff0000",
+//!   "timestamp": "RFC3339 o milisegundos Unix",
+//!   "badges": ["moderator", "vip"],
+//!   "emotes": [{ "name": "pog", "url": "https://..." }],
+//!   "reply_to": "id opcional",
+//!   "data": { "cualquier": "valor" }
+//! }
+//! ```
+//!
+//! El mismo esquema se acepta como NDJSON por la entrada estándar
+//! (`overlay-native run --stdin`), ver [`forward_ndjson`].
+
+use async_trait::async_trait;
+use futures::StreamExt;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::io::BufRead;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::sync::mpsc;
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::http::HeaderValue;
+use tokio_tungstenite::tungstenite::Message;
+
+use crate::capture::PayloadCapture;
+use crate::config::{PlatformConfig, PlatformType};
+use crate::connection::{
+    Badge, ChatMessage, Emote, EmoteSource, MessageMetadata, MessageType, StreamingPlatform,
+    TextPosition,
+};
+use crate::health::ErrorBudget;
+use crate::platforms::base::{BasePlatform, ChannelReaders};
+use crate::platforms::{utils, PlatformCreator, PlatformError, PlatformWrapperError};
+
+#[derive(Debug, thiserror::Error)]
+pub enum CustomError {
+    #[error("Missing setting: {0}")]
+    MissingSetting(&'static str),
+    #[error("Connection error: {0}")]
+    ConnectionError(String),
+    #[error("Invalid message: {0}")]
+    InvalidMessage(String),
+}
+
+/// Mensaje en el esquema JSON canónico
+#[derive(Debug, Deserialize)]
+struct CanonicalMessage {
+    id: Option<String>,
+    platform: Option<String>,
+    username: String,
+    display_name: Option<String>,
+    #[serde(alias = "message")]
+    content: String,
+    channel: Option<String>,
+    #[serde(rename = "type")]
+    message_type: Option<MessageType>,
+    color: Option<String>,
+    timestamp: Option<CanonicalTimestamp>,
+    #[serde(default)]
+    badges: Vec<String>,
+    #[serde(default)]
+    emotes: Vec<CanonicalEmote>,
+    reply_to: Option<String>,
+    #[serde(default)]
+    data: HashMap<String, serde_json::Value>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum CanonicalTimestamp {
+    UnixMillis(u64),
+    Rfc3339(String),
+}
+
+#[derive(Debug, Deserialize)]
+struct CanonicalEmote {
+    name: String,
+    id: Option<String>,
+    url: Option<String>,
+    #[serde(default)]
+    animated: bool,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum CanonicalFrame {
+    One(Box<CanonicalMessage>),
+    Many(Vec<CanonicalMessage>),
+}
+
+/// Convierte un frame de texto en mensajes de chat
+pub fn parse_custom_frame(text: &str) -> Result<Vec<ChatMessage>, CustomError> {
+    let frame: CanonicalFrame =
+        serde_json::from_str(text).map_err(|e| CustomError::InvalidMessage(e.to_string()))?;
+    let messages = match frame {
+        CanonicalFrame::One(message) => vec![*message],
+        CanonicalFrame::Many(messages) => messages,
+    };
+    Ok(messages.into_iter().map(to_chat_message).collect())
+}
+
+fn to_chat_message(message: CanonicalMessage) -> ChatMessage {
+    let timestamp = match message.timestamp {
+        Some(CanonicalTimestamp::UnixMillis(ms)) => UNIX_EPOCH + Duration::from_millis(ms),
+        Some(CanonicalTimestamp::Rfc3339(text)) => chrono::DateTime::parse_from_rfc3339(&text)
+            .map(SystemTime::from)
+            .unwrap_or_else(|_| SystemTime::now()),
+        None => SystemTime::now(),
+    };
+    let message_type = message.message_type.unwrap_or(MessageType::Normal);
+    let emotes = message
+        .emotes
+        .into_iter()
+        .filter_map(|emote| locate_emote(&message.content, emote))
+        .collect();
+    let badges = message
+        .badges
+        .into_iter()
+        .map(|name| Badge {
+            id: name.clone(),
+            name: name.clone(),
+            version: "1".to_string(),
+            url: None,
+            title: Some(name),
+            source: EmoteSource::Local,
+        })
+        .collect();
+
+    ChatMessage {
+        id: message.id.unwrap_or_else(utils::generate_message_id),
+        platform: message.platform.unwrap_or_else(|| "custom".to_string()),
+        channel: message.channel.unwrap_or_default(),
+        username: message.username,
+        display_name: message.display_name,
+        content: message.content,
+        emotes,
+        badges,
+        timestamp,
+        user_color: message.color,
+        metadata: MessageMetadata {
+            is_action: matches!(message_type, MessageType::Action),
+            is_whisper: matches!(message_type, MessageType::Whisper),
+            is_highlighted: matches!(message_type, MessageType::Highlight),
+            is_me_message: false,
+            reply_to: message.reply_to,
+            thread_id: None,
+            custom_data: message.data,
+        },
+        message_type,
+    }
+}
+
+/// Lee mensajes NDJSON (uno o un array por línea) y los envía al pipeline
+///
+/// Las líneas vacías se ignoran y las inválidas se registran y se saltan.
+/// Devuelve cuántos mensajes se enviaron.
+pub fn forward_ndjson(reader: impl BufRead, sender: &mpsc::UnboundedSender<ChatMessage>) -> usize {
+    let mut forwarded = 0;
+    for (index, line) in reader.lines().enumerate() {
+        let line = match line {
+            Ok(line) => line,
+            Err(e) => {
+                eprintln!("⚠️ Failed to read NDJSON input: {}", e);
+                break;
+            }
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        match parse_custom_frame(&line) {
+            Ok(messages) => {
+                for message in messages {
+                    if sender.send(message).is_err() {
+                        return forwarded;
+                    }
+                    forwarded += 1;
+                }
+            }
+            Err(e) => eprintln!("⚠️ Ignoring NDJSON line {}: {}", index + 1, e),
+        }
+    }
+    forwarded
+}
+
+/// Busca las apariciones del emote como palabra completa (posiciones en caracteres)
+fn locate_emote(content: &str, emote: CanonicalEmote) -> Option<Emote> {
+    let length = emote.name.chars().count();
+    let positions: Vec<TextPosition> = crate::text::words(content)
+        .into_iter()
+        .filter(|(_, word)| *word == emote.name)
+        .map(|(start, _)| TextPosition {
+            start,
+            end: start + length - 1,
+        })
+        .collect();
+    if positions.is_empty() {
+        return None;
+    }
+
+    Some(Emote {
+        id: emote.id.unwrap_or_else(|| emote.name.clone()),
+        name: emote.name,
+        source: EmoteSource::Local,
+        positions,
+        url: emote.url,
+        is_animated: emote.animated,
+        ..Default::default()
+    })
+}
+
+pub struct CustomPlatform {
+    base: BasePlatform,
+    url: Option<String>,
+    token: Option<String>,
+    readers: ChannelReaders,
+    is_connected: bool,
+}
+
+impl CustomPlatform {
+    pub fn new(config: PlatformConfig) -> Self {
+        let url = config
+            .settings
+            .custom_settings
+            .get("url")
+            .and_then(|v| v.as_str())
+            .map(str::to_string);
+        let token = config.credentials.oauth_token.clone();
+
+        Self {
+            base: BasePlatform::new("custom".to_string(), PlatformType::Custom, config),
+            url,
+            token,
+            readers: ChannelReaders::new(),
+            is_connected: false,
+        }
+    }
+
+    fn channel_url(&self, channel: &str) -> Result<String, CustomError> {
+        let url = self
+            .url
+            .as_ref()
+            .ok_or(CustomError::MissingSetting("url"))?;
+        Ok(url.replace("{channel}", channel))
+    }
+}
+
+/// Lee frames del WebSocket hasta que se cierre
+async fn read_frames(
+    url: String,
+    token: Option<String>,
+    channel: String,
+    sender: mpsc::UnboundedSender<ChatMessage>,
+) -> Result<(), CustomError> {
+    let mut request = url
+        .as_str()
+        .into_client_request()
+        .map_err(|e| CustomError::ConnectionError(e.to_string()))?;
+    if let Some(token) = token {
+        let value = HeaderValue::from_str(&format!("Bearer {}", token))
+            .map_err(|e| CustomError::ConnectionError(e.to_string()))?;
+        request.headers_mut().insert("Authorization", value);
+    }
+
+    let (mut stream, _) = tokio_tungstenite::connect_async(request)
+        .await
+        .map_err(|e| CustomError::ConnectionError(e.to_string()))?;
+
+    while let Some(frame) = stream.next().await {
+        let text = match frame {
+            Ok(Message::Text(text)) => text,
+            Ok(Message::Binary(bytes)) => match String::from_utf8(bytes) {
+                Ok(text) => text,
+                Err(_) => continue,
+            },
+            Ok(Message::Close(_)) => break,
+            Ok(_) => continue,
+            Err(e) => return Err(CustomError::ConnectionError(e.to_string())),
+        };
+
+        let capture = PayloadCapture::global();
+        if capture.is_enabled() {
+            capture.record("custom", &channel, &text);
+        }
+
+        match parse_custom_frame(&text) {
+            Ok(messages) => {
+                for mut message in messages {
+                    if message.channel.is_empty() {
+                        message.channel = channel.clone();
+                    }
+                    if sender.send(message).is_err() {
+                        return Ok(());
+                    }
+                }
+            }
+            Err(e) => {
+                eprintln!("⚠️ Ignoring custom frame: {}", e);
+                ErrorBudget::global().record_failure("platform:custom", &e.to_string());
+            }
+        }
+    }
+    Ok(())
+}
+
+#[async_trait]
+impl StreamingPlatform for CustomPlatform {
+    type Error = PlatformWrapperError;
+
+    async fn connect(&mut self) -> Result<(), Self::Error> {
+        // La conexión real se abre por canal en join_channel
+        if self.url.is_none() {
+            return Err(CustomError::MissingSetting("url").into());
+        }
+        self.is_connected = true;
+        Ok(())
+    }
+
+    async fn join_channel(&mut self, channel: String) -> Result<(), Self::Error> {
+        let url = self.channel_url(&channel)?;
+        let token = self.token.clone();
+        let reader_channel = channel.clone();
+
+        self.readers.spawn(channel, |sender| async move {
+            if let Err(e) = read_frames(url, token, reader_channel.clone(), sender).await {
+                eprintln!("❌ Custom source for '{}' failed: {}", reader_channel, e);
+                ErrorBudget::global().record_failure("platform:custom", &e.to_string());
+            }
+        });
+        Ok(())
+    }
+
+    async fn leave_channel(&mut self, channel: String) -> Result<(), Self::Error> {
+        self.readers.stop(&channel);
+        Ok(())
+    }
+
+    async fn next_message(&mut self) -> Option<ChatMessage> {
+        self.readers.recv().await
+    }
+
+    async fn disconnect(&mut self) -> Result<(), Self::Error> {
+        self.readers.stop_all();
+        self.is_connected = false;
+        Ok(())
+    }
+
+    fn is_connected(&self) -> bool {
+        self.is_connected
+    }
+
+    fn platform_name(&self) -> &str {
+        "custom"
+    }
+
+    async fn get_channel_emotes(&self, _channel: &str) -> Result<Vec<Emote>, Self::Error> {
+        Ok(Vec::new())
+    }
+
+    async fn get_global_emotes(&self) -> Result<Vec<Emote>, Self::Error> {
+        Ok(Vec::new())
+    }
+
+    fn parse_emotes(&self, _content: &str, _emote_data: &str) -> Vec<Emote> {
+        // Los emotes llegan ya resueltos en el JSON
+        Vec::new()
+    }
+
+    fn parse_badges(&self, _badge_data: &str) -> Vec<Badge> {
+        Vec::new()
+    }
+
+    fn apply_message_filters(
+        &self,
+        message: &mut ChatMessage,
+        filters: &crate::config::MessageFilters,
+    ) -> bool {
+        self.base.apply_message_filters(message, filters)
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+}
+
+pub struct CustomCreator;
+
+#[async_trait]
+impl PlatformCreator for CustomCreator {
+    async fn create(
+        &self,
+        config: PlatformConfig,
+    ) -> Result<Box<dyn StreamingPlatform<Error = PlatformWrapperError> + Send + Sync>, PlatformError>
+    {
+        if !config.settings.custom_settings.contains_key("url") {
+            return Err(PlatformError::ConfigError(
+                "custom platform requires settings.custom_settings.url".to_string(),
+            ));
+        }
+        Ok(Box::new(CustomPlatform::new(config)))
+    }
+
+    fn platform_name(&self) -> &str {
+        "custom"
+    }
+
+    fn required_credentials(&self) -> Vec<&'static str> {
+        Vec::new()
+    }
+
+    async fn validate_credentials(
+        &self,
+        _credentials: &crate::config::Credentials,
+    ) -> Result<bool, PlatformError> {
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_single_message_with_emotes_and_badges() {
+        let frame = r##"{
+            "username": "bridge_bot",
+            "message": "gg pog pog",
+            "type": "highlight",
+            "color": "#00ff00",
+            "timestamp": 1700000000000,
+            "badges": ["moderator"],
+            "emotes": [{"name": "pog", "url": "https://example.com/pog.png"}],
+            "data": {"source": "discord"}
+        }"##;
+
+        let messages = parse_custom_frame(frame).unwrap();
+        assert_eq!(messages.len(), 1);
+        let message = &messages[0];
+        assert_eq!(message.platform, "custom");
+        assert_eq!(message.content, "gg pog pog");
+        assert!(matches!(message.message_type, MessageType::Highlight));
+        assert!(message.metadata.is_highlighted);
+        assert_eq!(message.user_color.as_deref(), Some("#00ff00"));
+        assert_eq!(
+            message.timestamp,
+            UNIX_EPOCH + Duration::from_millis(1_700_000_000_000)
+        );
+        assert_eq!(message.badges[0].name, "moderator");
+        assert_eq!(message.emotes[0].positions.len(), 2);
+        assert_eq!(message.emotes[0].positions[0].start, 3);
+        assert_eq!(message.metadata.custom_data["source"], "discord");
+    }
+
+    #[test]
+    fn test_parse_batch_and_rfc3339_timestamps() {
+        let frame = r#"[
+            {"id": "a", "username": "x", "content": "one", "timestamp": "2024-01-01T00:00:00Z"},
+            {"id": "b", "username": "y", "content": "two", "channel": "game"}
+        ]"#;
+
+        let messages = parse_custom_frame(frame).unwrap();
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[0].id, "a");
+        assert_eq!(
+            messages[0].timestamp,
+            UNIX_EPOCH + Duration::from_secs(1_704_067_200)
+        );
+        assert_eq!(messages[1].channel, "game");
+    }
+
+    #[test]
+    fn test_forward_ndjson_skips_blank_and_invalid_lines() {
+        let input = concat!(
+            "{\"username\": \"a\", \"content\": \"one\", \"platform\": \"twitch\"}\n",
+            "\n",
+            "not json\n",
+            "[{\"username\": \"b\", \"content\": \"two\"}, {\"username\": \"c\", \"content\": \"three\"}]\n",
+        );
+        let (sender, mut receiver) = mpsc::unbounded_channel();
+
+        assert_eq!(forward_ndjson(std::io::Cursor::new(input), &sender), 3);
+        assert_eq!(receiver.try_recv().unwrap().platform, "twitch");
+        assert_eq!(receiver.try_recv().unwrap().content, "two");
+        assert_eq!(receiver.try_recv().unwrap().platform, "custom");
+        assert!(receiver.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_invalid_frames_are_rejected() {
+        assert!(parse_custom_frame(r#"{"content": "no user"}"#).is_err());
+        assert!(parse_custom_frame("not json").is_err());
+    }
+}
@@ -0,0 +1,596 @@
+//! Donaciones de StreamElements o Streamlabs por sus sockets en tiempo real
+//!
+//! Ambos servicios usan Socket.IO (Engine.IO v3), que aquí se habla
+//! directamente sobre el WebSocket: `0{..}` abre la sesión e indica el
+//! `pingInterval`, el cliente envía `2` y el servidor responde `3`, y los
+//! eventos llegan como `42["nombre", datos]`.
+//!
+//! El servicio se elige con `settings.custom_settings.provider`
+//! (`"streamelements"` por defecto o `"streamlabs"`) y el token va en
+//! `credentials.oauth_token`: el JWT de StreamElements o el token del socket
+//! de Streamlabs. Cada propina se convierte en un mensaje `Cheer` con el
+//! importe en `custom_data`.
+
+use async_trait::async_trait;
+use futures::{SinkExt, StreamExt};
+use std::collections::HashMap;
+use std::time::{Duration, SystemTime};
+use tokio::sync::mpsc;
+use tokio_tungstenite::tungstenite::Message;
+
+use crate::capture::PayloadCapture;
+use crate::config::{PlatformConfig, PlatformSettings, PlatformType};
+use crate::connection::{
+    Badge, ChatMessage, Emote, MessageMetadata, MessageType, StreamingPlatform,
+};
+use crate::health::ErrorBudget;
+use crate::platforms::base::{BasePlatform, ChannelReaders};
+use crate::platforms::{utils, PlatformCreator, PlatformError, PlatformWrapperError};
+
+const STREAMELEMENTS_URL: &str =
+    "wss://realtime.streamelements.com/socket.io/?cluster=main&EIO=3&transport=websocket";
+const STREAMLABS_URL: &str = "wss://sockets.streamlabs.com/socket.io/?EIO=3&transport=websocket";
+const DEFAULT_PING_INTERVAL: Duration = Duration::from_secs(25);
+
+/// Clave en `custom_data` con el importe de la donación
+pub const DONATION_AMOUNT_KEY: &str = "donation_amount";
+/// Clave en `custom_data` con la moneda (`USD`, `EUR`...)
+pub const DONATION_CURRENCY_KEY: &str = "donation_currency";
+
+#[derive(Debug, thiserror::Error)]
+pub enum DonationsError {
+    #[error("Missing credential: {0}")]
+    MissingCredential(&'static str),
+    #[error("Unknown donation provider: {0}")]
+    UnknownProvider(String),
+    #[error("Connection error: {0}")]
+    ConnectionError(String),
+    #[error("Authentication failed: {0}")]
+    Unauthorized(String),
+}
+
+/// Servicio de donaciones
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DonationProvider {
+    StreamElements,
+    Streamlabs,
+}
+
+impl DonationProvider {
+    /// Servicio de `custom_settings.provider`; StreamElements si no se indica
+    pub fn from_settings(settings: &PlatformSettings) -> Result<Self, DonationsError> {
+        match settings
+            .custom_settings
+            .get("provider")
+            .and_then(|p| p.as_str())
+        {
+            None => Ok(Self::StreamElements),
+            Some(name) => match name.to_lowercase().as_str() {
+                "streamelements" | "se" => Ok(Self::StreamElements),
+                "streamlabs" | "sl" => Ok(Self::Streamlabs),
+                _ => Err(DonationsError::UnknownProvider(name.to_string())),
+            },
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::StreamElements => "streamelements",
+            Self::Streamlabs => "streamlabs",
+        }
+    }
+
+    /// Streamlabs recibe el token en la URL; StreamElements tras conectar
+    fn socket_url(self, token: &str) -> String {
+        match self {
+            Self::StreamElements => STREAMELEMENTS_URL.to_string(),
+            Self::Streamlabs => format!("{}&token={}", STREAMLABS_URL, token),
+        }
+    }
+
+    fn auth_frame(self, token: &str) -> Option<String> {
+        match self {
+            Self::StreamElements => Some(format!(
+                "42{}",
+                serde_json::json!(["authenticate", { "method": "jwt", "token": token }])
+            )),
+            Self::Streamlabs => None,
+        }
+    }
+}
+
+/// Paquete de Socket.IO sobre Engine.IO v3
+#[derive(Debug, PartialEq)]
+pub enum SocketFrame {
+    /// Apertura de la sesión con el intervalo de ping del servidor
+    Open {
+        ping_interval: Option<Duration>,
+    },
+    /// Conexión al namespace por defecto (`40`)
+    Connected,
+    Pong,
+    /// Evento `42["nombre", datos]`
+    Event(String, serde_json::Value),
+    /// El servidor cierra la sesión
+    Close,
+    Other,
+}
+
+pub fn parse_socket_frame(text: &str) -> SocketFrame {
+    if let Some(open) = text.strip_prefix('0') {
+        let ping_interval = serde_json::from_str::<serde_json::Value>(open)
+            .ok()
+            .and_then(|open| open.get("pingInterval")?.as_u64())
+            .map(Duration::from_millis);
+        return SocketFrame::Open { ping_interval };
+    }
+    if text == "1" || text == "41" {
+        return SocketFrame::Close;
+    }
+    if text == "3" {
+        return SocketFrame::Pong;
+    }
+    if text.starts_with("40") {
+        return SocketFrame::Connected;
+    }
+    if let Some(event) = text.strip_prefix("42") {
+        if let Ok(serde_json::Value::Array(mut parts)) = serde_json::from_str(event) {
+            if let Some(serde_json::Value::String(name)) = parts.first().cloned() {
+                let data = if parts.len() > 1 {
+                    parts.swap_remove(1)
+                } else {
+                    serde_json::Value::Null
+                };
+                return SocketFrame::Event(name, data);
+            }
+        }
+    }
+    SocketFrame::Other
+}
+
+/// Importe que puede llegar como número o como texto (`"5.00"`)
+fn amount(value: Option<&serde_json::Value>) -> Option<f64> {
+    match value? {
+        serde_json::Value::Number(number) => number.as_f64(),
+        serde_json::Value::String(text) => text.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+fn text<'a>(value: &'a serde_json::Value, key: &str) -> Option<&'a str> {
+    value.get(key)?.as_str().filter(|text| !text.is_empty())
+}
+
+/// Donaciones de un evento del socket; el resto de eventos no producen mensajes
+///
+/// StreamElements envía una propina por evento (`event` con `type: "tip"`);
+/// Streamlabs puede agrupar varias (`event` con `type: "donation"` y una lista
+/// en `message`). Los eventos de prueba y las actualizaciones se ignoran.
+pub fn parse_donations(
+    provider: DonationProvider,
+    event: &str,
+    data: &serde_json::Value,
+    channel: &str,
+) -> Vec<ChatMessage> {
+    if event != "event" {
+        return Vec::new();
+    }
+    match provider {
+        DonationProvider::StreamElements => {
+            if text(data, "type") != Some("tip") {
+                return Vec::new();
+            }
+            let Some(tip) = data.get("data") else {
+                return Vec::new();
+            };
+            let timestamp = text(data, "createdAt")
+                .and_then(|created| chrono::DateTime::parse_from_rfc3339(created).ok())
+                .map(SystemTime::from);
+            donation_message(provider, tip, text(data, "_id"), timestamp, channel)
+                .into_iter()
+                .collect()
+        }
+        DonationProvider::Streamlabs => {
+            if text(data, "type") != Some("donation") {
+                return Vec::new();
+            }
+            let donations = match data.get("message") {
+                Some(serde_json::Value::Array(donations)) => donations.iter().collect(),
+                Some(donation @ serde_json::Value::Object(_)) => vec![donation],
+                _ => Vec::new(),
+            };
+            donations
+                .into_iter()
+                .filter_map(|donation| {
+                    let id = text(donation, "_id").or_else(|| text(data, "event_id"));
+                    donation_message(provider, donation, id, None, channel)
+                })
+                .collect()
+        }
+    }
+}
+
+fn donation_message(
+    provider: DonationProvider,
+    donation: &serde_json::Value,
+    id: Option<&str>,
+    timestamp: Option<SystemTime>,
+    channel: &str,
+) -> Option<ChatMessage> {
+    let amount = amount(donation.get("amount"))?;
+    let username = text(donation, "username")
+        .or_else(|| text(donation, "name"))
+        .unwrap_or("Anonymous");
+    let display_name = text(donation, "displayName")
+        .or_else(|| text(donation, "name"))
+        .unwrap_or(username);
+    let currency = text(donation, "currency").unwrap_or_default();
+    let formatted = text(donation, "formatted_amount")
+        .map(str::to_string)
+        .unwrap_or_else(|| format!("{:.2} {}", amount, currency).trim().to_string());
+    let message = text(donation, "message").unwrap_or_default().trim();
+
+    let mut custom_data: HashMap<String, serde_json::Value> = HashMap::new();
+    custom_data.insert(DONATION_AMOUNT_KEY.to_string(), amount.into());
+    custom_data.insert(DONATION_CURRENCY_KEY.to_string(), currency.into());
+    custom_data.insert("formatted_amount".to_string(), formatted.clone().into());
+    custom_data.insert("donation_provider".to_string(), provider.name().into());
+
+    Some(ChatMessage {
+        id: id
+            .map(str::to_string)
+            .unwrap_or_else(utils::generate_message_id),
+        platform: "donations".to_string(),
+        channel: channel.to_string(),
+        username: username.to_string(),
+        display_name: Some(display_name.to_string()),
+        content: if message.is_empty() {
+            formatted
+        } else {
+            format!("{}: {}", formatted, message)
+        },
+        emotes: Vec::new(),
+        badges: Vec::new(),
+        timestamp: timestamp.unwrap_or_else(SystemTime::now),
+        user_color: None,
+        message_type: MessageType::Cheer,
+        metadata: MessageMetadata {
+            is_action: false,
+            is_whisper: false,
+            is_highlighted: true,
+            is_me_message: false,
+            reply_to: None,
+            thread_id: None,
+            custom_data,
+        },
+    })
+}
+
+/// Lee las donaciones hasta que se cierre el socket
+async fn read_donations(
+    provider: DonationProvider,
+    token: String,
+    channel: String,
+    sender: mpsc::UnboundedSender<ChatMessage>,
+) -> Result<(), DonationsError> {
+    let (mut stream, _) = tokio_tungstenite::connect_async(provider.socket_url(&token))
+        .await
+        .map_err(|e| DonationsError::ConnectionError(e.to_string()))?;
+
+    let mut ping_interval = DEFAULT_PING_INTERVAL;
+    let mut next_ping = tokio::time::Instant::now() + ping_interval;
+
+    loop {
+        let frame = tokio::select! {
+            frame = stream.next() => frame,
+            _ = tokio::time::sleep_until(next_ping) => {
+                stream
+                    .send(Message::Text("2".to_string()))
+                    .await
+                    .map_err(|e| DonationsError::ConnectionError(e.to_string()))?;
+                next_ping = tokio::time::Instant::now() + ping_interval;
+                continue;
+            }
+        };
+
+        let text = match frame {
+            Some(Ok(Message::Text(text))) => text,
+            Some(Ok(Message::Close(_))) | None => break,
+            Some(Ok(_)) => continue,
+            Some(Err(e)) => return Err(DonationsError::ConnectionError(e.to_string())),
+        };
+
+        let capture = PayloadCapture::global();
+        if capture.is_enabled() {
+            capture.record(provider.name(), &channel, &text);
+        }
+
+        match parse_socket_frame(&text) {
+            SocketFrame::Open {
+                ping_interval: interval,
+            } => {
+                if let Some(interval) = interval {
+                    ping_interval = interval;
+                    next_ping = tokio::time::Instant::now() + ping_interval;
+                }
+            }
+            SocketFrame::Connected => {
+                if let Some(auth) = provider.auth_frame(&token) {
+                    stream
+                        .send(Message::Text(auth))
+                        .await
+                        .map_err(|e| DonationsError::ConnectionError(e.to_string()))?;
+                }
+            }
+            SocketFrame::Event(event, data) if event == "unauthorized" => {
+                return Err(DonationsError::Unauthorized(data.to_string()));
+            }
+            SocketFrame::Event(event, data) => {
+                for message in parse_donations(provider, &event, &data, &channel) {
+                    if sender.send(message).is_err() {
+                        return Ok(());
+                    }
+                }
+            }
+            SocketFrame::Close => break,
+            SocketFrame::Pong | SocketFrame::Other => {}
+        }
+    }
+    Ok(())
+}
+
+pub struct DonationsPlatform {
+    base: BasePlatform,
+    provider: DonationProvider,
+    token: Option<String>,
+    readers: ChannelReaders,
+    is_connected: bool,
+}
+
+impl DonationsPlatform {
+    pub fn new(config: PlatformConfig) -> Self {
+        // `DonationsCreator` ya rechaza los servicios desconocidos
+        let provider = DonationProvider::from_settings(&config.settings)
+            .unwrap_or(DonationProvider::StreamElements);
+        let token = config.credentials.oauth_token.clone();
+
+        Self {
+            base: BasePlatform::new("donations".to_string(), PlatformType::Donations, config),
+            provider,
+            token,
+            readers: ChannelReaders::new(),
+            is_connected: false,
+        }
+    }
+}
+
+#[async_trait]
+impl StreamingPlatform for DonationsPlatform {
+    type Error = PlatformWrapperError;
+
+    async fn connect(&mut self) -> Result<(), Self::Error> {
+        // El socket se abre en join_channel; el canal solo etiqueta los mensajes
+        if self.token.is_none() {
+            return Err(DonationsError::MissingCredential("oauth_token").into());
+        }
+        self.is_connected = true;
+        Ok(())
+    }
+
+    async fn join_channel(&mut self, channel: String) -> Result<(), Self::Error> {
+        let provider = self.provider;
+        let token = self
+            .token
+            .clone()
+            .ok_or(DonationsError::MissingCredential("oauth_token"))?;
+        let channel = utils::sanitize_channel_name(&channel);
+        let reader_channel = channel.clone();
+
+        self.readers.spawn(channel, |sender| async move {
+            if let Err(e) = read_donations(provider, token, reader_channel, sender).await {
+                eprintln!("❌ {} donations failed: {}", provider.name(), e);
+                ErrorBudget::global().record_failure("platform:donations", &e.to_string());
+            }
+        });
+        Ok(())
+    }
+
+    async fn leave_channel(&mut self, channel: String) -> Result<(), Self::Error> {
+        self.readers.stop(&utils::sanitize_channel_name(&channel));
+        Ok(())
+    }
+
+    async fn next_message(&mut self) -> Option<ChatMessage> {
+        self.readers.recv().await
+    }
+
+    async fn disconnect(&mut self) -> Result<(), Self::Error> {
+        self.readers.stop_all();
+        self.is_connected = false;
+        Ok(())
+    }
+
+    fn is_connected(&self) -> bool {
+        self.is_connected
+    }
+
+    fn platform_name(&self) -> &str {
+        "donations"
+    }
+
+    async fn get_channel_emotes(&self, _channel: &str) -> Result<Vec<Emote>, Self::Error> {
+        Ok(Vec::new())
+    }
+
+    async fn get_global_emotes(&self) -> Result<Vec<Emote>, Self::Error> {
+        Ok(Vec::new())
+    }
+
+    fn parse_emotes(&self, _content: &str, _emote_data: &str) -> Vec<Emote> {
+        Vec::new()
+    }
+
+    fn parse_badges(&self, _badge_data: &str) -> Vec<Badge> {
+        Vec::new()
+    }
+
+    fn apply_message_filters(
+        &self,
+        message: &mut ChatMessage,
+        filters: &crate::config::MessageFilters,
+    ) -> bool {
+        self.base.apply_message_filters(message, filters)
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+}
+
+pub struct DonationsCreator;
+
+#[async_trait]
+impl PlatformCreator for DonationsCreator {
+    async fn create(
+        &self,
+        config: PlatformConfig,
+    ) -> Result<Box<dyn StreamingPlatform<Error = PlatformWrapperError> + Send + Sync>, PlatformError>
+    {
+        self.validate_credentials(&config.credentials).await?;
+        DonationProvider::from_settings(&config.settings)
+            .map_err(|e| PlatformError::ConfigError(e.to_string()))?;
+        Ok(Box::new(DonationsPlatform::new(config)))
+    }
+
+    fn platform_name(&self) -> &str {
+        "donations"
+    }
+
+    fn required_credentials(&self) -> Vec<&'static str> {
+        vec!["oauth_token"]
+    }
+
+    async fn validate_credentials(
+        &self,
+        credentials: &crate::config::Credentials,
+    ) -> Result<bool, PlatformError> {
+        match credentials.oauth_token.as_deref() {
+            Some(token) if !token.trim().is_empty() => Ok(true),
+            _ => Err(PlatformError::AuthError(
+                "donations requires credentials.oauth_token (StreamElements JWT or Streamlabs socket token)"
+                    .to_string(),
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_socket_frames() {
+        assert_eq!(
+            parse_socket_frame(r#"0{"sid":"abc","pingInterval":25000,"pingTimeout":5000}"#),
+            SocketFrame::Open {
+                ping_interval: Some(Duration::from_secs(25))
+            }
+        );
+        assert_eq!(parse_socket_frame("40"), SocketFrame::Connected);
+        assert_eq!(parse_socket_frame("3"), SocketFrame::Pong);
+        assert_eq!(
+            parse_socket_frame(r#"42["authenticated",{"channelId":"c1"}]"#),
+            SocketFrame::Event(
+                "authenticated".to_string(),
+                serde_json::json!({"channelId": "c1"})
+            )
+        );
+        assert_eq!(parse_socket_frame("42not json"), SocketFrame::Other);
+    }
+
+    #[test]
+    fn test_streamelements_tip() {
+        let event = serde_json::json!({
+            "_id": "tip1",
+            "type": "tip",
+            "createdAt": "2024-05-01T12:00:00.000Z",
+            "data": {
+                "amount": 5,
+                "currency": "USD",
+                "username": "fan",
+                "displayName": "Fan",
+                "message": "great stream"
+            }
+        });
+
+        let messages = parse_donations(DonationProvider::StreamElements, "event", &event, "me");
+        assert_eq!(messages.len(), 1);
+        let tip = &messages[0];
+        assert!(matches!(tip.message_type, MessageType::Cheer));
+        assert_eq!(tip.id, "tip1");
+        assert_eq!(tip.username, "fan");
+        assert_eq!(tip.display_name.as_deref(), Some("Fan"));
+        assert_eq!(tip.content, "5.00 USD: great stream");
+        assert_eq!(tip.metadata.custom_data[DONATION_AMOUNT_KEY], 5.0);
+        assert_eq!(tip.metadata.custom_data[DONATION_CURRENCY_KEY], "USD");
+
+        // Pruebas desde el panel, actualizaciones y otros tipos no abren ventana
+        assert!(
+            parse_donations(DonationProvider::StreamElements, "event:test", &event, "me")
+                .is_empty()
+        );
+        let follow = serde_json::json!({"type": "follower", "data": {"username": "x"}});
+        assert!(
+            parse_donations(DonationProvider::StreamElements, "event", &follow, "me").is_empty()
+        );
+    }
+
+    #[test]
+    fn test_streamlabs_donations() {
+        let event = serde_json::json!({
+            "type": "donation",
+            "event_id": "evt1",
+            "message": [
+                {"_id": "d1", "name": "Fan", "amount": "3.50", "formatted_amount": "$3.50",
+                 "currency": "USD", "message": ""},
+                {"name": "Other", "amount": 10, "currency": "EUR", "message": "hola"}
+            ]
+        });
+
+        let messages = parse_donations(DonationProvider::Streamlabs, "event", &event, "me");
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[0].id, "d1");
+        assert_eq!(messages[0].content, "$3.50");
+        assert_eq!(messages[0].metadata.custom_data[DONATION_AMOUNT_KEY], 3.5);
+        assert_eq!(messages[1].id, "evt1");
+        assert_eq!(messages[1].content, "10.00 EUR: hola");
+        assert_eq!(
+            messages[1].metadata.custom_data["donation_provider"],
+            "streamlabs"
+        );
+    }
+
+    #[test]
+    fn test_provider_from_settings() {
+        let mut settings = PlatformSettings::default();
+        assert_eq!(
+            DonationProvider::from_settings(&settings).unwrap(),
+            DonationProvider::StreamElements
+        );
+        settings
+            .custom_settings
+            .insert("provider".to_string(), "Streamlabs".into());
+        assert_eq!(
+            DonationProvider::from_settings(&settings).unwrap(),
+            DonationProvider::Streamlabs
+        );
+        settings
+            .custom_settings
+            .insert("provider".to_string(), "patreon".into());
+        assert!(DonationProvider::from_settings(&settings).is_err());
+    }
+}
@@ -1,14 +1,17 @@
 use async_trait::async_trait;
 use kick_rust::KickClient;
 use std::collections::HashMap;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use std::time::SystemTime;
-use tokio::sync::{mpsc, Mutex};
+use tokio::sync::mpsc;
 
+use crate::capture::PayloadCapture;
 use crate::config::{PlatformConfig, PlatformType};
 use crate::connection::{
     Badge, ChatMessage, Emote, MessageMetadata, MessageType, StreamingPlatform,
+    DELETED_MESSAGE_KEY,
 };
+use crate::health::ErrorBudget;
 use crate::platforms::base::BasePlatform;
 use crate::platforms::{PlatformCreator, PlatformError, PlatformWrapperError};
 
@@ -69,7 +72,7 @@ impl KickPlatform {
     async fn setup_callbacks(&mut self) -> Result<(), KickError> {
         if let Some(client) = &self.client {
             if let Some(sender) = self.message_sender.take() {
-                let sender = Arc::new(Mutex::new(sender));
+                let sender = Arc::new(Mutex::new(Some(sender)));
 
                 // Handle chat messages
                 let sender_clone = Arc::clone(&sender);
@@ -77,13 +80,15 @@ impl KickPlatform {
                     let chat_message = ChatMessage {
                         id: data.id.clone(),
                         platform: "Kick".to_string(),
-                        channel: "unknown".to_string(), // Will be set when joining channel
+                        channel: String::new(), // Will be set when joining channel
                         username: data.sender.username.clone(),
                         display_name: Some(data.sender.username.clone()),
                         content: data.content.clone(),
                         emotes: Vec::new(), // TODO: Parse emotes from kick_rust if available
                         badges: Vec::new(), // TODO: Parse badges from kick_rust if available
-                        timestamp: SystemTime::now(),
+                        timestamp: chrono::DateTime::parse_from_rfc3339(&data.created_at)
+                            .map(SystemTime::from)
+                            .unwrap_or_else(|_| SystemTime::now()),
                         user_color: None, // TODO: Get user color from kick_rust if available
                         message_type: MessageType::Normal,
                         metadata: MessageMetadata {
@@ -97,11 +102,62 @@ impl KickPlatform {
                         },
                     };
 
-                    if let Ok(sender) = sender_clone.try_lock() {
-                        let _ = sender.send(chat_message);
+                    forward(&sender_clone, chat_message);
+                }).await;
+
+                // Mensaje fijado por la plataforma
+                let sender_clone = Arc::clone(&sender);
+                client.on_pinned_message_created(move |data| {
+                    let pinned = kick_pin_event(
+                        &data.message.id,
+                        &data.message.sender.username,
+                        &data.message.content,
+                        MessageType::Pinned,
+                    );
+                    forward(&sender_clone, pinned);
+                }).await;
+
+                // Captura de frames de Pusher crudos (solo si está activada)
+                // kick_rust no parsea PinnedMessageDeletedEvent: se detecta aquí
+                let sender_clone = Arc::clone(&sender);
+                client.on_raw_message(move |raw| {
+                    if raw.event_type == PINNED_MESSAGE_DELETED_EVENT {
+                        let unpinned = kick_pin_event("", "", "", MessageType::Unpinned);
+                        forward(&sender_clone, unpinned);
+                    }
+                    // kick_rust espera `message_id`, pero Kick envía `message.id`
+                    if let Some(moderation) = kick_moderation_event(&raw.event_type, &raw.data) {
+                        forward(&sender_clone, moderation);
+                    }
+                    // Subs, regalos y follows: kick_rust no conoce `FollowersUpdated`
+                    // y espera `recipients` en los regalos (Kick envía `gifted_usernames`)
+                    if let Some(alert) = kick_alert_event(&raw.event_type, &raw.data) {
+                        forward(&sender_clone, alert);
+                    }
+
+                    let capture = PayloadCapture::global();
+                    if capture.is_enabled() {
+                        let channel = serde_json::from_str::<serde_json::Value>(&raw.raw_json)
+                            .ok()
+                            .and_then(|v| v.get("channel").and_then(|c| c.as_str()).map(String::from))
+                            .unwrap_or_else(|| "unknown".to_string());
+                        capture.record("kick", &channel, &raw.raw_json);
+                    }
+                }).await;
+
+                // Al cerrarse el socket se suelta el emisor: `next_message` devuelve
+                // `None` y el supervisor de conexiones reconecta
+                let sender_clone = Arc::clone(&sender);
+                client.on_disconnected(move |_| {
+                    if let Ok(mut sender) = sender_clone.lock() {
+                        sender.take();
                     }
                 }).await;
 
+                client.on_error(move |e| {
+                    ErrorBudget::global().record_failure("platform:kick", &e.to_string());
+                }).await;
+
                 // Handle connection ready
                 client.on_ready(move |_| {
                     println!("Connected to Kick chat!");
@@ -112,11 +168,207 @@ impl KickPlatform {
     }
 }
 
+/// Envía al canal de mensajes salvo que el socket ya se haya cerrado
+fn forward(sender: &Mutex<Option<mpsc::UnboundedSender<ChatMessage>>>, message: ChatMessage) {
+    if let Ok(sender) = sender.lock() {
+        if let Some(sender) = sender.as_ref() {
+            let _ = sender.send(message);
+        }
+    }
+}
+
+/// Evento de Pusher que Kick envía al quitar el mensaje fijado
+const PINNED_MESSAGE_DELETED_EVENT: &str = "App\\Events\\PinnedMessageDeletedEvent";
+
+/// Evento de Pusher de un mensaje borrado por un moderador
+const MESSAGE_DELETED_EVENT: &str = "App\\Events\\MessageDeletedEvent";
+
+/// Evento de Pusher de un usuario expulsado o silenciado temporalmente
+const USER_BANNED_EVENT: &str = "App\\Events\\UserBannedEvent";
+
+/// Evento de Pusher de una suscripción nueva o renovada
+const SUBSCRIPTION_EVENT: &str = "App\\Events\\SubscriptionEvent";
+
+/// Evento de Pusher de una tanda de subs regaladas
+const GIFTED_SUBSCRIPTIONS_EVENT: &str = "App\\Events\\GiftedSubscriptionsEvent";
+
+/// Evento de Pusher del contador de seguidores (follows y unfollows)
+const FOLLOWERS_UPDATED_EVENT: &str = "App\\Events\\FollowersUpdated";
+
+/// `data` de un evento crudo de Pusher como JSON
+fn pusher_data(data: &str) -> Option<serde_json::Value> {
+    let data: serde_json::Value = serde_json::from_str(data).ok()?;
+    // Pusher manda `data` como cadena JSON dentro del frame
+    match &data {
+        serde_json::Value::String(inner) => serde_json::from_str(inner).ok(),
+        _ => Some(data),
+    }
+}
+
+/// Aviso de borrado (`System` con `DELETED_MESSAGE_KEY`) o de expulsión (`Ban`)
+/// a partir de un evento crudo de Pusher; `None` para el resto de eventos
+fn kick_moderation_event(event_type: &str, data: &str) -> Option<ChatMessage> {
+    let data = pusher_data(data)?;
+    match event_type {
+        MESSAGE_DELETED_EVENT => {
+            let message_id = data
+                .pointer("/message/id")
+                .or_else(|| data.get("message_id"))
+                .and_then(|id| id.as_str())?;
+            let mut deleted = kick_pin_event(
+                &format!("deleted-{}", message_id),
+                "",
+                "Message deleted by a moderator",
+                MessageType::System,
+            );
+            deleted
+                .metadata
+                .custom_data
+                .insert(DELETED_MESSAGE_KEY.to_string(), message_id.into());
+            Some(deleted)
+        }
+        USER_BANNED_EVENT => {
+            let username = data
+                .pointer("/user/username")
+                .or_else(|| data.get("username"))
+                .or_else(|| data.get("banned_username"))
+                .and_then(|name| name.as_str())?;
+            let banned_by = data
+                .pointer("/banned_by/username")
+                .and_then(|name| name.as_str());
+            let permanent = data
+                .get("permanent")
+                .and_then(|p| p.as_bool())
+                .unwrap_or(true);
+            let content = match (permanent, data.get("duration").and_then(|d| d.as_u64())) {
+                (false, Some(minutes)) => format!("{} was timed out for {} min", username, minutes),
+                _ => format!("{} was banned", username),
+            };
+            let mut ban = kick_pin_event(
+                data.get("id").and_then(|id| id.as_str()).unwrap_or_default(),
+                username,
+                &content,
+                MessageType::Ban,
+            );
+            if let Some(banned_by) = banned_by {
+                ban.metadata
+                    .custom_data
+                    .insert("banned_by".to_string(), banned_by.into());
+            }
+            Some(ban)
+        }
+        _ => None,
+    }
+}
+
+/// Alerta de sub (`Subscription` con `cumulative_months`, como las de Twitch),
+/// de subs regaladas (`Subscription` con `gift_count`) o de follow (`Follow`);
+/// `None` para el resto de eventos y para los unfollows
+fn kick_alert_event(event_type: &str, data: &str) -> Option<ChatMessage> {
+    let data = pusher_data(data)?;
+    let text = |key: &str| data.get(key).and_then(|v| v.as_str());
+    let mut custom_data: HashMap<String, serde_json::Value> = HashMap::new();
+
+    let (username, content, message_type) = match event_type {
+        SUBSCRIPTION_EVENT => {
+            let username = text("username")?;
+            let months = data.get("months").and_then(|m| m.as_u64()).unwrap_or(1);
+            custom_data.insert("is_resub".to_string(), (months > 1).into());
+            custom_data.insert("cumulative_months".to_string(), months.into());
+            let content = if months > 1 {
+                format!("{} resubscribed for {} months", username, months)
+            } else {
+                format!("{} subscribed", username)
+            };
+            (username, content, MessageType::Subscription)
+        }
+        GIFTED_SUBSCRIPTIONS_EVENT => {
+            let gifter = text("gifter_username")
+                .or_else(|| text("gifted_by"))
+                .unwrap_or("Anonymous");
+            let recipients: Vec<&str> = data
+                .get("gifted_usernames")
+                .and_then(|r| r.as_array())
+                .map(|r| r.iter().filter_map(|name| name.as_str()).collect())
+                .unwrap_or_default();
+            if recipients.is_empty() {
+                return None;
+            }
+            let content = match recipients.as_slice() {
+                [recipient] => format!("{} gifted a sub to {}", gifter, recipient),
+                _ => format!("{} gifted {} subs", gifter, recipients.len()),
+            };
+            custom_data.insert("gift_sub".to_string(), true.into());
+            custom_data.insert("gift_count".to_string(), recipients.len().into());
+            custom_data.insert("gifted_usernames".to_string(), recipients.into());
+            if let Some(total) = data.get("gifter_total").and_then(|t| t.as_u64()) {
+                custom_data.insert("gifter_total".to_string(), total.into());
+            }
+            (gifter, content, MessageType::Subscription)
+        }
+        FOLLOWERS_UPDATED_EVENT => {
+            // También llega al dejar de seguir y en actualizaciones sin usuario
+            if data.get("followed").and_then(|f| f.as_bool()) == Some(false) {
+                return None;
+            }
+            let username = text("username")?;
+            if let Some(count) = data.get("followersCount").and_then(|c| c.as_u64()) {
+                custom_data.insert("followers_count".to_string(), count.into());
+            }
+            (
+                username,
+                format!("{} is now following", username),
+                MessageType::Follow,
+            )
+        }
+        _ => return None,
+    };
+
+    let id = match text("created_at") {
+        Some(created_at) => format!("{}-{}", username, created_at),
+        None => String::new(),
+    };
+    let mut alert = kick_pin_event(&id, username, &content, message_type);
+    alert.metadata.custom_data = custom_data;
+    Some(alert)
+}
+
+/// Mensaje para los eventos de fijado de Kick (`Pinned` / `Unpinned`)
+fn kick_pin_event(id: &str, username: &str, content: &str, message_type: MessageType) -> ChatMessage {
+    ChatMessage {
+        id: id.to_string(),
+        platform: "Kick".to_string(),
+        channel: String::new(),
+        username: username.to_string(),
+        display_name: Some(username.to_string()),
+        content: content.to_string(),
+        emotes: Vec::new(),
+        badges: Vec::new(),
+        timestamp: SystemTime::now(),
+        user_color: None,
+        message_type,
+        metadata: MessageMetadata {
+            is_action: false,
+            is_whisper: false,
+            is_highlighted: false,
+            is_me_message: false,
+            reply_to: None,
+            thread_id: None,
+            custom_data: HashMap::new(),
+        },
+    }
+}
+
 #[async_trait]
 impl StreamingPlatform for KickPlatform {
     type Error = KickError;
 
     async fn connect(&mut self) -> Result<(), Self::Error> {
+        // Canal nuevo en cada conexión: el anterior se cierra al caer el socket
+        let (message_sender, message_receiver) = mpsc::unbounded_channel();
+        self.message_sender = Some(message_sender);
+        self.message_receiver = Some(message_receiver);
+
         let client = KickClient::new();
         self.client = Some(client);
 
@@ -154,8 +406,12 @@ impl StreamingPlatform for KickPlatform {
     }
 
     async fn disconnect(&mut self) -> Result<(), Self::Error> {
-        // Note: kick_rust library may not have explicit disconnect method
-        // This is a placeholder implementation
+        if let Some(client) = self.client.take() {
+            client
+                .disconnect()
+                .await
+                .map_err(|e| KickError::ConnectionError(e.to_string()))?;
+        }
         self.is_connected = false;
         self.current_channel = None;
         Ok(())
@@ -353,3 +609,92 @@ impl Default for KickPlatform {
         Self::new(PlatformConfig::default())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::connection::Retraction;
+
+    #[test]
+    fn test_deleted_message_and_ban_events() {
+        let deleted = kick_moderation_event(
+            MESSAGE_DELETED_EVENT,
+            r#"{"id":"d1","message":{"id":"abc-123"},"aiModerated":false}"#,
+        )
+        .unwrap();
+        assert!(matches!(deleted.message_type, MessageType::System));
+        assert_eq!(
+            deleted.retraction(),
+            Some(Retraction::Message("abc-123".to_string()))
+        );
+
+        let timeout = kick_moderation_event(
+            USER_BANNED_EVENT,
+            r#"{"id":"b1","user":{"id":1,"username":"spammer"},"banned_by":{"username":"mod"},"permanent":false,"duration":5}"#,
+        )
+        .unwrap();
+        assert!(matches!(timeout.message_type, MessageType::Ban));
+        assert_eq!(timeout.username, "spammer");
+        assert_eq!(timeout.content, "spammer was timed out for 5 min");
+        assert_eq!(timeout.metadata.custom_data["banned_by"], "mod");
+        assert_eq!(timeout.retraction(), None);
+
+        // Tal como llega en `RawMessage.data`: la cadena JSON de Pusher
+        let ban = kick_moderation_event(
+            USER_BANNED_EVENT,
+            r#""{\"user\":{\"username\":\"troll\"},\"permanent\":true}""#,
+        )
+        .unwrap();
+        assert_eq!(ban.content, "troll was banned");
+
+        assert!(kick_moderation_event(PINNED_MESSAGE_DELETED_EVENT, "{}").is_none());
+        assert!(kick_moderation_event(MESSAGE_DELETED_EVENT, "{}").is_none());
+    }
+
+    #[test]
+    fn test_subscription_gift_and_follow_events() {
+        let resub = kick_alert_event(
+            SUBSCRIPTION_EVENT,
+            r#""{\"chatroom_id\":1,\"username\":\"fan\",\"months\":3}""#,
+        )
+        .unwrap();
+        assert!(matches!(resub.message_type, MessageType::Subscription));
+        assert_eq!(resub.username, "fan");
+        assert_eq!(resub.content, "fan resubscribed for 3 months");
+        assert_eq!(
+            crate::theme::events::EventCard::from_message(&resub)
+                .unwrap()
+                .headline,
+            "3 months"
+        );
+
+        let gifts = kick_alert_event(
+            GIFTED_SUBSCRIPTIONS_EVENT,
+            r#"{"chatroom_id":1,"gifted_usernames":["a","b"],"gifter_username":"santa","gifter_total":7}"#,
+        )
+        .unwrap();
+        assert!(matches!(gifts.message_type, MessageType::Subscription));
+        assert_eq!(gifts.username, "santa");
+        assert_eq!(gifts.content, "santa gifted 2 subs");
+        assert_eq!(gifts.metadata.custom_data["gift_count"], 2);
+        assert_eq!(gifts.metadata.custom_data["gifted_usernames"][1], "b");
+
+        let follow = kick_alert_event(
+            FOLLOWERS_UPDATED_EVENT,
+            r#"{"followersCount":42,"channel_id":9,"username":"newbie","created_at":1700000000,"followed":true}"#,
+        )
+        .unwrap();
+        assert!(matches!(follow.message_type, MessageType::Follow));
+        assert_eq!(follow.content, "newbie is now following");
+        assert_eq!(follow.metadata.custom_data["followers_count"], 42);
+
+        // Unfollows y actualizaciones sin usuario no abren ventana
+        assert!(kick_alert_event(
+            FOLLOWERS_UPDATED_EVENT,
+            r#"{"followersCount":41,"username":"newbie","followed":false}"#,
+        )
+        .is_none());
+        assert!(kick_alert_event(FOLLOWERS_UPDATED_EVENT, r#"{"followersCount":41}"#).is_none());
+        assert!(kick_alert_event(MESSAGE_DELETED_EVENT, "{}").is_none());
+    }
+}
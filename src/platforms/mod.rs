@@ -1,10 +1,17 @@
 pub mod base;
+pub mod custom;
+pub mod donations;
 pub mod kick;
+pub mod trovo;
 pub mod twitch;
+pub mod twitch_eventsub;
 pub mod youtube;
 
 pub use base::*;
+pub use custom::*;
+pub use donations::*;
 pub use kick::*;
+pub use trovo::*;
 pub use twitch::*;
 pub use youtube::*;
 
@@ -13,6 +20,8 @@ use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
+use crate::secrets::{self, OsKeyring, SecretError, SecretStore};
+
 /// Fábrica de plataformas
 pub struct PlatformFactory {
     platforms: HashMap<String, Arc<dyn PlatformCreator + Send + Sync>>,
@@ -28,6 +37,9 @@ impl PlatformFactory {
         factory.register_platform("twitch".to_string(), Arc::new(TwitchCreator));
         // factory.register_platform("youtube".to_string(), Arc::new(YouTubeCreator));
         factory.register_platform("kick".to_string(), Arc::new(KickCreator));
+        factory.register_platform("custom".to_string(), Arc::new(CustomCreator));
+        factory.register_platform("trovo".to_string(), Arc::new(TrovoCreator));
+        factory.register_platform("donations".to_string(), Arc::new(DonationsCreator));
 
         factory
     }
@@ -121,15 +133,46 @@ pub enum PlatformError {
 #[derive(Clone)]
 pub struct CredentialManager {
     credentials: Arc<RwLock<HashMap<String, crate::config::Credentials>>>,
+    /// Almacén de los valores `keyring:` de la configuración
+    secrets: Arc<dyn SecretStore>,
 }
 
 impl CredentialManager {
     pub fn new() -> Self {
+        Self::with_secret_store(Arc::new(OsKeyring))
+    }
+
+    pub fn with_secret_store(secrets: Arc<dyn SecretStore>) -> Self {
         Self {
             credentials: Arc::new(RwLock::new(HashMap::new())),
+            secrets,
         }
     }
 
+    /// Copia de `credentials` con los marcadores `keyring:` ya sustituidos
+    pub fn resolve_credentials(
+        &self,
+        platform: &str,
+        credentials: &crate::config::Credentials,
+    ) -> Result<crate::config::Credentials, SecretError> {
+        let mut resolved = credentials.clone();
+        secrets::resolve_credentials(self.secrets.as_ref(), platform, &mut resolved)?;
+        Ok(resolved)
+    }
+
+    /// Guarda un secreto en el almacén, para usarlo como `keyring:<key>`
+    pub fn store_secret(&self, key: &str, secret: &str) -> Result<(), SecretError> {
+        self.secrets.store(key, secret)
+    }
+
+    pub fn get_secret(&self, key: &str) -> Result<String, SecretError> {
+        self.secrets.get(key)
+    }
+
+    pub fn remove_secret(&self, key: &str) -> Result<bool, SecretError> {
+        self.secrets.remove(key)
+    }
+
     pub async fn store_credentials(
         &self,
         platform: String,
@@ -162,6 +205,12 @@ pub enum PlatformWrapperError {
     Twitch(#[from] crate::platforms::twitch::TwitchError),
     #[error("Kick error: {0}")]
     Kick(#[from] crate::platforms::kick::KickError),
+    #[error("Custom source error: {0}")]
+    Custom(#[from] crate::platforms::custom::CustomError),
+    #[error("Trovo error: {0}")]
+    Trovo(#[from] crate::platforms::trovo::TrovoError),
+    #[error("Donations error: {0}")]
+    Donations(#[from] crate::platforms::donations::DonationsError),
     #[error("Generic platform error: {0}")]
     Generic(String),
 }
@@ -234,14 +283,14 @@ pub mod utils {
     ) -> bool {
         // Longitud mínima
         if let Some(min_len) = filters.min_message_length {
-            if content.len() < min_len {
+            if crate::text::grapheme_len(content) < min_len {
                 return false;
             }
         }
 
         // Longitud máxima
         if let Some(max_len) = filters.max_message_length {
-            if content.len() > max_len {
+            if crate::text::grapheme_len(content) > max_len {
                 return false;
             }
         }
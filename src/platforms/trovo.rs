@@ -0,0 +1,624 @@
+//! Plataforma Trovo: chat por el WebSocket de la API abierta
+//!
+//! Flujo: se resuelve el `channel_id` del canal (`/getusers`), se pide un token
+//! de chat de solo lectura (`/chat/channel-token/{id}`) y se autentica en
+//! `wss://open-chat.trovo.live/chat`. Trovo exige `Client-ID`, que se toma de
+//! `credentials.client_id`. El servidor indica en cada `PONG` cada cuántos
+//! segundos hay que enviar el siguiente `PING`.
+//!
+//! Al entrar, Trovo reenvía los últimos mensajes del chat; se descartan los
+//! anteriores a la conexión.
+
+use async_trait::async_trait;
+use futures::{SinkExt, StreamExt};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::sync::mpsc;
+use tokio_tungstenite::tungstenite::Message;
+
+use crate::capture::PayloadCapture;
+use crate::config::{PlatformConfig, PlatformType};
+use crate::connection::{
+    Badge, ChatMessage, Emote, EmoteSource, MessageMetadata, MessageType, StreamingPlatform,
+};
+use crate::health::ErrorBudget;
+use crate::platforms::base::{BasePlatform, ChannelReaders};
+use crate::platforms::{utils, PlatformCreator, PlatformError, PlatformWrapperError};
+
+const API_BASE: &str = "https://open-api.trovo.live/openplatform";
+const CHAT_URL: &str = "wss://open-chat.trovo.live/chat";
+const DEFAULT_PING_GAP: Duration = Duration::from_secs(30);
+
+/// Clave en `custom_data` con el tipo de chat original de Trovo
+pub const TROVO_TYPE_KEY: &str = "trovo_type";
+
+#[derive(Debug, thiserror::Error)]
+pub enum TrovoError {
+    #[error("Missing credential: {0}")]
+    MissingCredential(&'static str),
+    #[error("API error: {0}")]
+    ApiError(String),
+    #[error("Connection error: {0}")]
+    ConnectionError(String),
+    #[error("Invalid message: {0}")]
+    InvalidMessage(String),
+}
+
+/// Tipos de chat de Trovo que se muestran
+mod chat_type {
+    pub const NORMAL: i64 = 0;
+    pub const SPELL: i64 = 5;
+    pub const MAGIC_CHAT_SUPER_CAP: i64 = 6;
+    pub const MAGIC_CHAT_BULLET: i64 = 9;
+    pub const SUBSCRIPTION: i64 = 5001;
+    pub const SYSTEM: i64 = 5002;
+    pub const FOLLOW: i64 = 5003;
+    pub const WELCOME: i64 = 5004;
+    pub const GIFT_SUB_RANDOM: i64 = 5005;
+    pub const GIFT_SUB: i64 = 5006;
+    pub const ACTIVITY: i64 = 5007;
+    pub const RAID: i64 = 5008;
+    pub const CUSTOM_SPELL: i64 = 5009;
+    pub const STREAM_STATE: i64 = 5012;
+    pub const UNFOLLOW: i64 = 5013;
+}
+
+/// Frame del WebSocket de chat
+#[derive(Debug, Deserialize)]
+struct TrovoFrame {
+    #[serde(rename = "type")]
+    frame_type: String,
+    #[serde(default)]
+    error: Option<String>,
+    #[serde(default)]
+    data: serde_json::Value,
+}
+
+#[derive(Debug, Deserialize)]
+struct TrovoChat {
+    #[serde(rename = "type", default)]
+    chat_type: i64,
+    #[serde(default)]
+    content: String,
+    #[serde(default)]
+    nick_name: String,
+    user_name: Option<String>,
+    sender_id: Option<i64>,
+    message_id: Option<String>,
+    send_time: Option<i64>,
+    #[serde(default)]
+    roles: Vec<String>,
+    #[serde(default)]
+    medals: Vec<String>,
+    avatar: Option<String>,
+}
+
+/// Contenido de los hechizos (`type` 5 y 5009)
+#[derive(Debug, Deserialize)]
+struct TrovoSpell {
+    gift: String,
+    #[serde(default = "one")]
+    num: u64,
+    gift_value: Option<u64>,
+    value_type: Option<String>,
+}
+
+fn one() -> u64 {
+    1
+}
+
+/// Convierte un frame `CHAT` en mensajes; el resto de frames no producen mensajes
+///
+/// Los chats enviados antes de `since` (segundos Unix) se descartan.
+pub fn parse_trovo_chats(
+    text: &str,
+    channel: &str,
+    since: Option<i64>,
+) -> Result<Vec<ChatMessage>, TrovoError> {
+    let frame: TrovoFrame =
+        serde_json::from_str(text).map_err(|e| TrovoError::InvalidMessage(e.to_string()))?;
+    if frame.frame_type != "CHAT" {
+        return Ok(Vec::new());
+    }
+
+    let chats: Vec<TrovoChat> = match frame.data.get("chats") {
+        Some(chats) => serde_json::from_value(chats.clone())
+            .map_err(|e| TrovoError::InvalidMessage(e.to_string()))?,
+        None => Vec::new(),
+    };
+    Ok(chats
+        .into_iter()
+        .filter(|chat| match (since, chat.send_time) {
+            (Some(since), Some(sent)) => sent >= since,
+            _ => true,
+        })
+        .filter_map(|chat| to_chat_message(chat, channel))
+        .collect())
+}
+
+fn to_chat_message(chat: TrovoChat, channel: &str) -> Option<ChatMessage> {
+    let mut custom_data: HashMap<String, serde_json::Value> = HashMap::new();
+    custom_data.insert(TROVO_TYPE_KEY.to_string(), chat.chat_type.into());
+    if let Some(avatar) = &chat.avatar {
+        custom_data.insert("avatar".to_string(), avatar.clone().into());
+    }
+    if let Some(sender_id) = chat.sender_id {
+        custom_data.insert("sender_id".to_string(), sender_id.into());
+    }
+
+    let mut content = chat.content;
+    let message_type = match chat.chat_type {
+        chat_type::NORMAL => MessageType::Normal,
+        chat_type::MAGIC_CHAT_SUPER_CAP..=chat_type::MAGIC_CHAT_BULLET => MessageType::Highlight,
+        chat_type::SPELL | chat_type::CUSTOM_SPELL => {
+            let spell: TrovoSpell = serde_json::from_str(&content).ok()?;
+            content = format!("{} x{}", spell.gift, spell.num);
+            custom_data.insert("gift".to_string(), spell.gift.into());
+            custom_data.insert("gift_count".to_string(), spell.num.into());
+            if let Some(value) = spell.gift_value {
+                custom_data.insert("gift_value".to_string(), value.into());
+            }
+            if let Some(value_type) = spell.value_type {
+                custom_data.insert("gift_currency".to_string(), value_type.into());
+            }
+            MessageType::Cheer
+        }
+        chat_type::SUBSCRIPTION => MessageType::Subscription,
+        chat_type::GIFT_SUB_RANDOM | chat_type::GIFT_SUB => {
+            custom_data.insert("gift_sub".to_string(), true.into());
+            MessageType::Subscription
+        }
+        chat_type::RAID => MessageType::Raid,
+        chat_type::SYSTEM | chat_type::FOLLOW | chat_type::ACTIVITY | chat_type::STREAM_STATE => {
+            MessageType::System
+        }
+        // Entradas y unfollows solo serían ruido en el overlay
+        chat_type::WELCOME | chat_type::UNFOLLOW => return None,
+        _ => MessageType::Unknown,
+    };
+
+    let timestamp = chat
+        .send_time
+        .map(|secs| UNIX_EPOCH + Duration::from_secs(secs.max(0) as u64))
+        .unwrap_or_else(SystemTime::now);
+    let username = chat
+        .user_name
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| chat.nick_name.clone());
+
+    Some(ChatMessage {
+        id: chat.message_id.unwrap_or_else(utils::generate_message_id),
+        platform: "trovo".to_string(),
+        channel: channel.to_string(),
+        username,
+        display_name: Some(chat.nick_name),
+        content,
+        emotes: Vec::new(),
+        badges: trovo_badges(&chat.roles, &chat.medals),
+        timestamp,
+        user_color: None,
+        metadata: MessageMetadata {
+            is_action: false,
+            is_whisper: false,
+            is_highlighted: matches!(message_type, MessageType::Highlight),
+            is_me_message: false,
+            reply_to: None,
+            thread_id: None,
+            custom_data,
+        },
+        message_type,
+    })
+}
+
+/// Roles con nombre del paquete de insignias integrado y medallas tal cual
+fn trovo_badges(roles: &[String], medals: &[String]) -> Vec<Badge> {
+    let roles = roles.iter().filter_map(|role| {
+        let name = match role.to_lowercase().as_str() {
+            "streamer" => "broadcaster",
+            "mod" | "supermod" => "moderator",
+            "subscriber" => "subscriber",
+            "admin" | "warden" => "staff",
+            _ => return None,
+        };
+        Some((name.to_string(), "1".to_string(), role.clone()))
+    });
+    // Medallas como `sub_L1_T1`: nombre `sub`, versión `L1_T1`
+    let medals = medals.iter().map(|medal| {
+        let (name, version) = medal.split_once('_').unwrap_or((medal.as_str(), "1"));
+        (name.to_lowercase(), version.to_string(), medal.clone())
+    });
+
+    let mut badges: Vec<Badge> = Vec::new();
+    for (name, version, title) in roles.chain(medals) {
+        if badges.iter().any(|badge| badge.name == name) {
+            continue;
+        }
+        badges.push(Badge {
+            id: name.clone(),
+            name,
+            version,
+            url: None,
+            title: Some(title),
+            source: EmoteSource::Trovo,
+        });
+    }
+    badges
+}
+
+fn api_request(
+    http: &reqwest::Client,
+    method: reqwest::Method,
+    path: &str,
+    client_id: &str,
+) -> reqwest::RequestBuilder {
+    http.request(method, format!("{}{}", API_BASE, path))
+        .header("Accept", "application/json")
+        .header("Client-ID", client_id)
+}
+
+/// `channel_id` numérico de un canal (se acepta el id directamente)
+async fn resolve_channel_id(
+    http: &reqwest::Client,
+    client_id: &str,
+    channel: &str,
+) -> Result<String, TrovoError> {
+    if !channel.is_empty() && channel.chars().all(|c| c.is_ascii_digit()) {
+        return Ok(channel.to_string());
+    }
+
+    let response: serde_json::Value =
+        api_request(http, reqwest::Method::POST, "/getusers", client_id)
+            .json(&serde_json::json!({ "user": [channel] }))
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| TrovoError::ApiError(e.to_string()))?
+            .json()
+            .await
+            .map_err(|e| TrovoError::ApiError(e.to_string()))?;
+
+    response
+        .get("users")
+        .and_then(|users| users.get(0))
+        .and_then(|user| user.get("channel_id"))
+        .and_then(|id| match id {
+            serde_json::Value::String(id) => Some(id.clone()),
+            serde_json::Value::Number(id) => Some(id.to_string()),
+            _ => None,
+        })
+        .ok_or_else(|| TrovoError::ApiError(format!("Channel '{}' not found", channel)))
+}
+
+async fn chat_token(
+    http: &reqwest::Client,
+    client_id: &str,
+    channel_id: &str,
+) -> Result<String, TrovoError> {
+    let path = format!("/chat/channel-token/{}", channel_id);
+    let response: serde_json::Value = api_request(http, reqwest::Method::GET, &path, client_id)
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| TrovoError::ApiError(e.to_string()))?
+        .json()
+        .await
+        .map_err(|e| TrovoError::ApiError(e.to_string()))?;
+
+    response
+        .get("token")
+        .and_then(|token| token.as_str())
+        .map(str::to_string)
+        .ok_or_else(|| TrovoError::ApiError("Chat token missing in response".to_string()))
+}
+
+fn control_frame(frame_type: &str, data: Option<serde_json::Value>) -> Message {
+    let mut frame = serde_json::json!({
+        "type": frame_type,
+        "nonce": utils::generate_message_id(),
+    });
+    if let Some(data) = data {
+        frame["data"] = data;
+    }
+    Message::Text(frame.to_string())
+}
+
+/// Lee el chat de un canal hasta que se cierre el WebSocket
+async fn read_chat(
+    client_id: String,
+    channel: String,
+    sender: mpsc::UnboundedSender<ChatMessage>,
+) -> Result<(), TrovoError> {
+    let http = reqwest::Client::new();
+    let channel_id = resolve_channel_id(&http, &client_id, &channel).await?;
+    let token = chat_token(&http, &client_id, &channel_id).await?;
+
+    let (mut stream, _) = tokio_tungstenite::connect_async(CHAT_URL)
+        .await
+        .map_err(|e| TrovoError::ConnectionError(e.to_string()))?;
+    stream
+        .send(control_frame(
+            "AUTH",
+            Some(serde_json::json!({ "token": token })),
+        ))
+        .await
+        .map_err(|e| TrovoError::ConnectionError(e.to_string()))?;
+
+    let connected_at = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs() as i64;
+    let mut ping_gap = DEFAULT_PING_GAP;
+    let mut next_ping = tokio::time::Instant::now() + ping_gap;
+
+    loop {
+        let frame = tokio::select! {
+            frame = stream.next() => frame,
+            _ = tokio::time::sleep_until(next_ping) => {
+                stream
+                    .send(control_frame("PING", None))
+                    .await
+                    .map_err(|e| TrovoError::ConnectionError(e.to_string()))?;
+                next_ping = tokio::time::Instant::now() + ping_gap;
+                continue;
+            }
+        };
+
+        let text = match frame {
+            Some(Ok(Message::Text(text))) => text,
+            Some(Ok(Message::Close(_))) | None => break,
+            Some(Ok(_)) => continue,
+            Some(Err(e)) => return Err(TrovoError::ConnectionError(e.to_string())),
+        };
+
+        let capture = PayloadCapture::global();
+        if capture.is_enabled() {
+            capture.record("trovo", &channel, &text);
+        }
+
+        if let Ok(frame) = serde_json::from_str::<TrovoFrame>(&text) {
+            match frame.frame_type.as_str() {
+                "RESPONSE" if frame.error.is_some() => {
+                    return Err(TrovoError::ConnectionError(format!(
+                        "Authentication failed: {}",
+                        frame.error.unwrap_or_default()
+                    )));
+                }
+                "PONG" => {
+                    if let Some(gap) = frame.data.get("gap").and_then(|gap| gap.as_u64()) {
+                        ping_gap = Duration::from_secs(gap.max(1));
+                    }
+                    continue;
+                }
+                _ => {}
+            }
+        }
+
+        match parse_trovo_chats(&text, &channel, Some(connected_at)) {
+            Ok(messages) => {
+                for message in messages {
+                    if sender.send(message).is_err() {
+                        return Ok(());
+                    }
+                }
+            }
+            Err(e) => {
+                eprintln!("⚠️ Ignoring Trovo frame: {}", e);
+                ErrorBudget::global().record_failure("platform:trovo", &e.to_string());
+            }
+        }
+    }
+    Ok(())
+}
+
+pub struct TrovoPlatform {
+    base: BasePlatform,
+    client_id: Option<String>,
+    readers: ChannelReaders,
+    is_connected: bool,
+}
+
+impl TrovoPlatform {
+    pub fn new(config: PlatformConfig) -> Self {
+        let client_id = config.credentials.client_id.clone();
+
+        Self {
+            base: BasePlatform::new("trovo".to_string(), PlatformType::Trovo, config),
+            client_id,
+            readers: ChannelReaders::new(),
+            is_connected: false,
+        }
+    }
+}
+
+#[async_trait]
+impl StreamingPlatform for TrovoPlatform {
+    type Error = PlatformWrapperError;
+
+    async fn connect(&mut self) -> Result<(), Self::Error> {
+        // El WebSocket se abre por canal en join_channel (cada uno con su token)
+        if self.client_id.is_none() {
+            return Err(TrovoError::MissingCredential("client_id").into());
+        }
+        self.is_connected = true;
+        Ok(())
+    }
+
+    async fn join_channel(&mut self, channel: String) -> Result<(), Self::Error> {
+        let client_id = self
+            .client_id
+            .clone()
+            .ok_or(TrovoError::MissingCredential("client_id"))?;
+        let channel = utils::sanitize_channel_name(&channel);
+        let reader_channel = channel.clone();
+
+        self.readers.spawn(channel, |sender| async move {
+            if let Err(e) = read_chat(client_id, reader_channel.clone(), sender).await {
+                eprintln!("❌ Trovo chat for '{}' failed: {}", reader_channel, e);
+                ErrorBudget::global().record_failure("platform:trovo", &e.to_string());
+            }
+        });
+        Ok(())
+    }
+
+    async fn leave_channel(&mut self, channel: String) -> Result<(), Self::Error> {
+        self.readers.stop(&utils::sanitize_channel_name(&channel));
+        Ok(())
+    }
+
+    async fn next_message(&mut self) -> Option<ChatMessage> {
+        self.readers.recv().await
+    }
+
+    async fn disconnect(&mut self) -> Result<(), Self::Error> {
+        self.readers.stop_all();
+        self.is_connected = false;
+        Ok(())
+    }
+
+    fn is_connected(&self) -> bool {
+        self.is_connected
+    }
+
+    fn platform_name(&self) -> &str {
+        "trovo"
+    }
+
+    async fn get_channel_emotes(&self, _channel: &str) -> Result<Vec<Emote>, Self::Error> {
+        Ok(Vec::new())
+    }
+
+    async fn get_global_emotes(&self) -> Result<Vec<Emote>, Self::Error> {
+        Ok(Vec::new())
+    }
+
+    fn parse_emotes(&self, _content: &str, _emote_data: &str) -> Vec<Emote> {
+        Vec::new()
+    }
+
+    fn parse_badges(&self, badge_data: &str) -> Vec<Badge> {
+        let roles: Vec<String> = badge_data
+            .split(',')
+            .map(str::trim)
+            .filter(|role| !role.is_empty())
+            .map(str::to_string)
+            .collect();
+        trovo_badges(&roles, &[])
+    }
+
+    fn apply_message_filters(
+        &self,
+        message: &mut ChatMessage,
+        filters: &crate::config::MessageFilters,
+    ) -> bool {
+        self.base.apply_message_filters(message, filters)
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+}
+
+pub struct TrovoCreator;
+
+#[async_trait]
+impl PlatformCreator for TrovoCreator {
+    async fn create(
+        &self,
+        config: PlatformConfig,
+    ) -> Result<Box<dyn StreamingPlatform<Error = PlatformWrapperError> + Send + Sync>, PlatformError>
+    {
+        self.validate_credentials(&config.credentials).await?;
+        Ok(Box::new(TrovoPlatform::new(config)))
+    }
+
+    fn platform_name(&self) -> &str {
+        "trovo"
+    }
+
+    fn required_credentials(&self) -> Vec<&'static str> {
+        vec!["client_id"]
+    }
+
+    async fn validate_credentials(
+        &self,
+        credentials: &crate::config::Credentials,
+    ) -> Result<bool, PlatformError> {
+        match credentials.client_id.as_deref() {
+            Some(id) if !id.trim().is_empty() => Ok(true),
+            _ => Err(PlatformError::AuthError(
+                "trovo requires credentials.client_id".to_string(),
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chat_frame_with_badges_and_history_filter() {
+        let frame = r#"{
+            "type": "CHAT",
+            "channel_info": {"channel_id": "100000031"},
+            "data": {"eid": "1", "chats": [
+                {"type": 0, "content": "old", "nick_name": "Old", "send_time": 1000},
+                {"type": 0, "content": "hello", "nick_name": "Viewer", "user_name": "viewer1",
+                 "message_id": "m1", "sender_id": 42, "send_time": 2000,
+                 "roles": ["mod", "follower"], "medals": ["sub_L1_T2"]}
+            ]}
+        }"#;
+
+        let messages = parse_trovo_chats(frame, "streamer", Some(1500)).unwrap();
+        assert_eq!(messages.len(), 1);
+        let message = &messages[0];
+        assert_eq!(message.id, "m1");
+        assert_eq!(message.platform, "trovo");
+        assert_eq!(message.username, "viewer1");
+        assert_eq!(message.display_name.as_deref(), Some("Viewer"));
+        assert_eq!(message.timestamp, UNIX_EPOCH + Duration::from_secs(2000));
+        let badges: Vec<_> = message.badges.iter().map(|b| b.name.as_str()).collect();
+        assert_eq!(badges, vec!["moderator", "sub"]);
+        assert_eq!(message.badges[1].version, "L1_T2");
+        assert_eq!(message.metadata.custom_data["sender_id"], 42);
+    }
+
+    #[test]
+    fn test_spells_gift_subs_and_noise() {
+        let frame = r#"{"type": "CHAT", "data": {"chats": [
+            {"type": 5, "nick_name": "Fan",
+             "content": "{\"gift\":\"Bomb\",\"num\":3,\"gift_value\":100,\"value_type\":\"Elixir\"}"},
+            {"type": 5006, "nick_name": "Fan", "content": "gifted a sub to Viewer"},
+            {"type": 5008, "nick_name": "Raider", "content": "is raiding with 20 viewers"},
+            {"type": 5004, "nick_name": "Lurker", "content": "joined"}
+        ]}}"#;
+
+        let messages = parse_trovo_chats(frame, "streamer", None).unwrap();
+        assert_eq!(messages.len(), 3);
+        assert!(matches!(messages[0].message_type, MessageType::Cheer));
+        assert_eq!(messages[0].content, "Bomb x3");
+        assert_eq!(messages[0].metadata.custom_data["gift_count"], 3);
+        assert_eq!(messages[0].metadata.custom_data["gift_currency"], "Elixir");
+        assert!(matches!(
+            messages[1].message_type,
+            MessageType::Subscription
+        ));
+        assert_eq!(messages[1].metadata.custom_data["gift_sub"], true);
+        assert!(matches!(messages[2].message_type, MessageType::Raid));
+    }
+
+    #[test]
+    fn test_control_frames_produce_no_messages() {
+        assert!(
+            parse_trovo_chats(r#"{"type": "PONG", "data": {"gap": 30}}"#, "c", None)
+                .unwrap()
+                .is_empty()
+        );
+        assert!(parse_trovo_chats("not json", "c", None).is_err());
+    }
+}
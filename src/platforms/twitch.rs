@@ -3,15 +3,22 @@ use std::collections::HashMap;
 use std::time::{Instant, SystemTime};
 use tokio::sync::mpsc;
 use twitch_irc::login::StaticLoginCredentials;
-use twitch_irc::message::{PrivmsgMessage, ServerMessage, TwitchUserBasics};
+use twitch_irc::message::{
+    AsRawIRC, ClearChatAction, ClearChatMessage, ClearMsgMessage, PrivmsgMessage, ServerMessage,
+    TwitchUserBasics, UserNoticeEvent, UserNoticeMessage,
+};
 use twitch_irc::{ClientConfig, SecureTCPTransport, TwitchIRCClient};
 
+use crate::capture::PayloadCapture;
 use crate::config::{Credentials, PlatformConfig, PlatformType};
 use crate::connection::{
     Badge, ChatMessage, Emote, EmoteMetadata, EmoteSource, MessageMetadata, MessageType,
-    StreamingPlatform, TextPosition,
+    StreamingPlatform, TextPosition, CLEARED_USER_KEY, CLEAR_TYPE_KEY, DELETED_MESSAGE_KEY,
 };
+use crate::emotes::EmoteQuality;
+use crate::health::ErrorBudget;
 use crate::platforms::base::{emote_utils::RawEmote, BasePlatform, ChannelInfo};
+use crate::platforms::twitch_eventsub::{self, EventSubAuth, EventSubTopic};
 use crate::platforms::{utils, PlatformCreator, PlatformError, PlatformWrapperError};
 
 #[derive(Debug)]
@@ -39,6 +46,13 @@ pub struct TwitchPlatform {
     base: BasePlatform,
     client: Option<TwitchIRCClient<SecureTCPTransport, StaticLoginCredentials>>,
     message_receiver: Option<mpsc::UnboundedReceiver<ServerMessage>>,
+    /// Credenciales de EventSub; `None` si solo se lee el chat IRC
+    eventsub: Option<EventSubAuth>,
+    eventsub_topics: Vec<EventSubTopic>,
+    event_sender: mpsc::UnboundedSender<ChatMessage>,
+    event_receiver: mpsc::UnboundedReceiver<ChatMessage>,
+    /// Sesión EventSub de cada canal
+    event_readers: HashMap<String, tokio::task::JoinHandle<()>>,
 }
 
 impl TwitchPlatform {
@@ -47,11 +61,19 @@ impl TwitchPlatform {
             BasePlatform::new("twitch".to_string(), PlatformType::Twitch, config.clone());
 
         // Credenciales son opcionales para conexiones anónimas
+        let eventsub = EventSubAuth::from_credentials(&config.credentials);
+        let eventsub_topics = EventSubTopic::from_settings(&config.settings);
+        let (event_sender, event_receiver) = mpsc::unbounded_channel();
 
         Ok(Self {
             base,
             client: None,
             message_receiver: None,
+            eventsub,
+            eventsub_topics,
+            event_sender,
+            event_receiver,
+            event_readers: HashMap::new(),
         })
     }
 
@@ -75,10 +97,7 @@ impl TwitchPlatform {
                         start: emote.char_range.start,
                         end: emote.char_range.end,
                     }],
-                    url: Some(format!(
-                        "https://static-cdn.jtvnw.net/emoticons/v2/{}",
-                        emote.id
-                    )),
+                    url: Some(EmoteQuality::global().twitch_url(&emote.id, "default")),
                     is_animated: false, // Twitch no indica esto en el mensaje base
                     width: Some(28),
                     height: Some(28),
@@ -111,7 +130,9 @@ impl TwitchPlatform {
     }
 
     fn convert_privmsg_message(msg: PrivmsgMessage) -> ChatMessage {
-        let message_type = if msg.message_text.starts_with("/me") {
+        let message_type = if msg.bits.is_some() {
+            MessageType::Cheer
+        } else if msg.message_text.starts_with("/me") {
             MessageType::Action
         } else if msg.message_text.starts_with('!') {
             MessageType::Normal // Podría ser comando, pero lo tratamos como normal
@@ -124,7 +145,13 @@ impl TwitchPlatform {
             is_whisper: false,
             is_highlighted: false,
             is_me_message: msg.message_text.starts_with("/me"),
-            reply_to: None, // TODO: Fix reply field access when available
+            reply_to: msg
+                .source
+                .tags
+                .0
+                .get("reply-parent-msg-id")
+                .cloned()
+                .flatten(),
             thread_id: None,
             custom_data: HashMap::new(),
         };
@@ -141,6 +168,12 @@ impl TwitchPlatform {
             .custom_data
             .insert("room_id".to_string(), msg.channel_id.clone().into());
 
+        if let Some(bits) = msg.bits {
+            metadata
+                .custom_data
+                .insert(crate::hype::BITS_KEY.to_string(), bits.into());
+        }
+
         ChatMessage {
             id: msg.message_id.to_string(),
             platform: "twitch".to_string(),
@@ -150,13 +183,199 @@ impl TwitchPlatform {
             content: msg.message_text.clone(),
             emotes: Self::convert_twitch_emotes(&msg.emotes),
             badges: Self::convert_twitch_badges(&msg.badges),
-            timestamp: SystemTime::now(),
+            timestamp: msg.server_timestamp.into(),
             user_color: None,
             message_type,
             metadata,
         }
     }
 
+    /// Convierte un `USERNOTICE` (subs, resubs, raids...) en un mensaje de evento
+    ///
+    /// En subs y resubs se guardan en `custom_data` los meses acumulados
+    /// (`cumulative_months`), la racha (`streak_months`, solo si el usuario la
+    /// comparte) y el plan; el contenido es el mensaje del usuario, o el texto
+    /// de sistema de Twitch si no escribió nada.
+    pub fn convert_usernotice_message(msg: UserNoticeMessage) -> ChatMessage {
+        let mut data: HashMap<String, serde_json::Value> = HashMap::new();
+        data.insert("notice_type".to_string(), msg.event_id.clone().into());
+        data.insert("system_message".to_string(), msg.system_message.clone().into());
+        data.insert("user_id".to_string(), msg.sender.id.clone().into());
+        data.insert("room_id".to_string(), msg.channel_id.clone().into());
+
+        let message_type = match &msg.event {
+            UserNoticeEvent::SubOrResub {
+                is_resub,
+                cumulative_months,
+                streak_months,
+                sub_plan,
+                sub_plan_name,
+            } => {
+                data.insert("is_resub".to_string(), (*is_resub).into());
+                data.insert("cumulative_months".to_string(), (*cumulative_months).into());
+                if let Some(streak) = streak_months {
+                    data.insert("streak_months".to_string(), (*streak).into());
+                }
+                data.insert("sub_plan".to_string(), sub_plan.clone().into());
+                data.insert("sub_plan_name".to_string(), sub_plan_name.clone().into());
+                MessageType::Subscription
+            }
+            UserNoticeEvent::Raid { viewer_count, .. } => {
+                data.insert("viewer_count".to_string(), (*viewer_count).into());
+                MessageType::Raid
+            }
+            UserNoticeEvent::SubMysteryGift {
+                mass_gift_count, ..
+            }
+            | UserNoticeEvent::AnonSubMysteryGift {
+                mass_gift_count, ..
+            } => {
+                data.insert(
+                    crate::hype::MASS_GIFT_KEY.to_string(),
+                    (*mass_gift_count).into(),
+                );
+                MessageType::Subscription
+            }
+            UserNoticeEvent::SubGift { .. }
+            | UserNoticeEvent::GiftPaidUpgrade { .. }
+            | UserNoticeEvent::AnonGiftPaidUpgrade { .. } => MessageType::Subscription,
+            _ => MessageType::System,
+        };
+
+        let (content, emotes) = match msg.message_text {
+            Some(text) if !text.trim().is_empty() => (text, Self::convert_twitch_emotes(&msg.emotes)),
+            _ => (msg.system_message, Vec::new()),
+        };
+
+        ChatMessage {
+            id: msg.message_id,
+            platform: "twitch".to_string(),
+            channel: msg.channel_login,
+            username: msg.sender.login,
+            display_name: Some(msg.sender.name),
+            content,
+            emotes,
+            badges: Self::convert_twitch_badges(&msg.badges),
+            timestamp: msg.server_timestamp.into(),
+            user_color: msg.name_color.map(|color| color.to_string()),
+            message_type,
+            metadata: MessageMetadata {
+                is_action: false,
+                is_whisper: false,
+                is_highlighted: true,
+                is_me_message: false,
+                reply_to: None,
+                thread_id: None,
+                custom_data: data,
+            },
+        }
+    }
+
+    /// Aviso de timeout, ban o `/clear`; retira los mensajes afectados
+    fn convert_clearchat_message(msg: ClearChatMessage) -> ChatMessage {
+        let mut custom_data = HashMap::new();
+        custom_data.insert(CLEAR_TYPE_KEY.to_string(), "chat".into());
+        let content = match &msg.action {
+            ClearChatAction::UserBanned { user_login, .. } => {
+                custom_data.insert(CLEARED_USER_KEY.to_string(), user_login.as_str().into());
+                format!("{} has been banned", user_login)
+            }
+            ClearChatAction::UserTimedOut { user_login, .. } => {
+                custom_data.insert(CLEARED_USER_KEY.to_string(), user_login.as_str().into());
+                format!("{} has been timed out", user_login)
+            }
+            ClearChatAction::ChatCleared => "Chat has been cleared by a moderator".to_string(),
+        };
+        Self::moderation_notice(msg.channel_login, content, custom_data)
+    }
+
+    /// Borrado de un único mensaje; no se muestra, solo retira el original
+    fn convert_clearmsg_message(msg: ClearMsgMessage) -> ChatMessage {
+        let mut custom_data = HashMap::new();
+        custom_data.insert(DELETED_MESSAGE_KEY.to_string(), msg.message_id.into());
+        Self::moderation_notice(
+            msg.channel_login,
+            format!("Message from {} deleted by a moderator", msg.sender_login),
+            custom_data,
+        )
+    }
+
+    fn moderation_notice(
+        channel: String,
+        content: String,
+        custom_data: HashMap<String, serde_json::Value>,
+    ) -> ChatMessage {
+        ChatMessage {
+            id: utils::generate_message_id(),
+            platform: "twitch".to_string(),
+            channel,
+            username: "system".to_string(),
+            display_name: Some("System".to_string()),
+            content,
+            emotes: Vec::new(),
+            badges: Vec::new(),
+            timestamp: SystemTime::now(),
+            user_color: Some("#ff0000".to_string()),
+            message_type: MessageType::System,
+            metadata: MessageMetadata {
+                is_action: false,
+                is_whisper: false,
+                is_highlighted: true,
+                is_me_message: false,
+                reply_to: None,
+                thread_id: None,
+                custom_data,
+            },
+        }
+    }
+
+    /// Abre la sesión EventSub del canal si hay credenciales y temas; se
+    /// reintenta tras cada corte salvo que Twitch rechace el token
+    fn start_eventsub(&mut self, channel: &str) {
+        let Some(auth) = self.eventsub.clone() else {
+            return;
+        };
+        if self.eventsub_topics.is_empty() {
+            return;
+        }
+        let topics = self.eventsub_topics.clone();
+        let sender = self.event_sender.clone();
+        let reader_channel = channel.to_string();
+        let max_attempts = self.base.config.settings.max_reconnect_attempts;
+        let delay = std::time::Duration::from_millis(self.base.config.settings.reconnect_delay_ms);
+
+        let reader = tokio::spawn(async move {
+            let mut failures = 0;
+            loop {
+                let result = twitch_eventsub::read_events(
+                    auth.clone(),
+                    topics.clone(),
+                    reader_channel.clone(),
+                    sender.clone(),
+                )
+                .await;
+                if sender.is_closed() {
+                    return;
+                }
+                match result {
+                    Ok(()) => failures = 0,
+                    Err(e) => {
+                        eprintln!("❌ Twitch EventSub for '{}' failed: {}", reader_channel, e);
+                        ErrorBudget::global().record_failure("platform:twitch", &e.to_string());
+                        failures += 1;
+                        if matches!(e, TwitchError::AuthError(_)) || failures >= max_attempts {
+                            return;
+                        }
+                    }
+                }
+                tokio::time::sleep(delay).await;
+            }
+        });
+        if let Some(previous) = self.event_readers.insert(channel.to_string(), reader) {
+            previous.abort();
+        }
+    }
+
     async fn handle_server_message(&mut self, message: ServerMessage) -> Option<ChatMessage> {
         eprintln!("[DEBUG] Received Twitch message: {:?}", message);
         match message {
@@ -175,88 +394,9 @@ impl TwitchPlatform {
 
                 Some(Self::convert_privmsg_message(privmsg))
             }
-            ServerMessage::ClearChat(msg) => {
-                // Mensaje de sistema de timeout/ban
-                Some(ChatMessage {
-                    id: utils::generate_message_id(),
-                    platform: "twitch".to_string(),
-                    channel: msg.channel_login,
-                    username: "system".to_string(),
-                    display_name: Some("System".to_string()),
-                    content: match &msg.action {
-                        twitch_irc::message::ClearChatAction::UserBanned { user_login, .. } => {
-                            format!("{} has been banned", user_login)
-                        }
-                        twitch_irc::message::ClearChatAction::UserTimedOut {
-                            user_login, ..
-                        } => {
-                            format!("{} has been timed out", user_login)
-                        }
-                        twitch_irc::message::ClearChatAction::ChatCleared => {
-                            "Chat has been cleared by a moderator".to_string()
-                        }
-                    },
-                    emotes: Vec::new(),
-                    badges: Vec::new(),
-                    timestamp: SystemTime::now(),
-                    user_color: Some("#ff0000".to_string()),
-                    message_type: MessageType::System,
-                    metadata: MessageMetadata {
-                        is_action: false,
-                        is_whisper: false,
-                        is_highlighted: true,
-                        is_me_message: false,
-                        reply_to: None,
-                        thread_id: None,
-                        custom_data: {
-                            let mut data = HashMap::new();
-                            data.insert("clear_type".to_string(), "chat".into());
-                            data
-                        },
-                    },
-                })
-            }
-            ServerMessage::UserNotice(msg) => {
-                // Mensajes de suscripción, raid, etc.
-                let message_content = match msg.message_id.as_str() {
-                    "sub" | "resub" => {
-                        // For subscription messages, we'll use a generic message
-                        // since cumulative_months may not be available in all cases
-                        format!("{} has subscribed!", msg.sender.name)
-                    }
-                    "raid" => {
-                        format!("{} is raiding the channel!", msg.sender.name)
-                    }
-                    _ => format!("System notice from {}", msg.sender.name),
-                };
-
-                Some(ChatMessage {
-                    id: utils::generate_message_id(),
-                    platform: "twitch".to_string(),
-                    channel: msg.channel_login,
-                    username: "system".to_string(),
-                    display_name: Some("System".to_string()),
-                    content: message_content,
-                    emotes: Vec::new(),
-                    badges: Vec::new(),
-                    timestamp: SystemTime::now(),
-                    user_color: Some("#00ff00".to_string()),
-                    message_type: MessageType::Subscription,
-                    metadata: MessageMetadata {
-                        is_action: false,
-                        is_whisper: false,
-                        is_highlighted: true,
-                        is_me_message: false,
-                        reply_to: None,
-                        thread_id: None,
-                        custom_data: {
-                            let mut data = HashMap::new();
-                            data.insert("notice_type".to_string(), msg.message_id.into());
-                            data
-                        },
-                    },
-                })
-            }
+            ServerMessage::ClearChat(msg) => Some(Self::convert_clearchat_message(msg)),
+            ServerMessage::ClearMsg(msg) => Some(Self::convert_clearmsg_message(msg)),
+            ServerMessage::UserNotice(msg) => Some(Self::convert_usernotice_message(msg)),
             ServerMessage::RoomState(msg) => {
                 // Actualizar información del canal
                 let channel_info = ChannelInfo {
@@ -272,6 +412,12 @@ impl TwitchPlatform {
                     .update_channel_info(msg.channel_login, channel_info);
                 None
             }
+            ServerMessage::Notice(notice) if notice.channel_login.is_none() => {
+                // Avisos sin canal: fallos de login ("Login authentication failed", ...)
+                eprintln!("⚠️ Twitch notice: {}", notice.message_text);
+                ErrorBudget::global().record_failure("platform:twitch", &notice.message_text);
+                None
+            }
             ServerMessage::Ping(_) | ServerMessage::Pong(_) => {
                 // Ignorar mensajes de ping/pong
                 None
@@ -343,6 +489,7 @@ impl StreamingPlatform for TwitchPlatform {
             };
             self.base
                 .update_channel_info(sanitized_channel.clone(), channel_info);
+            self.start_eventsub(&sanitized_channel);
 
             Ok(())
         } else {
@@ -360,6 +507,9 @@ impl StreamingPlatform for TwitchPlatform {
 
             // Remover canal de la lista
             self.base.channels.remove(&sanitized_channel);
+            if let Some(reader) = self.event_readers.remove(&sanitized_channel) {
+                reader.abort();
+            }
 
             Ok(())
         } else {
@@ -372,7 +522,10 @@ impl StreamingPlatform for TwitchPlatform {
     async fn next_message(&mut self) -> Option<ChatMessage> {
         loop {
             let message = match &mut self.message_receiver {
-                Some(receiver) => receiver.recv().await,
+                Some(receiver) => tokio::select! {
+                    message = receiver.recv() => message,
+                    Some(event) = self.event_receiver.recv() => return Some(event),
+                },
                 None => {
                     eprintln!("[DEBUG] No message receiver available");
                     return None;
@@ -381,6 +534,17 @@ impl StreamingPlatform for TwitchPlatform {
 
             if let Some(message) = message {
                 eprintln!("[DEBUG] Raw message received from Twitch IRC");
+                let capture = PayloadCapture::global();
+                if capture.is_enabled() {
+                    let source = message.source();
+                    let channel = source
+                        .params
+                        .first()
+                        .filter(|p| p.starts_with('#'))
+                        .map(|p| p.as_str())
+                        .unwrap_or("server");
+                    capture.record("twitch", channel, &source.as_raw_irc());
+                }
                 if let Some(chat_message) = self.handle_server_message(message).await {
                     eprintln!(
                         "[DEBUG] Converted to ChatMessage: {} - {}",
@@ -401,6 +565,9 @@ impl StreamingPlatform for TwitchPlatform {
         self.base.connected = false;
         self.client = None;
         self.message_receiver = None;
+        for (_, reader) in self.event_readers.drain() {
+            reader.abort();
+        }
         self.base.channels.clear();
         Ok(())
     }
@@ -592,3 +759,91 @@ impl Default for TwitchPlatform {
         Self::new(PlatformConfig::default()).unwrap()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mapping::{MappedMessageType, MappingSystem, RawPlatformMessage};
+    use crate::theme::events::EventCard;
+    use twitch_irc::message::IRCMessage;
+
+    const RESUB: &str = "@badge-info=subscriber/2;badges=subscriber/0;color=#0000FF;display-name=Gutrin;emotes=1035663:0-3;flags=;id=e0975c76-054c-4954-8cb0-91b8867ec1ca;login=gutrin;mod=0;msg-id=resub;msg-param-cumulative-months=14;msg-param-months=0;msg-param-should-share-streak=1;msg-param-streak-months=9;msg-param-sub-plan-name=Channel\\sSubscription\\s(xqcow);msg-param-sub-plan=1000;room-id=71092938;subscriber=1;system-msg=Gutrin\\ssubscribed\\sat\\sTier\\s1.;tmi-sent-ts=1581713640019;user-id=21156217;user-type= :tmi.twitch.tv USERNOTICE #xqcow :xqcL";
+
+    fn usernotice(src: &str) -> UserNoticeMessage {
+        UserNoticeMessage::try_from(IRCMessage::parse(src).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_resub_keeps_user_message_and_months() {
+        let message = TwitchPlatform::convert_usernotice_message(usernotice(RESUB));
+        assert!(matches!(message.message_type, MessageType::Subscription));
+        assert_eq!(message.username, "gutrin");
+        assert_eq!(message.content, "xqcL");
+        assert_eq!(message.emotes.len(), 1);
+        assert_eq!(message.user_color.as_deref(), Some("#0000FF"));
+        let data = &message.metadata.custom_data;
+        assert_eq!(data["notice_type"], "resub");
+        assert_eq!(data["cumulative_months"], 14);
+        assert_eq!(data["streak_months"], 9);
+
+        let card = EventCard::from_message(&message).unwrap();
+        assert_eq!(card.headline, "14 months");
+        assert_eq!(card.detail.as_deref(), Some("9-month streak · Tier 1"));
+    }
+
+    #[tokio::test]
+    async fn test_resub_fields_survive_mapping() {
+        let message = TwitchPlatform::convert_usernotice_message(usernotice(RESUB));
+        let raw = RawPlatformMessage {
+            platform: "twitch".to_string(),
+            channel: message.channel.clone(),
+            raw_data: serde_json::to_value(&message).unwrap(),
+            timestamp: chrono::Utc::now(),
+            message_id: Some(message.id.clone()),
+        };
+
+        let mapped = MappingSystem::default().map_message(&raw).await.unwrap();
+        assert_eq!(mapped.message_type, MappedMessageType::Subscription);
+        assert_eq!(mapped.content, "xqcL");
+        assert_eq!(mapped.metadata.subscription_months, Some(14));
+        assert_eq!(mapped.metadata.streak_months, Some(9));
+
+        // Sin texto del usuario se muestra el mensaje de sistema
+        let plain_sub = RESUB
+            .replace("msg-id=resub", "msg-id=sub")
+            .replace(" :xqcL", "");
+        let message = TwitchPlatform::convert_usernotice_message(usernotice(&plain_sub));
+        assert_eq!(message.content, "Gutrin subscribed at Tier 1.");
+        assert_eq!(
+            EventCard::from_message(&message).unwrap().headline,
+            "New subscriber"
+        );
+    }
+
+    #[test]
+    fn test_clearchat_and_clearmsg_retract_messages() {
+        use crate::connection::Retraction;
+
+        let clearmsg = ClearMsgMessage::try_from(IRCMessage::parse("@login=ronni;room-id=;target-msg-id=abc-123-def;tmi-sent-ts=1642720582342 :tmi.twitch.tv CLEARMSG #dallas :HeyGuys").unwrap()).unwrap();
+        let deleted = TwitchPlatform::convert_clearmsg_message(clearmsg);
+        assert_eq!(deleted.channel, "dallas");
+        assert_eq!(
+            deleted.retraction(),
+            Some(Retraction::Message("abc-123-def".to_string()))
+        );
+
+        let timeout = ClearChatMessage::try_from(IRCMessage::parse("@ban-duration=350;room-id=11148817;target-user-id=148973258;tmi-sent-ts=1594553828245 :tmi.twitch.tv CLEARCHAT #pajlada :fabzeef").unwrap()).unwrap();
+        let notice = TwitchPlatform::convert_clearchat_message(timeout);
+        assert_eq!(notice.content, "fabzeef has been timed out");
+        assert_eq!(
+            notice.retraction(),
+            Some(Retraction::User("fabzeef".to_string()))
+        );
+
+        let cleared = ClearChatMessage::try_from(IRCMessage::parse("@room-id=40286300;tmi-sent-ts=1594561392337 :tmi.twitch.tv CLEARCHAT #randers").unwrap()).unwrap();
+        assert_eq!(
+            TwitchPlatform::convert_clearchat_message(cleared).retraction(),
+            Some(Retraction::Channel)
+        );
+    }
+}
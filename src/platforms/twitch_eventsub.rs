@@ -0,0 +1,667 @@
+//! EventSub de Twitch: canjes de puntos, follows, suscripciones y Hype Trains
+//!
+//! El chat IRC no trae estos eventos. Si hay `oauth_token` y `client_id`, cada
+//! canal abre además una sesión en `wss://eventsub.wss.twitch.tv/ws`: con el
+//! `session_id` del `session_welcome` se crean las suscripciones por Helix y
+//! cada `notification` se convierte en un `ChatMessage` con su `MessageType`.
+//!
+//! Los temas se eligen con `settings.custom_settings.eventsub` (por defecto
+//! canjes, follows y Hype Trains; las suscripciones ya llegan por IRC). El
+//! token debe ser del broadcaster o de un moderador con los scopes de cada
+//! tema; las suscripciones rechazadas se registran y el resto sigue.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::time::{Duration, SystemTime};
+
+use futures::StreamExt;
+use serde::Deserialize;
+use tokio::sync::mpsc;
+use tokio_tungstenite::tungstenite::Message;
+
+use crate::capture::PayloadCapture;
+use crate::config::{Credentials, PlatformSettings};
+use crate::connection::{ChatMessage, MessageMetadata, MessageType};
+use crate::health::ErrorBudget;
+use crate::platforms::twitch::TwitchError;
+
+pub const EVENTSUB_URL: &str = "wss://eventsub.wss.twitch.tv/ws";
+const HELIX_URL: &str = "https://api.twitch.tv/helix";
+/// Margen sobre `keepalive_timeout_seconds` antes de dar la sesión por muerta
+const KEEPALIVE_GRACE: Duration = Duration::from_secs(5);
+const DEFAULT_KEEPALIVE: Duration = Duration::from_secs(10);
+/// Twitch puede reenviar una notificación; se recuerdan los últimos ids
+const SEEN_MESSAGE_IDS: usize = 256;
+
+/// Clave en `custom_data` con el tipo de suscripción EventSub original
+pub const EVENTSUB_TYPE_KEY: &str = "eventsub_type";
+
+/// Token y `Client-Id` para Helix y EventSub
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EventSubAuth {
+    pub token: String,
+    pub client_id: String,
+}
+
+impl EventSubAuth {
+    /// `None` si faltan credenciales o el token es el de ejemplo
+    pub fn from_credentials(credentials: &Credentials) -> Option<Self> {
+        let token = credentials.oauth_token.as_deref()?;
+        let token = token.strip_prefix("oauth:").unwrap_or(token).trim();
+        let client_id = credentials.client_id.as_deref()?.trim();
+        if token.is_empty() || token == "YOUR_OAUTH_TOKEN_HERE" || client_id.is_empty() {
+            return None;
+        }
+        Some(Self {
+            token: token.to_string(),
+            client_id: client_id.to_string(),
+        })
+    }
+}
+
+/// Grupo de eventos que se puede activar en `custom_settings.eventsub`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EventSubTopic {
+    /// `channel:read:redemptions`
+    Redemptions,
+    /// `moderator:read:followers`
+    Follows,
+    /// `channel:read:subscriptions`
+    Subscriptions,
+    /// `channel:read:hype_train`
+    HypeTrain,
+}
+
+impl EventSubTopic {
+    pub const DEFAULT: [EventSubTopic; 3] = [
+        EventSubTopic::Redemptions,
+        EventSubTopic::Follows,
+        EventSubTopic::HypeTrain,
+    ];
+
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "redemptions" | "redemption" | "channel_points" => Some(EventSubTopic::Redemptions),
+            "follows" | "follow" => Some(EventSubTopic::Follows),
+            "subscriptions" | "subscription" | "subs" => Some(EventSubTopic::Subscriptions),
+            "hype_train" | "hypetrain" => Some(EventSubTopic::HypeTrain),
+            _ => None,
+        }
+    }
+
+    /// Temas de `custom_settings.eventsub`: una lista de nombres, o `false`
+    /// para no abrir EventSub; sin la clave se usan los temas por defecto
+    pub fn from_settings(settings: &PlatformSettings) -> Vec<Self> {
+        match settings.custom_settings.get("eventsub") {
+            None | Some(serde_json::Value::Bool(true)) => Self::DEFAULT.to_vec(),
+            Some(serde_json::Value::Array(names)) => {
+                let mut topics = Vec::new();
+                for name in names.iter().filter_map(|name| name.as_str()) {
+                    match Self::parse(name) {
+                        Some(topic) if !topics.contains(&topic) => topics.push(topic),
+                        Some(_) => {}
+                        None => eprintln!("⚠️ Unknown Twitch EventSub topic '{}'", name),
+                    }
+                }
+                topics
+            }
+            Some(_) => Vec::new(),
+        }
+    }
+
+    /// Tipos y versiones de suscripción EventSub del tema
+    fn subscriptions(&self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            EventSubTopic::Redemptions => {
+                &[("channel.channel_points_custom_reward_redemption.add", "1")]
+            }
+            EventSubTopic::Follows => &[("channel.follow", "2")],
+            EventSubTopic::Subscriptions => &[
+                ("channel.subscribe", "1"),
+                ("channel.subscription.message", "1"),
+            ],
+            EventSubTopic::HypeTrain => &[
+                ("channel.hype_train.begin", "2"),
+                ("channel.hype_train.end", "2"),
+            ],
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct EventSubFrame {
+    metadata: FrameMetadata,
+    #[serde(default)]
+    payload: serde_json::Value,
+}
+
+#[derive(Debug, Deserialize)]
+struct FrameMetadata {
+    message_id: String,
+    message_type: String,
+    #[serde(default)]
+    message_timestamp: Option<String>,
+    #[serde(default)]
+    subscription_type: Option<String>,
+}
+
+/// Convierte el `event` de una notificación en un mensaje de evento; `None`
+/// si el tipo de suscripción no se muestra
+pub fn convert_notification(
+    subscription_type: &str,
+    event: &serde_json::Value,
+    message_id: &str,
+    timestamp: SystemTime,
+) -> Option<ChatMessage> {
+    let text = |value: &serde_json::Value, key: &str| {
+        value
+            .get(key)
+            .and_then(|v| v.as_str())
+            .unwrap_or_default()
+            .to_string()
+    };
+    let number = |value: &serde_json::Value, key: &str| value.get(key).and_then(|v| v.as_u64());
+
+    let mut data: HashMap<String, serde_json::Value> = HashMap::new();
+    data.insert(EVENTSUB_TYPE_KEY.to_string(), subscription_type.into());
+    let user_id = text(event, "user_id");
+    if !user_id.is_empty() {
+        data.insert("user_id".to_string(), user_id.into());
+    }
+    data.insert(
+        "room_id".to_string(),
+        text(event, "broadcaster_user_id").into(),
+    );
+
+    let mut username = text(event, "user_login");
+    let mut display_name = text(event, "user_name");
+    let shown_name = if display_name.is_empty() {
+        username.clone()
+    } else {
+        display_name.clone()
+    };
+
+    let (message_type, content) = match subscription_type {
+        "channel.channel_points_custom_reward_redemption.add" => {
+            let reward = event.get("reward")?;
+            let title = text(reward, "title");
+            let input = text(event, "user_input");
+            data.insert("reward_id".to_string(), text(reward, "id").into());
+            data.insert("reward_title".to_string(), title.clone().into());
+            data.insert(
+                "reward_cost".to_string(),
+                number(reward, "cost").unwrap_or(0).into(),
+            );
+            let content = if input.trim().is_empty() {
+                title
+            } else {
+                format!("{}: {}", title, input)
+            };
+            data.insert("user_input".to_string(), input.into());
+            (MessageType::Redemption, content)
+        }
+        "channel.follow" => (
+            MessageType::Follow,
+            format!("{} is now following", shown_name),
+        ),
+        "channel.subscribe" => {
+            let is_gift = event
+                .get("is_gift")
+                .and_then(|v| v.as_bool())
+                .unwrap_or(false);
+            data.insert("is_resub".to_string(), false.into());
+            data.insert("cumulative_months".to_string(), 1.into());
+            data.insert("sub_plan".to_string(), text(event, "tier").into());
+            data.insert("gift_sub".to_string(), is_gift.into());
+            let content = if is_gift {
+                format!("{} received a gift sub", shown_name)
+            } else {
+                format!("{} subscribed", shown_name)
+            };
+            (MessageType::Subscription, content)
+        }
+        "channel.subscription.message" => {
+            let months = number(event, "cumulative_months").unwrap_or(1);
+            data.insert("is_resub".to_string(), true.into());
+            data.insert("cumulative_months".to_string(), months.into());
+            if let Some(streak) = number(event, "streak_months") {
+                data.insert("streak_months".to_string(), streak.into());
+            }
+            data.insert("sub_plan".to_string(), text(event, "tier").into());
+            let message = event
+                .get("message")
+                .map(|m| text(m, "text"))
+                .unwrap_or_default();
+            let content = if message.trim().is_empty() {
+                format!("{} resubscribed for {} months", shown_name, months)
+            } else {
+                message
+            };
+            (MessageType::Subscription, content)
+        }
+        "channel.hype_train.begin" | "channel.hype_train.end" => {
+            let level = number(event, "level").unwrap_or(1);
+            let ended = subscription_type.ends_with(".end");
+            data.insert(
+                "hype_train_phase".to_string(),
+                if ended { "end" } else { "begin" }.into(),
+            );
+            data.insert("hype_train_level".to_string(), level.into());
+            if let Some(total) = number(event, "total") {
+                data.insert("hype_train_total".to_string(), total.into());
+            }
+            if let Some(goal) = number(event, "goal") {
+                data.insert("hype_train_goal".to_string(), goal.into());
+            }
+            // El evento es del canal, no de un usuario
+            username = text(event, "broadcaster_user_login");
+            display_name = text(event, "broadcaster_user_name");
+            let content = if ended {
+                format!("Hype Train ended at level {}", level)
+            } else {
+                format!("Hype Train started! Level {}", level)
+            };
+            (MessageType::HypeTrain, content)
+        }
+        _ => return None,
+    };
+
+    let id = Some(text(event, "id"))
+        .filter(|id| !id.is_empty())
+        .unwrap_or_else(|| message_id.to_string());
+
+    Some(ChatMessage {
+        id,
+        platform: "twitch".to_string(),
+        channel: text(event, "broadcaster_user_login"),
+        username,
+        display_name: (!display_name.is_empty()).then_some(display_name),
+        content,
+        emotes: Vec::new(),
+        badges: Vec::new(),
+        timestamp,
+        user_color: None,
+        message_type,
+        metadata: MessageMetadata {
+            is_action: false,
+            is_whisper: false,
+            is_highlighted: true,
+            is_me_message: false,
+            reply_to: None,
+            thread_id: None,
+            custom_data: data,
+        },
+    })
+}
+
+fn helix_request(
+    http: &reqwest::Client,
+    auth: &EventSubAuth,
+    method: reqwest::Method,
+    path: &str,
+) -> reqwest::RequestBuilder {
+    http.request(method, format!("{}{}", HELIX_URL, path))
+        .bearer_auth(&auth.token)
+        .header("Client-Id", &auth.client_id)
+}
+
+/// Id del usuario `login`, o del dueño del token si es `None`
+async fn user_id(
+    http: &reqwest::Client,
+    auth: &EventSubAuth,
+    login: Option<&str>,
+) -> Result<String, TwitchError> {
+    let path = match login {
+        Some(login) => format!("/users?login={}", login),
+        None => "/users".to_string(),
+    };
+    let response = helix_request(http, auth, reqwest::Method::GET, &path)
+        .send()
+        .await
+        .map_err(|e| TwitchError::ConnectionError(e.to_string()))?;
+    if response.status() == reqwest::StatusCode::UNAUTHORIZED {
+        return Err(TwitchError::AuthError(
+            "Helix rejected the OAuth token".to_string(),
+        ));
+    }
+    let body: serde_json::Value = response
+        .error_for_status()
+        .map_err(|e| TwitchError::ConnectionError(e.to_string()))?
+        .json()
+        .await
+        .map_err(|e| TwitchError::ParseError(e.to_string()))?;
+
+    body.get("data")
+        .and_then(|data| data.get(0))
+        .and_then(|user| user.get("id"))
+        .and_then(|id| id.as_str())
+        .map(str::to_string)
+        .ok_or_else(|| {
+            TwitchError::ParseError(format!(
+                "Twitch user '{}' not found",
+                login.unwrap_or("<token>")
+            ))
+        })
+}
+
+/// Crea las suscripciones de los temas en la sesión; devuelve cuántas aceptó
+/// Twitch
+async fn subscribe(
+    http: &reqwest::Client,
+    auth: &EventSubAuth,
+    topics: &[EventSubTopic],
+    session_id: &str,
+    broadcaster_id: &str,
+    moderator_id: &str,
+) -> usize {
+    let mut accepted = 0;
+    for (subscription_type, version) in topics.iter().flat_map(|topic| topic.subscriptions()) {
+        let mut condition = serde_json::json!({ "broadcaster_user_id": broadcaster_id });
+        if *subscription_type == "channel.follow" {
+            condition["moderator_user_id"] = moderator_id.into();
+        }
+        let body = serde_json::json!({
+            "type": subscription_type,
+            "version": version,
+            "condition": condition,
+            "transport": { "method": "websocket", "session_id": session_id },
+        });
+
+        let result = helix_request(http, auth, reqwest::Method::POST, "/eventsub/subscriptions")
+            .json(&body)
+            .send()
+            .await;
+        match result {
+            Ok(response) if response.status().is_success() => accepted += 1,
+            Ok(response) => {
+                let status = response.status();
+                let detail = response.text().await.unwrap_or_default();
+                let error = format!(
+                    "EventSub subscription {} rejected ({}): {}",
+                    subscription_type, status, detail
+                );
+                eprintln!("⚠️ {}", error);
+                ErrorBudget::global().record_failure("platform:twitch", &error);
+            }
+            Err(e) => {
+                eprintln!(
+                    "⚠️ EventSub subscription {} failed: {}",
+                    subscription_type, e
+                );
+                ErrorBudget::global().record_failure("platform:twitch", &e.to_string());
+            }
+        }
+    }
+    accepted
+}
+
+/// Ids de notificaciones ya entregadas
+#[derive(Debug, Default)]
+struct SeenMessages {
+    ids: HashSet<String>,
+    order: VecDeque<String>,
+}
+
+impl SeenMessages {
+    /// `false` si el id ya se había visto
+    fn insert(&mut self, id: &str) -> bool {
+        if !self.ids.insert(id.to_string()) {
+            return false;
+        }
+        self.order.push_back(id.to_string());
+        if self.order.len() > SEEN_MESSAGE_IDS {
+            if let Some(oldest) = self.order.pop_front() {
+                self.ids.remove(&oldest);
+            }
+        }
+        true
+    }
+}
+
+/// Lee los eventos de un canal hasta que se cierre la sesión
+///
+/// Un `session_reconnect` abre la URL indicada sin volver a suscribirse (las
+/// suscripciones pasan a la sesión nueva).
+pub async fn read_events(
+    auth: EventSubAuth,
+    topics: Vec<EventSubTopic>,
+    channel: String,
+    sender: mpsc::UnboundedSender<ChatMessage>,
+) -> Result<(), TwitchError> {
+    let http = reqwest::Client::new();
+    let broadcaster_id = user_id(&http, &auth, Some(&channel)).await?;
+    let moderator_id = user_id(&http, &auth, None).await?;
+
+    let mut url = EVENTSUB_URL.to_string();
+    let mut subscribed = false;
+    let mut seen = SeenMessages::default();
+
+    'session: loop {
+        let (mut stream, _) = tokio_tungstenite::connect_async(url.as_str())
+            .await
+            .map_err(|e| TwitchError::ConnectionError(e.to_string()))?;
+        let mut keepalive = DEFAULT_KEEPALIVE;
+
+        loop {
+            let frame = match tokio::time::timeout(keepalive + KEEPALIVE_GRACE, stream.next()).await
+            {
+                Ok(frame) => frame,
+                Err(_) => {
+                    return Err(TwitchError::ConnectionError(
+                        "EventSub keepalive timed out".to_string(),
+                    ))
+                }
+            };
+            let text = match frame {
+                Some(Ok(Message::Text(text))) => text,
+                Some(Ok(Message::Close(_))) | None => return Ok(()),
+                Some(Ok(_)) => continue,
+                Some(Err(e)) => return Err(TwitchError::ConnectionError(e.to_string())),
+            };
+
+            let capture = PayloadCapture::global();
+            if capture.is_enabled() {
+                capture.record("twitch", &channel, &text);
+            }
+
+            let frame: EventSubFrame = match serde_json::from_str(&text) {
+                Ok(frame) => frame,
+                Err(e) => {
+                    eprintln!("⚠️ Ignoring Twitch EventSub frame: {}", e);
+                    ErrorBudget::global().record_failure("platform:twitch", &e.to_string());
+                    continue;
+                }
+            };
+            let session = &frame.payload["session"];
+
+            match frame.metadata.message_type.as_str() {
+                "session_welcome" => {
+                    if let Some(seconds) = session["keepalive_timeout_seconds"].as_u64() {
+                        keepalive = Duration::from_secs(seconds.max(1));
+                    }
+                    if !subscribed {
+                        let session_id = session["id"].as_str().unwrap_or_default();
+                        let accepted = subscribe(
+                            &http,
+                            &auth,
+                            &topics,
+                            session_id,
+                            &broadcaster_id,
+                            &moderator_id,
+                        )
+                        .await;
+                        if accepted == 0 {
+                            return Err(TwitchError::AuthError(format!(
+                                "No EventSub subscription accepted for '{}'",
+                                channel
+                            )));
+                        }
+                        println!(
+                            "🔔 Twitch EventSub: {} subscriptions for {}",
+                            accepted, channel
+                        );
+                        subscribed = true;
+                    }
+                }
+                "session_reconnect" => {
+                    if let Some(reconnect_url) = session["reconnect_url"].as_str() {
+                        url = reconnect_url.to_string();
+                        continue 'session;
+                    }
+                }
+                "notification" => {
+                    if !seen.insert(&frame.metadata.message_id) {
+                        continue;
+                    }
+                    let subscription_type = frame
+                        .metadata
+                        .subscription_type
+                        .as_deref()
+                        .unwrap_or_default();
+                    let timestamp = frame
+                        .metadata
+                        .message_timestamp
+                        .as_deref()
+                        .and_then(|ts| chrono::DateTime::parse_from_rfc3339(ts).ok())
+                        .map(SystemTime::from)
+                        .unwrap_or_else(SystemTime::now);
+                    if let Some(message) = convert_notification(
+                        subscription_type,
+                        &frame.payload["event"],
+                        &frame.metadata.message_id,
+                        timestamp,
+                    ) {
+                        if sender.send(message).is_err() {
+                            return Ok(());
+                        }
+                    }
+                }
+                "revocation" => {
+                    let error = format!(
+                        "EventSub subscription {} revoked: {}",
+                        frame.payload["subscription"]["type"]
+                            .as_str()
+                            .unwrap_or("?"),
+                        frame.payload["subscription"]["status"]
+                            .as_str()
+                            .unwrap_or("?")
+                    );
+                    eprintln!("⚠️ {}", error);
+                    ErrorBudget::global().record_failure("platform:twitch", &error);
+                }
+                // session_keepalive: solo renueva el plazo
+                _ => {}
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_notifications_become_event_messages() {
+        let redemption = serde_json::json!({
+            "id": "r-1",
+            "broadcaster_user_id": "1337",
+            "broadcaster_user_login": "cool_streamer",
+            "user_id": "42",
+            "user_login": "viewer",
+            "user_name": "Viewer",
+            "user_input": "play despacito",
+            "reward": { "id": "rw", "title": "Song request", "cost": 500 }
+        });
+        let message = convert_notification(
+            "channel.channel_points_custom_reward_redemption.add",
+            &redemption,
+            "m-1",
+            SystemTime::now(),
+        )
+        .unwrap();
+        assert!(matches!(message.message_type, MessageType::Redemption));
+        assert_eq!(message.id, "r-1");
+        assert_eq!(message.channel, "cool_streamer");
+        assert_eq!(message.content, "Song request: play despacito");
+        assert_eq!(message.metadata.custom_data["reward_cost"], 500);
+
+        let follow = serde_json::json!({
+            "broadcaster_user_login": "cool_streamer",
+            "user_login": "fan",
+            "user_name": "Fan",
+        });
+        let message =
+            convert_notification("channel.follow", &follow, "m-2", SystemTime::now()).unwrap();
+        assert!(matches!(message.message_type, MessageType::Follow));
+        assert_eq!(message.id, "m-2");
+        assert_eq!(message.content, "Fan is now following");
+
+        let hype = serde_json::json!({
+            "broadcaster_user_login": "cool_streamer",
+            "broadcaster_user_name": "Cool_Streamer",
+            "level": 3,
+            "total": 4200,
+        });
+        let message =
+            convert_notification("channel.hype_train.end", &hype, "m-3", SystemTime::now())
+                .unwrap();
+        assert!(matches!(message.message_type, MessageType::HypeTrain));
+        assert_eq!(message.username, "cool_streamer");
+        assert_eq!(message.metadata.custom_data["hype_train_phase"], "end");
+
+        let resub = serde_json::json!({
+            "broadcaster_user_login": "cool_streamer",
+            "user_login": "loyal",
+            "tier": "1000",
+            "cumulative_months": 12,
+            "message": { "text": "" }
+        });
+        let message = convert_notification(
+            "channel.subscription.message",
+            &resub,
+            "m-4",
+            SystemTime::now(),
+        )
+        .unwrap();
+        assert!(matches!(message.message_type, MessageType::Subscription));
+        assert_eq!(message.content, "loyal resubscribed for 12 months");
+
+        assert!(convert_notification("channel.ban", &follow, "m-5", SystemTime::now()).is_none());
+    }
+
+    #[test]
+    fn test_topics_and_auth_from_config() {
+        let mut settings = PlatformSettings::default();
+        assert_eq!(
+            EventSubTopic::from_settings(&settings),
+            EventSubTopic::DEFAULT.to_vec()
+        );
+        settings.custom_settings.insert(
+            "eventsub".to_string(),
+            serde_json::json!(["subs", "follow", "follows", "nope"]),
+        );
+        assert_eq!(
+            EventSubTopic::from_settings(&settings),
+            vec![EventSubTopic::Subscriptions, EventSubTopic::Follows]
+        );
+        settings
+            .custom_settings
+            .insert("eventsub".to_string(), false.into());
+        assert!(EventSubTopic::from_settings(&settings).is_empty());
+
+        let mut credentials = Credentials {
+            oauth_token: Some("oauth:abc123".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(EventSubAuth::from_credentials(&credentials), None);
+        credentials.client_id = Some("client".to_string());
+        assert_eq!(
+            EventSubAuth::from_credentials(&credentials),
+            Some(EventSubAuth {
+                token: "abc123".to_string(),
+                client_id: "client".to_string(),
+            })
+        );
+        credentials.oauth_token = Some("oauth:YOUR_OAUTH_TOKEN_HERE".to_string());
+        assert_eq!(EventSubAuth::from_credentials(&credentials), None);
+    }
+}
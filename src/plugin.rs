@@ -0,0 +1,135 @@
+//! API estable para extender el overlay desde otros crates (feature `plugin-api`)
+//!
+//! Reúne los tipos que necesita un crate externo para escribir
+//! transformaciones de mensajes y proveedores de emotes. Los objetos de trait
+//! [`BoxedTransform`] y [`BoxedEmoteProvider`] son los que aceptan
+//! `OverlayApp::register_transform` (o `MappingSystem::register_transform`) y
+//! `EmoteSystem::register_provider`.
+//!
+//! Lo que se exporta aquí solo cambia de forma incompatible junto con
+//! [`PLUGIN_API_VERSION`]. Los enums de error son `#[non_exhaustive]`, así
+//! que pueden ganar variantes sin romper a quien los recorre con `match`.
+//!
+//! ```ignore
+//! use overlay_native::plugin::{MappingError, MessageTransform, StandardizedMessage};
+//!
+//! struct Shout;
+//!
+//! impl MessageTransform for Shout {
+//!     fn transform(
+//!         &mut self,
+//!         mut message: StandardizedMessage,
+//!     ) -> Result<StandardizedMessage, MappingError> {
+//!         message.content = message.content.to_uppercase();
+//!         Ok(message)
+//!     }
+//!
+//!     fn name(&self) -> &str {
+//!         "shout"
+//!     }
+//! }
+//!
+//! app.register_transform(Box::new(Shout)).await;
+//! ```
+
+pub use crate::connection::{Badge, Emote, EmoteMetadata, EmoteSource, TextPosition};
+pub use crate::emotes::{EmoteData, EmoteError, EmoteProvider, EmoteSystem};
+pub use crate::mapping::{
+    MappedMessageType, MappingError, MappingSystem, MessageTransform, StandardizedMessage,
+    UserLevel,
+};
+
+/// Versión de la API de plugins; sube con cada cambio incompatible
+pub const PLUGIN_API_VERSION: u32 = 1;
+
+/// Transformación que se registra en [`MappingSystem`]
+pub type BoxedTransform = Box<dyn MessageTransform>;
+
+/// Proveedor de emotes que se registra en [`EmoteSystem`]
+pub type BoxedEmoteProvider = Box<dyn EmoteProvider>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mapping::RawPlatformMessage;
+
+    struct Shout;
+
+    impl MessageTransform for Shout {
+        fn transform(
+            &mut self,
+            mut message: StandardizedMessage,
+        ) -> Result<StandardizedMessage, MappingError> {
+            if message.content.is_empty() {
+                return Err(MappingError::ValidationError("empty message".to_string()));
+            }
+            message.content = message.content.to_uppercase();
+            Ok(message)
+        }
+
+        fn name(&self) -> &str {
+            "shout"
+        }
+    }
+
+    struct PluginEmotes;
+
+    #[async_trait::async_trait]
+    impl EmoteProvider for PluginEmotes {
+        async fn parse_emotes(&self, _: &str, _: &str) -> Result<Vec<Emote>, EmoteError> {
+            Ok(Vec::new())
+        }
+
+        async fn get_channel_emotes(&self, _: &str, _: &str) -> Result<Vec<EmoteData>, EmoteError> {
+            Err(EmoteError::ApiError("plugin offline".to_string()))
+        }
+
+        async fn get_global_emotes(&self) -> Result<Vec<EmoteData>, EmoteError> {
+            Ok(Vec::new())
+        }
+
+        fn provider_name(&self) -> &str {
+            "plugin"
+        }
+    }
+
+    fn raw(content: &str) -> RawPlatformMessage {
+        RawPlatformMessage {
+            platform: "twitch".to_string(),
+            channel: "foo".to_string(),
+            raw_data: serde_json::json!({ "username": "viewer", "content": content }),
+            timestamp: chrono::Utc::now(),
+            message_id: Some("msg-1".to_string()),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_registered_transform_runs_after_mapping() {
+        let mut mapping = MappingSystem::default();
+        let transform: BoxedTransform = Box::new(Shout);
+        mapping.register_transform(transform);
+        assert_eq!(mapping.transform_names(), vec!["shout"]);
+
+        let mapped = mapping.map_message(&raw("hola")).await.unwrap();
+        assert_eq!(mapped.content, "HOLA");
+        assert!(matches!(
+            mapping.map_message(&raw("")).await,
+            Err(MappingError::ValidationError(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_registered_provider_is_consulted() {
+        let mut config = crate::config::EmoteConfig::default();
+        config.enable_bttv = false;
+        config.enable_ffz = false;
+        config.enable_7tv = false;
+        let mut emotes = EmoteSystem::new(config);
+        let provider: BoxedEmoteProvider = Box::new(PluginEmotes);
+        emotes.register_provider("plugin".to_string(), provider);
+
+        let errors = emotes.refresh_channel("twitch", "foo").await;
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].0, "plugin");
+    }
+}
@@ -0,0 +1,238 @@
+//! Presets de pantalla y configuración inicial según el monitor
+//!
+//! En el primer arranque los tamaños (ventana, rejilla, márgenes, fuente) se
+//! calculan a partir de la resolución y el DPI detectados en lugar de usar
+//! valores fijos. `overlay-native init` permite elegir otro preset.
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::{Config, DisplayConfig};
+
+/// Resolución del monitor principal en píxeles físicos y su escala DPI
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MonitorInfo {
+    pub width: i32,
+    pub height: i32,
+    /// 1.0 = 96 ppp; 1.5 y 2.0 en pantallas escaladas al 150 % y 200 %
+    pub scale_factor: f64,
+}
+
+impl Default for MonitorInfo {
+    fn default() -> Self {
+        Self {
+            width: 1920,
+            height: 1080,
+            scale_factor: 1.0,
+        }
+    }
+}
+
+impl MonitorInfo {
+    /// Tamaño en píxeles lógicos, los que usan las ventanas para posicionarse
+    pub fn logical_size(&self) -> (i32, i32) {
+        let scale = if self.scale_factor > 0.0 {
+            self.scale_factor
+        } else {
+            1.0
+        };
+        (
+            (self.width as f64 / scale).round() as i32,
+            (self.height as f64 / scale).round() as i32,
+        )
+    }
+
+    /// Factor respecto a una pantalla lógica de 1080 líneas
+    fn size_factor(&self) -> f64 {
+        (self.logical_size().1 as f64 / 1080.0).clamp(0.6, 2.0)
+    }
+}
+
+/// Zona del monitor donde aparecen las ventanas de mensajes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DisplayArea {
+    /// Todo el monitor
+    #[default]
+    Full,
+    /// Tercio inferior derecho
+    Corner,
+    /// Franja derecha (un cuarto del ancho)
+    Side,
+    /// Tercio central
+    Center,
+}
+
+impl DisplayArea {
+    /// Posiciones de la rejilla de `grid_size`×`grid_size` dentro de la zona
+    /// Zona `(x, y, ancho, alto)` en el monitor; la esquina se ajusta para que
+    /// una ventana colocada en ella no se salga de la pantalla
+    pub fn bounds(&self, monitor: (i32, i32), margin: i32, window_size: i32) -> (i32, i32, i32, i32) {
+        let (width, height) = monitor;
+        let (x, y, w, h) = match self {
+            DisplayArea::Full => (0, 0, width, height),
+            DisplayArea::Corner => (width * 2 / 3, height * 2 / 3, width / 3, height / 3),
+            DisplayArea::Side => (width * 3 / 4, 0, width / 4, height),
+            DisplayArea::Center => (width / 3, height / 3, width / 3, height / 3),
+        };
+        // La zona nunca empuja la ventana fuera del monitor
+        let x = x.min(width - margin - window_size).max(0);
+        let y = y.min(height - margin - window_size).max(0);
+        (x, y, w, h)
+    }
+
+    pub fn grid_positions(
+        &self,
+        monitor: (i32, i32),
+        margin: i32,
+        window_size: i32,
+        grid_size: i32,
+    ) -> Vec<(i32, i32)> {
+        let (x, y, w, h) = self.bounds(monitor, margin, window_size);
+
+        let grid_size = grid_size.max(1);
+        let step_x = ((w - margin - window_size) / grid_size).max(0);
+        let step_y = ((h - margin - window_size) / grid_size).max(0);
+
+        let mut positions = Vec::new();
+        for column in 0..grid_size {
+            for row in 0..grid_size {
+                positions.push((x + column * step_x, y + row * step_y));
+            }
+        }
+        positions
+    }
+}
+
+/// Preset de pantalla seleccionable en `overlay-native init`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DisplayPreset {
+    /// Ventanas pequeñas en la esquina inferior derecha
+    MinimalCorner,
+    /// Columna de chat en el lateral derecho
+    #[default]
+    FullSideFeed,
+    /// Solo eventos (subs, raids, cheers) grandes en el centro
+    CenterAlertsOnly,
+}
+
+impl DisplayPreset {
+    pub const ALL: [DisplayPreset; 3] = [
+        DisplayPreset::MinimalCorner,
+        DisplayPreset::FullSideFeed,
+        DisplayPreset::CenterAlertsOnly,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            DisplayPreset::MinimalCorner => "minimal_corner",
+            DisplayPreset::FullSideFeed => "full_side_feed",
+            DisplayPreset::CenterAlertsOnly => "center_alerts_only",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            DisplayPreset::MinimalCorner => "Small messages in the bottom-right corner",
+            DisplayPreset::FullSideFeed => "Chat column along the right side",
+            DisplayPreset::CenterAlertsOnly => "Only subs, raids and cheers, large and centered",
+        }
+    }
+
+    /// Acepta el nombre con guiones o guiones bajos
+    pub fn from_name(name: &str) -> Option<Self> {
+        let name = name.trim().to_lowercase().replace('-', "_");
+        Self::ALL.into_iter().find(|preset| preset.name() == name)
+    }
+
+    /// Ajusta tamaños y zona de `display` al monitor
+    pub fn apply_to(&self, display: &mut DisplayConfig, monitor: &MonitorInfo) {
+        let factor = monitor.size_factor();
+        let scaled = |value: f64| (value * factor).round() as i32;
+        let (_, logical_height) = monitor.logical_size();
+
+        let (window_size, margin, font_size) = match self {
+            DisplayPreset::MinimalCorner => (scaled(160.0), scaled(24.0), 12.0),
+            DisplayPreset::FullSideFeed => (scaled(220.0), scaled(40.0), 14.0),
+            DisplayPreset::CenterAlertsOnly => (scaled(320.0), scaled(60.0), 20.0),
+        };
+        display.window_size = window_size;
+        display.monitor_margin = margin;
+        display.font_size = ((font_size * factor).round() as u32).max(11);
+        display.grid_size = match self {
+            DisplayPreset::MinimalCorner => 3,
+            DisplayPreset::FullSideFeed => (logical_height / window_size.max(1)).clamp(3, 10),
+            DisplayPreset::CenterAlertsOnly => 2,
+        };
+        display.area = match self {
+            DisplayPreset::MinimalCorner => DisplayArea::Corner,
+            DisplayPreset::FullSideFeed => DisplayArea::Side,
+            DisplayPreset::CenterAlertsOnly => DisplayArea::Center,
+        };
+        display.alerts_only = *self == DisplayPreset::CenterAlertsOnly;
+    }
+}
+
+/// Configuración por defecto con la pantalla ajustada al monitor y al preset
+pub fn initial_config(monitor: &MonitorInfo, preset: DisplayPreset) -> Config {
+    let mut config = Config::default();
+    preset.apply_to(&mut config.display, monitor);
+    config
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_presets_scale_with_resolution_and_dpi() {
+        let full_hd = initial_config(&MonitorInfo::default(), DisplayPreset::FullSideFeed);
+        let four_k = initial_config(
+            &MonitorInfo {
+                width: 3840,
+                height: 2160,
+                scale_factor: 1.0,
+            },
+            DisplayPreset::FullSideFeed,
+        );
+        assert_eq!(full_hd.display.window_size, 220);
+        assert_eq!(four_k.display.window_size, 440);
+        assert_eq!(four_k.display.font_size, 28);
+        assert_eq!(full_hd.display.area, DisplayArea::Side);
+
+        // 4K al 200 % ocupa lo mismo que 1080p en píxeles lógicos
+        let scaled_4k = initial_config(
+            &MonitorInfo {
+                width: 3840,
+                height: 2160,
+                scale_factor: 2.0,
+            },
+            DisplayPreset::FullSideFeed,
+        );
+        assert_eq!(scaled_4k.display.window_size, full_hd.display.window_size);
+
+        let alerts = initial_config(&MonitorInfo::default(), DisplayPreset::CenterAlertsOnly);
+        assert!(alerts.display.alerts_only);
+        assert_eq!(
+            DisplayPreset::from_name("center-alerts-only"),
+            Some(DisplayPreset::CenterAlertsOnly)
+        );
+    }
+
+    #[test]
+    fn test_grid_positions_stay_inside_area() {
+        let monitor = (1920, 1080);
+        let full = DisplayArea::Full.grid_positions(monitor, 40, 200, 4);
+        assert_eq!(full.len(), 16);
+        assert_eq!(full[0], (0, 0));
+        assert_eq!(full[15], (3 * 420, 3 * 210));
+
+        for (x, y) in DisplayArea::Side.grid_positions(monitor, 40, 200, 4) {
+            assert!(x >= 1440 && x + 200 <= 1920 - 40);
+            assert!(y >= 0 && y + 200 <= 1080);
+        }
+        for (x, y) in DisplayArea::Corner.grid_positions(monitor, 40, 200, 3) {
+            assert!(x >= 1280 && x + 200 <= 1920 - 40);
+            assert!(y >= 720 && y + 200 <= 1080);
+        }
+    }
+}
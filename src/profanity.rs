@@ -0,0 +1,426 @@
+//! Filtro de lenguaje ofensivo con reglas y listas de palabras externas
+//!
+//! A diferencia de `filters.blocked_words` (subcadena literal por conexión),
+//! `profanity` se aplica a todos los mensajes y admite palabras completas,
+//! expresiones regulares, normalización de leetspeak (`b4d` → `bad`),
+//! excepciones (`allowlist`) y listas cargadas desde ficheros. Cada
+//! coincidencia descarta el mensaje, se enmascara con `***` o se sustituye por
+//! un emoji.
+//!
+//! Formato de los ficheros de `word_lists`: una entrada por línea; las líneas
+//! vacías y las que empiezan por `#` se ignoran y el prefijo `re:` marca una
+//! expresión regular.
+
+use std::collections::HashSet;
+use std::ops::Range;
+use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock};
+
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+
+use crate::connection::ChatMessage;
+use crate::links::replace_content;
+use crate::moderation::DropReason;
+
+static GLOBAL_FILTER: OnceLock<ProfanityFilter> = OnceLock::new();
+
+/// Prefijo de las expresiones regulares en los ficheros de `word_lists`
+const REGEX_PREFIX: &str = "re:";
+
+/// Qué hacer con un mensaje que contiene lenguaje ofensivo
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ProfanityAction {
+    /// Descarta el mensaje (aparece en el carril de moderación)
+    Drop,
+    /// Sustituye cada coincidencia por `mask`
+    #[default]
+    Mask,
+    /// Sustituye cada coincidencia por `emoji`
+    ReplaceEmoji,
+}
+
+/// `profanity`
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct ProfanityConfig {
+    pub enabled: bool,
+    pub action: ProfanityAction,
+    /// Palabras completas, sin distinguir mayúsculas
+    pub words: Vec<String>,
+    /// Expresiones regulares; se evalúan sobre el texto en minúsculas y, con
+    /// `normalize_leetspeak`, ya normalizado
+    pub patterns: Vec<String>,
+    /// Ficheros con más palabras o expresiones; se leen al cargar la
+    /// configuración (rutas relativas al directorio de trabajo)
+    pub word_lists: Vec<PathBuf>,
+    /// Palabras que nunca se filtran aunque contengan una coincidencia
+    /// (p. ej. `scunthorpe`)
+    pub allowlist: Vec<String>,
+    /// Lee `4` como `a`, `3` como `e`, `$` como `s`... antes de comparar
+    pub normalize_leetspeak: bool,
+    pub mask: String,
+    pub emoji: String,
+}
+
+impl Default for ProfanityConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            action: ProfanityAction::Mask,
+            words: Vec::new(),
+            patterns: Vec::new(),
+            word_lists: Vec::new(),
+            allowlist: Vec::new(),
+            normalize_leetspeak: true,
+            mask: "***".to_string(),
+            emoji: "🤬".to_string(),
+        }
+    }
+}
+
+/// Letra que representa un carácter de leetspeak
+fn leet_letter(c: char) -> Option<char> {
+    Some(match c {
+        '0' => 'o',
+        '1' => 'i',
+        '3' => 'e',
+        '4' | '@' => 'a',
+        '5' | '$' => 's',
+        '7' => 't',
+        '8' => 'b',
+        '9' => 'g',
+        _ => return None,
+    })
+}
+
+/// Texto en minúsculas (y sin leetspeak) con el rango original de cada byte
+struct NormalizedText {
+    text: String,
+    /// Rango en bytes del texto original del que sale cada byte de `text`
+    origins: Vec<Range<usize>>,
+}
+
+impl NormalizedText {
+    fn new(original: &str, leetspeak: bool) -> Self {
+        let mut text = String::with_capacity(original.len());
+        let mut origins = Vec::with_capacity(original.len());
+        for (start, c) in original.char_indices() {
+            let origin = start..start + c.len_utf8();
+            let mut push = |normalized: char| {
+                text.push(normalized);
+                origins.extend(std::iter::repeat_n(origin.clone(), normalized.len_utf8()));
+            };
+            match leetspeak.then(|| leet_letter(c)).flatten() {
+                Some(letter) => push(letter),
+                None => c.to_lowercase().for_each(push),
+            }
+        }
+        Self { text, origins }
+    }
+
+    /// Rango del texto original que corresponde a `range` del normalizado
+    fn original_range(&self, range: &Range<usize>) -> Range<usize> {
+        self.origins[range.start].start..self.origins[range.end - 1].end
+    }
+
+    /// Palabra (caracteres alfanuméricos) que contiene `range`
+    fn enclosing_word(&self, range: &Range<usize>) -> &str {
+        let is_word = |c: char| c.is_alphanumeric() || c == '_';
+        let start = self.text[..range.start]
+            .char_indices()
+            .rev()
+            .find(|&(_, c)| !is_word(c))
+            .map_or(0, |(i, c)| i + c.len_utf8());
+        let end = self.text[range.end..]
+            .find(|c: char| !is_word(c))
+            .map_or(self.text.len(), |i| range.end + i);
+        &self.text[start..end]
+    }
+}
+
+fn normalize(text: &str, leetspeak: bool) -> String {
+    NormalizedText::new(text, leetspeak).text
+}
+
+/// Expresión que encuentra `word` completa; `\b` solo en los extremos que son
+/// letras, para admitir entradas como `f*ck`
+fn word_pattern(word: &str) -> String {
+    let boundary = |c: Option<char>| {
+        if c.is_some_and(|c| c.is_alphanumeric() || c == '_') {
+            r"\b"
+        } else {
+            ""
+        }
+    };
+    format!(
+        "{}{}{}",
+        boundary(word.chars().next()),
+        regex::escape(word),
+        boundary(word.chars().last())
+    )
+}
+
+fn compile(pattern: &str) -> Result<Regex, regex::Error> {
+    RegexBuilder::new(pattern).case_insensitive(true).build()
+}
+
+/// Reglas compiladas de una configuración
+#[derive(Debug)]
+struct ProfanityRules {
+    config: ProfanityConfig,
+    /// Todas las palabras en una sola alternancia
+    words: Option<Regex>,
+    patterns: Vec<Regex>,
+    allowlist: HashSet<String>,
+}
+
+impl ProfanityRules {
+    /// Compila las reglas; los ficheros ilegibles y las expresiones no
+    /// válidas de los ficheros se avisan en el log y se omiten
+    fn compile(config: ProfanityConfig) -> Self {
+        let leetspeak = config.normalize_leetspeak;
+        let mut words: Vec<String> = config.words.clone();
+        let mut patterns: Vec<String> = config.patterns.clone();
+
+        for path in &config.word_lists {
+            let contents = match std::fs::read_to_string(path) {
+                Ok(contents) => contents,
+                Err(e) => {
+                    eprintln!("[PROFANITY] ⚠️ Could not read word list {:?}: {}", path, e);
+                    continue;
+                }
+            };
+            for line in contents.lines().map(str::trim) {
+                if line.is_empty() || line.starts_with('#') {
+                    continue;
+                }
+                match line.strip_prefix(REGEX_PREFIX) {
+                    Some(pattern) => patterns.push(pattern.trim().to_string()),
+                    None => words.push(line.to_string()),
+                }
+            }
+        }
+
+        let mut alternatives: Vec<String> = words
+            .iter()
+            .map(|word| normalize(word.trim(), leetspeak))
+            .filter(|word| !word.is_empty())
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .collect();
+        // Las más largas primero para que la alternancia prefiera la coincidencia completa
+        alternatives.sort_by(|a, b| b.len().cmp(&a.len()).then_with(|| a.cmp(b)));
+        let words = (!alternatives.is_empty())
+            .then(|| {
+                let pattern = alternatives
+                    .iter()
+                    .map(|word| word_pattern(word))
+                    .collect::<Vec<_>>()
+                    .join("|");
+                compile(&pattern)
+            })
+            .and_then(|regex| {
+                regex
+                    .map_err(|e| eprintln!("[PROFANITY] ⚠️ Word list too large: {}", e))
+                    .ok()
+            });
+
+        let patterns = patterns
+            .iter()
+            .filter_map(|pattern| match compile(pattern) {
+                Ok(regex) => Some(regex),
+                Err(e) => {
+                    eprintln!(
+                        "[PROFANITY] ⚠️ Ignoring invalid pattern '{}': {}",
+                        pattern, e
+                    );
+                    None
+                }
+            })
+            .collect();
+
+        let allowlist = config
+            .allowlist
+            .iter()
+            .map(|word| normalize(word.trim(), leetspeak))
+            .collect();
+
+        Self {
+            config,
+            words,
+            patterns,
+            allowlist,
+        }
+    }
+
+    /// Rangos (en bytes del texto original) de las coincidencias, ordenados
+    /// y sin solapes
+    fn find(&self, content: &str) -> Vec<Range<usize>> {
+        let normalized = NormalizedText::new(content, self.config.normalize_leetspeak);
+        let mut found: Vec<Range<usize>> = self
+            .words
+            .iter()
+            .chain(&self.patterns)
+            .flat_map(|regex| regex.find_iter(&normalized.text))
+            .map(|m| m.range())
+            .filter(|range| !range.is_empty())
+            .filter(|range| {
+                !self.allowlist.contains(&normalized.text[range.clone()])
+                    && !self.allowlist.contains(normalized.enclosing_word(range))
+            })
+            .map(|range| normalized.original_range(&range))
+            .collect();
+
+        found.sort_by_key(|range| range.start);
+        let mut merged: Vec<Range<usize>> = Vec::with_capacity(found.len());
+        for range in found {
+            match merged.last_mut() {
+                Some(last) if range.start <= last.end => last.end = last.end.max(range.end),
+                _ => merged.push(range),
+            }
+        }
+        merged
+    }
+}
+
+/// Filtro global, reconfigurado al cargar o recargar la configuración
+#[derive(Debug)]
+pub struct ProfanityFilter {
+    rules: Mutex<ProfanityRules>,
+}
+
+impl ProfanityFilter {
+    pub fn new(config: ProfanityConfig) -> Self {
+        Self {
+            rules: Mutex::new(ProfanityRules::compile(config)),
+        }
+    }
+
+    pub fn global() -> &'static ProfanityFilter {
+        GLOBAL_FILTER.get_or_init(|| Self::new(ProfanityConfig::default()))
+    }
+
+    /// Compila las reglas y vuelve a leer los ficheros de `word_lists`
+    pub fn configure(&self, config: ProfanityConfig) {
+        let rules = ProfanityRules::compile(config);
+        *self.lock() = rules;
+    }
+
+    /// Aplica la acción configurada al mensaje
+    ///
+    /// Devuelve cuántas coincidencias se enmascararon o sustituyeron; con
+    /// `drop`, el motivo del descarte con la primera coincidencia.
+    pub fn apply(&self, message: &mut ChatMessage) -> Result<usize, DropReason> {
+        let rules = self.lock();
+        if !rules.config.enabled {
+            return Ok(0);
+        }
+        let found = rules.find(&message.content);
+        let replacement = match (found.first(), rules.config.action) {
+            (None, _) => return Ok(0),
+            (Some(first), ProfanityAction::Drop) => {
+                return Err(DropReason::Profanity(
+                    message.content[first.clone()].to_string(),
+                ))
+            }
+            (Some(_), ProfanityAction::Mask) => rules.config.mask.as_str(),
+            (Some(_), ProfanityAction::ReplaceEmoji) => rules.config.emoji.as_str(),
+        };
+
+        // De atrás hacia delante para que los rangos anteriores sigan siendo válidos
+        for range in found.iter().rev() {
+            replace_content(message, range.clone(), replacement);
+        }
+        Ok(found.len())
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, ProfanityRules> {
+        self.rules.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Primera expresión regular no válida de `patterns`, con su error
+pub fn invalid_pattern(config: &ProfanityConfig) -> Option<(&str, regex::Error)> {
+    config
+        .patterns
+        .iter()
+        .find_map(|pattern| compile(pattern).err().map(|e| (pattern.as_str(), e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::connection::{Emote, TextPosition};
+
+    fn filter(action: ProfanityAction) -> ProfanityFilter {
+        ProfanityFilter::new(ProfanityConfig {
+            enabled: true,
+            action,
+            words: vec!["darn".to_string(), "heck".to_string()],
+            patterns: vec![r"cr[a-z]*p".to_string()],
+            allowlist: vec!["crepe".to_string()],
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn test_mask_normalizes_leetspeak_and_respects_allowlist() {
+        let filter = filter(ProfanityAction::Mask);
+
+        let mut msg = ChatMessage::test("viewer", "D4RN it, what the H3CK Kappa");
+        msg.emotes.push(Emote {
+            id: "25".to_string(),
+            name: "Kappa".to_string(),
+            positions: vec![TextPosition { start: 23, end: 27 }],
+            ..Default::default()
+        });
+        assert_eq!(filter.apply(&mut msg), Ok(2));
+        assert_eq!(msg.content, "*** it, what the *** Kappa");
+        let pos = &msg.emotes[0].positions[0];
+        let chars: String = msg.content.chars().skip(pos.start).take(5).collect();
+        assert_eq!(chars, "Kappa");
+
+        let mut allowed = ChatMessage::test("viewer", "darned crepe, not crap");
+        assert_eq!(filter.apply(&mut allowed), Ok(1));
+        assert_eq!(allowed.content, "darned crepe, not ***");
+    }
+
+    #[test]
+    fn test_drop_and_emoji_actions() {
+        let mut msg = ChatMessage::test("viewer", "oh heck");
+        assert_eq!(
+            filter(ProfanityAction::Drop).apply(&mut msg),
+            Err(DropReason::Profanity("heck".to_string()))
+        );
+        assert_eq!(filter(ProfanityAction::ReplaceEmoji).apply(&mut msg), Ok(1));
+        assert_eq!(msg.content, "oh 🤬");
+
+        let disabled = ProfanityFilter::new(ProfanityConfig {
+            words: vec!["heck".to_string()],
+            ..Default::default()
+        });
+        let mut msg = ChatMessage::test("viewer", "oh heck");
+        assert_eq!(disabled.apply(&mut msg), Ok(0));
+    }
+
+    #[test]
+    fn test_word_lists_are_loaded_from_files() {
+        let path = std::env::temp_dir().join(format!(
+            "overlay-native-profanity-{}.txt",
+            std::process::id()
+        ));
+        std::fs::write(&path, "# lista de prueba\n\nfrick\nre:g+r+\n").unwrap();
+        let filter = ProfanityFilter::new(ProfanityConfig {
+            enabled: true,
+            word_lists: vec![path.clone(), PathBuf::from("/nonexistent/list.txt")],
+            ..Default::default()
+        });
+        std::fs::remove_file(&path).unwrap();
+
+        let mut msg = ChatMessage::test("viewer", "fr1ck, grrr");
+        assert_eq!(filter.apply(&mut msg), Ok(2));
+        assert_eq!(msg.content, "***, ***");
+    }
+}
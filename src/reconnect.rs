@@ -0,0 +1,416 @@
+//! Supervisión de conexiones: reconexión con espera exponencial
+//!
+//! La tarea de lectura de cada conexión detecta cuándo se cierra el flujo de
+//! mensajes de la plataforma (socket IRC de Twitch, Pusher de Kick...), vuelve
+//! a conectar con espera exponencial y jitter, se une de nuevo al canal y
+//! publica cada cambio de estado.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tokio::sync::{broadcast, mpsc};
+
+use crate::config::PlatformSettings;
+use crate::connection::{ChatMessage, PlatformError, StreamingPlatform};
+use crate::health::ErrorBudget;
+use crate::platforms::PlatformWrapperError;
+
+/// Espera máxima de un mensaje con la plataforma bloqueada
+const POLL_INTERVAL: Duration = Duration::from_millis(25);
+
+/// Plataforma compartida por las conexiones que la usan
+pub type SharedPlatform =
+    Arc<tokio::sync::Mutex<Box<dyn StreamingPlatform<Error = PlatformWrapperError> + Send + Sync>>>;
+
+/// Espera máxima entre dos intentos, por largo que sea el historial de fallos
+pub const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(60);
+
+/// Cuántas veces y con qué espera se reintenta una conexión caída
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReconnectPolicy {
+    /// Intentos seguidos antes de rendirse; 0 = sin límite
+    pub max_attempts: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+}
+
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        Self::from(&PlatformSettings::default())
+    }
+}
+
+impl From<&PlatformSettings> for ReconnectPolicy {
+    fn from(settings: &PlatformSettings) -> Self {
+        Self {
+            max_attempts: settings.max_reconnect_attempts,
+            base_delay: Duration::from_millis(settings.reconnect_delay_ms),
+            max_delay: MAX_RECONNECT_DELAY,
+        }
+    }
+}
+
+impl ReconnectPolicy {
+    /// Espera antes del intento `attempt` (desde 1): `base_delay * 2^(attempt-1)`
+    /// hasta `max_delay`, reducida por `jitter` (0..=1) hasta la mitad para que
+    /// las conexiones caídas a la vez no reintenten al unísono
+    pub fn delay_for(&self, attempt: u32, jitter: f64) -> Duration {
+        let exponent = attempt.saturating_sub(1).min(16);
+        let delay = self
+            .base_delay
+            .saturating_mul(1 << exponent)
+            .min(self.max_delay);
+        delay.mul_f64(1.0 - 0.5 * jitter.clamp(0.0, 1.0))
+    }
+
+    /// `delay_for` con jitter aleatorio
+    pub fn next_delay(&self, attempt: u32) -> Duration {
+        self.delay_for(attempt, rand::random::<f64>())
+    }
+
+    pub fn allows(&self, attempt: u32) -> bool {
+        self.max_attempts == 0 || attempt <= self.max_attempts
+    }
+}
+
+/// Estado de una conexión
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "state", rename_all = "snake_case")]
+pub enum ConnectionState {
+    Connecting,
+    Connected,
+    /// El flujo se cerró; el intento `attempt` empieza tras `delay_ms`
+    Reconnecting {
+        attempt: u32,
+        delay_ms: u64,
+    },
+    /// No se pudo conectar o se agotaron los intentos
+    Failed {
+        reason: String,
+    },
+    /// Detenida desde la API o al recargar la configuración
+    Stopped,
+}
+
+impl std::fmt::Display for ConnectionState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConnectionState::Connecting => f.write_str("connecting"),
+            ConnectionState::Connected => f.write_str("connected"),
+            ConnectionState::Reconnecting { attempt, delay_ms } => {
+                write!(f, "reconnecting (attempt {} in {} ms)", attempt, delay_ms)
+            }
+            ConnectionState::Failed { reason } => write!(f, "failed ({})", reason),
+            ConnectionState::Stopped => f.write_str("stopped"),
+        }
+    }
+}
+
+/// Cambio de estado de una conexión
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ConnectionStateChange {
+    pub connection_id: String,
+    pub platform: String,
+    pub channel: String,
+    #[serde(flatten)]
+    pub state: ConnectionState,
+}
+
+/// Último estado de cada conexión y difusión de sus cambios
+#[derive(Debug, Clone)]
+pub struct ConnectionStates {
+    states: Arc<Mutex<HashMap<String, ConnectionState>>>,
+    sender: broadcast::Sender<ConnectionStateChange>,
+}
+
+impl Default for ConnectionStates {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ConnectionStates {
+    pub fn new() -> Self {
+        let (sender, _) = broadcast::channel(64);
+        Self {
+            states: Arc::new(Mutex::new(HashMap::new())),
+            sender,
+        }
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<ConnectionStateChange> {
+        self.sender.subscribe()
+    }
+
+    pub fn get(&self, connection_id: &str) -> Option<ConnectionState> {
+        self.lock().get(connection_id).cloned()
+    }
+
+    pub fn set(&self, connection_id: &str, platform: &str, channel: &str, state: ConnectionState) {
+        if matches!(state, ConnectionState::Reconnecting { .. }) {
+            crate::stats::Stats::global().record_reconnect();
+        }
+        self.lock().insert(connection_id.to_string(), state.clone());
+        // Sin suscriptores el cambio solo queda en el mapa
+        let _ = self.sender.send(ConnectionStateChange {
+            connection_id: connection_id.to_string(),
+            platform: platform.to_string(),
+            channel: channel.to_string(),
+            state,
+        });
+    }
+
+    pub fn remove(&self, connection_id: &str) {
+        self.lock().remove(connection_id);
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, ConnectionState>> {
+        self.states.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Conexión cuya tarea de lectura se supervisa
+pub struct SupervisedConnection {
+    pub connection_id: String,
+    pub platform_name: String,
+    pub channel: String,
+    pub platform: SharedPlatform,
+    /// Reconexiones de la plataforma, compartido por todas sus conexiones
+    pub generation: Arc<AtomicU64>,
+    pub policy: ReconnectPolicy,
+}
+
+impl SupervisedConnection {
+    /// Reenvía mensajes a `sender` y reconecta cada vez que el flujo se cierra;
+    /// termina si el gestor deja de escuchar o se agotan los intentos
+    pub async fn run(self, sender: mpsc::UnboundedSender<ChatMessage>, states: ConnectionStates) {
+        let mut generation = self.generation.load(Ordering::SeqCst);
+        // Intentos fallidos seguidos; solo se reinicia al recibir un mensaje
+        let mut attempt = 0;
+        self.set_state(&states, ConnectionState::Connected);
+
+        loop {
+            // El lock solo se retiene durante `POLL_INTERVAL`: el filtrado de
+            // mensajes, `join_channel` y la API de control usan la misma
+            // plataforma. `next_message` no pierde mensajes al cancelarse
+            let polled = {
+                let mut platform = self.platform.lock().await;
+                tokio::time::timeout(POLL_INTERVAL, platform.next_message()).await
+            };
+            let Ok(message) = polled else {
+                continue;
+            };
+
+            if let Some(mut message) = message {
+                attempt = 0;
+                message.platform = self.platform_name.clone();
+                // Una plataforma con varios canales ya indica el suyo
+                if message.channel.is_empty() {
+                    message.channel = self.channel.clone();
+                }
+                if sender.send(message).is_err() {
+                    eprintln!("[DEBUG] Failed to send message, breaking loop");
+                    return;
+                }
+                continue;
+            }
+
+            eprintln!(
+                "[RECONNECT] ⚠️ {} lost its message stream ({}:{})",
+                self.connection_id, self.platform_name, self.channel
+            );
+            loop {
+                attempt += 1;
+                if !self.policy.allows(attempt) {
+                    let reason = format!("gave up after {} attempts", attempt - 1);
+                    eprintln!("[RECONNECT] ❌ {} {}", self.connection_id, reason);
+                    self.set_state(&states, ConnectionState::Failed { reason });
+                    return;
+                }
+
+                let delay = self.policy.next_delay(attempt);
+                self.set_state(
+                    &states,
+                    ConnectionState::Reconnecting {
+                        attempt,
+                        delay_ms: delay.as_millis() as u64,
+                    },
+                );
+                tokio::time::sleep(delay).await;
+
+                match self.rejoin(generation).await {
+                    Ok(current) => {
+                        generation = current;
+                        println!(
+                            "[RECONNECT] ✅ {} rejoined '{}' on attempt {}",
+                            self.connection_id, self.channel, attempt
+                        );
+                        self.set_state(&states, ConnectionState::Connected);
+                        break;
+                    }
+                    Err(e) => {
+                        eprintln!(
+                            "[RECONNECT] Attempt {} for {} failed: {}",
+                            attempt, self.connection_id, e
+                        );
+                        ErrorBudget::global().record_failure(
+                            &format!("platform:{}", self.platform_name.to_lowercase()),
+                            &e.to_string(),
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    /// Reconecta la plataforma salvo que otra conexión suya ya lo haya hecho
+    /// desde `generation`, y vuelve a unirse al canal; devuelve la generación actual
+    async fn rejoin(&self, generation: u64) -> Result<u64, PlatformError> {
+        let mut platform = self.platform.lock().await;
+        if self.generation.load(Ordering::SeqCst) == generation {
+            // La desconexión limpia el estado anterior; su error no impide reconectar
+            let _ = platform.disconnect().await;
+            platform.connect().await?;
+            self.generation.fetch_add(1, Ordering::SeqCst);
+        }
+        platform.join_channel(self.channel.clone()).await?;
+        Ok(self.generation.load(Ordering::SeqCst))
+    }
+
+    fn set_state(&self, states: &ConnectionStates, state: ConnectionState) {
+        states.set(
+            &self.connection_id,
+            &self.platform_name,
+            &self.channel,
+            state,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_delay_grows_exponentially_with_jitter_and_cap() {
+        let policy = ReconnectPolicy {
+            max_attempts: 5,
+            base_delay: Duration::from_millis(1000),
+            max_delay: Duration::from_secs(10),
+        };
+        assert_eq!(policy.delay_for(1, 0.0), Duration::from_millis(1000));
+        assert_eq!(policy.delay_for(3, 0.0), Duration::from_millis(4000));
+        assert_eq!(policy.delay_for(3, 1.0), Duration::from_millis(2000));
+        assert_eq!(policy.delay_for(10, 0.0), Duration::from_secs(10));
+        // Un historial enorme de intentos no desborda
+        assert_eq!(policy.delay_for(u32::MAX, 0.0), Duration::from_secs(10));
+
+        for _ in 0..20 {
+            let delay = policy.next_delay(2);
+            assert!(delay >= Duration::from_millis(1000) && delay <= Duration::from_millis(2000));
+        }
+
+        assert!(policy.allows(5));
+        assert!(!policy.allows(6));
+        let unlimited = ReconnectPolicy {
+            max_attempts: 0,
+            ..policy
+        };
+        assert!(unlimited.allows(1000));
+    }
+
+    #[test]
+    fn test_states_keep_latest_and_broadcast_changes() {
+        let states = ConnectionStates::new();
+        let mut changes = states.subscribe();
+        states.set("main", "twitch", "chan", ConnectionState::Connecting);
+        states.set(
+            "main",
+            "twitch",
+            "chan",
+            ConnectionState::Reconnecting {
+                attempt: 2,
+                delay_ms: 2000,
+            },
+        );
+
+        assert_eq!(
+            changes.try_recv().unwrap().state,
+            ConnectionState::Connecting
+        );
+        let change = changes.try_recv().unwrap();
+        let json = serde_json::to_value(&change).unwrap();
+        assert_eq!(json["connection_id"], "main");
+        assert_eq!(json["state"], "reconnecting");
+        assert_eq!(json["attempt"], 2);
+        assert_eq!(
+            states.get("main").unwrap().to_string(),
+            "reconnecting (attempt 2 in 2000 ms)"
+        );
+
+        states.remove("main");
+        assert!(states.get("main").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_ended_reader_moves_connection_to_reconnecting() {
+        use crate::config::{PlatformConfig, PlatformType};
+        use crate::platforms::CustomPlatform;
+
+        // Puerto sin nadie escuchando: la tarea de lectura termina al instante
+        let port = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let mut config = PlatformConfig {
+            platform_type: PlatformType::Custom,
+            ..Default::default()
+        };
+        config.settings.custom_settings.insert(
+            "url".to_string(),
+            serde_json::json!(format!("ws://127.0.0.1:{}/{{channel}}", port)),
+        );
+        let mut platform = CustomPlatform::new(config);
+        platform.connect().await.unwrap();
+        platform.join_channel("chan".to_string()).await.unwrap();
+
+        let connection = SupervisedConnection {
+            connection_id: "custom".to_string(),
+            platform_name: "custom".to_string(),
+            channel: "chan".to_string(),
+            platform: Arc::new(tokio::sync::Mutex::new(Box::new(platform))),
+            generation: Arc::new(AtomicU64::new(0)),
+            policy: ReconnectPolicy {
+                max_attempts: 1,
+                base_delay: Duration::from_secs(60),
+                max_delay: Duration::from_secs(60),
+            },
+        };
+        let states = ConnectionStates::new();
+        let mut changes = states.subscribe();
+        let (sender, _receiver) = mpsc::unbounded_channel();
+        let supervisor = tokio::spawn(connection.run(sender, states.clone()));
+
+        let reconnecting = tokio::time::timeout(Duration::from_secs(5), async {
+            loop {
+                let change = changes.recv().await.unwrap();
+                if matches!(change.state, ConnectionState::Reconnecting { .. }) {
+                    return change;
+                }
+            }
+        })
+        .await
+        .expect("the supervisor never noticed the reader ended");
+        assert_eq!(reconnecting.connection_id, "custom");
+        assert!(matches!(
+            states.get("custom"),
+            Some(ConnectionState::Reconnecting { attempt: 1, .. })
+        ));
+        supervisor.abort();
+    }
+}
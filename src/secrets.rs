@@ -0,0 +1,188 @@
+//! Secretos de las plataformas fuera del fichero de configuración
+//!
+//! Un campo de `credentials` con el valor `"keyring:<clave>"` se lee del
+//! almacén de secretos al inicializar la plataforma en lugar de guardarse en
+//! claro en `config.json`; `"keyring:"` sin clave usa `<plataforma>/<campo>`
+//! (p. ej. `twitch/oauth_token`). Con la feature `keyring` el almacén es el
+//! del sistema operativo (Keychain, Credential Manager o Secret Service);
+//! sin ella los marcadores fallan con un error que lo indica.
+//!
+//! Los secretos se guardan con `overlay-native keyring set <clave>`.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use crate::config::Credentials;
+
+/// Prefijo de los valores que se leen del almacén de secretos
+pub const KEYRING_PREFIX: &str = "keyring:";
+
+/// Servicio con el que se guardan las entradas en el almacén del sistema
+pub const KEYRING_SERVICE: &str = "overlay-native";
+
+#[derive(Debug, thiserror::Error)]
+pub enum SecretError {
+    #[error("keyring support is not compiled in (build with --features keyring)")]
+    Unavailable,
+    #[error("secret '{0}' not found in the keyring")]
+    NotFound(String),
+    #[error("keyring error: {0}")]
+    Backend(String),
+}
+
+/// Almacén de secretos por clave
+pub trait SecretStore: Send + Sync {
+    fn store(&self, key: &str, secret: &str) -> Result<(), SecretError>;
+    fn get(&self, key: &str) -> Result<String, SecretError>;
+    /// `false` si la clave no existía
+    fn remove(&self, key: &str) -> Result<bool, SecretError>;
+}
+
+/// Almacén de secretos del sistema operativo
+#[derive(Debug, Clone, Copy, Default)]
+pub struct OsKeyring;
+
+#[cfg(feature = "keyring")]
+impl OsKeyring {
+    fn entry(key: &str) -> Result<::keyring::Entry, SecretError> {
+        ::keyring::Entry::new(KEYRING_SERVICE, key).map_err(|e| SecretError::Backend(e.to_string()))
+    }
+}
+
+#[cfg(feature = "keyring")]
+impl SecretStore for OsKeyring {
+    fn store(&self, key: &str, secret: &str) -> Result<(), SecretError> {
+        Self::entry(key)?
+            .set_password(secret)
+            .map_err(|e| SecretError::Backend(e.to_string()))
+    }
+
+    fn get(&self, key: &str) -> Result<String, SecretError> {
+        match Self::entry(key)?.get_password() {
+            Ok(secret) => Ok(secret),
+            Err(::keyring::Error::NoEntry) => Err(SecretError::NotFound(key.to_string())),
+            Err(e) => Err(SecretError::Backend(e.to_string())),
+        }
+    }
+
+    fn remove(&self, key: &str) -> Result<bool, SecretError> {
+        match Self::entry(key)?.delete_credential() {
+            Ok(()) => Ok(true),
+            Err(::keyring::Error::NoEntry) => Ok(false),
+            Err(e) => Err(SecretError::Backend(e.to_string())),
+        }
+    }
+}
+
+#[cfg(not(feature = "keyring"))]
+impl SecretStore for OsKeyring {
+    fn store(&self, _key: &str, _secret: &str) -> Result<(), SecretError> {
+        Err(SecretError::Unavailable)
+    }
+
+    fn get(&self, _key: &str) -> Result<String, SecretError> {
+        Err(SecretError::Unavailable)
+    }
+
+    fn remove(&self, _key: &str) -> Result<bool, SecretError> {
+        Err(SecretError::Unavailable)
+    }
+}
+
+/// Almacén en memoria, para pruebas e integraciones que gestionan sus secretos
+#[derive(Debug, Default)]
+pub struct MemorySecretStore {
+    secrets: Mutex<HashMap<String, String>>,
+}
+
+impl MemorySecretStore {
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, String>> {
+        self.secrets.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl SecretStore for MemorySecretStore {
+    fn store(&self, key: &str, secret: &str) -> Result<(), SecretError> {
+        self.lock().insert(key.to_string(), secret.to_string());
+        Ok(())
+    }
+
+    fn get(&self, key: &str) -> Result<String, SecretError> {
+        self.lock()
+            .get(key)
+            .cloned()
+            .ok_or_else(|| SecretError::NotFound(key.to_string()))
+    }
+
+    fn remove(&self, key: &str) -> Result<bool, SecretError> {
+        Ok(self.lock().remove(key).is_some())
+    }
+}
+
+/// Clave del almacén si `value` es un marcador `keyring:`
+pub fn keyring_key(value: &str, default_key: impl FnOnce() -> String) -> Option<String> {
+    let key = value.strip_prefix(KEYRING_PREFIX)?.trim();
+    Some(if key.is_empty() {
+        default_key()
+    } else {
+        key.to_string()
+    })
+}
+
+/// Sustituye los marcadores `keyring:` de `credentials` por los secretos
+pub fn resolve_credentials(
+    store: &dyn SecretStore,
+    platform: &str,
+    credentials: &mut Credentials,
+) -> Result<(), SecretError> {
+    for (field, value) in [
+        ("username", &mut credentials.username),
+        ("oauth_token", &mut credentials.oauth_token),
+        ("api_key", &mut credentials.api_key),
+        ("client_id", &mut credentials.client_id),
+        ("client_secret", &mut credentials.client_secret),
+    ] {
+        let Some(current) = value.as_deref() else {
+            continue;
+        };
+        if let Some(key) = keyring_key(current, || format!("{}/{}", platform, field)) {
+            *value = Some(store.get(&key)?);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_placeholders_are_resolved_from_the_store() {
+        let store = MemorySecretStore::default();
+        store.store("twitch/oauth_token", "oauth:secret").unwrap();
+        store.store("shared-client", "abc").unwrap();
+
+        let mut credentials = Credentials {
+            username: Some("streamer".to_string()),
+            oauth_token: Some("keyring:".to_string()),
+            client_id: Some("keyring:shared-client".to_string()),
+            ..Credentials::default()
+        };
+        resolve_credentials(&store, "twitch", &mut credentials).unwrap();
+        assert_eq!(credentials.username.as_deref(), Some("streamer"));
+        assert_eq!(credentials.oauth_token.as_deref(), Some("oauth:secret"));
+        assert_eq!(credentials.client_id.as_deref(), Some("abc"));
+
+        let mut missing = Credentials {
+            client_secret: Some("keyring:".to_string()),
+            ..Credentials::default()
+        };
+        assert!(matches!(
+            resolve_credentials(&store, "trovo", &mut missing),
+            Err(SecretError::NotFound(key)) if key == "trovo/client_secret"
+        ));
+
+        assert!(store.remove("shared-client").unwrap());
+        assert!(!store.remove("shared-client").unwrap());
+    }
+}
@@ -0,0 +1,249 @@
+//! Sonidos de alerta por tipo de evento
+//!
+//! Con `sound.enabled`, cada suscripción, raid, cheer o mensaje destacado que
+//! abre ventana reproduce el fichero configurado para su tipo (WAV, OGG, FLAC
+//! o MP3). Las conexiones con `mute_sounds` no suenan. La reproducción usa
+//! rodio en un hilo propio y necesita la feature `sound`; sin ella se avisa
+//! una vez en el log y no suena nada.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock};
+
+use serde::{Deserialize, Serialize};
+
+use crate::connection::{ChatMessage, MessageType};
+
+static GLOBAL_PLAYER: OnceLock<SoundPlayer> = OnceLock::new();
+
+/// Tipo de evento con sonido propio
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SoundEvent {
+    Subscription,
+    Raid,
+    Cheer,
+    /// Mensajes destacados (primeras participaciones, menciones, Highlight)
+    Highlighted,
+}
+
+impl SoundEvent {
+    pub fn of(message: &ChatMessage) -> Option<Self> {
+        match message.message_type {
+            MessageType::Subscription => Some(SoundEvent::Subscription),
+            MessageType::Raid => Some(SoundEvent::Raid),
+            MessageType::Cheer => Some(SoundEvent::Cheer),
+            // Los mensajes del propio overlay también van marcados como destacados
+            _ if message.metadata.is_highlighted
+                && message.platform != crate::system_users::SYSTEM_PLATFORM =>
+            {
+                Some(SoundEvent::Highlighted)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Fichero de un tipo de evento
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct SoundFile {
+    pub path: PathBuf,
+    /// Volumen propio (0.0 - 1.0); se multiplica por `sound.volume`
+    #[serde(default)]
+    pub volume: Option<f32>,
+}
+
+/// `sound`
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct SoundConfig {
+    /// Desactivado por defecto
+    pub enabled: bool,
+    /// Volumen general (0.0 - 1.0)
+    pub volume: f32,
+    pub sounds: HashMap<SoundEvent, SoundFile>,
+}
+
+impl Default for SoundConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            volume: 0.8,
+            sounds: HashMap::new(),
+        }
+    }
+}
+
+impl SoundConfig {
+    /// Fichero y volumen final del sonido de un mensaje
+    pub fn sound_for(&self, message: &ChatMessage) -> Option<(PathBuf, f32)> {
+        if !self.enabled {
+            return None;
+        }
+        let file = self.sounds.get(&SoundEvent::of(message)?)?;
+        let volume = (self.volume * file.volume.unwrap_or(1.0)).clamp(0.0, 1.0);
+        (volume > 0.0).then(|| (file.path.clone(), volume))
+    }
+}
+
+#[derive(Debug, Default)]
+struct PlayerState {
+    config: SoundConfig,
+    sender: Option<std::sync::mpsc::Sender<(PathBuf, f32)>>,
+    warned: bool,
+}
+
+/// Reproductor compartido; el dispositivo de audio vive en su propio hilo
+#[derive(Debug, Default)]
+pub struct SoundPlayer {
+    state: Mutex<PlayerState>,
+}
+
+impl SoundPlayer {
+    pub fn global() -> &'static SoundPlayer {
+        GLOBAL_PLAYER.get_or_init(SoundPlayer::default)
+    }
+
+    pub fn configure(&self, config: SoundConfig) {
+        self.lock().config = config;
+    }
+
+    /// Reproduce el sonido del mensaje si tiene; `muted` para las conexiones
+    /// con `mute_sounds`
+    pub fn play_for(&self, message: &ChatMessage, muted: bool) {
+        if muted {
+            return;
+        }
+        let mut state = self.lock();
+        let Some((path, volume)) = state.config.sound_for(message) else {
+            return;
+        };
+        if state.sender.is_none() {
+            state.sender = spawn_output();
+        }
+        match &state.sender {
+            // El hilo terminó (sin dispositivo): se reintenta en el siguiente
+            Some(sender) if sender.send((path, volume)).is_err() => state.sender = None,
+            Some(_) => {}
+            None if !state.warned => {
+                state.warned = true;
+                eprintln!("⚠️ Sound alerts need the `sound` feature (build with --features sound)");
+            }
+            None => {}
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, PlayerState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Hilo con la salida de audio por defecto; `None` si no hay
+#[cfg(feature = "sound")]
+fn spawn_output() -> Option<std::sync::mpsc::Sender<(PathBuf, f32)>> {
+    let (sender, receiver) = std::sync::mpsc::channel::<(PathBuf, f32)>();
+    std::thread::Builder::new()
+        .name("sound".to_string())
+        .spawn(move || {
+            // `OutputStream` no es Send: se crea y se queda en este hilo
+            let (_stream, handle) = match rodio::OutputStream::try_default() {
+                Ok(output) => output,
+                Err(e) => {
+                    eprintln!("⚠️ No audio output device: {}", e);
+                    return;
+                }
+            };
+            for (path, volume) in receiver {
+                let source = std::fs::File::open(&path)
+                    .map_err(|e| e.to_string())
+                    .and_then(|file| {
+                        rodio::Decoder::new(std::io::BufReader::new(file))
+                            .map_err(|e| e.to_string())
+                    });
+                let sink = rodio::Sink::try_new(&handle).map_err(|e| e.to_string());
+                match (source, sink) {
+                    (Ok(source), Ok(sink)) => {
+                        sink.set_volume(volume);
+                        sink.append(source);
+                        // Sigue sonando sin bloquear el siguiente
+                        sink.detach();
+                    }
+                    (Err(e), _) | (_, Err(e)) => {
+                        eprintln!("⚠️ Failed to play {}: {}", path.display(), e);
+                    }
+                }
+            }
+        })
+        .ok()?;
+    Some(sender)
+}
+
+#[cfg(not(feature = "sound"))]
+fn spawn_output() -> Option<std::sync::mpsc::Sender<(PathBuf, f32)>> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sound_for_event_types_and_volume() {
+        let mut config = SoundConfig {
+            enabled: true,
+            volume: 0.5,
+            ..SoundConfig::default()
+        };
+        config.sounds.insert(
+            SoundEvent::Raid,
+            SoundFile {
+                path: PathBuf::from("raid.ogg"),
+                volume: Some(0.5),
+            },
+        );
+        config.sounds.insert(
+            SoundEvent::Highlighted,
+            SoundFile {
+                path: PathBuf::from("ding.wav"),
+                volume: None,
+            },
+        );
+
+        assert_eq!(
+            config.sound_for(
+                &ChatMessage::test("viewer", "event")
+                    .with_type(MessageType::Raid)
+                    .highlighted(true)
+            ),
+            Some((PathBuf::from("raid.ogg"), 0.25))
+        );
+        assert_eq!(
+            config.sound_for(&ChatMessage::test("viewer", "event").highlighted(true)),
+            Some((PathBuf::from("ding.wav"), 0.5))
+        );
+        assert_eq!(
+            config.sound_for(&ChatMessage::test("viewer", "event")),
+            None
+        );
+        // Sin fichero para cheers
+        assert_eq!(
+            config.sound_for(
+                &ChatMessage::test("viewer", "event")
+                    .with_type(MessageType::Cheer)
+                    .highlighted(true)
+            ),
+            None
+        );
+
+        let system = ChatMessage::test("viewer", "event")
+            .highlighted(true)
+            .on(crate::system_users::SYSTEM_PLATFORM, "");
+        assert_eq!(config.sound_for(&system), None);
+
+        config.enabled = false;
+        assert_eq!(
+            config.sound_for(&ChatMessage::test("viewer", "event").with_type(MessageType::Raid)),
+            None
+        );
+    }
+}
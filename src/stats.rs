@@ -0,0 +1,302 @@
+//! Estadísticas de funcionamiento
+//!
+//! Contadores acumulados desde el arranque: mensajes recibidos por plataforma
+//! y canal, ventanas abiertas, aciertos y fallos de la caché de emotes y
+//! reconexiones. Se resumen en el log cada `summary_interval_secs` y la API
+//! de control los publica en el formato de texto de Prometheus en
+//! `GET /metrics`, para vigilar overlays que funcionan 24/7.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+static GLOBAL_STATS: OnceLock<Stats> = OnceLock::new();
+
+/// `stats`
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct StatsConfig {
+    /// Segundos entre resúmenes en el log; 0 los desactiva
+    pub summary_interval_secs: u64,
+}
+
+impl Default for StatsConfig {
+    fn default() -> Self {
+        Self {
+            summary_interval_secs: 600,
+        }
+    }
+}
+
+impl StatsConfig {
+    pub fn summary_interval(&self) -> Option<Duration> {
+        (self.summary_interval_secs > 0).then(|| Duration::from_secs(self.summary_interval_secs))
+    }
+}
+
+/// Mensajes recibidos de un canal
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ChannelCount {
+    pub platform: String,
+    pub channel: String,
+    pub messages: u64,
+}
+
+/// Copia de los contadores en un momento dado
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct StatsSnapshot {
+    pub uptime_secs: u64,
+    /// Ordenados por plataforma y canal
+    pub messages: Vec<ChannelCount>,
+    pub windows_spawned: u64,
+    pub emote_cache_hits: u64,
+    pub emote_cache_misses: u64,
+    pub reconnects: u64,
+}
+
+impl StatsSnapshot {
+    pub fn total_messages(&self) -> u64 {
+        self.messages.iter().map(|count| count.messages).sum()
+    }
+
+    /// Línea para el log
+    pub fn summary(&self) -> String {
+        let channels: Vec<String> = self
+            .messages
+            .iter()
+            .map(|count| format!("{}:{} {}", count.platform, count.channel, count.messages))
+            .collect();
+        let lookups = self.emote_cache_hits + self.emote_cache_misses;
+        let hit_rate = if lookups == 0 {
+            0.0
+        } else {
+            self.emote_cache_hits as f64 * 100.0 / lookups as f64
+        };
+        format!(
+            "📊 Stats after {}h{:02}m: {} messages{}, {} windows, emote cache {:.0}% hits ({} lookups), {} reconnects",
+            self.uptime_secs / 3600,
+            self.uptime_secs / 60 % 60,
+            self.total_messages(),
+            if channels.is_empty() {
+                String::new()
+            } else {
+                format!(" ({})", channels.join(", "))
+            },
+            self.windows_spawned,
+            hit_rate,
+            lookups,
+            self.reconnects
+        )
+    }
+
+    /// Formato de exposición de texto de Prometheus
+    pub fn to_prometheus(&self) -> String {
+        let mut out = String::new();
+        metric_header(
+            &mut out,
+            "overlay_uptime_seconds",
+            "gauge",
+            "Seconds since the overlay started",
+        );
+        let _ = writeln!(out, "overlay_uptime_seconds {}", self.uptime_secs);
+
+        metric_header(
+            &mut out,
+            "overlay_messages_total",
+            "counter",
+            "Chat messages received",
+        );
+        for count in &self.messages {
+            let _ = writeln!(
+                out,
+                "overlay_messages_total{{platform=\"{}\",channel=\"{}\"}} {}",
+                escape_label(&count.platform),
+                escape_label(&count.channel),
+                count.messages
+            );
+        }
+
+        for (name, help, value) in [
+            (
+                "overlay_windows_spawned_total",
+                "Overlay windows shown",
+                self.windows_spawned,
+            ),
+            (
+                "overlay_emote_cache_hits_total",
+                "Emote cache hits",
+                self.emote_cache_hits,
+            ),
+            (
+                "overlay_emote_cache_misses_total",
+                "Emote cache misses",
+                self.emote_cache_misses,
+            ),
+            (
+                "overlay_reconnects_total",
+                "Connection reconnect attempts",
+                self.reconnects,
+            ),
+        ] {
+            metric_header(&mut out, name, "counter", help);
+            let _ = writeln!(out, "{} {}", name, value);
+        }
+        out
+    }
+}
+
+fn metric_header(out: &mut String, name: &str, kind: &str, help: &str) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} {}", name, kind);
+}
+
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[derive(Debug)]
+struct StatsState {
+    started: Instant,
+    /// (plataforma, canal) -> mensajes
+    messages: HashMap<(String, String), u64>,
+    windows_spawned: u64,
+    emote_cache_hits: u64,
+    emote_cache_misses: u64,
+    reconnects: u64,
+}
+
+/// Contadores compartidos por todo el proceso
+#[derive(Debug)]
+pub struct Stats {
+    state: Mutex<StatsState>,
+}
+
+impl Default for Stats {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Stats {
+    pub fn new() -> Self {
+        Self {
+            state: Mutex::new(StatsState {
+                started: Instant::now(),
+                messages: HashMap::new(),
+                windows_spawned: 0,
+                emote_cache_hits: 0,
+                emote_cache_misses: 0,
+                reconnects: 0,
+            }),
+        }
+    }
+
+    pub fn global() -> &'static Stats {
+        GLOBAL_STATS.get_or_init(Self::new)
+    }
+
+    pub fn record_message(&self, platform: &str, channel: &str) {
+        *self
+            .lock()
+            .messages
+            .entry((platform.to_string(), channel.to_string()))
+            .or_insert(0) += 1;
+    }
+
+    pub fn record_window(&self) {
+        self.lock().windows_spawned += 1;
+    }
+
+    pub fn record_emote_cache(&self, hit: bool) {
+        let mut state = self.lock();
+        if hit {
+            state.emote_cache_hits += 1;
+        } else {
+            state.emote_cache_misses += 1;
+        }
+    }
+
+    pub fn record_reconnect(&self) {
+        self.lock().reconnects += 1;
+    }
+
+    pub fn snapshot(&self) -> StatsSnapshot {
+        let state = self.lock();
+        let messages: BTreeMap<_, _> = state.messages.iter().collect();
+        StatsSnapshot {
+            uptime_secs: state.started.elapsed().as_secs(),
+            messages: messages
+                .into_iter()
+                .map(|((platform, channel), messages)| ChannelCount {
+                    platform: platform.clone(),
+                    channel: channel.clone(),
+                    messages: *messages,
+                })
+                .collect(),
+            windows_spawned: state.windows_spawned,
+            emote_cache_hits: state.emote_cache_hits,
+            emote_cache_misses: state.emote_cache_misses,
+            reconnects: state.reconnects,
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, StatsState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_counters_and_summary() {
+        let stats = Stats::new();
+        stats.record_message("twitch", "foo");
+        stats.record_message("twitch", "foo");
+        stats.record_message("kick", "bar");
+        stats.record_window();
+        stats.record_emote_cache(true);
+        stats.record_emote_cache(true);
+        stats.record_emote_cache(true);
+        stats.record_emote_cache(false);
+        stats.record_reconnect();
+
+        let snapshot = stats.snapshot();
+        assert_eq!(snapshot.total_messages(), 3);
+        assert_eq!(snapshot.messages[0].platform, "kick");
+        assert_eq!(snapshot.messages[1].messages, 2);
+        let summary = snapshot.summary();
+        assert!(
+            summary.contains("3 messages (kick:bar 1, twitch:foo 2)"),
+            "{}",
+            summary
+        );
+        assert!(
+            summary.contains("emote cache 75% hits (4 lookups), 1 reconnects"),
+            "{}",
+            summary
+        );
+    }
+
+    #[test]
+    fn test_prometheus_exposition() {
+        let stats = Stats::new();
+        stats.record_message("twitch", "we\"ird");
+        stats.record_window();
+
+        let text = stats.snapshot().to_prometheus();
+        assert!(text.contains("# TYPE overlay_messages_total counter\n"));
+        assert!(
+            text.contains("overlay_messages_total{platform=\"twitch\",channel=\"we\\\"ird\"} 1\n")
+        );
+        assert!(text.contains("overlay_windows_spawned_total 1\n"));
+        assert!(text.contains("overlay_reconnects_total 0\n"));
+    }
+}
@@ -0,0 +1,569 @@
+//! Archivo del chat en SQLite
+//!
+//! Con `storage.enabled`, cada mensaje mapeado que supera el pipeline se guarda
+//! en `storage.path` con índices por plataforma y canal, usuario y fecha, para
+//! revisar o exportar el chat después del directo con cualquier cliente de
+//! SQLite. Las escrituras van en un hilo propio y necesitan la feature
+//! `storage`; sin ella se avisa una vez en el log y no se guarda nada.
+//!
+//! [`export`] vuelca un intervalo del archivo a JSON o CSV, con el nivel de
+//! usuario y los nombres de los emotes (`overlay-native export`).
+
+use std::borrow::Cow;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::mapping::MappedMessage;
+
+static GLOBAL_STORE: OnceLock<MessageStore> = OnceLock::new();
+
+/// Tabla `messages`; `data` guarda el [`MappedMessage`] completo en JSON
+#[cfg(feature = "storage")]
+pub const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS messages (
+    id TEXT PRIMARY KEY,
+    platform TEXT NOT NULL,
+    channel TEXT NOT NULL,
+    username TEXT NOT NULL,
+    display_name TEXT,
+    content TEXT NOT NULL,
+    user_level TEXT NOT NULL,
+    message_type TEXT NOT NULL,
+    timestamp_ms INTEGER NOT NULL,
+    data TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS idx_messages_channel ON messages (platform, channel, timestamp_ms);
+CREATE INDEX IF NOT EXISTS idx_messages_username ON messages (username, timestamp_ms);
+CREATE INDEX IF NOT EXISTS idx_messages_timestamp ON messages (timestamp_ms);
+";
+
+/// `storage`
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct StorageConfig {
+    /// Desactivado por defecto
+    pub enabled: bool,
+    /// Base de datos; se crea si no existe
+    pub path: PathBuf,
+}
+
+impl Default for StorageConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            path: PathBuf::from("chat.sqlite3"),
+        }
+    }
+}
+
+/// Fila de `messages`
+#[derive(Debug, Clone, PartialEq)]
+pub struct StoredMessage {
+    pub id: String,
+    pub platform: String,
+    pub channel: String,
+    /// En minúsculas, para buscar por usuario sin distinguir mayúsculas
+    pub username: String,
+    pub display_name: Option<String>,
+    pub content: String,
+    pub user_level: String,
+    pub message_type: String,
+    pub timestamp_ms: i64,
+    pub data: String,
+}
+
+/// Nombre serializado de un enum (`moderator`, `subscription`...)
+fn variant_name<T: Serialize>(value: &T) -> String {
+    serde_json::to_value(value)
+        .ok()
+        .and_then(|v| v.as_str().map(str::to_string))
+        .unwrap_or_default()
+}
+
+impl StoredMessage {
+    pub fn from_mapped(message: &MappedMessage) -> Self {
+        Self {
+            id: message.id.clone(),
+            platform: message.platform.clone(),
+            channel: message.channel.clone(),
+            username: message.username.to_lowercase(),
+            display_name: message.display_name.clone(),
+            content: message.content.clone(),
+            user_level: variant_name(&message.user_level),
+            message_type: variant_name(&message.message_type),
+            timestamp_ms: message.timestamp.timestamp_millis(),
+            data: serde_json::to_string(message).unwrap_or_default(),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Writer {
+    sender: std::sync::mpsc::Sender<StoredMessage>,
+    thread: std::thread::JoinHandle<()>,
+}
+
+#[derive(Debug, Default)]
+struct StoreState {
+    config: StorageConfig,
+    writer: Option<Writer>,
+    /// No se pudo abrir (o falta la feature): no se reintenta hasta reconfigurar
+    unavailable: bool,
+}
+
+/// Archivo compartido; la conexión a SQLite vive en su propio hilo
+#[derive(Debug, Default)]
+pub struct MessageStore {
+    state: Mutex<StoreState>,
+}
+
+impl MessageStore {
+    pub fn global() -> &'static MessageStore {
+        GLOBAL_STORE.get_or_init(MessageStore::default)
+    }
+
+    /// Aplica la configuración; si cambia, la base de datos anterior se cierra
+    /// y la nueva se abre con el siguiente mensaje
+    pub fn configure(&self, config: StorageConfig) {
+        let previous = {
+            let mut state = self.lock();
+            if state.config == config {
+                return;
+            }
+            state.config = config;
+            state.unavailable = false;
+            state.writer.take()
+        };
+        close(previous);
+    }
+
+    /// Encola un mensaje para guardarlo
+    pub fn store(&self, message: &MappedMessage) {
+        let mut state = self.lock();
+        if !state.config.enabled || state.unavailable {
+            return;
+        }
+        if state.writer.is_none() {
+            state.writer = spawn_writer(&state.config.path);
+            state.unavailable = state.writer.is_none();
+        }
+        let sent = state.writer.as_ref().is_some_and(|writer| {
+            writer
+                .sender
+                .send(StoredMessage::from_mapped(message))
+                .is_ok()
+        });
+        if !sent {
+            // El hilo terminó: se reabre con el siguiente mensaje
+            state.writer = None;
+        }
+    }
+
+    /// Escribe lo pendiente y cierra la base de datos (al salir)
+    pub fn shutdown(&self) {
+        let writer = self.lock().writer.take();
+        close(writer);
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, StoreState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Suelta el canal y espera a que el hilo vacíe la cola
+fn close(writer: Option<Writer>) {
+    if let Some(Writer { sender, thread }) = writer {
+        drop(sender);
+        let _ = thread.join();
+    }
+}
+
+/// Abre la base de datos y crea la tabla y los índices si faltan
+#[cfg(feature = "storage")]
+pub fn open_database(path: &std::path::Path) -> rusqlite::Result<rusqlite::Connection> {
+    let connection = rusqlite::Connection::open(path)?;
+    connection.pragma_update(None, "journal_mode", "WAL")?;
+    connection.execute_batch(SCHEMA)?;
+    Ok(connection)
+}
+
+/// Inserta un lote en una transacción; los ids repetidos se ignoran
+#[cfg(feature = "storage")]
+pub fn insert_messages(
+    connection: &mut rusqlite::Connection,
+    messages: &[StoredMessage],
+) -> rusqlite::Result<usize> {
+    let transaction = connection.transaction()?;
+    let mut inserted = 0;
+    {
+        let mut statement = transaction.prepare_cached(
+            "INSERT OR IGNORE INTO messages (id, platform, channel, username, display_name, \
+             content, user_level, message_type, timestamp_ms, data) \
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+        )?;
+        for message in messages {
+            inserted += statement.execute(rusqlite::params![
+                message.id,
+                message.platform,
+                message.channel,
+                message.username,
+                message.display_name,
+                message.content,
+                message.user_level,
+                message.message_type,
+                message.timestamp_ms,
+                message.data,
+            ])?;
+        }
+    }
+    transaction.commit()?;
+    Ok(inserted)
+}
+
+/// Hilo que escribe por lotes lo que llega por el canal; `None` si no se
+/// pudo abrir la base de datos
+#[cfg(feature = "storage")]
+fn spawn_writer(path: &std::path::Path) -> Option<Writer> {
+    let mut connection = match open_database(path) {
+        Ok(connection) => connection,
+        Err(e) => {
+            eprintln!("[STORAGE] ⚠️ Cannot open {}: {}", path.display(), e);
+            return None;
+        }
+    };
+    println!("[STORAGE] 💾 Archiving chat to {}", path.display());
+
+    let (sender, receiver) = std::sync::mpsc::channel::<StoredMessage>();
+    let thread = std::thread::Builder::new()
+        .name("storage".to_string())
+        .spawn(move || {
+            while let Ok(first) = receiver.recv() {
+                // Lo que se acumuló mientras se escribía el lote anterior
+                let mut batch = vec![first];
+                batch.extend(receiver.try_iter());
+                if let Err(e) = insert_messages(&mut connection, &batch) {
+                    eprintln!(
+                        "[STORAGE] ⚠️ Failed to store {} messages: {}",
+                        batch.len(),
+                        e
+                    );
+                }
+            }
+        })
+        .ok()?;
+    Some(Writer { sender, thread })
+}
+
+#[cfg(not(feature = "storage"))]
+fn spawn_writer(_path: &std::path::Path) -> Option<Writer> {
+    eprintln!("⚠️ Chat storage needs the `storage` feature (build with --features storage)");
+    None
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum StorageError {
+    #[cfg(not(feature = "storage"))]
+    #[error("chat storage is not compiled in (build with --features storage)")]
+    Unavailable,
+    #[error("database error: {0}")]
+    Database(String),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
+
+/// Formato de [`export`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ExportFormat {
+    #[default]
+    Json,
+    Csv,
+}
+
+impl ExportFormat {
+    pub const ALL: [ExportFormat; 2] = [ExportFormat::Json, ExportFormat::Csv];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ExportFormat::Json => "json",
+            ExportFormat::Csv => "csv",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|format| format.name().eq_ignore_ascii_case(name))
+    }
+}
+
+/// Intervalo de fechas exportado; un extremo sin indicar no limita
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ExportRange {
+    pub since: Option<DateTime<Utc>>,
+    pub until: Option<DateTime<Utc>>,
+}
+
+/// Mensaje tal como se exporta
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ExportedMessage {
+    pub timestamp: DateTime<Utc>,
+    pub platform: String,
+    pub channel: String,
+    pub username: String,
+    pub display_name: Option<String>,
+    pub user_level: String,
+    pub message_type: String,
+    pub content: String,
+    /// Nombres de los emotes del mensaje, sin repetir
+    pub emotes: Vec<String>,
+}
+
+impl From<StoredMessage> for ExportedMessage {
+    fn from(row: StoredMessage) -> Self {
+        let mut emotes: Vec<String> = Vec::new();
+        let data: serde_json::Value = serde_json::from_str(&row.data).unwrap_or_default();
+        for name in data["emotes"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|emote| emote["name"].as_str())
+        {
+            if !emotes.iter().any(|known| known == name) {
+                emotes.push(name.to_string());
+            }
+        }
+
+        Self {
+            timestamp: DateTime::from_timestamp_millis(row.timestamp_ms).unwrap_or_default(),
+            platform: row.platform,
+            channel: row.channel,
+            username: row.username,
+            display_name: row.display_name,
+            user_level: row.user_level,
+            message_type: row.message_type,
+            content: row.content,
+            emotes,
+        }
+    }
+}
+
+const CSV_HEADER: &str =
+    "timestamp,platform,channel,username,display_name,user_level,message_type,content,emotes";
+
+/// Campo CSV entrecomillado si lleva comas, comillas o saltos de línea
+fn csv_field(value: &str) -> Cow<'_, str> {
+    if value.contains([',', '"', '\n', '\r']) {
+        Cow::Owned(format!("\"{}\"", value.replace('"', "\"\"")))
+    } else {
+        Cow::Borrowed(value)
+    }
+}
+
+/// Volcado en el formato pedido; en CSV los emotes van separados por espacios
+pub fn render_export(messages: &[ExportedMessage], format: ExportFormat) -> String {
+    match format {
+        ExportFormat::Json => serde_json::to_string_pretty(messages).unwrap_or_default(),
+        ExportFormat::Csv => {
+            let mut csv = String::from(CSV_HEADER);
+            csv.push('\n');
+            for message in messages {
+                let timestamp = message
+                    .timestamp
+                    .to_rfc3339_opts(chrono::SecondsFormat::Millis, true);
+                let emotes = message.emotes.join(" ");
+                let fields = [
+                    timestamp.as_str(),
+                    &message.platform,
+                    &message.channel,
+                    &message.username,
+                    message.display_name.as_deref().unwrap_or(""),
+                    &message.user_level,
+                    &message.message_type,
+                    &message.content,
+                    &emotes,
+                ];
+                let line: Vec<Cow<'_, str>> = fields.iter().map(|field| csv_field(field)).collect();
+                csv.push_str(&line.join(","));
+                csv.push('\n');
+            }
+            csv
+        }
+    }
+}
+
+/// Mensajes del intervalo, del más antiguo al más nuevo
+#[cfg(feature = "storage")]
+pub fn read_messages(
+    connection: &rusqlite::Connection,
+    range: ExportRange,
+) -> rusqlite::Result<Vec<StoredMessage>> {
+    let mut statement = connection.prepare(
+        "SELECT id, platform, channel, username, display_name, content, user_level, \
+         message_type, timestamp_ms, data FROM messages \
+         WHERE timestamp_ms BETWEEN ?1 AND ?2 ORDER BY timestamp_ms, rowid",
+    )?;
+    let since = range
+        .since
+        .map_or(i64::MIN, |since| since.timestamp_millis());
+    let until = range
+        .until
+        .map_or(i64::MAX, |until| until.timestamp_millis());
+    let rows = statement.query_map(rusqlite::params![since, until], |row| {
+        Ok(StoredMessage {
+            id: row.get(0)?,
+            platform: row.get(1)?,
+            channel: row.get(2)?,
+            username: row.get(3)?,
+            display_name: row.get(4)?,
+            content: row.get(5)?,
+            user_level: row.get(6)?,
+            message_type: row.get(7)?,
+            timestamp_ms: row.get(8)?,
+            data: row.get(9)?,
+        })
+    })?;
+    rows.collect()
+}
+
+/// Exporta a `path` los mensajes archivados en `database` dentro de `range`;
+/// devuelve cuántos se escribieron
+pub fn export(
+    database: &Path,
+    range: ExportRange,
+    format: ExportFormat,
+    path: &Path,
+) -> Result<usize, StorageError> {
+    if !database.exists() {
+        return Err(StorageError::Database(format!(
+            "{} does not exist (is `storage.enabled` set?)",
+            database.display()
+        )));
+    }
+    let messages: Vec<ExportedMessage> = load_messages(database, range)?
+        .into_iter()
+        .map(ExportedMessage::from)
+        .collect();
+    std::fs::write(path, render_export(&messages, format))?;
+    Ok(messages.len())
+}
+
+#[cfg(feature = "storage")]
+fn load_messages(database: &Path, range: ExportRange) -> Result<Vec<StoredMessage>, StorageError> {
+    open_database(database)
+        .and_then(|connection| read_messages(&connection, range))
+        .map_err(|e| StorageError::Database(e.to_string()))
+}
+
+#[cfg(not(feature = "storage"))]
+fn load_messages(
+    _database: &Path,
+    _range: ExportRange,
+) -> Result<Vec<StoredMessage>, StorageError> {
+    Err(StorageError::Unavailable)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mapping::{MappedMessageType, MappedMetadata, UserLevel};
+
+    fn mapped(id: &str, username: &str) -> MappedMessage {
+        MappedMessage {
+            id: id.to_string(),
+            platform: "twitch".to_string(),
+            channel: "chan".to_string(),
+            username: username.to_string(),
+            display_name: Some(username.to_string()),
+            content: "hola".to_string(),
+            emotes: Vec::new(),
+            badges: Vec::new(),
+            timestamp: chrono::DateTime::from_timestamp_millis(1_700_000_000_000).unwrap(),
+            user_level: UserLevel::Moderator,
+            message_type: MappedMessageType::Subscription,
+            metadata: MappedMetadata::default(),
+        }
+    }
+
+    #[test]
+    fn test_stored_message_columns() {
+        let row = StoredMessage::from_mapped(&mapped("m1", "SomeUser"));
+        assert_eq!(row.username, "someuser");
+        assert_eq!(row.display_name.as_deref(), Some("SomeUser"));
+        assert_eq!(row.user_level, "moderator");
+        assert_eq!(row.message_type, "subscription");
+        assert_eq!(row.timestamp_ms, 1_700_000_000_000);
+        let data: MappedMessage = serde_json::from_str(&row.data).unwrap();
+        assert_eq!(data.id, "m1");
+    }
+
+    #[test]
+    fn test_render_export_csv_and_json() {
+        let mut message = mapped("m1", "SomeUser");
+        message.content = "hola, \"chat\" Kappa Kappa".to_string();
+        message.emotes = vec![
+            crate::connection::Emote {
+                name: "Kappa".to_string(),
+                ..Default::default()
+            };
+            2
+        ];
+        let exported = ExportedMessage::from(StoredMessage::from_mapped(&message));
+        assert_eq!(exported.emotes, vec!["Kappa"]);
+
+        let csv = render_export(std::slice::from_ref(&exported), ExportFormat::Csv);
+        let mut lines = csv.lines();
+        assert_eq!(lines.next(), Some(CSV_HEADER));
+        assert_eq!(
+            lines.next(),
+            Some("2023-11-14T22:13:20.000Z,twitch,chan,someuser,SomeUser,moderator,subscription,\"hola, \"\"chat\"\" Kappa Kappa\",Kappa")
+        );
+
+        let json: serde_json::Value =
+            serde_json::from_str(&render_export(&[exported], ExportFormat::Json)).unwrap();
+        assert_eq!(json[0]["user_level"], "moderator");
+        assert_eq!(json[0]["emotes"][0], "Kappa");
+        assert_eq!(ExportFormat::from_name("CSV"), Some(ExportFormat::Csv));
+    }
+
+    #[cfg(feature = "storage")]
+    #[test]
+    fn test_insert_messages_ignores_duplicates() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut connection = open_database(&dir.path().join("chat.sqlite3")).unwrap();
+        let rows = [
+            StoredMessage::from_mapped(&mapped("m1", "a")),
+            StoredMessage::from_mapped(&mapped("m2", "b")),
+        ];
+        assert_eq!(insert_messages(&mut connection, &rows).unwrap(), 2);
+        assert_eq!(insert_messages(&mut connection, &rows[..1]).unwrap(), 0);
+
+        let count: i64 = connection
+            .query_row(
+                "SELECT COUNT(*) FROM messages WHERE username = ?1",
+                ["b"],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(count, 1);
+
+        let mut late = mapped("m3", "c");
+        late.timestamp += chrono::Duration::hours(1);
+        insert_messages(&mut connection, &[StoredMessage::from_mapped(&late)]).unwrap();
+        let range = ExportRange {
+            since: Some(late.timestamp),
+            until: None,
+        };
+        let recent = read_messages(&connection, range).unwrap();
+        assert_eq!(recent.len(), 1);
+        assert_eq!(recent[0].id, "m3");
+        assert_eq!(
+            read_messages(&connection, ExportRange::default())
+                .unwrap()
+                .len(),
+            3
+        );
+    }
+}
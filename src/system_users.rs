@@ -0,0 +1,105 @@
+//! Usuarios virtuales para los mensajes que genera el propio overlay
+//!
+//! Anuncios, mensajes programados y diagnósticos se muestran con un nombre,
+//! color e insignia configurables para distinguirlos del chat real.
+
+use std::collections::HashMap;
+use std::time::SystemTime;
+
+use crate::config::SystemUser;
+use crate::connection::{Badge, ChatMessage, EmoteSource, MessageMetadata, MessageType};
+
+/// Plataforma de los mensajes generados internamente
+pub const SYSTEM_PLATFORM: &str = "overlay";
+/// Clave en `custom_data` con el rol del usuario virtual
+pub const SYSTEM_ROLE_KEY: &str = "system_user";
+
+/// Insignia del usuario virtual: un nombre del paquete integrado o una ruta/URL
+fn badge_for(user: &SystemUser) -> Option<Badge> {
+    let badge = user.badge.as_ref()?;
+    let is_icon = badge.contains('/') || badge.contains('.');
+    let name = if is_icon {
+        "system".to_string()
+    } else {
+        badge.to_lowercase()
+    };
+
+    Some(Badge {
+        id: name.clone(),
+        url: is_icon.then(|| badge.clone()),
+        title: Some(user.name.clone()),
+        name,
+        version: "1".to_string(),
+        source: EmoteSource::Local,
+    })
+}
+
+/// Mensaje de sistema firmado por `user`
+///
+/// `role` identifica el origen (`announcements`, `scheduler`, `diagnostics`) y
+/// se guarda en `custom_data` junto a `extra_data`.
+pub fn system_message(
+    user: &SystemUser,
+    role: &str,
+    content: impl Into<String>,
+    extra_data: HashMap<String, serde_json::Value>,
+) -> ChatMessage {
+    let mut custom_data = extra_data;
+    custom_data.insert(SYSTEM_ROLE_KEY.to_string(), role.into());
+
+    ChatMessage {
+        id: format!("{}-{}", role, chrono::Utc::now().timestamp_millis()),
+        platform: SYSTEM_PLATFORM.to_string(),
+        channel: String::new(),
+        username: user.name.clone(),
+        display_name: Some(user.name.clone()),
+        content: content.into(),
+        emotes: Vec::new(),
+        badges: badge_for(user).into_iter().collect(),
+        timestamp: SystemTime::now(),
+        user_color: user.color.clone(),
+        message_type: MessageType::System,
+        metadata: MessageMetadata {
+            is_action: false,
+            is_whisper: false,
+            is_highlighted: true,
+            is_me_message: false,
+            reply_to: None,
+            thread_id: None,
+            custom_data,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::SystemUsersConfig;
+
+    #[test]
+    fn test_system_message_uses_virtual_user() {
+        let users = SystemUsersConfig::default();
+        let message = system_message(&users.diagnostics, "diagnostics", "hi", HashMap::new());
+
+        assert_eq!(message.platform, SYSTEM_PLATFORM);
+        assert_eq!(message.username, users.diagnostics.name);
+        assert_eq!(message.user_color, users.diagnostics.color);
+        assert_eq!(message.metadata.custom_data[SYSTEM_ROLE_KEY], "diagnostics");
+        assert_eq!(message.badges.len(), 1);
+        assert!(message.badges[0].url.is_none());
+    }
+
+    #[test]
+    fn test_badge_icon_paths_and_missing_badges() {
+        let mut user = SystemUser {
+            badge: Some("icons/bell.png".to_string()),
+            ..SystemUser::default()
+        };
+        let message = system_message(&user, "announcements", "hi", HashMap::new());
+        assert_eq!(message.badges[0].url.as_deref(), Some("icons/bell.png"));
+
+        user.badge = None;
+        let message = system_message(&user, "announcements", "hi", HashMap::new());
+        assert!(message.badges.is_empty());
+    }
+}
@@ -0,0 +1,288 @@
+//! Renderer de terminal para entornos sin servidor gráfico
+//!
+//! Con `--renderer terminal`, o automáticamente cuando no hay servidor
+//! gráfico (SSH, CI, contenedores), cada mensaje se imprime como una línea de
+//! texto con colores ANSI en lugar de abrir una ventana: hora, plataforma,
+//! icono del evento, insignias como glifos y nombre con el color del usuario.
+//! Los emotes se muestran por su nombre resaltado. Si la salida no es una
+//! terminal o `NO_COLOR` está definida, las líneas van sin colores.
+
+use std::io::{IsTerminal, Write};
+
+use crossterm::style::{Color, StyledContent, Stylize};
+
+use crate::config::DisplayConfig;
+use crate::connection::{ChatMessage, MessageType};
+use crate::mapping::MappedMessageType;
+use crate::theme::events::OverlayEventKind;
+use crate::theme::Rgb;
+
+/// Backend con el que se muestran los mensajes (`--renderer`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RendererKind {
+    /// Ventanas nativas si hay servidor gráfico; si no, la terminal
+    #[default]
+    Auto,
+    /// Ventanas nativas (GTK, Win32 o NSPanel)
+    Native,
+    Terminal,
+}
+
+impl RendererKind {
+    pub const ALL: [RendererKind; 3] = [
+        RendererKind::Auto,
+        RendererKind::Native,
+        RendererKind::Terminal,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            RendererKind::Auto => "auto",
+            RendererKind::Native => "native",
+            RendererKind::Terminal => "terminal",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|kind| kind.name().eq_ignore_ascii_case(name))
+    }
+
+    /// Si se usa la terminal; `Auto` la elige sin servidor gráfico
+    pub fn uses_terminal(self) -> bool {
+        match self {
+            RendererKind::Auto => !display_available(),
+            RendererKind::Native => false,
+            RendererKind::Terminal => true,
+        }
+    }
+}
+
+/// Hay un servidor X11 o Wayland al que conectar; en Windows y macOS
+/// siempre hay escritorio
+pub fn display_available() -> bool {
+    if cfg!(any(windows, target_os = "macos")) {
+        return true;
+    }
+    ["DISPLAY", "WAYLAND_DISPLAY"]
+        .iter()
+        .any(|var| std::env::var_os(var).is_some_and(|value| !value.is_empty()))
+}
+
+/// Glifo y color de una insignia; `None` para las que no se muestran
+pub fn badge_glyph(name: &str) -> Option<(char, Color)> {
+    match name.to_lowercase().as_str() {
+        "broadcaster" => Some(('◉', Color::Red)),
+        "moderator" | "mod" => Some(('⚔', Color::Green)),
+        "vip" => Some(('◆', Color::Magenta)),
+        "subscriber" | "founder" | "og" => Some(('★', Color::Yellow)),
+        "staff" | "admin" | "global_mod" => Some(('⚙', Color::Cyan)),
+        "partner" | "verified" => Some(('✔', Color::Blue)),
+        "bits" | "bits-leader" => Some(('♦', Color::DarkCyan)),
+        _ => None,
+    }
+}
+
+/// Formatea mensajes como líneas de terminal con los colores de `display`
+#[derive(Debug, Clone)]
+pub struct TerminalRenderer {
+    display: DisplayConfig,
+    color: bool,
+}
+
+impl TerminalRenderer {
+    /// Con colores solo si la salida estándar es una terminal
+    pub fn new(display: DisplayConfig) -> Self {
+        let color = std::io::stdout().is_terminal() && std::env::var_os("NO_COLOR").is_none();
+        Self::with_color(display, color)
+    }
+
+    pub fn with_color(display: DisplayConfig, color: bool) -> Self {
+        Self { display, color }
+    }
+
+    /// Imprime `message` en la salida estándar
+    pub fn print(&self, message: &ChatMessage) {
+        let mut stdout = std::io::stdout().lock();
+        // Un fallo al escribir (p.ej. tubería cerrada) no detiene el overlay
+        let _ = writeln!(stdout, "{}", self.format(message));
+        let _ = stdout.flush();
+    }
+
+    /// Línea con la que se muestra `message`
+    pub fn format(&self, message: &ChatMessage) -> String {
+        let template = self
+            .display
+            .event_template(OverlayEventKind::of(&MappedMessageType::of(message)));
+        let mut line = String::new();
+
+        let time = chrono::DateTime::<chrono::Local>::from(message.timestamp).format("%H:%M");
+        line.push_str(&self.paint(format!("{} [{}]", time, message.platform), |s| s.dim()));
+        line.push(' ');
+
+        if let Some(icon) = template.icon.as_deref().filter(|icon| !icon.is_empty()) {
+            line.push_str(&sanitize(icon));
+            line.push(' ');
+        }
+        for (glyph, color) in message.badges.iter().filter_map(|b| badge_glyph(&b.name)) {
+            line.push_str(&self.paint(glyph.to_string(), |s| s.with(color)));
+        }
+        if message
+            .badges
+            .iter()
+            .any(|b| badge_glyph(&b.name).is_some())
+        {
+            line.push(' ');
+        }
+
+        let name_color = template
+            .username_color
+            .as_deref()
+            .or(message.user_color.as_deref())
+            .and_then(Rgb::parse_hex)
+            .map(|Rgb(r, g, b)| Color::Rgb { r, g, b });
+        let name = sanitize(message.display_name.as_deref().unwrap_or(&message.username));
+        line.push_str(&self.paint(name, |s| match name_color {
+            Some(color) => s.with(color).bold(),
+            None => s.bold(),
+        }));
+
+        // `/me` se escribe a continuación del nombre y en cursiva
+        let is_action =
+            message.metadata.is_action || matches!(message.message_type, MessageType::Action);
+        line.push_str(if is_action { " " } else { ": " });
+        line.push_str(&self.content(message, is_action));
+        line
+    }
+
+    /// Texto del mensaje con los emotes resaltados
+    fn content(&self, message: &ChatMessage, is_action: bool) -> String {
+        let chars: Vec<char> = message.content.chars().collect();
+        let mut ranges: Vec<(usize, usize)> = message
+            .emotes
+            .iter()
+            .flat_map(|emote| emote.positions.iter())
+            .filter(|position| position.start <= position.end && position.end < chars.len())
+            .map(|position| (position.start, position.end + 1))
+            .collect();
+        ranges.sort_unstable();
+
+        let emphasis =
+            message.metadata.is_highlighted || matches!(message.message_type, MessageType::System);
+        let text = move |s: StyledContent<String>| match (is_action, emphasis) {
+            (true, _) => s.italic(),
+            (false, true) => s.bold(),
+            (false, false) => s,
+        };
+
+        let mut content = String::new();
+        let mut cursor = 0;
+        for (start, end) in ranges {
+            // Posiciones solapadas (zero-width de 7TV): ya está resaltado
+            if start < cursor {
+                continue;
+            }
+            let before: String = chars[cursor..start].iter().collect();
+            content.push_str(&self.paint(sanitize(&before), text));
+            let emote: String = chars[start..end].iter().collect();
+            content.push_str(&self.paint(sanitize(&emote), |s| s.cyan().bold()));
+            cursor = end;
+        }
+        let rest: String = chars[cursor..].iter().collect();
+        content.push_str(&self.paint(sanitize(&rest), text));
+        content
+    }
+
+    /// Aplica `style` solo si hay colores
+    fn paint(
+        &self,
+        text: String,
+        style: impl FnOnce(StyledContent<String>) -> StyledContent<String>,
+    ) -> String {
+        if !self.color || text.is_empty() {
+            return text;
+        }
+        style(text.stylize()).to_string()
+    }
+}
+
+/// Sustituye los caracteres de control para que el chat no pueda mover el
+/// cursor ni cambiar los colores de la terminal
+fn sanitize(text: &str) -> String {
+    text.chars()
+        .map(|c| if c.is_control() { '�' } else { c })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::connection::{Emote, TextPosition};
+
+    /// Línea sin la hora, que depende del reloj
+    fn without_time(line: String) -> String {
+        line.split_once(' ').unwrap().1.to_string()
+    }
+
+    #[test]
+    fn test_plain_line_has_badges_name_and_sanitized_text() {
+        let renderer = TerminalRenderer::with_color(Config::default().display, false);
+        let mut chat = ChatMessage::test("Viewer", "hola\x1b[2J Kappa")
+            .with_badge("moderator")
+            .with_badge("subscriber")
+            .with_badge("premium");
+        chat.emotes.push(Emote {
+            id: "25".to_string(),
+            name: "Kappa".to_string(),
+            positions: vec![TextPosition { start: 9, end: 13 }],
+            ..Emote::default()
+        });
+
+        assert_eq!(
+            without_time(renderer.format(&chat)),
+            "[twitch] ⚔★ Viewer: hola�[2J Kappa"
+        );
+
+        chat.badges.clear();
+        chat.metadata.is_action = true;
+        assert_eq!(
+            without_time(renderer.format(&chat)),
+            "[twitch] Viewer hola�[2J Kappa"
+        );
+    }
+
+    #[test]
+    fn test_colored_line_uses_user_color_and_highlights_emotes() {
+        let renderer = TerminalRenderer::with_color(Config::default().display, true);
+        let mut chat = ChatMessage::test("Viewer", "hi Kappa");
+        chat.user_color = Some("#ff0000".to_string());
+        chat.emotes.push(Emote {
+            id: "25".to_string(),
+            name: "Kappa".to_string(),
+            positions: vec![TextPosition { start: 3, end: 7 }],
+            ..Emote::default()
+        });
+
+        let line = renderer.format(&chat);
+        assert!(line.contains(
+            &"Viewer"
+                .with(Color::Rgb { r: 255, g: 0, b: 0 })
+                .bold()
+                .to_string()
+        ));
+        assert!(line.contains(&"Kappa".cyan().bold().to_string()));
+    }
+
+    #[test]
+    fn test_renderer_names() {
+        assert_eq!(
+            RendererKind::from_name("Terminal"),
+            Some(RendererKind::Terminal)
+        );
+        assert_eq!(RendererKind::from_name("gtk"), None);
+        assert!(RendererKind::Terminal.uses_terminal());
+        assert!(!RendererKind::Native.uses_terminal());
+    }
+}
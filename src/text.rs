@@ -0,0 +1,209 @@
+//! Utilidades de texto seguras para Unicode
+//!
+//! Todas las operaciones trabajan sobre clusters de grafemas o índices de
+//! caracteres, nunca sobre índices de bytes, para no partir caracteres
+//! multibyte ni emojis compuestos.
+
+use std::ops::Range;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Número de grafemas visibles en el texto
+pub fn grapheme_len(text: &str) -> usize {
+    text.graphemes(true).count()
+}
+
+/// Devuelve el prefijo con como máximo `max` grafemas
+pub fn truncate_graphemes(text: &str, max: usize) -> &str {
+    match text.grapheme_indices(true).nth(max) {
+        Some((byte_idx, _)) => &text[..byte_idx],
+        None => text,
+    }
+}
+
+/// Trunca a `max` grafemas añadiendo `ellipsis` solo si se recortó algo
+pub fn truncate_with_ellipsis(text: &str, max: usize, ellipsis: &str) -> String {
+    let truncated = truncate_graphemes(text, max);
+    if truncated.len() == text.len() {
+        text.to_string()
+    } else {
+        format!("{}{}", truncated, ellipsis)
+    }
+}
+
+/// Primer grafema del texto (vacío si el texto está vacío)
+pub fn first_grapheme(text: &str) -> &str {
+    text.graphemes(true).next().unwrap_or("")
+}
+
+/// Convierte un índice de carácter (code point) a índice de byte
+///
+/// Acepta `char_idx == chars().count()` y devuelve `text.len()`.
+pub fn char_to_byte_index(text: &str, char_idx: usize) -> Option<usize> {
+    text.char_indices()
+        .map(|(byte_idx, _)| byte_idx)
+        .chain(std::iter::once(text.len()))
+        .nth(char_idx)
+}
+
+/// Sub-cadena entre dos índices de carácter (`end` exclusivo)
+pub fn slice_chars(text: &str, range: Range<usize>) -> Option<&str> {
+    if range.start > range.end {
+        return None;
+    }
+    let start = char_to_byte_index(text, range.start)?;
+    let end = char_to_byte_index(text, range.end)?;
+    Some(&text[start..end])
+}
+
+/// Sub-cadena entre dos índices de carácter con `end` inclusivo (formato de Twitch)
+pub fn slice_chars_inclusive(text: &str, start: usize, end: usize) -> Option<&str> {
+    slice_chars(text, start..end.checked_add(1)?)
+}
+
+/// Palabras separadas por espacios en blanco, con el índice de carácter en
+/// que empieza cada una
+///
+/// Los emotes de terceros solo se reconocen como palabra completa: `Kappa`
+/// no aparece dentro de `KappaPride` ni de `Kappa,`.
+pub fn words(text: &str) -> Vec<(usize, &str)> {
+    let mut words = Vec::new();
+    // (carácter, byte) donde empieza la palabra en curso
+    let mut start: Option<(usize, usize)> = None;
+    for (char_index, (byte_index, c)) in text.char_indices().enumerate() {
+        if c.is_whitespace() {
+            if let Some((char_start, byte_start)) = start.take() {
+                words.push((char_start, &text[byte_start..byte_index]));
+            }
+        } else if start.is_none() {
+            start = Some((char_index, byte_index));
+        }
+    }
+    if let Some((char_start, byte_start)) = start {
+        words.push((char_start, &text[byte_start..]));
+    }
+    words
+}
+
+/// Sub-cadena por índices de byte que devuelve `None` en vez de entrar en pánico
+pub fn slice_bytes(text: &str, range: Range<usize>) -> Option<&str> {
+    text.get(range)
+}
+
+/// Tramo de un mensaje: texto o el emote con ese índice
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextRun<'a> {
+    Text(&'a str),
+    Emote(usize),
+}
+
+/// Intercala el texto con los emotes que ocupan `ranges` (índices de carácter,
+/// fin exclusivo, en orden)
+///
+/// Un rango vacío, fuera del texto o solapado con el anterior no se sustituye:
+/// su código queda como texto.
+pub fn interleave_runs(
+    text: &str,
+    ranges: impl IntoIterator<Item = Range<usize>>,
+) -> Vec<TextRun<'_>> {
+    let mut runs = Vec::new();
+    let mut start = 0;
+    for (index, range) in ranges.into_iter().enumerate() {
+        if range.start < start || range.is_empty() || slice_chars(text, range.clone()).is_none() {
+            continue;
+        }
+        if let Some(plain) = slice_chars(text, start..range.start).filter(|t| !t.is_empty()) {
+            runs.push(TextRun::Text(plain));
+        }
+        runs.push(TextRun::Emote(index));
+        start = range.end;
+    }
+    if let Some(rest) = slice_chars(text, start..text.chars().count()).filter(|t| !t.is_empty()) {
+        runs.push(TextRun::Text(rest));
+    }
+    runs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn test_truncate_graphemes_keeps_emoji_intact() {
+        let text = "hi 👨‍👩‍👧 there";
+        assert_eq!(truncate_graphemes(text, 4), "hi 👨‍👩‍👧");
+        assert_eq!(truncate_graphemes("ñandú", 2), "ña");
+        assert_eq!(truncate_graphemes("abc", 10), "abc");
+        assert_eq!(truncate_with_ellipsis("abcdef", 3, "..."), "abc...");
+        assert_eq!(truncate_with_ellipsis("abc", 3, "..."), "abc");
+    }
+
+    #[test]
+    fn test_slice_chars() {
+        let text = "¡Hola Kappa!";
+        assert_eq!(slice_chars_inclusive(text, 6, 10), Some("Kappa"));
+        assert_eq!(slice_chars(text, 0..1), Some("¡"));
+        assert_eq!(slice_chars(text, 0..100), None);
+        let (start, end) = (3, 2);
+        assert_eq!(slice_chars(text, start..end), None);
+        assert_eq!(slice_bytes(text, 0..1), None);
+    }
+
+    #[test]
+    fn test_words_use_char_offsets() {
+        assert_eq!(
+            words("  ñandú\tKappa  KappaPride "),
+            vec![(2, "ñandú"), (8, "Kappa"), (15, "KappaPride")]
+        );
+        assert!(words(" \n ").is_empty());
+    }
+
+    #[test]
+    fn test_first_grapheme() {
+        assert_eq!(first_grapheme("🇦🇷flag"), "🇦🇷");
+        assert_eq!(first_grapheme(""), "");
+    }
+
+    proptest! {
+        #[test]
+        fn fuzz_truncate_never_panics(text in "\\PC*", max in 0usize..64) {
+            let truncated = truncate_graphemes(&text, max);
+            prop_assert!(text.starts_with(truncated));
+            prop_assert!(grapheme_len(truncated) <= max);
+            let _ = truncate_with_ellipsis(&text, max, "...");
+        }
+
+        #[test]
+        fn fuzz_slice_chars_never_panics(text in "\\PC*", start in 0usize..80, len in 0usize..80) {
+            if let Some(slice) = slice_chars(&text, start..start + len) {
+                prop_assert_eq!(slice.chars().count(), len);
+                prop_assert!(text.contains(slice));
+            }
+            let _ = slice_chars_inclusive(&text, start, start + len);
+        }
+    }
+
+    #[test]
+    fn test_interleave_runs() {
+        let text = "hola Kappa ñ PogChamp";
+        assert_eq!(
+            interleave_runs(text, [5..10, 13..21]),
+            vec![
+                TextRun::Text("hola "),
+                TextRun::Emote(0),
+                TextRun::Text(" ñ "),
+                TextRun::Emote(1),
+            ]
+        );
+        // Solapado y fuera del texto: se quedan como texto
+        assert_eq!(
+            interleave_runs(text, [5..10, 8..12, 30..35]),
+            vec![
+                TextRun::Text("hola "),
+                TextRun::Emote(0),
+                TextRun::Text(" ñ PogChamp"),
+            ]
+        );
+        assert!(interleave_runs("", []).is_empty());
+    }
+}
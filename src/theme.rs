@@ -0,0 +1,163 @@
+//! Temas visuales integrados para las ventanas del overlay
+
+use serde::{Deserialize, Serialize};
+
+pub mod events;
+pub mod import;
+pub mod levels;
+pub mod named;
+pub mod schedule;
+pub mod template;
+
+/// Tamaño mínimo de fuente que impone el tema de alto contraste
+pub const HIGH_CONTRAST_MIN_FONT_SIZE: u32 = 18;
+
+/// Tema visual seleccionado en `DisplayConfig`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ThemeName {
+    /// Apariencia por defecto de cada backend
+    #[default]
+    Default,
+    /// Fondo negro opaco, texto blanco y acentos amarillos
+    HighContrast,
+    /// Colores de `display` (`background_color`, `text_color`, `username_color`)
+    Custom,
+}
+
+/// Color RGB de 8 bits por canal
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rgb(pub u8, pub u8, pub u8);
+
+impl Rgb {
+    /// Parsea colores en formato `#rrggbb` o `rrggbb`
+    pub fn parse_hex(value: &str) -> Option<Self> {
+        let hex = value.trim().trim_start_matches('#');
+        if hex.len() != 6 || !hex.is_ascii() {
+            return None;
+        }
+        let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+        Some(Rgb(channel(0)?, channel(2)?, channel(4)?))
+    }
+
+    pub fn to_hex(self) -> String {
+        format!("#{:02x}{:02x}{:02x}", self.0, self.1, self.2)
+    }
+}
+
+/// Colores usados por los renderers
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ThemePalette {
+    pub background: Rgb,
+    pub border: Rgb,
+    pub text: Rgb,
+    pub username: Rgb,
+    pub progress: Rgb,
+    pub progress_background: Rgb,
+}
+
+impl ThemePalette {
+    /// Paleta de alto contraste (ratio > 15:1 entre texto y fondo)
+    pub fn high_contrast() -> Self {
+        Self {
+            background: Rgb(0, 0, 0),
+            border: Rgb(255, 255, 255),
+            text: Rgb(255, 255, 255),
+            username: Rgb(255, 255, 0),
+            progress: Rgb(255, 255, 0),
+            progress_background: Rgb(64, 64, 64),
+        }
+    }
+
+    /// Colores de los backends nativos (Win32 y NSPanel) sin tema; GTK toma
+    /// los suyos de `style.css`
+    pub fn fallback() -> Self {
+        Self {
+            background: Rgb(40, 40, 40),
+            border: Rgb(40, 40, 40),
+            text: Rgb(255, 255, 255),
+            username: Rgb(255, 255, 255),
+            progress: Rgb(0, 150, 255),
+            progress_background: Rgb(60, 60, 60),
+        }
+    }
+
+    /// Paleta a partir de los tres colores configurables; el nombre de usuario
+    /// sirve también de acento
+    pub fn from_colors(background: Rgb, text: Rgb, username: Rgb) -> Self {
+        let lighten = |c: u8| c + (255 - c) / 4;
+        Self {
+            background,
+            border: username,
+            text,
+            username,
+            progress: username,
+            progress_background: Rgb(
+                lighten(background.0),
+                lighten(background.1),
+                lighten(background.2),
+            ),
+        }
+    }
+
+    /// Hoja de estilos GTK equivalente a la paleta
+    pub fn to_css(&self, font_size: u32) -> String {
+        format!(
+            "window {{\n  background-color: {};\n  border: solid 3px {};\n}}\n\n\
+             label, textview text {{\n  font-size: {}px;\n  color: {};\n}}\n\n\
+             progress {{\n  background: {};\n}}\n\n\
+             trough {{\n  background: {};\n}}\n",
+            self.background.to_hex(),
+            self.border.to_hex(),
+            font_size,
+            self.text.to_hex(),
+            self.progress.to_hex(),
+            self.progress_background.to_hex(),
+        )
+    }
+}
+
+impl ThemeName {
+    /// Paleta fija del tema, o `None` si el backend usa su apariencia por defecto
+    /// (o, en `Custom`, si depende de los colores configurados)
+    pub fn palette(&self) -> Option<ThemePalette> {
+        match self {
+            ThemeName::Default | ThemeName::Custom => None,
+            ThemeName::HighContrast => Some(ThemePalette::high_contrast()),
+        }
+    }
+
+    /// Tamaño mínimo de fuente que exige el tema
+    pub fn min_font_size(&self) -> u32 {
+        match self {
+            ThemeName::Default | ThemeName::Custom => 0,
+            ThemeName::HighContrast => HIGH_CONTRAST_MIN_FONT_SIZE,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_hex() {
+        assert_eq!(Rgb::parse_hex("#1e1e1e"), Some(Rgb(30, 30, 30)));
+        assert_eq!(Rgb::parse_hex("ffffff"), Some(Rgb(255, 255, 255)));
+        assert_eq!(Rgb::parse_hex("#fff"), None);
+        assert_eq!(Rgb::parse_hex("#zzzzzz"), None);
+        assert_eq!(Rgb(0, 255, 16).to_hex(), "#00ff10");
+    }
+
+    #[test]
+    fn test_high_contrast_theme() {
+        let palette = ThemeName::HighContrast.palette().unwrap();
+        assert_eq!(palette.background, Rgb(0, 0, 0));
+        assert!(palette.to_css(20).contains("font-size: 20px"));
+        assert!(ThemeName::Default.palette().is_none());
+        assert_eq!(
+            ThemeName::HighContrast.min_font_size(),
+            HIGH_CONTRAST_MIN_FONT_SIZE
+        );
+    }
+}
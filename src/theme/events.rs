@@ -0,0 +1,233 @@
+//! Plantillas visuales por tipo de evento (suscripciones, raids, cheers,
+//! follows, canjes de puntos y Hype Trains)
+//!
+//! Cada evento tiene una plantilla integrada con colores, duración e icono
+//! propios; `display.event_templates` sobrescribe campo a campo.
+
+use serde::{Deserialize, Serialize};
+
+use crate::connection::ChatMessage;
+use crate::mapping::MappedMessageType;
+use crate::theme::{Rgb, ThemePalette};
+
+/// Tipo de evento que se muestra en la ventana
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OverlayEventKind {
+    /// Mensaje de chat normal (y cualquier tipo sin plantilla propia)
+    #[default]
+    Chat,
+    Subscription,
+    Raid,
+    Cheer,
+    Follow,
+    /// Canje de puntos del canal
+    Redemption,
+    HypeTrain,
+}
+
+impl OverlayEventKind {
+    pub fn of(message_type: &MappedMessageType) -> Self {
+        match message_type {
+            MappedMessageType::Subscription => OverlayEventKind::Subscription,
+            MappedMessageType::Raid => OverlayEventKind::Raid,
+            MappedMessageType::Cheer => OverlayEventKind::Cheer,
+            MappedMessageType::Follow => OverlayEventKind::Follow,
+            MappedMessageType::Redemption => OverlayEventKind::Redemption,
+            MappedMessageType::HypeTrain => OverlayEventKind::HypeTrain,
+            _ => OverlayEventKind::Chat,
+        }
+    }
+
+    /// Plantilla integrada; el chat no cambia la apariencia
+    pub fn builtin_template(&self) -> EventTemplate {
+        let (accent, background, duration_seconds, icon) = match self {
+            OverlayEventKind::Chat => return EventTemplate::default(),
+            OverlayEventKind::Subscription => ("#9146ff", "#241640", 15, "⭐"),
+            OverlayEventKind::Raid => ("#ff5a1f", "#3a1a0e", 20, "🚀"),
+            OverlayEventKind::Cheer => ("#ffc400", "#332a08", 15, "💎"),
+            OverlayEventKind::Follow => ("#1f69ff", "#0e1b33", 10, "💜"),
+            OverlayEventKind::Redemption => ("#00c8af", "#0b2e2a", 12, "🎁"),
+            OverlayEventKind::HypeTrain => ("#ff2e6f", "#3a0e1c", 20, "🚂"),
+        };
+        EventTemplate {
+            background_color: Some(background.to_string()),
+            text_color: None,
+            username_color: Some(accent.to_string()),
+            accent_color: Some(accent.to_string()),
+            duration_seconds: Some(duration_seconds),
+            icon: Some(icon.to_string()),
+        }
+    }
+}
+
+/// Plantilla de un evento; los campos `None` heredan la apariencia normal
+#[derive(Debug, Deserialize, Serialize, Clone, Default, PartialEq)]
+#[serde(default)]
+pub struct EventTemplate {
+    pub background_color: Option<String>,
+    pub text_color: Option<String>,
+    pub username_color: Option<String>,
+    /// Borde y barra de progreso
+    pub accent_color: Option<String>,
+    pub duration_seconds: Option<u64>,
+    /// Se antepone al nombre de usuario; `""` lo quita
+    pub icon: Option<String>,
+}
+
+impl EventTemplate {
+    /// Campos de `self` con los que falten tomados de `base`
+    pub fn or(self, base: EventTemplate) -> EventTemplate {
+        EventTemplate {
+            background_color: self.background_color.or(base.background_color),
+            text_color: self.text_color.or(base.text_color),
+            username_color: self.username_color.or(base.username_color),
+            accent_color: self.accent_color.or(base.accent_color),
+            duration_seconds: self.duration_seconds.or(base.duration_seconds),
+            icon: self.icon.or(base.icon),
+        }
+    }
+
+    pub fn has_colors(&self) -> bool {
+        self.background_color.is_some()
+            || self.text_color.is_some()
+            || self.username_color.is_some()
+            || self.accent_color.is_some()
+    }
+
+    /// Colores de la plantilla aplicados sobre `palette`
+    pub fn apply_to(&self, mut palette: ThemePalette) -> ThemePalette {
+        let color = |value: &Option<String>| value.as_deref().and_then(Rgb::parse_hex);
+        if let Some(background) = color(&self.background_color) {
+            palette.background = background;
+        }
+        if let Some(text) = color(&self.text_color) {
+            palette.text = text;
+        }
+        if let Some(username) = color(&self.username_color) {
+            palette.username = username;
+        }
+        if let Some(accent) = color(&self.accent_color) {
+            palette.border = accent;
+            palette.progress = accent;
+        }
+        palette
+    }
+
+    /// Primer color que no es `#rrggbb`, con el nombre de su campo
+    pub fn invalid_color(&self) -> Option<(&'static str, &str)> {
+        [
+            ("background_color", &self.background_color),
+            ("text_color", &self.text_color),
+            ("username_color", &self.username_color),
+            ("accent_color", &self.accent_color),
+        ]
+        .into_iter()
+        .find_map(|(field, value)| {
+            value
+                .as_deref()
+                .filter(|color| Rgb::parse_hex(color).is_none())
+                .map(|color| (field, color))
+        })
+    }
+}
+
+/// Cabecera destacada de la ventana de un evento, p. ej. los meses de un resub
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EventCard {
+    /// Texto grande (`12 months`)
+    pub headline: String,
+    /// Línea secundaria (`9-month streak · Tier 1`)
+    pub detail: Option<String>,
+}
+
+impl EventCard {
+    /// Tarjeta de sub/resub a partir de `cumulative_months`, `streak_months` y
+    /// `sub_plan` en `custom_data`; `None` si el mensaje no los tiene
+    pub fn from_message(message: &ChatMessage) -> Option<Self> {
+        let data = &message.metadata.custom_data;
+        let months = data.get("cumulative_months")?.as_u64()?;
+        let is_resub = data
+            .get("is_resub")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(months > 1);
+
+        let headline = match months {
+            _ if !is_resub => "New subscriber".to_string(),
+            1 => "1 month".to_string(),
+            months => format!("{} months", months),
+        };
+        let streak = data
+            .get("streak_months")
+            .and_then(|v| v.as_u64())
+            .filter(|streak| *streak > 1)
+            .map(|streak| format!("{}-month streak", streak));
+        let plan = data
+            .get("sub_plan")
+            .and_then(|v| v.as_str())
+            .and_then(plan_label)
+            .map(str::to_string);
+
+        let detail: Vec<String> = streak.into_iter().chain(plan).collect();
+        Some(Self {
+            headline,
+            detail: (!detail.is_empty()).then(|| detail.join(" · ")),
+        })
+    }
+}
+
+fn plan_label(plan: &str) -> Option<&'static str> {
+    match plan {
+        "Prime" => Some("Prime"),
+        "1000" => Some("Tier 1"),
+        "2000" => Some("Tier 2"),
+        "3000" => Some("Tier 3"),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_event_kind_from_mapped_type() {
+        assert_eq!(
+            OverlayEventKind::of(&MappedMessageType::Raid),
+            OverlayEventKind::Raid
+        );
+        assert_eq!(
+            OverlayEventKind::of(&MappedMessageType::Redemption),
+            OverlayEventKind::Redemption
+        );
+        assert_eq!(
+            OverlayEventKind::of(&MappedMessageType::Highlight),
+            OverlayEventKind::Chat
+        );
+        assert_eq!(
+            OverlayEventKind::Chat.builtin_template(),
+            EventTemplate::default()
+        );
+    }
+
+    #[test]
+    fn test_configured_template_overrides_builtin_per_field() {
+        let configured: EventTemplate =
+            serde_json::from_str(r##"{"accent_color": "#00ff00", "icon": ""}"##).unwrap();
+        let template = configured.or(OverlayEventKind::Subscription.builtin_template());
+        assert_eq!(template.icon.as_deref(), Some(""));
+        assert_eq!(template.duration_seconds, Some(15));
+
+        let palette = template.apply_to(ThemePalette::high_contrast());
+        assert_eq!(palette.progress, Rgb(0, 255, 0));
+        assert_eq!(palette.border, Rgb(0, 255, 0));
+        assert_eq!(palette.background, Rgb(0x24, 0x16, 0x40));
+        assert_eq!(palette.text, Rgb(255, 255, 255));
+
+        let broken = EventTemplate {
+            text_color: Some("white".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(broken.invalid_color(), Some(("text_color", "white")));
+    }
+}
@@ -0,0 +1,376 @@
+//! Importación de temas de widgets de chat de StreamElements y Streamlabs
+//!
+//! Las exportaciones de ambos servicios guardan los ajustes del widget con
+//! nombres distintos (`fontSize`, `text_size`, `nameColor`...) y a veces
+//! anidados (`widgets[].variables`, `fieldData`, o campos `{ "value": ... }`).
+//! Se recorre todo el JSON buscando los ajustes conocidos y se convierten a
+//! `DisplayConfig` con el tema `custom`.
+
+use serde_json::Value;
+
+use super::{Rgb, ThemeName};
+use crate::config::Config;
+
+/// Opacidad mínima al importar fondos (casi) transparentes: la opacidad del
+/// overlay afecta a toda la ventana, texto incluido
+pub const MIN_IMPORTED_OPACITY: f32 = 0.3;
+
+const FONT_FAMILY_KEYS: &[&str] = &["fontfamily", "fontname", "googlefont", "font"];
+const FONT_SIZE_KEYS: &[&str] = &["fontsize", "textsize", "messagefontsize"];
+const BACKGROUND_KEYS: &[&str] = &[
+    "backgroundcolor",
+    "messagebackgroundcolor",
+    "messagebackground",
+    "bgcolor",
+    "background",
+];
+const TEXT_KEYS: &[&str] = &["textcolor", "messagecolor", "fontcolor", "messagetextcolor"];
+const USERNAME_KEYS: &[&str] = &[
+    "usernamecolor",
+    "namecolor",
+    "nicknamecolor",
+    "displaynamecolor",
+];
+const BADGE_KEYS: &[&str] = &["showbadges", "displaybadges", "badges", "enablebadges"];
+
+const STREAMELEMENTS_MARKERS: &[&str] = &["widgets", "fielddata", "listener", "widgetname"];
+const STREAMLABS_MARKERS: &[&str] = &["textsize", "messagehidedelay", "hidecommonchatbots"];
+
+#[derive(Debug, thiserror::Error)]
+pub enum ThemeImportError {
+    #[error("Invalid theme export: {0}")]
+    Parse(String),
+    #[error("Not a StreamElements or Streamlabs chat widget export")]
+    UnknownFormat,
+    #[error("The export contains no supported chat settings")]
+    NoSettings,
+}
+
+/// Servicio del que procede la exportación
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ThemeSource {
+    StreamElements,
+    Streamlabs,
+}
+
+/// Ajustes reconocidos en una exportación
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportedTheme {
+    pub source: ThemeSource,
+    pub font_family: Option<String>,
+    pub font_size: Option<u32>,
+    pub background: Option<Rgb>,
+    /// Alfa del color de fondo (0.0–1.0) si venía en `rgba()` o `#rrggbbaa`
+    pub background_alpha: Option<f32>,
+    pub text: Option<Rgb>,
+    pub username: Option<Rgb>,
+    pub show_badges: Option<bool>,
+}
+
+/// Ajustes aplanados: clave normalizada -> primer valor encontrado
+struct Settings(Vec<(String, Value)>);
+
+impl Settings {
+    fn collect(value: &Value) -> Self {
+        let mut entries = Vec::new();
+        Self::walk(value, &mut entries);
+        Settings(entries)
+    }
+
+    fn walk(value: &Value, entries: &mut Vec<(String, Value)>) {
+        match value {
+            Value::Object(map) => {
+                for (key, value) in map {
+                    // Campos de SE con la forma { "type": ..., "value": ... }
+                    let value = value
+                        .as_object()
+                        .and_then(|field| field.get("value"))
+                        .unwrap_or(value);
+                    entries.push((normalize_key(key), value.clone()));
+                }
+                for value in map.values() {
+                    Self::walk(value, entries);
+                }
+            }
+            Value::Array(items) => items.iter().for_each(|item| Self::walk(item, entries)),
+            _ => {}
+        }
+    }
+
+    fn has_key(&self, keys: &[&str]) -> bool {
+        self.0.iter().any(|(key, _)| keys.contains(&key.as_str()))
+    }
+
+    /// Primer valor escalar de cualquiera de las claves, por orden de preferencia
+    fn find<T>(&self, keys: &[&str], parse: impl Fn(&Value) -> Option<T>) -> Option<T> {
+        keys.iter().find_map(|wanted| {
+            self.0
+                .iter()
+                .filter(|(key, _)| key == wanted)
+                .find_map(|(_, value)| parse(value))
+        })
+    }
+
+    /// Streamlabs usa un interruptor por tipo de insignia (`show_moderator_icons`...)
+    fn icon_toggles(&self) -> Option<bool> {
+        let toggles: Vec<bool> = self
+            .0
+            .iter()
+            .filter(|(key, _)| key.starts_with("show") && key.ends_with("icons"))
+            .filter_map(|(_, value)| as_bool(value))
+            .collect();
+        (!toggles.is_empty()).then(|| toggles.iter().any(|&shown| shown))
+    }
+}
+
+fn normalize_key(key: &str) -> String {
+    key.chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_lowercase())
+        .collect()
+}
+
+fn as_string(value: &Value) -> Option<String> {
+    value
+        .as_str()
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+}
+
+fn as_font_size(value: &Value) -> Option<u32> {
+    let size = match value {
+        Value::Number(n) => n.as_f64()?,
+        Value::String(s) => s.trim().trim_end_matches("px").trim().parse().ok()?,
+        _ => return None,
+    };
+    (size > 0.0).then(|| size.round() as u32)
+}
+
+fn as_bool(value: &Value) -> Option<bool> {
+    match value {
+        Value::Bool(b) => Some(*b),
+        Value::Number(n) => n.as_i64().map(|n| n != 0),
+        Value::String(s) => match s.trim().to_lowercase().as_str() {
+            "true" | "yes" | "on" | "1" | "show" => Some(true),
+            "false" | "no" | "off" | "0" | "hide" => Some(false),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn as_color(value: &Value) -> Option<(Rgb, f32)> {
+    parse_css_color(value.as_str()?)
+}
+
+/// Parsea `#rgb`, `#rrggbb`, `#rrggbbaa`, `rgb()`/`rgba()` y `transparent`
+pub fn parse_css_color(value: &str) -> Option<(Rgb, f32)> {
+    let value = value.trim().to_lowercase();
+    if value == "transparent" {
+        return Some((Rgb(0, 0, 0), 0.0));
+    }
+
+    if let Some(args) = value
+        .strip_prefix("rgba(")
+        .or_else(|| value.strip_prefix("rgb("))
+        .and_then(|rest| rest.strip_suffix(')'))
+    {
+        let parts: Vec<&str> = args.split(',').map(str::trim).collect();
+        let channel = |i: usize| {
+            parts
+                .get(i)?
+                .parse::<f32>()
+                .ok()
+                .map(|c| c.clamp(0.0, 255.0) as u8)
+        };
+        let alpha = match parts.get(3) {
+            Some(alpha) => alpha.parse::<f32>().ok()?.clamp(0.0, 1.0),
+            None => 1.0,
+        };
+        return Some((Rgb(channel(0)?, channel(1)?, channel(2)?), alpha));
+    }
+
+    let hex = value.strip_prefix('#')?;
+    if !hex.is_ascii() {
+        return None;
+    }
+    match hex.len() {
+        3 => {
+            let expanded: String = hex.chars().flat_map(|c| [c, c]).collect();
+            Rgb::parse_hex(&expanded).map(|rgb| (rgb, 1.0))
+        }
+        6 => Rgb::parse_hex(hex).map(|rgb| (rgb, 1.0)),
+        8 => {
+            let alpha = u8::from_str_radix(&hex[6..], 16).ok()? as f32 / 255.0;
+            Rgb::parse_hex(&hex[..6]).map(|rgb| (rgb, alpha))
+        }
+        _ => None,
+    }
+}
+
+/// Lee una exportación JSON de StreamElements o Streamlabs
+pub fn import_theme(content: &str) -> Result<ImportedTheme, ThemeImportError> {
+    let value: Value =
+        serde_json::from_str(content).map_err(|e| ThemeImportError::Parse(e.to_string()))?;
+    let settings = Settings::collect(&value);
+
+    let source = if settings.has_key(STREAMLABS_MARKERS) || settings.icon_toggles().is_some() {
+        ThemeSource::Streamlabs
+    } else if settings.has_key(STREAMELEMENTS_MARKERS) {
+        ThemeSource::StreamElements
+    } else {
+        return Err(ThemeImportError::UnknownFormat);
+    };
+
+    let background = settings.find(BACKGROUND_KEYS, as_color);
+    let theme = ImportedTheme {
+        source,
+        font_family: settings.find(FONT_FAMILY_KEYS, as_string),
+        font_size: settings.find(FONT_SIZE_KEYS, as_font_size),
+        background: background.map(|(rgb, _)| rgb),
+        background_alpha: background.map(|(_, alpha)| alpha),
+        text: settings.find(TEXT_KEYS, as_color).map(|(rgb, _)| rgb),
+        username: settings.find(USERNAME_KEYS, as_color).map(|(rgb, _)| rgb),
+        show_badges: settings
+            .find(BADGE_KEYS, as_bool)
+            .or_else(|| settings.icon_toggles()),
+    };
+
+    if theme == ImportedTheme::empty(source) {
+        return Err(ThemeImportError::NoSettings);
+    }
+    Ok(theme)
+}
+
+impl ImportedTheme {
+    fn empty(source: ThemeSource) -> Self {
+        Self {
+            source,
+            font_family: None,
+            font_size: None,
+            background: None,
+            background_alpha: None,
+            text: None,
+            username: None,
+            show_badges: None,
+        }
+    }
+
+    /// Aplica los ajustes reconocidos; los colores activan el tema `custom`
+    pub fn apply_to(&self, config: &mut Config) {
+        let display = &mut config.display;
+        if let Some(font_family) = &self.font_family {
+            display.font_family = font_family.clone();
+        }
+        if let Some(font_size) = self.font_size {
+            display.font_size = font_size.clamp(8, 96);
+        }
+        if let Some(background) = self.background {
+            display.background_color = background.to_hex();
+        }
+        if let Some(alpha) = self.background_alpha {
+            display.opacity = alpha.max(MIN_IMPORTED_OPACITY);
+        }
+        if let Some(text) = self.text {
+            display.text_color = text.to_hex();
+        }
+        if let Some(username) = self.username {
+            display.username_color = username.to_hex();
+        }
+        if self.background.is_some() || self.text.is_some() || self.username.is_some() {
+            display.theme = ThemeName::Custom;
+        }
+
+        if let Some(show_badges) = self.show_badges {
+            for platform in config.platforms.values_mut() {
+                platform.settings.enable_badges = show_badges;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_streamelements_widget_export() {
+        let export = r##"{
+            "name": "My overlay",
+            "widgets": [{
+                "type": "custom",
+                "fieldData": {
+                    "fontName": { "type": "googleFont", "value": "Roboto" },
+                    "fontSize": { "type": "number", "value": 22 },
+                    "backgroundColor": "rgba(10, 20, 30, 0.5)",
+                    "textColor": "#fff",
+                    "nameColor": "#9146FF",
+                    "displayBadges": "hide"
+                }
+            }]
+        }"##;
+
+        let theme = import_theme(export).unwrap();
+        assert_eq!(theme.source, ThemeSource::StreamElements);
+        assert_eq!(theme.font_family.as_deref(), Some("Roboto"));
+        assert_eq!(theme.font_size, Some(22));
+        assert_eq!(theme.background, Some(Rgb(10, 20, 30)));
+        assert_eq!(theme.background_alpha, Some(0.5));
+        assert_eq!(theme.text, Some(Rgb(255, 255, 255)));
+        assert_eq!(theme.username, Some(Rgb(0x91, 0x46, 0xff)));
+        assert_eq!(theme.show_badges, Some(false));
+
+        let mut config = Config::default();
+        theme.apply_to(&mut config);
+        assert_eq!(config.display.theme, ThemeName::Custom);
+        assert_eq!(config.display.username_color, "#9146ff");
+        assert!(config.platforms.values().all(|p| !p.settings.enable_badges));
+        assert!(Config::parse(&serde_json::to_string(&config).unwrap()).is_ok());
+        assert_eq!(
+            config.display.palette().unwrap().background,
+            Rgb(10, 20, 30)
+        );
+    }
+
+    #[test]
+    fn test_streamlabs_chatbox_export() {
+        let export = r##"{
+            "theme": "twitch",
+            "background_color": "transparent",
+            "text_color": "#e0e0e0",
+            "text_size": "18px",
+            "message_hide_delay": 60,
+            "show_moderator_icons": false,
+            "show_subscriber_icons": true
+        }"##;
+
+        let theme = import_theme(export).unwrap();
+        assert_eq!(theme.source, ThemeSource::Streamlabs);
+        assert_eq!(theme.font_size, Some(18));
+        assert_eq!(theme.background_alpha, Some(0.0));
+        assert_eq!(theme.show_badges, Some(true));
+
+        let mut config = Config::default();
+        theme.apply_to(&mut config);
+        assert_eq!(config.display.opacity, MIN_IMPORTED_OPACITY);
+        assert_eq!(config.display.text_color, "#e0e0e0");
+    }
+
+    #[test]
+    fn test_rejects_unrelated_json() {
+        assert!(matches!(
+            import_theme(r#"{"foo": 1}"#),
+            Err(ThemeImportError::UnknownFormat)
+        ));
+        assert!(matches!(
+            import_theme(r#"{"widgets": []}"#),
+            Err(ThemeImportError::NoSettings)
+        ));
+        assert!(matches!(import_theme("{"), Err(ThemeImportError::Parse(_))));
+        assert_eq!(
+            parse_css_color("#11223380").unwrap().0,
+            Rgb(0x11, 0x22, 0x33)
+        );
+    }
+}
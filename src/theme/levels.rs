@@ -0,0 +1,135 @@
+//! Estilo de los mensajes de chat según el nivel del usuario
+//!
+//! Con `display.user_levels.enabled`, el nombre y el borde de la ventana toman
+//! el color del nivel (broadcaster dorado, moderador verde, VIP rosa,
+//! suscriptor morado); `display.user_levels.styles` sobrescribe campo a campo.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::mapping::UserLevel;
+use crate::theme::{Rgb, ThemePalette};
+
+/// Colores de un nivel; los campos `None` heredan la apariencia normal
+#[derive(Debug, Deserialize, Serialize, Clone, Default, PartialEq)]
+#[serde(default)]
+pub struct UserLevelStyle {
+    pub username_color: Option<String>,
+    /// Borde de la ventana
+    pub border_color: Option<String>,
+}
+
+impl UserLevelStyle {
+    /// Estilo integrado; los niveles sin distintivo no cambian la apariencia
+    pub fn builtin(level: &UserLevel) -> Self {
+        let color = match level {
+            UserLevel::Broadcaster => "#ffd700",
+            UserLevel::Moderator => "#00ad03",
+            UserLevel::Vip => "#e005b9",
+            UserLevel::Subscriber => "#8205b4",
+            _ => return Self::default(),
+        };
+        Self {
+            username_color: Some(color.to_string()),
+            border_color: Some(color.to_string()),
+        }
+    }
+
+    /// Campos de `self` con los que falten tomados de `base`
+    pub fn or(self, base: UserLevelStyle) -> UserLevelStyle {
+        UserLevelStyle {
+            username_color: self.username_color.or(base.username_color),
+            border_color: self.border_color.or(base.border_color),
+        }
+    }
+
+    pub fn has_colors(&self) -> bool {
+        self.username_color.is_some() || self.border_color.is_some()
+    }
+
+    /// Colores del nivel aplicados sobre `palette`
+    pub fn apply_to(&self, mut palette: ThemePalette) -> ThemePalette {
+        let color = |value: &Option<String>| value.as_deref().and_then(Rgb::parse_hex);
+        if let Some(username) = color(&self.username_color) {
+            palette.username = username;
+        }
+        if let Some(border) = color(&self.border_color) {
+            palette.border = border;
+        }
+        palette
+    }
+
+    /// Primer color que no es `#rrggbb`, con el nombre de su campo
+    pub fn invalid_color(&self) -> Option<(&'static str, &str)> {
+        [
+            ("username_color", &self.username_color),
+            ("border_color", &self.border_color),
+        ]
+        .into_iter()
+        .find_map(|(field, value)| {
+            value
+                .as_deref()
+                .filter(|color| Rgb::parse_hex(color).is_none())
+                .map(|color| (field, color))
+        })
+    }
+}
+
+/// `display.user_levels`
+#[derive(Debug, Deserialize, Serialize, Clone, Default, PartialEq)]
+#[serde(default)]
+pub struct UserLevelStyles {
+    /// Desactivado por defecto para no cambiar la apariencia de overlays existentes
+    pub enabled: bool,
+    /// Se combinan con los estilos integrados
+    pub styles: HashMap<UserLevel, UserLevelStyle>,
+}
+
+impl UserLevelStyles {
+    /// Estilo efectivo de un nivel; `None` si está desactivado o el nivel no
+    /// tiene colores
+    pub fn style_for(&self, level: &UserLevel) -> Option<UserLevelStyle> {
+        if !self.enabled {
+            return None;
+        }
+        let builtin = UserLevelStyle::builtin(level);
+        let style = match self.styles.get(level) {
+            Some(configured) => configured.clone().or(builtin),
+            None => builtin,
+        };
+        style.has_colors().then_some(style)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_configured_styles_override_builtin_per_field() {
+        let mut levels = UserLevelStyles::default();
+        assert_eq!(levels.style_for(&UserLevel::Broadcaster), None);
+
+        levels.enabled = true;
+        levels.styles.insert(
+            UserLevel::Moderator,
+            UserLevelStyle {
+                border_color: Some("#0000ff".to_string()),
+                ..Default::default()
+            },
+        );
+        let moderator = levels.style_for(&UserLevel::Moderator).unwrap();
+        assert_eq!(moderator.username_color.as_deref(), Some("#00ad03"));
+
+        let palette = moderator.apply_to(ThemePalette::high_contrast());
+        assert_eq!(palette.username, Rgb(0, 173, 3));
+        assert_eq!(palette.border, Rgb(0, 0, 255));
+        assert_eq!(palette.text, ThemePalette::high_contrast().text);
+
+        assert_eq!(levels.style_for(&UserLevel::Normal), None);
+        assert!(levels
+            .style_for(&UserLevel::Broadcaster)
+            .is_some_and(|style| style.invalid_color().is_none()));
+    }
+}
@@ -0,0 +1,314 @@
+//! Temas con nombre que se cambian en caliente
+//!
+//! Además de los integrados (`dark`, `light` y `high_contrast`),
+//! `display.themes` define temas propios con sus colores, fuente, radio del
+//! borde y opacidad. `display.active_theme` elige el tema al arrancar, y la
+//! API de control (`/themes`) o las órdenes IPC `theme` y `next_theme` lo
+//! cambian sin reiniciar; el cambio se pierde al recargar la configuración.
+
+use std::collections::BTreeMap;
+use std::sync::{Mutex, OnceLock};
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::config::DisplayConfig;
+use crate::theme::ThemeName;
+
+static GLOBAL_SWITCHER: OnceLock<ThemeSwitcher> = OnceLock::new();
+
+/// Nombre reservado: `POST /themes/next` pasa al siguiente tema
+pub const NEXT_THEME: &str = "next";
+
+/// Tema con nombre; los campos `None` conservan el valor de `display`
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct NamedTheme {
+    /// Tema integrado en el que se basa; `custom` usa los colores de abajo
+    pub theme: ThemeName,
+    pub background_color: Option<String>,
+    pub text_color: Option<String>,
+    pub username_color: Option<String>,
+    pub font_family: Option<String>,
+    pub font_size: Option<u32>,
+    pub border_radius: Option<u32>,
+    pub opacity: Option<f32>,
+}
+
+impl Default for NamedTheme {
+    fn default() -> Self {
+        Self {
+            theme: ThemeName::Custom,
+            background_color: None,
+            text_color: None,
+            username_color: None,
+            font_family: None,
+            font_size: None,
+            border_radius: None,
+            opacity: None,
+        }
+    }
+}
+
+impl NamedTheme {
+    fn colors(background: &str, text: &str, username: &str) -> Self {
+        Self {
+            background_color: Some(background.to_string()),
+            text_color: Some(text.to_string()),
+            username_color: Some(username.to_string()),
+            ..Self::default()
+        }
+    }
+
+    /// Colores definidos en el tema, con el nombre del campo para los errores
+    pub fn color_fields(&self) -> impl Iterator<Item = (&'static str, &str)> {
+        [
+            ("background_color", &self.background_color),
+            ("text_color", &self.text_color),
+            ("username_color", &self.username_color),
+        ]
+        .into_iter()
+        .filter_map(|(field, color)| color.as_deref().map(|color| (field, color)))
+    }
+
+    /// Aplica el tema sobre `display`
+    pub fn apply_to(&self, display: &mut DisplayConfig) {
+        display.theme = self.theme;
+        for (target, value) in [
+            (&mut display.background_color, &self.background_color),
+            (&mut display.text_color, &self.text_color),
+            (&mut display.username_color, &self.username_color),
+            (&mut display.font_family, &self.font_family),
+        ] {
+            if let Some(value) = value {
+                target.clone_from(value);
+            }
+        }
+        if let Some(font_size) = self.font_size {
+            display.font_size = font_size;
+        }
+        if let Some(border_radius) = self.border_radius {
+            display.border_radius = border_radius;
+        }
+        if let Some(opacity) = self.opacity {
+            display.opacity = opacity;
+        }
+    }
+}
+
+/// Temas integrados por nombre
+pub fn builtin_themes() -> BTreeMap<String, NamedTheme> {
+    BTreeMap::from([
+        (
+            "dark".to_string(),
+            NamedTheme::colors("#1e1e1e", "#ffffff", "#bf94ff"),
+        ),
+        (
+            "light".to_string(),
+            NamedTheme {
+                opacity: Some(0.95),
+                ..NamedTheme::colors("#f4f4f5", "#18181b", "#6441a5")
+            },
+        ),
+        (
+            "high_contrast".to_string(),
+            NamedTheme {
+                theme: ThemeName::HighContrast,
+                ..NamedTheme::default()
+            },
+        ),
+    ])
+}
+
+/// Si `name` es un tema integrado o uno de `display.themes`
+pub fn theme_exists(display: &DisplayConfig, name: &str) -> bool {
+    display.themes.contains_key(name) || builtin_themes().contains_key(name)
+}
+
+/// Tema activo y temas disponibles, tal como se exponen en `GET /themes`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, JsonSchema)]
+pub struct ThemeStatus {
+    /// `None` con la apariencia de `display` sin tema con nombre
+    pub active: Option<String>,
+    pub available: Vec<String>,
+}
+
+#[derive(Debug, Default)]
+struct SwitcherState {
+    /// `display` de la configuración, sin ningún tema aplicado
+    base: Option<DisplayConfig>,
+    themes: BTreeMap<String, NamedTheme>,
+    active: Option<String>,
+}
+
+impl SwitcherState {
+    fn display(&self) -> Option<DisplayConfig> {
+        let mut display = self.base.clone()?;
+        if let Some(theme) = self.active.as_ref().and_then(|name| self.themes.get(name)) {
+            theme.apply_to(&mut display);
+        }
+        display.active_theme.clone_from(&self.active);
+        Some(display)
+    }
+}
+
+/// Tema con nombre activo y apariencia resultante
+#[derive(Debug, Default)]
+pub struct ThemeSwitcher {
+    state: Mutex<SwitcherState>,
+}
+
+impl ThemeSwitcher {
+    pub fn global() -> &'static ThemeSwitcher {
+        GLOBAL_SWITCHER.get_or_init(ThemeSwitcher::default)
+    }
+
+    /// Toma `display` como base y devuelve la apariencia con `active_theme`
+    /// aplicado; un tema desconocido se ignora
+    pub fn configure(&self, display: &DisplayConfig) -> DisplayConfig {
+        let mut themes = builtin_themes();
+        themes.extend(display.themes.clone());
+        let active = display.active_theme.clone().filter(|name| {
+            let known = themes.contains_key(name);
+            if !known {
+                eprintln!("⚠️ Unknown theme '{}', using display colors", name);
+            }
+            known
+        });
+
+        let mut state = self.lock();
+        let mut base = display.clone();
+        base.active_theme = None;
+        *state = SwitcherState {
+            base: Some(base),
+            themes,
+            active,
+        };
+        state.display().unwrap_or_else(|| display.clone())
+    }
+
+    /// Activa `name`, o vuelve a la apariencia de `display` con `None`
+    pub fn switch(&self, name: Option<&str>) -> Result<DisplayConfig, String> {
+        let mut state = self.lock();
+        if let Some(name) = name {
+            if !state.themes.contains_key(name) {
+                return Err(format!("Theme '{}' not found", name));
+            }
+        }
+        state.active = name.map(str::to_string);
+        state
+            .display()
+            .ok_or_else(|| "Themes are not configured".to_string())
+    }
+
+    /// Activa el siguiente tema por orden alfabético, volviendo al primero
+    pub fn next(&self) -> Result<DisplayConfig, String> {
+        let next = {
+            let state = self.lock();
+            let mut names = state.themes.keys();
+            match &state.active {
+                Some(active) => names
+                    .clone()
+                    .skip_while(|name| *name != active)
+                    .nth(1)
+                    .or_else(|| names.next()),
+                None => names.next(),
+            }
+            .cloned()
+        };
+        self.switch(next.as_deref())
+    }
+
+    pub fn status(&self) -> ThemeStatus {
+        let state = self.lock();
+        ThemeStatus {
+            active: state.active.clone(),
+            available: state.themes.keys().cloned().collect(),
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, SwitcherState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    #[test]
+    fn test_switch_applies_theme_over_config_display() {
+        let mut display = Config::default().display;
+        display.themes.insert(
+            "stream".to_string(),
+            NamedTheme {
+                font_family: Some("Inter".to_string()),
+                border_radius: Some(16),
+                ..NamedTheme::colors("#000000", "#eeeeee", "#ff0000")
+            },
+        );
+        display.active_theme = Some("light".to_string());
+
+        let switcher = ThemeSwitcher::default();
+        let light = switcher.configure(&display);
+        assert_eq!(light.theme, ThemeName::Custom);
+        assert_eq!(light.background_color, "#f4f4f5");
+        assert_eq!(light.active_theme.as_deref(), Some("light"));
+
+        let stream = switcher.switch(Some("stream")).unwrap();
+        assert_eq!(stream.background_color, "#000000");
+        assert_eq!(stream.font_family, "Inter");
+        assert_eq!(stream.border_radius, 16);
+        // Los campos que el tema no define vuelven a los de la configuración
+        assert_eq!(stream.opacity, display.opacity);
+
+        let base = switcher.switch(None).unwrap();
+        assert_eq!(base.theme, display.theme);
+        assert_eq!(base.background_color, display.background_color);
+        assert_eq!(base.active_theme, None);
+
+        assert!(switcher.switch(Some("missing")).is_err());
+        assert_eq!(
+            switcher.status().available,
+            vec!["dark", "high_contrast", "light", "stream"]
+        );
+    }
+
+    #[test]
+    fn test_next_cycles_through_themes() {
+        let switcher = ThemeSwitcher::default();
+        switcher.configure(&Config::default().display);
+
+        let names: Vec<Option<String>> = (0..4)
+            .map(|_| switcher.next().unwrap().active_theme)
+            .collect();
+        assert_eq!(
+            names,
+            ["dark", "high_contrast", "light", "dark"].map(|name| Some(name.to_string()))
+        );
+        assert_eq!(switcher.status().active.as_deref(), Some("dark"));
+    }
+
+    #[test]
+    fn test_invalid_themes_are_rejected() {
+        let mut config = Config::default();
+        config.display.active_theme = Some("neon".to_string());
+        assert!(Config::parse(&serde_json::to_string(&config).unwrap()).is_err());
+
+        config.display.themes.insert(
+            "neon".to_string(),
+            NamedTheme::colors("#000000", "white", "#ff00ff"),
+        );
+        assert!(Config::parse(&serde_json::to_string(&config).unwrap()).is_err());
+
+        config.display.themes.get_mut("neon").unwrap().text_color = Some("#ffffff".to_string());
+        assert!(Config::parse(&serde_json::to_string(&config).unwrap()).is_ok());
+
+        // `next` chocaría con `POST /themes/next`
+        let neon = config.display.themes.remove("neon").unwrap();
+        config.display.themes.insert(NEXT_THEME.to_string(), neon);
+        config.display.active_theme = None;
+        assert!(Config::parse(&serde_json::to_string(&config).unwrap()).is_err());
+    }
+}
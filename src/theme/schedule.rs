@@ -0,0 +1,174 @@
+//! Programación de temas por hora del día
+//!
+//! `ThemeScheduler` guarda la apariencia configurada en `display` y, cuando la
+//! hora entra o sale de una franja de `theme_schedule`, devuelve la nueva
+//! apariencia para aplicarla en caliente a las ventanas siguientes.
+
+use chrono::NaiveTime;
+
+use crate::config::{Config, DisplayConfig, ThemeScheduleConfig, ThemeScheduleEntry};
+
+/// Parsea una hora `HH:MM`
+pub fn parse_time(value: &str) -> Option<NaiveTime> {
+    NaiveTime::parse_from_str(value.trim(), "%H:%M").ok()
+}
+
+impl ThemeScheduleEntry {
+    /// Si `time` cae en la franja (inicio incluido, fin excluido)
+    pub fn contains(&self, time: NaiveTime) -> bool {
+        let (Some(start), Some(end)) = (parse_time(&self.start), parse_time(&self.end)) else {
+            return false;
+        };
+        if start <= end {
+            start <= time && time < end
+        } else {
+            time >= start || time < end
+        }
+    }
+
+    /// Aplica el tema y los colores de la franja sobre `display`
+    pub fn apply_to(&self, display: &mut DisplayConfig) {
+        display.theme = self.theme;
+        if let Some(color) = &self.background_color {
+            display.background_color = color.clone();
+        }
+        if let Some(color) = &self.text_color {
+            display.text_color = color.clone();
+        }
+        if let Some(color) = &self.username_color {
+            display.username_color = color.clone();
+        }
+    }
+}
+
+/// Decide qué apariencia corresponde a cada hora
+#[derive(Debug)]
+pub struct ThemeScheduler {
+    schedule: ThemeScheduleConfig,
+    base: DisplayConfig,
+    /// Franja aplicada; `None` si aún no se ha evaluado
+    active: Option<Option<usize>>,
+}
+
+impl ThemeScheduler {
+    pub fn new(config: &Config) -> Self {
+        Self {
+            schedule: config.theme_schedule.clone(),
+            base: config.display.clone(),
+            active: None,
+        }
+    }
+
+    /// Toma una configuración recargada; la siguiente `update` vuelve a aplicar la franja
+    pub fn reset(&mut self, config: &Config) {
+        *self = Self::new(config);
+    }
+
+    pub fn check_interval(&self) -> std::time::Duration {
+        std::time::Duration::from_secs(self.schedule.check_interval_secs.max(1))
+    }
+
+    /// Primera franja que contiene `time`
+    pub fn entry_at(&self, time: NaiveTime) -> Option<usize> {
+        self.schedule
+            .entries
+            .iter()
+            .position(|entry| entry.contains(time))
+    }
+
+    /// Nueva apariencia si la franja activa cambió desde la última llamada
+    pub fn update(&mut self, time: NaiveTime) -> Option<DisplayConfig> {
+        if !self.schedule.enabled {
+            return None;
+        }
+        let entry = self.entry_at(time);
+        if self.active == Some(entry) {
+            return None;
+        }
+        // Al arrancar fuera de toda franja no hay nada que cambiar
+        let first = self.active.is_none();
+        self.active = Some(entry);
+        if first && entry.is_none() {
+            return None;
+        }
+
+        let mut display = self.base.clone();
+        if let Some(i) = entry {
+            self.schedule.entries[i].apply_to(&mut display);
+        }
+        Some(display)
+    }
+
+    /// Descripción de la franja activa para los logs
+    pub fn describe_active(&self) -> String {
+        match self.active.flatten() {
+            Some(i) => {
+                let entry = &self.schedule.entries[i];
+                format!("{:?} ({}–{})", entry.theme, entry.start, entry.end)
+            }
+            None => format!("{:?} (default)", self.base.theme),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::theme::ThemeName;
+
+    fn night_config() -> Config {
+        let mut config = Config::default();
+        config.theme_schedule.enabled = true;
+        config.theme_schedule.entries = vec![ThemeScheduleEntry {
+            start: "22:00".to_string(),
+            end: "06:00".to_string(),
+            theme: ThemeName::Custom,
+            background_color: Some("#000000".to_string()),
+            text_color: None,
+            username_color: None,
+        }];
+        config
+    }
+
+    fn at(value: &str) -> NaiveTime {
+        parse_time(value).unwrap()
+    }
+
+    #[test]
+    fn test_entry_crossing_midnight() {
+        let entry = &night_config().theme_schedule.entries[0];
+        assert!(entry.contains(at("23:30")));
+        assert!(entry.contains(at("00:00")));
+        assert!(!entry.contains(at("06:00")));
+        assert!(!entry.contains(at("12:00")));
+        assert!(parse_time("25:00").is_none());
+    }
+
+    #[test]
+    fn test_scheduler_switches_only_on_changes() {
+        let config = night_config();
+        let mut scheduler = ThemeScheduler::new(&config);
+
+        assert!(scheduler.update(at("20:00")).is_none());
+        let night = scheduler.update(at("22:00")).unwrap();
+        assert_eq!(night.theme, ThemeName::Custom);
+        assert_eq!(night.background_color, "#000000");
+        assert_eq!(night.text_color, config.display.text_color);
+        assert!(scheduler.update(at("23:00")).is_none());
+
+        let day = scheduler.update(at("07:00")).unwrap();
+        assert_eq!(day.theme, config.display.theme);
+        assert_eq!(day.background_color, config.display.background_color);
+
+        // Tras recargar la configuración se vuelve a aplicar la franja actual
+        scheduler.reset(&config);
+        assert!(scheduler.update(at("23:00")).is_some());
+    }
+
+    #[test]
+    fn test_invalid_schedule_is_rejected() {
+        let mut config = night_config();
+        config.theme_schedule.entries[0].end = "6pm".to_string();
+        assert!(Config::parse(&serde_json::to_string(&config).unwrap()).is_err());
+    }
+}
@@ -0,0 +1,351 @@
+//! Plantilla del contenido de las ventanas
+//!
+//! `display.template` describe qué muestra cada ventana con marcadores entre
+//! llaves. La primera línea es la cabecera (en negrita, junto al icono del
+//! evento y el avatar) y el resto es el cuerpo, donde se dibujan los emotes.
+//! Sin salto de línea todo va en el cuerpo:
+//!
+//! ```text
+//! {username}\n{content}           por defecto: nombre encima del mensaje
+//! {badge} {username}: {content}   todo en una línea
+//! {content}                       solo el mensaje
+//! ```
+//!
+//! Marcadores: `username`, `content`, `badge` (símbolo del nivel del usuario),
+//! `level`, `platform`, `channel` y `time` (hora local, `HH:MM`); `{{` y `}}`
+//! escriben llaves. La plantilla se analiza una vez al cargar la configuración
+//! y la usan los dos renderers.
+
+use serde::{Deserialize, Serialize};
+
+use crate::connection::ChatMessage;
+use crate::mapping::UserLevel;
+
+/// Nombre encima del mensaje, como sin plantilla
+pub const DEFAULT_TEMPLATE: &str = "{username}\n{content}";
+
+/// Dato del mensaje que sustituye a un marcador
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Placeholder {
+    Username,
+    Content,
+    Badge,
+    Level,
+    Platform,
+    Channel,
+    Time,
+}
+
+impl Placeholder {
+    pub const ALL: [Placeholder; 7] = [
+        Placeholder::Username,
+        Placeholder::Content,
+        Placeholder::Badge,
+        Placeholder::Level,
+        Placeholder::Platform,
+        Placeholder::Channel,
+        Placeholder::Time,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Placeholder::Username => "username",
+            Placeholder::Content => "content",
+            Placeholder::Badge => "badge",
+            Placeholder::Level => "level",
+            Placeholder::Platform => "platform",
+            Placeholder::Channel => "channel",
+            Placeholder::Time => "time",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|placeholder| placeholder.name() == name)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum TemplateError {
+    #[error("unknown placeholder '{{{0}}}'")]
+    UnknownPlaceholder(String),
+    #[error("unclosed '{{' at position {0}")]
+    Unclosed(usize),
+    #[error("unmatched '}}' at position {0} (use '}}}}' for a literal brace)")]
+    UnmatchedClose(usize),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    Text(String),
+    Field(Placeholder),
+}
+
+fn parse_segments(source: &str, offset: usize) -> Result<Vec<Segment>, TemplateError> {
+    let mut segments = Vec::new();
+    let mut text = String::new();
+    let mut chars = source.char_indices().peekable();
+    while let Some((index, c)) = chars.next() {
+        match c {
+            '{' if chars.next_if(|(_, next)| *next == '{').is_some() => text.push('{'),
+            '}' if chars.next_if(|(_, next)| *next == '}').is_some() => text.push('}'),
+            '{' => {
+                let mut name = String::new();
+                loop {
+                    match chars.next() {
+                        Some((_, '}')) => break,
+                        Some((_, c)) => name.push(c),
+                        None => return Err(TemplateError::Unclosed(offset + index)),
+                    }
+                }
+                let placeholder = Placeholder::from_name(name.trim())
+                    .ok_or(TemplateError::UnknownPlaceholder(name))?;
+                if !text.is_empty() {
+                    segments.push(Segment::Text(std::mem::take(&mut text)));
+                }
+                segments.push(Segment::Field(placeholder));
+            }
+            '}' => return Err(TemplateError::UnmatchedClose(offset + index)),
+            c => text.push(c),
+        }
+    }
+    if !text.is_empty() {
+        segments.push(Segment::Text(text));
+    }
+    Ok(segments)
+}
+
+/// Plantilla ya analizada; en la configuración se escribe como texto
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct WindowTemplate {
+    source: String,
+    header: Vec<Segment>,
+    body: Vec<Segment>,
+}
+
+impl Default for WindowTemplate {
+    fn default() -> Self {
+        Self::parse(DEFAULT_TEMPLATE).expect("default template is valid")
+    }
+}
+
+impl TryFrom<String> for WindowTemplate {
+    type Error = TemplateError;
+
+    fn try_from(source: String) -> Result<Self, Self::Error> {
+        Self::parse(&source)
+    }
+}
+
+impl From<WindowTemplate> for String {
+    fn from(template: WindowTemplate) -> Self {
+        template.source
+    }
+}
+
+/// Textos de una ventana tras aplicar la plantilla
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RenderedWindow {
+    /// Cabecera; vacía si la plantilla es de una sola línea
+    pub header: String,
+    pub body: String,
+    /// Carácter del cuerpo donde empieza el primer `{content}`; `None` si no
+    /// aparece en el cuerpo (el mensaje se muestra sin emotes)
+    pub content_offset: Option<usize>,
+}
+
+impl RenderedWindow {
+    /// Emotes del mensaje (rangos de carácter sobre `content`) en su sitio
+    /// dentro del cuerpo
+    pub fn place_emotes(
+        &self,
+        emotes: Vec<twitch_irc::message::Emote>,
+    ) -> Vec<twitch_irc::message::Emote> {
+        let Some(offset) = self.content_offset else {
+            return Vec::new();
+        };
+        emotes
+            .into_iter()
+            .map(|mut emote| {
+                emote.char_range = emote.char_range.start + offset..emote.char_range.end + offset;
+                emote
+            })
+            .collect()
+    }
+}
+
+/// Símbolo del nivel del usuario para `{badge}`; vacío para el resto
+fn badge_symbol(level: &UserLevel) -> &'static str {
+    match level {
+        UserLevel::Broadcaster => "🎥",
+        UserLevel::Moderator => "🛡",
+        UserLevel::Vip => "💎",
+        UserLevel::Subscriber => "⭐",
+        UserLevel::Staff | UserLevel::Admin | UserLevel::GlobalModerator => "🔧",
+        UserLevel::Normal | UserLevel::Unknown => "",
+    }
+}
+
+impl WindowTemplate {
+    pub fn parse(source: &str) -> Result<Self, TemplateError> {
+        let (header, body) = match source.split_once('\n') {
+            Some((header, body)) => (
+                parse_segments(header, 0)?,
+                parse_segments(body, header.len() + 1)?,
+            ),
+            None => (Vec::new(), parse_segments(source, 0)?),
+        };
+        Ok(Self {
+            source: source.to_string(),
+            header,
+            body,
+        })
+    }
+
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    pub fn render(&self, message: &ChatMessage) -> RenderedWindow {
+        let level = UserLevel::of(message);
+        let value = |placeholder: Placeholder| -> String {
+            match placeholder {
+                Placeholder::Username => message
+                    .display_name
+                    .clone()
+                    .unwrap_or_else(|| message.username.clone()),
+                Placeholder::Content => message.content.clone(),
+                Placeholder::Badge => badge_symbol(&level).to_string(),
+                Placeholder::Level => serde_json::to_value(&level)
+                    .ok()
+                    .and_then(|v| v.as_str().map(str::to_string))
+                    .unwrap_or_default(),
+                Placeholder::Platform => message.platform.clone(),
+                Placeholder::Channel => message.channel.clone(),
+                Placeholder::Time => chrono::DateTime::<chrono::Local>::from(message.timestamp)
+                    .format("%H:%M")
+                    .to_string(),
+            }
+        };
+
+        let mut header = String::new();
+        for segment in &self.header {
+            match segment {
+                Segment::Text(text) => header.push_str(text),
+                Segment::Field(placeholder) => header.push_str(&value(*placeholder)),
+            }
+        }
+
+        let mut body = String::new();
+        let mut content_offset = None;
+        for segment in &self.body {
+            match segment {
+                Segment::Text(text) => body.push_str(text),
+                Segment::Field(Placeholder::Content) if content_offset.is_none() => {
+                    content_offset = Some(body.chars().count());
+                    body.push_str(&message.content);
+                }
+                Segment::Field(placeholder) => body.push_str(&value(*placeholder)),
+            }
+        }
+
+        // Un `{badge}` vacío no deja espacios delante del texto
+        let leading = body
+            .chars()
+            .take_while(|c| c.is_whitespace())
+            .count()
+            .min(content_offset.unwrap_or(usize::MAX));
+        let body: String = body.chars().skip(leading).collect();
+        let mut body = body.trim_end().to_string();
+
+        // La traducción (`translation`) va en una línea aparte bajo el mensaje
+        let translation = message
+            .metadata
+            .custom_data
+            .get(crate::translation::TRANSLATION_KEY)
+            .and_then(|translation| translation.as_str());
+        if let (Some(translation), Some(_)) = (translation, content_offset) {
+            body.push('\n');
+            body.push_str(translation);
+        }
+        RenderedWindow {
+            header: header.trim().to_string(),
+            body,
+            content_offset: content_offset.map(|offset| offset - leading),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn emote(range: std::ops::Range<usize>) -> twitch_irc::message::Emote {
+        twitch_irc::message::Emote {
+            id: "25".to_string(),
+            code: "Kappa".to_string(),
+            char_range: range,
+        }
+    }
+
+    #[test]
+    fn test_default_template_keeps_username_on_top() {
+        let rendered = WindowTemplate::default()
+            .render(&ChatMessage::test("Viewer", "hola Kappa").with_level(UserLevel::Normal));
+        assert_eq!(rendered.header, "Viewer");
+        assert_eq!(rendered.body, "hola Kappa");
+        assert_eq!(rendered.place_emotes(vec![emote(5..10)])[0].char_range, 5..10);
+
+        // La traducción va en su propia línea, después del mensaje
+        let translated = ChatMessage::test("Viewer", "hola Kappa")
+            .with_level(UserLevel::Normal)
+            .with_data(crate::translation::TRANSLATION_KEY, "hello Kappa");
+        let rendered = WindowTemplate::default().render(&translated);
+        assert_eq!(rendered.body, "hola Kappa\nhello Kappa");
+        assert_eq!(rendered.place_emotes(vec![emote(5..10)])[0].char_range, 5..10);
+    }
+
+    #[test]
+    fn test_inline_template_shifts_emotes() {
+        let template = WindowTemplate::parse("{badge} {username}: {content}").unwrap();
+
+        let rendered = template
+            .render(&ChatMessage::test("Viewer", "Kappa ñ").with_level(UserLevel::Moderator));
+        assert_eq!(rendered.header, "");
+        assert_eq!(rendered.body, "🛡 Viewer: Kappa ñ");
+        assert_eq!(rendered.place_emotes(vec![emote(0..5)])[0].char_range, 10..15);
+
+        // Sin insignia no quedan espacios delante
+        let rendered =
+            template.render(&ChatMessage::test("Viewer", "Kappa").with_level(UserLevel::Normal));
+        assert_eq!(rendered.body, "Viewer: Kappa");
+        assert_eq!(rendered.content_offset, Some(8));
+
+        let header_only = WindowTemplate::parse("{username} ({platform})\n{{{level}}}").unwrap();
+        let rendered =
+            header_only.render(&ChatMessage::test("Viewer", "Kappa").with_level(UserLevel::Vip));
+        assert_eq!(rendered.header, "Viewer (twitch)");
+        assert_eq!(rendered.body, "{vip}");
+        assert!(rendered.place_emotes(vec![emote(0..5)]).is_empty());
+    }
+
+    #[test]
+    fn test_invalid_templates_are_rejected() {
+        assert_eq!(
+            WindowTemplate::parse("{user}: {content}"),
+            Err(TemplateError::UnknownPlaceholder("user".to_string()))
+        );
+        assert_eq!(
+            WindowTemplate::parse("{username}\n{content"),
+            Err(TemplateError::Unclosed(11))
+        );
+        assert_eq!(
+            WindowTemplate::parse("a } b"),
+            Err(TemplateError::UnmatchedClose(2))
+        );
+
+        let config: WindowTemplate = serde_json::from_str("\"{content}\"").unwrap();
+        assert_eq!(config.source(), "{content}");
+        assert!(serde_json::from_str::<WindowTemplate>("\"{nope}\"").is_err());
+    }
+}
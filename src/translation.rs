@@ -0,0 +1,364 @@
+//! Detección de idioma y traducción de los mensajes
+//!
+//! Con `translation.enabled` se detecta el idioma de cada mensaje (whatlang,
+//! feature `translation`) y, si no es `target_language` ni está en
+//! `skip_languages`, se pide la traducción a LibreTranslate o DeepL. La
+//! traducción se guarda en `custom_data["translation"]` y las ventanas la
+//! muestran debajo del texto original. Sin la feature se avisa una vez en el
+//! log y no se traduce nada.
+//!
+//! La petición se hace dentro del pipeline: `timeout_ms` acota lo que puede
+//! retrasar un mensaje, y si el servicio falla el mensaje sale sin traducir.
+
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::connection::ChatMessage;
+use crate::health::ErrorBudget;
+
+static GLOBAL_TRANSLATOR: OnceLock<Translator> = OnceLock::new();
+
+/// Clave en `custom_data` con la traducción del mensaje
+pub const TRANSLATION_KEY: &str = "translation";
+/// Clave en `custom_data` con el idioma detectado (ISO 639-1)
+pub const LANGUAGE_KEY: &str = "language";
+
+/// Servicio de traducción
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum TranslationProvider {
+    #[default]
+    LibreTranslate,
+    DeepL,
+}
+
+/// `translation`
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct TranslationConfig {
+    /// Desactivado por defecto
+    pub enabled: bool,
+    pub provider: TranslationProvider,
+    /// Endpoint de traducción (`.../translate` o `.../v2/translate`)
+    pub url: String,
+    /// Clave del servicio; opcional en instancias propias de LibreTranslate
+    pub api_key: Option<String>,
+    /// Idioma al que se traduce (ISO 639-1)
+    pub target_language: String,
+    /// Idiomas que no se traducen, además de `target_language`
+    pub skip_languages: Vec<String>,
+    /// Caracteres (sin emotes) por debajo de los que no se detecta el idioma
+    pub min_chars: usize,
+    /// Confianza mínima de la detección (0-1)
+    pub min_confidence: f64,
+    pub timeout_ms: u64,
+}
+
+impl Default for TranslationConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            provider: TranslationProvider::LibreTranslate,
+            url: "https://libretranslate.com/translate".to_string(),
+            api_key: None,
+            target_language: "en".to_string(),
+            skip_languages: Vec::new(),
+            min_chars: 12,
+            min_confidence: 0.5,
+            timeout_ms: 2000,
+        }
+    }
+}
+
+impl TranslationConfig {
+    /// `true` si los mensajes en `language` se dejan como están
+    pub fn skips(&self, language: &str) -> bool {
+        language.eq_ignore_ascii_case(&self.target_language)
+            || self
+                .skip_languages
+                .iter()
+                .any(|skip| skip.eq_ignore_ascii_case(language))
+    }
+}
+
+/// Texto del mensaje sin los emotes, que confunden la detección
+fn detection_text(message: &ChatMessage) -> String {
+    crate::text::words(&message.content)
+        .into_iter()
+        .filter(|(_, word)| !message.emotes.iter().any(|emote| emote.name == *word))
+        .map(|(_, word)| word)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Idioma (ISO 639-1) y confianza de la detección; `None` si no se reconoce
+/// o el idioma no tiene código de dos letras
+#[cfg(feature = "translation")]
+pub fn detect_language(text: &str) -> Option<(&'static str, f64)> {
+    let info = whatlang::detect(text)?;
+    Some((iso_639_1(info.lang())?, info.confidence()))
+}
+
+#[cfg(not(feature = "translation"))]
+pub fn detect_language(_text: &str) -> Option<(&'static str, f64)> {
+    None
+}
+
+/// Código de dos letras que entienden LibreTranslate y DeepL
+#[cfg(feature = "translation")]
+fn iso_639_1(lang: whatlang::Lang) -> Option<&'static str> {
+    use whatlang::Lang;
+    Some(match lang {
+        Lang::Eng => "en",
+        Lang::Spa => "es",
+        Lang::Por => "pt",
+        Lang::Fra => "fr",
+        Lang::Deu => "de",
+        Lang::Ita => "it",
+        Lang::Nld => "nl",
+        Lang::Rus => "ru",
+        Lang::Ukr => "uk",
+        Lang::Pol => "pl",
+        Lang::Ces => "cs",
+        Lang::Slk => "sk",
+        Lang::Slv => "sl",
+        Lang::Hun => "hu",
+        Lang::Ron => "ro",
+        Lang::Bul => "bg",
+        Lang::Ell => "el",
+        Lang::Tur => "tr",
+        Lang::Swe => "sv",
+        Lang::Dan => "da",
+        Lang::Nob => "nb",
+        Lang::Fin => "fi",
+        Lang::Est => "et",
+        Lang::Lav => "lv",
+        Lang::Lit => "lt",
+        Lang::Cat => "ca",
+        Lang::Ara => "ar",
+        Lang::Heb => "he",
+        Lang::Pes => "fa",
+        Lang::Hin => "hi",
+        Lang::Ben => "bn",
+        Lang::Urd => "ur",
+        Lang::Ind => "id",
+        Lang::Vie => "vi",
+        Lang::Tha => "th",
+        Lang::Tgl => "tl",
+        Lang::Jpn => "ja",
+        Lang::Kor => "ko",
+        Lang::Cmn => "zh",
+        Lang::Epo => "eo",
+        _ => return None,
+    })
+}
+
+/// Cuerpo de la petición al servicio
+pub fn request_body(config: &TranslationConfig, text: &str, source: &str) -> serde_json::Value {
+    match config.provider {
+        TranslationProvider::LibreTranslate => {
+            let mut body = serde_json::json!({
+                "q": text,
+                "source": source,
+                "target": config.target_language,
+                "format": "text",
+            });
+            if let Some(key) = &config.api_key {
+                body["api_key"] = key.clone().into();
+            }
+            body
+        }
+        // DeepL quiere los códigos en mayúsculas y la clave en una cabecera
+        TranslationProvider::DeepL => serde_json::json!({
+            "text": [text],
+            "source_lang": source.to_uppercase(),
+            "target_lang": config.target_language.to_uppercase(),
+        }),
+    }
+}
+
+/// Texto traducido de la respuesta del servicio
+pub fn parse_response(
+    provider: TranslationProvider,
+    response: &serde_json::Value,
+) -> Option<String> {
+    let text = match provider {
+        TranslationProvider::LibreTranslate => response.get("translatedText")?,
+        TranslationProvider::DeepL => response.pointer("/translations/0/text")?,
+    };
+    text.as_str()
+        .map(str::trim)
+        .filter(|text| !text.is_empty())
+        .map(str::to_string)
+}
+
+pub struct Translator {
+    config: Mutex<TranslationConfig>,
+    http: reqwest::Client,
+    warned: std::sync::Once,
+}
+
+impl Translator {
+    pub fn new(config: TranslationConfig) -> Self {
+        Self {
+            config: Mutex::new(config),
+            http: reqwest::Client::new(),
+            warned: std::sync::Once::new(),
+        }
+    }
+
+    pub fn global() -> &'static Translator {
+        GLOBAL_TRANSLATOR.get_or_init(|| Self::new(TranslationConfig::default()))
+    }
+
+    pub fn configure(&self, config: TranslationConfig) {
+        *self.config.lock().unwrap_or_else(|e| e.into_inner()) = config;
+    }
+
+    fn config(&self) -> TranslationConfig {
+        self.config
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// Detecta el idioma del mensaje y, si hace falta, añade la traducción
+    /// en `custom_data`; nunca descarta el mensaje
+    pub async fn apply(&self, message: &mut ChatMessage) {
+        let config = self.config();
+        if !config.enabled || message.platform == crate::system_users::SYSTEM_PLATFORM {
+            return;
+        }
+        if cfg!(not(feature = "translation")) {
+            self.warned.call_once(|| {
+                eprintln!(
+                    "⚠️ Translation needs the `translation` feature (build with --features translation)"
+                );
+            });
+            return;
+        }
+
+        let text = detection_text(message);
+        if text.chars().count() < config.min_chars {
+            return;
+        }
+        let Some((language, confidence)) = detect_language(&text) else {
+            return;
+        };
+        if confidence < config.min_confidence {
+            return;
+        }
+        message
+            .metadata
+            .custom_data
+            .insert(LANGUAGE_KEY.to_string(), language.into());
+        if config.skips(language) {
+            return;
+        }
+
+        match self.translate(&config, &message.content, language).await {
+            Ok(Some(translation)) if translation != message.content => {
+                message
+                    .metadata
+                    .custom_data
+                    .insert(TRANSLATION_KEY.to_string(), translation.into());
+            }
+            Ok(_) => {}
+            Err(e) => {
+                eprintln!("[TRANSLATION] ⚠️ {}", e);
+                ErrorBudget::global().record_failure("service:translation", &e);
+            }
+        }
+    }
+
+    async fn translate(
+        &self,
+        config: &TranslationConfig,
+        text: &str,
+        source: &str,
+    ) -> Result<Option<String>, String> {
+        let mut request = self
+            .http
+            .post(&config.url)
+            .timeout(Duration::from_millis(config.timeout_ms))
+            .json(&request_body(config, text, source));
+        if let (TranslationProvider::DeepL, Some(key)) = (config.provider, &config.api_key) {
+            request = request.header("Authorization", format!("DeepL-Auth-Key {}", key));
+        }
+        let response: serde_json::Value = request
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| e.to_string())?
+            .json()
+            .await
+            .map_err(|e| e.to_string())?;
+        Ok(parse_response(config.provider, &response))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_and_response_per_provider() {
+        let mut config = TranslationConfig {
+            api_key: Some("secret".to_string()),
+            ..TranslationConfig::default()
+        };
+        let body = request_body(&config, "hola a todos", "es");
+        assert_eq!(body["q"], "hola a todos");
+        assert_eq!(body["source"], "es");
+        assert_eq!(body["target"], "en");
+        assert_eq!(body["api_key"], "secret");
+        assert_eq!(
+            parse_response(
+                TranslationProvider::LibreTranslate,
+                &serde_json::json!({"translatedText": "hello everyone"})
+            )
+            .as_deref(),
+            Some("hello everyone")
+        );
+
+        config.provider = TranslationProvider::DeepL;
+        let body = request_body(&config, "hola a todos", "es");
+        assert_eq!(body["text"][0], "hola a todos");
+        assert_eq!(body["source_lang"], "ES");
+        assert_eq!(body["target_lang"], "EN");
+        assert!(body.get("api_key").is_none());
+        assert_eq!(
+            parse_response(
+                TranslationProvider::DeepL,
+                &serde_json::json!({"translations": [{"detected_source_language": "ES", "text": "hello everyone"}]})
+            )
+            .as_deref(),
+            Some("hello everyone")
+        );
+        assert!(parse_response(TranslationProvider::DeepL, &serde_json::json!({})).is_none());
+    }
+
+    #[test]
+    fn test_skip_languages() {
+        let config = TranslationConfig {
+            skip_languages: vec!["ES".to_string()],
+            ..TranslationConfig::default()
+        };
+        assert!(config.skips("en"));
+        assert!(config.skips("es"));
+        assert!(!config.skips("pt"));
+    }
+
+    #[cfg(feature = "translation")]
+    #[test]
+    fn test_detects_common_languages() {
+        let (language, _) =
+            detect_language("Hola a todos, ¿cómo están hoy? Qué buen directo").unwrap();
+        assert_eq!(language, "es");
+        let (language, _) =
+            detect_language("Hello everyone, how is the stream going today?").unwrap();
+        assert_eq!(language, "en");
+    }
+}
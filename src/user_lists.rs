@@ -0,0 +1,266 @@
+//! Listas de usuarios editables en caliente por conexión
+//!
+//! `blocked_users`, `allowed_users`, `allowed_users_only` e `ignore_bots` de
+//! `filters` se pueden cambiar desde la API de control (p. ej. para silenciar
+//! a alguien durante el directo) sin editar `config.json`. Como las de
+//! [`crate::command_mode`], las sobrescrituras viven en memoria y se pierden
+//! al reiniciar.
+
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::config::MessageFilters;
+
+static GLOBAL_USER_LISTS: OnceLock<ConnectionUserLists> = OnceLock::new();
+
+/// Listas efectivas de una conexión tal y como se exponen en la API
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct UserLists {
+    pub blocked_users: Vec<String>,
+    pub allowed_users: Vec<String>,
+    pub allowed_users_only: bool,
+    pub ignore_bots: bool,
+    /// `true` si se cambiaron en caliente y no coinciden con `config.json`
+    pub overridden: bool,
+}
+
+/// Cuerpo de `POST /connections/{id}/users`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct UserListsRequest {
+    /// Usuarios que se añaden a `blocked_users` (y se quitan de `allowed_users`)
+    pub block: Vec<String>,
+    pub unblock: Vec<String>,
+    /// Usuarios que se añaden a `allowed_users` (y se quitan de `blocked_users`)
+    pub allow: Vec<String>,
+    pub disallow: Vec<String>,
+    /// Sin indicar, se conserva el valor actual
+    pub allowed_users_only: Option<bool>,
+    pub ignore_bots: Option<bool>,
+}
+
+#[derive(Debug, Clone)]
+struct UserListsOverride {
+    blocked_users: Vec<String>,
+    allowed_users: Vec<String>,
+    allowed_users_only: bool,
+    ignore_bots: bool,
+}
+
+impl UserListsOverride {
+    fn from_filters(filters: &MessageFilters) -> Self {
+        Self {
+            blocked_users: filters.blocked_users.clone(),
+            allowed_users: filters.allowed_users.clone(),
+            allowed_users_only: filters.allowed_users_only,
+            ignore_bots: filters.ignore_bots,
+        }
+    }
+
+    /// Si equivale a los filtros configurados, sin distinguir mayúsculas
+    fn matches(&self, filters: &MessageFilters) -> bool {
+        let names = |users: &[String]| users.iter().map(|user| normalize(user)).collect::<Vec<_>>();
+        names(&self.blocked_users) == names(&filters.blocked_users)
+            && names(&self.allowed_users) == names(&filters.allowed_users)
+            && self.allowed_users_only == filters.allowed_users_only
+            && self.ignore_bots == filters.ignore_bots
+    }
+}
+
+/// Nombre tal y como se guarda en las listas
+fn normalize(user: &str) -> String {
+    user.trim().trim_start_matches('@').to_lowercase()
+}
+
+fn remove_users(list: &mut Vec<String>, users: &[String]) {
+    list.retain(|user| !users.contains(&normalize(user)));
+}
+
+fn add_users(list: &mut Vec<String>, users: &[String]) {
+    for user in users {
+        if !list.iter().any(|listed| normalize(listed) == *user) {
+            list.push(user.clone());
+        }
+    }
+}
+
+/// Sobrescrituras en caliente de las listas de usuarios por id de conexión
+#[derive(Debug, Default)]
+pub struct ConnectionUserLists {
+    overrides: Mutex<HashMap<String, UserListsOverride>>,
+}
+
+impl ConnectionUserLists {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn global() -> &'static ConnectionUserLists {
+        GLOBAL_USER_LISTS.get_or_init(Self::new)
+    }
+
+    /// Listas efectivas de la conexión sobre sus filtros configurados
+    pub fn lists(&self, connection_id: &str, filters: &MessageFilters) -> UserLists {
+        let (lists, overridden) = match self.lock().get(connection_id) {
+            Some(lists) => (lists.clone(), true),
+            None => (UserListsOverride::from_filters(filters), false),
+        };
+        UserLists {
+            blocked_users: lists.blocked_users,
+            allowed_users: lists.allowed_users,
+            allowed_users_only: lists.allowed_users_only,
+            ignore_bots: lists.ignore_bots,
+            overridden,
+        }
+    }
+
+    /// Aplica `request` sobre las listas actuales y devuelve las resultantes
+    pub fn set(
+        &self,
+        connection_id: &str,
+        filters: &MessageFilters,
+        request: UserListsRequest,
+    ) -> UserLists {
+        let names = |users: Vec<String>| -> Vec<String> {
+            users
+                .iter()
+                .map(|user| normalize(user))
+                .filter(|user| !user.is_empty())
+                .collect()
+        };
+        let (block, unblock) = (names(request.block), names(request.unblock));
+        let (allow, disallow) = (names(request.allow), names(request.disallow));
+
+        let current = self.lists(connection_id, filters);
+        let mut lists = UserListsOverride {
+            blocked_users: current.blocked_users,
+            allowed_users: current.allowed_users,
+            allowed_users_only: request
+                .allowed_users_only
+                .unwrap_or(current.allowed_users_only),
+            ignore_bots: request.ignore_bots.unwrap_or(current.ignore_bots),
+        };
+        remove_users(&mut lists.blocked_users, &unblock);
+        remove_users(&mut lists.blocked_users, &allow);
+        add_users(&mut lists.blocked_users, &block);
+        remove_users(&mut lists.allowed_users, &disallow);
+        remove_users(&mut lists.allowed_users, &block);
+        add_users(&mut lists.allowed_users, &allow);
+
+        let mut overrides = self.lock();
+        // Volver a lo configurado equivale a quitar la sobrescritura
+        if lists.matches(filters) {
+            overrides.remove(connection_id);
+        } else {
+            overrides.insert(connection_id.to_string(), lists);
+        }
+        drop(overrides);
+        self.lists(connection_id, filters)
+    }
+
+    /// Vuelve a los filtros de `config.json`; `false` si no había sobrescritura
+    pub fn reset(&self, connection_id: &str) -> bool {
+        self.lock().remove(connection_id).is_some()
+    }
+
+    /// Filtros de la conexión con la sobrescritura aplicada
+    pub fn filters_for(&self, connection_id: &str, filters: &MessageFilters) -> MessageFilters {
+        let mut filters = filters.clone();
+        if let Some(lists) = self.lock().get(connection_id) {
+            filters.blocked_users = lists.blocked_users.clone();
+            filters.allowed_users = lists.allowed_users.clone();
+            filters.allowed_users_only = lists.allowed_users_only;
+            filters.ignore_bots = lists.ignore_bots;
+        }
+        filters
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, UserListsOverride>> {
+        self.overrides.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn filters() -> MessageFilters {
+        MessageFilters {
+            min_message_length: None,
+            max_message_length: None,
+            blocked_users: vec!["Spammer".to_string()],
+            allowed_users: vec![],
+            allowed_users_only: true,
+            ignore_bots: false,
+            bot_users: vec![],
+            blocked_words: vec![],
+            hide_link_only: false,
+            commands_only: false,
+            command_prefixes: vec![],
+            subscribers_only: false,
+            vip_only: false,
+        }
+    }
+
+    #[test]
+    fn test_block_allow_and_reset() {
+        let lists = ConnectionUserLists::new();
+        let base = filters();
+
+        let current = lists.set(
+            "main",
+            &base,
+            UserListsRequest {
+                block: vec![" @Troll ".to_string(), "".to_string()],
+                allow: vec!["spammer".to_string()],
+                ignore_bots: Some(true),
+                ..UserListsRequest::default()
+            },
+        );
+        assert!(current.overridden);
+        assert!(current.ignore_bots);
+        assert_eq!(current.blocked_users, vec!["troll"]);
+        assert_eq!(current.allowed_users, vec!["spammer"]);
+
+        let effective = lists.filters_for("main", &base);
+        assert_eq!(effective.blocked_users, vec!["troll"]);
+        assert!(effective.ignore_bots);
+        // Otras conexiones no se ven afectadas
+        assert_eq!(
+            lists.filters_for("other", &base).blocked_users,
+            vec!["Spammer"]
+        );
+
+        assert!(lists.reset("main"));
+        assert!(!lists.reset("main"));
+        assert!(!lists.lists("main", &base).overridden);
+    }
+
+    #[test]
+    fn test_returning_to_config_clears_override() {
+        let lists = ConnectionUserLists::new();
+        let base = filters();
+
+        lists.set(
+            "main",
+            &base,
+            UserListsRequest {
+                unblock: vec!["spammer".to_string()],
+                ..UserListsRequest::default()
+            },
+        );
+        let current = lists.set(
+            "main",
+            &base,
+            UserListsRequest {
+                block: vec!["Spammer".to_string()],
+                ..UserListsRequest::default()
+            },
+        );
+        assert!(!current.overridden);
+        assert_eq!(current.blocked_users, vec!["Spammer"]);
+    }
+}
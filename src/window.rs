@@ -1,4 +1,5 @@
 use std::fmt;
+use std::time::Duration;
 
 use gdk::Monitor;
 use tokio::time::Instant;
@@ -8,7 +9,14 @@ use glib::{object_subclass, wrapper};
 use glib_macros::Properties;
 use gtk::prelude::{ContainerExt, GtkWindowExt, WidgetExt};
 use gtk::{prelude::*, subclass::prelude::*};
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+use crate::config::{FadeTiming, WindowStyle};
+use crate::overlay_window::{OverlayWindow, RecycledOverlayWindow, WindowBackend};
+use crate::text::TextRun;
+use crate::theme::events::{EventCard, OverlayEventKind};
+use crate::theme::ThemePalette;
 
 wrapper! {
     pub struct Window(ObjectSubclass<WindowPriv>)
@@ -35,6 +43,15 @@ pub struct WindowPriv {
         default = 0
     )]
     y: RefCell<i32>,
+
+    /// Estilos del evento o del nivel de usuario aplicados a esta ventana
+    event_css: RefCell<Option<gtk::CssProvider>>,
+
+    /// Arrastre con `display.click_through` desactivado (ver [`set_draggable`])
+    drag: RefCell<Option<gtk::GestureDrag>>,
+
+    /// Conexión del mensaje mostrado, cuyo desplazamiento guarda el arrastre
+    drag_connection: RefCell<Option<String>>,
 }
 
 // This should match the default values from the ParamSpecs
@@ -43,6 +60,9 @@ impl Default for WindowPriv {
         WindowPriv {
             x: RefCell::new(0),
             y: RefCell::new(0),
+            event_css: RefCell::new(None),
+            drag: RefCell::new(None),
+            drag_connection: RefCell::new(None),
         }
     }
 }
@@ -89,18 +109,232 @@ impl WidgetImpl for WindowPriv {}
 pub struct SpawnedWindow {
     pub w: Window,
     pub progress: gtk::ProgressBar,
+    pub username: gtk::Label,
+    /// Texto del nombre sin el contador de repeticiones
+    pub username_text: String,
     pub created: Instant,
+    pub duration: Duration,
+    /// Ver [`track_occlusion`]
+    pub occluded: Rc<Cell<bool>>,
+    /// Posición que mantiene el manejador de `configure-event`
+    pub geometry: Rc<Cell<WindowGeometry>>,
+    /// Opacidad configurada, sin fundido
+    pub opacity: f64,
+    pub fade: FadeTiming,
+    /// Momento en que se mostró; a diferencia de `created` no cambia con las repeticiones
+    pub shown: Instant,
+}
+
+/// Ventana oculta y vacía, lista para mostrar otro mensaje sin crear una nueva
+#[derive(Debug)]
+pub struct RecycledWindow {
+    w: Window,
+    occluded: Rc<Cell<bool>>,
+    geometry: Rc<Cell<WindowGeometry>>,
+}
+
+impl RecycledWindow {
+    fn move_to(&self, pos: (i32, i32), monitor_geometry: gdk::Rectangle) {
+        #[cfg(target_os = "linux")]
+        crate::x11::move_to(&self.w, pos, monitor_geometry, &self.geometry);
+        #[cfg(not(target_os = "linux"))]
+        {
+            let _ = monitor_geometry;
+            self.w.move_(pos.0, pos.1);
+        }
+    }
+}
+
+impl RecycledOverlayWindow for RecycledWindow {
+    fn close(self) {
+        self.w.close();
+    }
+}
+
+/// Backend de GTK (X11 en Linux)
+pub struct GtkBackend;
+
+#[async_trait::async_trait(?Send)]
+impl WindowBackend for GtkBackend {
+    type Window = SpawnedWindow;
+    type Monitor = gdk::Rectangle;
+
+    fn monitor_geometry() -> gdk::Rectangle {
+        use gdk::prelude::MonitorExt;
+        get_gdk_monitor().geometry()
+    }
+
+    fn monitor_size(monitor: &gdk::Rectangle) -> (i32, i32) {
+        (monitor.width(), monitor.height())
+    }
+
+    fn process_events() -> bool {
+        gtk::main_iteration_do(false)
+    }
+
+    async fn precompose(
+        spec: crate::emotes::MessageBitmapSpec,
+        style: &WindowStyle,
+        _position: (i32, i32),
+        font_family: &str,
+    ) -> Option<image::RgbaImage> {
+        // La escala del estilo también agranda el texto, como los emotes y márgenes
+        let font_size = (style.font_size as f32 * style.scale).round() as u32;
+        let text = CairoText::new(font_family, font_size);
+        crate::emotes::render_message_bitmap(spec, text).await
+    }
+
+    async fn spawn(
+        header: &str,
+        body: &str,
+        emotes: &[Emote],
+        position: (i32, i32),
+        monitor: gdk::Rectangle,
+        style: &WindowStyle,
+        recycled: Option<RecycledWindow>,
+    ) -> SpawnedWindow {
+        spawn_window(header, body, emotes, position, monitor, style, recycled).await
+    }
+}
+
+#[async_trait::async_trait(?Send)]
+impl OverlayWindow for SpawnedWindow {
+    type Recycled = RecycledWindow;
+
+    fn created(&self) -> Instant {
+        self.created
+    }
+
+    fn duration(&self) -> Duration {
+        self.duration
+    }
+
+    fn shown(&self) -> Instant {
+        self.shown
+    }
+
+    fn fade(&self) -> FadeTiming {
+        self.fade
+    }
+
+    /// Actualiza la barra de progreso salvo que la ventana esté tapada; el
+    /// siguiente tick tras volver a verse ya pinta el valor correcto
+    fn set_progress(&mut self, progress: f64) {
+        if !Cell::get(&self.occluded) {
+            self.progress.set_fraction(progress);
+        }
+    }
+
+    fn set_fade(&mut self, factor: f64) {
+        let opacity = self.opacity * factor;
+        if (self.w.opacity() - opacity).abs() > 0.005 {
+            self.w.set_opacity(opacity);
+        }
+    }
+
+    fn set_repeat_count(&mut self, count: u32) {
+        self.username
+            .set_text(&format!("{} ×{}", self.username_text, count));
+        self.created = Instant::now();
+    }
+
+    /// Rehace el contenido de la ventana con sus estilos; GTK la ajusta al
+    /// tamaño del nuevo mensaje
+    async fn update_content(
+        &mut self,
+        header: &str,
+        body: &str,
+        emotes: &[Emote],
+        style: &WindowStyle,
+    ) {
+        clear_message(&self.w);
+        let content = message_layout(header, body, emotes, style).await;
+        self.w.add(&content.layout);
+        apply_message_styles(&self.w, &content.progress, style);
+        self.w.resize(1, 1);
+        self.w.show_all();
+
+        self.progress = content.progress;
+        self.username = content.username;
+        self.username_text = content.username_text;
+        self.created = Instant::now();
+        self.duration = style.duration;
+    }
+
+    /// Además quita los estilos del mensaje
+    fn recycle(self) -> RecycledWindow {
+        self.w.hide();
+        clear_message(&self.w);
+        RecycledWindow {
+            w: self.w,
+            occluded: self.occluded,
+            geometry: self.geometry,
+        }
+    }
+
+    fn close(self) {
+        self.w.close();
+    }
+}
+
+/// Sigue si la ventana está completamente tapada o sin mapear
+///
+/// Sin compositor, X11 envía `VisibilityNotify` cuando otra ventana la cubre
+/// entera; con compositor solo llega al ocultarse o minimizarse.
+pub fn track_occlusion(w: &impl IsA<gtk::Widget>) -> Rc<Cell<bool>> {
+    let occluded = Rc::new(Cell::new(false));
+    w.add_events(gdk::EventMask::VISIBILITY_NOTIFY_MASK);
+
+    let state = occluded.clone();
+    w.connect_event(move |_, event| {
+        if let Some(visibility) = event.downcast_ref::<gdk::EventVisibility>() {
+            state.set(visibility.state() == gdk::VisibilityState::FullyObscured);
+        }
+        gtk::Inhibit(false)
+    });
+    let state = occluded.clone();
+    w.connect_unmap(move |_| state.set(true));
+    let state = occluded.clone();
+    w.connect_map(move |_| state.set(false));
+
+    occluded
 }
 
 pub fn init_window(pos: (i32, i32), monitor_geometry: gdk::Rectangle) -> (Option<WindowGeometry>, Window) {
     #[cfg(target_os = "linux")]
-    {
-        crate::x11::a(pos, monitor_geometry)
-    } 
+    let (geometry, w) = crate::x11::a(pos, monitor_geometry);
     #[cfg(not(target_os = "linux"))]
-    {
-        (None, Window::new(gtk::WindowType::Toplevel, pos.0, pos.1))
-    }
+    let (geometry, w) = (None, Window::new(gtk::WindowType::Popup, pos.0, pos.1));
+
+    apply_no_focus_hints(&w);
+    (geometry, w)
+}
+
+/// Oculta la ventana de la barra de tareas y el paginador e impide que reciba
+/// el foco, ni al mostrarse ni al hacer clic: se declara como notificación y
+/// con una región de entrada vacía los clics pasan a la ventana de debajo
+/// (salvo que [`set_draggable`] la vuelva a llenar)
+pub fn apply_no_focus_hints(w: &Window) {
+    w.set_type_hint(gdk::WindowTypeHint::Notification);
+    w.set_skip_taskbar_hint(true);
+    w.set_skip_pager_hint(true);
+    w.set_accept_focus(false);
+    w.set_focus_on_map(false);
+    w.set_can_focus(false);
+    w.input_shape_combine_region(Some(&gtk::cairo::Region::create()));
+}
+
+/// Comprueba las pistas de [`apply_no_focus_hints`], que sea una ventana
+/// override-redirect (fuera del gestor de ventanas) y que no tenga el foco
+pub fn is_focus_safe(w: &Window) -> bool {
+    w.window_type() == gtk::WindowType::Popup
+        && w.type_hint() == gdk::WindowTypeHint::Notification
+        && w.skips_taskbar_hint()
+        && w.skips_pager_hint()
+        && !w.accepts_focus()
+        && !w.gets_focus_on_map()
+        && !w.has_toplevel_focus()
+        && !w.is_active()
 }
 
 pub async fn spawn_window(
@@ -109,87 +343,653 @@ pub async fn spawn_window(
     emotes: &[Emote],
     pos: (i32, i32),
     monitor_geometry: gdk::Rectangle,
+    style: &WindowStyle,
+    recycled: Option<RecycledWindow>,
 ) -> SpawnedWindow {
-    let (geometry, w) = init_window(pos, monitor_geometry);
+    // Las ventanas reutilizadas ya están realizadas y con sus manejadores
+    let (w, geometry, occluded) = match recycled {
+        Some(recycled) => {
+            recycled.move_to(pos, monitor_geometry);
+            (recycled.w, recycled.geometry, Some(recycled.occluded))
+        }
+        None => {
+            let (geometry, w) = init_window(pos, monitor_geometry);
+            (w, Rc::new(Cell::new(geometry.unwrap_or_default())), None)
+        }
+    };
+
+    let MessageLayout {
+        layout,
+        username,
+        username_text,
+        progress,
+    } = message_layout(user, message, emotes, style).await;
+    w.add(&layout);
+
+    if occluded.is_none() {
+        w.realize();
+
+        #[cfg(target_os = "linux")]
+        {
+            crate::x11::b(w.clone(), monitor_geometry, geometry.clone())
+        }
+    }
+    set_draggable(&w, style, monitor_geometry, &geometry);
+    apply_message_styles(&w, &progress, style);
+
+    // Con fundido de entrada aparece transparente y el temporizador la revela
+    let opacity = style.opacity as f64;
+    w.set_opacity(if style.fade.fade_in.is_zero() { opacity } else { 0.0 });
+    let occluded = occluded.unwrap_or_else(|| track_occlusion(&w));
+    w.show_all();
 
-    let progress = {
-        let layout = gtk::Box::new(gtk::Orientation::Vertical, 5);
+    SpawnedWindow {
+        w,
+        progress,
+        username,
+        username_text,
+        created: Instant::now(),
+        duration: style.duration,
+        occluded,
+        geometry,
+        opacity,
+        fade: style.fade,
+        shown: Instant::now(),
+    }
+}
 
-        let username = gtk::Label::new(Some(user));
-        layout.add(&username);
+/// Contenido de la ventana de un mensaje
+struct MessageLayout {
+    layout: gtk::Box,
+    username: gtk::Label,
+    /// Texto del nombre sin el contador de repeticiones
+    username_text: String,
+    progress: gtk::ProgressBar,
+}
 
-        let messagebox = gtk::Box::new(gtk::Orientation::Horizontal, 2);
+/// Cabecera (icono de la plataforma, avatar y nombre), tarjeta del evento,
+/// mensaje y barra de progreso, o el bitmap ya compuesto con `display.precomposed`
+async fn message_layout(
+    user: &str,
+    message: &str,
+    emotes: &[Emote],
+    style: &WindowStyle,
+) -> MessageLayout {
+    let username_text = style.username_label(user);
+    let username = styled_label(&username_text, style, true);
+    let layout = gtk::Box::new(gtk::Orientation::Vertical, 5);
 
-        let mut start = 0;
-        for emote in emotes {
-            let plain = start..emote.char_range.start;
-            if !plain.is_empty() {
-                let plain_txt = &message[plain];
-                let label = gtk::Label::new(Some(plain_txt));
-                messagebox.add(&label);
+    username.style_context().add_class("username");
+    if let Some(bitmap) = &style.bitmap {
+        // `display.precomposed`: el mensaje ya viene dibujado
+        layout.add(&bitmap_image(bitmap));
+    } else {
+        let images: Vec<gtk::Image> = [
+            style.platform_icon.and_then(|icon| platform_icon_image(icon, style)),
+            style
+                .avatar
+                .as_deref()
+                .and_then(|png| avatar_image(png, style)),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+        if !images.is_empty() {
+            let header = gtk::Box::new(gtk::Orientation::Horizontal, 6);
+            for image in &images {
+                header.add(image);
             }
+            header.add(&username);
+            layout.add(&header);
+        } else if !username_text.is_empty() {
+            // Plantilla de una sola línea: sin cabecera
+            layout.add(&username);
+        }
 
-            start = emote.char_range.end;
+        if let Some(card) = &style.card {
+            layout.add(&event_card(card, style));
+        }
+
+        layout.add(&message_view(message, emotes, style).await);
+    }
 
-            let emote_id = &emote.id;
-            let img = load_emote(emote_id).await;
+    let progress = gtk::ProgressBar::new();
+    layout.add(&progress);
+
+    MessageLayout {
+        layout,
+        username,
+        username_text,
+        progress,
+    }
+}
 
-            messagebox.add(&img);
+/// Colores del evento o del nivel del usuario y la clase `level-*`
+fn apply_message_styles(w: &Window, progress: &gtk::ProgressBar, style: &WindowStyle) {
+    if style.event != OverlayEventKind::Chat || style.user_level.is_some() {
+        if let Some(palette) = style.palette {
+            apply_event_css(w, progress, &palette, style.font_size);
+        }
+    }
+    if let Some(level) = &style.user_level {
+        // `level-broadcaster`, `level-moderator`... para style.css
+        if let Ok(serde_json::Value::String(name)) = serde_json::to_value(level) {
+            w.style_context().add_class(&format!("level-{}", name));
         }
+    }
+}
 
-        let plain = start..message.len();
-        if !plain.is_empty() {
-            let plain_txt = &message[plain];
-            let label = gtk::Label::new(Some(plain_txt));
-            messagebox.add(&label);
+/// Quita el contenido del mensaje y sus estilos
+fn clear_message(w: &Window) {
+    if let Some(child) = w.child() {
+        w.remove(&child);
+    }
+    let context = w.style_context();
+    for class in context.list_classes() {
+        if class.starts_with("level-") {
+            context.remove_class(&class);
         }
+    }
+    if let Some(provider) = w.imp().event_css.take() {
+        context.remove_provider(&provider);
+    }
+}
 
-        layout.add(&messagebox);
+/// Deja que los clics pasen a la ventana de debajo o, si el estilo es
+/// `draggable`, permite moverla con el ratón; al soltarla lo que se movió se
+/// suma al desplazamiento de su conexión (ver [`crate::layout`])
+///
+/// Las ventanas override-redirect no pueden usar `begin_move_drag` del gestor
+/// de ventanas, así que se mueven a mano siguiendo el puntero en coordenadas
+/// de pantalla. El gesto va en la fase de captura para que el texto del
+/// mensaje no se quede el clic.
+fn set_draggable(
+    w: &Window,
+    style: &WindowStyle,
+    monitor_geometry: gdk::Rectangle,
+    geometry: &Rc<Cell<WindowGeometry>>,
+) {
+    w.imp().drag_connection.replace(style.connection_id.clone());
+    if !style.draggable {
+        w.input_shape_combine_region(Some(&gtk::cairo::Region::create()));
+        return;
+    }
+    w.input_shape_combine_region(None);
+    if w.imp().drag.borrow().is_some() {
+        return;
+    }
 
-        let progress = gtk::ProgressBar::new();
-        layout.add(&progress);
+    w.add_events(
+        gdk::EventMask::BUTTON_PRESS_MASK
+            | gdk::EventMask::BUTTON_RELEASE_MASK
+            | gdk::EventMask::BUTTON_MOTION_MASK,
+    );
+    let gesture = gtk::GestureDrag::new(w);
+    gesture.set_propagation_phase(gtk::PropagationPhase::Capture);
 
-        w.add(&layout);
-        progress
+    let start: Rc<Cell<Option<DragStart>>> = Rc::default();
+    let pointer = |gesture: &gtk::GestureDrag| {
+        gesture
+            .last_event(gesture.current_sequence().as_ref())
+            .and_then(|event| event.root_coords())
     };
+    // El gesto es de la ventana: capturarla en los manejadores sería un ciclo
+    let window_of =
+        |gesture: &gtk::GestureDrag| gesture.widget().and_then(|w| w.downcast::<Window>().ok());
 
-    w.realize();
+    let (begin_start, begin_geometry) = (start.clone(), geometry.clone());
+    gesture.connect_drag_begin(move |gesture, _, _| {
+        gesture.set_state(gtk::EventSequenceState::Claimed);
+        let position = window_of(gesture).map(|w| window_position(&w, &begin_geometry));
+        begin_start.set(pointer(gesture).zip(position));
+    });
+    let (update_start, update_geometry) = (start.clone(), geometry.clone());
+    gesture.connect_drag_update(move |gesture, _, _| {
+        let (Some((from, position)), Some(to), Some(w)) = (
+            Cell::get(&update_start),
+            pointer(gesture),
+            window_of(gesture),
+        ) else {
+            return;
+        };
+        let target = (
+            position.0 + (to.0 - from.0).round() as i32,
+            position.1 + (to.1 - from.1).round() as i32,
+        );
+        drag_window_to(&w, target, monitor_geometry, &update_geometry);
+    });
+    let end_geometry = geometry.clone();
+    gesture.connect_drag_end(move |gesture, _, _| {
+        let (Some((_, from)), Some(w)) = (start.take(), window_of(gesture)) else {
+            return;
+        };
+        let connection_id = w.imp().drag_connection.borrow().clone();
+        if let Some(connection_id) = connection_id {
+            let to = window_position(&w, &end_geometry);
+            crate::layout::LayoutMemory::global().record_move(&connection_id, from, to);
+        }
+    });
+    w.imp().drag.replace(Some(gesture));
+}
+
+/// Puntero (coordenadas de pantalla) y posición de la ventana al pulsar
+type DragStart = ((f64, f64), (i32, i32));
 
+/// Posición de la ventana en el mismo sistema que usa [`drag_window_to`]
+fn window_position(w: &Window, geometry: &Cell<WindowGeometry>) -> (i32, i32) {
     #[cfg(target_os = "linux")]
     {
-        crate::x11::b(w.clone(), monitor_geometry, geometry.unwrap())
+        let _ = w;
+        geometry.get().offset.relative_to()
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = geometry;
+        w.position()
     }
+}
 
-    w.show_all();
+fn drag_window_to(
+    w: &Window,
+    pos: (i32, i32),
+    monitor_geometry: gdk::Rectangle,
+    geometry: &Cell<WindowGeometry>,
+) {
+    #[cfg(target_os = "linux")]
+    crate::x11::drag_to(w, pos, monitor_geometry, geometry);
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = (monitor_geometry, geometry);
+        w.move_(pos.0, pos.1);
+    }
+}
 
-    SpawnedWindow {
-        w,
-        progress,
-        created: Instant::now(),
+/// Fondo, borde y barra de progreso del evento o del nivel del usuario, solo para esta
+/// ventana (el CSS global del tema queda por debajo)
+fn apply_event_css(
+    w: &Window,
+    progress: &gtk::ProgressBar,
+    palette: &ThemePalette,
+    font_size: u32,
+) {
+    let provider = gtk::CssProvider::new();
+    if let Err(e) = provider.load_from_data(palette.to_css(font_size).as_bytes()) {
+        eprintln!("Cannot load event styles: {e}");
+        return;
     }
+    for context in [w.style_context(), progress.style_context()] {
+        context.add_provider(&provider, gtk::STYLE_PROVIDER_PRIORITY_APPLICATION + 2);
+    }
+    w.imp().event_css.replace(Some(provider));
+}
+
+/// Cabecera del evento: meses en grande y con el color de acento, detalle debajo
+fn event_card(card: &EventCard, style: &WindowStyle) -> gtk::Box {
+    let card_box = gtk::Box::new(gtk::Orientation::Vertical, 0);
+    card_box.style_context().add_class("event-card");
+
+    let headline = gtk::Label::new(Some(&card.headline));
+    headline.set_halign(gtk::Align::Start);
+    let attrs = pango::AttrList::new();
+    attrs.insert(pango::AttrSize::new_size_absolute(
+        (style.font_size as f32 * 1.6).round() as i32 * pango::SCALE,
+    ));
+    attrs.insert(pango::AttrInt::new_weight(pango::Weight::Bold));
+    if let Some(palette) = style.palette {
+        attrs.insert(pango::AttrColor::new_foreground(
+            palette.progress.0 as u16 * 257,
+            palette.progress.1 as u16 * 257,
+            palette.progress.2 as u16 * 257,
+        ));
+    }
+    headline.set_attributes(Some(&attrs));
+    card_box.add(&headline);
+
+    if let Some(detail) = &card.detail {
+        let detail = styled_label(detail, style, false);
+        detail.set_halign(gtk::Align::Start);
+        card_box.add(&detail);
+    }
+    card_box
+}
+
+/// Avatar ya recortado en círculo, al lado que le corresponde a la fuente
+fn avatar_image(png: &[u8], style: &WindowStyle) -> Option<gtk::Image> {
+    let loader = gtk::gdk_pixbuf::PixbufLoader::new();
+    loader.write(png).ok()?;
+    loader.close().ok()?;
+    let side = crate::avatars::avatar_side(style.font_size) as i32;
+    let pixbuf = loader
+        .pixbuf()?
+        .scale_simple(side, side, gtk::gdk_pixbuf::InterpType::Bilinear)?;
+    let image = gtk::Image::from_pixbuf(Some(&pixbuf));
+    image.style_context().add_class("avatar");
+    Some(image)
+}
+
+/// Icono de la plataforma, del alto de la fuente
+fn platform_icon_image(icon: &crate::assets::Asset, style: &WindowStyle) -> Option<gtk::Image> {
+    let pixbuf = asset_pixbuf(icon, style.font_size as i32)?;
+    let image = gtk::Image::from_pixbuf(Some(&pixbuf));
+    image.style_context().add_class("platform-icon");
+    Some(image)
+}
+
+/// Imagen de un mensaje compuesto con [`crate::emotes::compose_message`]
+fn bitmap_image(bitmap: &image::RgbaImage) -> gtk::Image {
+    let (width, height) = (bitmap.width() as i32, bitmap.height() as i32);
+    let pixbuf = gtk::gdk_pixbuf::Pixbuf::from_bytes(
+        &glib::Bytes::from(bitmap.as_raw().as_slice()),
+        gtk::gdk_pixbuf::Colorspace::Rgb,
+        true,
+        8,
+        width,
+        height,
+        width * 4,
+    );
+    let image = gtk::Image::from_pixbuf(Some(&pixbuf));
+    image.style_context().add_class("message-bitmap");
+    image
 }
 
-async fn load_emote(id: &str) -> gtk::Image {
+/// Texto con la API de Cairo sobre superficies en memoria: no toca GTK, así
+/// que sirve para componer mensajes en un hilo de trabajo
+pub struct CairoText {
+    family: String,
+    font_size: f64,
+}
+
+impl CairoText {
+    pub fn new(family: &str, font_size: u32) -> Self {
+        Self {
+            family: family.to_string(),
+            font_size: font_size as f64,
+        }
+    }
+
+    fn context(
+        &self,
+        surface: &gtk::cairo::ImageSurface,
+        bold: bool,
+    ) -> Option<gtk::cairo::Context> {
+        let cr = gtk::cairo::Context::new(surface).ok()?;
+        let weight = if bold {
+            gtk::cairo::FontWeight::Bold
+        } else {
+            gtk::cairo::FontWeight::Normal
+        };
+        cr.select_font_face(&self.family, gtk::cairo::FontSlant::Normal, weight);
+        cr.set_font_size(self.font_size);
+        Some(cr)
+    }
+}
+
+impl crate::emotes::TextRasterizer for CairoText {
+    fn measure(&mut self, text: &str, bold: bool) -> i32 {
+        gtk::cairo::ImageSurface::create(gtk::cairo::Format::ARgb32, 1, 1)
+            .ok()
+            .and_then(|surface| self.context(&surface, bold))
+            .and_then(|cr| cr.text_extents(text).ok())
+            .map_or(0, |extents| extents.x_advance().ceil() as i32)
+    }
+
+    fn rasterize(&mut self, text: &str, bold: bool, color: crate::theme::Rgb) -> image::RgbaImage {
+        let (width, height) = (self.measure(text, bold).max(1), self.line_height());
+        let Ok(mut surface) =
+            gtk::cairo::ImageSurface::create(gtk::cairo::Format::ARgb32, width, height)
+        else {
+            return image::RgbaImage::new(width as u32, height as u32);
+        };
+        if let Some(cr) = self.context(&surface, bold) {
+            cr.set_source_rgb(
+                color.0 as f64 / 255.0,
+                color.1 as f64 / 255.0,
+                color.2 as f64 / 255.0,
+            );
+            let ascent = cr
+                .font_extents()
+                .map_or(self.font_size, |extents| extents.ascent());
+            cr.move_to(0.0, ascent.round());
+            let _ = cr.show_text(text);
+        }
+        surface.flush();
+
+        // ARGB32 de Cairo: BGRA premultiplicado en memoria (little endian)
+        let stride = surface.stride() as usize;
+        let Ok(data) = surface.data() else {
+            return image::RgbaImage::new(width as u32, height as u32);
+        };
+        image::RgbaImage::from_fn(width as u32, height as u32, |x, y| {
+            let i = y as usize * stride + x as usize * 4;
+            let alpha = data[i + 3];
+            let unpremultiply = |value: u8| {
+                if alpha == 0 {
+                    0
+                } else {
+                    (value as u32 * 255 / alpha as u32).min(255) as u8
+                }
+            };
+            image::Rgba([
+                unpremultiply(data[i + 2]),
+                unpremultiply(data[i + 1]),
+                unpremultiply(data[i]),
+                alpha,
+            ])
+        })
+    }
+
+    fn line_height(&self) -> i32 {
+        self.font_size.ceil() as i32 + 4
+    }
+
+    fn resized(&self, factor: f32) -> Self {
+        Self {
+            family: self.family.clone(),
+            font_size: (self.font_size * factor as f64).round(),
+        }
+    }
+}
+
+/// Ancho máximo del mensaje, en múltiplos del tamaño de fuente
+const MESSAGE_WIDTH_EMS: i32 = 30;
+
+/// Texto del mensaje con los emotes intercalados en su sitio
+///
+/// Pango parte las líneas por palabras como el chat de Twitch; la vista pide
+/// el ancho de una sola línea hasta `MESSAGE_WIDTH_EMS`, así que los mensajes
+/// cortos no ocupan más de lo necesario.
+async fn message_view(message: &str, emotes: &[Emote], style: &WindowStyle) -> gtk::TextView {
+    let view = gtk::TextView::new();
+    view.style_context().add_class("message");
+    view.set_editable(false);
+    view.set_cursor_visible(false);
+    view.set_can_focus(false);
+    view.set_wrap_mode(gtk::WrapMode::WordChar);
+
+    let buffer = view.buffer().expect("TextView without buffer");
+    let tag = gtk::TextTag::new(Some("message"));
+    tag.set_size(style.font_size as i32 * pango::SCALE);
+    if let Some(palette) = style.palette {
+        let (r, g, b) = (palette.text.0, palette.text.1, palette.text.2);
+        tag.set_foreground_rgba(Some(&gdk::RGBA::new(
+            r as f64 / 255.0,
+            g as f64 / 255.0,
+            b as f64 / 255.0,
+            1.0,
+        )));
+    }
+    if let Some(table) = buffer.tag_table() {
+        table.add(&tag);
+    }
+
+    let mut text = String::new();
+    let mut emote_width = 0;
+    let runs = crate::text::interleave_runs(message, emotes.iter().map(|e| e.char_range.clone()));
+    for run in runs {
+        let mut end = buffer.end_iter();
+        match run {
+            TextRun::Text(plain) => {
+                buffer.insert(&mut end, plain);
+                text.push_str(plain);
+            }
+            TextRun::Emote(index) => {
+                let img = load_emote(&emotes[index].id, style.scale, style.animated).await;
+                img.style_context().add_class("emote");
+                emote_width += image_width(&img) + 4;
+                if let Some(anchor) = buffer.create_child_anchor(&mut end) {
+                    view.add_child_at_anchor(&img, &anchor);
+                }
+            }
+        }
+    }
+    let (start, end) = buffer.bounds();
+    buffer.apply_tag(&tag, &start, &end);
+
+    let layout = view.create_pango_layout(Some(&text));
+    let attrs = pango::AttrList::new();
+    attrs.insert(pango::AttrSize::new_size_absolute(
+        style.font_size as i32 * pango::SCALE,
+    ));
+    layout.set_attributes(Some(&attrs));
+    let single_line = layout.pixel_size().0 + emote_width + 2;
+    view.set_size_request(
+        single_line.min(style.font_size as i32 * MESSAGE_WIDTH_EMS),
+        -1,
+    );
+    view
+}
+
+/// Ancho en píxeles de la imagen o animación de un emote
+fn image_width(img: &gtk::Image) -> i32 {
+    img.pixbuf()
+        .map(|p| p.width())
+        .or_else(|| img.animation().map(|a| a.width()))
+        .unwrap_or(0)
+}
+
+/// Crea una etiqueta con el tamaño de fuente efectivo y los colores del tema
+fn styled_label(text: &str, style: &WindowStyle, is_username: bool) -> gtk::Label {
+    let label = gtk::Label::new(Some(text));
+    let attrs = pango::AttrList::new();
+    attrs.insert(pango::AttrSize::new_size_absolute(
+        style.font_size as i32 * pango::SCALE,
+    ));
+    if let Some(palette) = style.palette {
+        let color = if is_username {
+            palette.username
+        } else {
+            palette.text
+        };
+        // Pango usa canales de 16 bits
+        attrs.insert(pango::AttrColor::new_foreground(
+            color.0 as u16 * 257,
+            color.1 as u16 * 257,
+            color.2 as u16 * 257,
+        ));
+    }
+    label.set_attributes(Some(&attrs));
+    label
+}
+
+async fn load_emote(id: &str, scale: f32, animated: bool) -> gtk::Image {
     let img = gtk::Image::new();
 
-    // TODO: load_from cache
-    if let Some(pixbuf) = load_emote_(id, "animated", "image/gif").await {
+    // Pilas con zero-width o modificadores, ya compuestas por `emotes::renderer`
+    if let Some(bytes) = crate::emotes::composited_image(id) {
+        let loader = gtk::gdk_pixbuf::PixbufLoader::new();
+        _ = loader.write(&bytes);
+        _ = loader.close();
+        match loader.animation() {
+            Some(animation) if animated && !animation.is_static_image() => {
+                img.set_pixbuf_animation(Some(&animation));
+            }
+            _ => img.set_pixbuf(loader.pixbuf().and_then(|p| scale_pixbuf(&p, scale)).as_ref()),
+        }
+        return img;
+    }
+
+    // Los CDN sirven de 1x a 4x según `emotes.quality`: todo se decodifica
+    // al alto de un emote a 1x por la escala del estilo
+    let height = (crate::emotes::quality::BASE_EMOTE_SIZE as f32 * scale).round() as i32;
+
+    // Imágenes que ya bajó el descargador compartido (precarga del canal)
+    if let Some(bytes) = crate::emotes::EmoteDownloader::global().image(id) {
+        let loader = sized_loader(gtk::gdk_pixbuf::PixbufLoader::new(), height);
+        if loader.write(&bytes).is_ok() && loader.close().is_ok() {
+            match loader.animation() {
+                Some(animation) if animated && !animation.is_static_image() => {
+                    img.set_pixbuf_animation(Some(&animation));
+                    return img;
+                }
+                _ => {
+                    if let Some(pixbuf) = loader.pixbuf() {
+                        img.set_pixbuf(Some(&pixbuf));
+                        return img;
+                    }
+                }
+            }
+        }
+    }
+
+    let animation = if animated {
+        load_emote_(id, "animated", "image/gif", height).await
+    } else {
+        None
+    };
+    if let Some(pixbuf) = animation {
         img.set_pixbuf_animation(pixbuf.animation().as_ref());
-    } else if let Some(pixbuf) = load_emote_(id, "static", "image/png").await {
+    } else if let Some(pixbuf) = load_emote_(id, "static", "image/png", height).await {
         img.set_pixbuf(pixbuf.pixbuf().as_ref());
     } else {
-        eprintln!("Cannot load emote: {id}")
+        eprintln!("Cannot load emote: {id}");
+        img.set_pixbuf(asset_pixbuf(crate::assets::emote_placeholder(), height).as_ref());
     }
 
     img
 }
 
+fn scale_pixbuf(
+    pixbuf: &gtk::gdk_pixbuf::Pixbuf,
+    scale: f32,
+) -> Option<gtk::gdk_pixbuf::Pixbuf> {
+    pixbuf.scale_simple(
+        (pixbuf.width() as f32 * scale).round() as i32,
+        (pixbuf.height() as f32 * scale).round() as i32,
+        gtk::gdk_pixbuf::InterpType::Bilinear,
+    )
+}
+
+/// Decodifica la imagen, o cada frame de la animación, a `height` píxeles de
+/// alto conservando la proporción
+fn sized_loader(
+    loader: gtk::gdk_pixbuf::PixbufLoader,
+    height: i32,
+) -> gtk::gdk_pixbuf::PixbufLoader {
+    loader.connect_size_prepared(move |loader, width, source_height| {
+        if source_height > 0 && source_height != height {
+            loader.set_size((width * height / source_height).max(1), height);
+        }
+    });
+    loader
+}
+
+/// Decodifica un recurso integrado escalado a `size` píxeles de lado
+fn asset_pixbuf(asset: &crate::assets::Asset, size: i32) -> Option<gtk::gdk_pixbuf::Pixbuf> {
+    let loader = gtk::gdk_pixbuf::PixbufLoader::with_mime_type(asset.mime_type).ok()?;
+    loader.write(asset.bytes).ok()?;
+    loader.close().ok()?;
+    loader
+        .pixbuf()?
+        .scale_simple(size, size, gtk::gdk_pixbuf::InterpType::Bilinear)
+}
+
 async fn load_emote_(
     id: &str,
     format: &str,
     mime_type: &str,
+    height: i32,
 ) -> Option<gtk::gdk_pixbuf::PixbufLoader> {
-    let url_gif = format!("https://static-cdn.jtvnw.net/emoticons/v2/{id}/{format}/dark/1.0");
+    let url_gif = crate::emotes::EmoteQuality::global().twitch_url(id, format);
     let Ok(emote_res) = reqwest::get(&url_gif).await else {
         println!("Error getting emote");
         return None;
@@ -200,8 +1000,11 @@ async fn load_emote_(
     }
 
     let img_src = emote_res.bytes().await.expect("Error getting emote");
-    let img_loader = gtk::gdk_pixbuf::PixbufLoader::with_mime_type(mime_type)
-        .expect("Cannot create image loader");
+    let img_loader = sized_loader(
+        gtk::gdk_pixbuf::PixbufLoader::with_mime_type(mime_type)
+            .expect("Cannot create image loader"),
+        height,
+    );
     _ = img_loader.write(&img_src);
     _ = img_loader.close();
     Some(img_loader)
@@ -217,6 +1020,15 @@ pub fn get_gdk_monitor() -> Monitor {
     monitor
 }
 
+/// Posición del puntero relativa al monitor principal
+pub fn cursor_position() -> Option<(i32, i32)> {
+    let display = gdk::Display::default()?;
+    let pointer = display.default_seat()?.pointer()?;
+    let (_, x, y) = pointer.position();
+    let origin = display.primary_monitor()?.geometry();
+    Some((x - origin.x(), y - origin.y()))
+}
+
 #[derive(Default, Debug, Clone, Copy, PartialEq)]
 pub struct WindowGeometry {
     pub anchor_point: AnchorPoint,
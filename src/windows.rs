@@ -1,26 +1,78 @@
-use std::collections::HashMap;
 use std::ffi::OsStr;
 use std::iter::once;
 use std::os::windows::ffi::OsStrExt;
 
+use std::cell::RefCell;
+use std::ffi::c_void;
 use std::ptr::null_mut;
-use std::sync::{Arc, Mutex, Once};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::{Once, OnceLock};
+use std::time::Duration;
 use tokio::time::Instant;
 use twitch_irc::message::Emote;
-use winapi::shared::windef::{HBITMAP, HDC, HWND, RECT};
-use winapi::um::libloaderapi::GetModuleHandleW;
+use winapi::shared::minwindef::DWORD;
+use winapi::shared::windef::{HBITMAP, HDC, HFONT, HGDIOBJ, HMONITOR, HWND, POINT, RECT, SIZE};
+use winapi::um::dwmapi::{DwmGetWindowAttribute, DWMWA_CLOAKED};
+use winapi::um::libloaderapi::{GetModuleHandleW, GetProcAddress, LoadLibraryW};
+use winapi::um::processthreadsapi::GetCurrentThreadId;
 use winapi::um::wingdi::*;
 use winapi::um::wingdi::{BITMAPINFO, BITMAPINFOHEADER, BI_RGB, DIB_RGB_COLORS, RGBQUAD};
 use winapi::um::winuser::*;
 
+use crate::config::{FadeTiming, WindowStyle};
+use crate::emotes::frames::FrameTimeline;
+use crate::flow::{FlowContent, FlowItem, FlowLayout, FlowMetrics};
+use crate::overlay_window::{OverlayWindow, RecycledOverlayWindow, WindowBackend};
+use crate::theme::events::EventCard;
+use crate::theme::{Rgb, ThemePalette};
+
 static REGISTER_CLASS: Once = Once::new();
 
+/// Estilos extendidos de las ventanas del overlay: siempre encima, fuera de la
+/// barra de tareas y Alt-Tab (`WS_EX_TOOLWINDOW`), transparentes al ratón y sin
+/// activarse nunca (`WS_EX_NOACTIVATE`); las arrastrables (ver
+/// [`WindowStyle::draggable`]) no llevan `WS_EX_TRANSPARENT`
+pub const OVERLAY_EX_STYLE: u32 = WS_EX_LAYERED
+    | WS_EX_TOPMOST
+    | WS_EX_TOOLWINDOW
+    | WS_EX_TRANSPARENT
+    | WS_EX_NOACTIVATE;
+
 // Window data structure to store with each window
 #[repr(C)]
 pub struct WindowData {
     pub progress: f64,
     pub created_time: u64,
     pub emote_images: *mut Vec<EmoteImage>,
+    pub scale: f32,
+    pub font_size: i32,
+    pub palette: Option<ThemePalette>,
+    /// Reproduce los emotes animados; con `false` se dibuja solo el primer frame
+    pub animated: bool,
+    /// La ventana está tapada o oculta: no se invalida nada hasta que se vea
+    pub occluded: bool,
+    /// Momento (ms Unix) de la última comprobación de oclusión
+    pub occlusion_checked: u64,
+    /// Cabecera en negrita (nombre de usuario y contador de repeticiones);
+    /// vacía si la plantilla no tiene
+    pub header: String,
+    /// Cabecera del evento (meses de un resub) bajo el nombre de usuario
+    pub card: Option<EventCard>,
+    /// Avatar en BGRA, ya mezclado con el fondo, de `avatar_side(font_size)` de lado
+    pub avatar: Option<Vec<u8>>,
+    /// PNG del icono de la plataforma con `display.show_platform_icons`
+    pub platform_icon: Option<&'static [u8]>,
+    /// Texto del mensaje ya repartido en líneas, en coordenadas de la ventana
+    pub message_runs: Vec<FlowItem>,
+    /// Mensaje compuesto con `display.precomposed` en BGRA: (píxeles, ancho, alto)
+    pub bitmap: Option<(Vec<u8>, u32, u32)>,
+    /// Con `display.click_through` desactivado toda la ventana sirve para arrastrarla
+    pub draggable: bool,
+    /// Conexión cuyo desplazamiento se actualiza al soltar la ventana
+    pub connection_id: Option<String>,
+    /// Esquina superior izquierda (píxeles físicos) al empezar a arrastrar
+    pub drag_start: Option<(i32, i32)>,
 }
 
 #[derive(Clone)]
@@ -31,28 +83,48 @@ pub struct EmoteImage {
     pub height: u32,
     pub x: i32,
     pub y: i32,
+    /// Frames decodificados al tamaño del emote, la primera vez que se pinta
+    /// con la imagen ya en caché
+    pub timeline: Option<FrameTimeline>,
 }
 
-#[derive(Clone)]
+/// Handle de una ventana del overlay; la ventana pertenece al hilo de la
+/// interfaz y los demás hilos solo la consultan o le mandan órdenes
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Hwnd(pub HWND);
+
+// Fuera del hilo de la interfaz el handle no se usa para modificar la ventana
+unsafe impl Send for Hwnd {}
+unsafe impl Sync for Hwnd {}
+
+/// Sin `Clone`: cada valor es dueño de su HWND y lo cierra o recicla una sola vez
 pub struct WindowsWindow {
-    pub hwnd: HWND,
+    pub hwnd: Hwnd,
     pub created: Instant,
+    pub duration: Duration,
     pub progress: f64,
     pub username: String,
+    /// Nombre tal y como se dibuja (con el icono del evento)
+    pub username_label: String,
     pub message: String,
     pub emotes: Vec<twitch_irc::message::Emote>,
+    /// Opacidad configurada, sin fundido
+    pub opacity: f32,
+    pub fade: FadeTiming,
+    /// Momento en que se mostró; a diferencia de `created` no cambia con las repeticiones
+    pub shown: Instant,
+    /// Alfa aplicado con `SetLayeredWindowAttributes`
+    pub alpha: u8,
 }
 
-// Global cache for emote images
-static EMOTE_CACHE: Once = Once::new();
-static mut EMOTE_IMAGES: Option<Arc<Mutex<HashMap<String, Vec<u8>>>>> = None;
+/// Ventana oculta y sin datos, lista para mostrar otro mensaje sin volver a
+/// pasar por `CreateWindowExW`
+#[derive(Debug)]
+pub struct RecycledWindow(Hwnd);
 
-fn get_emote_cache() -> Arc<Mutex<HashMap<String, Vec<u8>>>> {
-    unsafe {
-        EMOTE_CACHE.call_once(|| {
-            EMOTE_IMAGES = Some(Arc::new(Mutex::new(HashMap::new())));
-        });
-        EMOTE_IMAGES.as_ref().unwrap().clone()
+impl RecycledWindow {
+    pub fn close(self) {
+        UiThread::global().send(UiCommand::Close(self.0));
     }
 }
 
@@ -65,254 +137,1101 @@ pub struct WindowGeometry {
 }
 
 impl WindowsWindow {
-    /// Preload emote images (simplified version that creates placeholders)
-    fn preload_emotes(emotes: &[Emote]) -> Vec<EmoteImage> {
-        let mut emote_images = Vec::new();
-
-        for (index, emote) in emotes.iter().enumerate() {
-            // For now, create placeholder emote images
-            // In a production version, you would download and cache actual images
-            emote_images.push(EmoteImage {
-                id: emote.id.clone(),
-                image_data: None, // Will be downloaded asynchronously later
-                width: 32,
-                height: 32,
-                x: 10 + (index as i32 * 36), // Position emotes horizontally
-                y: 25,
-            });
-        }
+    /// Emotes en las posiciones que les dio la maquetación del mensaje; la
+    /// imagen se descarga después en segundo plano
+    fn preload_emotes(emotes: &[Emote], layout: &FlowLayout, top: i32) -> Vec<EmoteImage> {
+        layout
+            .items
+            .iter()
+            .filter_map(|item| match item.content {
+                FlowContent::Emote(index) => Some(EmoteImage {
+                    id: emotes[index].id.clone(),
+                    image_data: None,
+                    width: item.width as u32,
+                    height: item.height as u32,
+                    x: MESSAGE_LEFT + item.x,
+                    y: top + item.y,
+                    timeline: None,
+                }),
+                FlowContent::Text(_) => None,
+            })
+            .collect()
+    }
 
-        emote_images
+    /// Reparte el mensaje en líneas del ancho máximo de la ventana, midiendo el
+    /// texto con la misma fuente con la que se dibuja
+    unsafe fn layout_message(message: &str, emotes: &[Emote], style: &WindowStyle) -> FlowLayout {
+        let font_size = style.font_size as i32;
+        let emote_side = scaled(32, style.scale);
+        let measure = TextMeasure::new(font_size, FW_NORMAL);
+        let runs =
+            crate::text::interleave_runs(message, emotes.iter().map(|e| e.char_range.clone()));
+        crate::flow::flow_runs(
+            &runs,
+            FlowMetrics {
+                measure: |text: &str| measure.width(text),
+                emote_size: |_| (emote_side, emote_side),
+                max_width: scaled(MAX_WINDOW_WIDTH, style.scale) - MESSAGE_LEFT * 2,
+                line_height: font_size + 4,
+                emote_margin: 2,
+            },
+        )
     }
 
     /// Get emote URL based on source
     fn get_emote_url(emote: &Emote) -> String {
-        format!(
-            "https://static-cdn.jtvnw.net/emoticons/v2/{}/default/dark/1.0",
-            emote.id
-        )
+        crate::emotes::EmoteQuality::global().twitch_url(&emote.id, "default")
     }
 
-    /// Schedule async download of emote images (works from sync context)
-    fn schedule_emote_downloads(emotes: Vec<Emote>) {
-        // Use a blocking task to avoid the Tokio runtime issue
-        std::thread::spawn(move || {
-            let rt = tokio::runtime::Runtime::new().unwrap();
-            rt.block_on(async {
-                let cache = get_emote_cache();
-
-                for emote in emotes {
-                    // Check if already cached
-                    if let Ok(cache_guard) = cache.lock() {
-                        if cache_guard.contains_key(&emote.id) {
-                            continue;
-                        }
-                    }
-
-                    // Download emote with timeout
-                    let url = WindowsWindow::get_emote_url(&emote);
-                    if let Ok(image_data) = WindowsWindow::download_emote_async(&url).await {
-                        // Store in cache
-                        if let Ok(mut cache_guard) = cache.lock() {
-                            cache_guard.insert(emote.id.clone(), image_data);
-                        }
-                    }
-                }
-            });
-        });
+    /// Pide al descargador compartido las imágenes que aún no tiene
+    fn schedule_emote_downloads(emotes: &[Emote]) {
+        let downloader = crate::emotes::EmoteDownloader::global();
+        for emote in emotes {
+            // Las pilas compuestas ya están en `emotes::renderer`
+            if !emote.id.starts_with(crate::emotes::COMPOSITE_ID_PREFIX) {
+                downloader.request(&emote.id, &Self::get_emote_url(emote));
+            }
+        }
     }
 
-    /// Download emote image asynchronously with timeout
-    async fn download_emote_async(url: &str) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
-        let client = reqwest::Client::builder()
-            .timeout(std::time::Duration::from_secs(3))
-            .build()?;
+    /// Maqueta el mensaje y espera a que el hilo de la interfaz cree la
+    /// ventana, sin bloquear el hilo del runtime que la pide
+    pub async fn new(
+        user: &str,
+        message: &str,
+        emotes: &[Emote],
+        pos: (i32, i32),
+        style: &WindowStyle,
+        recycled: Option<RecycledWindow>,
+    ) -> Self {
+        let mut window = Self::layout_window(user, message, emotes, pos, style);
+        window.recycled = recycled.map(|RecycledWindow(hwnd)| hwnd);
+        let alpha = window.alpha;
+
+        let (reply, created) = tokio::sync::oneshot::channel();
+        UiThread::global().send(UiCommand::Create(Box::new(window), reply));
+        let hwnd = created.await.unwrap_or_else(|_| {
+            eprintln!("[WINDOWS] ⚠️ UI thread is gone, window not created");
+            Hwnd(null_mut())
+        });
 
-        let response = client.get(url).send().await?;
-        let bytes = response.bytes().await?;
-        Ok(bytes.to_vec())
+        WindowsWindow {
+            hwnd,
+            created: Instant::now(),
+            duration: style.duration,
+            progress: 0.0,
+            username: user.to_string(),
+            username_label: style.username_label(user),
+            message: message.to_string(),
+            emotes: emotes.to_vec(),
+            opacity: style.opacity,
+            fade: style.fade,
+            shown: Instant::now(),
+            alpha,
+        }
     }
-    pub fn new(user: &str, message: &str, emotes: &[Emote], pos: (i32, i32)) -> Self {
-        unsafe {
-            let class_name = wide_string("OverlayWindow");
-            let window_name = wide_string(&format!("{}: {}", user, message));
-
-            let hinstance = GetModuleHandleW(null_mut());
-
-            // Register window class only once
-            REGISTER_CLASS.call_once(|| {
-                let wc = WNDCLASSW {
-                    style: CS_HREDRAW | CS_VREDRAW,
-                    lpfnWndProc: Some(window_proc),
-                    cbClsExtra: 0,
-                    cbWndExtra: 0,
-                    hInstance: hinstance,
-                    hIcon: null_mut(),
-                    hCursor: LoadCursorW(null_mut(), IDC_ARROW),
-                    hbrBackground: CreateSolidBrush(RGB(30, 30, 30)) as *mut _,
-                    lpszMenuName: null_mut(),
-                    lpszClassName: class_name.as_ptr(),
-                };
-
-                RegisterClassW(&wc);
-            });
-
-            // Calculate window size based on text length
-            let text_width = (user.len() + message.len()).max(20) * 8 + 20;
-            let window_width = text_width.min(400).max(200);
-
-            let hwnd = CreateWindowExW(
-                WS_EX_LAYERED | WS_EX_TOPMOST | WS_EX_TOOLWINDOW | WS_EX_TRANSPARENT,
-                class_name.as_ptr(),
-                window_name.as_ptr(),
-                WS_POPUP,
-                pos.0,
-                pos.1,
-                window_width as i32,
-                80,
-                null_mut(),
-                null_mut(),
-                hinstance,
-                null_mut(),
-            );
 
-            // Make window semi-transparent
-            SetLayeredWindowAttributes(hwnd, 0, 220, LWA_ALPHA);
+    /// Ventana maquetada para `pos` (píxeles lógicos), sin reutilizar ninguna
+    fn layout_window(
+        user: &str,
+        message: &str,
+        emotes: &[Emote],
+        pos: (i32, i32),
+        style: &WindowStyle,
+    ) -> NewWindow {
+        // El título solo lo ven los lectores de pantalla y las herramientas
+        // de depuración; WM_PAINT dibuja la cabecera de `WindowData`
+        let window_name = match style.username_label(user) {
+            header if header.is_empty() => message.to_string(),
+            header => format!("{}: {}", header, message),
+        };
 
-            // Create emote images data structure
-            let emote_images = Box::new(Self::preload_emotes(emotes));
+        // El layout trabaja en píxeles lógicos del monitor principal; la
+        // ventana se coloca y se dibuja en píxeles físicos del suyo
+        let pos = to_physical(pos);
+        let style = &dpi_scaled(style, monitor_dpi_scale(pos));
 
-            // Schedule async download of emote images in background
-            Self::schedule_emote_downloads(emotes.to_vec());
+        // Tamaño según el mensaje ya maquetado y el nombre de usuario
+        let font_size = style.font_size as i32;
+        let layout = match style.bitmap {
+            Some(_) => FlowLayout::default(),
+            None => unsafe { Self::layout_message(message, emotes, style) },
+        };
+        let avatar_width = match style.avatar {
+            Some(_) => crate::avatars::avatar_side(style.font_size) as i32 + 6,
+            None => 0,
+        };
+        let icon_width = match style.platform_icon {
+            Some(_) => font_size + 6,
+            None => 0,
+        };
+        let header_width = unsafe { TextMeasure::new(font_size, FW_BOLD) }
+            .width(&style.username_label(user))
+            + avatar_width
+            + icon_width;
+        let window_width = (layout.width.max(header_width) + MESSAGE_LEFT * 2).clamp(
+            scaled(MIN_WINDOW_WIDTH, style.scale),
+            scaled(MAX_WINDOW_WIDTH, style.scale),
+        );
+        let header = style.username_label(user);
+        let content_y = content_top(
+            font_size,
+            has_header_band(
+                &header,
+                style.avatar.is_some() || style.platform_icon.is_some(),
+            ),
+            style.card.as_ref(),
+        );
+        let window_height =
+            scaled(80, style.scale).max(content_y + layout.height + scaled(5 + 25, style.scale));
+        // Con el mensaje ya compuesto solo queda sitio para la barra de progreso
+        let size = match &style.bitmap {
+            Some(bitmap) => (
+                bitmap.width() as i32,
+                bitmap.height() as i32 + scaled(20, style.scale),
+            ),
+            None => (window_width, window_height),
+        };
 
-            // Store window data
-            let window_data = Box::new(WindowData {
-                progress: 0.0,
-                created_time: std::time::SystemTime::now()
-                    .duration_since(std::time::UNIX_EPOCH)
-                    .unwrap_or_default()
-                    .as_millis() as u64,
-                emote_images: Box::into_raw(emote_images),
-            });
+        let ex_style = if style.draggable {
+            OVERLAY_EX_STYLE & !WS_EX_TRANSPARENT
+        } else {
+            OVERLAY_EX_STYLE
+        };
 
-            SetWindowLongPtrW(hwnd, GWLP_USERDATA, Box::into_raw(window_data) as isize);
+        // Make window semi-transparent; con fundido de entrada empieza invisible
+        let alpha = if style.fade.fade_in.is_zero() {
+            layered_alpha(style.opacity, 1.0)
+        } else {
+            0
+        };
 
-            ShowWindow(hwnd, SW_SHOW);
-            UpdateWindow(hwnd);
+        // Create emote images data structure
+        let emote_images = Self::preload_emotes(emotes, &layout, content_y);
+
+        // Schedule async download of emote images in background
+        Self::schedule_emote_downloads(emotes);
+
+        // Store window data; el hilo de la interfaz le añade los emotes
+        let data = Box::new(WindowData {
+            progress: 0.0,
+            created_time: now_millis(),
+            emote_images: null_mut(),
+            scale: style.scale,
+            font_size: style.font_size as i32,
+            palette: style.palette,
+            animated: style.animated,
+            occluded: false,
+            occlusion_checked: 0,
+            header,
+            card: style.card.clone(),
+            avatar: style.avatar.as_deref().and_then(|png| {
+                crate::avatars::flatten_bgra(
+                    png,
+                    crate::avatars::avatar_side(style.font_size),
+                    style
+                        .palette
+                        .unwrap_or_else(ThemePalette::fallback)
+                        .background,
+                )
+            }),
+            platform_icon: style.platform_icon.map(|icon| icon.bytes),
+            message_runs: layout
+                .items
+                .into_iter()
+                .filter(|item| matches!(item.content, FlowContent::Text(_)))
+                .map(|item| FlowItem {
+                    x: MESSAGE_LEFT + item.x,
+                    y: content_y + item.y,
+                    ..item
+                })
+                .collect(),
+            bitmap: style.bitmap.as_ref().map(|bitmap| {
+                let bgra = bitmap
+                    .pixels()
+                    .flat_map(|pixel| [pixel[2], pixel[1], pixel[0], pixel[3]])
+                    .collect();
+                (bgra, bitmap.width(), bitmap.height())
+            }),
+            draggable: style.draggable,
+            connection_id: style.connection_id.clone(),
+            drag_start: None,
+        });
 
-            WindowsWindow {
-                hwnd,
-                created: Instant::now(),
-                progress: 0.0,
-                username: user.to_string(),
-                message: message.to_string(),
-                emotes: emotes.to_vec(),
-            }
+        NewWindow {
+            title: window_name,
+            pos,
+            size,
+            ex_style,
+            alpha,
+            recycled: None,
+            data,
+            emote_images,
         }
     }
 
+    /// Vuelve a maquetar la ventana con otro mensaje en su posición actual;
+    /// mantiene la opacidad para no repetir el fundido de entrada
+    pub fn update_content(
+        &mut self,
+        user: &str,
+        message: &str,
+        emotes: &[Emote],
+        style: &WindowStyle,
+    ) {
+        let Some(rect) = (unsafe { window_rect(self.hwnd.0) }) else {
+            return;
+        };
+        let mut window = Self::layout_window(
+            user,
+            message,
+            emotes,
+            to_logical((rect.left, rect.top)),
+            style,
+        );
+        window.alpha = self.alpha;
+        window.recycled = Some(self.hwnd);
+        UiThread::global().send(UiCommand::Update(Box::new(window)));
+
+        self.created = Instant::now();
+        self.duration = style.duration;
+        self.username = user.to_string();
+        self.username_label = style.username_label(user);
+        self.message = message.to_string();
+        self.emotes = emotes.to_vec();
+        self.opacity = style.opacity;
+        self.fade = style.fade;
+    }
+
     pub fn close(&self) {
-        unsafe {
-            // Clean up window data before destroying
-            let window_data_ptr = GetWindowLongPtrW(self.hwnd, GWLP_USERDATA) as *mut WindowData;
-            if !window_data_ptr.is_null() {
-                let window_data = Box::from_raw(window_data_ptr);
-                // Clean up emote images
-                if !window_data.emote_images.is_null() {
-                    let _ = Box::from_raw(window_data.emote_images);
+        UiThread::global().send(UiCommand::Close(self.hwnd));
+    }
+
+    /// Aplica `factor` (ver [`FadeTiming::factor`]) a la opacidad configurada
+    pub fn set_fade(&mut self, factor: f64) {
+        let alpha = layered_alpha(self.opacity, factor);
+        if alpha != self.alpha {
+            self.alpha = alpha;
+            UiThread::global().send(UiCommand::SetAlpha(self.hwnd, alpha));
+        }
+    }
+
+    /// Oculta la ventana y libera los datos del mensaje
+    pub fn recycle(self) -> RecycledWindow {
+        UiThread::global().send(UiCommand::Recycle(self.hwnd));
+        RecycledWindow(self.hwnd)
+    }
+
+    /// Muestra `×count` junto al nombre y vuelve a empezar el tiempo visible
+    pub fn set_repeat_count(&mut self, count: u32) {
+        let header = format!("{} ×{}", self.username_label, count);
+        UiThread::global().send(UiCommand::SetHeader(self.hwnd, header));
+        self.created = Instant::now();
+    }
+
+    pub fn set_progress(&mut self, progress: f64) {
+        // Only repaint if progress changed significantly to reduce flickering
+        let repaint = (self.progress - progress).abs() >= 0.02;
+        if repaint {
+            self.progress = progress;
+        }
+        // Aunque no se repinte la barra, los emotes animados avanzan con cada
+        // tick del temporizador
+        UiThread::global().send(UiCommand::SetProgress {
+            hwnd: self.hwnd,
+            progress,
+            repaint,
+        });
+    }
+}
+
+/// Backend de Win32; las ventanas viven en el hilo de la interfaz
+pub struct Win32Backend;
+
+#[async_trait::async_trait(?Send)]
+impl WindowBackend for Win32Backend {
+    type Window = WindowsWindow;
+    type Monitor = WindowGeometry;
+
+    fn monitor_geometry() -> WindowGeometry {
+        get_monitor_geometry()
+    }
+
+    fn monitor_size(monitor: &WindowGeometry) -> (i32, i32) {
+        (monitor.width, monitor.height)
+    }
+
+    fn process_events() -> bool {
+        process_messages()
+    }
+
+    async fn precompose(
+        spec: crate::emotes::MessageBitmapSpec,
+        style: &WindowStyle,
+        position: (i32, i32),
+        _font_family: &str,
+    ) -> Option<image::RgbaImage> {
+        compose_message(spec, style, position).await
+    }
+
+    async fn spawn(
+        header: &str,
+        body: &str,
+        emotes: &[Emote],
+        position: (i32, i32),
+        _monitor: WindowGeometry,
+        style: &WindowStyle,
+        recycled: Option<RecycledWindow>,
+    ) -> WindowsWindow {
+        WindowsWindow::new(header, body, emotes, position, style, recycled).await
+    }
+}
+
+#[async_trait::async_trait(?Send)]
+impl OverlayWindow for WindowsWindow {
+    type Recycled = RecycledWindow;
+
+    fn created(&self) -> Instant {
+        self.created
+    }
+
+    fn duration(&self) -> Duration {
+        self.duration
+    }
+
+    fn shown(&self) -> Instant {
+        self.shown
+    }
+
+    fn fade(&self) -> FadeTiming {
+        self.fade
+    }
+
+    fn set_progress(&mut self, progress: f64) {
+        WindowsWindow::set_progress(self, progress);
+    }
+
+    fn set_fade(&mut self, factor: f64) {
+        WindowsWindow::set_fade(self, factor);
+    }
+
+    fn set_repeat_count(&mut self, count: u32) {
+        WindowsWindow::set_repeat_count(self, count);
+    }
+
+    async fn update_content(
+        &mut self,
+        header: &str,
+        body: &str,
+        emotes: &[Emote],
+        style: &WindowStyle,
+    ) {
+        WindowsWindow::update_content(self, header, body, emotes, style);
+    }
+
+    fn recycle(self) -> RecycledWindow {
+        WindowsWindow::recycle(self)
+    }
+
+    fn close(self) {
+        WindowsWindow::close(&self);
+    }
+}
+
+impl RecycledOverlayWindow for RecycledWindow {
+    fn close(self) {
+        RecycledWindow::close(self);
+    }
+}
+
+fn layered_alpha(opacity: f32, factor: f64) -> u8 {
+    (opacity.clamp(0.0, 1.0) as f64 * factor.clamp(0.0, 1.0) * 255.0).round() as u8
+}
+
+/// Lo que necesita el hilo de la interfaz para mostrar una ventana nueva
+struct NewWindow {
+    title: String,
+    /// Esquina superior izquierda en píxeles físicos
+    pos: (i32, i32),
+    size: (i32, i32),
+    ex_style: u32,
+    alpha: u8,
+    recycled: Option<Hwnd>,
+    /// Con `emote_images` aún nulo
+    data: Box<WindowData>,
+    emote_images: Vec<EmoteImage>,
+}
+
+// `data.emote_images` es nulo hasta que el hilo de la interfaz lo rellena
+unsafe impl Send for NewWindow {}
+
+/// Órdenes que ejecuta el hilo de la interfaz sobre sus ventanas
+enum UiCommand {
+    Create(Box<NewWindow>, tokio::sync::oneshot::Sender<Hwnd>),
+    /// Sustituye los datos de la ventana `recycled` por los de la nueva maquetación
+    Update(Box<NewWindow>),
+    Close(Hwnd),
+    Recycle(Hwnd),
+    SetProgress {
+        hwnd: Hwnd,
+        progress: f64,
+        repaint: bool,
+    },
+    SetAlpha(Hwnd, u8),
+    /// Cambia la cabecera dibujada, sin volver a maquetar la ventana
+    SetHeader(Hwnd, String),
+    /// Responde cuando ya se ejecutaron todas las órdenes anteriores
+    Flush(Sender<()>),
+}
+
+impl UiCommand {
+    unsafe fn run(self) {
+        match self {
+            UiCommand::Create(window, reply) => {
+                let _ = reply.send(Hwnd(create_window(*window)));
+            }
+            UiCommand::Update(window) => {
+                if let Some(Hwnd(hwnd)) = window.recycled {
+                    free_window_data(hwnd);
+                    create_window(*window);
+                }
+            }
+            UiCommand::Close(Hwnd(hwnd)) => {
+                // Clean up window data before destroying
+                free_window_data(hwnd);
+                DestroyWindow(hwnd);
+            }
+            UiCommand::Recycle(Hwnd(hwnd)) => {
+                ShowWindow(hwnd, SW_HIDE);
+                free_window_data(hwnd);
+            }
+            UiCommand::SetProgress {
+                hwnd: Hwnd(hwnd),
+                progress,
+                repaint,
+            } => update_progress(hwnd, progress, repaint),
+            UiCommand::SetAlpha(Hwnd(hwnd), alpha) => {
+                SetLayeredWindowAttributes(hwnd, 0, alpha, LWA_ALPHA);
+            }
+            UiCommand::SetHeader(Hwnd(hwnd), header) => {
+                let window_data_ptr = GetWindowLongPtrW(hwnd, GWLP_USERDATA) as *mut WindowData;
+                if !window_data_ptr.is_null() {
+                    (*window_data_ptr).header = header;
+                    InvalidateRect(hwnd, null_mut(), 1);
                 }
-                SetWindowLongPtrW(self.hwnd, GWLP_USERDATA, 0);
             }
-            DestroyWindow(self.hwnd);
+            UiCommand::Flush(reply) => {
+                let _ = reply.send(());
+            }
         }
     }
+}
 
-    pub fn set_progress(&mut self, progress: f64) {
-        // Only update if progress changed significantly to reduce flickering
-        let progress_diff = (self.progress - progress).abs();
-        if progress_diff < 0.02 {
-            return; // Skip update if change is less than 2%
+/// Mensaje con el que se avisa al hilo de la interfaz de que hay órdenes
+const WM_UI_COMMANDS: u32 = WM_APP + 1;
+
+static UI_THREAD: OnceLock<UiThread> = OnceLock::new();
+/// El hilo de la interfaz salió de su bucle de mensajes
+static UI_CLOSED: AtomicBool = AtomicBool::new(false);
+
+thread_local! {
+    /// Órdenes pendientes; solo existe en el hilo de la interfaz
+    static UI_COMMANDS: RefCell<Option<Receiver<UiCommand>>> = const { RefCell::new(None) };
+}
+
+/// Hilo dueño de todas las ventanas del overlay, con su propio bucle de
+/// mensajes. Las ventanas de Win32 solo se pueden destruir desde el hilo que
+/// las creó y reciben sus mensajes en él, así que el resto de hilos (los del
+/// runtime de tokio incluidos) le mandan [`UiCommand`]s
+struct UiThread {
+    commands: Sender<UiCommand>,
+    /// Ventana solo de mensajes (`HWND_MESSAGE`) que recibe `WM_UI_COMMANDS`;
+    /// a diferencia de los mensajes de hilo, llega también durante el bucle
+    /// modal de arrastre de una ventana
+    notify: Hwnd,
+}
+
+impl UiThread {
+    fn global() -> &'static UiThread {
+        UI_THREAD.get_or_init(UiThread::spawn)
+    }
+
+    fn spawn() -> Self {
+        let (commands, receiver) = channel();
+        let (ready, started) = channel();
+        std::thread::Builder::new()
+            .name("overlay-ui".to_string())
+            .spawn(move || unsafe {
+                UI_COMMANDS.with(|commands| *commands.borrow_mut() = Some(receiver));
+                let _ = ready.send(Hwnd(create_notify_window()));
+                run_message_loop();
+                UI_CLOSED.store(true, Ordering::Relaxed);
+                println!("[WINDOWS] 🛑 UI thread stopped");
+            })
+            .expect("Cannot start the UI thread");
+        let notify = started.recv().expect("UI thread exited during startup");
+        Self { commands, notify }
+    }
+
+    fn send(&self, command: UiCommand) {
+        if self.commands.send(command).is_ok() {
+            unsafe {
+                PostMessageW(self.notify.0, WM_UI_COMMANDS, 0, 0);
+            }
         }
+    }
+}
 
-        self.progress = progress;
-        unsafe {
-            // Update the stored window data
-            let window_data_ptr = GetWindowLongPtrW(self.hwnd, GWLP_USERDATA) as *mut WindowData;
-            if !window_data_ptr.is_null() {
-                (*window_data_ptr).progress = progress;
+unsafe fn create_notify_window() -> HWND {
+    let class_name = wide_string("OverlayUiThread");
+    let hinstance = GetModuleHandleW(null_mut());
+    let wc = WNDCLASSW {
+        style: 0,
+        lpfnWndProc: Some(notify_proc),
+        cbClsExtra: 0,
+        cbWndExtra: 0,
+        hInstance: hinstance,
+        hIcon: null_mut(),
+        hCursor: null_mut(),
+        hbrBackground: null_mut(),
+        lpszMenuName: null_mut(),
+        lpszClassName: class_name.as_ptr(),
+    };
+    RegisterClassW(&wc);
+    CreateWindowExW(
+        0,
+        class_name.as_ptr(),
+        null_mut(),
+        0,
+        0,
+        0,
+        0,
+        0,
+        HWND_MESSAGE,
+        null_mut(),
+        hinstance,
+        null_mut(),
+    )
+}
+
+unsafe extern "system" fn notify_proc(hwnd: HWND, msg: u32, wparam: usize, lparam: isize) -> isize {
+    if msg != WM_UI_COMMANDS {
+        return DefWindowProcW(hwnd, msg, wparam, lparam);
+    }
+    UI_COMMANDS.with(|commands| {
+        if let Some(commands) = commands.borrow().as_ref() {
+            for command in commands.try_iter() {
+                command.run();
             }
+        }
+    });
+    0
+}
 
-            // Only invalidate the progress bar area to reduce flickering
-            let mut rect = RECT {
-                left: 0,
-                top: 0,
-                right: 0,
-                bottom: 0,
-            };
-            GetClientRect(self.hwnd, &mut rect);
-            let progress_rect = RECT {
-                left: 10,
-                top: rect.bottom - 15,
-                right: rect.right - 10,
-                bottom: rect.bottom - 5,
+unsafe fn run_message_loop() {
+    println!(
+        "[WINDOWS] 🧵 UI thread started (id {})",
+        GetCurrentThreadId()
+    );
+    let mut msg: MSG = std::mem::zeroed();
+    while GetMessageW(&mut msg, null_mut(), 0, 0) > 0 {
+        TranslateMessage(&msg);
+        DispatchMessageW(&msg);
+    }
+}
+
+/// Crea la ventana (o reutiliza la del pool) y la muestra; en el hilo de la interfaz
+unsafe fn create_window(window: NewWindow) -> HWND {
+    let NewWindow {
+        title,
+        pos,
+        size,
+        ex_style,
+        alpha,
+        recycled,
+        mut data,
+        emote_images,
+    } = window;
+    let class_name = wide_string("OverlayWindow");
+    let window_name = wide_string(&title);
+    let hinstance = GetModuleHandleW(null_mut());
+
+    // Register window class only once
+    REGISTER_CLASS.call_once(|| {
+        let wc = WNDCLASSW {
+            style: CS_HREDRAW | CS_VREDRAW,
+            lpfnWndProc: Some(window_proc),
+            cbClsExtra: 0,
+            cbWndExtra: 0,
+            hInstance: hinstance,
+            hIcon: null_mut(),
+            hCursor: LoadCursorW(null_mut(), IDC_ARROW),
+            hbrBackground: CreateSolidBrush(colorref(ThemePalette::fallback().background))
+                as *mut _,
+            lpszMenuName: null_mut(),
+            lpszClassName: class_name.as_ptr(),
+        };
+
+        RegisterClassW(&wc);
+    });
+
+    let hwnd = match recycled {
+        Some(Hwnd(hwnd)) => {
+            SetWindowTextW(hwnd, window_name.as_ptr());
+            SetWindowLongW(hwnd, GWL_EXSTYLE, ex_style as i32);
+            SetWindowPos(
+                hwnd,
+                HWND_TOPMOST,
+                pos.0,
+                pos.1,
+                size.0,
+                size.1,
+                SWP_NOACTIVATE,
+            );
+            InvalidateRect(hwnd, null_mut(), 1);
+            hwnd
+        }
+        None => CreateWindowExW(
+            ex_style,
+            class_name.as_ptr(),
+            window_name.as_ptr(),
+            WS_POPUP,
+            pos.0,
+            pos.1,
+            size.0,
+            size.1,
+            null_mut(),
+            null_mut(),
+            hinstance,
+            null_mut(),
+        ),
+    };
+
+    SetLayeredWindowAttributes(hwnd, 0, alpha, LWA_ALPHA);
+
+    data.emote_images = Box::into_raw(Box::new(emote_images));
+    SetWindowLongPtrW(hwnd, GWLP_USERDATA, Box::into_raw(data) as isize);
+
+    // SW_SHOW activaría la ventana y robaría el foco al juego
+    ShowWindow(hwnd, SW_SHOWNOACTIVATE);
+    UpdateWindow(hwnd);
+    hwnd
+}
+
+unsafe fn update_progress(hwnd: HWND, progress: f64, repaint: bool) {
+    // Tapada por otra ventana (OBS, juego a pantalla completa...): se guarda
+    // el progreso para el próximo WM_PAINT pero no se repinta nada
+    if refresh_occlusion(hwnd, progress) {
+        return;
+    }
+
+    // Los emotes animados avanzan con cada tick del temporizador
+    invalidate_animated_emotes(hwnd);
+
+    if !repaint {
+        return;
+    }
+
+    // Update the stored window data
+    let window_data_ptr = GetWindowLongPtrW(hwnd, GWLP_USERDATA) as *mut WindowData;
+    if !window_data_ptr.is_null() {
+        (*window_data_ptr).progress = progress;
+    }
+
+    // Only invalidate the progress bar area to reduce flickering
+    let mut rect = RECT {
+        left: 0,
+        top: 0,
+        right: 0,
+        bottom: 0,
+    };
+    GetClientRect(hwnd, &mut rect);
+    let progress_rect = RECT {
+        left: 10,
+        top: rect.bottom - 15,
+        right: rect.right - 10,
+        bottom: rect.bottom - 5,
+    };
+    InvalidateRect(hwnd, &progress_rect, 0); // Don't erase background
+}
+
+/// Espera (como mucho un segundo) a que el hilo de la interfaz ejecute las
+/// órdenes ya enviadas, p. ej. los cierres antes de salir
+pub fn flush_ui() {
+    let Some(ui) = UI_THREAD.get() else {
+        return;
+    };
+    let (reply, done) = channel();
+    ui.send(UiCommand::Flush(reply));
+    let _ = done.recv_timeout(Duration::from_secs(1));
+}
+
+unsafe fn free_window_data(hwnd: HWND) {
+    let window_data_ptr = GetWindowLongPtrW(hwnd, GWLP_USERDATA) as *mut WindowData;
+    if !window_data_ptr.is_null() {
+        let window_data = Box::from_raw(window_data_ptr);
+        // Clean up emote images
+        if !window_data.emote_images.is_null() {
+            let _ = Box::from_raw(window_data.emote_images);
+        }
+        SetWindowLongPtrW(hwnd, GWLP_USERDATA, 0);
+    }
+}
+
+/// Intervalo mínimo entre comprobaciones de oclusión (recorre el orden Z)
+const OCCLUSION_CHECK_INTERVAL_MS: u64 = 500;
+
+fn now_millis() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+/// Actualiza el estado de oclusión de la ventana y devuelve si está tapada
+///
+/// Mientras está tapada solo se guarda `progress`; al volver a verse se
+/// invalida la ventana entera para no mostrar contenido desfasado.
+unsafe fn refresh_occlusion(hwnd: HWND, progress: f64) -> bool {
+    let window_data_ptr = GetWindowLongPtrW(hwnd, GWLP_USERDATA) as *mut WindowData;
+    if window_data_ptr.is_null() {
+        return false;
+    }
+    let window_data = &mut *window_data_ptr;
+
+    let now = now_millis();
+    if now.saturating_sub(window_data.occlusion_checked) >= OCCLUSION_CHECK_INTERVAL_MS {
+        window_data.occlusion_checked = now;
+        let occluded = is_occluded(hwnd);
+        if window_data.occluded && !occluded {
+            InvalidateRect(hwnd, null_mut(), 0);
+        }
+        window_data.occluded = occluded;
+    }
+
+    if window_data.occluded {
+        window_data.progress = progress;
+    }
+    window_data.occluded
+}
+
+/// Oculta, minimizada, ocultada por DWM (otro escritorio virtual) o cubierta
+/// por completo por una ventana opaca que está por encima en el orden Z
+unsafe fn is_occluded(hwnd: HWND) -> bool {
+    if IsWindowVisible(hwnd) == 0 || IsIconic(hwnd) != 0 || is_cloaked(hwnd) {
+        return true;
+    }
+
+    let Some(own) = window_rect(hwnd) else {
+        return false;
+    };
+    let mut above = GetWindow(hwnd, GW_HWNDPREV);
+    while !above.is_null() {
+        // Las ventanas transparentes al ratón (otros overlays) no tapan
+        let ex_style = GetWindowLongW(above, GWL_EXSTYLE) as u32;
+        if IsWindowVisible(above) != 0
+            && ex_style & WS_EX_TRANSPARENT == 0
+            && !is_cloaked(above)
+            && window_rect(above).map_or(false, |rect| covers(&rect, &own))
+        {
+            return true;
+        }
+        above = GetWindow(above, GW_HWNDPREV);
+    }
+    false
+}
+
+unsafe fn is_cloaked(hwnd: HWND) -> bool {
+    let mut cloaked: DWORD = 0;
+    let result = DwmGetWindowAttribute(
+        hwnd,
+        DWMWA_CLOAKED,
+        &mut cloaked as *mut DWORD as *mut _,
+        std::mem::size_of::<DWORD>() as u32,
+    );
+    result == 0 && cloaked != 0
+}
+
+unsafe fn window_rect(hwnd: HWND) -> Option<RECT> {
+    let mut rect = RECT {
+        left: 0,
+        top: 0,
+        right: 0,
+        bottom: 0,
+    };
+    (GetWindowRect(hwnd, &mut rect) != 0).then_some(rect)
+}
+
+fn covers(outer: &RECT, inner: &RECT) -> bool {
+    outer.left <= inner.left
+        && outer.top <= inner.top
+        && outer.right >= inner.right
+        && outer.bottom >= inner.bottom
+}
+
+/// Invalida el área de los emotes animados para que se pinte el siguiente frame
+unsafe fn invalidate_animated_emotes(hwnd: HWND) {
+    let window_data_ptr = GetWindowLongPtrW(hwnd, GWLP_USERDATA) as *mut WindowData;
+    if window_data_ptr.is_null()
+        || !(*window_data_ptr).animated
+        || (*window_data_ptr).emote_images.is_null()
+    {
+        return;
+    }
+
+    for emote_image in &*(*window_data_ptr).emote_images {
+        if emote_image
+            .timeline
+            .as_ref()
+            .map_or(false, FrameTimeline::is_animated)
+        {
+            let emote_rect = RECT {
+                left: emote_image.x,
+                top: emote_image.y,
+                right: emote_image.x + emote_image.width as i32,
+                bottom: emote_image.y + emote_image.height as i32,
             };
-            InvalidateRect(self.hwnd, &progress_rect, 0); // Don't erase background
+            InvalidateRect(hwnd, &emote_rect, 0);
         }
     }
 }
 
+/// Milisegundos transcurridos desde la creación de la ventana
+fn window_age(window_data: &WindowData) -> Duration {
+    Duration::from_millis(now_millis().saturating_sub(window_data.created_time))
+}
+
 fn wide_string(s: &str) -> Vec<u16> {
     OsStr::new(s).encode_wide().chain(once(0)).collect()
 }
 
+/// Escala una medida en píxeles según el factor de estilo de la ventana
+fn scaled(value: i32, scale: f32) -> i32 {
+    (value as f32 * scale).round() as i32
+}
+
+/// Estilo con la fuente, los emotes y las medidas de la ventana
+/// multiplicados por la escala DPI del monitor
+fn dpi_scaled(style: &WindowStyle, dpi_scale: f32) -> WindowStyle {
+    WindowStyle {
+        scale: style.scale * dpi_scale,
+        font_size: (style.font_size as f32 * dpi_scale).round() as u32,
+        ..style.clone()
+    }
+}
+
+/// Margen izquierdo del nombre, la cabecera y el mensaje
+const MESSAGE_LEFT: i32 = 10;
+
+/// Ancho de la ventana antes de aplicar la escala del estilo
+const MIN_WINDOW_WIDTH: i32 = 200;
+const MAX_WINDOW_WIDTH: i32 = 400;
+
+/// Arial del tamaño y peso indicados; se libera con `DeleteObject`
+unsafe fn create_font(font_size: i32, weight: i32) -> HFONT {
+    CreateFontW(
+        font_size,
+        0,
+        0,
+        0,
+        weight,
+        0,
+        0,
+        0,
+        DEFAULT_CHARSET,
+        OUT_DEFAULT_PRECIS,
+        CLIP_DEFAULT_PRECIS,
+        DEFAULT_QUALITY,
+        DEFAULT_PITCH | FF_DONTCARE,
+        wide_string("Arial").as_ptr(),
+    )
+}
+
+/// Mide texto con `GetTextExtentPoint32W` sobre el DC de la pantalla
+struct TextMeasure {
+    hdc: HDC,
+    font: HFONT,
+    old_font: HGDIOBJ,
+}
+
+impl TextMeasure {
+    unsafe fn new(font_size: i32, weight: i32) -> Self {
+        let hdc = GetDC(null_mut());
+        let font = create_font(font_size, weight);
+        let old_font = SelectObject(hdc, font as *mut _);
+        Self {
+            hdc,
+            font,
+            old_font,
+        }
+    }
+
+    /// Ancho en píxeles del texto en una sola línea
+    fn width(&self, text: &str) -> i32 {
+        let wide: Vec<u16> = text.encode_utf16().collect();
+        let mut size = SIZE { cx: 0, cy: 0 };
+        unsafe {
+            GetTextExtentPoint32W(self.hdc, wide.as_ptr(), wide.len() as i32, &mut size);
+        }
+        size.cx
+    }
+}
+
+impl Drop for TextMeasure {
+    fn drop(&mut self) {
+        unsafe {
+            SelectObject(self.hdc, self.old_font);
+            DeleteObject(self.font as *mut _);
+            ReleaseDC(null_mut(), self.hdc);
+        }
+    }
+}
+
+/// Altura reservada para el nombre de usuario
+fn header_height(font_size: i32) -> i32 {
+    5 + font_size + 6
+}
+
+/// Tamaño de fuente de los meses en la cabecera del evento
+fn card_font_size(font_size: i32) -> i32 {
+    font_size * 8 / 5
+}
+
+/// Con una plantilla de una sola línea y sin avatar ni icono de plataforma no
+/// se reserva la franja del nombre de usuario
+fn has_header_band(header: &str, images: bool) -> bool {
+    !header.is_empty() || images
+}
+
+/// Inicio de la cabecera del evento: bajo el usuario, si lo hay
+fn card_top(font_size: i32, header_band: bool) -> i32 {
+    if header_band {
+        header_height(font_size)
+    } else {
+        5
+    }
+}
+
+/// Inicio de los emotes y el mensaje: bajo el usuario y la cabecera del evento
+fn content_top(font_size: i32, header_band: bool, card: Option<&EventCard>) -> i32 {
+    let card_height = match card {
+        Some(card) if card.detail.is_some() => card_font_size(font_size) + font_size + 8,
+        Some(_) => card_font_size(font_size) + 4,
+        None => 0,
+    };
+    card_top(font_size, header_band) + card_height
+}
+
+/// Dibuja una línea de texto en Arial con el peso indicado y devuelve su altura
+unsafe fn draw_text_line(
+    hdc: HDC,
+    text: &str,
+    left: i32,
+    top: i32,
+    right: i32,
+    font_size: i32,
+    weight: i32,
+) -> i32 {
+    let font = create_font(font_size, weight);
+    let old_font = SelectObject(hdc, font as *mut _);
+    let text_wide = wide_string(text);
+    let mut text_rect = RECT {
+        left,
+        top,
+        right,
+        bottom: top + font_size + 4,
+    };
+    DrawTextW(
+        hdc,
+        text_wide.as_ptr(),
+        text_wide.len() as i32 - 1,
+        &mut text_rect,
+        DT_LEFT | DT_TOP | DT_SINGLELINE | DT_END_ELLIPSIS,
+    );
+    SelectObject(hdc, old_font);
+    DeleteObject(font as *mut _);
+    font_size + 4
+}
+
+fn colorref(color: Rgb) -> u32 {
+    RGB(color.0, color.1, color.2)
+}
+
 // Separate rendering function to reduce flickering with double buffering
 unsafe fn render_window_content(hdc: HDC, rect: &RECT, hwnd: HWND) {
+    let window_data_ptr = GetWindowLongPtrW(hwnd, GWLP_USERDATA) as *mut WindowData;
+    let (font_size, scale, palette) = if !window_data_ptr.is_null() {
+        (
+            (*window_data_ptr).font_size,
+            (*window_data_ptr).scale,
+            (*window_data_ptr).palette.unwrap_or_else(ThemePalette::fallback),
+        )
+    } else {
+        (14, 1.0, ThemePalette::fallback())
+    };
+
     // Background
-    let bg_brush = CreateSolidBrush(RGB(40, 40, 40));
+    let bg_brush = CreateSolidBrush(colorref(palette.background));
     FillRect(hdc, rect, bg_brush);
     DeleteObject(bg_brush as *mut _);
 
+    if palette.border != palette.background {
+        let border_brush = CreateSolidBrush(colorref(palette.border));
+        FrameRect(hdc, rect, border_brush);
+        DeleteObject(border_brush as *mut _);
+    }
+
     // Set text properties
-    SetTextColor(hdc, RGB(255, 255, 255));
+    SetTextColor(hdc, colorref(palette.text));
     SetBkMode(hdc, TRANSPARENT as i32);
 
-    // Get window title to extract username and message
-    let mut title_buffer = [0u16; 512];
-    let title_len = GetWindowTextW(hwnd, title_buffer.as_mut_ptr(), 512);
-    if title_len > 0 {
-        let title = String::from_utf16_lossy(&title_buffer[..title_len as usize]);
-        if let Some(colon_pos) = title.find(": ") {
-            let username = &title[..colon_pos];
-            let message = &title[colon_pos + 2..];
+    // `display.precomposed`: el mensaje ya viene dibujado
+    let bitmap = if window_data_ptr.is_null() {
+        None
+    } else {
+        (*window_data_ptr).bitmap.as_ref()
+    };
+    if let Some((bgra, width, height)) = bitmap {
+        blit_bgra(hdc, bgra, 0, 0, *width, *height);
+    }
+
+    // Sin datos (ventana reciclada) o con el mensaje ya compuesto solo queda la barra
+    if !window_data_ptr.is_null() && bitmap.is_none() {
+        let window_data = &mut *window_data_ptr;
+        let header_band = has_header_band(
+            &window_data.header,
+            window_data.avatar.is_some() || window_data.platform_icon.is_some(),
+        );
+        if header_band {
+            // Icono de la plataforma y avatar a la izquierda del nombre
+            let mut username_left = 10;
+            if let Some(icon) = window_data.platform_icon {
+                let side = font_size.max(0) as u32;
+                if draw_image(hdc, icon, username_left, 5, side, side) {
+                    username_left += font_size + 6;
+                }
+            }
+            if let Some(avatar) = &window_data.avatar {
+                let side = crate::avatars::avatar_side(font_size as u32);
+                if blit_bgra(hdc, avatar, username_left, 3, side, side) {
+                    username_left += side as i32 + 6;
+                }
+            }
 
             // Draw username (bold)
-            let username_wide = wide_string(username);
+            let username_wide = wide_string(&window_data.header);
             let mut username_rect = RECT {
-                left: 10,
+                left: username_left,
                 top: 5,
                 right: rect.right - 10,
-                bottom: 25,
+                bottom: header_height(font_size),
             };
 
             // Create bold font for username
-            let bold_font = CreateFontW(
-                14,
-                0,
-                0,
-                0,
-                FW_BOLD,
-                0,
-                0,
-                0,
-                DEFAULT_CHARSET,
-                OUT_DEFAULT_PRECIS,
-                CLIP_DEFAULT_PRECIS,
-                DEFAULT_QUALITY,
-                DEFAULT_PITCH | FF_DONTCARE,
-                wide_string("Arial").as_ptr(),
-            );
+            let bold_font = create_font(font_size, FW_BOLD);
             let old_font = SelectObject(hdc, bold_font as *mut _);
 
+            SetTextColor(hdc, colorref(palette.username));
             DrawTextW(
                 hdc,
                 username_wide.as_ptr(),
@@ -324,24 +1243,63 @@ unsafe fn render_window_content(hdc: HDC, rect: &RECT, hwnd: HWND) {
             // Restore original font and delete bold font
             SelectObject(hdc, old_font);
             DeleteObject(bold_font as *mut _);
+        }
 
-            // Draw emotes first (if any)
-            let window_data_ptr = GetWindowLongPtrW(hwnd, GWLP_USERDATA) as *mut WindowData;
-            if !window_data_ptr.is_null() && !(*window_data_ptr).emote_images.is_null() {
-                let emote_images = &*(*window_data_ptr).emote_images;
-                let cache = get_emote_cache();
-
-                for emote_image in emote_images {
-                    // Try to get image from cache
-                    let image_data = if let Ok(cache_guard) = cache.lock() {
-                        cache_guard.get(&emote_image.id).cloned()
-                    } else {
-                        None
-                    };
-
-                    render_emote_image(
+        // Cabecera del evento: meses en grande con el color de acento
+        if let Some(card) = &window_data.card {
+            let mut top = card_top(font_size, header_band);
+            SetTextColor(hdc, colorref(palette.progress));
+            top += draw_text_line(
+                hdc,
+                &card.headline,
+                10,
+                top,
+                rect.right - 10,
+                card_font_size(font_size),
+                FW_BOLD,
+            );
+            if let Some(detail) = &card.detail {
+                SetTextColor(hdc, colorref(palette.text));
+                draw_text_line(hdc, detail, 10, top, rect.right - 10, font_size, FW_NORMAL);
+            }
+        }
+        SetTextColor(hdc, colorref(palette.text));
+
+        // Emotes en su sitio dentro del texto
+        if !window_data.emote_images.is_null() {
+            let emote_images = &mut *window_data.emote_images;
+            let downloader = crate::emotes::EmoteDownloader::global();
+            let elapsed = if window_data.animated {
+                window_age(window_data)
+            } else {
+                Duration::ZERO
+            };
+
+            for emote_image in emote_images.iter_mut() {
+                // Decode once, as soon as the image is in the cache
+                if emote_image.timeline.is_none() {
+                    let image_data = downloader
+                        .image(&emote_image.id)
+                        .map(|bytes| bytes.to_vec())
+                        .or_else(|| crate::emotes::composited_image(&emote_image.id));
+                    emote_image.timeline = image_data.and_then(|bytes| {
+                        FrameTimeline::decode(&bytes, emote_image.width, emote_image.height)
+                    });
+                }
+
+                let drawn = emote_image.timeline.as_ref().map_or(false, |timeline| {
+                    blit_bgra(
+                        hdc,
+                        &timeline.frame_at(elapsed).bgra,
+                        emote_image.x,
+                        emote_image.y,
+                        timeline.width,
+                        timeline.height,
+                    )
+                });
+                if !drawn {
+                    render_emote_placeholder(
                         hdc,
-                        image_data.as_deref().unwrap_or(&[]),
                         emote_image.x,
                         emote_image.y,
                         emote_image.width,
@@ -349,53 +1307,35 @@ unsafe fn render_window_content(hdc: HDC, rect: &RECT, hwnd: HWND) {
                     );
                 }
             }
+        }
 
-            // Draw message (adjust position if there are emotes)
-            let message_y =
-                if !window_data_ptr.is_null() && !(*window_data_ptr).emote_images.is_null() {
-                    let emote_images = &*(*window_data_ptr).emote_images;
-                    if !emote_images.is_empty() {
-                        60 // Space for emotes
-                    } else {
-                        25
-                    }
-                } else {
-                    25
-                };
-
-            let message_wide = wide_string(message);
-            let mut message_rect = RECT {
-                left: 10,
-                top: message_y,
-                right: rect.right - 10,
-                bottom: rect.bottom - 25,
-            };
-
-            DrawTextW(
-                hdc,
-                message_wide.as_ptr(),
-                message_wide.len() as i32 - 1,
-                &mut message_rect,
-                DT_LEFT | DT_TOP | DT_WORDBREAK,
-            );
+        // Texto del mensaje en las posiciones calculadas al crear la ventana
+        let message_font = create_font(font_size, FW_NORMAL);
+        let old_font = SelectObject(hdc, message_font as *mut _);
+        for item in &window_data.message_runs {
+            if let FlowContent::Text(text) = &item.content {
+                let wide: Vec<u16> = text.encode_utf16().collect();
+                TextOutW(hdc, item.x, item.y, wide.as_ptr(), wide.len() as i32);
+            }
         }
+        SelectObject(hdc, old_font);
+        DeleteObject(message_font as *mut _);
     }
 
     // Draw progress bar
     let progress_bg_rect = RECT {
         left: 10,
-        top: rect.bottom - 15,
+        top: rect.bottom - scaled(15, scale),
         right: rect.right - 10,
-        bottom: rect.bottom - 5,
+        bottom: rect.bottom - scaled(5, scale),
     };
 
     // Progress background
-    let progress_bg_brush = CreateSolidBrush(RGB(60, 60, 60));
+    let progress_bg_brush = CreateSolidBrush(colorref(palette.progress_background));
     FillRect(hdc, &progress_bg_rect, progress_bg_brush);
     DeleteObject(progress_bg_brush as *mut _);
 
     // Get progress from stored window data
-    let window_data_ptr = GetWindowLongPtrW(hwnd, GWLP_USERDATA) as *mut WindowData;
     let progress = if !window_data_ptr.is_null() {
         (*window_data_ptr).progress
     } else {
@@ -413,145 +1353,233 @@ unsafe fn render_window_content(hdc: HDC, rect: &RECT, hwnd: HWND) {
             bottom: progress_bg_rect.bottom,
         };
 
-        let progress_brush = CreateSolidBrush(RGB(0, 150, 255));
+        let progress_brush = CreateSolidBrush(colorref(palette.progress));
         FillRect(hdc, &progress_rect, progress_brush);
         DeleteObject(progress_brush as *mut _);
     }
 }
 
-/// Render an emote image using Windows GDI with real image decoding
-
-unsafe fn render_emote_image(hdc: HDC, image_data: &[u8], x: i32, y: i32, width: u32, height: u32) {
-    // If no image data, render placeholder
+/// Decode and blit an image; returns false if nothing was drawn
+unsafe fn draw_image(hdc: HDC, image_data: &[u8], x: i32, y: i32, width: u32, height: u32) -> bool {
     if image_data.is_empty() {
-        render_emote_placeholder(hdc, x, y, width, height);
-        return;
+        return false;
     }
 
-    // Try to decode the image
-    if let Ok(image) = image::load_from_memory(image_data) {
-        let rgba_image = image.to_rgba8();
-        let (img_width, img_height) = rgba_image.dimensions();
-
-        // Scale to target size if needed
-        let (target_width, target_height) = (width as u32, height as u32);
-        let scaled_image = if img_width != target_width || img_height != target_height {
-            image::imageops::resize(
-                &rgba_image,
-                target_width,
-                target_height,
-                image::imageops::FilterType::Lanczos3,
-            )
-        } else {
-            rgba_image
-        };
+    match FrameTimeline::decode(image_data, width, height) {
+        Some(timeline) => blit_bgra(
+            hdc,
+            &timeline.frame_at(Duration::ZERO).bgra,
+            x,
+            y,
+            width,
+            height,
+        ),
+        None => false,
+    }
+}
 
-        // Create bitmap from image data
-        let bitmap_info = BITMAPINFO {
-            bmiHeader: BITMAPINFOHEADER {
-                biSize: std::mem::size_of::<BITMAPINFOHEADER>() as u32,
-                biWidth: target_width as i32,
-                biHeight: -(target_height as i32), // Negative for top-down bitmap
-                biPlanes: 1,
-                biBitCount: 32,
-                biCompression: BI_RGB,
-                biSizeImage: 0,
-                biXPelsPerMeter: 0,
-                biYPelsPerMeter: 0,
-                biClrUsed: 0,
-                biClrImportant: 0,
-            },
-            bmiColors: [RGBQUAD {
-                rgbBlue: 0,
-                rgbGreen: 0,
-                rgbRed: 0,
-                rgbReserved: 0,
-            }],
-        };
+/// Blit a top-down BGRA buffer of `width`x`height` pixels
+unsafe fn blit_bgra(hdc: HDC, bgra: &[u8], x: i32, y: i32, width: u32, height: u32) -> bool {
+    if bgra.len() < (width * height * 4) as usize {
+        return false;
+    }
 
-        let mut bitmap: HBITMAP = null_mut();
-        let mut bitmap_bits: *mut winapi::ctypes::c_void = null_mut();
+    let bitmap_info = BITMAPINFO {
+        bmiHeader: BITMAPINFOHEADER {
+            biSize: std::mem::size_of::<BITMAPINFOHEADER>() as u32,
+            biWidth: width as i32,
+            biHeight: -(height as i32), // Negative for top-down bitmap
+            biPlanes: 1,
+            biBitCount: 32,
+            biCompression: BI_RGB,
+            biSizeImage: 0,
+            biXPelsPerMeter: 0,
+            biYPelsPerMeter: 0,
+            biClrUsed: 0,
+            biClrImportant: 0,
+        },
+        bmiColors: [RGBQUAD {
+            rgbBlue: 0,
+            rgbGreen: 0,
+            rgbRed: 0,
+            rgbReserved: 0,
+        }],
+    };
 
-        bitmap = CreateDIBSection(
-            hdc,
-            &bitmap_info,
-            DIB_RGB_COLORS,
-            &mut bitmap_bits,
-            null_mut(),
-            0,
-        );
+    let mut bitmap_bits: *mut winapi::ctypes::c_void = null_mut();
+    let bitmap: HBITMAP = CreateDIBSection(
+        hdc,
+        &bitmap_info,
+        DIB_RGB_COLORS,
+        &mut bitmap_bits,
+        null_mut(),
+        0,
+    );
+    if bitmap.is_null() || bitmap_bits.is_null() {
+        return false;
+    }
 
-        if !bitmap.is_null() && !bitmap_bits.is_null() {
-            // Copy image data to bitmap
-            let pixels = std::slice::from_raw_parts_mut(
-                bitmap_bits as *mut u8,
-                (target_width * target_height * 4) as usize,
-            );
+    let pixels =
+        std::slice::from_raw_parts_mut(bitmap_bits as *mut u8, (width * height * 4) as usize);
+    pixels.copy_from_slice(&bgra[..pixels.len()]);
+
+    // Create memory DC and select bitmap
+    let mem_dc = CreateCompatibleDC(hdc);
+    let old_bitmap = SelectObject(mem_dc, bitmap as *mut _);
+
+    let success = BitBlt(
+        hdc,
+        x,
+        y,
+        width as i32,
+        height as i32,
+        mem_dc,
+        0,
+        0,
+        SRCCOPY,
+    );
+
+    // Cleanup
+    SelectObject(mem_dc, old_bitmap);
+    DeleteDC(mem_dc);
+    DeleteObject(bitmap as _);
+
+    success != 0
+}
 
-            // Convert RGBA to BGRA format for Windows
-            for (i, pixel) in scaled_image.chunks_exact(4).enumerate() {
-                if i * 4 < pixels.len() {
-                    pixels[i * 4] = pixel[2]; // B
-                    pixels[i * 4 + 1] = pixel[1]; // G
-                    pixels[i * 4 + 2] = pixel[0]; // R
-                    pixels[i * 4 + 3] = pixel[3]; // A
-                }
-            }
+/// Texto dibujado con GDI en un DIB en memoria; no necesita ninguna ventana,
+/// así que sirve para componer mensajes en un hilo de trabajo
+pub struct GdiText {
+    font_size: i32,
+}
 
-            // Create memory DC and select bitmap
-            let mem_dc = CreateCompatibleDC(hdc);
-            let old_bitmap = SelectObject(mem_dc, bitmap as *mut _);
+impl GdiText {
+    pub fn new(font_size: u32) -> Self {
+        Self {
+            font_size: font_size as i32,
+        }
+    }
+}
 
-            // Draw the bitmap
-            let success = BitBlt(
-                hdc,
-                x,
-                y,
-                target_width as i32,
-                target_height as i32,
+impl crate::emotes::TextRasterizer for GdiText {
+    fn measure(&mut self, text: &str, bold: bool) -> i32 {
+        let weight = if bold { FW_BOLD } else { FW_NORMAL };
+        unsafe { TextMeasure::new(self.font_size, weight).width(text) }
+    }
+
+    fn rasterize(&mut self, text: &str, bold: bool, color: Rgb) -> image::RgbaImage {
+        let weight = if bold { FW_BOLD } else { FW_NORMAL };
+        let (width, height) = (self.measure(text, bold).max(1), self.line_height());
+        let mut image = image::RgbaImage::new(width as u32, height as u32);
+        unsafe {
+            let bitmap_info = BITMAPINFO {
+                bmiHeader: BITMAPINFOHEADER {
+                    biSize: std::mem::size_of::<BITMAPINFOHEADER>() as u32,
+                    biWidth: width,
+                    biHeight: -height,
+                    biPlanes: 1,
+                    biBitCount: 32,
+                    biCompression: BI_RGB,
+                    biSizeImage: 0,
+                    biXPelsPerMeter: 0,
+                    biYPelsPerMeter: 0,
+                    biClrUsed: 0,
+                    biClrImportant: 0,
+                },
+                bmiColors: [RGBQUAD {
+                    rgbBlue: 0,
+                    rgbGreen: 0,
+                    rgbRed: 0,
+                    rgbReserved: 0,
+                }],
+            };
+            let mem_dc = CreateCompatibleDC(null_mut());
+            if mem_dc.is_null() {
+                return image;
+            }
+            let mut bits: *mut winapi::ctypes::c_void = null_mut();
+            let bitmap: HBITMAP = CreateDIBSection(
                 mem_dc,
+                &bitmap_info,
+                DIB_RGB_COLORS,
+                &mut bits,
+                null_mut(),
                 0,
-                0,
-                SRCCOPY,
             );
+            if bitmap.is_null() || bits.is_null() {
+                DeleteDC(mem_dc);
+                return image;
+            }
+            let old_bitmap = SelectObject(mem_dc, bitmap as *mut _);
+            let font = create_font(self.font_size, weight);
+            let old_font = SelectObject(mem_dc, font as *mut _);
+
+            // Blanco sobre negro: la luminancia de cada píxel es la cobertura del texto
+            SetBkMode(mem_dc, OPAQUE as i32);
+            SetBkColor(mem_dc, RGB(0, 0, 0));
+            SetTextColor(mem_dc, RGB(255, 255, 255));
+            let wide: Vec<u16> = text.encode_utf16().collect();
+            TextOutW(mem_dc, 0, 0, wide.as_ptr(), wide.len() as i32);
+            GdiFlush();
+
+            let pixels =
+                std::slice::from_raw_parts(bits as *const u8, (width * height * 4) as usize);
+            for (pixel, bgra) in image.pixels_mut().zip(pixels.chunks_exact(4)) {
+                let coverage = bgra[0].max(bgra[1]).max(bgra[2]);
+                *pixel = image::Rgba([color.0, color.1, color.2, coverage]);
+            }
 
-            // Cleanup
+            SelectObject(mem_dc, old_font);
+            DeleteObject(font as *mut _);
             SelectObject(mem_dc, old_bitmap);
-            DeleteDC(mem_dc);
             DeleteObject(bitmap as _);
+            DeleteDC(mem_dc);
+        }
+        image
+    }
 
-            if success == 0 {
-                // Fallback to rectangle if BitBlt failed
-                render_emote_placeholder(hdc, x, y, width, height);
-            }
-        } else {
-            // Fallback to rectangle if bitmap creation failed
-            render_emote_placeholder(hdc, x, y, width, height);
+    fn line_height(&self) -> i32 {
+        self.font_size + 4
+    }
+
+    fn resized(&self, factor: f32) -> Self {
+        Self {
+            font_size: (self.font_size as f32 * factor).round() as i32,
         }
-    } else {
-        // Fallback to rectangle if image decoding failed
-        render_emote_placeholder(hdc, x, y, width, height);
     }
 }
 
-/// Fallback function to render a placeholder rectangle when image rendering fails
+/// Compone el mensaje para una ventana en `pos` (píxeles lógicos) con la
+/// fuente y la escala del DPI de su monitor
+pub async fn compose_message(
+    mut spec: crate::emotes::MessageBitmapSpec,
+    style: &WindowStyle,
+    pos: (i32, i32),
+) -> Option<image::RgbaImage> {
+    let scaled_style = dpi_scaled(style, monitor_dpi_scale(to_physical(pos)));
+    spec.scale = scaled_style.scale;
+    // La escala del estilo también agranda el texto, como los emotes y márgenes
+    let font_size = (scaled_style.font_size as f32 * style.scale).round() as u32;
+    crate::emotes::render_message_bitmap(spec, GdiText::new(font_size)).await
+}
+
+/// Fallback when an emote image can't be rendered: the bundled placeholder art,
+/// or a plain tile if even that fails
 unsafe fn render_emote_placeholder(hdc: HDC, x: i32, y: i32, width: u32, height: u32) {
+    let placeholder = crate::assets::emote_placeholder();
+    if draw_image(hdc, placeholder.bytes, x, y, width, height) {
+        return;
+    }
+
     let emote_rect = RECT {
         left: x,
         top: y,
         right: x + width as i32,
         bottom: y + height as i32,
     };
-
-    // Draw a purple rectangle as emote placeholder
-    let emote_brush = CreateSolidBrush(RGB(128, 0, 128));
+    let emote_brush = CreateSolidBrush(RGB(43, 43, 51));
     FillRect(hdc, &emote_rect, emote_brush);
     DeleteObject(emote_brush as _);
-
-    // Draw border
-    let border_brush = CreateSolidBrush(RGB(255, 255, 255));
-    FrameRect(hdc, &emote_rect, border_brush);
-    DeleteObject(border_brush as _);
 }
 
 unsafe extern "system" fn window_proc(hwnd: HWND, msg: u32, wparam: usize, lparam: isize) -> isize {
@@ -612,6 +1640,44 @@ unsafe extern "system" fn window_proc(hwnd: HWND, msg: u32, wparam: usize, lpara
             EndPaint(hwnd, &ps);
             0
         }
+        // Un clic nunca debe activar el overlay
+        WM_MOUSEACTIVATE => MA_NOACTIVATE as isize,
+        // Las ventanas arrastrables se mueven como si todo fuera la barra de título
+        WM_NCHITTEST => {
+            let window_data_ptr = GetWindowLongPtrW(hwnd, GWLP_USERDATA) as *mut WindowData;
+            if !window_data_ptr.is_null() && (*window_data_ptr).draggable {
+                HTCAPTION
+            } else {
+                DefWindowProcW(hwnd, msg, wparam, lparam)
+            }
+        }
+        WM_ENTERSIZEMOVE => {
+            let window_data_ptr = GetWindowLongPtrW(hwnd, GWLP_USERDATA) as *mut WindowData;
+            if !window_data_ptr.is_null() {
+                (*window_data_ptr).drag_start =
+                    window_rect(hwnd).map(|rect| (rect.left, rect.top));
+            }
+            0
+        }
+        // Al soltarla, lo que se movió (en píxeles lógicos) pasa a la conexión
+        WM_EXITSIZEMOVE => {
+            let window_data_ptr = GetWindowLongPtrW(hwnd, GWLP_USERDATA) as *mut WindowData;
+            if !window_data_ptr.is_null() {
+                let window_data = &mut *window_data_ptr;
+                if let (Some(from), Some(rect), Some(connection_id)) = (
+                    window_data.drag_start.take(),
+                    window_rect(hwnd),
+                    window_data.connection_id.as_deref(),
+                ) {
+                    crate::layout::LayoutMemory::global().record_move(
+                        connection_id,
+                        to_logical(from),
+                        to_logical((rect.left, rect.top)),
+                    );
+                }
+            }
+            0
+        }
         WM_DESTROY => {
             // Clean up window data to prevent memory leak
             let window_data_ptr = GetWindowLongPtrW(hwnd, GWLP_USERDATA) as *mut WindowData;
@@ -629,7 +1695,107 @@ unsafe extern "system" fn window_proc(hwnd: HWND, msg: u32, wparam: usize, lpara
     }
 }
 
+/// Posición del puntero relativa al escritorio, en píxeles lógicos
+pub fn cursor_position() -> Option<(i32, i32)> {
+    let mut point = POINT { x: 0, y: 0 };
+    let origin = get_monitor_geometry();
+    unsafe {
+        if GetCursorPos(&mut point) == 0 {
+            return None;
+        }
+    }
+    let scale = get_dpi_scale();
+    Some((
+        (point.x as f64 / scale).round() as i32 - origin.x,
+        (point.y as f64 / scale).round() as i32 - origin.y,
+    ))
+}
+
+/// `DPI_AWARENESS_CONTEXT_PER_MONITOR_AWARE_V2`
+const PER_MONITOR_AWARE_V2: isize = -4;
+/// `MDT_EFFECTIVE_DPI`: el DPI que el usuario eligió en la configuración de pantalla
+const MDT_EFFECTIVE_DPI: u32 = 0;
+
+type SetProcessDpiAwarenessContextFn = unsafe extern "system" fn(*mut c_void) -> i32;
+type GetDpiForMonitorFn = unsafe extern "system" fn(HMONITOR, u32, *mut u32, *mut u32) -> i32;
+
+/// Función exportada por una DLL del sistema; `None` si esta versión de
+/// Windows no la tiene
+unsafe fn system_proc(dll: &str, name: &[u8]) -> Option<*mut c_void> {
+    let module = LoadLibraryW(wide_string(dll).as_ptr());
+    if module.is_null() {
+        return None;
+    }
+    let proc = GetProcAddress(module, name.as_ptr() as *const i8);
+    (!proc.is_null()).then_some(proc as *mut c_void)
+}
+
+/// Declara el proceso consciente del DPI de cada monitor para que Windows no
+/// estire las ventanas (borrosas y diminutas en pantallas al 150 % o 200 %).
+/// Usa `SetProcessDpiAwarenessContext` (Windows 10 1703+) y, si no existe,
+/// el DPI del sistema. Hay que llamarla antes de crear ventanas o medir el monitor
+pub fn enable_dpi_awareness() {
+    static AWARENESS: Once = Once::new();
+    AWARENESS.call_once(|| unsafe {
+        if let Some(proc) = system_proc("user32.dll", b"SetProcessDpiAwarenessContext\0") {
+            let set_context: SetProcessDpiAwarenessContextFn = std::mem::transmute(proc);
+            if set_context(PER_MONITOR_AWARE_V2 as *mut c_void) != 0 {
+                println!("[WINDOWS] 🖥️ Per-monitor DPI awareness enabled");
+                return;
+            }
+        }
+        SetProcessDPIAware();
+    });
+}
+
+/// Escala DPI del monitor que contiene el punto (en píxeles físicos); la del
+/// sistema si Windows no expone `GetDpiForMonitor` (anterior a 8.1)
+fn monitor_dpi_scale(point: (i32, i32)) -> f32 {
+    static GET_DPI_FOR_MONITOR: OnceLock<Option<GetDpiForMonitorFn>> = OnceLock::new();
+    let get_dpi = GET_DPI_FOR_MONITOR.get_or_init(|| unsafe {
+        system_proc("shcore.dll", b"GetDpiForMonitor\0")
+            .map(|proc| std::mem::transmute::<*mut c_void, GetDpiForMonitorFn>(proc))
+    });
+
+    if let Some(get_dpi) = get_dpi {
+        unsafe {
+            let monitor = MonitorFromPoint(
+                POINT {
+                    x: point.0,
+                    y: point.1,
+                },
+                MONITOR_DEFAULTTONEAREST,
+            );
+            let (mut dpi_x, mut dpi_y) = (0u32, 0u32);
+            if !monitor.is_null()
+                && get_dpi(monitor, MDT_EFFECTIVE_DPI, &mut dpi_x, &mut dpi_y) == 0
+                && dpi_x > 0
+            {
+                return dpi_x as f32 / 96.0;
+            }
+        }
+    }
+    get_dpi_scale() as f32
+}
+
+/// Posición lógica del layout a píxeles físicos del escritorio
+fn to_physical(pos: (i32, i32)) -> (i32, i32) {
+    let scale = get_dpi_scale() as f32;
+    (scaled(pos.0, scale), scaled(pos.1, scale))
+}
+
+/// Píxeles físicos del escritorio a la posición lógica del layout
+fn to_logical(pos: (i32, i32)) -> (i32, i32) {
+    let scale = get_dpi_scale();
+    let logical = |value: i32| (value as f64 / scale).round() as i32;
+    (logical(pos.0), logical(pos.1))
+}
+
+/// Escritorio en píxeles lógicos (96 ppp), los que usa el layout para
+/// colocar las ventanas
 pub fn get_monitor_geometry() -> WindowGeometry {
+    let scale = get_dpi_scale();
+    let logical = |value: i32| (value as f64 / scale).round() as i32;
     unsafe {
         let desktop = GetDesktopWindow();
         let mut rect = RECT {
@@ -641,32 +1807,34 @@ pub fn get_monitor_geometry() -> WindowGeometry {
         GetWindowRect(desktop, &mut rect);
 
         WindowGeometry {
-            x: rect.left,
-            y: rect.top,
-            width: rect.right - rect.left,
-            height: rect.bottom - rect.top,
+            x: logical(rect.left),
+            y: logical(rect.top),
+            width: logical(rect.right - rect.left),
+            height: logical(rect.bottom - rect.top),
         }
     }
 }
 
-pub fn process_messages() -> bool {
+/// Escala DPI del sistema (la del monitor principal, 1.0 = 96 ppp); 1.0 si
+/// no se llamó a [`enable_dpi_awareness`], porque Windows ya escala sus ventanas
+pub fn get_dpi_scale() -> f64 {
     unsafe {
-        let mut msg = MSG {
-            hwnd: null_mut(),
-            message: 0,
-            wParam: 0,
-            lParam: 0,
-            time: 0,
-            pt: winapi::shared::windef::POINT { x: 0, y: 0 },
-        };
-
-        while PeekMessageW(&mut msg, null_mut(), 0, 0, PM_REMOVE) != 0 {
-            if msg.message == WM_QUIT {
-                return false;
-            }
-            TranslateMessage(&msg);
-            DispatchMessageW(&msg);
+        let hdc = GetDC(null_mut());
+        if hdc.is_null() {
+            return 1.0;
+        }
+        let dpi = GetDeviceCaps(hdc, LOGPIXELSX);
+        ReleaseDC(null_mut(), hdc);
+        if dpi > 0 {
+            dpi as f64 / 96.0
+        } else {
+            1.0
         }
-        true
     }
 }
+
+/// Los mensajes de las ventanas los atiende el hilo de la interfaz; `false`
+/// cuando ese hilo ya salió de su bucle (`WM_QUIT`)
+pub fn process_messages() -> bool {
+    !UI_CLOSED.load(Ordering::Relaxed)
+}
@@ -1,4 +1,7 @@
 
+use std::cell::Cell;
+use std::rc::Rc;
+
 use anyhow::{Context, Result};
 use gdk::prelude::MonitorExt;
 use gdk::Monitor;
@@ -24,6 +27,8 @@ x11rb::atom_manager! {
         _NET_WM_STATE_STICKY,
         _NET_WM_STATE_ABOVE,
         _NET_WM_STATE_BELOW,
+        _NET_WM_STATE_SKIP_TASKBAR,
+        _NET_WM_STATE_SKIP_PAGER,
         _NET_WM_NAME,
         _NET_WM_STRUT,
         _NET_WM_STRUT_PARTIAL,
@@ -146,9 +151,11 @@ impl X11BackendConnection {
         // X11WindowType::Utility =>
         // self.atoms._NET_WM_WINDOW_TYPE_UTILITY
         // X11WindowType::Desktop =>
-        self.atoms._NET_WM_WINDOW_TYPE_DESKTOP;
+        // self.atoms._NET_WM_WINDOW_TYPE_DESKTOP
         // X11WindowType::Notification =>
-        // self.atoms._NET_WM_WINDOW_TYPE_NOTIFICATION
+        // Los compositores tratan DESKTOP como fondo de escritorio (sin
+        // transparencia ni orden correcto); NOTIFICATION es una ventana emergente
+        self.atoms._NET_WM_WINDOW_TYPE_NOTIFICATION;
 
         // TODO possibly support setting multiple window types
         x11rb::wrapper::ConnectionExt::change_property32(
@@ -161,6 +168,23 @@ impl X11BackendConnection {
         )?
         .check()?;
 
+        // Algunos gestores ignoran las pistas de GTK si la ventana no las
+        // declara también en _NET_WM_STATE (se añaden a los estados existentes)
+        x11rb::wrapper::ConnectionExt::change_property32(
+            &self.conn,
+            PropMode::APPEND,
+            win_id,
+            self.atoms._NET_WM_STATE,
+            self.atoms.ATOM,
+            &[
+                self.atoms._NET_WM_STATE_SKIP_TASKBAR,
+                self.atoms._NET_WM_STATE_SKIP_PAGER,
+                self.atoms._NET_WM_STATE_ABOVE,
+                self.atoms._NET_WM_STATE_STICKY,
+            ],
+        )?
+        .check()?;
+
         self.conn
             .flush()
             .context("Failed to send requests to X server")
@@ -172,29 +196,30 @@ pub enum Side {
     Top,
 }
 
-pub fn a(pos: (i32, i32), monitor_geometry: gdk::Rectangle) -> (Option<WindowGeometry>, crate::window::Window) {
-    let geometry = WindowGeometry {
+fn geometry_at(pos: (i32, i32)) -> WindowGeometry {
+    WindowGeometry {
         anchor_point: AnchorPoint {
             x: AnchorAlignment::START,
             y: AnchorAlignment::START,
         },
         offset: Coords { x: pos.0, y: pos.1 },
         size: Coords { x: 200, y: 50 },
-    };
+    }
+}
+
+pub fn a(pos: (i32, i32), monitor_geometry: gdk::Rectangle) -> (Option<WindowGeometry>, crate::window::Window) {
+    let geometry = geometry_at(pos);
     let (actual_window_rect, x, y) = {
         let rect = get_window_rectangle(geometry, monitor_geometry);
         (Some(rect), rect.x(), rect.y())
     };
 
-    let window_type = if true {
-        gtk::WindowType::Popup
-    } else {
-        gtk::WindowType::Toplevel
-    };
-    let w = crate::window::Window::new(window_type, x, y);
+    // Override-redirect: el gestor de ventanas no la decora, no la lista en la
+    // barra de tareas ni en Alt-Tab y no le da el foco
+    let w = crate::window::Window::new(gtk::WindowType::Popup, x, y);
     w.set_resizable(false);
-    w.set_keep_above(false);
-    w.set_keep_below(true);
+    w.set_keep_above(true);
+    w.set_keep_below(false);
     if true {
         w.stick();
     } else {
@@ -210,8 +235,6 @@ pub fn a(pos: (i32, i32), monitor_geometry: gdk::Rectangle) -> (Option<WindowGeo
         w.set_default_size(actual_window_rect.width(), actual_window_rect.height());
     }
     w.set_decorated(false);
-    w.set_skip_taskbar_hint(true);
-    w.set_skip_pager_hint(true);
 
     // run on_screen_changed to set the visual correctly initially.
     on_screen_changed(&w, None);
@@ -219,11 +242,11 @@ pub fn a(pos: (i32, i32), monitor_geometry: gdk::Rectangle) -> (Option<WindowGeo
     ( Some(geometry), w )
 }
 
-pub fn b(w: crate::window::Window, monitor_geometry: gdk::Rectangle, geometry: WindowGeometry) {
-    let _ = apply_window_position(geometry, monitor_geometry, &w);
+pub fn b(w: crate::window::Window, monitor_geometry: gdk::Rectangle, geometry: Rc<Cell<WindowGeometry>>) {
+    let _ = apply_window_position(geometry.get(), monitor_geometry, &w);
     if true {
         w.connect_configure_event(move |window, _| {
-            let _ = apply_window_position(geometry, monitor_geometry, window);
+            let _ = apply_window_position(geometry.get(), monitor_geometry, window);
             false
         });
     }
@@ -231,6 +254,25 @@ pub fn b(w: crate::window::Window, monitor_geometry: gdk::Rectangle, geometry: W
     backend.set_xprops_for(&w, get_gdk_monitor()).unwrap();
 }
 
+/// Lleva una ventana reutilizada a `pos`; el manejador de `configure-event`
+/// de [`b`] lee la nueva geometría de `geometry`
+pub fn move_to(w: &Window, pos: (i32, i32), monitor_geometry: gdk::Rectangle, geometry: &Cell<WindowGeometry>) {
+    let new_geometry = geometry_at(pos);
+    geometry.set(new_geometry);
+    let rect = get_window_rectangle(new_geometry, monitor_geometry);
+    w.resize(rect.width(), rect.height());
+    apply_window_position(new_geometry, monitor_geometry, w);
+}
+
+/// Mueve la ventana mientras se arrastra; a diferencia de [`move_to`]
+/// conserva su tamaño
+pub fn drag_to(w: &Window, pos: (i32, i32), monitor_geometry: gdk::Rectangle, geometry: &Cell<WindowGeometry>) {
+    let mut new_geometry = geometry.get();
+    new_geometry.offset = Coords::from_pixels(pos);
+    geometry.set(new_geometry);
+    apply_window_position(new_geometry, monitor_geometry, w);
+}
+
 fn apply_window_position(
     mut window_geometry: WindowGeometry,
     monitor_geometry: gdk::Rectangle,
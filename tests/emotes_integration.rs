@@ -88,6 +88,10 @@ fn create_test_emote_data(id: &str, name: &str) -> EmoteData {
     }
 }
 
+fn test_http() -> EmoteHttpClient {
+    EmoteHttpClient::new(&config::EmoteHttpConfig::default())
+}
+
 fn create_test_config() -> config::EmoteConfig {
     config::EmoteConfig {
         enable_global_emotes: true,
@@ -101,6 +105,14 @@ fn create_test_config() -> config::EmoteConfig {
         max_emotes_per_message: 10,
         cache_enabled: true,
         cache_ttl_hours: 1,
+        http: Default::default(),
+        blocklist: Default::default(),
+        custom_providers: Vec::new(),
+        seventv_live_updates: false,
+        prefetch: Default::default(),
+        channel_set_ttl_minutes: 30,
+        quality: Default::default(),
+        usage: Default::default(),
     }
 }
 
@@ -268,7 +280,7 @@ async fn test_emote_parser_functionality() {
 #[tokio::test]
 async fn test_emote_renderer_functionality() {
     let temp_dir = TempDir::new().unwrap();
-    let renderer = EmoteRenderer::new(temp_dir.path().to_path_buf());
+    let renderer = EmoteRenderer::new(temp_dir.path().to_path_buf(), test_http());
 
     // Test URL resolution for different sources
     let twitch_emote = Emote {
@@ -341,6 +353,14 @@ async fn test_emote_system_with_third_party_providers() {
         max_emotes_per_message: 10,
         cache_enabled: true,
         cache_ttl_hours: 24,
+        http: Default::default(),
+        blocklist: Default::default(),
+        custom_providers: Vec::new(),
+        seventv_live_updates: false,
+        prefetch: Default::default(),
+        channel_set_ttl_minutes: 30,
+        quality: Default::default(),
+        usage: Default::default(),
     };
     let mut emote_system = EmoteSystem::new(config);
 
@@ -447,6 +467,14 @@ async fn test_emote_system_error_handling() {
         max_emotes_per_message: 10,
         cache_enabled: true,
         cache_ttl_hours: 24,
+        http: Default::default(),
+        blocklist: Default::default(),
+        custom_providers: Vec::new(),
+        seventv_live_updates: false,
+        prefetch: Default::default(),
+        channel_set_ttl_minutes: 30,
+        quality: Default::default(),
+        usage: Default::default(),
     };
     let mut emote_system = EmoteSystem::new(config);
 
@@ -491,6 +519,14 @@ async fn test_emote_configuration_updates() {
         max_emotes_per_message: 5,
         cache_enabled: false,
         cache_ttl_hours: 24,
+        http: Default::default(),
+        blocklist: Default::default(),
+        custom_providers: Vec::new(),
+        seventv_live_updates: false,
+        prefetch: Default::default(),
+        channel_set_ttl_minutes: 30,
+        quality: Default::default(),
+        usage: Default::default(),
     };
 
     emote_system.update_config(new_config.clone());